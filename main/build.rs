@@ -1,12 +1,20 @@
-use rustc_version::{version_meta, Channel};
+use rustc_version::version_meta;
+
+include!("build/nightly.rs");
 
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=build/nightly.rs");
+    println!("cargo:rustc-check-cfg=cfg(pre_diagnostic_namespace)");
+
+    let version = version_meta().ok();
 
-    match version_meta() {
-        Ok(version) if version.channel == Channel::Nightly => {
-            println!("cargo:rustc-cfg=nightly");
+    // The `diagnostic` attribute namespace is available since Rust 1.78.
+    if let Some(version) = &version {
+        if (version.semver.major, version.semver.minor) >= (1, 78) {
+            println!("cargo:rustc-cfg=pre_diagnostic_namespace");
         }
-        _ => (),
     }
+
+    detect_nightly(version.as_ref());
 }
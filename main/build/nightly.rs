@@ -0,0 +1,174 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::PathBuf,
+    process::{self, Command},
+};
+
+use rustc_version::{Channel, VersionMeta};
+
+/// Forces the stable version of pre, even on a nightly compiler.
+const FORCE_STABLE: &str = "PRE_FORCE_STABLE";
+
+/// Forces the nightly version of pre, failing the build if the compiler does not support it.
+const FORCE_NIGHTLY: &str = "PRE_FORCE_NIGHTLY";
+
+/// The features that the nightly version of pre relies on, in the order they are tried.
+///
+/// Newer nightly compilers need `unsized_const_params` for `&'static str` const parameters.
+const FEATURE_SETS: [&[&str]; 2] = [
+    &["adt_const_params"],
+    &["adt_const_params", "unsized_const_params"],
+];
+
+/// Sets the `nightly` cfg if the nightly version of pre is used with the given compiler version.
+///
+/// This is shared by the build scripts of all crates in the workspace, so that they agree on
+/// which version of pre is used.
+fn detect_nightly(version: Option<&VersionMeta>) {
+    println!("cargo:rerun-if-env-changed={}", FORCE_STABLE);
+    println!("cargo:rerun-if-env-changed={}", FORCE_NIGHTLY);
+    println!("cargo:rustc-check-cfg=cfg(nightly)");
+    println!("cargo:rustc-check-cfg=cfg(pre_unsized_const_params)");
+
+    let force_stable = is_set(FORCE_STABLE);
+    let force_nightly = is_set(FORCE_NIGHTLY);
+    let backend_struct = env::var_os("CARGO_FEATURE_BACKEND_STRUCT").is_some();
+    let backend_const_generics = env::var_os("CARGO_FEATURE_BACKEND_CONST_GENERICS").is_some();
+
+    if force_stable && force_nightly {
+        fail(&format!(
+            "both `{}` and `{}` are set, but only one of them can be used at a time",
+            FORCE_STABLE, FORCE_NIGHTLY
+        ));
+    }
+    if backend_struct && backend_const_generics {
+        let note = if env::var_os("CARGO_FEATURE_MARKER_COMPAT").is_some() {
+            "\nnote: the `marker-compat` feature enables the `backend-struct` feature"
+        } else {
+            ""
+        };
+
+        fail(&format!(
+            "both the `backend-struct` and the `backend-const-generics` features of pre are \
+             enabled, but only one of them can be used at a time{}",
+            note
+        ));
+    }
+
+    // The reason why the nightly version must be used, along with how to avoid it.
+    let nightly_requirement = if backend_const_generics {
+        Some((
+            "the `backend-const-generics` feature of pre is enabled".to_string(),
+            "disable the `backend-const-generics` feature".to_string(),
+        ))
+    } else if force_nightly {
+        Some((
+            format!("`{}` is set", FORCE_NIGHTLY),
+            format!("unset `{}`", FORCE_NIGHTLY),
+        ))
+    } else {
+        None
+    };
+
+    if force_stable {
+        if let Some((reason, _)) = nightly_requirement {
+            fail(&format!(
+                "`{}` is set, but {}, which requires the nightly version of pre",
+                FORCE_STABLE, reason
+            ));
+        }
+
+        return;
+    }
+
+    let is_nightly_channel = match version {
+        Some(version) => matches!(version.channel, Channel::Nightly | Channel::Dev),
+        None => false,
+    };
+
+    if !is_nightly_channel && nightly_requirement.is_none() {
+        return;
+    }
+
+    let mut last_error = String::new();
+    for features in FEATURE_SETS.iter() {
+        match probe(features) {
+            Ok(()) => {
+                println!("cargo:rustc-cfg=nightly");
+                if features.contains(&"unsized_const_params") {
+                    println!("cargo:rustc-cfg=pre_unsized_const_params");
+                }
+
+                return;
+            }
+            Err(error) => last_error = error,
+        }
+    }
+
+    if let Some((reason, alternative)) = nightly_requirement {
+        fail(&format!(
+            "{}, but the compiler cannot build the nightly version of pre\n\
+             \n\
+             the nightly version of pre requires const parameters of type `&'static str`, \
+             which failed to compile with the following error:\n\
+             \n\
+             {}\n\
+             help: use a nightly compiler that supports the `adt_const_params` feature or {}",
+            reason,
+            last_error.trim_end(),
+            alternative
+        ));
+    }
+}
+
+/// Checks whether the environment variable is set to a value other than `0`.
+fn is_set(name: &str) -> bool {
+    match env::var_os(name) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
+/// Checks whether the compiler can build code like the nightly version of pre does with the given
+/// features enabled.
+///
+/// On failure, the compiler output is returned.
+fn probe(features: &[&str]) -> Result<(), String> {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets `OUT_DIR`"));
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| OsString::from("rustc"));
+    let probe_file = out_dir.join("pre_nightly_probe.rs");
+
+    let source = format!(
+        "#![no_std]\n\
+         #![feature({})]\n\
+         #![allow(incomplete_features)]\n\
+         pub struct Condition<const TEXT: &'static str>;\n\
+         pub fn probe(_: Condition<\"probe\">) {{}}\n",
+        features.join(", ")
+    );
+    fs::write(&probe_file, source).map_err(|error| format!("{}\n", error))?;
+
+    let output = Command::new(rustc)
+        .arg("--crate-type=lib")
+        .arg("--crate-name=pre_nightly_probe")
+        .arg("--emit=metadata")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg(&probe_file)
+        .output()
+        .map_err(|error| format!("{}\n", error))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+/// Aborts the build with the given message.
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
@@ -0,0 +1,141 @@
+//! The order in which preconditions are encoded.
+//!
+//! On the nightly compiler, the preconditions of a function are encoded as a tuple of condition
+//! types, such as `(pre::ValidPtrCondition<"ptr", "r">, pre::BooleanCondition<"len > 0">)`. Two
+//! tuples with the same conditions in a different order are different types, so every crate
+//! passing the precondition argument must use the same order. This order is part of the public
+//! interface of pre and only changes in releases with breaking changes.
+//!
+//! The conditions are ordered by their kind first, in the order of the variants of
+//! [`ConditionKind`]. Conditions of the same kind are ordered by the arguments of their condition
+//! type, comparing the first argument and then the second argument as strings. The order in which
+//! the preconditions are written does not matter.
+//!
+//! The order of the fields in the structs used on the stable compiler does not affect
+//! compatibility, but they are generated in the same order, so that the output of pre is the same
+//! for the same preconditions.
+//!
+//! # Generating code
+//!
+//! Tools that generate calls to functions with preconditions without using the `assure`
+//! attribute can use [`sort_conditions`] to produce the same encoding as pre:
+//!
+//! ```rust
+//! use pre::encoding::{sort_conditions, ConditionKey, ConditionKind};
+//!
+//! let mut conditions = vec![
+//!     ("BooleanCondition", ConditionKey::new(ConditionKind::Boolean, "len > 0", "")),
+//!     ("ValidPtrCondition", ConditionKey::new(ConditionKind::ValidPtr, "ptr", "r")),
+//!     ("NonNullCondition", ConditionKey::new(ConditionKind::NonNull, "ptr", "")),
+//!     ("BooleanCondition", ConditionKey::new(ConditionKind::Boolean, "idx < len", "")),
+//! ];
+//!
+//! sort_conditions(&mut conditions, |(_, key)| *key);
+//!
+//! let order = conditions.iter().map(|(ty, key)| (*ty, key.first)).collect::<Vec<_>>();
+//! assert_eq!(
+//!     order,
+//!     [
+//!         ("ValidPtrCondition", "ptr"),
+//!         ("BooleanCondition", "idx < len"),
+//!         ("BooleanCondition", "len > 0"),
+//!         ("NonNullCondition", "ptr"),
+//!     ]
+//! );
+//! ```
+
+/// The kind of a precondition.
+///
+/// The variants are listed in the order in which the kinds are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConditionKind {
+    /// A `valid_ptr` precondition, encoded as `ValidPtrCondition`.
+    ValidPtr,
+    /// A `proper_align` precondition, encoded as `ProperAlignCondition`.
+    ProperAlign,
+    /// A boolean precondition, encoded as `BooleanCondition`.
+    Boolean,
+    /// A custom precondition, encoded as `CustomCondition`.
+    Custom,
+    /// A `token` precondition, encoded as `TokenCondition`.
+    Token,
+    /// A `non_null` precondition, encoded as `NonNullCondition`.
+    NonNull,
+    /// An `in_range` precondition, encoded as `InRangeCondition`.
+    InRange,
+    /// An exported or imported condition, encoded as `NamedCondition`.
+    ///
+    /// These are ordered by the name of the constant, not by the path to it.
+    Named,
+    /// A `valid_value` precondition, encoded as `ValidValueCondition`.
+    ValidValue,
+    /// A `consumed` precondition, encoded as `ConsumedCondition`.
+    Consumed,
+    /// A `proper_align_val` precondition, encoded as `ProperAlignValCondition`.
+    ProperAlignVal,
+    /// A `nul_terminated` precondition, encoded as `NulTerminatedCondition`.
+    NulTerminated,
+    /// A `valid_cstr` precondition, encoded as `ValidCStrCondition`.
+    ValidCStr,
+    /// An `interrupts_disabled` precondition, encoded as `InterruptsDisabledCondition`.
+    InterruptsDisabled,
+    /// A `dma_quiescent` precondition, encoded as `DmaQuiescentCondition`.
+    DmaQuiescent,
+    /// A `lives_until_end_of_scope` precondition, encoded as `LivesUntilEndOfScopeCondition`.
+    LivesUntilEndOfScope,
+    /// A `layout_compatible` precondition, encoded as `LayoutCompatibleCondition`.
+    LayoutCompatible,
+    /// An `initialized` precondition, encoded as `InitializedCondition`.
+    Initialized,
+    /// A `non_overlapping` precondition, encoded as `NonOverlappingCondition`.
+    NonOverlapping,
+    /// A `not_reentrant` precondition, encoded as `NotReentrantCondition`.
+    NotReentrant,
+    /// A `valid_utf8` precondition, encoded as `ValidUtf8Condition`.
+    ValidUtf8,
+    /// An `aligned_to` precondition, encoded as `AlignedToCondition`.
+    AlignedTo,
+}
+
+/// The key determining the position of a precondition in the encoding.
+///
+/// Keys are ordered by their kind, then by their first argument and then by their second
+/// argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConditionKey<'a> {
+    /// The kind of the precondition.
+    pub kind: ConditionKind,
+    /// The first argument of the condition type.
+    ///
+    /// For `token` preconditions this is the token type, with all tokens separated by a single
+    /// space, such as `& InitToken`. For custom preconditions it is the string without quotes.
+    pub first: &'a str,
+    /// The second argument of the condition type, or an empty string if it has only one.
+    ///
+    /// For `valid_ptr` preconditions this is `r`, `w` or `r+w`, followed by `, ` and the element
+    /// count, such as `r, count * 2`, if one is given.
+    /// For `initialized` preconditions this is the element count or an empty string, if none is
+    /// given.
+    /// For `non_overlapping` preconditions this is the second pointer, followed by `, ` and the
+    /// element count, such as `dst, count`.
+    pub second: &'a str,
+}
+
+impl<'a> ConditionKey<'a> {
+    /// Creates the key for a precondition of the given kind with the given arguments.
+    pub fn new(kind: ConditionKind, first: &'a str, second: &'a str) -> ConditionKey<'a> {
+        ConditionKey {
+            kind,
+            first,
+            second,
+        }
+    }
+}
+
+/// Sorts the conditions in the order in which pre encodes them.
+///
+/// `key` returns the key of each condition. Conditions with equal keys are encoded as the same
+/// type, so their relative order does not matter.
+pub fn sort_conditions<T>(conditions: &mut [T], mut key: impl FnMut(&T) -> ConditionKey<'_>) {
+    conditions.sort_unstable_by(|a, b| key(a).cmp(&key(b)));
+}
@@ -78,6 +78,9 @@
 //! `assure` attribute (on `main`).  To learn why the second usage is necessary, read the paragraph
 //! about the [checking functionality] on the documentation of the `pre` attribute.
 //!
+//! Instead of importing the attributes one by one, all of them can be imported using `use
+//! pre::prelude::*;`. See the [`prelude` module](prelude/index.html) for what it contains.
+//!
 //! With the [`assure` attribute] the programmer assures that the precondition was checked by them
 //! and is upheld. Without the `assure` attribute, the code would fail to compile.
 //!
@@ -119,9 +122,21 @@
 //!
 //!       This does not apply to `impl` blocks inside of an `extern_crate` annotated module. These
 //!       have their own limitations though (see below).
-//!     - Warnings from pre are only possible on nightly.
+//!     - Warnings from pre are reported as `deprecated` warnings on stable, which only contain
+//!       the message of the warning, but no notes or suggestions. The `missing_assure_summary`
+//!       lint is only reported on nightly.
 //!     - Errors can reference multiple locations providing better suggestions and messages on
 //!       nightly.
+//!     - pre detects whether the compiler supports the nightly version by compiling a small test
+//!       program when it is built. To override this, set the `PRE_FORCE_STABLE` environment
+//!       variable to use the stable version on a nightly compiler or the `PRE_FORCE_NIGHTLY`
+//!       environment variable to fail the build if the nightly version cannot be used.
+//!     - The `backend-struct` and `backend-const-generics` features select how preconditions are
+//!       encoded independently of the compiler, which makes builds reproducible across
+//!       toolchains. `backend-struct` uses the stable encoding on the nightly compiler as well, so
+//!       preconditions on methods are not supported with it. `backend-const-generics` requires a
+//!       compiler that can build the nightly version and fails the build otherwise. Only one of
+//!       them can be enabled at a time.
 //! - Since pre works by adding an additional argument to a function, it changes the function
 //!   signature. That won't make a difference in many cases, but if you use function pointers or
 //!   pass a function as an argument, it will have a different type from what it appears to be.
@@ -133,12 +148,26 @@
 //!   [`extern_crate` attribute] is not supported with the 2015 edition.
 //! - While using any of pre's attributes within a [`cfg_attr` attribute] works, there are two
 //!   limitations to that:
-//!     - All `cfg_attr` attributes must have the same configuration predicates. The same here
-//!       means syntactic equality, so `all(unix, target_endian = "little")` is not the same as
-//!       `all(target_endian = "little", unix)`. This is done easiest, by simply putting all
-//!       preconditions behind a single `cfg_attr`.
+//!     - All `cfg_attr` attributes of the preconditions of a function must have the same
+//!       configuration predicates. The same here means syntactic equality, so `all(unix,
+//!       target_endian = "little")` is not the same as `all(target_endian = "little", unix)`.
+//!       This is done easiest, by simply putting all preconditions behind a single `cfg_attr`.
+//!       The `assure` attributes of a call may use different predicates, for example if the call
+//!       is within an item that is only compiled for some configurations. The preconditions are
+//!       then only passed to the function if all of the predicates hold.
 //!     - Nested `cfg_attr` attributes are not supported, so `#[cfg_attr(unix,
 //!       cfg_attr(target_endian = "little", assure(...)))]` is currently not recognized by pre.
+//!     - A bare `#[cfg_attr(<predicate>, pre)]` gates all processing of the item by pre under the
+//!       predicate. All other `pre`, `assure` and `forward` attributes of the item, including the
+//!       ones in its body, then have to be behind a `cfg_attr` with the same predicate as well.
+//!       Otherwise they would activate pre on their own or not be handled at all in the other
+//!       configurations, so an error is emitted for them. Likewise it is an error to gate the
+//!       item if another `pre` attribute already activates pre in all configurations.
+//! - If a parameter of a function has a `#[cfg(<predicate>)]` attribute, the preconditions
+//!   referring to it are only required where the predicate holds, as if they were behind a
+//!   `cfg_attr` with the same predicate. Because all preconditions of a function must have the
+//!   same predicate, an error is emitted if the other preconditions of the function are not
+//!   behind the same predicate.
 //! - There are multiple limitations for functions and methods defined in a module which is
 //!   annotated with the [`extern_crate` attribute] or has a parent that is:
 //!     - Calls to such functions/methods call the original function/method for the original type,
@@ -148,36 +177,6 @@
 //!       functions to clash with names in their surrounding module. This is unlikely to occur in
 //!       regular usage, but possible. If you encounter such a case, please open an issue
 //!       describing the problem.
-//!     - Currently all type information for the `impl` block is discarded. This means that
-//!       multiple non-overlapping (in the type system sense) `impl` blocks can overlap in an
-//!       `extern_crate` annotated module.
-//!
-//!       ```rust,compile_fail
-//!       mod a {
-//!           pub(crate) struct X<T>(T);
-//!
-//!           impl X<bool> {
-//!               pub(crate) fn foo() {}
-//!           }
-//!
-//!           impl X<()> {
-//!               pub(crate) fn foo() {}
-//!           }
-//!       }
-//!
-//!       #[pre::extern_crate(crate::a)]
-//!       mod b {
-//!           impl X<bool> {
-//!               fn foo();
-//!           }
-//!
-//!           impl X<()> {
-//!               fn foo();
-//!           }
-//!       }
-//!       #
-//!       # fn main() {}
-//!       ```
 //!
 //! # Understanding the error messages
 //!
@@ -213,6 +212,13 @@
 //! attribute] and explain in the `reason`, why you're sure that they hold. You should be able
 //! to find the function preconditions in the documentation for the function.
 //!
+//! When using the nightly compiler, pre additionally emits a single warning per called function
+//! summarizing how many call sites in an item are missing `assure` attributes, along with the
+//! preconditions of the function and the `assure` attributes to copy to the call sites.
+//! At most five call sites are listed in the warning, the complete list can be found in the
+//! [exported metadata](#exporting-metadata).
+//! This only works for functions defined in the same crate.
+//!
 //! ---
 //!
 //! **nightly compiler error**
@@ -345,6 +351,70 @@
 //! To fix this error, either [add the `assure`d preconditions as preconditions to the
 //! function][`pre` attribute] or remove the `assure` attribute, if you added it in error.
 //!
+//! # Mixing versions of pre
+//!
+//! The preconditions of a function are part of its signature, so a crate calling a function of
+//! another crate must encode the `assure`d preconditions exactly like the crate defining the
+//! function. If the two crates depend on different versions of pre, this is only possible if
+//! both versions use the same encoding.
+//!
+//! On the stable compiler, the preconditions are encoded as a struct with the same name as the
+//! function, which has one field of type `()` per precondition. This naming scheme is stable:
+//!
+//! - The struct always contains a field named `_pre_marker_abi_<version>`, where `<version>` is
+//!   the version of the naming scheme. It is currently `1` and is only incremented with a new
+//!   minor version of pre that changes the naming scheme.
+//! - All other fields are named `_<kind>_<details>`, for example `_valid_ptr_<ptr>_<r|w|rw>`,
+//!   `_valid_ptr_<ptr>_<r|w|rw>_<count>`, `_non_null_<ptr>`, `_proper_align_val_<ptr>_<align>`,
+//!   `_aligned_to_<ptr>_<align>`, `_in_range_<value>_<range>`, `_boolean_<expr>` or
+//!   `_custom_<text>`. `<count>`, `<align>`, `<range>` and `<expr>` are written without
+//!   whitespace, except for a single space between two identifiers or literals and between two
+//!   punctuation characters that are not joined, such as in `& &x`. This does not depend on how
+//!   the code is formatted or on the compiler version. Any characters in these parts and in
+//!   `<text>` that are not ASCII letters or digits are escaped: `_` is written as `__` and any
+//!   other character as `_` followed by its code point in lowercase hexadecimal. `x > 0` is for
+//!   example encoded as `_boolean_x_3e0` and `x as u8 > 0` as `_boolean_x_20as_20u8_3e0`.
+//!
+//! The nightly compiler uses types defined in the `pre` crate instead, so crates depending on
+//! different versions of pre cannot call each others functions with preconditions. Enabling the
+//! `marker-compat` feature of pre selects the stable encoding on the nightly compiler as well (by
+//! enabling the `backend-struct` feature), so that adjacent minor versions that use the same naming
+//! scheme interoperate. Since the stable
+//! encoding does not support methods, preconditions on methods are not supported with this
+//! feature. Also `token` preconditions and imported conditions still refer to types defined in
+//! the `pre` crate, so they must be defined and `assure`d using the same version of pre.
+//!
+//! The order in which the preconditions are encoded is stable as well. It is described in the
+//! [`encoding` module](encoding/index.html), which also helps tools generating code that calls
+//! functions with preconditions to produce the same encoding.
+//!
+//! If the naming schemes of the two versions differ, the compiler reports an error naming the
+//! version field. For example, if `foo` is defined using a version of pre with naming scheme `2`
+//! and called from a crate using naming scheme `1`, the stable compiler reports:
+//!
+//! ```text
+//! error[E0560]: struct `foo` has no field named `_pre_marker_abi_1`
+//!  --> src/main.rs:9:10
+//!   |
+//! 9 |         #[assure(x > 41.9, reason = "42.0 > 41.9")]
+//!   |          ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ unknown field
+//!   |
+//! help: a field with a similar name exists
+//!   |
+//! 9 -         #[assure(x > 41.9, reason = "42.0 > 41.9")]
+//! 9 +         #_pre_marker_abi_2
+//!   |
+//! ```
+//!
+//! The suggestion replaces the `assure` attribute, because that is where the field is generated,
+//! so it should not be applied. Only the field names are relevant: `_pre_marker_abi_1` is the
+//! naming scheme of the calling crate and `_pre_marker_abi_2` the one of the crate defining `foo`.
+//! This means that the crate defining `foo` uses an older or newer version of pre than the crate
+//! calling it. To fix this error, update both crates to versions of pre with the same naming
+//! scheme. Without the `marker-compat` feature on the nightly compiler, the error instead
+//! mentions two different versions of the `pre` crate, such as "perhaps two different versions
+//! of crate `pre` are being used?".
+//!
 //! # Wording of preconditions
 //!
 //! While you can write any text you like in a [custom precondition][precondition syntax], it is
@@ -359,6 +429,254 @@
 //!
 //! > "the elements at `old_len..new_len` **are** initialized"
 //!
+//! ## Contracts about concurrent access
+//!
+//! Contracts such as "there is no concurrent mutable access", which are common in code using
+//! `UnsafeCell` or raw pointers shared between threads, are easier to check at a call site when
+//! they are split up. The parts that pre knows about can use the [structured
+//! preconditions][precondition syntax], such as `valid_ptr` and `proper_align`. The remaining rule
+//! is then stated on its own, naming the memory and how long the rule applies:
+//!
+//! ```rust
+//! # use pre::pre;
+//! use std::cell::UnsafeCell;
+//!
+//! #[pre(valid_ptr(ptr, r+w))]
+//! #[pre(proper_align(ptr))]
+//! #[pre("the memory at `ptr` is not accessed by any other pointer during the lifetime `'a`")]
+//! unsafe fn exclusive<'a, T>(ptr: *mut T) -> &'a mut T {
+//!     &mut *ptr
+//! }
+//!
+//! struct Counter {
+//!     value: UnsafeCell<u32>,
+//! }
+//!
+//! impl Counter {
+//!     #[pre]
+//!     fn increment(&self) {
+//!         #[assure(valid_ptr(ptr, r+w), reason = "the pointer comes from `UnsafeCell::get`")]
+//!         #[assure(proper_align(ptr), reason = "the pointer comes from `UnsafeCell::get`")]
+//!         #[assure(
+//!             "the memory at `ptr` is not accessed by any other pointer during the lifetime `'a`",
+//!             reason = "`Counter` is not `Sync` and never hands out references to its value"
+//!         )]
+//!         let value = unsafe { exclusive(self.value.get()) };
+//!         *value += 1;
+//!     }
+//! }
+//! #
+//! # let counter = Counter { value: UnsafeCell::new(0) };
+//! # counter.increment();
+//! # assert_eq!(counter.value.into_inner(), 1);
+//! ```
+//!
+//! Shared access is worded as "is not mutated by any pointer", like the preconditions of
+//! `slice::from_raw_parts` in `pre::core`, and atomic access as "is only accessed atomically",
+//! like the preconditions of `AtomicUsize::from_ptr`. If the same rule applies to multiple
+//! functions, [exporting it](attr.pre.html#precondition-syntax) allows referring to it without
+//! repeating the text.
+//!
+//! # Exporting metadata
+//!
+//! If the `PRE_METADATA_DIR` environment variable is set during compilation, pre writes
+//! information about the preconditions in each crate to `$PRE_METADATA_DIR/<crate name>.jsonl`.
+//! The file contains one JSON object per line and is overwritten each time the crate is compiled.
+//!
+//! The following kinds of objects are written:
+//!
+//! - `{"kind":"definition","function":"<name>","preconditions":["<precondition>", ...],"contract_id":"<id>","pure":<bool>,"docs":<markdown or null>}`
+//!   for each function with preconditions. The contract ID is a stable hash of the set of
+//!   preconditions, which is also shown in the generated documentation of the function. It does
+//!   not depend on how the preconditions are formatted and only changes when the preconditions
+//!   change, so it can be used to link reviews of a function's contract in external audit systems
+//!   to the exact version of the contract that was reviewed.
+//!   `docs` is the exact markdown text of the documentation pre generates for the preconditions,
+//!   so that it can be embedded in other documentation, such as a safety manual. It is `null`, if
+//!   no documentation was generated because of [`#[pre(no_doc)]`][`pre` attribute], which is also
+//!   the case for the methods of `impl` blocks in modules with an [`extern_crate` attribute].
+//!   `pure` is `true` for functions marked with [`#[pre(pure)]`][`pre` attribute], whose boolean
+//!   preconditions can be evaluated more than once.
+//! - `{"kind":"assure","function":<name or null>,"call":"<call>","precondition":"<precondition>","reason":"<reason>","audit":<reference or null>,"audited_by":<name or null>,"audited_date":<date or null>,"contract_id":<id or null>}`
+//!   for each precondition assured with a reason. `audit` is the reference given with
+//!   [`audit = "..."`](attr.assure.html#referencing-audits), `audited_by` and `audited_date` are
+//!   given with `audited(by = "...", date = "...")` and `contract_id` is the contract ID of the
+//!   called function, if it is defined in the same crate.
+//! - `{"kind":"missing_assure","function":"<name>","caller":"<name>","call":"<call>","generated":<bool>}`
+//!   for each call to a function with preconditions defined in the same crate that has no `assure`
+//!   attributes. `generated` is `true` for calls in a [`#[pre(trusted)]`][`pre` attribute] scope,
+//!   whose preconditions are assured automatically.
+//! - `{"kind":"unsafe_calls","function":"<name>","module":"<path>","covered":<n>,"uncovered":<n>,"safety_docs":<markdown or null>}`
+//!   for each function with calls in `unsafe` contexts, counting the calls with `assure`
+//!   attributes and the calls without them to functions with preconditions defined in the same
+//!   crate. Other calls are usually safe, so they are not counted. `safety_docs` is the `# Safety` section of the documentation of the
+//!   function. The [`metrics`](metrics/index.html) module evaluates these records.
+//! - `{"kind":"unsafe_call","caller":"<name>","function":"<name>","call":"<call>","assured":["<precondition>", ...]}`
+//!   for each call in an `unsafe` context with the preconditions assured for it with a reason.
+//! - `{"kind":"outline","function":"<name>","path":"<path>","preconditions":["<precondition>", ...]}`
+//!   for each function with preconditions in a module with an [`extern_crate` attribute]. The
+//!   [`wrappers`](wrappers/index.html) module compares these records with the `unsafe_call`
+//!   records of a crate with safe wrappers to find contracts that the wrappers silently drop.
+//! - `{"kind":"contract_flow","function":"<name>","discharged":["<precondition>", ...],"propagated":["<precondition>", ...],"unaccounted":["<precondition>", ...]}`
+//!   for each function with preconditions, showing how they flow through wrapper functions. A
+//!   precondition is `discharged`, if it is assured with a reason for a call in the function, and
+//!   `propagated`, if it is otherwise a precondition of a function defined earlier in the crate
+//!   that the function calls, for example in a [`#[pre(trusted)]`][`pre` attribute] scope. All
+//!   other preconditions are `unaccounted`.
+//! - `{"kind":"harvested_assert","function":"<name>","precondition":"<condition>"}` for each
+//!   assertion found by [`#[pre(harvest_asserts)]`][`pre` attribute], which can be replaced by
+//!   `#[pre(<condition>)]`.
+//!
+//! ```text
+//! PRE_METADATA_DIR=target/pre cargo check
+//! ```
+//!
+//! Note that cargo does not recompile crates when only this environment variable changes, so a
+//! `cargo clean` may be necessary to export the metadata of all crates.
+//!
+//! # Contracts manual
+//!
+//! If the `PRE_MANUAL_DIR` environment variable is set during compilation, pre collects the
+//! documentation it generates for the preconditions of each function into a single markdown file
+//! at `$PRE_MANUAL_DIR/<crate name>/PRECONDITIONS.md`. This gives a human-readable manual of all
+//! contracts of a crate, for example as a deliverable for a safety or security review. The
+//! documentation is included even for functions with [`#[pre(no_doc)]`][`pre` attribute].
+//!
+//! The file is overwritten each time the crate is compiled. The functions are listed in the order
+//! pre processes them, which usually is the order in the source code.
+//!
+//! ```text
+//! PRE_MANUAL_DIR=target/pre-manual cargo check
+//! ```
+//!
+//! # Configuring messages
+//!
+//! A `pre.toml` file next to the `Cargo.toml` of a crate can change the placeholder reason used in
+//! generated documentation and the texts of the messages pre emits for the crate. This allows
+//! adapting them to internal guidelines or translating them. All keys are optional:
+//!
+//! ```toml
+//! hint_reason = "<why is this ok?>"
+//!
+//! [messages]
+//! missing_reason = "a reason is required here"
+//! hint_reason = "replace the placeholder reason"
+//! unfinished_reason = "this reason is unfinished"
+//! reason_help = "see the guidelines on writing reasons"
+//! todo_help = "`TODO` is accepted temporarily, but emits a warning"
+//! missing_token_value = "pass the token value here"
+//! ```
+//!
+//! The `[defaults]` table changes the defaults of the `no_doc` and `no_debug_assert` flags of the
+//! [`pre` attribute] for all items in the crate. Single items can opt back in using
+//! `#[pre(doc)]` and `#[pre(debug_assert)]`:
+//!
+//! ```toml
+//! [defaults]
+//! no_doc = true
+//! no_debug_assert = true
+//! ```
+//!
+//! When several preconditions are missing at a call, the compiler reports the mismatch in terms of
+//! the encoding, which can be hard to read. With `summary_errors` in the `[diagnostics]` table,
+//! pre instead reports a single error listing the missing and the not required preconditions,
+//! together with `assure` attributes for the missing ones, and suppresses the errors of the
+//! encoding for that call. This only works for functions in the same crate that are defined
+//! before the call. The call must name the function directly from the module that defines it or
+//! through a module with a `pre` attribute that contains it, so that it cannot refer to another
+//! function with the same name, such as one brought into scope by a `use` item:
+//!
+//! ```toml
+//! [diagnostics]
+//! summary_errors = true
+//! ```
+//!
+//! # Configuring kinds of preconditions
+//!
+//! Domain-specific contracts, such as memory that must be usable for DMA, can be added as kinds of
+//! preconditions in the `[conditions]` table of `pre.toml`, without changing pre. Each kind has a
+//! name, the names of its arguments and a description for the generated documentation. The
+//! description and an optional boolean `check`, which is used like the `debug_assert` of a
+//! boolean precondition, can refer to the arguments as `{<arg>}`:
+//!
+//! ```toml
+//! [conditions.dma_capable]
+//! args = ["ptr", "len"]
+//! doc = "`{ptr}` must point to `{len}` bytes of DMA-capable memory"
+//! check = "crate::dma::is_dma_capable({ptr} as *const u8, {len})"
+//!
+//! [conditions.irq_disabled]
+//! doc = "interrupts must be disabled"
+//! ```
+//!
+//! These kinds are then used like the built-in ones, such as in `#[pre(dma_capable(buf, len))]`
+//! and `#[assure(irq_disabled(), reason = "...")]`. The arguments must be identifiers. A
+//! configured precondition is encoded like the string precondition with its description, so it
+//! works with all backends and across crates that configure the same kind. This also means that
+//! it can be assured by that string.
+//!
+//! As with the metadata export, cargo does not recompile a crate when only `pre.toml` changes.
+//!
+//! # Handling violated preconditions
+//!
+//! By default, preconditions that can be checked at runtime, such as boolean preconditions, are
+//! checked by `debug_assert` statements, which panic if the precondition was wrongly assured.
+//!
+//! With the `violation-handler` feature, which requires `std`, these checks report the violation
+//! to a handler installed with [`set_violation_handler`](fn.set_violation_handler.html) instead.
+//! This allows systems that cannot afford to panic to log or count violations, or to degrade
+//! gracefully. If no handler is installed, a violation panics with the same message as before.
+//!
+//! ```rust,ignore
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! static VIOLATIONS: AtomicUsize = AtomicUsize::new(0);
+//!
+//! fn count_violation(violation: &pre::ContractViolation) {
+//!     VIOLATIONS.fetch_add(1, Ordering::Relaxed);
+//!     eprintln!("{}", violation);
+//! }
+//!
+//! fn main() {
+//!     pre::set_violation_handler(count_violation);
+//!
+//!     // ...
+//! }
+//! ```
+//!
+//! Execution continues after the handler returns, so the function with the violated precondition
+//! is still called. A handler that cannot recover from a violation should panic or abort itself.
+//!
+//! Like the `debug_assert` statements, the checks are only performed when debug assertions are
+//! enabled. To keep them in release builds, set `debug-assertions = true` in the release profile.
+//! Checks in `const fn`s always use `debug_assert`, because the handler cannot be called there.
+//!
+//! ## Logging violated preconditions
+//!
+//! With the `log-violations` feature, violated preconditions are logged using `log::error!`
+//! before panicking. The `defmt-violations` feature does the same using `defmt::error!` for
+//! embedded targets, which requires a direct dependency on `defmt` and a global `defmt` logger.
+//! The logged message contains the violated precondition, the function and the [contract
+//! ID](#exporting-metadata) of its preconditions, which stays the same as long as the
+//! preconditions don't change:
+//!
+//! ```text
+//! boolean precondition was wrongly assured: `x > 0` (precondition of `foo` with contract ID `...`)
+//! ```
+//!
+//! To only log violated preconditions without panicking, add the following to the `pre.toml` of
+//! the crate (see ["Configuring messages"](#configuring-messages)):
+//!
+//! ```toml
+//! [logging]
+//! panic = false
+//! ```
+//!
+//! Tests generated by `gen_violation_tests` expect the checks to panic, so they fail in this
+//! case. Together with the `violation-handler` feature, violations are logged before they are
+//! reported to the handler. Like the other checks, the logging checks are only performed when
+//! debug assertions are enabled and not in `const fn`s.
+//!
 //! # Feature flags
 //!
 //! If you're planning on using pre in a library, you should consider how the increased
@@ -516,6 +834,46 @@
 //! }
 //! ```
 //!
+//! ## Shadow modules
+//!
+//! Writing the outline by hand gets tedious for modules with many functions.
+//! The [`shadow!` macro] writes it for you: the preconditions are written on the functions of
+//! the module themselves and `shadow!` moves them to a generated `pre_<module>` twin.
+//!
+//! ```rust
+//! use pre::pre;
+//!
+//! pre::shadow! {
+//!     mod some_module {
+//!         #[pre("some condition")]
+//!         pub(crate) unsafe fn some_fn() {
+//!             /* ... */
+//!         }
+//!     }
+//! }
+//!
+//! #[pre]
+//! fn main() {
+//!     use some_module::some_fn;
+//!
+//!     unsafe {
+//!         // Checks the preconditions of the function.
+//!         #[forward(pre_some_module)]
+//!         #[assure(
+//!             "some condition",
+//!             reason = "the reason you know the condition is true"
+//!         )]
+//!         some_fn();
+//!
+//!         // Does not check any preconditions as before the modifications.
+//!         some_fn();
+//!     }
+//! }
+//! ```
+//!
+//! Once all call sites are converted, removing the `shadow!` invocation makes the preconditions
+//! apply to the original functions.
+//!
 //! ## `"TODO"` as a reason
 //!
 //! **This paragraph only applies if you use the nightly compiler**, because it depends on the
@@ -530,8 +888,8 @@
 //! However it allows everything to compile again and have the compiler warnings remind you what
 //! you need to `assure` still.
 //!
-//! Because the warnings only work on the nightly compiler, **usage of `"TODO"` as a reason is
-//! discouraged when using the stable compiler**.
+//! On the stable compiler, the warning is reported as the use of a deprecated item named
+//! `todo_reason`, whose note contains the message of the warning.
 //!
 //! [`pre` attribute]: attr.pre.html
 //! [checking functionality]: attr.pre.html#checking-functionality
@@ -540,12 +898,14 @@
 //! [`assure`d]: attr.assure.html
 //! [`extern_crate` attribute]: attr.extern_crate.html
 //! [`forward` attribute]: attr.forward.html
+//! [`shadow!` macro]: macro.shadow.html
 //! [forward impl]: attr.forward.html#impl-call
 //! [`cfg_attr` attribute]: https://doc.rust-lang.org/reference/conditional-compilation.html#the-cfg_attr-attribute
 //! [`proc_macro_diagnostic` feature]: https://github.com/rust-lang/rust/issues/54140
 
 #![allow(clippy::needless_doctest_main)]
 #![cfg_attr(nightly, feature(adt_const_params))]
+#![cfg_attr(pre_unsized_const_params, feature(unsized_const_params))]
 #![cfg_attr(nightly, allow(incomplete_features))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -587,6 +947,10 @@
 ///
 ///    - `<string>`: An arbitrary string describing the condition.
 ///
+///    The string must be assured exactly as it is written here. If an assured string differs only
+///    slightly from a precondition of a function defined earlier in the same crate, pre reports the
+///    exact string that was likely meant.
+///
 ///    ### Example
 ///
 ///    ```rust
@@ -606,6 +970,10 @@
 ///    - `<access_modes>`: One of `r`, `w` or `r+w`. This specifies whether the pointer is valid
 ///    for reads (`r`) or writes (`w`) or both (`r+w`).
 ///
+///    Optionally, the number of elements that the pointer must be valid for can follow as an
+///    expression: `#[pre(valid_ptr(<ptr_name>, <access_modes>, <count>))]`. The pointer is then
+///    valid for accesses of `<count> * size_of::<T>()` bytes for a `*const T` or `*mut T`.
+///
 ///    ### Example
 ///
 ///    ```rust
@@ -613,14 +981,19 @@
 ///    #
 ///    #[pre(valid_ptr(ptr_name, r+w))]
 ///    fn foo(ptr_name: *mut i32) {}
+///
+///    #[pre(valid_ptr(src, r, count))]
+///    fn bar(src: *const i32, count: usize) {}
 ///    ```
 ///
 ///    This precondition **does not** guarantee:
 ///
 ///    - A proper alignment of the pointer.
-///    - A valid initialized value for the pointee.
+///    - A valid initialized value for the pointee, which can be required with an `initialized`
+///      precondition.
 ///
-///    Also there are no guarantees about the size of the allocated object.
+///    Also there are no guarantees about the size of the allocated object beyond the given number
+///    of elements.
 ///    If there are no other preconditions about the size of the allocated object, usually the size
 ///    of a single value can be assumed.
 /// 3. Proper alignment preconditions:
@@ -641,29 +1014,513 @@
 ///    #[pre(proper_align(ptr_name))]
 ///    fn foo(ptr_name: *mut i32) {}
 ///    ```
-/// 4. Boolean preconditions:
 ///
-///    This precondition is a boolean expression that should evaluate to  `true` for the
-///    precondition to hold.
+///    Some functions require a stricter alignment than that of the type, such as SIMD loads
+///    requiring a `*const f32` to be aligned to 16 bytes. This is expressed with the syntax
+///    `#[pre(proper_align_val(<ptr_name>, <align>))]`.
+///
+///    - `<ptr_name>`: The identifier of the pointer argument that must be aligned.
+///    - `<align>`: A `usize` expression for the alignment in bytes, such as `32` or a constant.
+///      Integer literals must be a power of two.
+///
+///    By default a `debug_assert` statement is added to the function for such a precondition.
+///    This can be disabled by a `#[pre(no_debug_assert)]` attribute.
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(proper_align_val(ptr_name, 32))]
+///    fn foo(ptr_name: *const u8) {}
+///    ```
+///
+///    Allocator and FFI functions often require an alignment that is given explicitly, for example
+///    by a `Layout` argument. This is expressed with the syntax
+///    `#[pre(aligned_to(<ptr_name>, <align>))]`, which takes the same arguments and is checked the
+///    same way, but is a separate kind of precondition. It does not match a `proper_align_val`
+///    precondition with the same arguments.
+///
+///    ```rust
+///    # use pre::pre;
+///    # use std::alloc::Layout;
+///    #
+///    #[pre(aligned_to(ptr, layout.align()))]
+///    unsafe fn dealloc(ptr: *mut u8, layout: Layout) {}
+///    ```
+/// 4. Non-null preconditions:
+///
+///    This precondition requires that a raw pointer is not null.
+///    By default a `debug_assert` statement is added to the function for such a precondition.
+///    This can be disabled by a `#[pre(no_debug_assert)]` attribute.
+///
+///    The syntax is `#[pre(non_null(<ptr_name>))]`.
+///
+///    - `<ptr_name>`: The identifier of the pointer argument that must not be null.
+///
+///    Prefer this over an equivalent boolean precondition such as `!ptr_name.is_null()`, so that
+///    the precondition matches across crates regardless of how the expression is spelled.
+///
+///    ### Example
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(non_null(ptr_name))]
+///    fn foo(ptr_name: *mut i32) {}
+///    ```
+/// 5. Range preconditions:
+///
+///    This precondition requires that a value is within a range.
 ///    By default a `debug_assert` statement is added to the function for such a precondition.
 ///    This can be disabled by a `#[pre(no_debug_assert)]` attribute.
 ///
-///    The syntax is `#[pre(<expr>)]`.
+///    The syntax is `#[pre(in_range(<name>, <range>))]`.
+///
+///    - `<name>`: The identifier of the argument that must be within the range.
+///    - `<range>`: A range expression with at least one bound, such as `a..b`, `a..=b`, `a..` or
+///      `..b`. The bounds must have the same type as the argument.
+///
+///    ### Example
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(in_range(count, 0..=isize::MAX as usize))]
+///    fn foo(count: usize) {}
+///    ```
+/// 6. Valid value preconditions:
+///
+///    This precondition requires that the bits of a value form a valid value of a type.
+///    This is the contract of transmute-like functions, such as `mem::transmute_copy`,
+///    `MaybeUninit::assume_init` or `char::from_u32_unchecked`.
+///
+///    The syntax is `#[pre(valid_value(<name>, <type>))]`.
+///
+///    - `<name>`: The identifier of the argument whose bits must form a valid value.
+///    - `<type>`: The type that the bits must be a valid value of. For enums this means that the
+///      bits are a valid discriminant.
+///
+///    ### Example
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(valid_value(bits, char))]
+///    fn foo(bits: u32) {}
+///    ```
+/// 7. Consumed preconditions:
+///
+///    This precondition requires that the ownership of an argument is transferred to the
+///    function, so the argument must not be used again after the call. This is the contract of
+///    raw constructors, such as `Box::from_raw` or `Vec::from_raw_parts`, which take ownership of
+///    the memory behind a pointer.
 ///
-///    - `<expr>`: A boolean expression that should evaluate to `true`.
+///    The syntax is `#[pre(consumed(<name>))]`.
+///
+///    - `<name>`: The identifier of the argument that must not be used after the call.
+///
+///    If a variable passed for this argument is used again after a call with an `assure`
+///    attribute for this precondition, the `use_after_consumed` lint is emitted. This check only
+///    considers later statements in the same and enclosing blocks of the same function.
+///
+///    ### Example
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(consumed(ptr))]
+///    fn foo(ptr: *mut i32) {}
+///    ```
+/// 8. C string preconditions:
+///
+///    These preconditions describe pointers to strings terminated by a nul byte, as used by
+///    foreign functions.
+///
+///    The syntax is `#[pre(nul_terminated(<ptr_name>))]` or `#[pre(valid_cstr(<ptr_name>))]`.
+///
+///    - `<ptr_name>`: The identifier of the pointer argument that points to the string.
+///
+///    `nul_terminated` requires that there is a nul byte within the allocated object that the
+///    pointer points into. `valid_cstr` additionally requires that the pointer is valid for reads
+///    of all bytes up to and including the nul byte, which must be at most `isize::MAX` bytes
+///    away, as required by `CStr::from_ptr`. The string must also stay valid for the duration of
+///    the call, so it may neither be freed nor mutated while the function runs.
+///
+///    With the `cstr-checks` feature, a `debug_assert` statement is added to the function for
+///    these preconditions. It checks that the pointer is not null. The nul terminator is not
+///    searched for, since the size of the allocated object is unknown, so the search could read
+///    out of bounds. It can be disabled by a `#[pre(no_debug_assert)]` attribute.
+///
+///    Passing `CString::new(s).unwrap().as_ptr()` for such a pointer is a common mistake, because
+///    the `CString` is dropped at the end of the statement, leaving the pointer dangling. If the
+///    argument of a call with an `assure` attribute for a C string or valid pointer precondition
+///    is the result of `as_ptr` or `as_mut_ptr` on a temporary value, the `temporary_storage`
+///    lint is emitted.
+///
+///    ### Example
+///
+///    ```rust
+///    # use pre::pre;
+///    # use std::os::raw::c_char;
+///    #
+///    #[pre(valid_cstr(name))]
+///    fn foo(name: *const c_char) {}
+///    ```
+/// 9. Embedded preconditions:
+///
+///    These preconditions describe contracts that are common on bare-metal systems when
+///    manipulating registers and buffers shared with hardware.
+///
+///    The syntax is `#[pre(interrupts_disabled())]` or `#[pre(dma_quiescent(<buf_name>))]`.
+///
+///    - `<buf_name>`: The identifier of the argument that refers to the buffer.
+///
+///    `interrupts_disabled` requires that interrupts are disabled for the duration of the call,
+///    for example because the function accesses state that is shared with interrupt handlers.
+///    `dma_quiescent` requires that no DMA engine reads or writes the memory of the buffer for the
+///    duration of the call. These conditions cannot be checked at runtime.
 ///
 ///    ### Example
 ///
 ///    ```rust
 ///    # use pre::pre;
 ///    #
-///    #[pre(a < b || b > 17)]
-///    fn foo(a: i32, b: i32) {}
+///    #[pre(interrupts_disabled())]
+///    #[pre(dma_quiescent(buf))]
+///    fn refill(buf: &mut [u8]) {}
 ///    ```
+/// 10. Scope lifetime preconditions:
+///
+///     This precondition requires that the storage an argument refers to lives until the end of
+///     the scope that the result of the call is used in. This is the contract of functions
+///     returning a value that borrows from a raw pointer, such as `CStr::from_ptr`.
+///
+///     The syntax is `#[pre(lives_until_end_of_scope(<name>))]`.
+///
+///     - `<name>`: The identifier of the argument that refers to the storage.
+///
+///     A common mistake is passing a pointer into a temporary value, such as in
+///     `CStr::from_ptr(CString::new("x")?.as_ptr())`, where the `CString` is dropped at the end of
+///     the statement. If the argument of a call with an `assure` attribute for this precondition
+///     is obtained from a temporary in the same expression, the `temporary_storage` lint is
+///     emitted. This check is purely syntactical.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     # use std::os::raw::c_char;
+///     #
+///     #[pre(lives_until_end_of_scope(ptr))]
+///     fn foo(ptr: *const c_char) {}
+///     ```
+/// 11. Layout compatibility preconditions:
+///
+///     This precondition requires that a value of one type can be read from the memory of a value
+///     of another type, which means that it is at most as large and at most as strictly aligned.
+///     This is part of the contract of reading a value through a pointer cast to a different
+///     type. Functions that read unaligned, such as `mem::transmute_copy`, only require the size
+///     and should state that with a custom precondition instead.
+///
+///     The syntax is `#[pre(layout_compatible(<from>, <to>))]`.
+///
+///     - `<from>`: The type whose memory is read.
+///     - `<to>`: The type that is read from the memory.
+///
+///     If neither type refers to a generic parameter of the function or `Self`, the sizes and
+///     alignments are compared at compile time and the function fails to compile if the
+///     precondition does not hold. Otherwise the precondition is only documented.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(layout_compatible(u32, [u8; 4]))]
+///     fn foo(value: &u32) {}
+///     ```
+/// 12. Initialized memory preconditions:
+///
+///     This precondition requires that the memory behind a raw pointer contains initialized
+///     values of its pointee type, as required for example by `ptr::read` or
+///     `MaybeUninit::assume_init_read`.
+///
+///     The syntax is `#[pre(initialized(<ptr_name>))]` or
+///     `#[pre(initialized(<ptr_name>, <count>))]`.
+///
+///     - `<ptr_name>`: The identifier of the pointer argument that points to the initialized
+///       memory.
+///     - `<count>`: An expression for the number of consecutive initialized values. Without it,
+///       the pointer must point to a single initialized value.
+///
+///     This precondition does not imply that the pointer is valid or properly aligned, which can
+///     be required with additional `valid_ptr` and `proper_align` preconditions.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(valid_ptr(src, r, count))]
+///     #[pre(initialized(src, count))]
+///     unsafe fn copy_out(src: *const u32, count: usize) -> Vec<u32> {
+///         std::slice::from_raw_parts(src, count).to_vec()
+///     }
+///     ```
+/// 13. Non-overlapping memory preconditions:
+///
+///     This precondition requires that the memory regions behind two raw pointers do not overlap,
+///     as required for example by `ptr::copy_nonoverlapping`.
+///
+///     The syntax is `#[pre(non_overlapping(<first_ptr>, <second_ptr>, <count>))]`.
+///
+///     - `<first_ptr>` and `<second_ptr>`: The identifiers of the pointer arguments.
+///     - `<count>`: An expression for the number of elements in each of the memory regions.
+///
+///     The pointers are matched by their identifiers, so
+///     `#[assure(non_overlapping(dst, src, count), ...)]` does not assure
+///     `#[pre(non_overlapping(src, dst, count))]`.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(valid_ptr(src, r, count))]
+///     #[pre(valid_ptr(dst, w, count))]
+///     #[pre(non_overlapping(src, dst, count))]
+///     unsafe fn copy(src: *const u8, dst: *mut u8, count: usize) {
+///         std::ptr::copy_nonoverlapping(src, dst, count)
+///     }
+///     ```
+/// 14. Slice preconditions:
+///
+///     This precondition requires that a raw pointer and a length describe a valid slice, as
+///     required for example by `slice::from_raw_parts`.
+///
+///     The syntax is `#[pre(valid_slice(<ptr_name>, <len>, <access_type>))]`.
+///
+///     - `<ptr_name>`: The identifier of the pointer argument that points to the first element.
+///     - `<len>`: An expression for the number of elements in the slice.
+///     - `<access_type>`: The type of access the slice must be valid for, as for `valid_ptr`
+///       preconditions.
+///
+///     It is a shorthand for the preconditions that such a slice requires and expands to
+///     `valid_ptr(<ptr_name>, <access_type>, <len>)`, `proper_align(<ptr_name>)`,
+///     `initialized(<ptr_name>, <len>)` and the boolean precondition
+///     `::pre::slice_len_fits(<ptr_name>, <len>)`, which requires that the slice is at most
+///     `isize::MAX` bytes large.
+///     Because of this, `#[assure(valid_slice(...), ...)]` assures all of these preconditions and
+///     the documentation lists them separately.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(valid_slice(data, len, r))]
+///     unsafe fn sum(data: *const u32, len: usize) -> u32 {
+///         std::slice::from_raw_parts(data, len).iter().sum()
+///     }
+///
+///     #[pre]
+///     fn main() {
+///         let values = [1, 2, 3];
+///
+///         #[assure(
+///             valid_slice(data, len, r),
+///             reason = "`values` is an initialized array of `len` elements"
+///         )]
+///         let result = unsafe { sum(values.as_ptr(), values.len()) };
+///
+///         assert_eq!(result, 6);
+///     }
+///     ```
+/// 15. Reentrancy preconditions:
+///
+///     This precondition requires that the function is not called again before the call
+///     returns, for example from a callback that it invokes or from a signal handler. This is the
+///     contract of functions that temporarily leave shared state in an inconsistent state.
+///
+///     The syntax is `#[pre(not_reentrant())]`.
+///
+///     With the `reentrancy-checks` feature, which requires `std`, a guard is added to the
+///     function that panics if the function is called again on the same thread before it
+///     returned. Like `debug_assert` statements, the guard is only added when debug assertions are
+///     enabled. It can be disabled by a `#[pre(no_debug_assert)]` attribute and is never added to
+///     `const fn`s and `async fn`s. Calls from other threads and from signal handlers are not
+///     detected.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(not_reentrant())]
+///     fn for_each_entry(callback: &mut dyn FnMut(u32)) {
+///         for entry in 0..3 {
+///             callback(entry);
+///         }
+///     }
+///
+///     #[pre]
+///     fn main() {
+///         let mut sum = 0;
+///
+///         #[assure(not_reentrant(), reason = "the callback does not call `for_each_entry`")]
+///         for_each_entry(&mut |entry| sum += entry);
+///
+///         assert_eq!(sum, 3);
+///     }
+///     ```
+/// 16. UTF-8 preconditions:
+///
+///     This precondition requires that the bytes of an argument are valid UTF-8. This is the
+///     contract of functions that turn bytes into strings without checking them, such as
+///     `str::from_utf8_unchecked` or `String::from_utf8_unchecked`.
+///
+///     The syntax is `#[pre(valid_utf8(<name>))]`.
+///
+///     - `<name>`: The identifier of the argument containing the bytes.
+///
+///     With the `utf8-checks` feature, a `debug_assert` statement is added to the function for
+///     this precondition. It validates the bytes with `core::str::from_utf8`, so the argument
+///     must be usable as a byte slice through `AsRef<[u8]>`. Because validating takes time linear
+///     in the number of bytes, the check is not enabled by default. It can be disabled by a
+///     `#[pre(no_debug_assert)]` attribute and is never added to `const fn`s.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(valid_utf8(bytes))]
+///     fn first_char(bytes: &[u8]) -> Option<char> {
+///         unsafe { core::str::from_utf8_unchecked(bytes) }.chars().next()
+///     }
+///
+///     #[pre]
+///     fn main() {
+///         #[assure(valid_utf8(bytes), reason = "the bytes are taken from a string literal")]
+///         let c = first_char("äbc".as_bytes());
+///
+///         assert_eq!(c, Some('ä'));
+///     }
+///     ```
+/// 17. Boolean preconditions:
+///
+///     This precondition is a boolean expression that should evaluate to  `true` for the
+///     precondition to hold.
+///     By default a `debug_assert` statement is added to the function for such a precondition.
+///     This can be disabled by a `#[pre(no_debug_assert)]` attribute.
+///
+///     The `debug_assert` is adapted to the generic parameters of the function: if the expression
+///     divides by `size_of::<T>()`, it is only checked for types with a non-zero size, and if it
+///     uses `size_of::<T>()` or `align_of::<T>()` for a type parameter declared as `T: ?Sized`,
+///     it is not checked at all and the `unchecked_condition` lint is reported instead.
+///
+///     The syntax is `#[pre(<expr>)]`.
+///
+///     - `<expr>`: A boolean expression that should evaluate to `true`.
+///
+///     The expression may refer to `self` in methods, which can have preconditions on the nightly
+///     compiler, for example `#[pre(self.len < self.cap)]`. The spacing of the expression is not
+///     significant, so `#[assure(self.len<self.cap, ...)]` assures the same precondition.
+///
+///     If a boolean precondition is assured at a call whose arguments for the parameters it
+///     refers to are constants, such as `foo(4, 1)` for `#[pre(align <= 16)]`, it is evaluated at
+///     compile time and a wrong `assure` fails to compile. This only applies to parameters of
+///     primitive types and expressions made of literals, operators, casts and a few `const`
+///     methods of primitive types, such as `is_power_of_two`. The called function must be defined
+///     earlier in the same crate and the call must not possibly refer to another function with
+///     the same name.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(a < b || b > 17)]
+///     fn foo(a: i32, b: i32) {}
+///     ```
+/// 18. Token preconditions:
+///
+///     This precondition requires that a value of a type implementing [`Token`](trait.Token.html)
+///     is passed to the function.
+///     Unlike other preconditions, this is enforced by the type system: the precondition can only
+///     be assured, if a token value is available.
+///
+///     The syntax is `#[pre(token(<type>))]`.
+///
+///     - `<type>`: The type of the token. Use a reference type (such as `&InitToken`) to borrow the
+///       token instead of consuming it.
+///
+///     When assuring this precondition, the value of the token must be specified:
+///     `#[assure(token(<type> = <value>))]`. No reason is required in that case.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     struct InitToken(());
+///
+///     impl pre::Token for InitToken {}
+///
+///     fn init() -> InitToken {
+///         InitToken(())
+///     }
+///
+///     #[pre(token(&InitToken))]
+///     fn foo() {}
+///
+///     #[pre]
+///     fn main() {
+///         let init_token = init();
+///
+///         #[assure(token(&InitToken = &init_token))]
+///         foo();
+///     }
+///     ```
+/// 19. Exported preconditions:
+///
+///     This is a custom precondition that is additionally exported as a
+///     [`ConditionDef`](struct.ConditionDef.html) constant.
+///     Other code, including other crates, can refer to the condition by the path to the constant
+///     instead of repeating its text, so changing the wording of the condition does not break them.
+///
+///     The syntax is `#[pre(export = <name>, "<string>")]` to define the condition and
+///     `#[pre(use <path>)]` or `#[assure(use <path>, reason = "<reason>")]` to refer to it.
+///
+///     - `<name>`: The name of the generated constant. It has the same visibility as the function.
+///     - `<string>`: An arbitrary string describing the condition.
+///     - `<path>`: The path to the generated constant.
+///
+///     Conditions are identified by a hidden marker type generated next to the constant, so a
+///     constant of the same name in another module is a different condition. Different exported
+///     conditions that are used together should still have different names, since they are
+///     ordered by their name. Re-exports of the constant must keep its name.
+///
+///     ### Example
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     mod library {
+///         # use pre::pre;
+///         #[pre(export = COND_INIT, "the library is initialized")]
+///         pub fn foo() {}
+///     }
+///
+///     #[pre]
+///     fn main() {
+///         #[assure(use library::COND_INIT, reason = "the library is initialized at startup")]
+///         library::foo();
+///     }
+///     ```
 ///
 /// # General syntax
 ///
-/// There are three uses of the `pre` attribute:
+/// There are nine uses of the `pre` attribute:
 ///
 /// 1. Specify one or multiple preconditions (for the exact syntax of the preconditions, see
 ///    ["Precondition syntax"](#precondition-syntax)):
@@ -698,7 +1555,15 @@
 ///    #[pre("some precondition")]
 ///    fn foo() {} // foo will not have any documentation generated by pre.
 ///    ```
-/// 4. Disable debug assertions for boolean preconditions.
+///
+///    If documentation is disabled for the whole crate (see ["Configuring
+///    messages"](index.html#configuring-messages)), `#[pre(doc)]` enables it again for a single
+///    item.
+/// 4. Disable debug assertions for boolean, non-null and range preconditions.
+///
+///    In `const fn`s, only boolean preconditions are checked by debug assertions, because the
+///    other checks cannot be evaluated at compile time.
+///
 ///    ```rust
 ///    # use pre::pre;
 ///    #
@@ -707,22 +1572,431 @@
 ///    fn foo() {} // foo will not have any `debug_assert`s generated by pre.
 ///    ```
 ///
-/// # Checking functionality
+///    If debug assertions are disabled for the whole crate (see ["Configuring
+///    messages"](index.html#configuring-messages)), `#[pre(debug_assert)]` enables them again
+///    for a single function.
+/// 5. Warn about operations that share an `unsafe` block with an assured call.
 ///
-/// The `pre` attribute can also be used to enable the functionalities of the
-/// [`assure`](attr.assure.html) and [`forward`](attr.forward.html) attributes for the item it is
-/// attached to. In this case it can be left empty: `#[pre]`. Any other form of a `pre` attribute
-/// being present on an item renders the empty one obsolete though, as any of its forms enables
-/// this functionality.
+///    This encourages keeping the scope of `unsafe` blocks minimal, so that the assured call is
+///    the only operation that relies on the `unsafe` block.
+///    Dereferences and unassured calls to functions that are known to have preconditions in the
+///    same `unsafe` block as an assured call (but not in its arguments) are reported. Other calls
+///    and macro invocations are usually safe, so they are not reported.
+///    On stable, the warnings are reported as uses of a deprecated item named `strict_unsafe`.
 ///
-/// Doing this is currently necessary, because the current (1.44.1) stable rust compiler does not
-/// support attribute macros being applied to statements or expressions directly.
+///    The setting applies to the annotated item and all items nested within it.
 ///
-/// # Documentation on items with preconditions
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(valid_ptr(ptr, r))]
+///    unsafe fn read_twice(ptr: *const i32) -> i32 {
+///        *ptr + *ptr
+///    }
 ///
-/// Items annotated with one or more preconditions have information about their preconditions
+///    #[pre(strict_unsafe)]
+///    fn main() {
+///        let val = 42;
+///        let ptr = &val as *const i32;
+///
+///        // This would cause a warning for `*ptr`:
+///        // let sum = unsafe {
+///        //     #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+///        //     let sum = read_twice(ptr);
+///        //     sum + *ptr
+///        // };
+///
+///        let sum = unsafe {
+///            #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+///            read_twice(ptr)
+///        };
+///        println!("{}", sum);
+///    }
+///    ```
+/// 6. Generate an adapter to use the function in higher-order contexts, such as
+///    `Iterator::map` or dispatch tables (see
+///    [`assure_for_all_calls`](macro.assure_for_all_calls.html)):
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(adapter)]
+///    #[pre(x > 0)]
+///    fn double(x: i32) -> i32 {
+///        x * 2
+///    }
+///    ```
+/// 7. Change how the warnings emitted by `pre` are reported, using `allow`, `warn` or `deny`
+///    followed by a list of lints.
+///
+///    `allow` silences the warnings, `warn` reports them as warnings (the default) and `deny`
+///    reports them as errors. On stable, warnings are reported as uses of a deprecated item named
+///    after the lint, so they can also be silenced with `#[allow(deprecated)]`. The
+///    `missing_assure_summary` lint is only reported on nightly.
+///
+///    The following lints exist:
+///    - `todo_reason`: a reason is `"TODO"`
+///    - `unfinished_reason`: a reason is empty or `"?"`
+///    - `ignored_attribute`: a `pre` attribute has no effect where it is used
+///    - `missing_assure_summary`: the summary of call sites missing `assure` attributes
+///    - `trusted_assure`: a call in a `#[pre(trusted)]` scope has its preconditions assured
+///      automatically
+///    - `use_after_consumed`: a variable is used after it was passed to a function with a
+///      `consumed` precondition
+///    - `foreign_attribute`: a call has an attribute of another crate named `assure` or
+///      `forward`, such as `#[other::assure(...)]`, which is not handled by pre
+///    - `forward_without_preconditions`: a `forward` attribute points to a function without
+///      preconditions in an [`extern_crate`](attr.extern_crate.html)-annotated module
+///    - `documentation_only`: the preconditions of a naked function are only documented, because
+///      they cannot be checked
+///    - `impure_condition`: a boolean precondition of a function marked with `pure` may have side
+///      effects
+///    - `temporary_storage`: the argument for a `lives_until_end_of_scope` precondition is
+///      obtained from a temporary value that is dropped at the end of the statement, or the
+///      argument for a C string or valid pointer precondition is a pointer into such a value
+///    - `unchecked_condition`: no `debug_assert` is generated for a boolean precondition, because
+///      it requires a type parameter declared as `?Sized` to be sized
+///    - `assure_outside_unsafe`: an assured call to an `unsafe fn` is not written within an
+///      `unsafe` block, which is possible in the body of an `unsafe fn`
+///
+///    The lint levels apply to the annotated item and all items nested within it, with the
+///    innermost setting taking precedence.
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(x > 0)]
+///    fn foo(x: i32) {}
+///
+///    #[pre(deny(unfinished_reason))]
+///    #[pre(allow(todo_reason))]
+///    fn main() {
+///        #[assure(x > 0, reason = "TODO")]
+///        foo(42);
+///    }
+///    ```
+/// 8. Scan the contents of all macro invocations for [`assure`](attr.assure.html) and
+///    [`forward`](attr.forward.html) attributes (see ["Calls inside of
+///    macros"](#calls-inside-of-macros)):
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    macro_rules! identity {
+///        ($($tokens:tt)*) => { $($tokens)* };
+///    }
+///
+///    #[pre(x > 0)]
+///    fn foo(x: i32) {}
+///
+///    #[pre(scan_macros)]
+///    fn main() {
+///        identity! {
+///            #[assure(x > 0, reason = "42 > 0")]
+///            foo(42)
+///        };
+///    }
+///    ```
+/// 9. Mark the function as `#[must_use]`.
+///
+///    This is mostly useful on modules annotated with
+///    [`extern_crate`](attr.extern_crate.html#qualifiers-and-attributes), where it applies to all
+///    contained functions that return a value.
+///
+///    ```rust
+///    # use pre::pre;
+///    #
+///    #[pre(must_use)]
+///    #[pre(x > 0)]
+///    fn double(x: i32) -> i32 {
+///        x * 2
+///    }
+///    ```
+/// 10. Trust code generated by other tools, such as bindgen or derive macros, that cannot contain
+///     [`assure`](attr.assure.html) attributes.
+///
+///     The preconditions of calls in the annotated item are assured automatically and a
+///     `trusted_assure` warning is emitted for each of these calls instead. The calls are
+///     exported as `"generated"` (see ["Exporting metadata"](index.html#exporting-metadata)).
+///     Inside of functions, `#[pre(trusted)]` can also be attached to statements and blocks.
+///
+///     This only works for calls to functions in the current crate whose definition was
+///     expanded before the call and that have no token preconditions. Calls in macro
+///     invocations are not assured.
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre]
+///     mod bindings {
+///         #[pre(x > 0)]
+///         pub(crate) fn foo(x: i32) {}
+///
+///         pub(crate) fn generated() {
+///             #[pre(trusted)]
+///             foo(42);
+///         }
+///     }
+///     ```
+/// 11. Generate tests verifying that the preconditions are checked at runtime, using
+///     `gen_violation_tests` followed by a list of cases.
+///
+///     Each case lists a value for every parameter of the function, such as `case(x = 0)`. For
+///     each case, a `#[should_panic]` test calls the function with these values, with all
+///     preconditions assured. The tests are generated in a module named
+///     `<function>__violation_tests__`, which only exists when compiling tests with debug
+///     assertions enabled.
+///
+///     At least one precondition of the function has to be checked by a `debug_assert`, so
+///     `no_debug_assert` cannot be used on the function. Methods, `async` functions and functions
+///     that cannot unwind, such as `extern "C"` functions, are not supported.
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(x > 0)]
+///     #[pre(gen_violation_tests(case(x = 0), case(x = -1)))]
+///     fn double(x: i32) -> i32 {
+///         x * 2
+///     }
+///     ```
+/// 12. Make the precondition argument the first argument of the function instead of the last one.
+///
+///     Because pre adds an argument to the function, calls that are missing `assure` attributes
+///     fail with an error about a missing argument. If the precondition argument is the last one,
+///     rustc may associate the remaining arguments with the wrong parameters in this error. With
+///     `precondition_arg_first`, the first argument is reported as missing instead.
+///
+///     rustc's suggested fix for this error should not be applied as-is: when the remaining
+///     parameters share a type, it currently proposes a call with the given arguments swapped,
+///     such as `div(/* … */, 4, 2)` for `div(2, 4)` below. This happens for any function with a
+///     missing first argument, not only with pre. Add the `assure` attribute from the
+///     `missing_assure_summary` warning instead and keep the arguments as they are.
+///
+///     Calls only know about this if the function is defined in the current crate and its
+///     definition is expanded before the call. Methods are not supported.
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(precondition_arg_first)]
+///     #[pre(x > 0)]
+///     fn div(x: i32, y: i32) -> i32 {
+///         y / x
+///     }
+///
+///     #[pre]
+///     fn main() {
+///         #[assure(x > 0, reason = "2 > 0")]
+///         let quotient = div(2, 4);
+///         assert_eq!(quotient, 2);
+///     }
+///     ```
+/// 13. Report the assertions at the start of functions that could be written as preconditions,
+///     using `harvest_asserts`.
+///
+///     This helps converting code that checks its requirements with assertions to pre. For each
+///     `assert!`, `debug_assert!`, `assert_eq!`, `assert_ne!` or their `debug_` variants before
+///     the first other statement of a function, a warning suggests the equivalent `pre`
+///     attribute. Assertions that are already preconditions of the function are skipped. The
+///     suggested attributes are only shown on nightly, so they are also written to the [exported
+///     metadata](index.html#exporting-metadata).
+///
+///     The assertions themselves are left unchanged. The setting applies to the annotated item
+///     and all items nested within it.
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(harvest_asserts)]
+///     fn checked_div(x: i32, y: i32) -> i32 {
+///         // warning: this assertion can be written as a precondition
+///         //    help: add `#[pre(y != 0)]` to `checked_div`
+///         assert!(y != 0);
+///
+///         x / y
+///     }
+///     ```
+/// 14. Place the `debug_assert` statements after some setup code, using
+///     `#[pre(assert_after_setup)]`.
+///
+///     By default the checks of the preconditions are placed at the very start of the function,
+///     where all parameters are available, including the bindings of pattern parameters such as
+///     `(a, b): (u32, u32)`. If a precondition refers to a binding that is only created in the
+///     function body, the statement creating it can be marked with `#[pre(assert_after_setup)]`.
+///     The checks are then placed directly after the marked statement.
+///
+///     There can only be one marker per function and it has to be on a statement directly in
+///     the function body. Note that the checks refer to the bindings visible at the marker, so
+///     shadowing or mutating a parameter in the setup code changes what is checked.
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(total <= limit)]
+///     fn remaining(parts: &[u32], limit: u32) -> u32 {
+///         #[pre(assert_after_setup)]
+///         let total: u32 = parts.iter().sum();
+///
+///         limit - total
+///     }
+///
+///     #[pre]
+///     fn main() {
+///         #[assure(total <= limit, reason = "1 + 2 + 3 <= 10")]
+///         let rest = remaining(&[1, 2, 3], 10);
+///         assert_eq!(rest, 4);
+///     }
+///     ```
+/// 15. Limit the number of unchecked calls in the function, using
+///     `#[pre(max_unchecked_calls = <n>)]`.
+///
+///     A call is unchecked, if it calls a function with preconditions without `assure`
+///     attributes, or if it calls the original of a function with preconditions in an
+///     [`extern_crate`](attr.extern_crate.html) module in an `unsafe` context without a
+///     [`forward`](attr.forward.html) attribute. An error is emitted, if the function contains
+///     more unchecked calls than allowed, so `max_unchecked_calls = 0` ensures that all calls in
+///     the function are checked by pre.
+///
+///     Only functions defined and `extern_crate` modules expanded before the function are known.
+///     Calls inside of macro invocations are not counted.
+///
+///     ```rust,compile_fail
+///     # use pre::pre;
+///     #
+///     #[pre::extern_crate(std)]
+///     mod pre_std {
+///         mod ptr {
+///             #[pre(valid_ptr(src, r))]
+///             unsafe fn read<T>(src: *const T) -> T;
+///         }
+///     }
+///
+///     // error: `read` contains 1 unchecked call, but at most 0 are allowed
+///     #[pre(max_unchecked_calls = 0)]
+///     fn read(value: &i32) -> i32 {
+///         // note: this call to `std::ptr::read` bypasses the preconditions of an
+///         //       `extern_crate` module
+///         unsafe { std::ptr::read(value) }
+///     }
+///     ```
+/// 16. Promise that the boolean preconditions have no side effects, using `#[pre(pure)]`.
+///
+///     Boolean preconditions are evaluated by the `debug_assert` statements in the function.
+///     Checking them anywhere else as well, for example before a call, evaluates them more than
+///     once, which is only correct if evaluating them has no side effects. `pure` marks the boolean
+///     preconditions of the function as safe to evaluate any number of times. Tools checking the
+///     preconditions more than once should only do so for functions marked as `pure`, which is
+///     recorded in the [exported metadata](index.html#exporting-metadata).
+///
+///     As a heuristic, the `impure_condition` lint reports method calls in the boolean
+///     preconditions whose names suggest that they mutate their receiver, such as `pop` or
+///     `fetch_add`, as well as assignments and mutable borrows.
+///
+///     ```rust
+///     # use pre::pre;
+///     #
+///     #[pre(pure)]
+///     #[pre(!values.is_empty())]
+///     fn first(values: &[i32]) -> i32 {
+///         values[0]
+///     }
+///     ```
+///
+/// # Checking functionality
+///
+/// The `pre` attribute can also be used to enable the functionalities of the
+/// [`assure`](attr.assure.html) and [`forward`](attr.forward.html) attributes for the item it is
+/// attached to. In this case it can be left empty: `#[pre]`. Any other form of a `pre` attribute
+/// being present on an item renders the empty one obsolete though, as any of its forms enables
+/// this functionality.
+///
+/// Doing this is currently necessary, because the current (1.44.1) stable rust compiler does not
+/// support attribute macros being applied to statements or expressions directly.
+///
+/// # Calls inside of macros
+///
+/// The arguments of the following std macros are treated as expressions, so calls inside of them
+/// can be annotated as usual: `assert`, `assert_eq`, `assert_ne`, `dbg`, `debug_assert`,
+/// `debug_assert_eq`, `debug_assert_ne`, `eprint`, `eprintln`, `format`, `format_args`, `panic`,
+/// `print`, `println`, `vec`, `write` and `writeln`.
+/// Macros are recognized by their name only, so a custom macro with the same name is treated the
+/// same way, as long as its arguments are comma separated expressions.
+///
+/// An attribute on an invocation of such a macro applies to the call among its arguments, if
+/// there is exactly one: `#[assure(...)] dbg!(foo(x))` is the same as `dbg!(#[assure(...)]
+/// foo(x))`.
+///
+/// The contents of other macro invocations are left untouched by default, because they don't
+/// necessarily consist of rust code. Using `#[pre(scan_macros)]`, the tokens of all macro
+/// invocations in the annotated item are scanned for attributes. Every attribute found this way
+/// must be followed by an expression containing the call.
+///
+/// # Functions exported to foreign code
+///
+/// The signature of functions with an explicit ABI (such as `extern "C" fn`) or with a
+/// `#[no_mangle]` or `#[export_name]` attribute cannot change, because foreign code relies on
+/// it. Preconditions on these functions are therefore not checked at call sites. They are still
+/// documented, exported as [metadata](index.html#exporting-metadata) and checked by debug
+/// assertions where possible. The generated documentation notes that callers need to make sure the
+/// preconditions hold themselves.
+///
+/// ```rust
+/// # use pre::pre;
+/// #
+/// #[pre(len <= 1024)]
+/// #[no_mangle]
+/// pub extern "C" fn process_buffer(len: usize) {}
+///
+/// fn main() {
+///     // No `assure` attribute is needed or possible here.
+///     process_buffer(512);
+/// }
+/// ```
+///
+/// The unsafe forms of these attributes, such as `#[unsafe(no_mangle)]`, are recognized as well.
+///
+/// # Codegen attributes
+///
+/// Codegen attributes, such as `#[inline(never)]`, `#[cold]` or `#[track_caller]`, are kept on
+/// the function and do not affect how its preconditions are handled.
+///
+/// Naked functions (`#[unsafe(naked)]`) are an exception: their body may only consist of inline
+/// assembly and their signature cannot change, so neither debug assertions nor checks at call
+/// sites can be added to them. Their preconditions are therefore only documented and exported as
+/// [metadata](index.html#exporting-metadata). This is reported by the `documentation_only` lint,
+/// which can be silenced using `#[pre(allow(documentation_only))]`.
+///
+/// # Attributes that rewrite the function body
+///
+/// Attribute macros are expanded from the outermost to the innermost attribute, so an attribute
+/// below `pre` sees the function with the precondition parameter and the `debug_assert`
+/// statements added by `pre`. Attributes that rewrite the body of the function may record the
+/// parameter or move or drop the statements. To avoid this, place the `pre` attributes below such
+/// attributes, so that `pre` is expanded last:
+///
+/// ```rust,ignore
+/// #[tracing::instrument]
+/// #[pre(x > 0)]
+/// fn foo(x: i32) {}
+/// ```
+///
+/// For the known attributes of this kind, `#[tracing::instrument]` and `#[logfn]` and
+/// `#[logfn_inputs]` of `log-derive`, this happens automatically: if one of them is below the
+/// `pre` attributes of a function, `pre` moves its attributes below it without changing the
+/// function, so that the preconditions are applied exactly once after the body was rewritten.
+/// This does not work for functions inside of an item annotated with `pre`, such as a module,
+/// because the function is expanded together with that item.
+///
+/// # Documentation on items with preconditions
+///
+/// Items annotated with one or more preconditions have information about their preconditions
 /// and how to call them with the preconditions appended at the end of their documentation.
 ///
+/// If the preconditions refer to type or const parameters of the item in code spans, such as
+/// `` "an all-zero byte-pattern is a valid value of `T`" ``, the documentation also lists the
+/// declarations of these parameters and their bounds and links to them.
+///
 /// If you wish not to add such documentation to a particular item, you can add `#[pre(no_doc)]` to
 /// the attributes of the item to prevent its generation.
 pub use pre_proc_macro::pre;
@@ -801,8 +2075,157 @@ pub use pre_proc_macro::pre;
 ///
 /// To learn more about the precondition syntax and the possible types of preconditions, you should
 /// look at the [documentation of the `pre` attribute](attr.pre.html#precondition-syntax).
+///
+/// # Referencing audits
+///
+/// If the reason was reviewed elsewhere, such as in a security review tracked in an issue tracker,
+/// a reference to the review can be added after the reason:
+///
+/// ```rust,ignore
+/// #[assure(
+///     valid_ptr(ptr, r),
+///     reason = "`ptr` comes from the validated FFI buffer",
+///     audit = "SEC-123"
+/// )]
+/// foo(ptr);
+/// ```
+///
+/// The reference has no effect on the compilation, but it is included in the
+/// [exported metadata](index.html#exporting-metadata) together with the contract ID of the called
+/// function. This allows external tools to detect assures whose review was done for a different
+/// version of the contract.
+///
+/// Who reviewed the reason and when can be recorded as well, after the optional reference:
+///
+/// ```rust,ignore
+/// #[assure(
+///     valid_ptr(ptr, r),
+///     reason = "`ptr` comes from the validated FFI buffer",
+///     audit = "SEC-123",
+///     audited(by = "alice", date = "2024-06-01")
+/// )]
+/// foo(ptr);
+/// ```
+///
+/// The date must have the form `YYYY-MM-DD`. Like the reference, the record does not affect which
+/// preconditions are assured and is only included in the exported metadata, so audit reports can
+/// show the reviewer and flag reviews that are older than the last change of the contract.
+///
+/// # Assuring the preconditions of another function
+///
+/// When writing a thin compatibility shim around a function whose contract is the same as the
+/// one of an existing function, all preconditions of the existing function can be assured at once
+/// using `same_as`:
+///
+/// ```rust,ignore
+/// #[assure(same_as(old_api::foo), reason = "`new_api::foo` has the contract of `old_api::foo`")]
+/// new_api::foo(ptr);
+/// ```
+///
+/// This is the same as writing one `assure` attribute with the given reason for every
+/// precondition of `old_api::foo`. The preconditions are copied when the attribute is expanded,
+/// so the named function must have preconditions and be defined earlier in the same crate.
+/// If functions with preconditions and the same name are defined in multiple modules, the path
+/// must name a module containing the function that has a `#[pre]` attribute, such as `old_api`
+/// in `#[pre] mod old_api { ... }`. Otherwise an error is reported, because it is unclear which of
+/// them is meant.
+/// Exported and imported conditions are resolved relative to the module of the named function and
+/// token preconditions are not copied, because their value is unknown.
+///
+/// # Placement
+///
+/// The attribute does not need to be attached to the call directly. It can also be attached to an
+/// expression or `let` statement containing the call, as long as the call is the only one in it
+/// that could be meant, for example `#[assure(...)] x = foo();`, `#[assure(...)] return unsafe {
+/// foo() };` or `#[assure(...)] if foo() { /* no calls here */ }`. If it is ambiguous which call
+/// is meant, an error is emitted.
+///
+/// In a method chain, the attribute applies to the last method call, unless the result of a call
+/// in the chain is adapted using `?`, `.await` or indexing. In that case it applies to the first
+/// such call, so `#[assure(...)] let len = foo()?.len();` applies to `foo`.
+///
+/// If the call the attribute would apply to is a method call with a closure argument containing a
+/// call, such as `map_err`, `and_then` or `unwrap_or_else`, it applies to the call in the closure
+/// instead, so `#[assure(...)] let x = r.map_err(|e| unsafe { foo(e) })?;` applies to `foo`. The
+/// attribute can also be attached to the closure or to an expression in its body directly. To
+/// apply the attribute to the method itself, store the closure in a variable first.
+///
+/// Attached to a struct expression, the attribute can apply to a call in the functional update
+/// base, such as `make` in `#[assure(...)] let s = S { x, ..make() };`, as long as none of the
+/// fields contain calls. If they do, move them into `let` statements before the struct expression.
+///
+/// To assure the same precondition for several calls to a function in an `unsafe` block, the
+/// [`assure_each` attribute](attr.assure_each.html) can be used instead.
 pub use pre_proc_macro::assure;
 
+/// Assure that a precondition holds for every call to a function in an `unsafe` block.
+///
+/// Currently this attribute does not work by itself.
+/// It needs to be used inside of a context that is annotated by a `pre` attribute.
+///
+/// This is a shorthand for attaching the same [`assure` attribute](attr.assure.html) to each
+/// call, which is useful if an `unsafe` block calls a function multiple times for the same reason.
+///
+/// # Example
+///
+/// ```rust
+/// use pre::pre;
+///
+/// #[pre(valid_ptr(ptr, r))]
+/// unsafe fn read_twice(ptr: *const u8) -> (u8, u8) {
+///     (*ptr, *ptr)
+/// }
+///
+/// #[pre]
+/// fn main() {
+///     let values = [1, 2, 3];
+///     let ptr = values.as_ptr();
+///
+///     #[assure_each(
+///         read_twice,
+///         valid_ptr(ptr, r),
+///         reason = "`ptr` points to the start of `values`",
+///         count = 2
+///     )]
+///     let (first, second) = unsafe { (read_twice(ptr), read_twice(ptr)) };
+///
+///     assert_eq!(first, (1, 1));
+///     assert_eq!(second, (1, 1));
+/// }
+/// ```
+///
+/// # Syntax
+///
+/// ```rust,ignore
+/// #[assure_each(
+///     <function>,
+///     <precondition>,
+///     reason = "<the reason why the precondition can be assured for every call>",
+///     count = <the number of calls>
+/// )]
+/// unsafe {
+///     // ...
+/// }
+/// ```
+///
+/// The function is given by the path used in the calls, so `#[assure_each(ptr::read, ...)]`
+/// applies to calls like `ptr::read(p)` and `ptr::read::<u8>(p)`, but not to `read(p)`. An
+/// `audit = "..."` reference may follow the reason, just like for `assure`. Multiple
+/// `assure_each` attributes can be attached to the same block to assure multiple preconditions.
+///
+/// The attribute can be attached to an `unsafe` block or to a `let` statement initialized by one.
+/// All calls to the function inside of the block are assured, including calls in the arguments of
+/// the [recognized macros](attr.pre.html#calls-inside-of-macros). Calls in closures, async blocks
+/// and nested items are excluded, because they do not necessarily run as part of the block.
+///
+/// # Counting calls
+///
+/// The optional `count` states how many calls to the function the block contains. If the number
+/// of calls differs, an error is emitted. This way calls added to the block later do not silently
+/// inherit the reason, but have to be reviewed first. Without a `count`, an error is only emitted
+/// if the block does not contain any call to the function.
+pub use pre_proc_macro::assure_each;
+
 /// Forward the call to a different function that has the preconditions for the original function.
 ///
 /// Currently this attribute does not work by itself.
@@ -931,6 +2354,9 @@ pub use pre_proc_macro::assure;
 ///
 /// `<path>` is prepended to the path of the annotated call.
 ///
+/// The resulting path starts with `::` exactly if `<path>` does, because the path of the call is
+/// now relative to `<path>`.
+///
 /// ### Example
 ///
 /// ```rust,ignore
@@ -952,6 +2378,14 @@ pub use pre_proc_macro::assure;
 ///
 /// `<old_path>` is replaced with `<new_path>` in the path of the annotated call.
 ///
+/// Generic arguments in the path of the call, such as in `abc::jkl::<u8>()`, don't need to be
+/// specified in `<old_path>`. They are kept, unless `<new_path>` specifies its own.
+///
+/// A leading `::` is ignored when matching `<old_path>`, so `#[forward(std -> new_std)]` also
+/// applies to `::std::ptr::read()`. The resulting path starts with `::` exactly if `<new_path>`
+/// does, so `#[forward(::std -> crate::new_std)]` turns `::std::ptr::read()` into
+/// `crate::new_std::ptr::read()`.
+///
 /// ### Example
 ///
 /// ```rust,ignore
@@ -1005,8 +2439,329 @@ pub use pre_proc_macro::assure;
 ///
 /// The exact inner workings of this are different to make it work in more contexts, but this is a
 /// good mental model to think about it.
+///
+/// On the nightly compiler, the [extension traits](attr.extern_crate.html#extension-traits)
+/// generated for `impl` blocks can be used instead, which don't require a `forward` attribute.
+///
+/// ### Generic arguments
+///
+/// If the `impl` block is for a type with concrete generic arguments, such as `impl X<bool>`,
+/// these arguments must be part of `<path>` as well: `#[forward(impl some_impl::X<bool>)]`.
+/// Generic arguments that are generic parameters of the `impl` block can be written as `_` or
+/// omitted entirely, if no argument is concrete.
+///
+/// ```rust
+/// use pre::pre;
+///
+/// mod a {
+///     pub(crate) struct X<T, U>(T, U);
+///
+///     impl<T> X<T, bool> {
+///         pub(crate) fn foo() {}
+///     }
+///
+///     impl<T> X<T, ()> {
+///         pub(crate) fn foo() {}
+///     }
+/// }
+///
+/// #[pre::extern_crate(crate::a)]
+/// mod b {
+///     impl<T> X<T, bool> {
+///         #[pre("foo is called on a `bool` instance")]
+///         fn foo();
+///     }
+///
+///     impl<T> X<T, ()> {
+///         #[pre("foo is called on a `()` instance")]
+///         fn foo();
+///     }
+/// }
+///
+/// #[pre]
+/// fn main() {
+///     #[forward(impl b::X<_, bool>)]
+///     #[assure("foo is called on a `bool` instance", reason = "it is")]
+///     a::X::<u8, bool>::foo();
+/// }
+/// ```
+///
+/// ### Type aliases
+///
+/// `<path>` may also name a [type alias](attr.extern_crate.html#type-aliases) for the type of the
+/// `impl` block that is declared in the same `extern_crate`-annotated module.
+///
+/// ### Trait implementations
+///
+/// For an `impl` block [implementing a trait](attr.extern_crate.html#trait-implementations),
+/// `<path>` is the path of the trait in the `extern_crate`-annotated module, followed by `for` and
+/// the type: `#[forward(impl new_std::alloc::GlobalAlloc for System)]`. Concrete generic arguments
+/// of the trait or the type must be given, as described above.
+///
+/// # Targets without preconditions
+///
+/// If a `forward` attribute points to a function without preconditions in an
+/// [`extern_crate`](attr.extern_crate.html)-annotated module, the call is not checked against any
+/// preconditions. This usually means that the attribute points to the wrong location, so the
+/// `forward_without_preconditions` [lint](attr.pre.html#general-syntax) is emitted for it. Use
+/// `#[pre(deny(forward_without_preconditions))]` to report it as an error on the stable compiler as
+/// well.
+///
+/// This only works for `extern_crate` modules in the same crate that are defined before the call.
 pub use pre_proc_macro::forward;
 
+/// Assure that the preconditions of a function hold for all calls made through an adapter.
+///
+/// Functions with preconditions cannot be passed to higher-order functions like `Iterator::map`
+/// directly, because they take an additional argument.
+/// If a function is annotated with `#[pre(adapter)]`, this macro can be used to obtain a closure
+/// with the original signature of the function.
+/// The preconditions are assured once for all calls made through the closure, so the `reason`
+/// should explain why the preconditions hold for every call.
+///
+/// # Example
+///
+/// ```rust
+/// use pre::{assure_for_all_calls, pre};
+///
+/// #[pre(adapter)]
+/// #[pre(x > 0)]
+/// fn double(x: i32) -> i32 {
+///     x * 2
+/// }
+///
+/// fn main() {
+///     let values = vec![1, 2, 3];
+///
+///     let doubled: Vec<_> = values
+///         .into_iter()
+///         .map(assure_for_all_calls!(
+///             #[assure(x > 0, reason = "all values are positive")]
+///             double
+///         ))
+///         .collect();
+///
+///     assert_eq!(doubled, vec![2, 4, 6]);
+/// }
+/// ```
+///
+/// # Syntax
+///
+/// ```rust,ignore
+/// assure_for_all_calls!(
+///     #[assure(<first precondition>, reason = "<reason>")]
+///     #[assure(<second precondition>, reason = "<reason>")]
+///     path::to::function
+/// )
+/// ```
+///
+/// # Storing adapters
+///
+/// The type of the returned closure cannot be named, so it cannot be stored in a struct field or
+/// in a dispatch table. For this, `#[pre(adapter)]` also generates a checked adapter type next to
+/// the function, named after the function in `UpperCamelCase` followed by `Checked`, such as
+/// `DoubleChecked` for `double`. Adding `as <checked adapter type>` creates a value of that type
+/// instead of a closure:
+///
+/// ```rust
+/// use pre::{assure_for_all_calls, pre};
+///
+/// #[pre(adapter)]
+/// #[pre(x > 0)]
+/// fn double(x: i32) -> i32 {
+///     x * 2
+/// }
+///
+/// struct Operation {
+///     name: &'static str,
+///     apply: DoubleChecked,
+/// }
+///
+/// fn main() {
+///     let operation = Operation {
+///         name: "double",
+///         apply: assure_for_all_calls!(
+///             #[assure(x > 0, reason = "operations are only applied to positive values")]
+///             double as DoubleChecked
+///         ),
+///     };
+///
+///     assert_eq!(operation.apply.call(2), 4);
+///     assert_eq!(operation.name, "double");
+/// }
+/// ```
+///
+/// The checked adapter type is `Copy` and has a `call` method taking the arguments of the function.
+/// Its `as_fn` method returns the closure that `assure_for_all_calls!` returns without `as`. Like
+/// the closure, the checked adapter type requires the preconditions only where it is created, so it
+/// can be called anywhere later. Functions whose names only differ in underscores, such as
+/// `foo_bar` and `foo__bar`, would get checked adapter types with the same name, which is reported
+/// as an error.
+///
+/// On the nightly compiler, the `adapter-fn-traits` feature of pre additionally implements the
+/// `Fn`, `FnMut` and `FnOnce` traits for the checked adapter types, so that they can be called
+/// like functions and passed where an `Fn` trait is required. Implementing these traits is
+/// unstable, so crates using this feature must enable `#![feature(unboxed_closures, fn_traits)]`.
+/// The feature has no effect on the stable compiler.
+///
+/// Unlike the [`assure`](attr.assure.html) attribute, this macro does not need to be used inside
+/// of an item annotated with a [`pre` attribute](attr.pre.html).
+///
+/// # Limitations
+///
+/// Adapters can only be generated for free functions. Methods, `async` functions and functions
+/// with `impl Trait` in their signature or with [token
+/// preconditions](attr.pre.html#precondition-syntax) are not supported.
+/// If the function is `unsafe`, the adapter is `unsafe` to create, but the resulting closure can
+/// be called without an `unsafe` block.
+pub use pre_proc_macro::assure_for_all_calls;
+
+/// Assert that a function has exactly the given preconditions.
+///
+/// This allows pinning the contract of a function in tests, so that accidental changes to its
+/// preconditions are noticed at compile time:
+///
+/// ```rust,ignore
+/// assert_requires!(path::to::function, [<first precondition>, <second precondition>])
+/// ```
+///
+/// The preconditions use the same [syntax](attr.pre.html#precondition-syntax) as in the [`pre`
+/// attribute](attr.pre.html). Their order does not matter, but no precondition may be missing
+/// and no additional precondition may be listed.
+///
+/// # Example
+///
+/// ```rust
+/// use pre::pre;
+///
+/// #[pre(valid_ptr(ptr, r))]
+/// #[pre("`ptr` points to an initialized `u32`")]
+/// unsafe fn read(ptr: *const u32) -> u32 {
+///     *ptr
+/// }
+///
+/// pre::assert_requires!(read, [valid_ptr(ptr, r), "`ptr` points to an initialized `u32`"]);
+/// #
+/// # fn main() {}
+/// ```
+///
+/// # Limitations
+///
+/// If the definition of the function in the current crate was expanded before the macro, the
+/// preconditions are compared directly and the error lists the differences. This is the case for
+/// unit tests at the end of the module defining the function. Only the name of the function is
+/// used to find its definition.
+///
+/// Otherwise the compiler compares the preconditions, which also works for functions in other
+/// crates, such as the ones in [`extern_crate`](attr.extern_crate.html) modules. The resulting
+/// errors are less readable in that case. On the nightly compiler, this is not possible, so only
+/// functions of the current crate can be checked there.
+pub use pre_proc_macro::assert_requires;
+
+/// Check that a custom precondition was reworded at all call sites.
+///
+/// When changing the text of a [custom precondition](attr.pre.html#precondition-syntax), all
+/// call sites that [`assure`](attr.assure.html) it need to be updated as well. The compiler
+/// already rejects call sites using the old text, but the resulting errors don't mention the
+/// condition. This macro reports every call site in the current crate that still assures the old
+/// text with an error mentioning both the old and the new text instead.
+///
+/// Once all call sites are updated, the macro can be removed again.
+///
+/// # Example
+///
+/// ```rust
+/// use pre::pre;
+///
+/// pre::rename_condition!("`ptr` is valid" => "`ptr` is valid for reads");
+///
+/// #[pre("`ptr` is valid for reads")]
+/// unsafe fn read(ptr: *const i32) -> i32 {
+///     *ptr
+/// }
+///
+/// #[pre]
+/// fn main() {
+///     let val = 42;
+///
+///     // Using "`ptr` is valid" here would cause an error.
+///     let read_val = unsafe {
+///         #[assure("`ptr` is valid for reads", reason = "`ptr` is created from a reference")]
+///         read(&val)
+///     };
+///     assert_eq!(read_val, 42);
+/// }
+/// ```
+///
+/// # Limitations
+///
+/// Only call sites in items annotated with a [`pre` attribute](attr.pre.html) are checked.
+/// Call sites that are expanded before the macro are listed in a single error at the macro, all
+/// other call sites are reported where they occur.
+pub use pre_proc_macro::rename_condition;
+
+/// Create a preconditioned twin of a local module.
+///
+/// This automates using the [`extern_crate` attribute](attr.extern_crate.html) for items of
+/// the current crate, which allows adding preconditions to a function without changing all of
+/// its call sites at once.
+///
+/// ```rust,ignore
+/// pre::shadow! {
+///     <visibility> mod <name> {
+///         <items>
+///     }
+/// }
+/// ```
+///
+/// The macro removes the preconditions from the functions and the methods of inherent impl
+/// blocks in the module, so existing call sites continue to compile unchanged. It additionally
+/// creates an [`extern_crate`](attr.extern_crate.html) module named `pre_<name>` next to the
+/// module, which contains the signatures of these functions with their preconditions.
+/// Call sites can then be converted one at a time with the [`forward`
+/// attribute](attr.forward.html).
+///
+/// Functions with preconditions must be visible outside of the module, for example by using
+/// `pub(crate)`, so that the twin module can call them.
+///
+/// # Example
+///
+/// ```rust
+/// use pre::pre;
+///
+/// pre::shadow! {
+///     mod buffer {
+///         pub(crate) struct Buffer(pub(crate) [u8; 4]);
+///
+///         impl Buffer {
+///             #[pre("`index` is less than 4")]
+///             pub(crate) unsafe fn get_unchecked(&self, index: usize) -> u8 {
+///                 *self.0.get_unchecked(index)
+///             }
+///         }
+///     }
+/// }
+///
+/// #[pre]
+/// fn main() {
+///     let buffer = buffer::Buffer([1, 2, 3, 4]);
+///
+///     // Not converted yet.
+///     assert_eq!(unsafe { buffer.get_unchecked(1) }, 2);
+///
+///     #[forward(impl pre_buffer::Buffer)]
+///     #[assure("`index` is less than 4", reason = "2 is less than 4")]
+///     let element = unsafe { buffer.get_unchecked(2) };
+///     assert_eq!(element, 3);
+/// }
+/// ```
+///
+/// # Limitations
+///
+/// Only functions and inherent impl blocks directly in the module are shadowed. Preconditions in
+/// nested modules and on methods of trait implementations are left unchanged.
+pub use pre_proc_macro::shadow;
+
 /// Provide preconditions for items in a different crate.
 ///
 /// This attribute can be used when a library has documented preconditions without using pre and
@@ -1074,6 +2829,156 @@ pub use pre_proc_macro::forward;
 /// Note the use of the [`forward` attribute](attr.forward.html) above. For more information about
 /// it and its use, you can read [its documentation](attr.forward.html).
 ///
+/// # Crate names
+///
+/// If the path in the attribute starts with the name of a crate, that crate must be listed as a
+/// dependency in the `Cargo.toml` of the package. Otherwise an error is reported at the
+/// attribute. `std`, `core`, `alloc`, `proc_macro` and `test` are always available. A crate that
+/// is declared under a different name with `extern crate <crate> as <name>` can be referred to as
+/// `crate::<name>` instead.
+///
+/// # Paths of `impl` blocks
+///
+/// The type of an `impl` block may be preceded by the path of the module containing it, so that
+/// the outline does not need a separate module for each level. This is the same as putting the
+/// `impl` block into these submodules, which are added to the outline if they are missing.
+///
+/// ```rust
+/// # use pre::pre;
+/// #[pre::extern_crate(core)]
+/// mod new_core {
+///     // The same as `mod sync { mod atomic { impl AtomicUsize { ... } } }`.
+///     impl sync::atomic::AtomicUsize {
+///         #[pre("the memory at `ptr` is only accessed atomically during the lifetime `'a`")]
+///         unsafe fn from_ptr<'a>(ptr: *mut usize) -> &'a AtomicUsize;
+///     }
+/// }
+/// ```
+///
+/// # Type aliases
+///
+/// The module may contain type aliases, which are available in the generated module as well. A
+/// type alias for the type of an `impl` block in the module can also be used in
+/// [`forward(impl ...)`](attr.forward.html#impl-call) attributes. This allows exposing names that
+/// stay the same even if the type is renamed in the original crate. Only one level of aliases is
+/// resolved and concrete generic arguments in the alias select the matching `impl` block.
+///
+/// ```rust
+/// # use pre::pre;
+/// #[pre::extern_crate(core)]
+/// mod new_core {
+///     type Ptr<T> = ptr::NonNull<T>;
+///
+///     impl<T> ptr::NonNull<T> {
+///         #[pre(!ptr.is_null())]
+///         const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T>;
+///     }
+/// }
+///
+/// #[pre]
+/// fn main() {
+///     let mut val = 42;
+///
+///     #[forward(impl new_core::Ptr)]
+///     #[assure(!ptr.is_null(), reason = "`ptr` is a reference")]
+///     let ptr = unsafe { core::ptr::NonNull::new_unchecked(&mut val) };
+/// #   assert_eq!(unsafe { *ptr.as_ptr() }, 42);
+/// }
+/// ```
+///
+/// # Trait implementations
+///
+/// An `impl` block may implement a trait, such as `impl alloc::GlobalAlloc for System`, to add
+/// preconditions to the methods of a foreign type's implementation of a trait with `unsafe`
+/// methods. The path in front of the trait is used as the module containing the `impl` block and
+/// the type is looked up in that module. Calls to the methods need a
+/// [`forward(impl <trait> for <type>)`](attr.forward.html#trait-implementations) attribute, since
+/// they are made through the trait. Extension traits are not generated for trait implementations.
+///
+/// ```rust
+/// # use pre::pre;
+/// mod a {
+///     pub(crate) mod io {
+///         pub(crate) trait Read {
+///             unsafe fn read_unchecked(&self, index: usize) -> u8;
+///         }
+///
+///         pub(crate) struct File(pub(crate) [u8; 4]);
+///
+///         impl Read for File {
+///             unsafe fn read_unchecked(&self, index: usize) -> u8 {
+///                 *self.0.get_unchecked(index)
+///             }
+///         }
+///     }
+/// }
+///
+/// #[pre::extern_crate(crate::a)]
+/// mod b {
+///     impl io::Read for File {
+///         #[pre("`index` is less than 4")]
+///         unsafe fn read_unchecked(&self, index: usize) -> u8;
+///     }
+/// }
+///
+/// #[pre]
+/// fn main() {
+///     use a::io::Read;
+///
+///     let file = a::io::File([1, 2, 3, 4]);
+///
+///     #[forward(impl b::io::Read for File)]
+///     #[assure("`index` is less than 4", reason = "2 is less than 4")]
+///     let value = unsafe { file.read_unchecked(2) };
+/// #   assert_eq!(value, 3);
+/// }
+/// ```
+///
+/// # Conditional outlines
+///
+/// A `cfg` predicate can be given after the path, such as `#[pre::extern_crate(std, cfg(test))]`.
+/// The generated module is then only included if the predicate holds. This avoids generating the
+/// outline in builds that don't use it, for example if pre is only used in tests as a
+/// dev-dependency.
+///
+/// # Condition groups
+///
+/// Some contracts depend on the features of the crate they belong to, for example if a feature
+/// relaxes a condition. A precondition of a function can be restricted to a condition group with
+/// `when(feature = "<name>")` or `when(not(feature = "<name>"))` before it:
+///
+/// ```rust,ignore
+/// #[pre::extern_crate(alloc_lib)]
+/// mod pre_alloc_lib {
+///     #[pre(valid_ptr(ptr, r))]
+///     #[pre(when(not(feature = "zeroed")), "`ptr` points to initialized memory")]
+///     unsafe fn read(ptr: *const u8) -> u8;
+/// }
+/// ```
+///
+/// The features of the other crate cannot be observed, so the features of the crate containing
+/// the outline select the condition set instead. It should therefore enable a feature of the same
+/// name exactly if it enables the feature of the other crate, such as with
+/// `zeroed = ["alloc_lib/zeroed"]` in its `Cargo.toml`.
+///
+/// All condition groups of a function must be keyed by the same feature, otherwise an error is
+/// reported. Condition groups are only supported for free functions, not for methods in `impl`
+/// blocks.
+///
+/// # Inlining
+///
+/// The generated wrapper functions are `#[inline(always)]` by default. This can be changed for the
+/// whole outline with an `inline` argument after the path, such as
+/// `#[pre::extern_crate(std, inline = "hint")]`:
+///
+/// - `"always"` generates `#[inline(always)]`, which is the default.
+/// - `"hint"` generates `#[inline]`, leaving the decision to the compiler.
+/// - `"never"` generates `#[inline(never)]`, which can help with debugging and profiling.
+/// - `"none"` generates no `inline` attribute.
+///
+/// An `inline` attribute on a function in the outline, such as `#[inline(never)]`, takes
+/// precedence over the mode of the outline for that function.
+///
 /// # Visibility
 ///
 /// Visibility modifiers on inner items of the module are ignored.
@@ -1082,6 +2987,61 @@ pub use pre_proc_macro::forward;
 /// visible.
 /// You can think of every item in the contained module having `pub` visibility (though in practice
 /// it's slightly more complicated).
+///
+/// # Qualifiers and attributes
+///
+/// The generated functions have exactly the signature given in the outline, so qualifiers such as
+/// `const` or `unsafe` need to be repeated there to be available. Attributes of the original
+/// function, such as `#[must_use]`, are not known to `pre` either. They can be added to the
+/// outline manually.
+///
+/// `impl Trait` may be used for arguments and return types, like
+/// `fn sort_by_key<T, K: Ord>(slice: &mut [T], f: impl FnMut(&T) -> K)`. The arguments are passed
+/// on to the original function without naming their types.
+///
+/// Adding `#[pre(must_use)]` to a module marks all functions that return a value within it and
+/// its inner modules as `#[must_use]`. This does not apply to methods in `impl` blocks, since
+/// their calls use the original methods directly.
+///
+/// ```rust
+/// # use pre::pre;
+/// #[pre::extern_crate(core)]
+/// #[pre(must_use)]
+/// mod new_core {
+///     mod mem {
+///         #[pre("`T` can be forgotten")]
+///         const fn forget<T>(t: T);
+///
+///         // This function is `#[must_use]`.
+///         #[pre("`dest` is a valid reference")]
+///         fn replace<T>(dest: &mut T, src: T) -> T;
+///     }
+/// }
+/// ```
+///
+/// # Extension traits
+///
+/// On the nightly compiler, an extension trait is generated for every `impl` block with methods
+/// that take `self`. Each method with preconditions is available in that trait with a `_pre`
+/// suffix, so it can be called with method call syntax without a
+/// [`forward` attribute](attr.forward.html#impl-call).
+///
+/// The extension traits of a module and all of its inner modules are brought into scope by
+/// importing `pre_ext::*` from it:
+///
+/// ```rust,ignore
+/// use new_core::pre_ext::*;
+///
+/// #[assure(
+///     "the contained value is an initialized, valid value of `T`",
+///     reason = "the value `true` was just written to `b`"
+/// )]
+/// let val = unsafe { b.assume_init_pre() };
+/// ```
+///
+/// The `const_pointer` and `mut_pointer` pseudo types in the outline refer to `*const T` and
+/// `*mut T` respectively. `async` methods and methods returning `impl Trait` are not available in
+/// extension traits.
 pub use pre_proc_macro::extern_crate;
 
 // Doctests don't work with this extern_crate, because there is a collision between it and `use
@@ -1116,12 +3076,167 @@ pub use libs::alloc;
 #[cfg(feature = "std")]
 pub use libs::std;
 
+#[cfg(feature = "std")]
+pub mod metrics;
+
+#[cfg(feature = "std")]
+pub mod wrappers;
+
+pub mod encoding;
+
+pub mod prelude;
+
+#[cfg(feature = "violation-handler")]
+mod violation;
+
+#[cfg(feature = "violation-handler")]
+pub use violation::{
+    report_violation, set_violation_handler, take_violation_handler, ContractViolation,
+};
+
+// *WARNING* This is not considered to be part of the public API and may change at any time without
+// notice.
+
+/// The `log` crate used to log violated preconditions with the `log-violations` feature.
+#[doc(hidden)]
+#[cfg(feature = "log-violations")]
+pub use log as __log;
+
+/// The `thread_local` macro used to check `not_reentrant` preconditions with the
+/// `reentrancy-checks` feature.
+#[doc(hidden)]
+#[cfg(feature = "reentrancy-checks")]
+pub use std::thread_local as __thread_local;
+
+/// Marks a function with a `not_reentrant` precondition as entered on the current thread.
+///
+/// This is used to check `not_reentrant` preconditions with the `reentrancy-checks` feature. The
+/// function is marked as returned again, when the guard is dropped.
+#[doc(hidden)]
+#[cfg(feature = "reentrancy-checks")]
+pub struct __ReentrancyGuard {
+    /// Whether the function is currently entered on this thread.
+    entered: &'static std::thread::LocalKey<core::cell::Cell<bool>>,
+}
+
+#[cfg(feature = "reentrancy-checks")]
+impl __ReentrancyGuard {
+    /// Marks the function as entered, panicking if it already is.
+    pub fn enter(
+        entered: &'static std::thread::LocalKey<core::cell::Cell<bool>>,
+        function: &str,
+    ) -> Self {
+        if entered.with(|entered| entered.replace(true)) {
+            panic!(
+                "`not_reentrant` precondition was wrongly assured: `{}` was called again before it returned",
+                function
+            );
+        }
+
+        __ReentrancyGuard { entered }
+    }
+}
+
+#[cfg(feature = "reentrancy-checks")]
+impl Drop for __ReentrancyGuard {
+    fn drop(&mut self) {
+        self.entered.with(|entered| entered.set(false));
+    }
+}
+
+/// Stands in for the precondition argument of a call whose mismatched preconditions were already
+/// reported by a summary error.
+///
+/// Such a call never compiles, so this is never executed.
+#[doc(hidden)]
+pub fn __mismatched_preconditions<T>() -> T {
+    unreachable!("calls with mismatched preconditions are rejected at compile time")
+}
+
+/// A type whose values prove that a precondition holds.
+///
+/// Types implementing this trait can be required using a [`token`
+/// precondition](attr.pre.html#precondition-syntax).
+/// A function with such a precondition can only be called by passing a value of the token type,
+/// so the precondition is enforced by the type system instead of relying on a `reason`.
+///
+/// By convention token types are zero-sized and can only be constructed by the code that
+/// establishes the precondition, for example by an initialization function.
+///
+/// References to tokens are tokens as well, so a token can also be borrowed instead of consumed.
+///
+/// On Rust 1.78 and newer, using a type that does not implement this trait in a `token`
+/// precondition reports a dedicated error pointing to these docs.
+#[cfg_attr(
+    pre_diagnostic_namespace,
+    diagnostic::on_unimplemented(
+        message = "`{Self}` cannot be used in a `token` precondition, because it is not a token type",
+        label = "not a token type",
+        note = "implement `pre::Token` for `{Self}`, if its values prove that the precondition holds",
+        note = "for more information, see https://docs.rs/pre/latest/pre/trait.Token.html"
+    )
+)]
+pub trait Token {}
+
+impl<T: Token + ?Sized> Token for &T {}
+
+impl<T: Token + ?Sized> Token for &mut T {}
+
+/// A condition exported by a function using `#[pre(export = <name>, "<string>")]`.
+///
+/// The condition can be referred to by the path to the constant using `use <path>` in a
+/// [`pre`](attr.pre.html#precondition-syntax) or [`assure`](attr.assure.html) attribute.
+/// The path also names a hidden marker type of the same name, which identifies the condition, so
+/// only constants generated by `export` can be referred to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConditionDef {
+    /// The name of the constant defining the condition.
+    pub name: &'static str,
+    /// The text describing the condition.
+    pub text: &'static str,
+}
+
+/// Checks whether a slice of `len` elements of type `T` is at most `isize::MAX` bytes large.
+///
+/// This is part of the [`valid_slice` precondition](attr.pre.html#precondition-syntax), which
+/// checks it in a `debug_assert` like other boolean preconditions.
+/// The pointer is only used to infer the type of the elements.
+///
+/// # Example
+///
+/// ```rust
+/// let data = [0u32; 4];
+///
+/// assert!(pre::slice_len_fits(data.as_ptr(), data.len()));
+/// assert!(!pre::slice_len_fits(data.as_ptr(), usize::MAX));
+/// assert!(pre::slice_len_fits(&() as *const (), usize::MAX));
+/// ```
+pub const fn slice_len_fits<T>(_data: *const T, len: usize) -> bool {
+    let size = core::mem::size_of::<T>();
+
+    size == 0 || len <= isize::MAX as usize / size
+}
+
+// *WARNING* These types are not considered to be part of the public API and may change at any
+// time without notice.
+
+/// A token passed to a function with a `token` precondition.
+#[doc(hidden)]
+pub struct TokenCondition<T: Token>(pub T);
+
+/// A condition exported by a function, identified by the marker type `C` generated next to its
+/// [`ConditionDef`](struct.ConditionDef.html) constant.
+#[doc(hidden)]
+pub struct NamedCondition<C>(pub C);
+
 cfg_if::cfg_if! {
     if #[cfg(nightly)] {
         // *WARNING* These types are not considered to be part of the public API and may change at
         // any time without notice.
 
         /// A condition that the pointer of name `PTR` is valid for `ACCESS_TYPE` accesses.
+        ///
+        /// `ACCESS_TYPE` is followed by `, ` and the number of elements, if one is given.
         #[doc(hidden)]
         pub struct ValidPtrCondition<const PTR: &'static str, const ACCESS_TYPE: &'static str>;
 
@@ -1129,6 +3244,81 @@ cfg_if::cfg_if! {
         #[doc(hidden)]
         pub struct ProperAlignCondition<const PTR: &'static str>;
 
+        /// A condition that the pointer of name `PTR` is aligned to `ALIGN` bytes.
+        #[doc(hidden)]
+        pub struct ProperAlignValCondition<const PTR: &'static str, const ALIGN: &'static str>;
+
+        /// A condition that the pointer of name `PTR` is aligned to `ALIGN` bytes, given by an
+        /// `aligned_to` precondition.
+        #[doc(hidden)]
+        pub struct AlignedToCondition<const PTR: &'static str, const ALIGN: &'static str>;
+
+        /// A condition that the pointer of name `PTR` is not null.
+        #[doc(hidden)]
+        pub struct NonNullCondition<const PTR: &'static str>;
+
+        /// A condition that the pointer of name `PTR` points to a nul-terminated string.
+        #[doc(hidden)]
+        pub struct NulTerminatedCondition<const PTR: &'static str>;
+
+        /// A condition that the pointer of name `PTR` points to a valid C string.
+        #[doc(hidden)]
+        pub struct ValidCStrCondition<const PTR: &'static str>;
+
+        /// A condition that interrupts are disabled for the duration of the call.
+        #[doc(hidden)]
+        pub struct InterruptsDisabledCondition;
+
+        /// A condition that the function is not called again before the call returns.
+        #[doc(hidden)]
+        pub struct NotReentrantCondition;
+
+        /// A condition that no DMA engine accesses the buffer of name `BUF` for the duration of
+        /// the call.
+        #[doc(hidden)]
+        pub struct DmaQuiescentCondition<const BUF: &'static str>;
+
+        /// A condition that the storage referenced by the argument of name `ARG` lives until the
+        /// end of the scope that the result of the call is used in.
+        #[doc(hidden)]
+        pub struct LivesUntilEndOfScopeCondition<const ARG: &'static str>;
+
+        /// A condition that the value of name `VALUE` is within `RANGE`.
+        #[doc(hidden)]
+        pub struct InRangeCondition<const VALUE: &'static str, const RANGE: &'static str>;
+
+        /// A condition that the value of name `VALUE` is a valid value of type `TYPE`.
+        #[doc(hidden)]
+        pub struct ValidValueCondition<const VALUE: &'static str, const TYPE: &'static str>;
+
+        /// A condition that a value of type `TO` can be read from the memory of a value of type
+        /// `FROM`.
+        #[doc(hidden)]
+        pub struct LayoutCompatibleCondition<const FROM: &'static str, const TO: &'static str>;
+
+        /// A condition that the pointer of name `PTR` points to `COUNT` initialized values.
+        ///
+        /// `COUNT` is empty, if the pointer points to a single initialized value.
+        #[doc(hidden)]
+        pub struct InitializedCondition<const PTR: &'static str, const COUNT: &'static str>;
+
+        /// A condition that the `COUNT` elements behind the pointers of names `FIRST` and `SECOND`
+        /// do not overlap.
+        #[doc(hidden)]
+        pub struct NonOverlappingCondition<
+            const FIRST: &'static str,
+            const SECOND: &'static str,
+            const COUNT: &'static str,
+        >;
+
+        /// A condition that the ownership of the value of name `VALUE` is transferred.
+        #[doc(hidden)]
+        pub struct ConsumedCondition<const VALUE: &'static str>;
+
+        /// A condition that the bytes of the value of name `BYTES` are valid UTF-8.
+        #[doc(hidden)]
+        pub struct ValidUtf8Condition<const BYTES: &'static str>;
+
         /// A boolean condition.
         #[doc(hidden)]
         pub struct BooleanCondition<const CONDITION: &'static str>;
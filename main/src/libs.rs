@@ -15,6 +15,11 @@ macro_rules! define_libs {
             $($alloc_item:item)*
         }
 
+        // For modules which are different in `alloc` than in `std` (such as `ffi`)
+        alloc_only {
+            $($alloc_only_item:item)*
+        }
+
         $std_name:ident {
             $($std_item:item)*
         }
@@ -72,15 +77,31 @@ macro_rules! define_libs {
         /// As a workaround, you can add the preconditions locally in your own crate using the
         /// [`extern_crate` attribute](../attr.extern_crate.html).
         ///
-        /// # Why is it named `alloc_lib` in the documentation?
+        /// # Allocator contracts
+        ///
+        /// The functions in `alloc::alloc` share a common set of preconditions, which can be reused
+        /// with the exact same wording for custom allocation functions:
+        ///
+        /// - Non-zero size: `layout.size() != 0` (or `new_size != 0` for `realloc`)
+        /// - Layout validity: `align.is_power_of_two()` and
+        ///   ``"`size`, when rounded up to the nearest multiple of `align`, does not overflow an `isize`"``
+        /// - Fit of the allocation: ``"`ptr` is currently allocated via the global allocator"`` and
+        ///   ``"`layout` is the same layout that was used to allocate `ptr`"``
+        ///
+        /// The boolean preconditions of `alloc`, `alloc_zeroed` and `realloc` are checked by a
+        /// `debug_assert`, so a zero-sized allocation is caught in debug builds.
+        ///
+        /// # Why is it named `crate::alloc_lib` in the documentation?
         ///
         /// If it were simply named `alloc` there would be a naming conflict with this module, so
-        /// either of them had to have a different name.
+        /// either of them had to have a different name. The `alloc` crate is declared as
+        /// `alloc_lib` at the root of this crate.
         #[cfg(feature = "alloc")]
-        #[pre::extern_crate(alloc_lib)]
+        #[pre::extern_crate(crate::alloc_lib)]
         #[pre::pre(no_doc)]
         pub mod $alloc_name {
             $($alloc_item)*
+            $($alloc_only_item)*
         }
 
         /// Precondition definitions for `unsafe` functions in the [`std` library](https://doc.rust-lang.org/std/index.html).
@@ -126,6 +147,59 @@ macro_rules! define_libs {
 
 define_libs! {
     core {
+        #[cfg(target_arch = "x86_64")]
+        mod arch {
+            mod x86_64 {
+                #[pre(valid_ptr(mem_addr, r, 4))]
+                #[pre(proper_align_val(mem_addr, 16))]
+                unsafe fn _mm_load_ps(mem_addr: *const f32) -> __m128;
+
+                #[pre(valid_ptr(mem_addr, r, 2))]
+                #[pre(proper_align_val(mem_addr, 16))]
+                unsafe fn _mm_load_pd(mem_addr: *const f64) -> __m128d;
+
+                #[pre(valid_ptr(mem_addr, w, 4))]
+                #[pre(proper_align_val(mem_addr, 16))]
+                unsafe fn _mm_store_ps(mem_addr: *mut f32, a: __m128);
+
+                #[pre(valid_ptr(mem_addr, w, 2))]
+                #[pre(proper_align_val(mem_addr, 16))]
+                unsafe fn _mm_store_pd(mem_addr: *mut f64, a: __m128d);
+
+                #[pre(valid_ptr(mem_addr, r, 8))]
+                #[pre(proper_align_val(mem_addr, 32))]
+                #[pre("the `avx` target feature is available")]
+                unsafe fn _mm256_load_ps(mem_addr: *const f32) -> __m256;
+
+                #[pre(valid_ptr(mem_addr, r, 4))]
+                #[pre(proper_align_val(mem_addr, 32))]
+                #[pre("the `avx` target feature is available")]
+                unsafe fn _mm256_load_pd(mem_addr: *const f64) -> __m256d;
+
+                #[pre(valid_ptr(mem_addr, w, 8))]
+                #[pre(proper_align_val(mem_addr, 32))]
+                #[pre("the `avx` target feature is available")]
+                unsafe fn _mm256_store_ps(mem_addr: *mut f32, a: __m256);
+
+                #[pre(valid_ptr(mem_addr, w, 4))]
+                #[pre(proper_align_val(mem_addr, 32))]
+                #[pre("the `avx` target feature is available")]
+                unsafe fn _mm256_store_pd(mem_addr: *mut f64, a: __m256d);
+            }
+        }
+
+        mod cell {
+            impl<T: ?Sized> RefCell<T> {
+                #[pre("the `RefCell` is not mutably borrowed while the returned reference is alive")]
+                unsafe fn try_borrow_unguarded(&self) -> Result<&T, BorrowError>;
+            }
+        }
+
+        mod char {
+            #[pre(valid_value(i, char))]
+            unsafe fn from_u32_unchecked(i: u32) -> char;
+        }
+
         mod mem {
             impl<T> ManuallyDrop<T> {
                 #[pre("this `ManuallyDrop` is not used again after this call")]
@@ -137,14 +211,15 @@ define_libs! {
                 unsafe fn drop(slot: &mut ManuallyDrop<T>);
             }
 
-            #[pre("I have read and understood https://doc.rust-lang.org/nightly/nomicon/transmutes.html")]
+            #[pre(valid_value(src, U))]
+            #[pre("`src` is valid for reads of `size_of::<U>()` bytes")]
             unsafe fn transmute_copy<T, U>(src: &T) -> U;
 
             #[pre("an all-zero byte-pattern is a valid value of `T`")]
             unsafe fn zeroed<T>() -> T;
 
             impl<T> MaybeUninit<T> {
-                #[pre("the `MaybeUninit` contains a fully initialized, valid value of `T`")]
+                #[pre(valid_value(self, T))]
                 unsafe fn assume_init(self) -> T;
             }
         }
@@ -153,7 +228,7 @@ define_libs! {
             #[pre("the starting and the resulting pointer are in bounds of the same allocated object")]
             #[pre("the computed offset, in bytes, does not overflow an `isize`")]
             #[pre("performing the addition does not result in overflow")]
-            unsafe fn add(self, count: usize) -> *const T;
+            unsafe fn add(self, count: usize) -> *const T where T: Sized;
 
             #[pre(proper_align(self))]
             #[pre("`self` is null or `self` is a valid for reads")]
@@ -161,58 +236,54 @@ define_libs! {
             #[pre("the memory referenced by the returned reference is not mutated by any pointer for the duration of `'a`, except inside a contained `UnsafeCell`")]
             unsafe fn as_ref<'a>(self) -> Option<&'a T>;
 
-            #[pre(valid_ptr(self, r))]
-            #[pre(valid_ptr(dest, w))]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`dest` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(self, r, count))]
+            #[pre(valid_ptr(dest, w, count))]
             #[pre(proper_align(self))]
             #[pre(proper_align(dest))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
-            unsafe fn copy_to(self, dest: *mut T, count: usize);
+            unsafe fn copy_to(self, dest: *mut T, count: usize) where T: Sized;
 
-            #[pre(valid_ptr(self, r))]
-            #[pre(valid_ptr(dest, w))]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`dest` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(self, r, count))]
+            #[pre(valid_ptr(dest, w, count))]
             #[pre(proper_align(self))]
             #[pre(proper_align(dest))]
-            #[pre("the memory regions of size `count * size_of::<T>` pointed to by `self` and `dest` do not overlap")]
+            #[pre(non_overlapping(self, dest, count))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
-            unsafe fn copy_to_nonoverlapping(self, dest: *mut T, count: usize);
+            unsafe fn copy_to_nonoverlapping(self, dest: *mut T, count: usize) where T: Sized;
 
             #[pre("the starting and the resulting pointer are in bounds of the same allocated object")]
             #[pre("the computed offset, in bytes, does not overflow an `isize`")]
             #[pre("performing the offset does not result in overflow")]
-            unsafe fn offset(self, count: isize) -> *const T;
+            unsafe fn offset(self, count: isize) -> *const T where T: Sized;
 
             #[pre(valid_ptr(self, r))]
             #[pre(proper_align(self))]
             #[pre("`self` points to a properly initialized value of type `T`")]
             #[pre("`T` is `Copy` or the value at `*self` isn't used after this call")]
-            unsafe fn read(self) -> T;
+            unsafe fn read(self) -> T where T: Sized;
 
             #[pre(valid_ptr(self, r))]
             #[pre("`self` points to a properly initialized value of type `T`")]
             #[pre("`T` is `Copy` or the value at `*self` isn't used after this call")]
-            unsafe fn read_unaligned(self) -> T;
+            unsafe fn read_unaligned(self) -> T where T: Sized;
 
             #[pre(valid_ptr(self, r))]
             #[pre(proper_align(self))]
             #[pre("`self` points to a properly initialized value of type `T`")]
             #[pre("`T` is `Copy` or the value at `*self` isn't used after this call")]
-            unsafe fn read_volatile(self) -> T;
+            unsafe fn read_volatile(self) -> T where T: Sized;
 
             #[pre("the starting and the resulting pointer are in bounds of the same allocated object")]
             #[pre("the computed offset, in bytes, does not overflow an `isize`")]
             #[pre("performing the subtraction does not result in overflow")]
-            unsafe fn sub(self, count: usize) -> *const T;
+            unsafe fn sub(self, count: usize) -> *const T where T: Sized;
         }
 
         impl<T> mut_pointer<T> where T: ?Sized {
             #[pre("the starting and the resulting pointer are in bounds of the same allocated object")]
             #[pre("the computed offset, in bytes, does not overflow an `isize`")]
             #[pre("performing the addition does not result in overflow")]
-            unsafe fn add(self, count: usize) -> *mut T;
+            unsafe fn add(self, count: usize) -> *mut T where T: Sized;
 
             #[pre(proper_align(self))]
             #[pre("`self` is null or `self` is a valid for both reads and writes")]
@@ -226,43 +297,35 @@ define_libs! {
             #[pre("the memory referenced by the returned reference is not mutated by any pointer for the duration of `'a`, except inside a contained `UnsafeCell`")]
             unsafe fn as_ref<'a>(self) -> Option<&'a T>;
 
-            #[pre(valid_ptr(src, r))]
-            #[pre(valid_ptr(self, w))]
-            #[pre("`src` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(src, r, count))]
+            #[pre(valid_ptr(self, w, count))]
             #[pre(proper_align(src))]
             #[pre(proper_align(self))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
-            unsafe fn copy_from(self, src: *const T, count: usize);
+            unsafe fn copy_from(self, src: *const T, count: usize) where T: Sized;
 
-            #[pre(valid_ptr(src, r))]
-            #[pre(valid_ptr(self, w))]
-            #[pre("`src` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(src, r, count))]
+            #[pre(valid_ptr(self, w, count))]
             #[pre(proper_align(src))]
             #[pre(proper_align(self))]
-            #[pre("the memory regions of size `count * size_of::<T>` pointed to by `src` and `self` do not overlap")]
+            #[pre(non_overlapping(src, self, count))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
-            unsafe fn copy_from_nonoverlapping(self, src: *const T, count: usize);
+            unsafe fn copy_from_nonoverlapping(self, src: *const T, count: usize) where T: Sized;
 
-            #[pre(valid_ptr(self, r))]
-            #[pre(valid_ptr(dest, w))]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`dest` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(self, r, count))]
+            #[pre(valid_ptr(dest, w, count))]
             #[pre(proper_align(self))]
             #[pre(proper_align(dest))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
-            unsafe fn copy_to(self, dest: *mut T, count: usize);
+            unsafe fn copy_to(self, dest: *mut T, count: usize) where T: Sized;
 
-            #[pre(valid_ptr(self, r))]
-            #[pre(valid_ptr(dest, w))]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`dest` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(self, r, count))]
+            #[pre(valid_ptr(dest, w, count))]
             #[pre(proper_align(self))]
             #[pre(proper_align(dest))]
-            #[pre("the memory regions of size `count * size_of::<T>` pointed to by `self` and `dest` do not overlap")]
+            #[pre(non_overlapping(self, dest, count))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
-            unsafe fn copy_to_nonoverlapping(self, dest: *mut T, count: usize);
+            unsafe fn copy_to_nonoverlapping(self, dest: *mut T, count: usize) where T: Sized;
 
             #[pre(valid_ptr(self, r+w))]
             #[pre(proper_align(self))]
@@ -273,57 +336,56 @@ define_libs! {
             #[pre("the starting and the resulting pointer are in bounds of the same allocated object")]
             #[pre("the computed offset, in bytes, does not overflow an `isize`")]
             #[pre("performing the offset does not result in overflow")]
-            unsafe fn offset(self, count: isize) -> *const T;
+            unsafe fn offset(self, count: isize) -> *mut T where T: Sized;
 
             #[pre(valid_ptr(self, r))]
             #[pre(proper_align(self))]
             #[pre("`self` points to a properly initialized value of type `T`")]
             #[pre("`T` is `Copy` or the value at `*self` isn't used after this call")]
-            unsafe fn read(self) -> T;
+            unsafe fn read(self) -> T where T: Sized;
 
             #[pre(valid_ptr(self, r))]
             #[pre("`self` points to a properly initialized value of type `T`")]
             #[pre("`T` is `Copy` or the value at `*self` isn't used after this call")]
-            unsafe fn read_unaligned(self) -> T;
+            unsafe fn read_unaligned(self) -> T where T: Sized;
 
             #[pre(valid_ptr(self, r))]
             #[pre(proper_align(self))]
             #[pre("`self` points to a properly initialized value of type `T`")]
             #[pre("`T` is `Copy` or the value at `*self` isn't used after this call")]
-            unsafe fn read_volatile(self) -> T;
+            unsafe fn read_volatile(self) -> T where T: Sized;
 
             #[pre(valid_ptr(self, r+w))]
             #[pre(proper_align(self))]
             #[pre("`self` points to a properly initialized value of type `T`")]
-            unsafe fn replace(self, src: T) -> T;
+            unsafe fn replace(self, src: T) -> T where T: Sized;
 
             #[pre("the starting and the resulting pointer are in bounds of the same allocated object")]
             #[pre("the computed offset, in bytes, does not overflow an `isize`")]
             #[pre("performing the subtraction does not result in overflow")]
-            unsafe fn sub(self, count: usize) -> *const T;
+            unsafe fn sub(self, count: usize) -> *mut T where T: Sized;
 
             #[pre(valid_ptr(self, r+w))]
             #[pre(valid_ptr(with, r+w))]
             #[pre(proper_align(self))]
             #[pre(proper_align(with))]
-            unsafe fn swap(self, with: *mut T);
+            unsafe fn swap(self, with: *mut T) where T: Sized;
 
             #[pre(valid_ptr(self, w))]
             #[pre(proper_align(self))]
-            unsafe fn write(self, val: T);
+            unsafe fn write(self, val: T) where T: Sized;
 
-            #[pre(valid_ptr(self, w))]
-            #[pre("`self` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(self, w, count))]
             #[pre(proper_align(self))]
             #[pre("a valid value of `T` is written to `*self` or `*self` is never used")]
-            unsafe fn write_bytes(self, val: u8, count: usize);
+            unsafe fn write_bytes(self, val: u8, count: usize) where T: Sized;
 
             #[pre(valid_ptr(self, w))]
-            unsafe fn write_unaligned(self, val: T);
+            unsafe fn write_unaligned(self, val: T) where T: Sized;
 
             #[pre(valid_ptr(self, w))]
             #[pre(proper_align(self))]
-            unsafe fn write_volatile(self, val: T);
+            unsafe fn write_volatile(self, val: T) where T: Sized;
         }
 
         mod ptr {
@@ -332,22 +394,18 @@ define_libs! {
                 const unsafe fn new_unchecked(ptr: *mut T) -> Self;
             }
 
-            #[pre(valid_ptr(src, r))]
-            #[pre(valid_ptr(dst, w))]
-            #[pre("`src` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`dst` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(src, r, count))]
+            #[pre(valid_ptr(dst, w, count))]
             #[pre(proper_align(src))]
             #[pre(proper_align(dst))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
             unsafe fn copy<T>(src: *const T, dst: *mut T, count: usize);
 
-            #[pre(valid_ptr(src, r))]
-            #[pre(valid_ptr(dst, w))]
-            #[pre("`src` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`dst` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(src, r, count))]
+            #[pre(valid_ptr(dst, w, count))]
             #[pre(proper_align(src))]
             #[pre(proper_align(dst))]
-            #[pre("the memory regions of size `count * size_of::<T>` pointed to by `src` and `dst` do not overlap")]
+            #[pre(non_overlapping(src, dst, count))]
             #[pre("`T` is `Copy` or only the values in one of the regions are used after this call")]
             unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize);
 
@@ -385,21 +443,18 @@ define_libs! {
             #[pre(proper_align(y))]
             unsafe fn swap<T>(x: *mut T, y: *mut T);
 
-            #[pre(valid_ptr(x, r+w))]
-            #[pre(valid_ptr(y, r+w))]
-            #[pre("`x` is valid for `count * size_of::<T>()` bytes")]
-            #[pre("`y` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(x, r+w, count))]
+            #[pre(valid_ptr(y, r+w, count))]
             #[pre(proper_align(x))]
             #[pre(proper_align(y))]
-            #[pre("the memory regions of size `count * size_of::<T>` pointed to by `x` and `y` do not overlap")]
+            #[pre(non_overlapping(x, y, count))]
             unsafe fn swap_nonoverlapping<T>(x: *mut T, y: *mut T, count: usize);
 
             #[pre(valid_ptr(dst, w))]
             #[pre(proper_align(dst))]
             unsafe fn write<T>(dst: *mut T, src: T);
 
-            #[pre(valid_ptr(dst, w))]
-            #[pre("`dst` is valid for `count * size_of::<T>()` bytes")]
+            #[pre(valid_ptr(dst, w, count))]
             #[pre(proper_align(dst))]
             #[pre("a valid value of `T` is written to `*dst` or `*dst` is never used")]
             unsafe fn write_bytes<T>(dst: *mut T, val: u8, count: usize);
@@ -413,41 +468,153 @@ define_libs! {
         }
 
         mod slice {
-            #[pre(valid_ptr(data, r))]
-            #[pre(proper_align(data))]
-            #[pre("the allocated object at `data` is valid for `len * mem::size_of::<T>()` bytes")]
+            #[pre(valid_slice(data, len, r))]
             #[pre("the memory referenced by the returned slice is not mutated by any pointer for the duration of `'a`, except inside a contained `UnsafeCell`")]
-            #[pre(len * ::core::mem::size_of::<T>() <= isize::MAX as usize)]
             unsafe fn from_raw_parts<'a, T>(data: *const T, len: usize) -> &'a [T];
 
-            #[pre(valid_ptr(data, r+w))]
-            #[pre(proper_align(data))]
-            #[pre("the allocated object at `data` is valid for `len * mem::size_of::<T>()` bytes")]
+            #[pre(valid_slice(data, len, r+w))]
             #[pre("the memory referenced by the returned slice is not accessed by any pointer other than the returned slice for the duration of `'a`")]
-            #[pre(len * ::core::mem::size_of::<T>() <= isize::MAX as usize)]
             unsafe fn from_raw_parts_mut<'a, T>(data: *mut T, len: usize) -> &'a mut [T];
         }
+
+        mod sync {
+            mod atomic {
+                impl AtomicBool {
+                    #[pre(valid_ptr(ptr, r+w))]
+                    #[pre("`ptr` is valid for reads and writes for the whole lifetime `'a`")]
+                    #[pre("the memory at `ptr` is only accessed atomically during the lifetime `'a`")]
+                    #[pre("the memory at `ptr` is not accessed by atomic operations of a different size during the lifetime `'a`")]
+                    const unsafe fn from_ptr<'a>(ptr: *mut bool) -> &'a AtomicBool;
+                }
+
+                impl AtomicIsize {
+                    #[pre(valid_ptr(ptr, r+w))]
+                    #[pre(proper_align_val(ptr, ::core::mem::align_of::<AtomicIsize>()))]
+                    #[pre("`ptr` is valid for reads and writes for the whole lifetime `'a`")]
+                    #[pre("the memory at `ptr` is only accessed atomically during the lifetime `'a`")]
+                    #[pre("the memory at `ptr` is not accessed by atomic operations of a different size during the lifetime `'a`")]
+                    const unsafe fn from_ptr<'a>(ptr: *mut isize) -> &'a AtomicIsize;
+                }
+
+                impl AtomicUsize {
+                    #[pre(valid_ptr(ptr, r+w))]
+                    #[pre(proper_align_val(ptr, ::core::mem::align_of::<AtomicUsize>()))]
+                    #[pre("`ptr` is valid for reads and writes for the whole lifetime `'a`")]
+                    #[pre("the memory at `ptr` is only accessed atomically during the lifetime `'a`")]
+                    #[pre("the memory at `ptr` is not accessed by atomic operations of a different size during the lifetime `'a`")]
+                    const unsafe fn from_ptr<'a>(ptr: *mut usize) -> &'a AtomicUsize;
+                }
+
+                impl<T> AtomicPtr<T> {
+                    #[pre(valid_ptr(ptr, r+w))]
+                    #[pre(proper_align_val(ptr, ::core::mem::align_of::<AtomicPtr<T>>()))]
+                    #[pre("`ptr` is valid for reads and writes for the whole lifetime `'a`")]
+                    #[pre("the memory at `ptr` is only accessed atomically during the lifetime `'a`")]
+                    #[pre("the memory at `ptr` is not accessed by atomic operations of a different size during the lifetime `'a`")]
+                    const unsafe fn from_ptr<'a>(ptr: *mut *mut T) -> &'a AtomicPtr<T>;
+                }
+            }
+        }
+
+        // `RawWaker` and `RawWakerVTable` are the only bundled APIs that take callbacks. They
+        // have no `not_reentrant` preconditions, because the callbacks are not expected to call
+        // these constructors and the `RawWaker` contract allows calling the vtable functions again
+        // while they run.
+        mod task {
+            impl RawWaker {
+                #[pre("the functions in `vtable` uphold the `RawWaker` contract when called with `data`")]
+                #[pre("the functions in `vtable` are safe to call with `data` from any thread")]
+                const fn new(data: *const (), vtable: &'static RawWakerVTable) -> RawWaker;
+            }
+
+            impl RawWakerVTable {
+                #[pre("`clone` returns a `RawWaker` that wakes up the same task as the given one and retains all resources required for that")]
+                #[pre("`wake` and `wake_by_ref` wake up the task associated with the given data pointer")]
+                #[pre("`wake` and `drop` release all resources associated with the given data pointer")]
+                #[pre("`clone`, `wake`, `wake_by_ref` and `drop` are safe to call from any thread")]
+                const fn new(
+                    clone: unsafe fn(*const ()) -> RawWaker,
+                    wake: unsafe fn(*const ()),
+                    wake_by_ref: unsafe fn(*const ()),
+                    drop: unsafe fn(*const ()),
+                ) -> RawWakerVTable;
+            }
+
+            impl Waker {
+                #[pre("the functions in the vtable of `waker` uphold the `RawWaker` contract when called with its data pointer")]
+                #[pre("the functions in the vtable of `waker` are safe to call with its data pointer from any thread")]
+                unsafe fn from_raw(waker: RawWaker) -> Waker;
+            }
+        }
     }
 
     core_only {
+        mod alloc {
+            impl Layout {
+                #[pre(align.is_power_of_two())]
+                #[pre("`size`, when rounded up to the nearest multiple of `align`, does not overflow an `isize`")]
+                const unsafe fn from_size_align_unchecked(size: usize, align: usize) -> Layout;
+            }
+        }
+
+        mod ffi {
+            impl CStr {
+                #[pre(valid_cstr(ptr))]
+                #[pre("the memory referenced by the returned `CStr` is not mutated for the duration of `'a`")]
+                unsafe fn from_ptr<'a>(ptr: *const c_char) -> &'a CStr;
+            }
+        }
+
         mod str {
-            #[pre("the content of `v` is valid UTF-8")]
+            #[pre(valid_utf8(v))]
             unsafe fn from_utf8_unchecked(v: &[u8]) -> &str;
 
-            #[pre("the content of `v` is valid UTF-8")]
+            #[pre(valid_utf8(v))]
             unsafe fn from_utf8_unchecked_mut(v: &mut [u8]) -> &mut str;
         }
     }
 
     alloc {
+        mod boxed {
+            impl<T: ?Sized> Box<T> {
+                #[pre(consumed(raw))]
+                #[pre("`raw` was returned by `Box::into_raw`")]
+                unsafe fn from_raw(raw: *mut T) -> Box<T>;
+            }
+        }
+
+        mod alloc {
+            impl Layout {
+                #[pre(align.is_power_of_two())]
+                #[pre("`size`, when rounded up to the nearest multiple of `align`, does not overflow an `isize`")]
+                const unsafe fn from_size_align_unchecked(size: usize, align: usize) -> Layout;
+            }
+
+            #[pre(layout.size() != 0)]
+            unsafe fn alloc(layout: Layout) -> *mut u8;
+
+            #[pre(layout.size() != 0)]
+            unsafe fn alloc_zeroed(layout: Layout) -> *mut u8;
+
+            #[pre("`ptr` is currently allocated via the global allocator")]
+            #[pre("`layout` is the same layout that was used to allocate `ptr`")]
+            unsafe fn dealloc(ptr: *mut u8, layout: Layout);
+
+            #[pre("`ptr` is currently allocated via the global allocator")]
+            #[pre("`layout` is the same layout that was used to allocate `ptr`")]
+            #[pre(new_size != 0)]
+            #[pre("`new_size`, when rounded up to the nearest multiple of `layout.align()`, does not overflow an `isize`")]
+            unsafe fn realloc(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8;
+        }
+
         mod str {
-            #[pre("the content of `v` is valid UTF-8")]
+            #[pre(valid_utf8(v))]
             unsafe fn from_boxed_utf8_unchecked(v: Box<[u8]>) -> Box<str>;
 
-            #[pre("the content of `v` is valid UTF-8")]
+            #[pre(valid_utf8(v))]
             unsafe fn from_utf8_unchecked(v: &[u8]) -> &str;
 
-            #[pre("the content of `v` is valid UTF-8")]
+            #[pre(valid_utf8(v))]
             unsafe fn from_utf8_unchecked_mut(v: &mut [u8]) -> &mut str;
         }
 
@@ -459,11 +626,11 @@ define_libs! {
                 #[pre("the memory at `buf` was allocated with the standard library allocator with an alignment of exactly 1")]
                 #[pre(length <= capacity)]
                 #[pre("`capacity` is the capacity that `buf` was allocated with")]
-                #[pre("`buf` is not used after this call")]
+                #[pre(consumed(buf))]
                 #[pre("the first `length` bytes at `buf` are valid UTF-8")]
                 unsafe fn from_raw_parts(buf: *mut u8, length: usize, capacity: usize) -> String;
 
-                #[pre("the content of `bytes` is valid UTF-8")]
+                #[pre(valid_utf8(bytes))]
                 unsafe fn from_utf8_unchecked(bytes: Vec<u8>) -> String;
             }
         }
@@ -474,7 +641,7 @@ define_libs! {
                 #[pre("`T` has the same size and alignment as what `ptr` was allocated with")]
                 #[pre(length <= capacity)]
                 #[pre("`capacity` is the capacity that `ptr` was allocated with")]
-                #[pre("`ptr` is not used after this call")]
+                #[pre(consumed(ptr))]
                 unsafe fn from_raw_parts(ptr: *mut T, length: usize, capacity: usize) -> Vec<T>;
 
                 #[pre(new_len <= self.capacity())]
@@ -484,6 +651,77 @@ define_libs! {
         }
     }
 
+    alloc_only {
+        mod ffi {
+            impl CString {
+                #[pre(nul_terminated(ptr))]
+                #[pre(consumed(ptr))]
+                #[pre("`ptr` was returned by `CString::into_raw`")]
+                #[pre("the length of the string at `ptr` was not changed since it was returned by `CString::into_raw`")]
+                unsafe fn from_raw(ptr: *mut c_char) -> CString;
+            }
+        }
+    }
+
     std {
+        mod ffi {
+            impl CStr {
+                #[pre(valid_cstr(ptr))]
+                #[pre("the memory referenced by the returned `CStr` is not mutated for the duration of `'a`")]
+                unsafe fn from_ptr<'a>(ptr: *const c_char) -> &'a CStr;
+            }
+
+            impl CString {
+                #[pre(nul_terminated(ptr))]
+                #[pre(consumed(ptr))]
+                #[pre("`ptr` was returned by `CString::into_raw`")]
+                #[pre("the length of the string at `ptr` was not changed since it was returned by `CString::into_raw`")]
+                unsafe fn from_raw(ptr: *mut c_char) -> CString;
+            }
+        }
+
+        #[cfg(unix)]
+        mod os {
+            mod fd {
+                impl<'fd> BorrowedFd<'fd> {
+                    #[pre("`fd` is an open file descriptor")]
+                    #[pre("`fd` remains open for the duration of `'fd`")]
+                    const unsafe fn borrow_raw(fd: RawFd) -> BorrowedFd<'fd>;
+                }
+
+                impl FromRawFd for File {
+                    #[pre("`fd` is an open file descriptor")]
+                    #[pre("`fd` is owned by the caller and is not used or closed anywhere else afterwards")]
+                    unsafe fn from_raw_fd(fd: RawFd) -> Self;
+                }
+
+                impl FromRawFd for OwnedFd {
+                    #[pre("`fd` is an open file descriptor")]
+                    #[pre("`fd` is owned by the caller and is not used or closed anywhere else afterwards")]
+                    unsafe fn from_raw_fd(fd: RawFd) -> Self;
+                }
+
+                impl FromRawFd for TcpListener {
+                    #[pre("`fd` is an open file descriptor")]
+                    #[pre("`fd` is owned by the caller and is not used or closed anywhere else afterwards")]
+                    #[pre("`fd` refers to a TCP socket listening for connections")]
+                    unsafe fn from_raw_fd(fd: RawFd) -> Self;
+                }
+
+                impl FromRawFd for TcpStream {
+                    #[pre("`fd` is an open file descriptor")]
+                    #[pre("`fd` is owned by the caller and is not used or closed anywhere else afterwards")]
+                    #[pre("`fd` refers to a connected TCP socket")]
+                    unsafe fn from_raw_fd(fd: RawFd) -> Self;
+                }
+
+                impl FromRawFd for UdpSocket {
+                    #[pre("`fd` is an open file descriptor")]
+                    #[pre("`fd` is owned by the caller and is not used or closed anywhere else afterwards")]
+                    #[pre("`fd` refers to a UDP socket")]
+                    unsafe fn from_raw_fd(fd: RawFd) -> Self;
+                }
+            }
+        }
     }
 }
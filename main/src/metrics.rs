@@ -0,0 +1,183 @@
+//! Evaluate the [exported metadata](../index.html#exporting-metadata) as unsafety metrics.
+//!
+//! For each function with calls in `unsafe` contexts (`unsafe` blocks and `unsafe fn`s), pre
+//! exports how many of these calls have [`assure`](../attr.assure.html) attributes and how many
+//! calls to functions with preconditions lack them. This module sums these counts per module and
+//! allows merging them with the numbers reported by
+//! [cargo-geiger](https://github.com/rust-secure-code/cargo-geiger), so that the percentage of
+//! unsafe calls with machine-checked assures can be tracked over time.
+//!
+//! # Example
+//!
+//! ```rust
+//! use pre::metrics::{GeigerCounts, UnsafetyReport};
+//!
+//! let metadata = r#"
+//! {"kind":"unsafe_calls","function":"read","module":"ffi","covered":3,"uncovered":1}
+//! {"kind":"unsafe_calls","function":"write","module":"ffi","covered":4,"uncovered":0}
+//! {"kind":"definition","function":"foo","preconditions":["x > 0"]}
+//! "#;
+//!
+//! let report = UnsafetyReport::from_metadata(metadata).with_geiger(GeigerCounts {
+//!     unsafe_exprs: 20,
+//!     unsafe_functions: 2,
+//! });
+//!
+//! assert_eq!(report.modules["ffi"].covered, 7);
+//! assert_eq!(report.total().coverage(), Some(7.0 / 8.0));
+//! assert_eq!(
+//!     report.to_json(),
+//!     r#"{"covered":7,"uncovered":1,"coverage":0.875,"geiger_unsafe_exprs":20,"geiger_unsafe_functions":2}"#
+//! );
+//! ```
+
+use std::{collections::BTreeMap, ops::AddAssign, string::String};
+
+/// The number of calls in `unsafe` contexts with and without `assure` attributes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UnsafeCallCounts {
+    /// The number of calls with `assure` attributes.
+    pub covered: u64,
+    /// The number of calls to functions with preconditions without `assure` attributes.
+    pub uncovered: u64,
+}
+
+impl UnsafeCallCounts {
+    /// The number of all calls.
+    pub fn total(&self) -> u64 {
+        self.covered + self.uncovered
+    }
+
+    /// The fraction of calls with `assure` attributes, if there are any calls.
+    pub fn coverage(&self) -> Option<f64> {
+        if self.total() == 0 {
+            None
+        } else {
+            Some(self.covered as f64 / self.total() as f64)
+        }
+    }
+}
+
+impl AddAssign for UnsafeCallCounts {
+    fn add_assign(&mut self, other: UnsafeCallCounts) {
+        self.covered += other.covered;
+        self.uncovered += other.uncovered;
+    }
+}
+
+/// The numbers reported by cargo-geiger for a crate.
+///
+/// These correspond to the `unsafety.used.exprs.unsafe_` and `unsafety.used.functions.unsafe_`
+/// fields of a package in the JSON output of cargo-geiger.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GeigerCounts {
+    /// The number of unsafe expressions used.
+    pub unsafe_exprs: u64,
+    /// The number of unsafe functions used.
+    pub unsafe_functions: u64,
+}
+
+/// The unsafety metrics of a crate.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UnsafetyReport {
+    /// The calls in `unsafe` contexts per module.
+    ///
+    /// The module paths are relative to the outermost item annotated with a [`pre`
+    /// attribute](../attr.pre.html), so functions in different annotated items may share an
+    /// entry.
+    pub modules: BTreeMap<String, UnsafeCallCounts>,
+    /// The numbers reported by cargo-geiger, if they were merged into the report.
+    pub geiger: Option<GeigerCounts>,
+}
+
+impl UnsafetyReport {
+    /// Creates a report from the metadata exported for a crate.
+    ///
+    /// Lines that are not `unsafe_calls` records are ignored.
+    pub fn from_metadata(metadata: &str) -> UnsafetyReport {
+        let mut report = UnsafetyReport::default();
+
+        for line in metadata.lines() {
+            if string_field(line, "kind") != Some("unsafe_calls") {
+                continue;
+            }
+
+            let module = string_field(line, "module").unwrap_or_default();
+            let counts = UnsafeCallCounts {
+                covered: number_field(line, "covered").unwrap_or(0),
+                uncovered: number_field(line, "uncovered").unwrap_or(0),
+            };
+
+            *report.modules.entry(module.into()).or_default() += counts;
+        }
+
+        report
+    }
+
+    /// Merges the numbers reported by cargo-geiger for the same crate into the report.
+    pub fn with_geiger(mut self, geiger: GeigerCounts) -> UnsafetyReport {
+        self.geiger = Some(geiger);
+        self
+    }
+
+    /// The calls in `unsafe` contexts of the whole crate.
+    pub fn total(&self) -> UnsafeCallCounts {
+        let mut total = UnsafeCallCounts::default();
+
+        for counts in self.modules.values() {
+            total += *counts;
+        }
+
+        total
+    }
+
+    /// Renders the totals of the report as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let total = self.total();
+        let mut json = format!(
+            r#"{{"covered":{},"uncovered":{},"coverage":{}"#,
+            total.covered,
+            total.uncovered,
+            total
+                .coverage()
+                .map_or_else(|| "null".into(), |coverage| coverage.to_string())
+        );
+
+        if let Some(geiger) = &self.geiger {
+            json.push_str(&format!(
+                r#","geiger_unsafe_exprs":{},"geiger_unsafe_functions":{}"#,
+                geiger.unsafe_exprs, geiger.unsafe_functions
+            ));
+        }
+
+        json.push('}');
+        json
+    }
+}
+
+/// Returns the raw value of the field in a line of exported metadata.
+///
+/// This relies on the format written by pre, where values never contain `"name":`.
+pub(crate) fn raw_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("\"{}\":", name);
+    let start = line.find(&key)? + key.len();
+
+    Some(&line[start..])
+}
+
+/// Returns the value of a string field without escape sequences in a line of exported metadata.
+fn string_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let value = raw_field(line, name)?.strip_prefix('"')?;
+
+    Some(&value[..value.find('"')?])
+}
+
+/// Returns the value of a number field in a line of exported metadata.
+fn number_field(line: &str, name: &str) -> Option<u64> {
+    let value = raw_field(line, name)?;
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+
+    value[..end].parse().ok()
+}
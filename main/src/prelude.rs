@@ -0,0 +1,55 @@
+//! The recommended set of items for using pre.
+//!
+//! Importing everything from this module makes all attributes and helper macros of pre available
+//! at once:
+//!
+//! ```rust
+//! use pre::prelude::*;
+//!
+//! #[pre(y != 0)]
+//! fn divide(x: u32, y: u32) -> u32 {
+//!     x / y
+//! }
+//!
+//! #[pre]
+//! fn main() {
+//!     #[assure(y != 0, reason = "2 != 0")]
+//!     let quotient = divide(8, 2);
+//!     assert_eq!(quotient, 4);
+//! }
+//! ```
+//!
+//! # Stability
+//!
+//! Items are only removed from the prelude in a release with breaking changes. New items may be
+//! added in any release, which can only cause name collisions with glob imports of other crates.
+//! Such collisions are resolved by importing the colliding item explicitly.
+//!
+//! The stability of the items themselves is the following:
+//!
+//! - [`pre`](macro@pre), [`assure`], [`forward`] and [`extern_crate`] are the core attributes
+//!   of pre. Their syntax only changes in releases with breaking changes.
+//! - [`assure_each`], [`assure_for_all_calls!`] and [`rename_condition!`] are helpers built on top
+//!   of the core attributes. They follow the same rules, but are newer, so their diagnostics are
+//!   more likely to be refined.
+//! - [`Token`] and [`ConditionDef`] are used by [`token`](../attr.pre.html#precondition-syntax)
+//!   and [exported](../attr.pre.html#precondition-syntax) preconditions respectively.
+//! - `set_violation_handler`, `take_violation_handler` and `ContractViolation` are only available
+//!   with the `violation-handler` feature. Their signatures may still change in minor releases
+//!   while the feature matures.
+//!
+//! The [`metrics`](../metrics/index.html) module and the preconditions for the standard library
+//! (`pre::std`, `pre::core` and `pre::alloc`) are not part of the prelude. Their names would
+//! collide with the crates of the standard library.
+
+pub use crate::{
+    assure, assure_each, assure_for_all_calls, extern_crate, forward, rename_condition,
+    ConditionDef, Token,
+};
+
+// `crate::pre` would also refer to the `extern crate self as pre` in the crate root, which cannot
+// be re-exported.
+pub use pre_proc_macro::pre;
+
+#[cfg(feature = "violation-handler")]
+pub use crate::{set_violation_handler, take_violation_handler, ContractViolation};
@@ -0,0 +1,69 @@
+//! Reporting violated preconditions to a custom handler.
+//!
+//! See the [crate documentation](../index.html#handling-violated-preconditions) for how this is
+//! used.
+
+use std::{fmt, sync::RwLock};
+
+/// The handler called for violated preconditions, if one is installed.
+static HANDLER: RwLock<Option<fn(&ContractViolation)>> = RwLock::new(None);
+
+/// A precondition that was checked at runtime and found to be violated.
+///
+/// Only preconditions that can be checked at runtime, such as boolean preconditions, `non_null`
+/// and `in_range`, are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractViolation {
+    /// The name of the function whose precondition was violated.
+    pub function: &'static str,
+    /// The violated precondition, as written in the `pre` attribute.
+    pub condition: &'static str,
+    /// A message describing the violation.
+    pub message: &'static str,
+    /// The file containing the function.
+    pub file: &'static str,
+    /// The line of the precondition in the file.
+    pub line: u32,
+}
+
+impl fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (precondition of `{}` at {}:{})",
+            self.message, self.function, self.file, self.line
+        )
+    }
+}
+
+/// Installs a handler that is called for each violated precondition.
+///
+/// The handler replaces the previously installed handler, if there was one.
+pub fn set_violation_handler(handler: fn(&ContractViolation)) {
+    *HANDLER.write().unwrap_or_else(|err| err.into_inner()) = Some(handler);
+}
+
+/// Removes the installed violation handler and returns it, if there was one.
+///
+/// Afterwards violated preconditions panic again.
+pub fn take_violation_handler() -> Option<fn(&ContractViolation)> {
+    HANDLER
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .take()
+}
+
+// *WARNING* This function is not considered to be part of the public API and may change at any
+// time without notice.
+
+/// Reports the violation to the installed handler or panics, if there is none.
+#[doc(hidden)]
+pub fn report_violation(violation: &ContractViolation) {
+    // The lock is released before calling the handler, so that it can change the handler.
+    let handler = *HANDLER.read().unwrap_or_else(|err| err.into_inner());
+
+    match handler {
+        Some(handler) => handler(violation),
+        None => panic!("{}", violation.message),
+    }
+}
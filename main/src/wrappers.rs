@@ -0,0 +1,239 @@
+//! Find safe wrappers that drop the contracts of the functions they call.
+//!
+//! Projects binding to a C library are often split into a `-sys` crate, whose functions are
+//! declared with their preconditions in an [`extern_crate` module](../attr.extern_crate.html), and
+//! a crate with safe wrapper functions calling them. If a wrapper neither assures a precondition of
+//! the called function nor states it itself, the contract is silently lost at the boundary.
+//!
+//! pre [exports](../index.html#exporting-metadata) the preconditions of the functions in
+//! `extern_crate` modules as `outline` records and each call in an `unsafe` context as an
+//! `unsafe_call` record. This module compares the two, so that the metadata of the two crates can
+//! be checked against each other, even though neither crate knows about the other.
+//!
+//! A precondition of a called function is considered to be handled by the wrapper, if it is
+//!
+//! - assured with a reason at the call,
+//! - also a precondition of the wrapper or
+//! - mentioned in the `# Safety` section of the documentation of the wrapper.
+//!
+//! # Example
+//!
+//! ```rust
+//! use pre::wrappers::dropped_contracts;
+//!
+//! let outline = r#"
+//! {"kind":"outline","function":"strlen","path":"libc::strlen","preconditions":["valid_cstr(s)"]}
+//! {"kind":"outline","function":"memcpy","path":"libc::memcpy","preconditions":["valid_ptr(src, r)","valid_ptr(dst, w)"]}
+//! "#;
+//! let wrapper = r#"
+//! {"kind":"unsafe_call","caller":"len","function":"strlen","call":"libc :: strlen (s)","assured":["valid_cstr(s)"]}
+//! {"kind":"unsafe_call","caller":"copy","function":"memcpy","call":"libc :: memcpy (d, s, n)","assured":["valid_ptr(dst, w)"]}
+//! {"kind":"unsafe_calls","function":"copy","module":"","covered":1,"uncovered":0,"safety_docs":null}
+//! "#;
+//!
+//! let dropped = dropped_contracts(outline, wrapper);
+//!
+//! assert_eq!(dropped.len(), 1);
+//! assert_eq!(dropped[0].wrapper, "copy");
+//! assert_eq!(dropped[0].function, "libc::memcpy");
+//! assert_eq!(dropped[0].precondition, "valid_ptr(src, r)");
+//! ```
+
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+use crate::metrics::raw_field;
+
+/// A precondition of a called function that a wrapper does not handle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedContract {
+    /// The name of the wrapper function.
+    pub wrapper: String,
+    /// The path of the called function in its `extern_crate` module.
+    pub function: String,
+    /// The call expression in the wrapper.
+    pub call: String,
+    /// The precondition that is not handled.
+    pub precondition: String,
+}
+
+/// Finds the preconditions of outlined functions that wrapper functions do not handle.
+///
+/// `outline_metadata` is the metadata exported for the crate with the `extern_crate` module and
+/// `wrapper_metadata` the metadata exported for the crate with the wrapper functions. Both may be
+/// the same, if the wrappers are in the same crate. Calls are matched to outlined functions by the
+/// name of the called function, so calls to functions with the same name from a different crate are
+/// checked as well.
+///
+/// # Example
+///
+/// ```rust
+/// use pre::wrappers::dropped_contracts;
+///
+/// let outline = r#"{"kind":"outline","function":"read","path":"sys::read","preconditions":["valid_ptr(buf, w)","\"fd is open\"","n > 0"]}"#;
+/// let wrapper = r#"
+/// {"kind":"definition","function":"read_into","preconditions":["n > 0"],"contract_id":"0","pure":false,"docs":null}
+/// {"kind":"unsafe_call","caller":"read_into","function":"read","call":"sys :: read (fd, buf, n)","assured":["valid_ptr(buf, w)"]}
+/// {"kind":"unsafe_calls","function":"read_into","module":"","covered":1,"uncovered":0,"safety_docs":"- `buf` is valid\n- fd is open"}
+/// {"kind":"unsafe_call","caller":"read_one","function":"read","call":"sys :: read (fd, buf, 1)","assured":[]}
+/// "#;
+///
+/// let dropped = dropped_contracts(outline, wrapper)
+///     .into_iter()
+///     .map(|dropped| (dropped.wrapper, dropped.precondition))
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     dropped,
+///     [
+///         ("read_one".to_string(), "valid_ptr(buf, w)".to_string()),
+///         ("read_one".to_string(), r#""fd is open""#.to_string()),
+///         ("read_one".to_string(), "n > 0".to_string()),
+///     ]
+/// );
+/// ```
+pub fn dropped_contracts(outline_metadata: &str, wrapper_metadata: &str) -> Vec<DroppedContract> {
+    let mut outlined = BTreeMap::new();
+    for line in outline_metadata.lines() {
+        if string_field(line, "kind").as_deref() != Some("outline") {
+            continue;
+        }
+
+        if let (Some(function), Some(path)) =
+            (string_field(line, "function"), string_field(line, "path"))
+        {
+            let preconditions = string_array_field(line, "preconditions").unwrap_or_default();
+            outlined.insert(function, (path, preconditions));
+        }
+    }
+
+    let mut own_preconditions = BTreeMap::new();
+    let mut safety_docs = BTreeMap::new();
+    for line in wrapper_metadata.lines() {
+        let function = match string_field(line, "function") {
+            Some(function) => function,
+            None => continue,
+        };
+
+        match string_field(line, "kind").as_deref() {
+            Some("definition") => {
+                own_preconditions.insert(
+                    function,
+                    string_array_field(line, "preconditions").unwrap_or_default(),
+                );
+            }
+            Some("unsafe_calls") => {
+                if let Some(docs) = string_field(line, "safety_docs") {
+                    safety_docs.insert(function, docs);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut dropped = Vec::new();
+    for line in wrapper_metadata.lines() {
+        if string_field(line, "kind").as_deref() != Some("unsafe_call") {
+            continue;
+        }
+
+        let (wrapper, function) =
+            match (string_field(line, "caller"), string_field(line, "function")) {
+                (Some(wrapper), Some(function)) => (wrapper, function),
+                _ => continue,
+            };
+        let (path, preconditions) = match outlined.get(&function) {
+            Some(outlined) => outlined,
+            None => continue,
+        };
+        let assured = string_array_field(line, "assured").unwrap_or_default();
+        let call = string_field(line, "call").unwrap_or_default();
+
+        for precondition in preconditions {
+            let handled = assured.contains(precondition)
+                || matches!(own_preconditions.get(&wrapper), Some(own) if own.contains(precondition))
+                || matches!(safety_docs.get(&wrapper), Some(docs) if mentions(docs, precondition));
+
+            if !handled {
+                dropped.push(DroppedContract {
+                    wrapper: wrapper.clone(),
+                    function: path.clone(),
+                    call: call.clone(),
+                    precondition: precondition.clone(),
+                });
+            }
+        }
+    }
+
+    dropped
+}
+
+/// Checks if the documentation mentions the precondition.
+///
+/// Custom preconditions are mentioned if their text appears in the documentation, all others if
+/// they appear verbatim.
+fn mentions(docs: &str, precondition: &str) -> bool {
+    let text = precondition
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(precondition);
+
+    docs.contains(text)
+}
+
+/// Parses a JSON string literal at the start of the value.
+///
+/// Returns the unescaped string and the rest of the value after the literal.
+fn parse_string(value: &str) -> Option<(String, &str)> {
+    let mut chars = value.strip_prefix('"')?.char_indices();
+    let mut string = String::new();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((string, &value[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => string.push('\n'),
+                'r' => string.push('\r'),
+                't' => string.push('\t'),
+                'u' => {
+                    let digits = (0..4)
+                        .map(|_| chars.next().map(|(_, c)| c))
+                        .collect::<Option<String>>()?;
+                    string.push(core::char::from_u32(
+                        u32::from_str_radix(&digits, 16).ok()?,
+                    )?);
+                }
+                escaped => string.push(escaped),
+            },
+            c => string.push(c),
+        }
+    }
+
+    None
+}
+
+/// Returns the value of a string field in a line of exported metadata.
+fn string_field(line: &str, name: &str) -> Option<String> {
+    parse_string(raw_field(line, name)?).map(|(string, _)| string)
+}
+
+/// Returns the value of a field containing an array of strings in a line of exported metadata.
+fn string_array_field(line: &str, name: &str) -> Option<Vec<String>> {
+    let mut value = raw_field(line, name)?.strip_prefix('[')?;
+    let mut strings = Vec::new();
+
+    if value.starts_with(']') {
+        return Some(strings);
+    }
+
+    loop {
+        let (string, rest) = parse_string(value)?;
+        strings.push(string);
+
+        if let Some(rest) = rest.strip_prefix(',') {
+            value = rest;
+        } else {
+            rest.strip_prefix(']')?;
+            return Some(strings);
+        }
+    }
+}
@@ -1,12 +1,12 @@
-use rustc_version::{version_meta, Channel};
+use rustc_version::version_meta;
+
+// The detection of the nightly version is shared with the `pre` crate, so that both agree on which
+// version of pre is used.
+include!("../main/build/nightly.rs");
 
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../main/build/nightly.rs");
 
-    match version_meta() {
-        Ok(version) if version.channel == Channel::Nightly => {
-            println!("cargo:rustc-cfg=nightly");
-        }
-        _ => (),
-    }
+    detect_nightly(version_meta().ok().as_ref());
 }
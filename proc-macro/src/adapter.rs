@@ -0,0 +1,468 @@
+//! Provides adapters for using functions with preconditions in higher-order contexts.
+//!
+//! # What the generated code looks like
+//!
+//! ```rust,ignore
+//! #[pre::pre(adapter)]
+//! #[pre::pre(x > 0)]
+//! fn double(x: i32) -> i32 {
+//!     x * 2
+//! }
+//!
+//! #[pre::pre]
+//! fn main() {
+//!     let adapter = pre::assure_for_all_calls!(
+//!         #[assure(x > 0, reason = "all values are positive")]
+//!         double
+//!     );
+//!     let doubled: Vec<_> = vec![1, 2, 3].into_iter().map(adapter).collect();
+//! }
+//! ```
+//!
+//! turns into (using the `const_generics_impl` backend for brevity)
+//!
+//! ```rust,ignore
+//! fn double(x: i32, _: (::pre::BooleanCondition<"x > 0">,)) -> i32 {
+//!     x * 2
+//! }
+//!
+//! #[doc(hidden)]
+//! fn double__adapter__(_: (::pre::BooleanCondition<"x > 0">,)) -> DoubleChecked {
+//!     DoubleChecked {
+//!         _marker: ::core::marker::PhantomData,
+//!     }
+//! }
+//!
+//! #[doc = "..."]
+//! struct DoubleChecked {
+//!     _marker: ::core::marker::PhantomData<fn() -> ()>,
+//! }
+//!
+//! // `Clone` and `Copy` implementations
+//!
+//! impl DoubleChecked {
+//!     fn call(self, __pre_arg_0: i32) -> i32 {
+//!         double(__pre_arg_0, (::pre::BooleanCondition<"x > 0">,))
+//!     }
+//!
+//!     fn as_fn(self) -> impl ::core::ops::Fn(i32) -> i32 + ::core::marker::Copy {
+//!         move |__pre_arg_0: i32| self.call(__pre_arg_0)
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let adapter = double__adapter__((::pre::BooleanCondition<"x > 0">,)).as_fn();
+//!     let doubled: Vec<_> = vec![1, 2, 3].into_iter().map(adapter).collect();
+//! }
+//! ```
+//!
+//! The checked adapter type can be named, so it can also be stored, for example in a dispatch
+//! table, by using `assure_for_all_calls!(/* ... */ double as DoubleChecked)`, which omits the
+//! call to `as_fn`.
+//!
+//! With the `adapter-fn-traits` feature on the nightly compiler, the checked adapter type also
+//! implements the `Fn` traits by calling `call`:
+//!
+//! ```rust,ignore
+//! impl ::core::ops::FnOnce<(i32,)> for DoubleChecked {
+//!     type Output = i32;
+//!
+//!     extern "rust-call" fn call_once(self, (__pre_arg_0,): (i32,)) -> i32 {
+//!         DoubleChecked::call(self, __pre_arg_0)
+//!     }
+//! }
+//!
+//! // `FnMut` and `Fn` implementations calling `DoubleChecked::call(*self, __pre_arg_0)`
+//! ```
+
+use std::{collections::HashMap, sync::Mutex};
+
+use lazy_static::lazy_static;
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::emit_error;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse2,
+    spanned::Spanned,
+    token::Paren,
+    Attribute, Expr, ExprCall, ExprPath, FnArg, GenericParam, Ident, ItemFn, Path, ReturnType,
+    Token, TypePath,
+};
+
+use crate::{
+    call::Call,
+    call_handling::{remove_call_attributes, render_call, CallAttributes},
+    helpers::contains_impl_trait,
+    lints::LintLevels,
+    precondition::{CfgPrecondition, Precondition},
+    render_assure, render_pre,
+};
+
+lazy_static! {
+    /// The functions that checked adapter types were generated for so far, indexed by the module
+    /// path and the name of the type.
+    ///
+    /// The module path consists of the modules with a `pre` attribute containing the function, so
+    /// it is empty if they are not known.
+    static ref CHECKED_TYPES: Mutex<HashMap<(Vec<String>, String), String>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Returns the name of the adapter for the function with the given name.
+fn adapter_name(fn_name: &Ident) -> Ident {
+    format_ident!("{}__adapter__", fn_name, span = fn_name.span())
+}
+
+/// Returns the name of the checked adapter type for the function with the given name.
+///
+/// The name is the name of the function in `UpperCamelCase`, followed by `Checked`, such as
+/// `ReadTwiceChecked` for `read_twice`.
+fn checked_type_name(fn_name: &Ident) -> Ident {
+    let name = fn_name.to_string();
+    let camel_case = name
+        .trim_start_matches("r#")
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<String>();
+
+    format_ident!("{}Checked", camel_case, span = fn_name.span())
+}
+
+/// Records that the checked adapter type was generated for the function in the module.
+///
+/// If the same name was already generated for a different function in the same module, such as
+/// for `foo_bar` and `foo__bar`, the name of that function is returned.
+fn register_checked_type(
+    module_path: &[Ident],
+    type_name: &Ident,
+    fn_name: &Ident,
+) -> Option<String> {
+    let key = (
+        module_path.iter().map(Ident::to_string).collect(),
+        type_name.to_string(),
+    );
+    let fn_name = fn_name.to_string();
+    let mut checked_types = CHECKED_TYPES.lock().unwrap_or_else(|err| err.into_inner());
+
+    match checked_types.get(&key) {
+        Some(other) if *other != fn_name => Some(other.clone()),
+        Some(_) => None,
+        None => {
+            checked_types.insert(key, fn_name);
+
+            None
+        }
+    }
+}
+
+/// Generates an adapter for the given function.
+///
+/// The adapter consists of the checked adapter type and a function creating it, which requires the
+/// preconditions of the function.
+///
+/// The function must not yet have the precondition argument added.
+/// `arg_first` specifies whether the precondition argument is the first argument of the function.
+/// `module_path` contains the modules with a `pre` attribute that contain the function.
+pub(crate) fn render_adapter(
+    function: &ItemFn,
+    module_path: &[Ident],
+    preconditions: &[CfgPrecondition],
+    span: Span,
+    arg_first: bool,
+) -> Option<TokenStream> {
+    let sig = &function.sig;
+    let mut supported = true;
+    let mut unsupported = |span: Span, reason: &str| {
+        emit_error!(span, "adapters are not supported for {}", reason);
+        supported = false;
+    };
+
+    if let Some(receiver) = sig.receiver() {
+        unsupported(receiver.span(), "methods");
+    }
+    if let Some(asyncness) = &sig.asyncness {
+        unsupported(asyncness.span, "`async` functions");
+    }
+    if let Some(variadic) = &sig.variadic {
+        unsupported(variadic.span(), "variadic functions");
+    }
+    for precondition in preconditions {
+        if let Precondition::Token { .. } = precondition.precondition() {
+            unsupported(
+                precondition.span(),
+                "functions with token preconditions, because tokens cannot be recreated",
+            );
+        }
+    }
+
+    let mut arg_names = Vec::new();
+    let mut arg_types = Vec::new();
+    for (i, arg) in sig.inputs.iter().enumerate() {
+        if let FnArg::Typed(pat_type) = arg {
+            if contains_impl_trait(&pat_type.ty) {
+                unsupported(pat_type.ty.span(), "functions with `impl Trait` arguments");
+            }
+
+            arg_names.push(format_ident!("__pre_arg_{}", i, span = span));
+            arg_types.push(&*pat_type.ty);
+        }
+    }
+
+    if let ReturnType::Type(_, ty) = &sig.output {
+        if contains_impl_trait(ty) {
+            unsupported(ty.span(), "functions returning `impl Trait`");
+        }
+    }
+
+    if !supported {
+        return None;
+    }
+
+    let fn_name = &sig.ident;
+    let generic_args = sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(ty) => Some(&ty.ident),
+            GenericParam::Const(constant) => Some(&constant.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let turbofish = if generic_args.is_empty() {
+        None
+    } else {
+        Some(quote_spanned! { span=> ::<#(#generic_args),*> })
+    };
+
+    let call: ExprCall = parse2(quote_spanned! { span=>
+        #fn_name #turbofish (#(#arg_names),*)
+    })
+    .expect("parses as a call expression");
+    let call = render_assure(preconditions.to_vec(), call.into(), span, arg_first);
+    let body = if sig.unsafety.is_some() {
+        quote_spanned! { span=> unsafe { #call } }
+    } else {
+        quote! { #call }
+    };
+
+    let output = match &sig.output {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+
+    let vis = &function.vis;
+    let unsafety = &sig.unsafety;
+    let generics = &sig.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let lifetimes = generics.lifetimes().map(|param| &param.lifetime);
+    let type_params = generics.type_params().map(|param| &param.ident);
+    let name = adapter_name(fn_name);
+    let type_name = checked_type_name(fn_name);
+    if let Some(other) = register_checked_type(module_path, &type_name, fn_name) {
+        emit_error!(
+            fn_name,
+            "the checked adapter type of `{}` has the same name as the one of `{}`: `{}`",
+            fn_name,
+            other,
+            type_name;
+            help = "rename one of the functions, so that their names differ in more than underscores"
+        );
+
+        return None;
+    }
+    let doc = format!(
+        "A checked adapter for calling [`{0}`]({0}) with its preconditions assured once for all \
+        calls.\n\nIt is created using `assure_for_all_calls!(/* assure attributes */ {0} as {1})`.",
+        fn_name, type_name
+    );
+
+    // The adapter only exists to be created by `assure_for_all_calls`, so it is not `const`.
+    let mut adapter: ItemFn = parse2(quote_spanned! { span=>
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        #[inline(always)]
+        #vis #unsafety fn #name #generics () -> #type_name #ty_generics #where_clause {
+            #type_name {
+                _marker: ::core::marker::PhantomData,
+            }
+        }
+    })
+    .expect("parses as a function");
+    let adapter = render_pre(preconditions.to_vec(), &mut adapter, span, false);
+
+    let fn_trait_impls = if cfg!(all(nightly, feature = "adapter-fn-traits")) {
+        let args = quote! { (#(#arg_names,)*): (#(#arg_types,)*) };
+
+        Some(quote_spanned! { span=>
+            impl #impl_generics ::core::ops::FnOnce<(#(#arg_types,)*)> for #type_name #ty_generics #where_clause {
+                type Output = #output;
+
+                #[inline(always)]
+                extern "rust-call" fn call_once(self, #args) -> #output {
+                    #type_name::call(self, #(#arg_names),*)
+                }
+            }
+
+            impl #impl_generics ::core::ops::FnMut<(#(#arg_types,)*)> for #type_name #ty_generics #where_clause {
+                #[inline(always)]
+                extern "rust-call" fn call_mut(&mut self, #args) -> #output {
+                    #type_name::call(*self, #(#arg_names),*)
+                }
+            }
+
+            impl #impl_generics ::core::ops::Fn<(#(#arg_types,)*)> for #type_name #ty_generics #where_clause {
+                #[inline(always)]
+                extern "rust-call" fn call(&self, #args) -> #output {
+                    #type_name::call(*self, #(#arg_names),*)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    Some(quote_spanned! { span=>
+        #adapter
+
+        #[doc = #doc]
+        #[allow(dead_code)]
+        #vis struct #type_name #generics #where_clause {
+            _marker: ::core::marker::PhantomData<fn() -> (#(&#lifetimes (),)* #(#type_params,)*)>,
+        }
+
+        impl #impl_generics ::core::clone::Clone for #type_name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::core::marker::Copy for #type_name #ty_generics #where_clause {}
+
+        #[allow(dead_code)]
+        impl #impl_generics #type_name #ty_generics #where_clause {
+            #[doc = "Calls the function with the given arguments."]
+            #[inline(always)]
+            #vis fn call(self, #(#arg_names: #arg_types),*) -> #output {
+                #body
+            }
+
+            #[doc = "Returns a closure calling the function, for use where an `Fn` trait is required."]
+            #[inline(always)]
+            #vis fn as_fn(
+                self,
+            ) -> impl ::core::ops::Fn(#(#arg_types),*) -> #output + ::core::marker::Copy {
+                move |#(#arg_names: #arg_types),*| self.call(#(#arg_names),*)
+            }
+        }
+
+        #fn_trait_impls
+    })
+}
+
+/// The input of the `assure_for_all_calls` macro.
+pub(crate) struct AssureForAllCalls {
+    /// The `assure` attributes.
+    attrs: Vec<Attribute>,
+    /// The path to the function to create an adapter for.
+    path: ExprPath,
+    /// The checked adapter type to create instead of a closure, such as `as DoubleChecked`.
+    checked_type: Option<(Token![as], TypePath)>,
+}
+
+impl Parse for AssureForAllCalls {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let path = input.parse()?;
+        let checked_type = if input.peek(Token![as]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+
+        Ok(AssureForAllCalls {
+            attrs,
+            path,
+            checked_type,
+        })
+    }
+}
+
+impl AssureForAllCalls {
+    /// Renders the call to the adapter of the function.
+    pub(crate) fn render(mut self) -> TokenStream {
+        let path_span = self.path.span();
+        let attrs = remove_call_attributes(&mut self.attrs).unwrap_or_else(|| CallAttributes {
+            span: path_span,
+            forward: None,
+            assure_attributes: Vec::new(),
+        });
+
+        for attr in &self.attrs {
+            emit_error!(
+                attr,
+                "unexpected attribute";
+                help = "only `assure` attributes are supported here"
+            );
+        }
+
+        if let Some(forward) = &attrs.forward {
+            emit_error!(
+                forward.span(),
+                "`forward` attributes are not supported by `assure_for_all_calls`"
+            );
+        }
+
+        let mut path: Path = self.path.path;
+        if let Some(last_segment) = path.segments.last_mut() {
+            if let Some((_, checked_type)) = &self.checked_type {
+                let expected = checked_type_name(&last_segment.ident);
+
+                match checked_type.path.segments.last() {
+                    Some(segment) if segment.ident == expected => (),
+                    _ => emit_error!(
+                        checked_type,
+                        "expected `{}`, the checked adapter type of `{}`",
+                        expected,
+                        last_segment.ident
+                    ),
+                }
+            }
+
+            last_segment.ident = adapter_name(&last_segment.ident);
+        }
+
+        let call = Call::Function(ExprCall {
+            attrs: Vec::new(),
+            func: Box::new(Expr::Path(ExprPath {
+                attrs: Vec::new(),
+                qself: self.path.qself,
+                path,
+            })),
+            paren_token: Paren { span: path_span },
+            args: Default::default(),
+        });
+
+        let call = render_call(
+            CallAttributes {
+                forward: None,
+                ..attrs
+            },
+            call,
+            &LintLevels::default(),
+        );
+
+        match self.checked_type {
+            Some(_) => quote! { #call },
+            None => quote_spanned! { path_span=> #call.as_fn() },
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Provides the `assert_requires!` macro that pins the preconditions of a function in tests.
+//!
+//! If the definition of the function was expanded before the macro in the same crate, the
+//! preconditions are compared directly, so that the differences can be listed. Otherwise the
+//! comparison is left to the compiler, which is only possible if the preconditions are encoded as
+//! structs, as is done on the stable compiler.
+
+use proc_macro2::TokenStream;
+use proc_macro_error::{emit_error, Diagnostic, Level};
+use quote::quote_spanned;
+use syn::{
+    bracketed,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token::{Bracket, Paren},
+    Expr, ExprCall, ExprPath, Token,
+};
+
+use crate::{
+    call::Call,
+    documentation::code_span,
+    metadata,
+    precondition::{CfgPrecondition, Precondition},
+    render_assure,
+};
+
+/// The parsed input of the `assert_requires!` macro.
+pub(crate) struct AssertRequires {
+    /// The path to the function whose preconditions are checked.
+    path: ExprPath,
+    /// The comma between the path and the preconditions.
+    _comma: Token![,],
+    /// The brackets around the preconditions.
+    _brackets: Bracket,
+    /// The preconditions that the function is expected to have.
+    preconditions: Punctuated<Precondition, Token![,]>,
+}
+
+impl Parse for AssertRequires {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let comma = input.parse()?;
+        let content;
+        let brackets = bracketed!(content in input);
+        let preconditions = content.parse_terminated(Precondition::parse)?;
+
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(AssertRequires {
+            path,
+            _comma: comma,
+            _brackets: brackets,
+            preconditions,
+        })
+    }
+}
+
+impl AssertRequires {
+    /// Renders the check that the function has exactly the expected preconditions.
+    pub(crate) fn render(self) -> TokenStream {
+        let path_str = self
+            .path
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::");
+        let name = match self.path.path.segments.last() {
+            Some(segment) => segment.ident.to_string(),
+            None => return TokenStream::new(),
+        };
+
+        let expected = self
+            .preconditions
+            .iter()
+            .map(|precondition| precondition.to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(definition) = metadata::definition(&name) {
+            let (missing, unexpected) = contract_differences(&definition.preconditions, &expected);
+
+            if missing.is_empty() && unexpected.is_empty() {
+                return TokenStream::new();
+            }
+
+            let mut diagnostic = Diagnostic::spanned(
+                self.path.span(),
+                Level::Error,
+                format!(
+                    "the preconditions of `{}` differ from the expected ones",
+                    path_str
+                ),
+            );
+            if !missing.is_empty() {
+                diagnostic = diagnostic.note(format!(
+                    "`{}` also requires {}",
+                    path_str,
+                    render_list(&missing)
+                ));
+            }
+            if !unexpected.is_empty() {
+                diagnostic = diagnostic.note(format!(
+                    "`{}` does not require {}",
+                    path_str,
+                    render_list(&unexpected)
+                ));
+            }
+            diagnostic.emit();
+
+            return TokenStream::new();
+        }
+
+        if cfg!(all(nightly, not(feature = "backend-struct"))) {
+            emit_error!(
+                self.path,
+                "the preconditions of `{}` are unknown", path_str;
+                help = "on the nightly compiler, only functions with preconditions defined earlier in the same crate can be checked"
+            );
+
+            return TokenStream::new();
+        }
+
+        self.render_type_check()
+    }
+
+    /// Renders a check that lets the compiler compare the preconditions.
+    ///
+    /// The check constructs the precondition argument for the function with the expected
+    /// preconditions, which only compiles if they are exactly the preconditions of the function.
+    fn render_type_check(self) -> TokenStream {
+        let span = self.path.span();
+        let preconditions = self
+            .preconditions
+            .into_iter()
+            .map(|precondition| CfgPrecondition {
+                span: precondition.span(),
+                precondition,
+                cfg: None,
+            })
+            .collect();
+
+        let call = Call::Function(ExprCall {
+            attrs: Vec::new(),
+            func: Box::new(Expr::Path(self.path)),
+            paren_token: Paren { span },
+            args: Punctuated::new(),
+        });
+        let arg = render_assure(preconditions, call, span, false)
+            .args_mut()
+            .pop()
+            .map(|arg| arg.into_value());
+
+        // The check is never executed, so placeholder values of token preconditions are fine.
+        quote_spanned! { span=>
+            const _: () = {
+                let _ = || {
+                    ::core::mem::drop(#arg);
+                };
+            };
+        }
+    }
+}
+
+/// Returns the declared preconditions that are not expected and the expected preconditions that
+/// are not declared, in this order.
+fn contract_differences<'a>(
+    declared: &'a [String],
+    expected: &'a [String],
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    let missing = declared
+        .iter()
+        .filter(|precondition| !expected.contains(precondition))
+        .map(|precondition| &**precondition)
+        .collect();
+    let unexpected = expected
+        .iter()
+        .filter(|precondition| !declared.contains(precondition))
+        .map(|precondition| &**precondition)
+        .collect();
+
+    (missing, unexpected)
+}
+
+/// Renders the preconditions as a list of code spans.
+fn render_list(preconditions: &[&str]) -> String {
+    preconditions
+        .iter()
+        .map(code_span)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn parse_assert_requires() {
+        let assertion: AssertRequires =
+            parse2(quote! { foo::bar, ["cond a", valid_ptr(p, r)] }).expect("valid assertion");
+        assert_eq!(assertion.preconditions.len(), 2);
+
+        assert!(parse2::<AssertRequires>(quote! { foo, [] }).is_ok());
+        assert!(parse2::<AssertRequires>(quote! { foo, ["cond a",], }).is_ok());
+        assert!(parse2::<AssertRequires>(quote! { foo, "cond a" }).is_err());
+        assert!(parse2::<AssertRequires>(quote! { foo ["cond a"] }).is_err());
+    }
+
+    #[test]
+    fn finds_contract_differences() {
+        let declared = vec!["\"a\"".to_string(), "x > 0".to_string()];
+        let expected = vec!["x > 0".to_string(), "\"b\"".to_string()];
+
+        assert_eq!(
+            contract_differences(&declared, &expected),
+            (vec!["\"a\""], vec!["\"b\""])
+        );
+        assert_eq!(
+            contract_differences(&declared, &declared),
+            (Vec::new(), Vec::new())
+        );
+        assert_eq!(render_list(&["\"a\"", "x > 0"]), "`\"a\"`, `x > 0`");
+        assert_eq!(render_list(&["\"`a`\""]), "``\"`a`\"``");
+    }
+}
@@ -1,9 +1,16 @@
 //! Allows treating function and method call expressions the same.
+//!
+//! The result of a call may also be adapted by `?`, `.await` or indexing, such as in
+//! `foo(x)?.bar()`. These adapters are kept as part of the call, so that they are preserved when
+//! the call is rendered.
 
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens, TokenStreamExt};
-use std::convert::TryFrom;
-use syn::{punctuated::Punctuated, token::Comma, Expr, ExprCall, ExprMethodCall, ExprPath};
+use std::{convert::TryFrom, mem};
+use syn::{
+    punctuated::Punctuated, token::Comma, Expr, ExprAwait, ExprCall, ExprIndex, ExprMethodCall,
+    ExprPath, ExprTry,
+};
 
 /// A call expression.
 #[derive(Clone)]
@@ -12,14 +19,45 @@ pub(crate) enum Call {
     Function(ExprCall),
     /// The call expression is a method call.
     Method(ExprMethodCall),
+    /// The result of the call expression is adapted, such as by `?` or `.await`.
+    Adapted(Box<Call>, CallAdapter),
 }
 
 impl Call {
+    /// The arguments of the call.
+    pub(crate) fn args(&self) -> &Punctuated<Expr, Comma> {
+        match self {
+            Call::Function(call) => &call.args,
+            Call::Method(call) => &call.args,
+            Call::Adapted(call, _) => call.args(),
+        }
+    }
+
     /// Grants mutable access to the arguments of the call.
     pub(crate) fn args_mut(&mut self) -> &mut Punctuated<Expr, Comma> {
         match self {
             Call::Function(call) => &mut call.args,
             Call::Method(call) => &mut call.args,
+            Call::Adapted(call, _) => call.args_mut(),
+        }
+    }
+
+    /// Adds the precondition argument to the call.
+    ///
+    /// If `first` is set, it is placed before all other arguments, otherwise after them.
+    pub(crate) fn add_precondition_arg(&mut self, arg: Expr, first: bool) {
+        if first {
+            self.args_mut().insert(0, arg);
+        } else {
+            self.args_mut().push(arg);
+        }
+    }
+
+    /// The call without any adapters applied to its result.
+    pub(crate) fn base(&self) -> &Call {
+        match self {
+            Call::Adapted(call, _) => call.base(),
+            call => call,
         }
     }
 
@@ -36,13 +74,95 @@ impl Call {
                 _ => None,
             },
             Call::Method(_) => None,
+            Call::Adapted(call, _) => call.path(),
+        }
+    }
+
+    /// The name of the function or method being called, if present.
+    ///
+    /// For function calls, this is the last segment of the path to the function.
+    pub(crate) fn name(&self) -> Option<String> {
+        match self {
+            Call::Function(_) => self.path().and_then(|path| {
+                path.path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident.to_string())
+            }),
+            Call::Method(call) => Some(call.method.to_string()),
+            Call::Adapted(call, _) => call.name(),
         }
     }
 
     /// Checks if the call expression is a function call.
     #[allow(dead_code)]
     pub(crate) fn is_function(&self) -> bool {
-        matches!(self, Call::Function(_))
+        matches!(self.base(), Call::Function(_))
+    }
+}
+
+/// An operation applied to the result of a call.
+///
+/// The adapted expression stored in the adapter is a placeholder, which is replaced by the call
+/// when the adapter is applied.
+#[derive(Clone)]
+pub(crate) enum CallAdapter {
+    /// The result is awaited: `foo(x).await`.
+    Await(ExprAwait),
+    /// The result is propagated using the `?` operator: `foo(x)?`.
+    Try(ExprTry),
+    /// The result is indexed: `foo(x)[i]`.
+    Index(ExprIndex),
+}
+
+impl CallAdapter {
+    /// Splits an expression into the adapter and the expression it adapts.
+    ///
+    /// If the expression is not adapted, it is returned unchanged without an adapter.
+    fn split(value: Expr) -> (Option<CallAdapter>, Expr) {
+        let placeholder = || Box::new(Expr::Verbatim(TokenStream::new()));
+
+        match value {
+            Expr::Await(mut expr) => {
+                let base = mem::replace(&mut expr.base, placeholder());
+
+                (Some(CallAdapter::Await(expr)), *base)
+            }
+            Expr::Try(mut expr) => {
+                let base = mem::replace(&mut expr.expr, placeholder());
+
+                (Some(CallAdapter::Try(expr)), *base)
+            }
+            Expr::Index(mut expr) => {
+                let base = mem::replace(&mut expr.expr, placeholder());
+
+                (Some(CallAdapter::Index(expr)), *base)
+            }
+            _ => (None, value),
+        }
+    }
+
+    /// Applies the adapter to the given expression.
+    ///
+    /// The expression needs to be parenthesized by the caller, if it isn't a postfix expression.
+    pub(crate) fn adapt(self, base: Expr) -> Expr {
+        match self {
+            CallAdapter::Await(mut expr) => {
+                *expr.base = base;
+
+                Expr::Await(expr)
+            }
+            CallAdapter::Try(mut expr) => {
+                *expr.expr = base;
+
+                Expr::Try(expr)
+            }
+            CallAdapter::Index(mut expr) => {
+                *expr.expr = base;
+
+                Expr::Index(expr)
+            }
+        }
     }
 }
 
@@ -65,7 +185,13 @@ impl TryFrom<Expr> for Call {
         match value {
             Expr::Call(call) => Ok(call.into()),
             Expr::MethodCall(call) => Ok(call.into()),
-            _ => Err(value),
+            _ => match CallAdapter::split(value) {
+                (Some(adapter), base) => match Call::try_from(base) {
+                    Ok(call) => Ok(Call::Adapted(Box::new(call), adapter)),
+                    Err(base) => Err(adapter.adapt(base)),
+                },
+                (None, value) => Err(value),
+            },
         }
     }
 }
@@ -75,6 +201,7 @@ impl From<Call> for Expr {
         match call {
             Call::Function(call) => Expr::Call(call),
             Call::Method(call) => Expr::MethodCall(call),
+            Call::Adapted(call, adapter) => adapter.adapt((*call).into()),
         }
     }
 }
@@ -84,6 +211,49 @@ impl ToTokens for Call {
         match self {
             Call::Function(call) => tokens.append_all(quote! { #call }),
             Call::Method(call) => tokens.append_all(quote! { #call }),
+            Call::Adapted(..) => {
+                let expr = Expr::from(self.clone());
+
+                tokens.append_all(quote! { #expr })
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    /// Converts the expression to a call, panicking if it is not one.
+    fn call(expr: Expr) -> Call {
+        Call::try_from(expr).unwrap_or_else(|_| panic!("expression is a call"))
+    }
+
+    #[test]
+    fn adapted_calls() {
+        let adapted = call(parse_quote! { foo::bar(x)?.await[0] });
+        assert_eq!(adapted.name().as_deref(), Some("bar"));
+        assert!(adapted.is_function());
+        assert_eq!(
+            adapted.to_token_stream().to_string(),
+            quote! { foo::bar(x)?.await[0] }.to_string()
+        );
+
+        let mut adapted = call(parse_quote! { x.foo()? });
+        assert!(!adapted.is_function());
+        adapted.args_mut().push(parse_quote! { y });
+        assert_eq!(
+            Expr::from(adapted).to_token_stream().to_string(),
+            quote! { x.foo(y)? }.to_string()
+        );
+
+        let expr: Expr = parse_quote! { foo[0]? };
+        assert_eq!(
+            Call::try_from(expr.clone())
+                .err()
+                .map(|expr| expr.to_token_stream().to_string()),
+            Some(expr.to_token_stream().to_string())
+        );
+    }
+}
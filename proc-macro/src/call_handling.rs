@@ -1,18 +1,30 @@
 //! Functionality for parsing and visiting `assure` attributes.
 
 use proc_macro2::Span;
-use proc_macro_error::{emit_error, emit_warning};
+use proc_macro_error::{emit_error, Diagnostic, Level};
+use quote::{quote, quote_spanned};
 use syn::{
+    parenthesized,
     parse::{Parse, ParseStream},
+    parse2, parse_str,
     spanned::Spanned,
-    Attribute, Expr, LitStr, Token,
+    token::Paren,
+    Attribute, Expr, Ident, LitStr, Token,
 };
 
 use self::forward::ForwardAttr;
 use crate::{
     call::Call,
-    helpers::{flatten_cfgs, visit_matching_attrs_parsed_mut, Attr, AttributeAction, HINT_REASON},
-    precondition::Precondition,
+    config::{hint_reason, summary_errors, Message},
+    extern_crate::outline_has_preconditions,
+    helpers::{
+        combine_call_site_cfg, flatten_cfgs, foreign_attr_path, visit_matching_attrs_parsed_mut,
+        Attr, AttributeAction, CRATE_NAME,
+    },
+    lints::{Lint, LintLevels},
+    metadata::{self, Record},
+    precondition::{CfgPrecondition, Precondition},
+    rename::register_assured_condition,
     render_assure,
 };
 
@@ -23,6 +35,10 @@ mod custom_keywords {
     use syn::custom_keyword;
 
     custom_keyword!(reason);
+    custom_keyword!(audit);
+    custom_keyword!(audited);
+    custom_keyword!(by);
+    custom_keyword!(date);
 }
 
 /// An attribute with an assurance that a precondition holds.
@@ -99,6 +115,10 @@ pub(crate) struct Reason {
     _eq: Token![=],
     /// The reason the precondition holds.
     reason: LitStr,
+    /// The optional reference to an external audit of the reason.
+    audit: Option<Audit>,
+    /// The optional record of who reviewed the reason and when.
+    audited: Option<Audited>,
 }
 
 impl Parse for Reason {
@@ -106,15 +126,147 @@ impl Parse for Reason {
         let reason_keyword = input.parse()?;
         let eq = input.parse()?;
         let reason = input.parse()?;
+        let audit = if input.peek(Token![,]) && input.peek2(custom_keywords::audit) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        let audited = if input.peek(Token![,]) && input.peek2(custom_keywords::audited) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
 
         Ok(Reason {
             _reason_keyword: reason_keyword,
             _eq: eq,
             reason,
+            audit,
+            audited,
+        })
+    }
+}
+
+impl Reason {
+    /// The text of the reason.
+    pub(crate) fn text(&self) -> String {
+        self.reason.value()
+    }
+}
+
+/// A reference to an external audit, such as a ticket of a security review.
+pub(crate) struct Audit {
+    /// The comma separating the reason from the audit.
+    _comma: Token![,],
+    /// The `audit` keyword.
+    _audit_keyword: custom_keywords::audit,
+    /// The `=` separating the `audit` keyword and the reference.
+    _eq: Token![=],
+    /// The reference to the audit.
+    reference: LitStr,
+}
+
+impl Parse for Audit {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let comma = input.parse()?;
+        let audit_keyword = input.parse()?;
+        let eq = input.parse()?;
+        let reference = input.parse()?;
+
+        Ok(Audit {
+            _comma: comma,
+            _audit_keyword: audit_keyword,
+            _eq: eq,
+            reference,
+        })
+    }
+}
+
+/// A record of who reviewed the reason and when, such as
+/// `audited(by = "alice", date = "2024-06-01")`.
+pub(crate) struct Audited {
+    /// The comma separating the record from the previous part of the attribute.
+    _comma: Token![,],
+    /// The `audited` keyword.
+    _audited_keyword: custom_keywords::audited,
+    /// The parentheses surrounding the record.
+    _parentheses: Paren,
+    /// The reviewer of the reason.
+    by: LitStr,
+    /// The date of the review.
+    date: LitStr,
+}
+
+impl Parse for Audited {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let comma = input.parse()?;
+        let audited_keyword: custom_keywords::audited = input.parse()?;
+        let content;
+        let parentheses = parenthesized!(content in input);
+
+        let mut by = None;
+        let mut date = None;
+        while !content.is_empty() {
+            let lookahead = content.lookahead1();
+            let field = if lookahead.peek(custom_keywords::by) {
+                content.parse::<custom_keywords::by>()?;
+                &mut by
+            } else if lookahead.peek(custom_keywords::date) {
+                content.parse::<custom_keywords::date>()?;
+                &mut date
+            } else {
+                return Err(lookahead.error());
+            };
+            content.parse::<Token![=]>()?;
+            let value: LitStr = content.parse()?;
+
+            if field.replace(value.clone()).is_some() {
+                return Err(syn::Error::new(value.span(), "duplicate audit field"));
+            }
+
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        let missing = |field| {
+            syn::Error::new(
+                audited_keyword.span,
+                format!("missing `{} = \"...\"` in `audited`", field),
+            )
+        };
+
+        Ok(Audited {
+            _comma: comma,
+            by: by.ok_or_else(|| missing("by"))?,
+            date: date.ok_or_else(|| missing("date"))?,
+            _audited_keyword: audited_keyword,
+            _parentheses: parentheses,
         })
     }
 }
 
+/// Checks whether the date has the form `YYYY-MM-DD`.
+fn is_iso_date(date: &str) -> bool {
+    let parts = date.split('-').collect::<Vec<_>>();
+    let number = |part: &str, len| {
+        if part.len() == len && part.bytes().all(|byte| byte.is_ascii_digit()) {
+            part.parse::<u32>().ok()
+        } else {
+            None
+        }
+    };
+
+    match parts[..] {
+        [year, month, day] => {
+            number(year, 4).is_some()
+                && matches!(number(month, 2), Some(1..=12))
+                && matches!(number(day, 2), Some(1..=31))
+        }
+        _ => false,
+    }
+}
+
 /// The attributes of a call expression.
 pub(crate) struct CallAttributes {
     /// The span best representing all the attributes.
@@ -170,6 +322,40 @@ pub(crate) fn remove_call_attributes(attributes: &mut Vec<Attribute>) -> Option<
     })
 }
 
+/// Warns about call-site attributes of other crates that are named like the ones of `pre`.
+///
+/// These attributes are not handled by `pre`, which is easily overlooked.
+pub(crate) fn check_foreign_call_attributes(attributes: &[Attribute], lints: &LintLevels) {
+    for attribute in attributes {
+        for target_attr in &["assure", "forward"] {
+            let path = match foreign_attr_path(target_attr, attribute) {
+                Some(path) => path,
+                None => continue,
+            };
+
+            if let Some(diagnostic) = lints.diagnostic(
+                Lint::ForeignAttribute,
+                path.span(),
+                format!(
+                    "`{}` is not handled by `pre`",
+                    quote! { #path }.to_string().replace(' ', "")
+                ),
+            ) {
+                diagnostic
+                    .note(format!(
+                        "only `{0}` and `{1}::{0}` are recognized as `pre` attributes",
+                        target_attr, *CRATE_NAME
+                    ))
+                    .help(format!(
+                        "if this should be handled by `pre`, use `{}` instead",
+                        target_attr
+                    ))
+                    .emit();
+            }
+        }
+    }
+}
+
 /// Renders the call using the found attributes for it.
 pub(crate) fn render_call(
     CallAttributes {
@@ -178,69 +364,595 @@ pub(crate) fn render_call(
         assure_attributes,
     }: CallAttributes,
     original_call: Call,
+    lints: &LintLevels,
 ) -> Expr {
-    check_reasons(&assure_attributes);
+    check_reasons(&assure_attributes, lints);
+    check_token_values(&assure_attributes);
+    check_exported_conditions(&assure_attributes);
+    check_renamed_conditions(&assure_attributes, &original_call);
+    export_assures(&assure_attributes, &original_call);
 
-    let precondition = assure_attributes
+    let precondition: Vec<CfgPrecondition> = assure_attributes
         .into_iter()
-        .map(|attr| attr.into())
+        .flat_map(|attr| CfgPrecondition::from(attr).expand())
         .collect();
 
-    if let Some((forward, _, _)) = forward.map(|fwd| fwd.into_content()) {
+    if forward.is_none()
+        && (summarize_mismatch(&precondition, &original_call, span)
+            || suggest_condition_spelling(&precondition, &original_call))
+    {
+        let arg_first = precondition_arg_first(&original_call);
+
+        return render_placeholder(&precondition, original_call, span, arg_first).into();
+    }
+
+    if let Some((forward, _, forward_span)) = forward.map(|fwd| fwd.into_content()) {
         forward.update_call(original_call, |call| {
-            render_assure(precondition, call, span)
+            check_forward_target(&call, forward_span, lints);
+
+            let arg_first = precondition_arg_first(&call);
+
+            render_assure(precondition, call, span, arg_first)
         })
     } else {
-        let output = render_assure(precondition, original_call, span);
+        let arg_first = precondition_arg_first(&original_call);
+        let output = render_assure(precondition, original_call, span, arg_first);
 
         output.into()
     }
 }
 
+/// Warns if the call was forwarded to a function without preconditions.
+///
+/// Such a call checks nothing, which most likely means that the `forward` attribute points to the
+/// wrong location. This is only known for functions in `extern_crate` modules of the current
+/// crate that were expanded before the call.
+fn check_forward_target(call: &Call, forward_span: Span, lints: &LintLevels) {
+    let has_preconditions = call
+        .path()
+        .and_then(|path| outline_has_preconditions(&path.path));
+
+    if has_preconditions != Some(false) {
+        return;
+    }
+
+    if let Some(diagnostic) = lints.diagnostic(
+        Lint::ForwardWithoutPreconditions,
+        forward_span,
+        "forwarded target has no preconditions".into(),
+    ) {
+        diagnostic
+            .help("did you point at the right `extern_crate` module?".into())
+            .emit();
+    }
+}
+
+/// Reports all preconditions that are missing or not required at the call in a single error.
+///
+/// This is only done if `summary_errors` is enabled in the `diagnostics` table of `pre.toml` and
+/// only for functions in the current crate whose definition was expanded before the call.
+/// Returns whether the error was emitted.
+fn summarize_mismatch(preconditions: &[CfgPrecondition], call: &Call, span: Span) -> bool {
+    if !summary_errors() {
+        return false;
+    }
+
+    let (name, definition, required) = match known_contract(call) {
+        Some(contract) => contract,
+        None => return false,
+    };
+
+    let assured = preconditions
+        .iter()
+        .map(CfgPrecondition::precondition)
+        .collect::<Vec<_>>();
+    let (missing, extra) = mismatched_preconditions(&required, &assured);
+
+    if missing.is_empty() && extra.is_empty() {
+        return false;
+    }
+
+    let mut diagnostic = Diagnostic::spanned(
+        span,
+        Level::Error,
+        format!(
+            "the assured preconditions do not match the preconditions of `{}`",
+            name
+        ),
+    )
+    .note(format!(
+        "`{}` has the following preconditions: {}",
+        name,
+        definition.preconditions.join(", ")
+    ));
+
+    if !missing.is_empty() {
+        diagnostic = diagnostic
+            .note(format!(
+                "missing: {}",
+                missing
+                    .iter()
+                    .map(|&i| format!("`{}`", definition.preconditions[i]))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .help(format!(
+                "add the following attributes and replace the reasons:\n{}",
+                missing
+                    .iter()
+                    .map(|&i| &*definition.skeleton[i])
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+    }
+
+    if !extra.is_empty() {
+        diagnostic = diagnostic
+            .note(format!(
+                "not required: {}",
+                extra
+                    .iter()
+                    .map(|precondition| format!("`{}`", precondition))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .help(
+                "remove the `assure` attributes of the preconditions that are not required".into(),
+            );
+    }
+
+    diagnostic.emit();
+
+    true
+}
+
+/// Returns the name, the definition and the parsed preconditions of the called function.
+///
+/// This is only known for functions in the current crate whose definition was expanded before the
+/// call, if the path of the call does not possibly refer to another function with the same name.
+fn known_contract(call: &Call) -> Option<(String, metadata::Definition, Vec<Precondition>)> {
+    if !call.is_function() {
+        return None;
+    }
+
+    let name = call.name()?;
+    let definition = metadata::callee_definition(&call.path()?.path)
+        .filter(|definition| definition.arity == call.args().len())?;
+    let required = definition
+        .preconditions
+        .iter()
+        .map(|precondition| parse_str::<Precondition>(precondition))
+        .collect::<syn::Result<Vec<_>>>()
+        .ok()?;
+
+    Some((name, definition, required))
+}
+
+/// Suggests the exact text of a custom precondition, if an assured custom precondition differs
+/// from it only slightly.
+///
+/// Otherwise the compiler reports the mismatch in terms of the encoded preconditions, which makes
+/// spotting a typo hard. Returns whether the suggestions cover all mismatched preconditions, in
+/// which case the compiler errors are not needed.
+fn suggest_condition_spelling(preconditions: &[CfgPrecondition], call: &Call) -> bool {
+    let (name, _, required) = match known_contract(call) {
+        Some(contract) => contract,
+        None => return false,
+    };
+
+    let assured = preconditions
+        .iter()
+        .map(CfgPrecondition::precondition)
+        .collect::<Vec<_>>();
+    let (mut missing, extra) = mismatched_preconditions(&required, &assured);
+    let mut suggested = false;
+    let mut unexplained = false;
+
+    for precondition in extra {
+        let text = match precondition {
+            Precondition::Custom(text) => text.value(),
+            _ => {
+                unexplained = true;
+                continue;
+            }
+        };
+
+        let closest = missing
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &index)| match &required[index] {
+                Precondition::Custom(expected) => {
+                    let expected = expected.value();
+                    let distance = edit_distance(&text, &expected);
+
+                    if distance <= max_typo_distance(&expected) {
+                        Some((distance, i, expected))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+            .min_by_key(|(distance, _, _)| *distance);
+
+        if let Some((_, i, expected)) = closest {
+            missing.remove(i);
+            suggested = true;
+
+            emit_error!(
+                precondition.span(),
+                "`{}` has no precondition {:?}", name, text;
+                help = "did you mean: {:?}", expected
+            );
+        } else {
+            unexplained = true;
+        }
+    }
+
+    suggested && !unexplained && missing.is_empty()
+}
+
+/// The largest edit distance to a custom precondition that is still considered a typo.
+///
+/// The distance scales with the length of the precondition, so that short preconditions are not
+/// suggested for unrelated text.
+fn max_typo_distance(expected: &str) -> usize {
+    expected.chars().count() / 6
+}
+
+/// Computes the Levenshtein distance between the two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a) in a.chars().enumerate() {
+        current[0] = i + 1;
+
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Returns the indices of the required preconditions that are not assured and the assured
+/// preconditions that are not required.
+fn mismatched_preconditions<'a>(
+    required: &[Precondition],
+    assured: &[&'a Precondition],
+) -> (Vec<usize>, Vec<&'a Precondition>) {
+    let missing = required
+        .iter()
+        .enumerate()
+        .filter(|(_, required)| !assured.contains(required))
+        .map(|(i, _)| i)
+        .collect();
+    let extra = assured
+        .iter()
+        .copied()
+        .filter(|precondition| !required.contains(precondition))
+        .collect();
+
+    (missing, extra)
+}
+
+/// Renders the call with a placeholder instead of the precondition argument.
+///
+/// This is used after the mismatched preconditions of the call were reported by
+/// `summarize_mismatch`, so that the compiler doesn't report the same mismatch again in terms of
+/// the encoding.
+fn render_placeholder(
+    preconditions: &[CfgPrecondition],
+    mut call: Call,
+    span: Span,
+    arg_first: bool,
+) -> Call {
+    let combined_cfg = combine_call_site_cfg(preconditions);
+    let crate_name = Ident::new(&CRATE_NAME, span);
+
+    call.add_precondition_arg(
+        parse2(quote_spanned! { span=>
+            #[cfg(all(not(doc), #combined_cfg))]
+            #crate_name::__mismatched_preconditions()
+        })
+        .expect("parses as an expression"),
+        arg_first,
+    );
+
+    call
+}
+
+/// Checks whether the called function takes the precondition argument first.
+///
+/// This is only known for functions in the current crate whose definition was expanded before the
+/// call.
+fn precondition_arg_first(call: &Call) -> bool {
+    call.is_function()
+        && matches!(
+            call.name().and_then(|name| metadata::definition(&name)),
+            Some(definition) if definition.precondition_arg_first
+        )
+}
+
 /// Checks that all reasons exist and make sense.
 ///
 /// This function emits errors, if appropriate.
-fn check_reasons(assure_attributes: &[Attr<AssureAttr>]) {
+fn check_reasons(assure_attributes: &[Attr<AssureAttr>], lints: &LintLevels) {
     for assure_attribute in assure_attributes.iter() {
         match assure_attribute.content() {
             AssureAttr::WithReason { reason, .. } => {
-                if let Some(reason) = unfinished_reason(&reason.reason) {
-                    emit_warning!(
-                        reason,
-                        "you should specify a different here";
-                        help = "specifying a meaningful reason will help you and others understand why this is ok in the future"
-                    )
-                } else if reason.reason.value() == HINT_REASON {
+                if let Some(lint) = unfinished_reason(&reason.reason) {
+                    if let Some(diagnostic) = lints.diagnostic(
+                        lint,
+                        reason.reason.span(),
+                        Message::UnfinishedReason.text().into(),
+                    ) {
+                        diagnostic.help(Message::ReasonHelp.text().into()).emit();
+                    }
+                } else if reason.reason.value() == hint_reason() {
                     let todo_help_msg = if cfg!(nightly) {
-                        Some("using `TODO` here will emit a warning, reminding you to fix this later")
+                        Some(Message::TodoHelp.text())
                     } else {
                         None
                     };
 
                     emit_error!(
                         reason.reason,
-                        "you need to specify a different reason here";
-                        help = "specifying a meaningful reason will help you and others understand why this is ok in the future";
+                        Message::HintReason.text();
+                        help = Message::ReasonHelp.text();
                         help =? todo_help_msg
                     )
                 }
+
+                if let Some(audit) = &reason.audit {
+                    if audit.reference.value().trim().is_empty() {
+                        emit_error!(
+                            audit.reference,
+                            "the audit reference is empty";
+                            help = "reference the audit of the reason, such as a ticket ID, or remove it"
+                        );
+                    }
+                }
+
+                if let Some(audited) = &reason.audited {
+                    if audited.by.value().trim().is_empty() {
+                        emit_error!(
+                            audited.by,
+                            "the reviewer of the audit is empty";
+                            help = "name the person or team that reviewed the reason"
+                        );
+                    }
+
+                    if !is_iso_date(&audited.date.value()) {
+                        emit_error!(
+                            audited.date,
+                            "the audit date {:?} is not of the form `YYYY-MM-DD`", audited.date.value();
+                            help = "write the date of the review like \"2024-06-01\""
+                        );
+                    }
+                }
             }
+            // Tokens are checked by the type system, so they don't require a reason.
+            AssureAttr::WithoutReason {
+                precondition: Precondition::Token { .. },
+            } => (),
             AssureAttr::WithoutReason { precondition } => emit_error!(
                 precondition.span(),
-                "you need to specify a reason why this precondition holds";
-                help = "add `, reason = {:?}`", HINT_REASON
+                Message::MissingReason.text();
+                help = "add `, reason = {:?}`", hint_reason()
             ),
         }
     }
 }
 
-/// Returns an unfinished reason declaration for the precondition if one exists.
-fn unfinished_reason(reason: &LitStr) -> Option<&LitStr> {
+/// Checks that all assured tokens have a value.
+///
+/// This function emits errors, if appropriate.
+fn check_token_values(assure_attributes: &[Attr<AssureAttr>]) {
+    for assure_attribute in assure_attributes.iter() {
+        let precondition = match assure_attribute.content() {
+            AssureAttr::WithReason { precondition, .. } => precondition,
+            AssureAttr::WithoutReason { precondition } => precondition,
+        };
+
+        if let Precondition::Token {
+            ty, value: None, ..
+        } = precondition
+        {
+            emit_error!(
+                precondition.span(),
+                Message::MissingTokenValue.text();
+                help = "add ` = <token value>` after `{}`", quote! { #ty }
+            );
+        }
+    }
+}
+
+/// Checks that no conditions are exported when assuring them.
+///
+/// This function emits errors, if appropriate.
+fn check_exported_conditions(assure_attributes: &[Attr<AssureAttr>]) {
+    for assure_attribute in assure_attributes.iter() {
+        let precondition = match assure_attribute.content() {
+            AssureAttr::WithReason { precondition, .. } => precondition,
+            AssureAttr::WithoutReason { precondition } => precondition,
+        };
+
+        if let Precondition::Exported { ident, .. } = precondition {
+            emit_error!(
+                precondition.span(),
+                "conditions can only be exported where they are required";
+                help = "try `use {}` instead", ident
+            );
+        }
+    }
+}
+
+/// Checks that no custom conditions are assured that were renamed using `rename_condition!`.
+///
+/// This function emits errors, if appropriate.
+fn check_renamed_conditions(assure_attributes: &[Attr<AssureAttr>], call: &Call) {
+    for assure_attribute in assure_attributes.iter() {
+        let precondition = match assure_attribute.content() {
+            AssureAttr::WithReason { precondition, .. } => precondition,
+            AssureAttr::WithoutReason { precondition } => precondition,
+        };
+
+        if let Precondition::Custom(condition) = precondition {
+            let old = condition.value();
+
+            if let Some(new) = register_assured_condition(&old, quote! { #call }.to_string()) {
+                emit_error!(
+                    condition,
+                    "the condition {:?} was renamed to {:?}", old, new;
+                    help = "replace it with {:?}", new
+                );
+            }
+        }
+    }
+}
+
+/// Exports the assured preconditions together with their reasons.
+fn export_assures(assure_attributes: &[Attr<AssureAttr>], call: &Call) {
+    let function = call.name();
+    let contract_id = function
+        .as_ref()
+        .and_then(|function| metadata::definition(function))
+        .map(|definition| definition.contract_id);
+    let call = quote! { #call }.to_string();
+
+    for assure_attribute in assure_attributes.iter() {
+        if let AssureAttr::WithReason {
+            precondition,
+            reason,
+            ..
+        } = assure_attribute.content()
+        {
+            metadata::export(Record::Assure {
+                function: function.as_deref(),
+                call: &call,
+                precondition: &precondition.to_string(),
+                reason: &reason.reason.value(),
+                audit: reason
+                    .audit
+                    .as_ref()
+                    .map(|audit| audit.reference.value())
+                    .as_deref(),
+                audited_by: reason
+                    .audited
+                    .as_ref()
+                    .map(|audited| audited.by.value())
+                    .as_deref(),
+                audited_date: reason
+                    .audited
+                    .as_ref()
+                    .map(|audited| audited.date.value())
+                    .as_deref(),
+                contract_id: contract_id.as_deref(),
+            });
+        }
+    }
+}
+
+/// Returns the lint for the reason, if it is unfinished.
+fn unfinished_reason(reason: &LitStr) -> Option<Lint> {
     let mut reason_val = reason.value();
 
     reason_val.make_ascii_lowercase();
     match &*reason_val {
-        "todo" | "?" | "" => Some(reason),
+        "todo" => Some(Lint::TodoReason),
+        "?" | "" => Some(Lint::UnfinishedReason),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mismatched_preconditions_are_found() {
+        let parse = |precondition: &str| parse_str::<Precondition>(precondition).expect("valid");
+
+        let required = [
+            parse("valid_ptr(p, r)"),
+            parse("x > 0"),
+            parse("\"is initialized\""),
+        ];
+        let assured = [
+            parse("\"is initialized\""),
+            parse("y > 0"),
+            parse("valid_ptr(p, r)"),
+        ];
+        let assured = assured.iter().collect::<Vec<_>>();
+
+        let (missing, extra) = mismatched_preconditions(&required, &assured);
+        assert_eq!(missing, [1]);
+        assert_eq!(
+            extra.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["y > 0"]
+        );
+
+        let (missing, extra) = mismatched_preconditions(&required, &assured[..1]);
+        assert_eq!(missing, [0, 1]);
+        assert!(extra.is_empty());
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("`p` is vaild", "`p` is valid"), 2);
+        assert_eq!(edit_distance("is initialised", "is initialized"), 1);
+        assert_eq!(edit_distance("äb", "ab"), 1);
+
+        assert_eq!(max_typo_distance("foo"), 0);
+        assert_eq!(max_typo_distance("`p` is valid"), 2);
+        assert_eq!(
+            max_typo_distance("the memory is not mutated for the duration of `'a`"),
+            8
+        );
+    }
+
+    #[test]
+    fn parse_audited() {
+        let parse = |tokens| match parse2::<AssureAttr>(tokens) {
+            Ok(AssureAttr::WithReason { reason, .. }) => reason.audited,
+            _ => panic!("expected an assure attribute with a reason"),
+        };
+
+        let audited = parse(quote! {
+            x > 0, reason = "valid", audit = "SEC-1", audited(date = "2024-06-01", by = "alice")
+        })
+        .expect("audited is given");
+        assert_eq!(audited.by.value(), "alice");
+        assert_eq!(audited.date.value(), "2024-06-01");
+
+        assert!(parse(quote! { x > 0, reason = "valid" }).is_none());
+        assert!(
+            parse2::<AssureAttr>(quote! { x > 0, reason = "valid", audited(by = "alice") })
+                .is_err()
+        );
+        assert!(parse2::<AssureAttr>(
+            quote! { x > 0, reason = "valid", audited(by = "a", by = "b", date = "2024-06-01") }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn iso_dates() {
+        assert!(is_iso_date("2024-06-01"));
+        assert!(is_iso_date("1999-12-31"));
+        assert!(!is_iso_date("2024-13-01"));
+        assert!(!is_iso_date("2024-06-00"));
+        assert!(!is_iso_date("2024-6-1"));
+        assert!(!is_iso_date("01.06.2024"));
+        assert!(!is_iso_date("2024-06-01-01"));
+    }
+}
@@ -44,10 +44,13 @@ use syn::{
     parse2,
     punctuated::Pair,
     spanned::Spanned,
-    Expr, ExprCall, ExprPath, Ident, Path, Token,
+    Expr, ExprCall, ExprPath, Generics, Ident, Path, Token,
 };
 
-use crate::{call::Call, extern_crate::impl_block_stub_name};
+use crate::{
+    call::Call,
+    extern_crate::{impl_block_stub_name, trait_impl_stub_name},
+};
 
 /// The content of a `forward` attribute.
 ///
@@ -65,7 +68,11 @@ pub(crate) enum ForwardAttr {
         /// The `impl` keyword that disambiguates this from a direct forward attribute.
         impl_keyword: Token![impl],
         /// The path to the impl block.
+        ///
+        /// For trait implementations this is the path to the trait.
         path: Path,
+        /// The type implementing the trait, if the impl block is a trait implementation.
+        self_ty: Option<(Token![for], Path)>,
     },
     /// The function to be called is found by replacing `from` with `to` in the path.
     Replace {
@@ -88,11 +95,20 @@ impl Parse for ForwardAttr {
 
         let first_path = input.parse()?;
 
+        if let (Some(impl_keyword), true) = (impl_keyword, input.peek(Token![for])) {
+            return Ok(ForwardAttr::ImplBlock {
+                impl_keyword,
+                path: first_path,
+                self_ty: Some((input.parse()?, input.parse()?)),
+            });
+        }
+
         Ok(if input.is_empty() {
             if let Some(impl_keyword) = impl_keyword {
                 ForwardAttr::ImplBlock {
                     impl_keyword,
                     path: first_path,
+                    self_ty: None,
                 }
             } else {
                 ForwardAttr::Direct { path: first_path }
@@ -114,10 +130,18 @@ impl Spanned for ForwardAttr {
     fn span(&self) -> Span {
         match self {
             ForwardAttr::Direct { path } => path.span(),
-            ForwardAttr::ImplBlock { impl_keyword, path } => impl_keyword
-                .span
-                .join(path.span())
-                .unwrap_or_else(|| path.span()),
+            ForwardAttr::ImplBlock {
+                impl_keyword,
+                path,
+                self_ty,
+            } => {
+                let end = match self_ty {
+                    Some((_, self_ty)) => self_ty.span(),
+                    None => path.span(),
+                };
+
+                impl_keyword.span.join(end).unwrap_or_else(|| path.span())
+            }
             ForwardAttr::Replace { from, to, .. } => {
                 from.span().join(to.span()).unwrap_or_else(|| to.span())
             }
@@ -128,6 +152,17 @@ impl Spanned for ForwardAttr {
 impl ForwardAttr {
     /// Updates the call to use the forwarded location.
     pub(super) fn update_call(self, mut call: Call, render: impl FnOnce(Call) -> Call) -> Expr {
+        if let Call::Adapted(call, adapter) = call {
+            let expr = self.update_call(*call, render);
+
+            // The updated call may be an `if` expression, which cannot be adapted directly.
+            return if let Expr::If(_) = expr {
+                adapter.adapt(parse2(quote! { (#expr) }).expect("valid expression"))
+            } else {
+                adapter.adapt(expr)
+            };
+        }
+
         let original_call = call.clone();
         let span = self.span();
 
@@ -158,14 +193,16 @@ impl ForwardAttr {
                             }
                         }
                     }
-                    ForwardAttr::ImplBlock { path, .. } => {
+                    ForwardAttr::ImplBlock { path, self_ty, .. } => {
                         let fn_name = if let Some(segment) = fn_path.path.segments.last() {
                             &segment.ident
                         } else {
                             return original_call.into();
                         };
 
-                        let rendered_call = render(create_empty_call(path, fn_name).into());
+                        let self_ty = self_ty.map(|(_, self_ty)| self_ty);
+                        let rendered_call =
+                            render(create_empty_call(path, self_ty.as_ref(), fn_name).into());
 
                         quote_spanned! { span=>
                             if true {
@@ -181,8 +218,26 @@ impl ForwardAttr {
                 .expect("valid expression")
             }
             Call::Method(method_call) => match self {
-                ForwardAttr::ImplBlock { path, .. } | ForwardAttr::Direct { path, .. } => {
-                    let rendered_call = render(create_empty_call(path, &method_call.method).into());
+                ForwardAttr::ImplBlock { path, self_ty, .. } => {
+                    let self_ty = self_ty.map(|(_, self_ty)| self_ty);
+                    let rendered_call = render(
+                        create_empty_call(path, self_ty.as_ref(), &method_call.method).into(),
+                    );
+
+                    parse2(quote_spanned! { span=>
+                        if true {
+                            #original_call
+                        } else {
+                            #rendered_call;
+
+                            unreachable!()
+                        }
+                    })
+                    .expect("valid expression")
+                }
+                ForwardAttr::Direct { path, .. } => {
+                    let rendered_call =
+                        render(create_empty_call(path, None, &method_call.method).into());
 
                     parse2(quote_spanned! { span=>
                         if true {
@@ -208,6 +263,7 @@ impl ForwardAttr {
                     original_call.into()
                 }
             },
+            Call::Adapted(..) => unreachable!("adapted calls are updated above"),
         }
     }
 
@@ -217,6 +273,10 @@ impl ForwardAttr {
 
         match self {
             ForwardAttr::Direct { ref path, .. } => {
+                // The forwarded path is now relative to the given path, so only the given path can
+                // be absolute.
+                resulting_path.path.leading_colon = path.leading_colon;
+
                 for (i, segment) in path.segments.iter().enumerate() {
                     resulting_path.path.segments.insert(i, segment.clone());
                 }
@@ -224,11 +284,28 @@ impl ForwardAttr {
             ForwardAttr::ImplBlock { .. } => {
                 unreachable!("`construct_new_path` is never called for an `impl` forward attribute")
             }
-            ForwardAttr::Replace { from, to, .. } => {
+            ForwardAttr::Replace { from, mut to, .. } => {
                 if !check_prefix(&from, &fn_path.path) {
                     return resulting_path;
                 }
 
+                // Keep the generic arguments of the replaced segments, such as in `foo::<u8>`, if
+                // the replacement doesn't specify its own.
+                let replaced_arguments = fn_path
+                    .path
+                    .segments
+                    .iter()
+                    .nth(from.segments.len() - 1)
+                    .map(|segment| &segment.arguments);
+                if let (Some(arguments), Some(last_segment)) =
+                    (replaced_arguments, to.segments.last_mut())
+                {
+                    if last_segment.arguments.is_empty() {
+                        last_segment.arguments = arguments.clone();
+                    }
+                }
+
+                resulting_path.path.leading_colon = to.leading_colon;
                 resulting_path.path.segments = to
                     .segments
                     .into_pairs()
@@ -254,10 +331,37 @@ impl ForwardAttr {
 }
 
 /// Creates an empty call to the given function.
-fn create_empty_call(mut path: Path, fn_name: &Ident) -> ExprCall {
+///
+/// If `self_ty` is given, `path` is the path of the trait implemented for it.
+fn create_empty_call(mut path: Path, self_ty: Option<&Path>, fn_name: &Ident) -> ExprCall {
     if let Some(segment_pair) = path.segments.pop() {
-        path.segments
-            .push(impl_block_stub_name(segment_pair.value(), fn_name, path.span()).into());
+        let span = path.span();
+        let stub_name = match self_ty.and_then(|self_ty| self_ty.segments.last()) {
+            Some(ty) => trait_impl_stub_name(
+                segment_pair.value(),
+                ty,
+                &Generics::default(),
+                fn_name,
+                span,
+            ),
+            None => {
+                // The stub is defined in the module in front of the type.
+                let module = path
+                    .segments
+                    .last()
+                    .map_or_else(String::new, |module| module.ident.to_string());
+
+                impl_block_stub_name(
+                    &module,
+                    segment_pair.value(),
+                    &Generics::default(),
+                    fn_name,
+                    span,
+                )
+            }
+        };
+
+        path.segments.push(stub_name.into());
     } else {
         abort!(path, "path must have at least one segment");
     }
@@ -278,6 +382,8 @@ fn create_empty_call(mut path: Path, fn_name: &Ident) -> ExprCall {
 }
 
 /// Checks if the path is a prefix and emits errors, if it isn't.
+///
+/// Generic arguments in the path are only compared if the prefix specifies them as well.
 fn check_prefix(possible_prefix: &Path, path: &Path) -> bool {
     if possible_prefix.segments.len() > path.segments.len() {
         emit_error!(
@@ -292,7 +398,10 @@ fn check_prefix(possible_prefix: &Path, path: &Path) -> bool {
 
     for (prefix_segment, path_segment) in possible_prefix.segments.iter().zip(path.segments.iter())
     {
-        if prefix_segment != path_segment {
+        if prefix_segment.ident != path_segment.ident
+            || (!prefix_segment.arguments.is_empty()
+                && prefix_segment.arguments != path_segment.arguments)
+        {
             emit_error!(
                 path,
                 "cannot replace `{}` in this path",
@@ -317,3 +426,79 @@ fn punctuate_end<T, P: Default>(pair: Pair<T, P>) -> Pair<T, P> {
         Pair::Punctuated(elem, punct) => Pair::Punctuated(elem, punct),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    /// Forwards the path using the given `forward` attribute content.
+    fn forward(attr: proc_macro2::TokenStream, path: proc_macro2::TokenStream) -> String {
+        let attr: ForwardAttr = parse2(attr).expect("valid forward attribute");
+        let path: ExprPath = parse2(path).expect("valid path");
+        let new_path = attr.construct_new_path(&path);
+
+        quote! { #new_path }.to_string()
+    }
+
+    #[test]
+    fn keeps_generic_arguments() {
+        assert_eq!(
+            forward(quote! { pre_std }, quote! { std::ptr::read::<u8> }),
+            quote! { pre_std::std::ptr::read::<u8> }.to_string()
+        );
+        assert_eq!(
+            forward(quote! { std -> pre_std }, quote! { std::ptr::read::<u8> }),
+            quote! { pre_std::ptr::read::<u8> }.to_string()
+        );
+        assert_eq!(
+            forward(
+                quote! { std::ptr::read -> pre_std::ptr::read },
+                quote! { std::ptr::read::<u8> }
+            ),
+            quote! { pre_std::ptr::read::<u8> }.to_string()
+        );
+        assert_eq!(
+            forward(
+                quote! { read -> checked::read::<u16> },
+                quote! { read::<u8> }
+            ),
+            quote! { checked::read::<u16> }.to_string()
+        );
+        assert_eq!(
+            forward(quote! { Vec -> checked::Vec }, quote! { Vec::<u8>::new }),
+            quote! { checked::Vec::<u8>::new }.to_string()
+        );
+    }
+
+    #[test]
+    fn keeps_leading_colon_of_forward_target() {
+        assert_eq!(
+            forward(quote! { ::pre_std }, quote! { std::ptr::read }),
+            quote! { ::pre_std::std::ptr::read }.to_string()
+        );
+        assert_eq!(
+            forward(quote! { crate::pre_std }, quote! { ::std::ptr::read }),
+            quote! { crate::pre_std::std::ptr::read }.to_string()
+        );
+        assert_eq!(
+            forward(
+                quote! { ::std -> crate::pre_std },
+                quote! { ::std::ptr::read }
+            ),
+            quote! { crate::pre_std::ptr::read }.to_string()
+        );
+        assert_eq!(
+            forward(quote! { std -> ::pre_std }, quote! { std::ptr::read }),
+            quote! { ::pre_std::ptr::read }.to_string()
+        );
+        assert_eq!(
+            forward(
+                quote! { std -> crate::pre_std },
+                quote! { ::std::ptr::read }
+            ),
+            quote! { crate::pre_std::ptr::read }.to_string()
+        );
+    }
+}
@@ -0,0 +1,514 @@
+//! Allows configuring the texts used by `pre` for a crate.
+//!
+//! The configuration is read from a `pre.toml` file next to the `Cargo.toml` of the crate being
+//! compiled. All keys are optional:
+//!
+//! ```toml
+//! # The placeholder reason shown in generated documentation and suggestions.
+//! hint_reason = "<why is this ok?>"
+//!
+//! # Replacements for the messages emitted by `pre`.
+//! [messages]
+//! missing_reason = "a reason is required here"
+//!
+//! # Defaults for the flags of `pre` attributes.
+//! [defaults]
+//! no_doc = true
+//! no_debug_assert = true
+//!
+//! # Whether violated preconditions panic after being logged.
+//! [logging]
+//! panic = false
+//!
+//! # Whether mismatched preconditions at a call are reported as a single error.
+//! [diagnostics]
+//! summary_errors = true
+//!
+//! # Additional kinds of preconditions, such as `dma_capable(buf, len)`.
+//! [conditions.dma_capable]
+//! args = ["ptr", "len"]
+//! doc = "`{ptr}` must point to `{len}` bytes of DMA-capable memory"
+//! check = "crate::dma::is_dma_capable({ptr} as *const u8, {len})"
+//! ```
+//!
+//! The available message keys are the names of the variants of `Message` in `snake_case`.
+
+use lazy_static::lazy_static;
+use proc_macro_error::abort_call_site;
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+use syn::{Expr, Ident};
+use toml::Value;
+
+use crate::precondition::BUILTIN_KINDS;
+
+/// The name of the configuration file.
+const CONFIG_FILE: &str = "pre.toml";
+
+/// The reason to display in examples on how to use reasons, if none is configured.
+const DEFAULT_HINT_REASON: &str = "<specify the reason why you can assure this here>";
+
+lazy_static! {
+    /// The configuration of the crate currently being compiled.
+    static ref CONFIG: Config = {
+        match read_config() {
+            Ok(config) => config,
+            Err(err) => abort_call_site!("invalid `{}`: {}", CONFIG_FILE, err),
+        }
+    };
+}
+
+/// A message emitted by `pre` that can be configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Message {
+    /// An `assure` attribute is missing a reason.
+    MissingReason,
+    /// An `assure` attribute uses the hint reason.
+    HintReason,
+    /// An `assure` attribute uses a reason that is unfinished, such as `TODO`.
+    UnfinishedReason,
+    /// Help on why a meaningful reason should be specified.
+    ReasonHelp,
+    /// Help on using `TODO` as a reason.
+    TodoHelp,
+    /// A token precondition is assured without a token value.
+    MissingTokenValue,
+}
+
+impl Message {
+    /// All messages that exist.
+    const ALL: [Message; 6] = [
+        Message::MissingReason,
+        Message::HintReason,
+        Message::UnfinishedReason,
+        Message::ReasonHelp,
+        Message::TodoHelp,
+        Message::MissingTokenValue,
+    ];
+
+    /// The key of the message in the configuration file.
+    fn key(self) -> &'static str {
+        match self {
+            Message::MissingReason => "missing_reason",
+            Message::HintReason => "hint_reason",
+            Message::UnfinishedReason => "unfinished_reason",
+            Message::ReasonHelp => "reason_help",
+            Message::TodoHelp => "todo_help",
+            Message::MissingTokenValue => "missing_token_value",
+        }
+    }
+
+    /// The text used for the message, if it is not configured.
+    fn default_text(self) -> &'static str {
+        match self {
+            Message::MissingReason => "you need to specify a reason why this precondition holds",
+            Message::HintReason => "you need to specify a different reason here",
+            Message::UnfinishedReason => "you should specify a different reason here",
+            Message::ReasonHelp => "specifying a meaningful reason will help you and others understand why this is ok in the future",
+            Message::TodoHelp => "using `TODO` here will emit a warning, reminding you to fix this later",
+            Message::MissingTokenValue => "you need to specify the token that should be passed",
+        }
+    }
+
+    /// The text of the message.
+    pub(crate) fn text(self) -> &'static str {
+        CONFIG.messages[self as usize]
+            .as_deref()
+            .unwrap_or_else(|| self.default_text())
+    }
+}
+
+/// The configuration of a crate.
+#[derive(Debug, Default)]
+struct Config {
+    /// The configured hint reason.
+    hint_reason: Option<String>,
+    /// The configured text of each message.
+    messages: [Option<String>; Message::ALL.len()],
+    /// Whether documentation should not be generated by default.
+    no_doc: bool,
+    /// Whether debug assertions should not be generated by default.
+    no_debug_assert: bool,
+    /// Whether violated preconditions should not panic after being logged.
+    log_only: bool,
+    /// Whether mismatched preconditions at a call should be reported as a single error.
+    summary_errors: bool,
+    /// The configured kinds of preconditions by their names.
+    conditions: BTreeMap<String, ConditionKind>,
+}
+
+/// A kind of precondition that is configured for a crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConditionKind {
+    /// The names of the arguments of the precondition.
+    pub(crate) args: Vec<String>,
+    /// The description of the precondition, with `{<arg>}` placeholders for the arguments.
+    pub(crate) doc: String,
+    /// A boolean expression checking the precondition, with the same placeholders.
+    pub(crate) check: Option<String>,
+}
+
+impl ConditionKind {
+    /// Returns the description of the precondition for the given arguments.
+    pub(crate) fn description(&self, args: &[String]) -> String {
+        fill_in_args(&self.doc, &self.args, args)
+    }
+
+    /// Returns a boolean expression checking the precondition for the given arguments, if one is
+    /// configured.
+    pub(crate) fn check(&self, args: &[String]) -> Option<Expr> {
+        self.check.as_ref().map(|check| {
+            syn::parse_str(&fill_in_args(check, &self.args, args))
+                .expect("checks are validated when reading the configuration")
+        })
+    }
+}
+
+/// Replaces the `{<name>}` placeholders in the template by the corresponding values.
+fn fill_in_args(template: &str, names: &[String], values: &[String]) -> String {
+    names
+        .iter()
+        .zip(values)
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Returns the placeholder reason to display in examples on how to use reasons.
+pub(crate) fn hint_reason() -> &'static str {
+    CONFIG.hint_reason.as_deref().unwrap_or(DEFAULT_HINT_REASON)
+}
+
+/// Returns whether documentation should be generated for items without `no_doc` or `doc` flags.
+pub(crate) fn render_docs_by_default() -> bool {
+    !CONFIG.no_doc
+}
+
+/// Returns whether debug assertions should be generated for functions without `no_debug_assert`
+/// or `debug_assert` flags.
+pub(crate) fn debug_assert_by_default() -> bool {
+    !CONFIG.no_debug_assert
+}
+
+/// Returns whether violated preconditions should panic after being logged.
+pub(crate) fn panic_after_logging() -> bool {
+    !CONFIG.log_only
+}
+
+/// Returns whether mismatched preconditions at a call should be reported as a single error.
+pub(crate) fn summary_errors() -> bool {
+    CONFIG.summary_errors
+}
+
+/// Returns the configured kind of precondition with the given name, if there is one.
+pub(crate) fn condition_kind(name: &str) -> Option<&'static ConditionKind> {
+    CONFIG.conditions.get(name)
+}
+
+/// Reads the configuration file of the crate currently being compiled, if there is one.
+fn read_config() -> Result<Config, String> {
+    let path = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir).join(CONFIG_FILE),
+        None => return Ok(Config::default()),
+    };
+
+    match fs::read_to_string(path) {
+        Ok(content) => parse_config(&content),
+        Err(_) => Ok(Config::default()),
+    }
+}
+
+/// Parses the content of a configuration file.
+fn parse_config(content: &str) -> Result<Config, String> {
+    let value: Value = content.parse().map_err(|err| format!("{}", err))?;
+    let mut config = Config::default();
+
+    let table = match value {
+        Value::Table(table) => table,
+        _ => return Err("expected a table".into()),
+    };
+
+    for (key, value) in table {
+        match (&*key, value) {
+            ("hint_reason", Value::String(reason)) => config.hint_reason = Some(reason),
+            ("messages", Value::Table(messages)) => {
+                for (key, value) in messages {
+                    let message = Message::ALL
+                        .iter()
+                        .find(|message| message.key() == key)
+                        .ok_or_else(|| {
+                            let known_keys = Message::ALL
+                                .iter()
+                                .map(|message| format!("`{}`", message.key()))
+                                .collect::<Vec<_>>()
+                                .join(", ");
+
+                            format!("unknown message `{}`, expected one of {}", key, known_keys)
+                        })?;
+
+                    match value {
+                        Value::String(text) => config.messages[*message as usize] = Some(text),
+                        _ => return Err(format!("expected a string for message `{}`", key)),
+                    }
+                }
+            }
+            ("defaults", Value::Table(defaults)) => {
+                for (key, value) in defaults {
+                    let flag = match &*key {
+                        "no_doc" => &mut config.no_doc,
+                        "no_debug_assert" => &mut config.no_debug_assert,
+                        _ => {
+                            return Err(format!(
+                                "unknown default `{}`, expected `no_doc` or `no_debug_assert`",
+                                key
+                            ))
+                        }
+                    };
+
+                    match value {
+                        Value::Boolean(value) => *flag = value,
+                        _ => return Err(format!("expected a boolean for default `{}`", key)),
+                    }
+                }
+            }
+            ("logging", Value::Table(logging)) => {
+                for (key, value) in logging {
+                    match (&*key, value) {
+                        ("panic", Value::Boolean(panic)) => config.log_only = !panic,
+                        ("panic", _) => return Err("expected a boolean for `logging.panic`".into()),
+                        (key, _) => {
+                            return Err(format!(
+                                "unknown logging option `{}`, expected `panic`",
+                                key
+                            ))
+                        }
+                    }
+                }
+            }
+            ("diagnostics", Value::Table(diagnostics)) => {
+                for (key, value) in diagnostics {
+                    match (&*key, value) {
+                        ("summary_errors", Value::Boolean(summary_errors)) => {
+                            config.summary_errors = summary_errors
+                        }
+                        ("summary_errors", _) => {
+                            return Err("expected a boolean for `diagnostics.summary_errors`".into())
+                        }
+                        (key, _) => {
+                            return Err(format!(
+                                "unknown diagnostics option `{}`, expected `summary_errors`",
+                                key
+                            ))
+                        }
+                    }
+                }
+            }
+            ("conditions", Value::Table(conditions)) => {
+                for (name, value) in conditions {
+                    let kind = match value {
+                        Value::Table(kind) => parse_condition_kind(&name, kind)?,
+                        _ => return Err(format!("expected a table for condition `{}`", name)),
+                    };
+
+                    config.conditions.insert(name, kind);
+                }
+            }
+            ("hint_reason", _) => return Err("expected a string for `hint_reason`".into()),
+            ("messages", _) => return Err("expected a table for `messages`".into()),
+            ("defaults", _) => return Err("expected a table for `defaults`".into()),
+            ("logging", _) => return Err("expected a table for `logging`".into()),
+            ("diagnostics", _) => return Err("expected a table for `diagnostics`".into()),
+            ("conditions", _) => return Err("expected a table for `conditions`".into()),
+            (key, _) => {
+                return Err(format!(
+                    "unknown key `{}`, expected `hint_reason`, `messages`, `defaults`, `logging`, `diagnostics` or `conditions`",
+                    key
+                ))
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+/// Parses the configuration of a kind of precondition with the given name.
+fn parse_condition_kind(name: &str, table: toml::value::Table) -> Result<ConditionKind, String> {
+    if syn::parse_str::<Ident>(name).is_err() || BUILTIN_KINDS.contains(&name) {
+        return Err(format!(
+            "`{}` cannot be used as the name of a condition, because it is not an identifier or already used by pre",
+            name
+        ));
+    }
+
+    let mut args = None;
+    let mut doc = None;
+    let mut check = None;
+
+    for (key, value) in table {
+        match (&*key, value) {
+            ("args", Value::Array(values)) => {
+                let names = values
+                    .into_iter()
+                    .map(|value| match value {
+                        Value::String(arg) if syn::parse_str::<Ident>(&arg).is_ok() => Ok(arg),
+                        _ => Err(format!(
+                            "expected identifiers as the `args` of condition `{}`",
+                            name
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                args = Some(names);
+            }
+            ("doc", Value::String(text)) => doc = Some(text),
+            ("check", Value::String(expr)) => check = Some(expr),
+            ("args", _) => {
+                return Err(format!(
+                    "expected an array for `args` of condition `{}`",
+                    name
+                ))
+            }
+            ("doc", _) => {
+                return Err(format!(
+                    "expected a string for `doc` of condition `{}`",
+                    name
+                ))
+            }
+            ("check", _) => {
+                return Err(format!(
+                    "expected a string for `check` of condition `{}`",
+                    name
+                ))
+            }
+            (key, _) => {
+                return Err(format!(
+                    "unknown key `{}` of condition `{}`, expected `args`, `doc` or `check`",
+                    key, name
+                ))
+            }
+        }
+    }
+
+    let kind = ConditionKind {
+        args: args.unwrap_or_default(),
+        doc: doc.ok_or_else(|| format!("missing `doc` for condition `{}`", name))?,
+        check,
+    };
+
+    if let Some(check) = &kind.check {
+        let check = fill_in_args(check, &kind.args, &kind.args);
+
+        if let Err(err) = syn::parse_str::<Expr>(&check) {
+            return Err(format!("invalid `check` of condition `{}`: {}", name, err));
+        }
+    }
+
+    Ok(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_empty_config() {
+        let config = parse_config("").expect("valid config");
+
+        assert!(config.hint_reason.is_none());
+        assert!(config.messages.iter().all(Option::is_none));
+        assert!(!config.no_doc);
+        assert!(!config.no_debug_assert);
+        assert!(!config.log_only);
+        assert!(!config.summary_errors);
+        assert!(config.conditions.is_empty());
+    }
+
+    #[test]
+    fn parse_full_config() {
+        let config = parse_config(
+            r#"
+            hint_reason = "<warum ist das in Ordnung?>"
+
+            [messages]
+            missing_reason = "hier fehlt eine Begründung"
+            reason_help = "siehe die internen Richtlinien"
+
+            [defaults]
+            no_doc = true
+            no_debug_assert = false
+
+            [logging]
+            panic = false
+
+            [diagnostics]
+            summary_errors = true
+
+            [conditions.dma_capable]
+            args = ["ptr", "len"]
+            doc = "`{ptr}` must point to `{len}` bytes of DMA-capable memory"
+            check = "dma::is_dma_capable({ptr}, {len})"
+
+            [conditions.irq_disabled]
+            doc = "interrupts must be disabled"
+            "#,
+        )
+        .expect("valid config");
+
+        assert_eq!(
+            config.hint_reason.as_deref(),
+            Some("<warum ist das in Ordnung?>")
+        );
+        assert_eq!(
+            config.messages[Message::MissingReason as usize].as_deref(),
+            Some("hier fehlt eine Begründung")
+        );
+        assert_eq!(
+            config.messages[Message::ReasonHelp as usize].as_deref(),
+            Some("siehe die internen Richtlinien")
+        );
+        assert!(config.messages[Message::TodoHelp as usize].is_none());
+        assert!(config.no_doc);
+        assert!(!config.no_debug_assert);
+        assert!(config.log_only);
+        assert!(config.summary_errors);
+
+        let dma_capable = &config.conditions["dma_capable"];
+        let args = ["buf".to_string(), "n".to_string()];
+        assert_eq!(dma_capable.args, vec!["ptr", "len"]);
+        assert_eq!(
+            dma_capable.description(&args),
+            "`buf` must point to `n` bytes of DMA-capable memory"
+        );
+        let check = dma_capable.check(&args).expect("check is configured");
+        assert_eq!(
+            quote::quote! { #check }.to_string(),
+            quote::quote! { dma::is_dma_capable(buf, n) }.to_string()
+        );
+
+        let irq_disabled = &config.conditions["irq_disabled"];
+        assert!(irq_disabled.args.is_empty());
+        assert!(irq_disabled.check(&[]).is_none());
+    }
+
+    #[test]
+    fn parse_wrong_config() {
+        assert!(parse_config("hint_reason = 42").is_err());
+        assert!(parse_config("[messages]\nunknown = \"text\"").is_err());
+        assert!(parse_config("[messages]\nmissing_reason = []").is_err());
+        assert!(parse_config("reason = \"text\"").is_err());
+        assert!(parse_config("hint_reason = ").is_err());
+        assert!(parse_config("defaults = true").is_err());
+        assert!(parse_config("[defaults]\nno_doc = \"yes\"").is_err());
+        assert!(parse_config("[defaults]\nno_assert = true").is_err());
+        assert!(parse_config("[logging]\npanic = 1").is_err());
+        assert!(parse_config("[logging]\nlevel = \"warn\"").is_err());
+        assert!(parse_config("[diagnostics]\nsummary_errors = \"yes\"").is_err());
+        assert!(parse_config("[diagnostics]\nverbose = true").is_err());
+        assert!(parse_config("conditions = 1").is_err());
+        assert!(parse_config("[conditions.dma]\nargs = [\"ptr\"]").is_err());
+        assert!(parse_config("[conditions.non_null]\ndoc = \"text\"").is_err());
+        assert!(parse_config("[conditions.\"dma safe\"]\ndoc = \"text\"").is_err());
+        assert!(parse_config("[conditions.dma]\ndoc = \"text\"\nargs = [\"a b\"]").is_err());
+        assert!(parse_config("[conditions.dma]\ndoc = \"text\"\ncheck = \"{ptr} &&\"").is_err());
+        assert!(parse_config("[conditions.dma]\ndoc = \"text\"\nassert = \"true\"").is_err());
+    }
+}
@@ -58,13 +58,23 @@ use syn::{parse2, spanned::Spanned, Ident, ItemFn, LitStr};
 
 use crate::{
     call::Call,
-    helpers::{add_span_to_signature, combine_cfg, CRATE_NAME},
-    precondition::{CfgPrecondition, Precondition, ReadWrite},
+    helpers::{
+        add_precondition_param, add_span_to_signature, combine_call_site_cfg, combine_cfg,
+        CRATE_NAME,
+    },
+    precondition::{sort_for_encoding, CfgPrecondition, Precondition},
 };
 
 /// Renders a precondition list to a token stream.
-fn render_condition_list(mut preconditions: Vec<CfgPrecondition>, span: Span) -> TokenStream {
-    preconditions.sort_unstable();
+///
+/// If `as_expr` is true, the list is rendered as the elements of a tuple expression, otherwise as
+/// the elements of a tuple type.
+fn render_condition_list(
+    mut preconditions: Vec<CfgPrecondition>,
+    span: Span,
+    as_expr: bool,
+) -> TokenStream {
+    sort_for_encoding(&mut preconditions);
 
     let mut tokens = TokenStream::new();
     let crate_name = Ident::new(&CRATE_NAME, span);
@@ -72,14 +82,13 @@ fn render_condition_list(mut preconditions: Vec<CfgPrecondition>, span: Span) ->
     for precondition in preconditions {
         match precondition.precondition() {
             Precondition::ValidPtr {
-                ident, read_write, ..
+                ident,
+                read_write,
+                count,
+                ..
             } => {
                 let ident_lit = LitStr::new(&ident.to_string(), ident.span());
-                let rw_str = match read_write {
-                    ReadWrite::Read { .. } => LitStr::new("r", read_write.span()),
-                    ReadWrite::Write { .. } => LitStr::new("w", read_write.span()),
-                    ReadWrite::Both { .. } => LitStr::new("r+w", read_write.span()),
-                };
+                let rw_str = LitStr::new(&read_write.encoding(count.as_ref()), read_write.span());
                 tokens.append_all(quote_spanned! { precondition.span()=>
                     ::#crate_name::ValidPtrCondition::<#ident_lit, #rw_str>
                 });
@@ -90,6 +99,160 @@ fn render_condition_list(mut preconditions: Vec<CfgPrecondition>, span: Span) ->
                     ::#crate_name::ProperAlignCondition::<#ident_lit>
                 });
             }
+            Precondition::ProperAlignVal { ident, align, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                let align_lit = LitStr::new(&quote! { #align }.to_string(), align.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::ProperAlignValCondition::<#ident_lit, #align_lit>
+                });
+            }
+            Precondition::AlignedTo { ident, align, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                let align_lit = LitStr::new(&quote! { #align }.to_string(), align.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::AlignedToCondition::<#ident_lit, #align_lit>
+                });
+            }
+            Precondition::NonNull { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NonNullCondition::<#ident_lit>
+                });
+            }
+            Precondition::NulTerminated { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NulTerminatedCondition::<#ident_lit>
+                });
+            }
+            Precondition::ValidCStr { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::ValidCStrCondition::<#ident_lit>
+                });
+            }
+            Precondition::Initialized { ident, count, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                let count_lit = match count {
+                    Some((_, count)) => LitStr::new(&quote! { #count }.to_string(), count.span()),
+                    None => LitStr::new("", ident.span()),
+                };
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::InitializedCondition::<#ident_lit, #count_lit>
+                });
+            }
+            Precondition::NonOverlapping {
+                first,
+                second,
+                count,
+                ..
+            } => {
+                let first_lit = LitStr::new(&first.to_string(), first.span());
+                let second_lit = LitStr::new(&second.to_string(), second.span());
+                let count_lit = LitStr::new(&quote! { #count }.to_string(), count.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NonOverlappingCondition::<#first_lit, #second_lit, #count_lit>
+                });
+            }
+            Precondition::InterruptsDisabled { .. } => {
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::InterruptsDisabledCondition
+                });
+            }
+            Precondition::NotReentrant { .. } => {
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NotReentrantCondition
+                });
+            }
+            Precondition::DmaQuiescent { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::DmaQuiescentCondition::<#ident_lit>
+                });
+            }
+            Precondition::LivesUntilEndOfScope { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::LivesUntilEndOfScopeCondition::<#ident_lit>
+                });
+            }
+            Precondition::InRange { ident, range, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                let range_lit = LitStr::new(&quote! { #range }.to_string(), range.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::InRangeCondition::<#ident_lit, #range_lit>
+                });
+            }
+            Precondition::Consumed { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::ConsumedCondition::<#ident_lit>
+                });
+            }
+            Precondition::ValidUtf8 { ident, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::ValidUtf8Condition::<#ident_lit>
+                });
+            }
+            Precondition::ValidValue { ident, ty, .. } => {
+                let ident_lit = LitStr::new(&ident.to_string(), ident.span());
+                let ty_lit = LitStr::new(&quote! { #ty }.to_string(), ty.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::ValidValueCondition::<#ident_lit, #ty_lit>
+                });
+            }
+            Precondition::LayoutCompatible { from, to, .. } => {
+                let from_lit = LitStr::new(&quote! { #from }.to_string(), from.span());
+                let to_lit = LitStr::new(&quote! { #to }.to_string(), to.span());
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::LayoutCompatibleCondition::<#from_lit, #to_lit>
+                });
+            }
+            Precondition::Token { ty, value, .. } => {
+                if !as_expr {
+                    tokens.append_all(quote_spanned! { precondition.span()=>
+                        ::#crate_name::TokenCondition::<#ty>
+                    });
+                } else if let Some((_, value)) = value {
+                    tokens.append_all(quote_spanned! { precondition.span()=>
+                        ::#crate_name::TokenCondition::<#ty>(#value)
+                    });
+                } else {
+                    // An error was already emitted for the missing value.
+                    // The placeholder does not diverge, to avoid an additional warning.
+                    tokens.append_all(quote_spanned! { precondition.span()=>
+                        ::#crate_name::TokenCondition::<#ty>(::core::option::Option::None.unwrap())
+                    });
+                }
+            }
+            // Make sure that the imported condition actually exists.
+            //
+            // The marker type is inferred, so that a wrong path is only reported once.
+            Precondition::Imported { path, .. } if as_expr => {
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    {
+                        const _: ::#crate_name::ConditionDef = #path;
+                        ::#crate_name::NamedCondition(#path {})
+                    }
+                })
+            }
+            Precondition::Imported { path, .. } => {
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NamedCondition::<#path>
+                });
+            }
+            // An error was already emitted for exporting the condition at the call.
+            Precondition::Exported { .. } if as_expr => {
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NamedCondition(::core::option::Option::None.unwrap())
+                });
+            }
+            Precondition::Exported { ident, .. } => {
+                tokens.append_all(quote_spanned! { precondition.span()=>
+                    ::#crate_name::NamedCondition::<#ident>
+                });
+            }
             Precondition::Boolean(expr) => {
                 let as_str = LitStr::new(&quote! { #expr }.to_string(), precondition.span());
 
@@ -97,7 +260,16 @@ fn render_condition_list(mut preconditions: Vec<CfgPrecondition>, span: Span) ->
                     ::#crate_name::BooleanCondition::<#as_str>
                 });
             }
-            Precondition::Custom(string) => {
+            Precondition::ValidSlice { .. } => {
+                unreachable!("`valid_slice` preconditions are expanded before they are rendered")
+            }
+            Precondition::Custom(_) | Precondition::Configured { .. } => {
+                let text = precondition
+                    .precondition()
+                    .custom_text()
+                    .expect("custom and configured preconditions have a text");
+                let string = LitStr::new(&text, precondition.span());
+
                 tokens.append_all(quote_spanned! { precondition.span()=>
                     ::#crate_name::CustomCondition::<#string>
                 });
@@ -113,24 +285,27 @@ fn render_condition_list(mut preconditions: Vec<CfgPrecondition>, span: Span) ->
 }
 
 /// Generates the code for the function with the precondition handling added.
+///
+/// If `arg_first` is set, the precondition argument is the first argument of the function.
 pub(crate) fn render_pre(
     preconditions: Vec<CfgPrecondition>,
     function: &mut ItemFn,
     span: Span,
+    arg_first: bool,
 ) -> TokenStream {
     let combined_cfg = combine_cfg(&preconditions, span);
-    let preconditions = render_condition_list(preconditions, span);
+    let preconditions = render_condition_list(preconditions, span, false);
 
     // Include the precondition site into the span of the function.
     // This improves the error messages for the case where no preconditions are specified.
     add_span_to_signature(span, &mut function.sig);
 
-    function.sig.inputs.push(
-        parse2(quote_spanned! { span=>
-            #[cfg(all(not(doc), #combined_cfg))]
-            _: (#preconditions)
-        })
-        .expect("parses as a function argument"),
+    add_precondition_param(
+        &mut function.sig,
+        combined_cfg,
+        quote_spanned! { span=> (#preconditions) },
+        arg_first,
+        span,
     );
 
     quote! {
@@ -139,20 +314,24 @@ pub(crate) fn render_pre(
 }
 
 /// Generates the code for the call with the precondition handling added.
+///
+/// If `arg_first` is set, the precondition argument is the first argument of the call.
 pub(crate) fn render_assure(
     preconditions: Vec<CfgPrecondition>,
     mut call: Call,
     span: Span,
+    arg_first: bool,
 ) -> Call {
-    let combined_cfg = combine_cfg(&preconditions, span);
-    let preconditions = render_condition_list(preconditions, span);
+    let combined_cfg = combine_call_site_cfg(&preconditions);
+    let preconditions = render_condition_list(preconditions, span, true);
 
-    call.args_mut().push(
+    call.add_precondition_arg(
         parse2(quote_spanned! { span=>
             #[cfg(all(not(doc), #combined_cfg))]
             (#preconditions)
         })
         .expect("parses as an expression"),
+        arg_first,
     );
 
     call
@@ -6,12 +6,14 @@ use std::{env, fmt::Write};
 use syn::{
     spanned::Spanned,
     token::{Bracket, Pound},
-    AttrStyle, Attribute, Ident, LitStr, Path, PathArguments, Signature,
+    AttrStyle, Attribute, GenericParam, Ident, LitStr, Path, PathArguments, PredicateType,
+    Signature, Type, WherePredicate,
 };
 
 use crate::{
-    extern_crate::{ImplBlock, Module},
-    helpers::HINT_REASON,
+    config::hint_reason,
+    extern_crate::{concrete_type_args, ImplBlock, Module},
+    metadata::contract_id,
     precondition::{CfgPrecondition, Precondition},
 };
 
@@ -41,6 +43,15 @@ pub(crate) struct ImplBlockContext<'a> {
     pub(crate) top_level_module: &'a Ident,
 }
 
+/// The reason why `pre` keeps the signature of a function with preconditions unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeptSignature {
+    /// The function is exported to foreign code.
+    ForeignExport,
+    /// The function is a naked function, which cannot contain anything but inline assembly.
+    Naked,
+}
+
 macro_rules! doc_inline {
     ($docs:expr) => {
         write!($docs).expect("string writes don't fail")
@@ -65,18 +76,65 @@ macro_rules! doc {
     };
 }
 
+/// Creates a `#[doc]` attribute with the given text.
+pub(crate) fn doc_attribute(docs: &str, span: Span) -> Attribute {
+    let docs = LitStr::new(docs, span);
+    Attribute {
+        pound_token: Pound { spans: [span] },
+        style: AttrStyle::Outer,
+        bracket_token: Bracket { span },
+        path: Ident::new("doc", span).into(),
+        tokens: quote_spanned! { span=>
+            = #docs
+        },
+    }
+}
+
 /// Generates documentation of the preconditions for a function or method.
+///
+/// If `kept_signature` is set, the signature of the function is kept unchanged and its
+/// preconditions are not checked at call sites.
 pub(crate) fn generate_docs(
     function: &Signature,
     preconditions: &[CfgPrecondition],
     impl_block_context: Option<ImplBlockContext>,
+    kept_signature: Option<KeptSignature>,
 ) -> Attribute {
     let span = function.span();
+    let docs = generate_docs_text(function, preconditions, impl_block_context, kept_signature);
+
+    doc_attribute(&docs, span)
+}
+
+/// Generates the text of the documentation of the preconditions for a function or method.
+///
+/// This is the text of the attribute generated by `generate_docs`.
+pub(crate) fn generate_docs_text(
+    function: &Signature,
+    preconditions: &[CfgPrecondition],
+    impl_block_context: Option<ImplBlockContext>,
+    kept_signature: Option<KeptSignature>,
+) -> String {
     let mut docs = String::new();
     let plural = preconditions.len() != 1;
 
     if let Some(ctx) = &impl_block_context {
-        let (path_str, path_str_no_generics) = if let Some(ty) = &ctx.impl_block.ty() {
+        let (path_str, path_str_no_generics) = if let Some((trait_, _)) = &ctx.impl_block.trait_ {
+            let path = &ctx.path;
+            let ty = &ctx.impl_block.self_ty;
+            let name = &function.ident;
+
+            (
+                format!(
+                    "<{} as {}::{}>::{}",
+                    quote! { #ty },
+                    quote! { #path },
+                    quote! { #trait_ },
+                    quote! { #name }
+                ),
+                None,
+            )
+        } else if let Some(ty) = &ctx.impl_block.ty() {
             let mut path_str = String::new();
             for segment in ctx.path.segments.iter() {
                 doc_inline!(path_str, "{}::", segment.ident);
@@ -156,10 +214,16 @@ pub(crate) fn generate_docs(
             None
         };
 
+        let trait_ = ctx
+            .impl_block
+            .trait_
+            .as_ref()
+            .map(|(trait_, for_token)| quote! { #trait_ #for_token });
         doc!(
             docs,
-            "impl{} {} {} {{",
+            "impl{} {}{} {} {{",
             quote! { #generics },
+            trait_.map_or_else(String::new, |trait_| format!("{} ", trait_)),
             quote! { #ty },
             quote! { #where_clause }
         );
@@ -189,90 +253,505 @@ pub(crate) fn generate_docs(
         }
         doc!(docs);
 
+        let type_parameters = type_parameters(function, impl_block_context.as_ref());
+        let mut mentioned_type_parameters = Vec::new();
+
         for precondition in preconditions {
-            match precondition.precondition() {
+            let description = match precondition.precondition() {
+                Precondition::ValidPtr {
+                    ident,
+                    read_write,
+                    count: Some((_, count)),
+                    ..
+                } => format!(
+                    "the pointer {} must be valid for {} of {} elements",
+                    code_span(ident),
+                    read_write.doc_description(),
+                    code_span(quote! { #count })
+                ),
                 Precondition::ValidPtr {
                     ident, read_write, ..
-                } => doc!(
-                    docs,
-                    "- the pointer `{}` must be valid for {}",
-                    ident.to_string(),
+                } => format!(
+                    "the pointer {} must be valid for {}",
+                    code_span(ident),
                     read_write.doc_description()
                 ),
-                Precondition::ProperAlign { ident, .. } => doc!(
-                    docs,
-                    "- the pointer `{}` must have a proper alignment for its type",
-                    ident.to_string()
+                Precondition::ProperAlign { ident, .. } => format!(
+                    "the pointer {} must have a proper alignment for its type",
+                    code_span(ident)
                 ),
-                Precondition::Boolean(expr) => doc!(docs, "- `{}`", quote! { #expr }),
-                Precondition::Custom(text) => doc!(docs, "- {}", text.value()),
-            }
-        }
+                Precondition::ProperAlignVal { ident, align, .. }
+                | Precondition::AlignedTo { ident, align, .. } => format!(
+                    "the pointer {} must be aligned to {} bytes",
+                    code_span(ident),
+                    code_span(quote! { #align })
+                ),
+                Precondition::NonNull { ident, .. } => {
+                    format!("the pointer {} must not be null", code_span(ident))
+                }
+                Precondition::NulTerminated { ident, .. } => format!(
+                    "the pointer {} must point to a string terminated by a nul byte within the same allocated object",
+                    code_span(ident)
+                ),
+                Precondition::ValidCStr { ident, .. } => format!(
+                    "the pointer {} must point to a valid C string: it is valid for reads up to and including its nul terminator, which is at most `isize::MAX` bytes away, and the string is neither freed nor mutated for the duration of the call",
+                    code_span(ident)
+                ),
+                Precondition::Initialized {
+                    ident,
+                    count: Some((_, count)),
+                    ..
+                } => format!(
+                    "the pointer {} must point to {} initialized elements",
+                    code_span(ident),
+                    code_span(quote! { #count })
+                ),
+                Precondition::Initialized { ident, .. } => format!(
+                    "the pointer {} must point to an initialized value",
+                    code_span(ident)
+                ),
+                Precondition::NonOverlapping {
+                    first,
+                    second,
+                    count,
+                    ..
+                } => format!(
+                    "the memory regions of {} elements pointed to by {} and {} must not overlap",
+                    code_span(quote! { #count }),
+                    code_span(first),
+                    code_span(second)
+                ),
+                Precondition::ValidSlice {
+                    ident,
+                    len,
+                    read_write,
+                    ..
+                } => format!(
+                    "the pointer {} must point to a properly aligned slice of {} initialized elements that is valid for {} and at most `isize::MAX` bytes large",
+                    code_span(ident),
+                    code_span(quote! { #len }),
+                    read_write.doc_description()
+                ),
+                Precondition::InterruptsDisabled { .. } => {
+                    "interrupts must be disabled for the duration of the call".to_string()
+                }
+                Precondition::NotReentrant { .. } => {
+                    "the function must not be called again before the call returns, for example from a callback".to_string()
+                }
+                Precondition::DmaQuiescent { ident, .. } => format!(
+                    "no DMA engine may access the memory of {} for the duration of the call",
+                    code_span(ident)
+                ),
+                Precondition::LivesUntilEndOfScope { ident, .. } => format!(
+                    "the storage referenced by {} must live until the end of the scope that the result of the call is used in",
+                    code_span(ident)
+                ),
+                Precondition::InRange { ident, range, .. } => format!(
+                    "{} is within {}",
+                    code_span(ident),
+                    code_span(quote! { #range })
+                ),
+                Precondition::ValidValue { ident, ty, .. } => format!(
+                    "the bits of {} must form a valid value of type {}",
+                    code_span(ident),
+                    code_span(quote! { #ty })
+                ),
+                Precondition::LayoutCompatible { from, to, .. } => format!(
+                    "a value of type {} can be read from the memory of a value of type {}: it is at most as large and at most as strictly aligned",
+                    code_span(quote! { #to }),
+                    code_span(quote! { #from })
+                ),
+                Precondition::Consumed { ident, .. } => format!(
+                    "the ownership of {} is transferred to this function, so it must not be used after the call",
+                    code_span(ident)
+                ),
+                Precondition::ValidUtf8 { ident, .. } => {
+                    format!("the bytes of {} must be valid UTF-8", code_span(ident))
+                }
+                Precondition::Token { ty, .. } => {
+                    format!("a token of type {} must be passed", code_span(quote! { #ty }))
+                }
+                Precondition::Exported { text, .. } => text.value(),
+                Precondition::Imported { path, .. } => {
+                    format!("the condition {}", code_span(quote! { #path }))
+                }
+                Precondition::Boolean(expr) => code_span(quote! { #expr }),
+                Precondition::Custom(_) | Precondition::Configured { .. } => precondition
+                    .precondition()
+                    .custom_text()
+                    .expect("custom and configured preconditions have a text"),
+            };
 
-        doc!(docs);
-        if plural {
             doc!(
                 docs,
-                "To call the function you need to [`assure`]({}) that the preconditions hold:",
-                ASSURE_LINK
+                "- {}",
+                link_type_parameters(
+                    &description,
+                    &type_parameters,
+                    &mut mentioned_type_parameters
+                )
             );
-        } else {
+        }
+
+        if !mentioned_type_parameters.is_empty() {
+            doc!(docs);
+            doc!(docs, "## Type parameters in the preconditions");
+            doc!(docs);
             doc!(
                 docs,
-                "To call the function you need to [`assure`]({}) that the precondition holds:",
-                ASSURE_LINK
+                "The preconditions refer to the following type parameters of this function:"
             );
+            doc!(docs);
+
+            for parameter in type_parameters
+                .iter()
+                .filter(|parameter| mentioned_type_parameters.contains(&parameter.name))
+            {
+                doc_inline!(
+                    docs,
+                    "- {}, declared as {}",
+                    code_span(&parameter.name),
+                    code_span(&parameter.declaration)
+                );
+                for bound in &parameter.where_bounds {
+                    doc_inline!(docs, " and bounded by {}", code_span(bound));
+                }
+                doc!(docs);
+            }
         }
+
         doc!(docs);
-        doc!(docs, "```rust,ignore");
+        let contract_id = contract_id(
+            &preconditions
+                .iter()
+                .map(|precondition| precondition.precondition().to_string())
+                .collect::<Vec<_>>(),
+        );
+        doc!(
+            docs,
+            "The contract ID of {} is `{}`.",
+            if plural {
+                "these preconditions"
+            } else {
+                "this precondition"
+            },
+            contract_id
+        );
+        doc!(docs);
+        if let Some(kept_signature) = kept_signature {
+            let (noun, hold) = if plural {
+                ("preconditions", "preconditions hold")
+            } else {
+                ("precondition", "precondition holds")
+            };
+
+            match kept_signature {
+                KeptSignature::ForeignExport => doc!(docs, "This function is exported to foreign code, so `pre` does not change its signature and the {} cannot be checked at call sites.", noun),
+                KeptSignature::Naked => doc!(docs, "This function is a naked function, so `pre` does not change its signature or its body and the {} cannot be checked at all.", noun),
+            }
+            doc!(
+                docs,
+                "Callers need to make sure that the {} without the help of `pre`.",
+                hold
+            );
+        } else {
+            if plural {
+                doc!(
+                    docs,
+                    "To call the function you need to [`assure`]({}) that the preconditions hold:",
+                    ASSURE_LINK
+                );
+            } else {
+                doc!(
+                    docs,
+                    "To call the function you need to [`assure`]({}) that the precondition holds:",
+                    ASSURE_LINK
+                );
+            }
+            doc!(docs);
+            doc!(docs, "```rust,ignore");
+
+            if let Some(ctx) = &impl_block_context {
+                let mut path_str = format!("{}", ctx.top_level_module);
+                for segment in ctx.path.segments.iter().skip(1) {
+                    doc_inline!(path_str, "::{}", segment.ident);
+                }
+
+                if ctx.impl_block.trait_.is_some() {
+                    if let (Some(trait_), Some(ty)) =
+                        (ctx.impl_block.trait_segment(), ctx.impl_block.ty())
+                    {
+                        doc_inline!(path_str, "::{}", trait_.ident);
+
+                        if let Some(args) = concrete_type_args(trait_, &ctx.impl_block.generics) {
+                            doc_inline!(path_str, "<{}>", quote! { #(#args),* });
+                        }
 
-        if let Some(ctx) = &impl_block_context {
-            let mut path_str = format!("{}", ctx.top_level_module);
-            for segment in ctx.path.segments.iter().skip(1).chain(ctx.impl_block.ty()) {
-                write!(path_str, "::{}", segment.ident).expect("string writes don't fail");
+                        doc_inline!(path_str, " for {}", ty.ident);
+
+                        if let Some(args) = concrete_type_args(ty, &ctx.impl_block.generics) {
+                            doc_inline!(path_str, "<{}>", quote! { #(#args),* });
+                        }
+                    }
+                } else if let Some(ty) = ctx.impl_block.ty() {
+                    doc_inline!(path_str, "::{}", ty.ident);
+
+                    if let Some(args) = concrete_type_args(ty, &ctx.impl_block.generics) {
+                        doc_inline!(path_str, "<{}>", quote! { #(#args),* });
+                    }
+                }
+
+                if let Ok(name) = env::var("CARGO_PKG_NAME") {
+                    let mut name = name.replace('-', "_");
+                    name.push_str("::");
+                    path_str.insert_str(0, &name);
+                }
+
+                doc!(docs, "#[forward(impl {})]", path_str);
             }
 
-            if let Ok(name) = env::var("CARGO_PKG_NAME") {
-                let mut name = name.replace('-', "_");
-                name.push_str("::");
-                path_str.insert_str(0, &name);
+            for precondition in preconditions {
+                if let Precondition::Token { ty, .. } = precondition.precondition() {
+                    doc!(
+                        docs,
+                        "#[assure(token({} = /* token value */))]",
+                        quote! { #ty }
+                    );
+                    continue;
+                }
+
+                doc!(docs, "#[assure(",);
+                if let Precondition::Exported { ident, .. } = precondition.precondition() {
+                    doc!(docs, "    use {},", ident);
+                } else {
+                    doc!(docs, "    {},", precondition.precondition());
+                }
+                doc!(docs, "    reason = {:?}", hint_reason());
+                doc!(docs, ")]");
             }
 
-            doc!(docs, "#[forward(impl {})]", path_str);
+            let receiver = if function.receiver().is_some() {
+                "x."
+            } else {
+                ""
+            };
+            let parameters = if function.inputs.is_empty() {
+                ""
+            } else {
+                "/* parameters omitted */"
+            };
+            doc!(docs, "{}{}({});", receiver, function.ident, parameters);
+
+            doc!(docs, "```");
         }
+    }
 
-        for precondition in preconditions {
-            doc!(docs, "#[assure(",);
-            doc!(docs, "    {},", precondition.precondition());
-            doc!(docs, "    reason = {:?}", HINT_REASON);
-            doc!(docs, ")]");
+    docs
+}
+
+/// A type or const parameter that preconditions can refer to.
+struct TypeParameter {
+    /// The name of the parameter.
+    name: String,
+    /// The declaration of the parameter, including its inline bounds.
+    declaration: String,
+    /// The predicates of `where` clauses bounding the parameter.
+    where_bounds: Vec<String>,
+}
+
+/// Collects the type and const parameters of the function and its `impl` block, if there is one.
+fn type_parameters(
+    function: &Signature,
+    impl_block_context: Option<&ImplBlockContext>,
+) -> Vec<TypeParameter> {
+    let generics = impl_block_context
+        .map(|ctx| &ctx.impl_block.generics)
+        .into_iter()
+        .chain(std::iter::once(&function.generics));
+
+    let mut parameters = Vec::new();
+    for generics in generics {
+        for param in &generics.params {
+            let name = match param {
+                GenericParam::Type(param) => &param.ident,
+                GenericParam::Const(param) => &param.ident,
+                GenericParam::Lifetime(_) => continue,
+            };
+
+            let where_bounds = generics
+                .where_clause
+                .iter()
+                .flat_map(|where_clause| &where_clause.predicates)
+                .filter(|predicate| match predicate {
+                    WherePredicate::Type(PredicateType { bounded_ty, .. }) => {
+                        matches!(bounded_ty, Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident(name))
+                    }
+                    _ => false,
+                })
+                .map(|predicate| quote! { #predicate }.to_string())
+                .collect();
+
+            parameters.push(TypeParameter {
+                name: name.to_string(),
+                declaration: quote! { #param }.to_string(),
+                where_bounds,
+            });
         }
+    }
 
-        let receiver = if function.receiver().is_some() {
-            "x."
-        } else {
-            ""
-        };
-        let parameters = if function.inputs.is_empty() {
-            ""
+    parameters
+}
+
+/// Wraps the given text in a markdown code span.
+///
+/// The text is rendered verbatim, so characters such as `<`, `>` and `&` are never interpreted as
+/// HTML. Backticks in the text are handled by using a longer run of backticks as the delimiter.
+pub(crate) fn code_span(text: impl ToString) -> String {
+    let text = text.to_string();
+
+    let mut longest_run = 0;
+    let mut current_run = 0;
+    for c in text.chars() {
+        if c == '`' {
+            current_run += 1;
+            longest_run = longest_run.max(current_run);
         } else {
-            "/* parameters omitted */"
-        };
-        doc!(docs, "{}{}({});", receiver, function.ident, parameters);
+            current_run = 0;
+        }
+    }
 
-        doc!(docs, "```");
+    let delimiter = "`".repeat(longest_run + 1);
+    // Markdown strips a single space on both sides, which is needed to separate the delimiter
+    // from backticks at the start or end of the text.
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+
+    format!("{0}{1}{2}{1}{0}", delimiter, padding, text)
+}
+
+/// A part of a markdown text.
+#[derive(Debug, PartialEq, Eq)]
+enum TextPart<'a> {
+    /// Text outside of code spans.
+    Text(&'a str),
+    /// A code span, including its delimiters.
+    CodeSpan {
+        /// The code span as it occurs in the text.
+        raw: &'a str,
+        /// The contents of the code span.
+        contents: &'a str,
+    },
+}
+
+/// Splits the text into code spans and the text between them.
+///
+/// Like in markdown, a code span starts with a run of backticks and ends with the next run of
+/// backticks of the same length. Runs of backticks without a matching run are plain text.
+fn split_code_spans(text: &str) -> Vec<TextPart<'_>> {
+    /// The length of the run of backticks at the start of the text.
+    fn backtick_run(text: &str) -> usize {
+        text.len() - text.trim_start_matches('`').len()
     }
 
-    let docs = LitStr::new(&docs, span);
-    Attribute {
-        pound_token: Pound { spans: [span] },
-        style: AttrStyle::Outer,
-        bracket_token: Bracket { span },
-        path: Ident::new("doc", span).into(),
-        tokens: quote_spanned! { span=>
-            = #docs
-        },
+    let mut parts = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while let Some(offset) = text[i..].find('`') {
+        let start = i + offset;
+        let delimiter_len = backtick_run(&text[start..]);
+        let contents_start = start + delimiter_len;
+
+        let mut search = contents_start;
+        let mut end = None;
+        while let Some(offset) = text[search..].find('`') {
+            let run_start = search + offset;
+            let run_len = backtick_run(&text[run_start..]);
+            if run_len == delimiter_len {
+                end = Some(run_start);
+                break;
+            }
+            search = run_start + run_len;
+        }
+
+        match end {
+            Some(end) => {
+                if text_start < start {
+                    parts.push(TextPart::Text(&text[text_start..start]));
+                }
+
+                let contents = &text[contents_start..end];
+                let contents = match contents.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                    Some(stripped) if !stripped.trim().is_empty() => stripped,
+                    _ => contents,
+                };
+                parts.push(TextPart::CodeSpan {
+                    raw: &text[start..end + delimiter_len],
+                    contents,
+                });
+
+                i = end + delimiter_len;
+                text_start = i;
+            }
+            None => i = contents_start,
+        }
+    }
+
+    if text_start < text.len() {
+        parts.push(TextPart::Text(&text[text_start..]));
     }
+
+    parts
+}
+
+/// Links the type parameters in the code spans of the description to their declarations.
+///
+/// Code spans consisting only of a type parameter are turned into links to the list of type
+/// parameters. All type parameters occurring in a code span are added to `mentioned`.
+fn link_type_parameters(
+    description: &str,
+    type_parameters: &[TypeParameter],
+    mentioned: &mut Vec<String>,
+) -> String {
+    if type_parameters.is_empty() {
+        return description.to_string();
+    }
+
+    let mut linked = String::new();
+
+    for part in split_code_spans(description) {
+        let (raw, contents) = match part {
+            TextPart::Text(text) => {
+                linked.push_str(text);
+                continue;
+            }
+            TextPart::CodeSpan { raw, contents } => (raw, contents),
+        };
+
+        for word in contents.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            if is_type_parameter(word, type_parameters) && !mentioned.iter().any(|m| m == word) {
+                mentioned.push(word.to_string());
+            }
+        }
+
+        if is_type_parameter(contents, type_parameters) {
+            doc_inline!(linked, "[{}](#type-parameters-in-the-preconditions)", raw);
+        } else {
+            linked.push_str(raw);
+        }
+    }
+
+    linked
+}
+
+/// Checks whether the given name is the name of one of the type parameters.
+fn is_type_parameter(name: &str, type_parameters: &[TypeParameter]) -> bool {
+    type_parameters
+        .iter()
+        .any(|parameter| parameter.name == name)
 }
 
 /// Generates documentation of the preconditions for a `extern_crate` module.
@@ -325,16 +804,7 @@ pub(crate) fn generate_module_docs(module: &Module, path: &Path) -> Attribute {
         path_str
     );
 
-    let docs = LitStr::new(&docs, span);
-    Attribute {
-        pound_token: Pound { spans: [span] },
-        style: AttrStyle::Outer,
-        bracket_token: Bracket { span },
-        path: Ident::new("doc", span).into(),
-        tokens: quote_spanned! { span=>
-            = #docs
-        },
-    }
+    doc_attribute(&docs, span)
 }
 
 /// Generates the start of the documentation for `extern_crate`-defined functions.
@@ -386,14 +856,146 @@ pub(crate) fn generate_extern_crate_fn_docs(
     }
     doc!(docs);
 
-    let docs = LitStr::new(&docs, span);
-    Attribute {
-        pound_token: Pound { spans: [span] },
-        style: AttrStyle::Outer,
-        bracket_token: Bracket { span },
-        path: Ident::new("doc", span).into(),
-        tokens: quote_spanned! { span=>
-            = #docs
-        },
+    doc_attribute(&docs, span)
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn links_type_parameters() {
+        let function: Signature = parse_quote! {
+            fn foo<'a, T: Copy, U, const N: usize>(x: &'a T) where U: Default
+        };
+        let type_parameters = type_parameters(&function, None);
+        let mut mentioned = Vec::new();
+
+        assert_eq!(
+            link_type_parameters(
+                "an all-zero byte-pattern is a valid value of `T`",
+                &type_parameters,
+                &mut mentioned
+            ),
+            "an all-zero byte-pattern is a valid value of [`T`](#type-parameters-in-the-preconditions)"
+        );
+        assert_eq!(
+            link_type_parameters(
+                "`x` fits into `[U; N]`, `A`",
+                &type_parameters,
+                &mut mentioned
+            ),
+            "`x` fits into `[U; N]`, `A`"
+        );
+        assert_eq!(
+            link_type_parameters("unclosed `T", &type_parameters, &mut mentioned),
+            "unclosed `T"
+        );
+        assert_eq!(mentioned, ["T", "U", "N"]);
+
+        let declarations = type_parameters
+            .iter()
+            .map(|parameter| (&*parameter.declaration, parameter.where_bounds.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            declarations,
+            [("T : Copy", 0), ("U", 1), ("const N : usize", 0)]
+        );
+    }
+
+    #[test]
+    fn code_spans() {
+        assert_eq!(code_span("a < b"), "`a < b`");
+        assert_eq!(code_span("a == \"`\""), "``a == \"`\"``");
+        assert_eq!(code_span("`a` ``b``"), "``` `a` ``b`` ```");
+
+        assert_eq!(
+            split_code_spans("a `b` c ``d`e`` `f"),
+            [
+                TextPart::Text("a "),
+                TextPart::CodeSpan {
+                    raw: "`b`",
+                    contents: "b"
+                },
+                TextPart::Text(" c "),
+                TextPart::CodeSpan {
+                    raw: "``d`e``",
+                    contents: "d`e"
+                },
+                TextPart::Text(" `f"),
+            ]
+        );
+        assert_eq!(
+            split_code_spans("``` `a` ```"),
+            [TextPart::CodeSpan {
+                raw: "``` `a` ```",
+                contents: "`a`"
+            }]
+        );
+
+        for text in &["`x`", "a < b && c > d", "'`'", "``"] {
+            assert_eq!(
+                split_code_spans(&code_span(text)),
+                [TextPart::CodeSpan {
+                    raw: &code_span(text),
+                    contents: text
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn renders_tricky_conditions() {
+        let function: Signature = parse_quote! {
+            fn foo<T: Copy>(a: usize, b: usize, p: *const T, s: &str)
+        };
+        let preconditions = [
+            parse_quote!(a < b && b > 0),
+            parse_quote!(a & 0xff != 0 || b << 2 >= a),
+            parse_quote!(s != "`quoted`" && s != "<br>"),
+            parse_quote!(s.as_bytes()[0] != b'`'),
+            parse_quote!(a < <T as Default>::default()),
+            parse_quote!(a == ::core::mem::size_of::<Vec<T>>()),
+            parse_quote!(in_range(a, 0..<T as Bounded>::MAX)),
+            parse_quote!(valid_ptr(p, r)),
+            parse_quote!(valid_ptr(p, r + w, a * 2)),
+            parse_quote!(valid_value(b, Option<&'static T>)),
+            parse_quote!(proper_align_val(p, <T as Align>::VALUE)),
+            parse_quote!("`a` is less than <code>`b`</code>"),
+        ]
+        .iter()
+        .map(|precondition: &Precondition| CfgPrecondition {
+            precondition: precondition.clone(),
+            cfg: None,
+            span: Span::call_site(),
+        })
+        .collect::<Vec<_>>();
+
+        let docs = generate_docs_text(&function, &preconditions, None, None);
+        let conditions = docs
+            .lines()
+            .filter(|line| line.starts_with("- "))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            conditions,
+            [
+                "- `a < b && b > 0`",
+                "- `a & 0xff != 0 || b << 2 >= a`",
+                "- ``s != \"`quoted`\" && s != \"<br>\"``",
+                "- ``s . as_bytes () [0] != b'`'``",
+                "- `a < < T as Default > :: default ()`",
+                "- `a == :: core :: mem :: size_of :: < Vec < T > > ()`",
+                "- `a` is within `0 .. < T as Bounded > :: MAX`",
+                "- the pointer `p` must be valid for reads",
+                "- the pointer `p` must be valid for reads and writes of `a * 2` elements",
+                "- the bits of `b` must form a valid value of type `Option < & 'static T >`",
+                "- the pointer `p` must be aligned to `< T as Align > :: VALUE` bytes",
+                "- `a` is less than <code>`b`</code>",
+                "- `T`, declared as `T : Copy`",
+            ]
+        );
     }
 }
@@ -42,7 +42,7 @@
 //!         #[pre(no_debug_assert)]
 //!         #[inline(always)]
 //!         #[allow(non_snake_case)]
-//!         pub(crate) fn NonNull__impl__new_unchecked__() {}
+//!         pub(crate) fn NonNull__impl_c23bd109__new_unchecked__() {}
 //!
 //!         #[pre(valid_ptr(src, r))]
 //!         #[inline(always)]
@@ -53,32 +53,306 @@
 //! }
 //! ```
 
-use proc_macro2::{Span, TokenStream};
+use lazy_static::lazy_static;
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
+use proc_macro_error::emit_error;
 use quote::{quote, quote_spanned, TokenStreamExt};
-use std::fmt;
+use std::{env, fmt, path::PathBuf, sync::Mutex};
 use syn::{
-    braced,
-    parse::{Parse, ParseStream},
+    braced, parenthesized,
+    parse::{Parse, ParseStream, Parser},
     spanned::Spanned,
-    token::Brace,
-    Attribute, FnArg, ForeignItemFn, Ident, ItemUse, Path, PathArguments, PathSegment, Token,
-    Visibility,
+    token::{Brace, Paren},
+    Attribute, FnArg, ForeignItemFn, Ident, ItemType, ItemUse, LitStr, Path, PathArguments,
+    PathSegment, ReturnType, Token, Visibility,
 };
 
 use crate::{
+    config,
     documentation::{generate_extern_crate_fn_docs, generate_module_docs},
-    helpers::{visit_matching_attrs_parsed_mut, AttributeAction, CRATE_NAME},
+    helpers::{
+        visit_matching_attrs_parsed, visit_matching_attrs_parsed_mut, AttributeAction, CRATE_NAME,
+    },
+    metadata::{self, Record},
     pre_attr::PreAttr,
 };
 
-pub(crate) use impl_block::{impl_block_stub_name, ImplBlock};
+use condition_groups::condition_sets;
+pub(crate) use condition_groups::{peek_when, unsupported_when_error};
+pub(crate) use impl_block::{
+    concrete_type_args, impl_block_stub_name, trait_impl_stub_name, ImplBlock,
+};
+use type_alias::render_type_alias;
 
+mod condition_groups;
 mod impl_block;
+mod type_alias;
+
+lazy_static! {
+    /// The functions generated in `extern_crate` modules so far.
+    static ref OUTLINE_FUNCTIONS: Mutex<Vec<OutlineFunction>> = Mutex::new(Vec::new());
+}
+
+/// A function generated in an `extern_crate` module.
+struct OutlineFunction {
+    /// The path of the function starting at the `extern_crate` module.
+    path: Vec<String>,
+    /// The path of the function that calls are forwarded to, if it is a free function.
+    original_path: Option<Vec<String>>,
+    /// Whether the function has preconditions.
+    has_preconditions: bool,
+    /// Whether the function is an `unsafe fn`.
+    is_unsafe: bool,
+}
+
+/// Registers a function generated in an `extern_crate` module.
+fn register_outline_function(
+    module_path: &[String],
+    original_path: Option<&Path>,
+    name: &Ident,
+    has_preconditions: bool,
+    is_unsafe: bool,
+) {
+    let mut path = module_path.to_vec();
+    path.push(name.to_string());
+
+    let original_path = original_path.map(|original_path| {
+        let mut original_path = path_to_strings(original_path);
+        original_path.push(name.to_string());
+        original_path
+    });
+
+    OUTLINE_FUNCTIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .push(OutlineFunction {
+            path,
+            original_path,
+            has_preconditions,
+            is_unsafe,
+        });
+}
+
+/// Converts the path into the names of its segments.
+fn path_to_strings(path: &Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
+/// Checks whether the function at the given path in an `extern_crate` module has preconditions.
+///
+/// Returns `None`, if no such function is known. This is the case for functions in other crates
+/// and for functions whose `extern_crate` module was not expanded yet.
+pub(crate) fn outline_has_preconditions(path: &Path) -> Option<bool> {
+    let path = path_to_strings(path);
+
+    OUTLINE_FUNCTIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .iter()
+        .filter(|function| path.ends_with(&function.path))
+        .map(|function| function.has_preconditions)
+        .reduce(|a, b| a || b)
+}
+
+/// Checks whether the function at the given path in an `extern_crate` module is an `unsafe fn`.
+///
+/// Like with [`outline_has_preconditions`], only functions that were already expanded are known.
+pub(crate) fn outline_is_unsafe(path: &Path) -> Option<bool> {
+    let path = path_to_strings(path);
+
+    OUTLINE_FUNCTIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .iter()
+        .filter(|function| path.ends_with(&function.path))
+        .map(|function| function.is_unsafe)
+        .reduce(|a, b| a || b)
+}
+
+/// Checks whether the path refers to the original of a function with preconditions in an
+/// `extern_crate` module, bypassing the preconditions.
+///
+/// Only paths with at least two segments are considered, because single identifiers are too likely
+/// to refer to unrelated functions. Paths that could also refer to the function in the
+/// `extern_crate` module itself are not considered either. Like with
+/// [`outline_has_preconditions`], only functions that were already expanded are known.
+pub(crate) fn bypasses_outline(path: &Path) -> bool {
+    let path = path_to_strings(path);
+
+    if path.len() < 2 {
+        return false;
+    }
+
+    OUTLINE_FUNCTIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .iter()
+        .filter(|function| function.has_preconditions && !function.path.ends_with(&path))
+        .filter_map(|function| function.original_path.as_ref())
+        .any(|original_path| original_path.ends_with(&path))
+}
+
+/// The custom keywords used in the `extern_crate` attribute.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(cfg);
+    custom_keyword!(inline);
+}
 
 /// The parsed version of the `extern_crate` attribute content.
 pub(crate) struct ExternCrateAttr {
     /// The path of the crate/module to which function calls will be forwarded.
     path: Path,
+    /// The predicate that the generated module is only included under, if any.
+    cfg: Option<ExternCrateCfg>,
+    /// How the generated wrapper functions are inlined, if specified.
+    inline: Option<ExternCrateInline>,
+}
+
+/// The `cfg(...)` predicate in an `extern_crate` attribute, such as `, cfg(test)`.
+struct ExternCrateCfg {
+    /// The comma separating the predicate from the path.
+    _comma: Token![,],
+    /// The `cfg` keyword.
+    cfg_keyword: custom_keywords::cfg,
+    /// The parentheses surrounding the predicate.
+    _parentheses: Paren,
+    /// The predicate itself.
+    predicate: TokenStream,
+}
+
+impl Parse for ExternCrateCfg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let comma = input.parse()?;
+        let cfg_keyword = input.parse()?;
+        let content;
+        let parentheses = parenthesized!(content in input);
+        let predicate = content.parse()?;
+
+        Ok(ExternCrateCfg {
+            _comma: comma,
+            cfg_keyword,
+            _parentheses: parentheses,
+            predicate,
+        })
+    }
+}
+
+/// How the wrapper functions in an `extern_crate` module are inlined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InlineMode {
+    /// The wrappers are `#[inline(always)]`.
+    Always,
+    /// The wrappers are `#[inline]`, leaving the decision to the compiler.
+    Hint,
+    /// The wrappers are `#[inline(never)]`.
+    Never,
+    /// The wrappers have no `inline` attribute.
+    None,
+}
+
+impl InlineMode {
+    /// All inline modes that exist.
+    const ALL: [InlineMode; 4] = [
+        InlineMode::Always,
+        InlineMode::Hint,
+        InlineMode::Never,
+        InlineMode::None,
+    ];
+
+    /// The name of the mode as used in the `extern_crate` attribute.
+    fn name(self) -> &'static str {
+        match self {
+            InlineMode::Always => "always",
+            InlineMode::Hint => "hint",
+            InlineMode::Never => "never",
+            InlineMode::None => "none",
+        }
+    }
+
+    /// Renders the `inline` attribute for a wrapper function with the given attributes.
+    ///
+    /// Functions that have an `inline` attribute in the outline keep it instead, so it should not
+    /// be rendered separately.
+    pub(crate) fn render_attribute(self, attrs: &[Attribute], span: Span) -> TokenStream {
+        if let Some(attr) = attrs.iter().find(|attr| is_inline_attr(attr)) {
+            return quote! { #attr };
+        }
+
+        match self {
+            InlineMode::Always => quote_spanned! { span=> #[inline(always)] },
+            InlineMode::Hint => quote_spanned! { span=> #[inline] },
+            InlineMode::Never => quote_spanned! { span=> #[inline(never)] },
+            InlineMode::None => TokenStream::new(),
+        }
+    }
+}
+
+/// Options for the generated wrapper functions that apply to a module and its inner modules.
+#[derive(Debug, Clone, Copy)]
+struct WrapperOptions {
+    /// Whether functions returning a value are `#[must_use]`.
+    must_use: bool,
+    /// How the functions are inlined.
+    inline: InlineMode,
+}
+
+/// Checks whether the attribute is an `inline` attribute.
+fn is_inline_attr(attr: &Attribute) -> bool {
+    attr.path.is_ident("inline")
+}
+
+/// The inline mode in an `extern_crate` attribute, such as `, inline = "hint"`.
+struct ExternCrateInline {
+    /// The comma separating the mode from the previous argument.
+    _comma: Token![,],
+    /// The `inline` keyword.
+    inline_keyword: custom_keywords::inline,
+    /// The `=` between the keyword and the mode.
+    _eq: Token![=],
+    /// The mode itself.
+    mode: InlineMode,
+}
+
+impl Parse for ExternCrateInline {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let comma = input.parse()?;
+        let inline_keyword = input.parse()?;
+        let eq = input.parse()?;
+        let lit: LitStr = input.parse()?;
+
+        let mode = InlineMode::ALL
+            .iter()
+            .copied()
+            .find(|mode| mode.name() == lit.value())
+            .ok_or_else(|| {
+                let modes = InlineMode::ALL
+                    .iter()
+                    .map(|mode| format!("`{}`", mode.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "unknown inline mode `{}`, expected one of {}",
+                        lit.value(),
+                        modes
+                    ),
+                )
+            })?;
+
+        Ok(ExternCrateInline {
+            _comma: comma,
+            inline_keyword,
+            _eq: eq,
+            mode,
+        })
+    }
 }
 
 impl fmt::Display for ExternCrateAttr {
@@ -89,22 +363,166 @@ impl fmt::Display for ExternCrateAttr {
             write!(f, "::")?;
         }
 
-        for segment in &self.path.segments {
+        for (i, segment) in self.path.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "::")?;
+            }
+
             write!(f, "{}", segment.ident)?;
         }
 
+        if let Some(cfg) = &self.cfg {
+            let predicate = &cfg.predicate;
+            write!(f, ", cfg({})", quote! { #predicate })?;
+        }
+
+        if let Some(inline) = &self.inline {
+            write!(f, ", inline = \"{}\"", inline.mode.name())?;
+        }
+
         write!(f, ")]")
     }
 }
 
 impl Parse for ExternCrateAttr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        Ok(ExternCrateAttr {
-            path: input.call(Path::parse_mod_style)?,
-        })
+        let path = input.call(Path::parse_mod_style)?;
+        let mut cfg = None;
+        let mut inline = None;
+
+        while !input.is_empty() {
+            if input.peek2(custom_keywords::inline) {
+                let span = input
+                    .fork()
+                    .parse::<ExternCrateInline>()?
+                    .inline_keyword
+                    .span;
+                if inline.replace(input.parse()?).is_some() {
+                    return Err(syn::Error::new(span, "duplicate `inline` argument"));
+                }
+            } else {
+                let span = input.fork().parse::<ExternCrateCfg>()?.cfg_keyword.span;
+                if cfg.replace(input.parse()?).is_some() {
+                    return Err(syn::Error::new(span, "duplicate `cfg` argument"));
+                }
+            }
+        }
+
+        Ok(ExternCrateAttr { path, cfg, inline })
     }
 }
 
+/// The crates that are available without being listed in `Cargo.toml`.
+const SYSROOT_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+
+impl ExternCrateAttr {
+    /// Checks that the crate that the attribute refers to is a dependency of the package.
+    ///
+    /// This is only a best-effort check based on the `Cargo.toml` of the package, so crates that
+    /// are made available in other ways are falsely reported. Returns whether no error was
+    /// reported.
+    pub(crate) fn check_crate_is_dependency(&self) -> bool {
+        let name = match referenced_crate(&self.path) {
+            Some(name) => name,
+            None => return true,
+        };
+        let manifest_exists = match env::var_os("CARGO_MANIFEST_DIR") {
+            Some(dir) => PathBuf::from(dir).join("Cargo.toml").is_file(),
+            None => false,
+        };
+        if !manifest_exists {
+            return true;
+        }
+
+        let name_str = name.to_string();
+        let is_dependency = [name_str.clone(), name_str.replace('_', "-")]
+            .iter()
+            .any(|candidate| proc_macro_crate::crate_name(candidate).as_ref() == Ok(&name_str));
+        let package = env::var("CARGO_PKG_NAME").unwrap_or_default();
+        let is_own_package = package.replace('-', "_") == name_str;
+
+        if is_dependency || is_own_package {
+            return true;
+        }
+
+        emit_error!(
+            name,
+            "crate `{}` is not a dependency of this package", name;
+            help = "add `{}` to the `[dependencies]` section of `Cargo.toml`", name;
+            note = "crates declared with `extern crate <crate> as {}` can be referred to as `crate::{}`", name, name
+        );
+
+        false
+    }
+}
+
+/// Returns the name of the crate that the path refers to, if it refers to a crate that must be a
+/// dependency.
+fn referenced_crate(path: &Path) -> Option<&Ident> {
+    let ident = &path.segments.first()?.ident;
+
+    if ident == "crate"
+        || ident == "self"
+        || ident == "super"
+        || SYSROOT_CRATES.iter().any(|name| ident == name)
+    {
+        None
+    } else {
+        Some(ident)
+    }
+}
+
+/// Parses a module for `Module::render_dummy`, keeping only the submodules.
+///
+/// Like in the actual rendering, the submodules are made visible to the outermost module.
+fn parse_dummy_module(
+    input: ParseStream,
+    visibility: Option<&TokenStream>,
+) -> syn::Result<TokenStream> {
+    let attrs = input.call(Attribute::parse_outer)?;
+    let local_vis: Visibility = input.parse()?;
+    let mod_token: Token![mod] = input.parse()?;
+    let ident: Ident = input.parse()?;
+    let content;
+    braced!(content in input);
+
+    let (visibility, inner_visibility) = match visibility {
+        Some(visibility) => (visibility.clone(), visibility.clone()),
+        None => {
+            let inner_visibility = match &local_vis {
+                Visibility::Public(pub_keyword) => quote! { #pub_keyword },
+                _ => quote! { pub(crate) },
+            };
+
+            (quote! { #local_vis }, inner_visibility)
+        }
+    };
+
+    let mut submodules = TokenStream::new();
+    while !content.is_empty() {
+        if parse_dummy_module(&content.fork(), Some(&inner_visibility)).is_ok() {
+            submodules.append_all(parse_dummy_module(&content, Some(&inner_visibility))?);
+            continue;
+        }
+
+        // Skip everything up to the end of the item.
+        while !content.is_empty() {
+            match content.parse()? {
+                TokenTree::Punct(punct) if punct.as_char() == ';' => break,
+                TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => break,
+                _ => (),
+            }
+        }
+    }
+
+    Ok(quote! {
+        #(#attrs)*
+        #visibility #mod_token #ident {
+            #submodules
+        }
+    })
+}
+
 /// A parsed `extern_crate` annotated module.
 pub(crate) struct Module {
     /// The attributes on the module.
@@ -121,6 +539,8 @@ pub(crate) struct Module {
     impl_blocks: Vec<ImplBlock>,
     /// The imports contained in the module.
     imports: Vec<ItemUse>,
+    /// The type aliases contained in the module.
+    type_aliases: Vec<ItemType>,
     /// The functions contained in the module.
     functions: Vec<ForeignItemFn>,
     /// The submodules contained in the module.
@@ -152,8 +572,9 @@ impl Parse for Module {
         let content;
         let braces = braced!(content in input);
 
-        let mut impl_blocks = Vec::new();
+        let mut impl_blocks: Vec<ImplBlock> = Vec::new();
         let mut imports = Vec::new();
+        let mut type_aliases = Vec::new();
         let mut functions = Vec::new();
         let mut modules = Vec::new();
 
@@ -162,52 +583,139 @@ impl Parse for Module {
                 impl_blocks.push(content.parse()?);
             } else if <ItemUse as Parse>::parse(&content.fork()).is_ok() {
                 imports.push(content.parse()?);
+            } else if <ItemType as Parse>::parse(&content.fork()).is_ok() {
+                type_aliases.push(content.parse()?);
             } else if <ForeignItemFn as Parse>::parse(&content.fork()).is_ok() {
                 functions.push(content.parse()?);
             } else {
                 modules.push(content.parse().map_err(|err| {
                     syn::Error::new(
                         err.span(),
-                        "expected a module, a function signature, an impl block or a use statement",
+                        "expected a module, a function signature, an impl block, a type alias or a use statement",
                     )
                 })?);
             }
         }
 
-        Ok(Module {
+        let mut module = Module {
             attrs,
             visibility,
             mod_token,
             ident,
             braces,
-            impl_blocks,
+            impl_blocks: Vec::new(),
             imports,
+            type_aliases,
             functions,
             modules,
-        })
+        };
+
+        for mut impl_block in impl_blocks {
+            let module_path = impl_block.take_module_path();
+
+            module.submodule(&module_path).impl_blocks.push(impl_block);
+        }
+
+        Ok(module)
     }
 }
 
 impl Module {
+    /// Creates an empty module with the given name.
+    ///
+    /// This is used for the submodules in the path of the type of an `impl` block.
+    fn empty(ident: Ident) -> Module {
+        let span = ident.span();
+
+        Module {
+            attrs: Vec::new(),
+            visibility: Visibility::Inherited,
+            mod_token: Token![mod](span),
+            ident,
+            braces: Brace { span },
+            impl_blocks: Vec::new(),
+            imports: Vec::new(),
+            type_aliases: Vec::new(),
+            functions: Vec::new(),
+            modules: Vec::new(),
+        }
+    }
+
+    /// Returns the submodule at the given path, creating the missing modules.
+    fn submodule(&mut self, path: &[Ident]) -> &mut Module {
+        let (ident, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return self,
+        };
+
+        let index = match self
+            .modules
+            .iter()
+            .position(|module| module.ident == *ident)
+        {
+            Some(index) => index,
+            None => {
+                self.modules.push(Module::empty(ident.clone()));
+                self.modules.len() - 1
+            }
+        };
+
+        self.modules[index].submodule(rest)
+    }
+
+    /// Renders a placeholder for an `extern_crate` annotated module that could not be parsed.
+    ///
+    /// The original tokens cannot be used as a placeholder, because function signatures without
+    /// bodies are not valid outside of `extern_crate` modules. Instead the module and its
+    /// submodules are rendered without any other content, so that paths to the modules still
+    /// resolve. If not even the outermost module can be parsed, nothing is rendered.
+    pub(crate) fn render_dummy(tokens: TokenStream) -> TokenStream {
+        (|input: ParseStream| parse_dummy_module(input, None))
+            .parse2(tokens)
+            .unwrap_or_default()
+    }
+
     /// Renders this `extern_crate` annotated module to its final result.
     pub(crate) fn render(&self, attr: ExternCrateAttr) -> TokenStream {
         let mut tokens = TokenStream::new();
 
-        self.render_inner(attr.path, &mut tokens, None, &self.ident);
+        if let Some(ExternCrateCfg {
+            cfg_keyword,
+            predicate,
+            ..
+        }) = &attr.cfg
+        {
+            tokens.append_all(quote_spanned! { cfg_keyword.span=> #[cfg(#predicate)] });
+        }
+
+        let options = WrapperOptions {
+            must_use: false,
+            inline: attr
+                .inline
+                .as_ref()
+                .map_or(InlineMode::Always, |inline| inline.mode),
+        };
+
+        self.render_inner(attr.path, &mut tokens, None, &self.ident, &[], options);
 
         tokens
     }
 
     /// A helper function to generate the final token stream.
     ///
-    /// This allows passing the top level visibility and the updated path into recursive calls.
+    /// This allows passing the top level visibility, the updated path, the path of the parent
+    /// module and the options for the generated functions into recursive calls.
+    ///
+    /// Returns whether extension traits were generated in the module or one of its submodules.
     fn render_inner(
         &self,
         mut path: Path,
         tokens: &mut TokenStream,
         visibility: Option<&TokenStream>,
         top_level_module: &Ident,
-    ) {
+        parent_path: &[String],
+        mut options: WrapperOptions,
+    ) -> bool {
         if visibility.is_some() {
             // Update the path only in recursive calls.
             path.segments.push(PathSegment {
@@ -216,14 +724,27 @@ impl Module {
             });
         }
 
+        let mut module_path = parent_path.to_vec();
+        module_path.push(self.ident.to_string());
+
         let mut attrs = self.attrs.clone();
-        let mut render_docs = true;
+        let mut render_docs = config::render_docs_by_default();
         visit_matching_attrs_parsed_mut(&mut attrs, "pre", |attr| match attr.content() {
             PreAttr::NoDoc(_) => {
                 render_docs = false;
 
                 AttributeAction::Remove
             }
+            PreAttr::Doc(_) => {
+                render_docs = true;
+
+                AttributeAction::Remove
+            }
+            PreAttr::MustUse(_) => {
+                options.must_use = true;
+
+                AttributeAction::Remove
+            }
             _ => AttributeAction::Keep,
         });
 
@@ -273,28 +794,108 @@ impl Module {
             use #crate_name::pre;
         });
 
+        let mut extension_imports = TokenStream::new();
         for impl_block in &self.impl_blocks {
-            impl_block.render(&mut brace_content, &path, &visibility, top_level_module);
+            impl_block.render(
+                &mut brace_content,
+                &path,
+                &visibility,
+                top_level_module,
+                &module_path,
+            );
+
+            if let Some(name) = impl_block.render_extension_trait(
+                &mut brace_content,
+                &path,
+                &module_path[module_path.len() - 1],
+                &visibility,
+                options.inline,
+            ) {
+                extension_imports.append_all(quote! { #visibility use super::#name as _; });
+            }
         }
 
         for import in &self.imports {
             brace_content.append_all(quote! { #import });
         }
 
+        for type_alias in &self.type_aliases {
+            render_type_alias(
+                type_alias,
+                &mut brace_content,
+                &visibility,
+                self,
+                &module_path,
+            );
+        }
+
         for function in &self.functions {
-            render_function(function, &mut brace_content, &path, &visibility);
+            let mut preconditions = Vec::new();
+
+            for variant in condition_sets(function) {
+                render_function(&variant, &mut brace_content, &path, &visibility, options);
+
+                visit_matching_attrs_parsed(&variant.attrs, "pre", |attr| {
+                    if let PreAttr::Precondition(precondition) = attr.content() {
+                        for precondition in precondition.clone().expand() {
+                            let precondition = precondition.to_string();
+
+                            if !preconditions.contains(&precondition) {
+                                preconditions.push(precondition);
+                            }
+                        }
+                    }
+                });
+            }
+            register_outline_function(
+                &module_path,
+                Some(&path),
+                &function.sig.ident,
+                !preconditions.is_empty(),
+                function.sig.unsafety.is_some(),
+            );
+
+            if !preconditions.is_empty() {
+                let mut original_path = path_to_strings(&path);
+                original_path.push(function.sig.ident.to_string());
+
+                metadata::export(Record::Outline {
+                    function: &function.sig.ident,
+                    path: &original_path.join("::"),
+                    preconditions: &preconditions,
+                });
+            }
         }
 
         for module in &self.modules {
-            module.render_inner(
+            let has_extension_traits = module.render_inner(
                 path.clone(),
                 &mut brace_content,
                 Some(&visibility),
                 top_level_module,
+                &module_path,
+                options,
             );
+
+            if has_extension_traits {
+                let ident = &module.ident;
+                extension_imports.append_all(quote! { #visibility use super::#ident::pre_ext::*; });
+            }
+        }
+
+        let has_extension_traits = !extension_imports.is_empty();
+        if has_extension_traits {
+            brace_content.append_all(quote_spanned! { self.braces.span=>
+                /// Brings the extension traits for the methods in this module into scope.
+                #visibility mod pre_ext {
+                    #extension_imports
+                }
+            });
         }
 
         tokens.append_all(quote_spanned! { self.braces.span=> { #brace_content } });
+
+        has_extension_traits
     }
 
     /// Generates a token stream that is semantically equivalent to the original token stream.
@@ -315,6 +916,7 @@ impl Module {
                 .map(|impl_block| impl_block.original_token_stream()),
         );
         content.append_all(&self.imports);
+        content.append_all(&self.type_aliases);
         content.append_all(&self.functions);
         content.append_all(self.modules.iter().map(|m| m.original_token_stream()));
 
@@ -330,11 +932,31 @@ fn render_function(
     tokens: &mut TokenStream,
     path: &Path,
     visibility: &TokenStream,
+    options: WrapperOptions,
 ) {
-    tokens.append_all(&function.attrs);
+    tokens.append_all(function.attrs.iter().filter(|attr| !is_inline_attr(attr)));
+    if function.attrs.iter().any(|attr| attr.path.is_ident("pre")) {
+        // The call to the original function has the same name as the wrapper, but does not
+        // require `assure` attributes.
+        tokens
+            .append_all(quote_spanned! { function.span()=> #[pre(allow(missing_assure_summary))] });
+    }
+    if options.must_use
+        && function.sig.output != ReturnType::Default
+        && !function
+            .attrs
+            .iter()
+            .any(|attr| attr.path.is_ident("must_use"))
+    {
+        tokens.append_all(quote_spanned! { function.span()=> #[must_use] });
+    }
     let doc_header = generate_extern_crate_fn_docs(path, &function.sig, function.span());
     tokens.append_all(quote! { #doc_header });
-    tokens.append_all(quote_spanned! { function.span()=> #[inline(always)] });
+    tokens.append_all(
+        options
+            .inline
+            .render_attribute(&function.attrs, function.span()),
+    );
     tokens.append_all(visibility.clone().into_iter().map(|mut token| {
         token.set_span(function.span());
         token
@@ -367,5 +989,127 @@ fn render_function(
         }),
         quote_spanned! { function.span()=> , },
     );
-    tokens.append_all(quote_spanned! { function.span()=> { #path(#args_list) } });
+
+    // The wrapper of an `async fn` returns its own future, so it needs to await the original one.
+    let await_original = function
+        .sig
+        .asyncness
+        .map(|_| quote_spanned! { function.span()=> .await });
+    tokens.append_all(quote_spanned! { function.span()=> { #path(#args_list) #await_original } });
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{parse2, parse_quote};
+
+    use super::*;
+
+    #[test]
+    fn parse_extern_crate_attr() {
+        let attr: ExternCrateAttr = parse2(quote! { std }).expect("valid attribute");
+        assert!(attr.inline.is_none());
+        assert_eq!(attr.to_string(), "#[extern_crate(std)]");
+
+        let attr: ExternCrateAttr =
+            parse2(quote! { std, inline = "hint", cfg(test) }).expect("valid attribute");
+        assert_eq!(
+            attr.inline.map(|inline| inline.mode),
+            Some(InlineMode::Hint)
+        );
+        assert!(attr.cfg.is_some());
+
+        let attr: ExternCrateAttr =
+            parse2(quote! { core, cfg(test), inline = "never" }).expect("valid attribute");
+        assert_eq!(
+            attr.to_string(),
+            "#[extern_crate(core, cfg(test), inline = \"never\")]"
+        );
+
+        let attr: ExternCrateAttr = parse2(quote! { ::std::collections }).expect("valid attribute");
+        assert_eq!(attr.to_string(), "#[extern_crate(::std::collections)]");
+
+        assert!(parse2::<ExternCrateAttr>(quote! { std, inline = "sometimes" }).is_err());
+        assert!(parse2::<ExternCrateAttr>(quote! { std, inline = never }).is_err());
+        assert!(
+            parse2::<ExternCrateAttr>(quote! { std, inline = "hint", inline = "never" }).is_err()
+        );
+        assert!(parse2::<ExternCrateAttr>(quote! { std, cfg(a), cfg(b) }).is_err());
+    }
+
+    #[test]
+    fn referenced_crates() {
+        let referenced = |path: TokenStream| {
+            let path: Path = parse2(path).expect("valid path");
+
+            referenced_crate(&path).map(|ident| ident.to_string())
+        };
+
+        assert_eq!(referenced(quote! { libc }), Some("libc".into()));
+        assert_eq!(referenced(quote! { ::libc::unix }), Some("libc".into()));
+        assert_eq!(referenced(quote! { std::ptr }), None);
+        assert_eq!(referenced(quote! { ::core }), None);
+        assert_eq!(referenced(quote! { crate::library }), None);
+        assert_eq!(referenced(quote! { super::library }), None);
+    }
+
+    #[test]
+    fn inline_attributes() {
+        let span = Span::call_site();
+        let render =
+            |mode: InlineMode, attrs: &[Attribute]| mode.render_attribute(attrs, span).to_string();
+
+        assert_eq!(
+            render(InlineMode::Always, &[]),
+            quote! { #[inline(always)] }.to_string()
+        );
+        assert_eq!(
+            render(InlineMode::Hint, &[]),
+            quote! { #[inline] }.to_string()
+        );
+        assert_eq!(
+            render(InlineMode::Never, &[]),
+            quote! { #[inline(never)] }.to_string()
+        );
+        assert_eq!(render(InlineMode::None, &[]), "");
+
+        let attrs: Vec<Attribute> = vec![
+            parse_quote! { #[must_use] },
+            parse_quote! { #[inline(never)] },
+        ];
+        assert_eq!(
+            render(InlineMode::Always, &attrs),
+            quote! { #[inline(never)] }.to_string()
+        );
+    }
+
+    #[test]
+    fn dummy_module() {
+        let dummy = Module::render_dummy(quote! {
+            #[doc = "core"]
+            mod pre_core {
+                const SIZE: usize = 4;
+                pub mod mem {
+                    unsafe fn zeroed<T>() -> T;
+                    impl Foo { fn foo(); }
+                    mod inner {}
+                }
+                fn broken() -> ;
+            }
+        });
+        assert_eq!(
+            dummy.to_string(),
+            quote! {
+                #[doc = "core"]
+                mod pre_core {
+                    pub(crate) mod mem {
+                        pub(crate) mod inner {}
+                    }
+                }
+            }
+            .to_string()
+        );
+
+        assert!(Module::render_dummy(quote! { fn foo(); }).is_empty());
+    }
 }
@@ -0,0 +1,328 @@
+//! Handles condition groups of functions in `extern_crate` modules.
+//!
+//! A condition group is a precondition that only applies if a feature of the callee crate is
+//! enabled or disabled, such as in
+//!
+//! ```rust,ignore
+//! #[pre::extern_crate(alloc_lib)]
+//! mod pre_alloc_lib {
+//!     #[pre(valid_ptr(ptr, r))]
+//!     #[pre(when(not(feature = "zeroed")), "`ptr` points to initialized memory")]
+//!     unsafe fn read(ptr: *const u8) -> u8;
+//! }
+//! ```
+//!
+//! The features of the callee crate cannot be observed by the consumer, so the features of the
+//! consumer crate are used instead. The consumer is expected to enable a feature of the same name
+//! exactly if it enables the feature of the callee crate. The function above turns into one
+//! wrapper for each condition set:
+//!
+//! ```rust,ignore
+//! #[cfg(feature = "zeroed")]
+//! #[pre(valid_ptr(ptr, r))]
+//! unsafe fn read(ptr: *const u8) -> u8;
+//!
+//! #[cfg(not(feature = "zeroed"))]
+//! #[pre(valid_ptr(ptr, r))]
+//! #[pre("`ptr` points to initialized memory")]
+//! unsafe fn read(ptr: *const u8) -> u8;
+//! ```
+//!
+//! All condition groups of a function must be keyed by the same feature, because otherwise the
+//! condition set of the function could not be determined by a single `cfg` predicate.
+
+use proc_macro2::{TokenStream, TokenTree};
+use proc_macro_error::emit_error;
+use quote::quote_spanned;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_quote_spanned,
+    spanned::Spanned,
+    token::Paren,
+    Attribute, ForeignItemFn, LitStr, Token,
+};
+
+/// The custom keywords used in condition groups.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(when);
+    custom_keyword!(feature);
+    custom_keyword!(not);
+}
+
+/// The feature that a condition group is keyed by.
+struct FeatureKey {
+    /// Whether the group applies if the feature is enabled, rather than disabled.
+    enabled: bool,
+    /// The name of the feature.
+    feature: LitStr,
+}
+
+impl Parse for FeatureKey {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let expected = "expected `feature = \"...\"` or `not(feature = \"...\")`";
+
+        if input.peek(custom_keywords::not) {
+            input.parse::<custom_keywords::not>()?;
+            let content;
+            parenthesized!(content in input);
+            let key: FeatureKey = content
+                .parse()
+                .map_err(|err| syn::Error::new(err.span(), expected))?;
+
+            if !key.enabled {
+                return Err(syn::Error::new(key.feature.span(), expected));
+            }
+
+            Ok(FeatureKey {
+                enabled: false,
+                feature: key.feature,
+            })
+        } else if input.peek(custom_keywords::feature) {
+            input.parse::<custom_keywords::feature>()?;
+            input.parse::<Token![=]>()?;
+
+            Ok(FeatureKey {
+                enabled: true,
+                feature: input.parse()?,
+            })
+        } else {
+            Err(input.error(expected))
+        }
+    }
+}
+
+impl FeatureKey {
+    /// Renders the `cfg` predicate under which the group applies.
+    fn predicate(&self, enabled: bool) -> TokenStream {
+        let feature = &self.feature;
+
+        if enabled {
+            quote_spanned! { feature.span()=> feature = #feature }
+        } else {
+            quote_spanned! { feature.span()=> not(feature = #feature) }
+        }
+    }
+}
+
+/// The content of a `pre` attribute with a condition group, such as
+/// `when(feature = "strict"), x > 0`.
+struct WhenAttr {
+    /// The `when` keyword.
+    _when_keyword: custom_keywords::when,
+    /// The parentheses around the feature key.
+    _parentheses: Paren,
+    /// The feature the group is keyed by.
+    key: FeatureKey,
+    /// The comma between the feature key and the precondition.
+    _comma: Token![,],
+    /// The tokens of the precondition.
+    ///
+    /// They are parsed when the `pre` attribute of the generated wrapper is expanded.
+    precondition: TokenStream,
+}
+
+impl Parse for WhenAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let when_keyword = input.parse()?;
+        let content;
+        let parentheses = parenthesized!(content in input);
+        let key = content.parse()?;
+
+        if !content.is_empty() {
+            return Err(content.error("unexpected token"));
+        }
+
+        let comma = input.parse()?;
+        let precondition: TokenStream = input.parse()?;
+
+        if precondition.is_empty() {
+            return Err(input.error("expected a precondition after the condition group"));
+        }
+
+        Ok(WhenAttr {
+            _when_keyword: when_keyword,
+            _parentheses: parentheses,
+            key,
+            _comma: comma,
+            precondition,
+        })
+    }
+}
+
+/// Checks whether the arguments of a `pre` attribute start with a condition group.
+fn is_when_attr(attr: &Attribute) -> bool {
+    if !attr.path.is_ident("pre") {
+        return false;
+    }
+
+    let mut tokens = match attr.parse_args::<TokenStream>() {
+        Ok(tokens) => tokens.into_iter(),
+        Err(_) => return false,
+    };
+
+    matches!(
+        (tokens.next(), tokens.next()),
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(_))) if ident == "when"
+    )
+}
+
+/// Returns the variants of the function for each condition set of its condition groups.
+///
+/// Each variant has a `cfg` attribute selecting it and the preconditions of the matching
+/// condition groups as regular `pre` attributes. A function without condition groups is returned
+/// unchanged as the only variant.
+pub(super) fn condition_sets(function: &ForeignItemFn) -> Vec<ForeignItemFn> {
+    let mut groups = Vec::new();
+
+    for (index, attr) in function.attrs.iter().enumerate() {
+        if !is_when_attr(attr) {
+            continue;
+        }
+
+        match attr.parse_args::<WhenAttr>() {
+            Ok(when_attr) => groups.push((index, attr.span(), when_attr)),
+            Err(err) => emit_error!(err.span(), "{}", err),
+        }
+    }
+
+    let without_groups = || ForeignItemFn {
+        attrs: function
+            .attrs
+            .iter()
+            .filter(|attr| !is_when_attr(attr))
+            .cloned()
+            .collect(),
+        ..function.clone()
+    };
+
+    let first_key = match groups.first() {
+        Some((_, _, when_attr)) => &when_attr.key,
+        None => return vec![without_groups()],
+    };
+
+    let mut ambiguous = false;
+    for (_, _, when_attr) in &groups[1..] {
+        if when_attr.key.feature.value() != first_key.feature.value() {
+            emit_error!(
+                when_attr.key.feature.span(),
+                "the condition set of `{}` cannot be determined unambiguously",
+                function.sig.ident;
+                note = first_key.feature.span() => "the condition groups are keyed by the feature `{}` here",
+                first_key.feature.value();
+                help = "all condition groups of a function must be keyed by the same feature"
+            );
+
+            ambiguous = true;
+        }
+    }
+
+    if ambiguous {
+        return vec![without_groups()];
+    }
+
+    [true, false]
+        .iter()
+        .map(|&enabled| {
+            let predicate = first_key.predicate(enabled);
+            let cfg_attr: Attribute = parse_quote_spanned! { first_key.feature.span()=>
+                #[cfg(#predicate)]
+            };
+
+            let mut attrs = vec![cfg_attr];
+            for (index, attr) in function.attrs.iter().enumerate() {
+                match groups
+                    .iter()
+                    .find(|(group_index, _, _)| *group_index == index)
+                {
+                    Some((_, span, when_attr)) => {
+                        if when_attr.key.enabled == enabled {
+                            let precondition = &when_attr.precondition;
+
+                            attrs.push(parse_quote_spanned! { *span=> #[pre(#precondition)] });
+                        }
+                    }
+                    None if is_when_attr(attr) => (),
+                    None => attrs.push(attr.clone()),
+                }
+            }
+
+            ForeignItemFn {
+                attrs,
+                ..function.clone()
+            }
+        })
+        .collect()
+}
+
+/// Returns the error for a condition group outside of a function in an `extern_crate` module.
+pub(crate) fn unsupported_when_error(input: ParseStream) -> syn::Error {
+    syn::Error::new(
+        input.span(),
+        "condition groups are only supported for free functions in `extern_crate` modules",
+    )
+}
+
+/// Checks whether the input starts with a condition group.
+pub(crate) fn peek_when(input: ParseStream) -> bool {
+    input.peek(custom_keywords::when) && input.peek2(Paren)
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    fn render(function: TokenStream) -> Vec<String> {
+        let function: ForeignItemFn = parse2(function).expect("valid function");
+
+        condition_sets(&function)
+            .iter()
+            .map(|variant| {
+                variant
+                    .attrs
+                    .iter()
+                    .map(|attr| quote! { #attr }.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_condition_sets() {
+        assert_eq!(
+            render(quote! {
+                #[pre(x > 0)]
+                #[pre(when(feature = "strict"), x < 10)]
+                #[pre(when(not(feature = "strict")), "x is small")]
+                fn foo(x: i32);
+            }),
+            [
+                "# [cfg (feature = \"strict\")] # [pre (x > 0)] # [pre (x < 10)]",
+                "# [cfg (not (feature = \"strict\"))] # [pre (x > 0)] # [pre (\"x is small\")]",
+            ]
+        );
+        assert_eq!(
+            render(quote! {
+                #[pre(x > 0)]
+                fn foo(x: i32);
+            }),
+            ["# [pre (x > 0)]"]
+        );
+    }
+
+    #[test]
+    fn parse_when_attr() {
+        assert!(parse2::<WhenAttr>(quote! { when(feature = "a"), x > 0 }).is_ok());
+        assert!(parse2::<WhenAttr>(quote! { when(not(feature = "a")), "foo" }).is_ok());
+        assert!(parse2::<WhenAttr>(quote! { when(feature = "a") }).is_err());
+        assert!(parse2::<WhenAttr>(quote! { when(feature = "a"), }).is_err());
+        assert!(parse2::<WhenAttr>(quote! { when(unix), x > 0 }).is_err());
+        assert!(parse2::<WhenAttr>(quote! { when(not(not(feature = "a"))), x > 0 }).is_err());
+    }
+}
@@ -6,24 +6,34 @@ use quote::{format_ident, quote, quote_spanned, TokenStreamExt};
 use syn::{
     braced,
     parse::{Parse, ParseStream},
+    parse_quote_spanned,
     spanned::Spanned,
     token::Brace,
-    ForeignItemFn, Generics, Ident, Path, PathArguments, PathSegment, Token, Type,
+    visit_mut::{visit_type_mut, VisitMut},
+    FnArg, ForeignItemFn, GenericArgument, GenericParam, Generics, Ident, ItemFn, Path,
+    PathArguments, PathSegment, QSelf, ReturnType, Signature, Token, Type, TypeInfer, TypePath,
+    Visibility,
 };
 
 use crate::{
+    config,
     documentation::{generate_docs, ImplBlockContext},
-    helpers::visit_matching_attrs_parsed,
+    helpers::{contains_impl_trait, visit_matching_attrs_parsed},
     pre_attr::PreAttr,
     precondition::CfgPrecondition,
+    render_pre,
 };
 
+use super::{is_inline_attr, register_outline_function, InlineMode};
+
 /// An impl block in a `extern_crate` module.
 pub(crate) struct ImplBlock {
     /// The impl keyword.
     impl_keyword: Token![impl],
     /// The generics for the impl block.
     pub(crate) generics: Generics,
+    /// The implemented trait, if this is a trait implementation.
+    pub(crate) trait_: Option<(Path, Token![for])>,
     /// The type which the impl block is for.
     pub(crate) self_ty: Box<Type>,
     /// The brace of the block.
@@ -36,6 +46,14 @@ impl Parse for ImplBlock {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let impl_keyword = input.parse()?;
         let generics = input.parse()?;
+
+        let fork = input.fork();
+        let trait_ = if fork.parse::<Path>().is_ok() && fork.peek(Token![for]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+
         let self_ty = input.parse()?;
         let where_clause = input.parse()?;
         let content;
@@ -53,6 +71,7 @@ impl Parse for ImplBlock {
                 where_clause,
                 ..generics
             },
+            trait_,
             self_ty,
             brace,
             items,
@@ -80,6 +99,9 @@ impl ImplBlock {
         tokens.append_all(quote! { #impl_keyword });
         let generics = &self.generics;
         tokens.append_all(quote! { #generics });
+        if let Some((trait_, for_token)) = &self.trait_ {
+            tokens.append_all(quote! { #trait_ #for_token });
+        }
         let self_ty = &self.self_ty;
         tokens.append_all(quote! { #self_ty });
         let where_clause = &generics.where_clause;
@@ -92,6 +114,39 @@ impl ImplBlock {
         tokens
     }
 
+    /// Removes the module path in front of the type of the impl block and returns it.
+    ///
+    /// This allows writing `impl sync::atomic::AtomicUsize { ... }` instead of putting the
+    /// `impl` block into the `sync` and `atomic` submodules. Prefixes that are not plain module
+    /// names, such as `super` or segments with generic arguments, are kept.
+    ///
+    /// For trait implementations the module path is taken from the trait instead, as in
+    /// `impl alloc::GlobalAlloc for System { ... }`.
+    pub(crate) fn take_module_path(&mut self) -> Vec<Ident> {
+        let path = match (&mut self.trait_, &mut *self.self_ty) {
+            (Some((path, _)), _) if path.leading_colon.is_none() => path,
+            (None, Type::Path(TypePath { qself: None, path })) if path.leading_colon.is_none() => {
+                path
+            }
+            _ => return Vec::new(),
+        };
+
+        let prefix_len = path.segments.len() - 1;
+        if prefix_len == 0 || !path.segments.iter().take(prefix_len).all(is_module_name) {
+            return Vec::new();
+        }
+
+        let mut segments = std::mem::take(&mut path.segments).into_iter();
+        let module_path = segments
+            .by_ref()
+            .take(prefix_len)
+            .map(|segment| segment.ident)
+            .collect();
+        path.segments = segments.collect();
+
+        module_path
+    }
+
     /// Returns the type that this impl block is for.
     pub(crate) fn ty(&self) -> Option<&PathSegment> {
         if let Type::Path(path) = &*self.self_ty {
@@ -106,16 +161,10 @@ impl ImplBlock {
                     }
                 }
 
-                let plural = if path.path.segments.len() > 2 {
-                    "submodules"
-                } else {
-                    "a submodule"
-                };
-
                 emit_error!(
                     path,
-                    "only paths of length 1 are supported here";
-                    help = "try adding `{}` as {} and put the `impl` block there", path_str, plural
+                    "only module names are supported in front of the type here";
+                    help = "`{}` cannot be turned into submodules, try removing it", path_str
                 );
                 return None;
             }
@@ -154,6 +203,80 @@ impl ImplBlock {
         }
     }
 
+    /// Returns the trait that this impl block implements, if it is a trait implementation.
+    pub(crate) fn trait_segment(&self) -> Option<&PathSegment> {
+        let (path, _) = self.trait_.as_ref()?;
+
+        if path.segments.len() != 1 || path.leading_colon.is_some() {
+            emit_error!(
+                path,
+                "only module names are supported in front of the trait here";
+                help = "try putting the `impl` block into the module containing the trait"
+            );
+            return None;
+        }
+
+        let trait_ = &path.segments[0];
+
+        if matches!(trait_.arguments, PathArguments::Parenthesized(_)) {
+            emit_error!(
+                trait_.arguments.span(),
+                "parenthesized type arguments are not supported here"
+            );
+
+            None
+        } else {
+            Some(trait_)
+        }
+    }
+
+    /// Generates the name of the stub function for a function in this impl block.
+    ///
+    /// `module` is the name of the module that the stub is defined in.
+    fn stub_name(
+        &self,
+        module: &str,
+        ty: &PathSegment,
+        fn_name: &Ident,
+        span: Span,
+    ) -> Option<Ident> {
+        if self.trait_.is_some() {
+            let trait_ = self.trait_segment()?;
+
+            Some(trait_impl_stub_name(
+                trait_,
+                ty,
+                &self.generics,
+                fn_name,
+                span,
+            ))
+        } else {
+            Some(impl_block_stub_name(
+                module,
+                ty,
+                &self.generics,
+                fn_name,
+                span,
+            ))
+        }
+    }
+
+    /// Returns a copy of the signature with all occurrences of `Self` replaced by the type of the
+    /// `impl` block.
+    ///
+    /// This allows the signature to be used outside of the `impl` block, for example in generated
+    /// code or documentation.
+    pub(crate) fn substitute_self(&self, signature: &Signature) -> Signature {
+        let mut signature = signature.clone();
+
+        ReplaceSelf {
+            self_ty: &self.self_ty,
+        }
+        .visit_signature_mut(&mut signature);
+
+        signature
+    }
+
     /// Generates the code for an impl block inside a `extern_crate` module.
     pub(crate) fn render(
         &self,
@@ -161,6 +284,7 @@ impl ImplBlock {
         path: &Path,
         visibility: &TokenStream,
         top_level_module: &Ident,
+        module_path: &[String],
     ) {
         let ty = if let Some(ty) = self.ty() {
             ty
@@ -170,41 +294,57 @@ impl ImplBlock {
 
         for function in &self.items {
             let docs = {
-                let mut render_docs = true;
+                let mut render_docs = config::render_docs_by_default();
                 let mut preconditions = Vec::new();
 
                 visit_matching_attrs_parsed(&function.attrs, "pre", |attr| {
                     match attr.into_content() {
                         (PreAttr::NoDoc(_), _, _) => render_docs = false,
-                        (PreAttr::Precondition(precondition), cfg, span) => {
-                            preconditions.push(CfgPrecondition {
+                        (PreAttr::Doc(_), _, _) => render_docs = true,
+                        (PreAttr::Precondition(precondition), cfg, span) => preconditions.extend(
+                            CfgPrecondition {
                                 precondition,
                                 cfg,
                                 span,
-                            })
-                        }
+                            }
+                            .expand(),
+                        ),
                         _ => (),
                     }
                 });
 
                 if render_docs {
                     Some(generate_docs(
-                        &function.sig,
+                        &self.substitute_self(&function.sig),
                         &preconditions,
                         Some(ImplBlockContext {
                             impl_block: self,
                             path,
                             top_level_module,
                         }),
+                        None,
                     ))
                 } else {
                     None
                 }
             };
 
-            let name = impl_block_stub_name(ty, &function.sig.ident, function.span());
+            let module = module_path.last().map_or("", |module| &module[..]);
+            let name = match self.stub_name(module, ty, &function.sig.ident, function.span()) {
+                Some(name) => name,
+                None => return,
+            };
+            register_outline_function(
+                module_path,
+                None,
+                &name,
+                !method_preconditions(function).is_empty(),
+                function.sig.unsafety.is_some(),
+            );
+            // The stub is `const`, so that `const` methods can be checked in `const` contexts.
+            let constness = &function.sig.constness;
             tokens.append_all(quote! { #docs });
-            tokens.append_all(&function.attrs);
+            tokens.append_all(function.attrs.iter().filter(|attr| !is_inline_attr(attr)));
             tokens.append_all(quote_spanned! { function.sig.span()=>
                 // The documentation for `impl` blocks is generated here instead of in the `pre`
                 // attribute, to allow access to information about the `impl` block.
@@ -217,14 +357,315 @@ impl ImplBlock {
                 #[pre(no_debug_assert)]
                 #[inline(always)]
                 #[allow(non_snake_case)]
-                #visibility fn #name() {}
+                #visibility #constness fn #name() {}
             });
         }
     }
 }
 
+impl ImplBlock {
+    /// Generates an extension trait for the methods of the impl block, if the backend supports it.
+    ///
+    /// The extension trait is implemented for the type in the original crate. Its methods are
+    /// named like the original methods with a `_pre` suffix and have the preconditions attached,
+    /// so they can be called using method call syntax without a `forward` attribute.
+    ///
+    /// Returns the name of the generated trait.
+    pub(crate) fn render_extension_trait(
+        &self,
+        tokens: &mut TokenStream,
+        path: &Path,
+        module: &str,
+        visibility: &TokenStream,
+        inline: InlineMode,
+    ) -> Option<Ident> {
+        // Preconditions on methods are only supported by the `const_generics_impl` backend.
+        if !cfg!(all(nightly, not(feature = "backend-struct"))) {
+            return None;
+        }
+
+        // The methods of trait implementations are called through the trait, so a `forward`
+        // attribute naming the trait is required for them.
+        if self.trait_.is_some() {
+            return None;
+        }
+
+        let ty = self.ty()?;
+        let span = self.span();
+        let original_ty = original_type(ty, path);
+
+        let mut trait_items = TokenStream::new();
+        let mut impl_items = TokenStream::new();
+        for function in &self.items {
+            let preconditions = method_preconditions(function);
+
+            // Only methods can be called using method call syntax and `async` methods are not
+            // supported in traits. Returning `impl Trait` from a trait method would require naming
+            // the lifetimes captured by the opaque type, so these methods need `forward` instead.
+            if preconditions.is_empty()
+                || function.sig.receiver().is_none()
+                || function.sig.asyncness.is_some()
+                || matches!(&function.sig.output, ReturnType::Type(_, ty) if contains_impl_trait(ty))
+            {
+                continue;
+            }
+
+            let mut method = render_extension_method(function, &original_ty);
+            let rendered = render_pre(preconditions, &mut method, function.span(), false);
+
+            let sig = &method.sig;
+            let inline = inline.render_attribute(&function.attrs, function.span());
+            trait_items.append_all(quote! { #sig; });
+            impl_items.append_all(quote! {
+                #inline
+                #rendered
+            });
+        }
+
+        if trait_items.is_empty() {
+            return None;
+        }
+
+        let name =
+            impl_block_stub_name(module, ty, &self.generics, &format_ident!("pre_ext"), span);
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        tokens.append_all(quote_spanned! { span=>
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #visibility trait #name #impl_generics #where_clause {
+                #trait_items
+            }
+
+            impl #impl_generics #name #ty_generics for #original_ty #where_clause {
+                #impl_items
+            }
+        });
+
+        Some(name)
+    }
+}
+
+/// Returns the type in the original crate that an impl block for `ty` applies to.
+///
+/// The `const_pointer` and `mut_pointer` pseudo types refer to raw pointers.
+fn original_type(ty: &PathSegment, path: &Path) -> Type {
+    let pointee = match &ty.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(pointee) => Some(pointee),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match (&*ty.ident.to_string(), pointee) {
+        ("const_pointer", Some(pointee)) => parse_quote_spanned! { ty.span()=> *const #pointee },
+        ("mut_pointer", Some(pointee)) => parse_quote_spanned! { ty.span()=> *mut #pointee },
+        _ => parse_quote_spanned! { ty.span()=> #path::#ty },
+    }
+}
+
+/// Checks whether the path segment is a plain module name.
+pub(super) fn is_module_name(segment: &PathSegment) -> bool {
+    segment.arguments.is_empty()
+        && !["self", "super", "crate", "Self"]
+            .iter()
+            .any(|keyword| segment.ident == keyword)
+}
+
+/// Returns the preconditions of a method in an impl block.
+pub(super) fn method_preconditions(function: &ForeignItemFn) -> Vec<CfgPrecondition> {
+    let mut preconditions = Vec::new();
+
+    visit_matching_attrs_parsed(&function.attrs, "pre", |attr| {
+        if let (PreAttr::Precondition(precondition), cfg, span) = attr.into_content() {
+            preconditions.extend(
+                CfgPrecondition {
+                    precondition,
+                    cfg,
+                    span,
+                }
+                .expand(),
+            )
+        }
+    });
+
+    preconditions
+}
+
+/// Generates a method of an extension trait that calls the original method.
+///
+/// The preconditions are not yet added to the generated method.
+fn render_extension_method(function: &ForeignItemFn, original_ty: &Type) -> ItemFn {
+    let span = function.span();
+    let mut sig = function.sig.clone();
+    let original_name = &function.sig.ident;
+    sig.ident = format_ident!("{}_pre", original_name, span = original_name.span());
+    // Trait methods cannot be `const`.
+    sig.constness = None;
+
+    let generic_args = sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(ty) => Some(&ty.ident),
+            GenericParam::Const(constant) => Some(&constant.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let turbofish = if generic_args.is_empty() {
+        None
+    } else {
+        Some(quote_spanned! { span=> ::<#(#generic_args),*> })
+    };
+    let args = sig.inputs.iter().map(|arg| match arg {
+        FnArg::Receiver(receiver) => {
+            let self_token = receiver.self_token;
+            quote! { #self_token }
+        }
+        FnArg::Typed(pat) => {
+            let pat = &pat.pat;
+            quote! { #pat }
+        }
+    });
+
+    let block = parse_quote_spanned! { span=>
+        {
+            <#original_ty>::#original_name #turbofish (#(#args),*)
+        }
+    };
+
+    ItemFn {
+        attrs: Vec::new(),
+        vis: Visibility::Inherited,
+        sig,
+        block,
+    }
+}
+
+/// Replaces all occurrences of the `Self` type with a given type.
+struct ReplaceSelf<'a> {
+    /// The type that `Self` refers to.
+    self_ty: &'a Type,
+}
+
+impl VisitMut for ReplaceSelf<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(TypePath { qself: None, path }) = ty {
+            if path.leading_colon.is_none()
+                && path.segments.first().map(|segment| segment.ident == "Self") == Some(true)
+            {
+                if path.segments.len() == 1 {
+                    *ty = self.self_ty.clone();
+                } else {
+                    // `Self::Assoc` becomes `<SelfTy>::Assoc`
+                    let span = path.segments[0].ident.span();
+                    let mut rest = path.clone();
+                    rest.segments = path.segments.clone().into_pairs().skip(1).collect();
+                    rest.leading_colon = Some(Token![::](span));
+
+                    *ty = Type::Path(TypePath {
+                        qself: Some(QSelf {
+                            lt_token: Token![<](span),
+                            ty: Box::new(self.self_ty.clone()),
+                            position: 0,
+                            as_token: None,
+                            gt_token: Token![>](span),
+                        }),
+                        path: rest,
+                    });
+                }
+
+                return;
+            }
+        }
+
+        visit_type_mut(self, ty);
+    }
+}
+
+/// Returns the generic arguments of `ty` that are not generic parameters of the `impl` block.
+///
+/// Type and const arguments that are generic parameters of the `impl` block (as given by
+/// `generics`) are replaced by `_` and lifetime arguments are removed entirely, since they never
+/// distinguish between `impl` blocks. If no concrete argument remains, `None` is returned.
+///
+/// At call sites no generic parameters are in scope, so `_` can be used there to refer to a
+/// generic argument of the `impl` block.
+pub(crate) fn concrete_type_args(
+    ty: &PathSegment,
+    generics: &Generics,
+) -> Option<Vec<GenericArgument>> {
+    let args = if let PathArguments::AngleBracketed(args) = &ty.arguments {
+        &args.args
+    } else {
+        return None;
+    };
+
+    let is_impl_param = |ident: &Ident| {
+        generics.params.iter().any(|param| match param {
+            GenericParam::Type(param) => param.ident == *ident,
+            GenericParam::Const(param) => param.ident == *ident,
+            GenericParam::Lifetime(_) => false,
+        })
+    };
+
+    let mut any_concrete = false;
+    let args: Vec<_> = args
+        .iter()
+        .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_)))
+        .map(|arg| match arg {
+            GenericArgument::Type(Type::Path(TypePath { qself: None, path }))
+                if path.get_ident().map(is_impl_param) == Some(true) =>
+            {
+                GenericArgument::Type(Type::Infer(TypeInfer {
+                    underscore_token: Token![_](path.span()),
+                }))
+            }
+            GenericArgument::Type(Type::Infer(_)) => arg.clone(),
+            _ => {
+                any_concrete = true;
+
+                arg.clone()
+            }
+        })
+        .collect();
+
+    if any_concrete {
+        Some(args)
+    } else {
+        None
+    }
+}
+
+/// Computes a short FNV-1a hash of the text.
+///
+/// The result is deterministic between different compilations and compiler versions.
+fn hash_text(text: &str) -> String {
+    const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    let hash = text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+
+    format!("{:08x}", hash)
+}
+
 /// Generates a name to use for an impl block stub function.
-pub(crate) fn impl_block_stub_name(ty: &PathSegment, fn_name: &Ident, span: Span) -> Ident {
+///
+/// `module` is the name of the module that the stub is defined in. At a call site this is the
+/// segment of the path in front of the type.
+///
+/// `generics` are the generic parameters of the `impl` block. When generating the name at a call
+/// site, where no generic parameters are known, `Generics::default()` should be passed.
+pub(crate) fn impl_block_stub_name(
+    module: &str,
+    ty: &PathSegment,
+    generics: &Generics,
+    fn_name: &Ident,
+    span: Span,
+) -> Ident {
     // Ideally this would start with `_` to reduce the chance for naming collisions with actual
     // functions. However this would silence any `dead_code` warnings, which the user may want to
     // be aware of. Instead this ends with `__` to reduce the chance for naming collisions.
@@ -232,8 +673,258 @@ pub(crate) fn impl_block_stub_name(ty: &PathSegment, fn_name: &Ident, span: Span
     // Note that hygiene would not help in reducing naming collisions, because the function needs
     // to be callable from an `assure` attribute that could possibly reside in a different hygenic
     // context.
-    let mut ident = format_ident!("{}__impl__{}__", ty.ident, fn_name);
+    //
+    // `impl` blocks for the same type with different concrete generic arguments (such as
+    // `X<bool>` and `X<()>`) or for same-named types in different modules would still collide, so
+    // a hash of the module and of those arguments is included.
+    // Only the innermost module is hashed, because that is all a call site can know about. The
+    // full path would break `forward` attributes that refer to the `impl` block through a `use`
+    // declaration, such as `forward(impl ptr::NonNull)` after `use pre_std::ptr`.
+    let args = concrete_type_args(ty, generics).unwrap_or_default();
+    let hash = hash_text(&format!("{}::{}", module, quote! { #(#args),* }));
+    let mut ident = format_ident!("{}__impl_{}__{}__", ty.ident, hash, fn_name);
+    ident.set_span(span);
+
+    ident
+}
+
+/// Generates a name to use for a stub function of an `impl` block implementing a trait.
+///
+/// The name contains the name of the trait, so that it does not collide with the stubs of
+/// inherent `impl` blocks or of implementations of other traits for the same type. As for
+/// `impl_block_stub_name`, concrete generic arguments of the trait and the type are included as a
+/// hash and `Generics::default()` should be passed at call sites.
+pub(crate) fn trait_impl_stub_name(
+    trait_: &PathSegment,
+    ty: &PathSegment,
+    generics: &Generics,
+    fn_name: &Ident,
+    span: Span,
+) -> Ident {
+    let trait_args = concrete_type_args(trait_, generics);
+    let ty_args = concrete_type_args(ty, generics);
+
+    let mut ident = if trait_args.is_some() || ty_args.is_some() {
+        let trait_args = trait_args.unwrap_or_default();
+        let ty_args = ty_args.unwrap_or_default();
+        let hash = hash_text(&quote! { #(#trait_args),* for #(#ty_args),* }.to_string());
+
+        format_ident!(
+            "{}__impl_{}_for_{}__{}__",
+            ty.ident,
+            trait_.ident,
+            hash,
+            fn_name
+        )
+    } else {
+        format_ident!("{}__impl_{}_for__{}__", ty.ident, trait_.ident, fn_name)
+    };
     ident.set_span(span);
 
     ident
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn substitute_self() {
+        let impl_block: ImplBlock = parse2(quote! {
+            impl<T> NonNull<T> {
+                const unsafe fn new_unchecked(ptr: *mut T) -> Self;
+                fn foo(self: Box<Self>, x: Option<Self>) -> Self::Target;
+            }
+        })
+        .expect("valid impl block");
+
+        let substituted: Vec<_> = impl_block
+            .items
+            .iter()
+            .map(|function| {
+                let signature = impl_block.substitute_self(&function.sig);
+
+                quote! { #signature }.to_string()
+            })
+            .collect();
+
+        assert_eq!(
+            substituted[0],
+            quote! { const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T> }.to_string()
+        );
+        assert_eq!(
+            substituted[1],
+            quote! { fn foo(self: Box<NonNull<T> >, x: Option<NonNull<T> >) -> <NonNull<T> >::Target }
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn stub_name_generic_impl() {
+        let impl_block: ImplBlock = parse2(quote! {
+            impl<'a, T, const N: usize> Foo<'a, T, N> {}
+        })
+        .expect("valid impl block");
+        let ty: PathSegment = parse2(quote! { Foo<'a, T, N> }).expect("valid path segment");
+        let fn_name: Ident = parse2(quote! { bar }).expect("valid identifier");
+
+        let name =
+            impl_block_stub_name("a", &ty, &impl_block.generics, &fn_name, Span::call_site());
+        let call_site_ty: PathSegment = parse2(quote! { Foo }).expect("valid path segment");
+
+        assert_eq!(
+            name,
+            impl_block_stub_name(
+                "a",
+                &call_site_ty,
+                &Generics::default(),
+                &fn_name,
+                Span::call_site()
+            )
+        );
+        assert!(name.to_string().starts_with("Foo__impl_"));
+        assert!(name.to_string().ends_with("__bar__"));
+    }
+
+    #[test]
+    fn stub_name_module() {
+        let ty: PathSegment = parse2(quote! { Foo }).expect("valid path segment");
+        let fn_name: Ident = parse2(quote! { bar }).expect("valid identifier");
+        let stub_name = |module| {
+            impl_block_stub_name(
+                module,
+                &ty,
+                &Generics::default(),
+                &fn_name,
+                Span::call_site(),
+            )
+            .to_string()
+        };
+
+        assert_ne!(stub_name("a"), stub_name("b"));
+        assert_eq!(stub_name("a"), stub_name("a"));
+    }
+
+    #[test]
+    fn stub_name_concrete_impls() {
+        let generics: Generics = parse2(quote! { <T> }).expect("valid generics");
+        let no_generics = Generics::default();
+        let fn_name: Ident = parse2(quote! { bar }).expect("valid identifier");
+        let stub_name = |ty, generics| {
+            let ty: PathSegment = parse2(ty).expect("valid path segment");
+
+            impl_block_stub_name("a", &ty, generics, &fn_name, Span::call_site()).to_string()
+        };
+
+        let bool_impl = stub_name(quote! { Foo<bool> }, &no_generics);
+        let unit_impl = stub_name(quote! { Foo<()> }, &no_generics);
+        let partial_impl = stub_name(quote! { Foo<T, bool> }, &generics);
+
+        assert_ne!(bool_impl, unit_impl);
+        assert_ne!(bool_impl, stub_name(quote! { Foo }, &no_generics));
+        assert!(bool_impl.starts_with("Foo__impl_") && bool_impl.ends_with("__bar__"));
+
+        // The name must be the same when generated at a call site.
+        assert_eq!(bool_impl, stub_name(quote! { Foo<bool> }, &no_generics));
+        assert_eq!(
+            partial_impl,
+            stub_name(quote! { Foo<_, bool> }, &no_generics)
+        );
+    }
+
+    #[test]
+    fn trait_impl_stub_names() {
+        let impl_block: ImplBlock = parse2(quote! {
+            impl<T> alloc::Convert<u8> for Foo<T> {}
+        })
+        .expect("valid impl block");
+        let fn_name: Ident = parse2(quote! { bar }).expect("valid identifier");
+        let stub_name = |trait_, ty, generics| {
+            let trait_: PathSegment = parse2(trait_).expect("valid path segment");
+            let ty: PathSegment = parse2(ty).expect("valid path segment");
+
+            trait_impl_stub_name(&trait_, &ty, generics, &fn_name, Span::call_site()).to_string()
+        };
+
+        let (trait_, _) = impl_block.trait_.as_ref().expect("trait implementation");
+        assert_eq!(quote! { #trait_ }.to_string(), "alloc :: Convert < u8 >");
+
+        let no_generics = Generics::default();
+        let plain = stub_name(quote! { GlobalAlloc }, quote! { System }, &no_generics);
+        let u8_impl = stub_name(
+            quote! { Convert<u8> },
+            quote! { Foo<T> },
+            &impl_block.generics,
+        );
+
+        assert_eq!(plain, "System__impl_GlobalAlloc_for__bar__");
+        assert_ne!(plain, "System__impl__bar__");
+        assert_ne!(
+            plain,
+            stub_name(quote! { Alloc }, quote! { System }, &no_generics)
+        );
+        assert!(u8_impl.starts_with("Foo__impl_Convert_for_") && u8_impl.ends_with("__bar__"));
+        assert_ne!(
+            u8_impl,
+            stub_name(
+                quote! { Convert<u16> },
+                quote! { Foo<T> },
+                &impl_block.generics
+            )
+        );
+
+        // The name must be the same when generated at a call site.
+        assert_eq!(
+            u8_impl,
+            stub_name(quote! { Convert<u8> }, quote! { Foo }, &no_generics)
+        );
+        assert_eq!(
+            u8_impl,
+            stub_name(quote! { Convert<u8> }, quote! { Foo<_> }, &no_generics)
+        );
+    }
+
+    #[test]
+    fn trait_impl_module_path() {
+        let mut impl_block: ImplBlock = parse2(quote! {
+            impl alloc::GlobalAlloc for System {}
+        })
+        .expect("valid impl block");
+
+        let module_path = impl_block.take_module_path();
+
+        assert_eq!(module_path, ["alloc"]);
+        assert!(impl_block.trait_segment().is_some());
+        assert_eq!(
+            impl_block.original_token_stream().to_string(),
+            quote! { impl GlobalAlloc for System {} }.to_string()
+        );
+    }
+
+    #[test]
+    fn original_types() {
+        let path: Path = parse2(quote! { core::mem }).expect("valid path");
+        let original = |ty| {
+            let ty: PathSegment = parse2(ty).expect("valid path segment");
+            let ty = original_type(&ty, &path);
+
+            quote! { #ty }.to_string()
+        };
+
+        assert_eq!(
+            original(quote! { MaybeUninit<T> }),
+            quote! { core::mem::MaybeUninit<T> }.to_string()
+        );
+        assert_eq!(
+            original(quote! { const_pointer<T> }),
+            quote! { *const T }.to_string()
+        );
+        assert_eq!(
+            original(quote! { mut_pointer<[u8]> }),
+            quote! { *mut [u8] }.to_string()
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Handles type aliases in `extern_crate` modules.
+//!
+//! A type alias for the type of an `impl` block in the same `extern_crate` module can be used in
+//! `forward(impl ...)` attributes, such as in `#[forward(impl pre_std::ptr::Ptr)]` for
+//!
+//! ```rust,ignore
+//! #[pre::extern_crate(std)]
+//! mod pre_std {
+//!     mod ptr {
+//!         type Ptr<T> = NonNull<T>;
+//!
+//!         impl<T> NonNull<T> {
+//!             #[pre(!ptr.is_null())]
+//!             const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T>;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! This works by re-exporting the stubs of the `impl` block under the name of the alias:
+//!
+//! ```rust,ignore
+//! #[doc(hidden)]
+//! #[allow(unused_imports)]
+//! pub(crate) use self::NonNull__impl_c23bd109__new_unchecked__ as Ptr__impl_c23bd109__new_unchecked__;
+//! ```
+//!
+//! Only one level of aliases is resolved, so an alias for another alias cannot be used in a
+//! `forward(impl ...)` attribute.
+
+use proc_macro2::TokenStream;
+use quote::{quote_spanned, TokenStreamExt};
+use syn::{spanned::Spanned, Generics, Ident, ItemType, PathSegment, Type, TypePath};
+
+use super::{
+    impl_block::{impl_block_stub_name, is_module_name, method_preconditions},
+    register_outline_function, Module,
+};
+
+/// Generates the code for a type alias inside of an `extern_crate` module.
+///
+/// `module` is the module containing the alias and `module_path` is its path in the crate.
+pub(super) fn render_type_alias(
+    alias: &ItemType,
+    tokens: &mut TokenStream,
+    visibility: &TokenStream,
+    module: &Module,
+    module_path: &[String],
+) {
+    let attrs = &alias.attrs;
+    let type_token = &alias.type_token;
+    let ident = &alias.ident;
+    let generics = &alias.generics;
+    let where_clause = &generics.where_clause;
+    let ty = &alias.ty;
+    // The alias may only be used in `forward` attributes, which don't use the alias itself.
+    tokens.append_all(quote_spanned! { alias.span()=>
+        #(#attrs)*
+        #[allow(dead_code)]
+        #visibility #type_token #ident #generics #where_clause = #ty;
+    });
+
+    let (target_module, target) = match alias_target(alias) {
+        Some(target) => target,
+        None => return,
+    };
+    let impl_blocks = target_module
+        .iter()
+        .try_fold(module, |module, ident| {
+            module
+                .modules
+                .iter()
+                .find(|submodule| submodule.ident == *ident)
+        })
+        .map_or(&[][..], |module| &module.impl_blocks[..]);
+    let alias_segment = PathSegment::from(alias.ident.clone());
+    let alias_module = &module_path[module_path.len() - 1];
+    let target_module_name = target_module
+        .last()
+        .map_or_else(|| alias_module.clone(), Ident::to_string);
+
+    // Trait implementations are addressed by the trait, not by the type.
+    for impl_block in impl_blocks.iter().filter(|block| block.trait_.is_none()) {
+        let impl_ty = match &*impl_block.self_ty {
+            Type::Path(TypePath { qself: None, path }) if path.segments.len() == 1 => {
+                &path.segments[0]
+            }
+            _ => continue,
+        };
+
+        if impl_ty.ident != target.ident {
+            continue;
+        }
+
+        for function in &impl_block.items {
+            let span = function.span();
+            let fn_name = &function.sig.ident;
+            let stub_name = impl_block_stub_name(
+                &target_module_name,
+                impl_ty,
+                &impl_block.generics,
+                fn_name,
+                span,
+            );
+
+            // Only `impl` blocks with the same concrete generic arguments as the alias apply.
+            if impl_block_stub_name(&target_module_name, target, &alias.generics, fn_name, span)
+                != stub_name
+            {
+                continue;
+            }
+
+            let alias_stub_name = impl_block_stub_name(
+                alias_module,
+                &alias_segment,
+                &Generics::default(),
+                fn_name,
+                span,
+            );
+            register_outline_function(
+                module_path,
+                None,
+                &alias_stub_name,
+                !method_preconditions(function).is_empty(),
+                function.sig.unsafety.is_some(),
+            );
+            tokens.append_all(quote_spanned! { span=>
+                #[doc(hidden)]
+                #[allow(unused_imports)]
+                #visibility use self::#(#target_module::)*#stub_name as #alias_stub_name;
+            });
+        }
+    }
+}
+
+/// Returns the module path relative to the alias and the type that the alias refers to.
+///
+/// Returns `None` if the alias does not refer to a type that an `impl` block in the `extern_crate`
+/// module could be for.
+fn alias_target(alias: &ItemType) -> Option<(Vec<Ident>, &PathSegment)> {
+    let path = match &*alias.ty {
+        Type::Path(TypePath { qself: None, path }) if path.leading_colon.is_none() => path,
+        _ => return None,
+    };
+
+    let target = path.segments.last()?;
+    let module_path = path.segments.iter().take(path.segments.len() - 1);
+
+    if !module_path.clone().all(is_module_name) {
+        return None;
+    }
+
+    Some((
+        module_path.map(|segment| segment.ident.clone()).collect(),
+        target,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn finds_alias_target() {
+        let target = |alias: ItemType| {
+            alias_target(&alias).map(|(module_path, target)| {
+                (
+                    quote! { #(#module_path)::* }.to_string(),
+                    quote! { #target }.to_string(),
+                )
+            })
+        };
+
+        assert_eq!(
+            target(parse_quote! { type Ptr<T> = NonNull<T>; }),
+            Some((String::new(), quote! { NonNull<T> }.to_string()))
+        );
+        assert_eq!(
+            target(parse_quote! { type Ptr<T> = ptr::NonNull<T>; }),
+            Some(("ptr".into(), quote! { NonNull<T> }.to_string()))
+        );
+        assert_eq!(
+            target(parse_quote! { type Ptr<T> = ::ptr::NonNull<T>; }),
+            None
+        );
+        assert_eq!(
+            target(parse_quote! { type Ptr<T> = super::NonNull<T>; }),
+            None
+        );
+        assert_eq!(target(parse_quote! { type Ptr<T> = *const T; }), None);
+    }
+}
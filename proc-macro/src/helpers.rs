@@ -1,9 +1,9 @@
 //! Allows retrieving the name of the main crate.
 
 use lazy_static::lazy_static;
-use proc_macro2::{Span, TokenStream, TokenTree};
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream, TokenTree};
 use proc_macro_error::{abort_call_site, emit_error};
-use quote::quote_spanned;
+use quote::{quote, quote_spanned};
 use std::env;
 use syn::{
     parenthesized,
@@ -11,17 +11,20 @@ use syn::{
     parse2,
     spanned::Spanned,
     token::Paren,
-    Attribute, Expr, Signature, Token,
+    visit::Visit,
+    Attribute, Expr, FnArg, Signature, Token, Type, TypeImplTrait,
 };
 
 use crate::precondition::CfgPrecondition;
 
-pub(crate) use attr::Attr;
+pub(crate) use attr::{
+    foreign_attr_path, is_in_cfg_attr, is_invoked_from_cfg_attr, is_matching_attr,
+    is_unconditional_attr, Attr,
+};
+pub(crate) use unsafe_attr::{has_codegen_attr, hide_unsafe_attrs, restore_unsafe_attrs};
 
 mod attr;
-
-/// The reason to display in examples on how to use reasons.
-pub(crate) const HINT_REASON: &str = "<specify the reason why you can assure this here>";
+mod unsafe_attr;
 
 lazy_static! {
     /// Returns the name of the main `pre` crate.
@@ -91,25 +94,36 @@ pub(crate) fn visit_matching_attrs_parsed<ParsedAttr: Parse + Spanned>(
     }
 }
 
+/// Evaluates to the attributes of an expression, if it can have attributes.
+///
+/// The given identifier is bound to the expression variant in the given attribute access.
+macro_rules! expression_attributes {
+    ($expr:expr, $e:ident => $attrs:expr) => {
+        expression_attributes!(@variants $expr, $e => $attrs;
+            Array, Assign, AssignOp, Async, Await, Binary, Block, Box, Break, Call, Cast,
+            Closure, Continue, Field, ForLoop, Group, If, Index, Let, Lit, Loop, Macro, Match,
+            MethodCall, Paren, Path, Range, Reference, Repeat, Return, Struct, Try, TryBlock, Tuple,
+            Type, Unary, Unsafe, While, Yield
+        )
+    };
+    (@variants $expr:expr, $e:ident => $attrs:expr; $($variant:ident),*) => {
+        match $expr {
+            $(
+                Expr::$variant($e) => Some($attrs),
+            )*
+            _ => None,
+        }
+    };
+}
+
 /// Returns the attributes of the given expression.
 pub(crate) fn attributes_of_expression(expr: &mut Expr) -> Option<&mut Vec<Attribute>> {
-    macro_rules! extract_attributes_from {
-        ($expr:expr => $($variant:ident),*) => {
-            match $expr {
-                $(
-                    Expr::$variant(e) => Some(&mut e.attrs),
-                )*
-                    _ => None,
-            }
-        }
-    }
+    expression_attributes!(expr, e => &mut e.attrs)
+}
 
-    extract_attributes_from!(expr =>
-        Array, Assign, AssignOp, Async, Await, Binary, Block, Box, Break, Call, Cast,
-        Closure, Continue, Field, ForLoop, Group, If, Index, Let, Lit, Loop, Macro, Match,
-        MethodCall, Paren, Path, Range, Reference, Repeat, Return, Struct, Try, TryBlock, Tuple,
-        Type, Unary, Unsafe, While, Yield
-    )
+/// Returns the attributes of the given expression without allowing to modify them.
+pub(crate) fn attributes_of_expression_ref(expr: &Expr) -> Option<&[Attribute]> {
+    expression_attributes!(expr, e => &e.attrs[..])
 }
 
 /// Incorporates the given span into the signature.
@@ -135,6 +149,37 @@ pub(crate) fn add_span_to_signature(span: Span, signature: &mut Signature) {
     }
 }
 
+/// Adds the precondition parameter of the given type to the signature.
+///
+/// If `first` is set, the parameter is placed before all other parameters except the receiver and
+/// gets a name, so that it can be recognized in error messages about missing arguments.
+pub(crate) fn add_precondition_param(
+    signature: &mut Signature,
+    cfg: Option<TokenStream>,
+    ty: TokenStream,
+    first: bool,
+    span: Span,
+) {
+    let name = if first {
+        quote_spanned! { span=> _assured_preconditions }
+    } else {
+        quote_spanned! { span=> _ }
+    };
+    let param: FnArg = parse2(quote_spanned! { span=>
+        #[cfg(all(not(doc), #cfg))]
+        #name: #ty
+    })
+    .expect("parses as a function argument");
+
+    if first {
+        let index = if signature.receiver().is_some() { 1 } else { 0 };
+
+        signature.inputs.insert(index, param);
+    } else {
+        signature.inputs.push(param);
+    }
+}
+
 /// Combines the `cfg` of all preconditions if possible.
 pub(crate) fn combine_cfg(preconditions: &[CfgPrecondition], _span: Span) -> Option<TokenStream> {
     const MISMATCHED_CFG: &str = "mismatched `cfg` predicates for preconditions";
@@ -173,6 +218,30 @@ pub(crate) fn combine_cfg(preconditions: &[CfgPrecondition], _span: Span) -> Opt
     first_cfg
 }
 
+/// Combines the `cfg` of all preconditions assured at a call site.
+///
+/// Unlike the preconditions of a definition, the `assure` attributes of a call site may have
+/// different `cfg` predicates, for example if some of them are only needed within a surrounding
+/// item that is itself behind a `cfg`. The preconditions are only passed, if all predicates hold.
+pub(crate) fn combine_call_site_cfg(preconditions: &[CfgPrecondition]) -> Option<TokenStream> {
+    let mut cfgs: Vec<&TokenStream> = Vec::new();
+
+    for cfg in preconditions.iter().filter_map(|p| p.cfg.as_ref()) {
+        if !cfgs
+            .iter()
+            .any(|other| other.to_string() == cfg.to_string())
+        {
+            cfgs.push(cfg);
+        }
+    }
+
+    match &cfgs[..] {
+        [] => None,
+        [cfg] => Some((*cfg).clone()),
+        cfgs => Some(quote! { all(#(#cfgs),*) }),
+    }
+}
+
 /// A `TokenStream` surrounded by parentheses.
 struct Parenthesized {
     /// The parentheses surrounding the `TokenStream`.
@@ -193,6 +262,91 @@ impl Parse for Parenthesized {
     }
 }
 
+/// Checks whether the type contains an `impl Trait` type anywhere.
+pub(crate) fn contains_impl_trait(ty: &Type) -> bool {
+    /// Searches for `impl Trait` types.
+    struct ImplTraitVisitor {
+        /// Whether an `impl Trait` type was found.
+        found: bool,
+    }
+
+    impl<'ast> Visit<'ast> for ImplTraitVisitor {
+        fn visit_type_impl_trait(&mut self, _: &'ast TypeImplTrait) {
+            self.found = true;
+        }
+    }
+
+    let mut visitor = ImplTraitVisitor { found: false };
+    visitor.visit_type(ty);
+
+    visitor.found
+}
+
+/// Renders the tokens as text that only depends on the tokens themselves.
+///
+/// The `Display` implementations of token streams choose the whitespace between tokens, which
+/// differs between compiler and `proc_macro2` versions. Here a space is only written between two
+/// identifiers or literals and between two punctuation characters that are not joined, since
+/// omitting it there could change the meaning. Invisible delimiters are not rendered.
+pub(crate) fn canonical_tokens(tokens: TokenStream) -> String {
+    /// Appends the canonical rendering of the tokens to `text`.
+    fn render(tokens: TokenStream, text: &mut String, previous: &mut Option<TokenTree>) {
+        for token in tokens {
+            let needs_space = match (&*previous, &token) {
+                (
+                    Some(TokenTree::Ident(_)) | Some(TokenTree::Literal(_)),
+                    TokenTree::Ident(_) | TokenTree::Literal(_),
+                ) => true,
+                // The apostrophe of a lifetime cannot be joined with the previous character.
+                (Some(TokenTree::Punct(previous)), TokenTree::Punct(punct)) => {
+                    previous.spacing() == Spacing::Alone && punct.as_char() != '\''
+                }
+                _ => false,
+            };
+            if needs_space {
+                text.push(' ');
+            }
+
+            match &token {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => {
+                            render(group.stream(), text, previous);
+                            continue;
+                        }
+                    };
+
+                    text.push_str(open);
+                    *previous = None;
+                    render(group.stream(), text, previous);
+                    text.push_str(close);
+                    *previous = Some(token.clone());
+                }
+                TokenTree::Ident(ident) => {
+                    text.push_str(&ident.to_string());
+                    *previous = Some(token.clone());
+                }
+                TokenTree::Punct(punct) => {
+                    text.push(punct.as_char());
+                    *previous = Some(token.clone());
+                }
+                TokenTree::Literal(literal) => {
+                    text.push_str(&literal.to_string());
+                    *previous = Some(token.clone());
+                }
+            }
+        }
+    }
+
+    let mut text = String::new();
+    render(tokens, &mut text, &mut None);
+
+    text
+}
+
 /// Parses the token stream to the next comma and returns the result as a new token stream.
 fn parse_to_comma(input: &mut TokenStream) -> (TokenStream, Option<Token![,]>) {
     let mut to_comma = TokenStream::new();
@@ -297,6 +451,21 @@ pub(crate) fn flatten_cfgs(attributes: &mut Vec<Attribute>) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn canonical_token_rendering() {
+        let render = |tokens: TokenStream| canonical_tokens(tokens);
+
+        assert_eq!(render(quote! { x > 0 }), "x>0");
+        assert_eq!(render(quote! { n * 2 }), "n*2");
+        assert_eq!(render(quote! { x as u8 > 0 }), "x as u8>0");
+        assert_eq!(render(quote! { a && b }), "a&&b");
+        assert_eq!(render(quote! { a & &b }), "a& &b");
+        assert_eq!(render(quote! { !ptr.is_null() }), "!ptr.is_null()");
+        assert_eq!(render(quote! { &'a [u8; 4] }), "&'a[u8;4]");
+        assert_eq!(render(quote! { 0..10 }), "0..10");
+        assert_eq!(render(quote! { s == "a b" }), "s==\"a b\"");
+    }
+
     #[test]
     fn basic_cfg_flattening() {
         let mut transformed_func: syn::ItemFn = syn::parse_quote! {
@@ -319,4 +488,32 @@ mod tests {
 
         assert_eq!(transformed_func, desired_result);
     }
+
+    #[test]
+    fn call_site_cfg_combination() {
+        let precondition = |cfg: Option<TokenStream>| CfgPrecondition {
+            precondition: syn::parse_quote!("foo"),
+            cfg,
+            span: Span::call_site(),
+        };
+        let render = |cfg: Option<TokenStream>| cfg.map(|cfg| cfg.to_string());
+
+        assert_eq!(render(combine_call_site_cfg(&[precondition(None)])), None);
+        assert_eq!(
+            render(combine_call_site_cfg(&[
+                precondition(None),
+                precondition(Some(quote! { unix })),
+                precondition(Some(quote! { unix })),
+            ])),
+            Some("unix".to_string())
+        );
+        assert_eq!(
+            render(combine_call_site_cfg(&[
+                precondition(Some(quote! { unix })),
+                precondition(None),
+                precondition(Some(quote! { target_endian = "little" })),
+            ])),
+            Some(quote! { all(unix, target_endian = "little") }.to_string())
+        );
+    }
 }
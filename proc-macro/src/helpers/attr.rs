@@ -27,6 +27,84 @@ fn is_attr(attr_to_check: &str, path: &Path) -> bool {
     }
 }
 
+/// Checks if the given attribute is a `target_attr` attribute, possibly inside of a `cfg_attr`.
+///
+/// Unlike `Attr::from_inner`, this does not emit errors for malformed attributes.
+pub(crate) fn is_matching_attr(target_attr: &str, attribute: &Attribute) -> bool {
+    if is_attr(target_attr, &attribute.path) {
+        return true;
+    } else if !attribute.path.is_ident("cfg_attr") {
+        return false;
+    }
+
+    let mut cfg_attr_content = match parse2::<Parenthesized>(attribute.tokens.clone()) {
+        Ok(Parenthesized { content, .. }) => content,
+        Err(_) => return false,
+    };
+
+    match parse_to_comma(&mut cfg_attr_content) {
+        (_, Some(_)) => parse2::<PathAndParenthesized>(cfg_attr_content)
+            .map(|inner| is_attr(target_attr, &inner.path))
+            .unwrap_or(false),
+        (_, None) => false,
+    }
+}
+
+/// Checks if the given attribute is a `target_attr` attribute that is not inside of a `cfg_attr`.
+pub(crate) fn is_unconditional_attr(target_attr: &str, attribute: &Attribute) -> bool {
+    is_attr(target_attr, &attribute.path) && !is_in_cfg_attr(attribute)
+}
+
+/// Checks if the given attribute was written inside of a `cfg_attr`.
+///
+/// The compiler evaluates the `cfg_attr`s of the annotated item itself before the item is passed
+/// to the attribute macro, so these are recognized by their source code instead.
+pub(crate) fn is_in_cfg_attr(attribute: &Attribute) -> bool {
+    attribute.path.is_ident("cfg_attr")
+        || matches!(
+            attribute.bracket_token.span.source_text(),
+            Some(text) if text.trim_start_matches('[').trim_start().starts_with("cfg_attr")
+        )
+}
+
+/// Checks if the currently expanded attribute macro was written inside of a `cfg_attr`.
+///
+/// The compiler passes only the arguments of the attribute to the macro, so this is recognized by
+/// the source code of the attribute: it starts with `#` unless it is inside of a `cfg_attr`.
+pub(crate) fn is_invoked_from_cfg_attr() -> bool {
+    matches!(Span::call_site().source_text(), Some(text) if !text.starts_with('#'))
+}
+
+/// Returns the path of the attribute, if it is a `target_attr` attribute of another crate.
+///
+/// Such attributes end in `target_attr`, but are not recognized as `pre` attributes, so they are
+/// likely to be confused with them. They may also be inside of a `cfg_attr`.
+pub(crate) fn foreign_attr_path(target_attr: &str, attribute: &Attribute) -> Option<Path> {
+    let is_foreign = |path: &Path| {
+        path.segments.len() > 1
+            && matches!(path.segments.last(), Some(segment) if segment.ident == target_attr)
+            && !is_attr(target_attr, path)
+    };
+
+    if is_foreign(&attribute.path) {
+        return Some(attribute.path.clone());
+    } else if !attribute.path.is_ident("cfg_attr") {
+        return None;
+    }
+
+    let mut cfg_attr_content = parse2::<Parenthesized>(attribute.tokens.clone())
+        .ok()?
+        .content;
+
+    match parse_to_comma(&mut cfg_attr_content) {
+        (_, Some(_)) => parse2::<PathAndParenthesized>(cfg_attr_content)
+            .ok()
+            .map(|inner| inner.path)
+            .filter(is_foreign),
+        (_, None) => None,
+    }
+}
+
 /// A `Path` followed by parentheses surrounding a `TokenStream`.
 struct PathAndParenthesized {
     /// The path at the beginning of the construct.
@@ -170,12 +248,22 @@ impl<Content: Parse + Spanned> Attr<Content> {
     /// Creates a parsed attribute from an attribute seen inside of a proc macro invocation.
     pub(crate) fn from_inner(target_attr: &str, attribute: &Attribute) -> Option<Attr<Content>> {
         if is_attr(target_attr, &attribute.path) {
+            // An attribute without arguments, such as a bare `#[pre]`, has empty content.
             let Parenthesized {
                 parentheses,
                 content,
-            } = parse2(attribute.tokens.clone())
-                .map_err(|err| emit_error!(err))
-                .ok()?;
+            } = if attribute.tokens.is_empty() {
+                Parenthesized {
+                    parentheses: Paren {
+                        span: attribute.bracket_token.span,
+                    },
+                    content: TokenStream::new(),
+                }
+            } else {
+                parse2(attribute.tokens.clone())
+                    .map_err(|err| emit_error!(err))
+                    .ok()?
+            };
 
             Some(Attr::WithParen {
                 _path: attribute.path.clone(),
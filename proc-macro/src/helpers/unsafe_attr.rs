@@ -0,0 +1,121 @@
+//! Handles unsafe attributes, such as `#[unsafe(naked)]`.
+//!
+//! The version of `syn` used cannot parse unsafe attributes, so they are replaced by a placeholder
+//! attribute before parsing and restored after the code is generated.
+
+use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
+use syn::Attribute;
+
+/// The name of the attribute that stands in for an unsafe attribute.
+const PLACEHOLDER: &str = "__pre_unsafe_attr";
+
+/// Replaces all unsafe attributes `#[unsafe(attr)]` with `#[__pre_unsafe_attr(attr)]`.
+pub(crate) fn hide_unsafe_attrs(tokens: TokenStream) -> TokenStream {
+    replace_attr_name(tokens, "unsafe", PLACEHOLDER)
+}
+
+/// Restores the unsafe attributes replaced by `hide_unsafe_attrs`.
+pub(crate) fn restore_unsafe_attrs(tokens: TokenStream) -> TokenStream {
+    replace_attr_name(tokens, PLACEHOLDER, "unsafe")
+}
+
+/// Checks whether the attributes contain the given codegen attribute.
+///
+/// Both the plain form `#[name]` and the unsafe form `#[unsafe(name)]` are recognized.
+pub(crate) fn has_codegen_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.path.is_ident(name) {
+            return true;
+        } else if !attr.path.is_ident(PLACEHOLDER) {
+            return false;
+        }
+
+        match attr.tokens.clone().into_iter().next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                matches!(group.stream().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == name)
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Replaces the names of attributes of the form `#[from(...)]` with `to`.
+fn replace_attr_name(tokens: TokenStream, from: &str, to: &str) -> TokenStream {
+    let mut previous_is_pound = false;
+
+    tokens
+        .into_iter()
+        .map(|tree| {
+            let is_pound = matches!(&tree, TokenTree::Punct(punct) if punct.as_char() == '#');
+
+            let tree = match tree {
+                TokenTree::Group(group) => {
+                    let stream = if previous_is_pound && group.delimiter() == Delimiter::Bracket {
+                        replace_name(group.stream(), from, to)
+                    } else {
+                        group.stream()
+                    };
+
+                    let mut new_group =
+                        Group::new(group.delimiter(), replace_attr_name(stream, from, to));
+                    new_group.set_span(group.span());
+
+                    TokenTree::Group(new_group)
+                }
+                tree => tree,
+            };
+
+            previous_is_pound = is_pound;
+
+            tree
+        })
+        .collect()
+}
+
+/// Replaces the name of the attribute in the given attribute contents, if it is `from(...)`.
+fn replace_name(attr: TokenStream, from: &str, to: &str) -> TokenStream {
+    let mut trees = attr.into_iter().collect::<Vec<_>>();
+
+    match &trees[..] {
+        [TokenTree::Ident(name), TokenTree::Group(group)]
+            if name == from && group.delimiter() == Delimiter::Parenthesis =>
+        {
+            trees[0] = TokenTree::Ident(Ident::new(to, name.span()));
+        }
+        _ => (),
+    }
+
+    trees.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{parse2, ItemFn};
+
+    use super::*;
+
+    #[test]
+    fn unsafe_attr_round_trip() {
+        let original = quote! {
+            #[unsafe(naked)]
+            #[inline(never)]
+            extern "C" fn foo() {
+                #[unsafe(no_mangle)]
+                extern "C" fn bar() {}
+                let _ = [unsafe { 1 }];
+            }
+        };
+
+        let hidden = hide_unsafe_attrs(original.clone());
+        let function: ItemFn = parse2(hidden.clone()).expect("valid function");
+        assert!(has_codegen_attr(&function.attrs, "naked"));
+        assert!(!has_codegen_attr(&function.attrs, "no_mangle"));
+        assert!(has_codegen_attr(&function.attrs, "inline"));
+
+        assert_eq!(
+            restore_unsafe_attrs(hidden).to_string(),
+            original.to_string()
+        );
+    }
+}
@@ -9,22 +9,37 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use proc_macro_error::{abort_call_site, proc_macro_error};
+use proc_macro_error::{abort_call_site, emit_error, proc_macro_error};
 use quote::quote;
 use syn::{parse_macro_input, visit_mut::VisitMut, File};
 
-use crate::pre_attr::PreAttrVisitor;
+use crate::{
+    helpers::{hide_unsafe_attrs, restore_unsafe_attrs},
+    pre_attr::{defer_behind_wrapping_attrs, invoked_attr, PreAttrVisitor},
+};
 
+mod adapter;
+mod assert_requires;
 mod call;
 mod call_handling;
+mod config;
 mod documentation;
 mod extern_crate;
 mod helpers;
+mod lints;
+mod metadata;
 mod pre_attr;
 mod precondition;
+mod rename;
+mod shadow;
+mod stable_warnings;
+mod violation_tests;
 
 cfg_if::cfg_if! {
-    if #[cfg(nightly)] {
+    // The `backend-struct` feature selects the struct encoding on the nightly compiler as well.
+    // It is enabled by the `marker-compat` feature, because the struct encoding does not depend on
+    // types defined in a specific version of `pre`.
+    if #[cfg(all(nightly, not(feature = "backend-struct")))] {
         mod const_generics_impl;
         pub(crate) use crate::const_generics_impl::{render_assure, render_pre};
     } else {
@@ -36,18 +51,37 @@ cfg_if::cfg_if! {
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn pre(attr: TokenStream, file: TokenStream) -> TokenStream {
+    stable_warnings::clear();
+
     let dummy_file: TokenStream2 = file.clone().into();
     proc_macro_error::set_dummy(quote! {
         #dummy_file
     });
 
-    let mut file = parse_macro_input!(file as File);
+    let file: TokenStream = hide_unsafe_attrs(file.into()).into();
+    let mut file = match syn::parse::<File>(file) {
+        Ok(file) => file,
+        Err(err) => {
+            // The original code is kept for error recovery. On stable, `proc_macro_error`
+            // replaces the returned code with the dummy once an error was emitted. On nightly,
+            // errors are emitted directly by the compiler and the returned code is used, so it
+            // must contain the original code as well.
+            emit_error!(err);
+            return dummy_file.into();
+        }
+    };
 
-    PreAttrVisitor::new(attr.into()).visit_file_mut(&mut file);
+    let attr: TokenStream2 = attr.into();
+    if defer_behind_wrapping_attrs(&mut file, || invoked_attr(&attr)) {
+        return restore_unsafe_attrs(quote! { #file }).into();
+    }
 
-    let output = quote! {
+    PreAttrVisitor::new(attr).visit_file_mut(&mut file);
+    stable_warnings::add_to_file(&mut file);
+
+    let output = restore_unsafe_attrs(quote! {
         #file
-    };
+    });
 
     // Reset the dummy here, in case errors were emitted while generating the code.
     // This will use the most up-to-date version of the generated code.
@@ -60,7 +94,10 @@ pub fn pre(attr: TokenStream, file: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 #[proc_macro_error]
-pub fn assure(_: TokenStream, _: TokenStream) -> TokenStream {
+pub fn assure(_: TokenStream, item: TokenStream) -> TokenStream {
+    // Keep the annotated item, so that the error does not cause follow-up errors.
+    proc_macro_error::set_dummy(item.into());
+
     // This macro currently only has two purposes:
     // - Exist as a place to put documentation for the actual `assure` attribute, which is
     // implemented inside the `pre` attribute.
@@ -74,7 +111,10 @@ pub fn assure(_: TokenStream, _: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 #[proc_macro_error]
-pub fn forward(_: TokenStream, _: TokenStream) -> TokenStream {
+pub fn forward(_: TokenStream, item: TokenStream) -> TokenStream {
+    // Keep the annotated item, so that the error does not cause follow-up errors.
+    proc_macro_error::set_dummy(item.into());
+
     // This macro currently only has two purposes:
     // - Exist as a place to put documentation for the actual `forward` attribute, which is
     // implemented inside the `pre` attribute.
@@ -86,13 +126,74 @@ pub fn forward(_: TokenStream, _: TokenStream) -> TokenStream {
     )
 }
 
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn assure_each(_: TokenStream, item: TokenStream) -> TokenStream {
+    // Keep the annotated item, so that the error does not cause follow-up errors.
+    proc_macro_error::set_dummy(item.into());
+
+    // This macro currently only has two purposes:
+    // - Exist as a place to put documentation for the actual `assure_each` attribute, which is
+    // implemented inside the `pre` attribute.
+    // - Emit an error with a more helpful message than "attribute not found", if the user uses
+    // `assure_each` in the wrong place.
+    abort_call_site!(
+        "this attribute by itself is currently non-functional";
+        help = "use it on an `unsafe` block in an item wrapped by a `pre` attribute"
+    )
+}
+
+#[proc_macro]
+#[proc_macro_error]
+pub fn assure_for_all_calls(input: TokenStream) -> TokenStream {
+    stable_warnings::clear();
+    let input = parse_macro_input!(input as adapter::AssureForAllCalls);
+
+    stable_warnings::add_to_expr(input.render()).into()
+}
+
+#[proc_macro]
+#[proc_macro_error]
+pub fn assert_requires(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as assert_requires::AssertRequires);
+
+    input.render().into()
+}
+
+#[proc_macro]
+#[proc_macro_error]
+pub fn rename_condition(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as rename::RenameCondition);
+
+    input.render().into()
+}
+
+#[proc_macro]
+#[proc_macro_error]
+pub fn shadow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as shadow::Shadow);
+    proc_macro_error::set_dummy(input.render_dummy());
+
+    input.render().into()
+}
+
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn extern_crate(attr: TokenStream, module: TokenStream) -> TokenStream {
-    let attr = parse_macro_input!(attr as extern_crate::ExternCrateAttr);
-    let module = parse_macro_input!(module as extern_crate::Module);
+    stable_warnings::clear();
+    let dummy = extern_crate::Module::render_dummy(module.clone().into());
+    proc_macro_error::set_dummy(dummy.clone());
+
+    let attr = syn::parse::<extern_crate::ExternCrateAttr>(attr).map_err(|err| emit_error!(err));
+    let module = syn::parse::<extern_crate::Module>(module).map_err(|err| emit_error!(err));
+    let (attr, module) = match (attr, module) {
+        (Ok(attr), Ok(module)) if attr.check_crate_is_dependency() => (attr, module),
+        // The dummy replaces the returned code on stable, but not on nightly, where errors are
+        // emitted directly by the compiler.
+        _ => return dummy.into(),
+    };
 
-    let output = module.render(attr);
+    let output = stable_warnings::add_to_items(module.render(attr));
 
     // Reset the dummy here, in case errors were emitted while generating the code.
     // This will use the most up-to-date version of the generated code.
@@ -0,0 +1,312 @@
+//! Allows configuring how the warnings emitted by `pre` are reported.
+//!
+//! Each warning belongs to a lint, which can be allowed, warned about or denied using
+//! `#[pre(allow(...))]`, `#[pre(warn(...))]` or `#[pre(deny(...))]` respectively.
+//! The lint levels apply to the item the attribute is attached to and everything contained in it.
+
+use proc_macro2::Span;
+use proc_macro_error::{Diagnostic, Level};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token::Paren,
+    Ident, Token,
+};
+
+use crate::stable_warnings;
+
+/// The custom keywords used in lint attributes.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(allow);
+    custom_keyword!(warn);
+    custom_keyword!(deny);
+}
+
+/// A lint for a warning emitted by `pre`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lint {
+    /// A reason that is `TODO`.
+    TodoReason,
+    /// A reason that is empty or `?`.
+    UnfinishedReason,
+    /// A `pre` attribute that has no effect where it is used.
+    IgnoredAttribute,
+    /// Call sites of a function that are missing `assure` attributes.
+    MissingAssureSummary,
+    /// Calls in a `#[pre(trusted)]` scope whose preconditions are assured automatically.
+    TrustedAssure,
+    /// Uses of a variable after it was passed to a function with a `consumed` precondition.
+    UseAfterConsumed,
+    /// Call-site attributes of other crates with the same name as the ones of `pre`.
+    ForeignAttribute,
+    /// `forward` attributes pointing to a function without preconditions.
+    ForwardWithoutPreconditions,
+    /// Functions whose preconditions are only documented because of their codegen attributes.
+    DocumentationOnly,
+    /// Boolean preconditions of a `#[pre(pure)]` function that may have side effects.
+    ImpureCondition,
+    /// Arguments for `lives_until_end_of_scope` preconditions obtained from temporary values.
+    TemporaryStorage,
+    /// Boolean preconditions that cannot be checked at runtime for the generics of the function.
+    UncheckedCondition,
+    /// Assured calls to `unsafe` functions that are not within an `unsafe` block.
+    AssureOutsideUnsafe,
+}
+
+impl Lint {
+    /// All lints that exist.
+    const ALL: [Lint; 13] = [
+        Lint::TodoReason,
+        Lint::UnfinishedReason,
+        Lint::IgnoredAttribute,
+        Lint::MissingAssureSummary,
+        Lint::TrustedAssure,
+        Lint::UseAfterConsumed,
+        Lint::ForeignAttribute,
+        Lint::ForwardWithoutPreconditions,
+        Lint::DocumentationOnly,
+        Lint::ImpureCondition,
+        Lint::TemporaryStorage,
+        Lint::UncheckedCondition,
+        Lint::AssureOutsideUnsafe,
+    ];
+
+    /// The name of the lint as used in lint attributes.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Lint::TodoReason => "todo_reason",
+            Lint::UnfinishedReason => "unfinished_reason",
+            Lint::IgnoredAttribute => "ignored_attribute",
+            Lint::MissingAssureSummary => "missing_assure_summary",
+            Lint::TrustedAssure => "trusted_assure",
+            Lint::UseAfterConsumed => "use_after_consumed",
+            Lint::ForeignAttribute => "foreign_attribute",
+            Lint::ForwardWithoutPreconditions => "forward_without_preconditions",
+            Lint::DocumentationOnly => "documentation_only",
+            Lint::ImpureCondition => "impure_condition",
+            Lint::TemporaryStorage => "temporary_storage",
+            Lint::UncheckedCondition => "unchecked_condition",
+            Lint::AssureOutsideUnsafe => "assure_outside_unsafe",
+        }
+    }
+
+    /// Whether warnings of the lint are made visible on the stable compiler.
+    ///
+    /// `missing_assure_summary` only summarizes errors that the compiler reports anyway.
+    fn warns_on_stable(self) -> bool {
+        self != Lint::MissingAssureSummary
+    }
+
+    /// Returns the lint with the given name, if it exists.
+    fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+
+    /// The index of the lint in `LintLevels`.
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The level at which a lint is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LintLevel {
+    /// The lint is not reported.
+    Allow,
+    /// The lint is reported as a warning.
+    Warn,
+    /// The lint is reported as an error.
+    Deny,
+}
+
+impl LintLevel {
+    /// The name of the level as used in lint attributes.
+    fn name(self) -> &'static str {
+        match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        }
+    }
+}
+
+/// An attribute setting the level of some lints.
+pub(crate) struct LintAttr {
+    /// The level the lints are set to.
+    level: LintLevel,
+    /// The span of the level keyword.
+    span: Span,
+    /// The parentheses around the lints.
+    _parentheses: Paren,
+    /// The lints that are set to the level.
+    lints: Vec<(Lint, Span)>,
+}
+
+impl LintAttr {
+    /// Checks whether the input starts with a lint attribute.
+    pub(crate) fn peek(input: ParseStream) -> bool {
+        (input.peek(custom_keywords::allow)
+            || input.peek(custom_keywords::warn)
+            || input.peek(custom_keywords::deny))
+            && input.peek2(Paren)
+    }
+
+    /// The span of the level keyword of the attribute.
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Parse for LintAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+        let (level, span) = if lookahead.peek(custom_keywords::allow) {
+            let keyword: custom_keywords::allow = input.parse()?;
+            (LintLevel::Allow, keyword.span)
+        } else if lookahead.peek(custom_keywords::warn) {
+            let keyword: custom_keywords::warn = input.parse()?;
+            (LintLevel::Warn, keyword.span)
+        } else if lookahead.peek(custom_keywords::deny) {
+            let keyword: custom_keywords::deny = input.parse()?;
+            (LintLevel::Deny, keyword.span)
+        } else {
+            return Err(lookahead.error());
+        };
+
+        let content;
+        let parentheses = parenthesized!(content in input);
+        let names = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+
+        let mut lints = Vec::with_capacity(names.len());
+        for name in names {
+            match Lint::from_name(&name.to_string()) {
+                Some(lint) => lints.push((lint, name.span())),
+                None => {
+                    let known_lints = Lint::ALL
+                        .iter()
+                        .map(|lint| format!("`{}`", lint.name()))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!("unknown lint `{}`, expected one of {}", name, known_lints),
+                    ));
+                }
+            }
+        }
+
+        Ok(LintAttr {
+            level,
+            span,
+            _parentheses: parentheses,
+            lints,
+        })
+    }
+}
+
+/// The levels of all lints at some point in the code.
+#[derive(Clone)]
+pub(crate) struct LintLevels {
+    /// The explicitly set level of each lint and the span where it was set.
+    levels: [Option<(LintLevel, Span)>; Lint::ALL.len()],
+}
+
+impl Default for LintLevels {
+    fn default() -> Self {
+        LintLevels {
+            levels: [None; Lint::ALL.len()],
+        }
+    }
+}
+
+impl LintLevels {
+    /// Applies the levels set by the attribute.
+    pub(crate) fn apply(&mut self, attr: &LintAttr) {
+        for &(lint, span) in &attr.lints {
+            self.levels[lint.index()] = Some((attr.level, span));
+        }
+    }
+
+    /// Returns the level of the given lint.
+    pub(crate) fn level(&self, lint: Lint) -> LintLevel {
+        self.levels[lint.index()].map_or(LintLevel::Warn, |(level, _)| level)
+    }
+
+    /// Creates a diagnostic for the lint, unless it is allowed.
+    ///
+    /// The diagnostic includes a note explaining where the lint level comes from.
+    pub(crate) fn diagnostic(&self, lint: Lint, span: Span, message: String) -> Option<Diagnostic> {
+        let level = self.level(lint);
+        let diagnostic_level = match level {
+            LintLevel::Allow => return None,
+            LintLevel::Warn => {
+                if lint.warns_on_stable() {
+                    stable_warnings::record(lint.name(), span, &message);
+                }
+
+                Level::Warning
+            }
+            LintLevel::Deny => Level::Error,
+        };
+        let diagnostic = Diagnostic::spanned(span, diagnostic_level, message);
+
+        Some(match self.levels[lint.index()] {
+            Some((_, level_span)) => diagnostic.span_note(
+                level_span,
+                format!(
+                    "the lint level is defined by `#[pre({}({}))]`",
+                    level.name(),
+                    lint.name()
+                ),
+            ),
+            None => diagnostic.note(format!("`#[pre(warn({}))]` on by default", lint.name())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn parse_lint_attrs() {
+        let attr: LintAttr = parse2(quote! { allow(todo_reason) }).expect("valid lint attribute");
+        assert_eq!(attr.level, LintLevel::Allow);
+        assert_eq!(
+            attr.lints.iter().map(|(lint, _)| *lint).collect::<Vec<_>>(),
+            [Lint::TodoReason]
+        );
+
+        let attr: LintAttr = parse2(quote! { deny(unfinished_reason, missing_assure_summary,) })
+            .expect("valid lint attribute");
+        assert_eq!(attr.level, LintLevel::Deny);
+        assert_eq!(
+            attr.lints.iter().map(|(lint, _)| *lint).collect::<Vec<_>>(),
+            [Lint::UnfinishedReason, Lint::MissingAssureSummary]
+        );
+
+        assert!(parse2::<LintAttr>(quote! { warn(ignored_attribute) }).is_ok());
+        assert!(parse2::<LintAttr>(quote! { allow(todo) }).is_err());
+        assert!(parse2::<LintAttr>(quote! { forbid(todo_reason) }).is_err());
+    }
+
+    #[test]
+    fn apply_lint_levels() {
+        let mut levels = LintLevels::default();
+        assert_eq!(levels.level(Lint::TodoReason), LintLevel::Warn);
+
+        levels.apply(&parse2(quote! { allow(todo_reason, ignored_attribute) }).unwrap());
+        levels.apply(&parse2(quote! { deny(todo_reason) }).unwrap());
+
+        assert_eq!(levels.level(Lint::TodoReason), LintLevel::Deny);
+        assert_eq!(levels.level(Lint::IgnoredAttribute), LintLevel::Allow);
+        assert_eq!(levels.level(Lint::UnfinishedReason), LintLevel::Warn);
+    }
+}
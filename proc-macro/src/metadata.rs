@@ -0,0 +1,1039 @@
+//! Keeps track of metadata about the preconditions in the current crate and optionally exports it.
+//!
+//! All procedural macro invocations for a crate usually happen in the same compiler process, so
+//! the information gathered here is shared between them.
+//! Note that the order of macro invocations is not guaranteed, so the information here is
+//! incomplete until all invocations are finished.
+//!
+//! If the `PRE_METADATA_DIR` environment variable is set, the metadata is written as [JSON
+//! lines](https://jsonlines.org/) into a file named after the crate in that directory.
+//! The file is overwritten for each compilation of the crate.
+//!
+//! If the `PRE_MANUAL_DIR` environment variable is set, the documentation generated for the
+//! preconditions of each function is additionally collected into a markdown file named
+//! `PRECONDITIONS.md` in a directory named after the crate in that directory.
+
+use lazy_static::lazy_static;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+use syn::{FnArg, GenericParam, Ident, Pat, PatType, Path, Signature};
+
+use crate::{
+    config::hint_reason,
+    helpers::canonical_tokens,
+    pre_attr::TRUSTED_REASON,
+    precondition::{CfgPrecondition, Precondition},
+};
+
+/// The environment variable specifying the directory to export the metadata to.
+pub(crate) const METADATA_DIR_VAR: &str = "PRE_METADATA_DIR";
+
+/// The environment variable specifying the directory to write the contracts manual to.
+pub(crate) const MANUAL_DIR_VAR: &str = "PRE_MANUAL_DIR";
+
+/// A function with preconditions that was defined in the current crate.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Definition {
+    /// The number of arguments of the function, not including the precondition argument.
+    pub(crate) arity: usize,
+    /// The names of the parameters of the function.
+    ///
+    /// Parameters that are not bound to a single name are named `_`.
+    pub(crate) parameters: Vec<String>,
+    /// The types of the parameters of the function, rendered as tokens.
+    pub(crate) parameter_types: Vec<String>,
+    /// The names of the type and const parameters of the function, in declaration order.
+    pub(crate) type_parameters: Vec<String>,
+    /// The preconditions of the function.
+    pub(crate) preconditions: Vec<String>,
+    /// The contract ID of the preconditions.
+    pub(crate) contract_id: String,
+    /// The `assure` attributes used to assure the preconditions automatically.
+    ///
+    /// This is `None` if they cannot be assured automatically.
+    pub(crate) assures: Option<Vec<String>>,
+    /// The `assure` attributes suggested for calls that are missing them, with placeholder reasons.
+    pub(crate) skeleton: Vec<String>,
+    /// Whether the precondition argument is the first argument of the function.
+    pub(crate) precondition_arg_first: bool,
+    /// Whether the function is an `unsafe fn`.
+    pub(crate) is_unsafe: bool,
+    /// The innermost modules containing the function, up to the outermost item with a `pre`
+    /// attribute.
+    ///
+    /// This is the end of the path of the module of the function. It is empty if the function
+    /// itself has the `pre` attribute, in which case its module is unknown.
+    pub(crate) module: Vec<String>,
+}
+
+/// The result of looking up the function that a path refers to.
+pub(crate) enum Resolution {
+    /// No known function matches the path.
+    Unknown,
+    /// Multiple known functions match the path.
+    Ambiguous,
+    /// Exactly one known function matches the path.
+    Resolved(Box<Definition>),
+}
+
+lazy_static! {
+    /// The functions with preconditions that were defined so far, indexed by their name.
+    ///
+    /// Functions with the same name in different modules are all kept, so that ambiguous names
+    /// can be detected.
+    static ref DEFINITIONS: Mutex<HashMap<String, Vec<Definition>>> = Mutex::new(HashMap::new());
+
+    /// The modules containing the code that is currently expanded, see `Definition::module`.
+    static ref CALLER_MODULE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+/// Whether the export file was already created for this compilation.
+static EXPORT_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the manual was already created for this compilation.
+static MANUAL_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// A piece of metadata that can be exported.
+pub(crate) enum Record<'a> {
+    /// A function with preconditions was defined.
+    Definition {
+        /// The name of the function.
+        function: &'a Ident,
+        /// The preconditions of the function.
+        preconditions: &'a [String],
+        /// The contract ID of the preconditions.
+        contract_id: &'a str,
+        /// Whether the boolean preconditions are promised to have no side effects.
+        pure: bool,
+        /// The documentation generated for the preconditions, if any was generated.
+        docs: Option<&'a str>,
+    },
+    /// A precondition was assured at a call site.
+    Assure {
+        /// The name of the called function, if it is known.
+        function: Option<&'a str>,
+        /// The call expression.
+        call: &'a str,
+        /// The precondition that was assured.
+        precondition: &'a str,
+        /// The reason given for the precondition holding.
+        reason: &'a str,
+        /// The reference to an external audit, if one was given.
+        audit: Option<&'a str>,
+        /// The reviewer of the reason, if one was given.
+        audited_by: Option<&'a str>,
+        /// The date of the review of the reason, if one was given.
+        audited_date: Option<&'a str>,
+        /// The contract ID of the called function, if its definition is known.
+        contract_id: Option<&'a str>,
+    },
+    /// A function with preconditions was called without assuring them.
+    MissingAssure {
+        /// The name of the called function.
+        function: &'a str,
+        /// The name of the function containing the call.
+        caller: &'a Ident,
+        /// The call expression.
+        call: &'a str,
+        /// Whether the call is in generated code marked with `#[pre(trusted)]`.
+        generated: bool,
+    },
+    /// The calls in `unsafe` contexts of a function were counted.
+    UnsafeCalls {
+        /// The name of the function.
+        function: &'a Ident,
+        /// The path of the module containing the function, relative to the annotated item.
+        module: &'a str,
+        /// The number of calls with `assure` attributes.
+        covered: usize,
+        /// The number of calls without `assure` attributes.
+        uncovered: usize,
+        /// The `# Safety` section of the documentation of the function, if it has one.
+        safety_docs: Option<&'a str>,
+    },
+    /// A function was called in an `unsafe` context.
+    UnsafeCall {
+        /// The name of the function containing the call.
+        caller: &'a Ident,
+        /// The name of the called function or method.
+        function: &'a str,
+        /// The call expression.
+        call: &'a str,
+        /// The preconditions assured for the call with a reason.
+        ///
+        /// Preconditions assured automatically by `#[pre(trusted)]` are not included.
+        assured: &'a [String],
+    },
+    /// A function with preconditions was declared in an `extern_crate` module.
+    Outline {
+        /// The name of the function.
+        function: &'a Ident,
+        /// The path of the function that calls are forwarded to.
+        path: &'a str,
+        /// The preconditions of the function.
+        preconditions: &'a [String],
+    },
+    /// The preconditions of a function were classified by how they flow into its calls.
+    ContractFlow {
+        /// The name of the function.
+        function: &'a Ident,
+        /// The preconditions assured with a reason for a call in the function.
+        discharged: &'a [String],
+        /// The preconditions of called functions that are not assured with a reason.
+        propagated: &'a [String],
+        /// The preconditions that no call in the function relates to.
+        unaccounted: &'a [String],
+    },
+    /// An assertion at the start of a function could be written as a precondition.
+    HarvestedAssert {
+        /// The name of the function.
+        function: &'a Ident,
+        /// The condition of the assertion.
+        precondition: &'a str,
+    },
+}
+
+impl Record<'_> {
+    /// Renders the record as a single line of JSON.
+    fn to_json(&self) -> String {
+        match self {
+            Record::Definition {
+                function,
+                preconditions,
+                contract_id,
+                pure,
+                docs,
+            } => {
+                format!(
+                    r#"{{"kind":"definition","function":{},"preconditions":{},"contract_id":{},"pure":{},"docs":{}}}"#,
+                    json_string(&function.to_string()),
+                    json_string_array(preconditions),
+                    json_string(contract_id),
+                    pure,
+                    json_optional_string(*docs)
+                )
+            }
+            Record::Assure {
+                function,
+                call,
+                precondition,
+                reason,
+                audit,
+                audited_by,
+                audited_date,
+                contract_id,
+            } => format!(
+                r#"{{"kind":"assure","function":{},"call":{},"precondition":{},"reason":{},"audit":{},"audited_by":{},"audited_date":{},"contract_id":{}}}"#,
+                json_optional_string(*function),
+                json_string(call),
+                json_string(precondition),
+                json_string(reason),
+                json_optional_string(*audit),
+                json_optional_string(*audited_by),
+                json_optional_string(*audited_date),
+                json_optional_string(*contract_id)
+            ),
+            Record::MissingAssure {
+                function,
+                caller,
+                call,
+                generated,
+            } => format!(
+                r#"{{"kind":"missing_assure","function":{},"caller":{},"call":{},"generated":{}}}"#,
+                json_string(function),
+                json_string(&caller.to_string()),
+                json_string(call),
+                generated
+            ),
+            Record::UnsafeCalls {
+                function,
+                module,
+                covered,
+                uncovered,
+                safety_docs,
+            } => format!(
+                r#"{{"kind":"unsafe_calls","function":{},"module":{},"covered":{},"uncovered":{},"safety_docs":{}}}"#,
+                json_string(&function.to_string()),
+                json_string(module),
+                covered,
+                uncovered,
+                json_optional_string(*safety_docs)
+            ),
+            Record::UnsafeCall {
+                caller,
+                function,
+                call,
+                assured,
+            } => format!(
+                r#"{{"kind":"unsafe_call","caller":{},"function":{},"call":{},"assured":{}}}"#,
+                json_string(&caller.to_string()),
+                json_string(function),
+                json_string(call),
+                json_string_array(assured)
+            ),
+            Record::Outline {
+                function,
+                path,
+                preconditions,
+            } => format!(
+                r#"{{"kind":"outline","function":{},"path":{},"preconditions":{}}}"#,
+                json_string(&function.to_string()),
+                json_string(path),
+                json_string_array(preconditions)
+            ),
+            Record::ContractFlow {
+                function,
+                discharged,
+                propagated,
+                unaccounted,
+            } => format!(
+                r#"{{"kind":"contract_flow","function":{},"discharged":{},"propagated":{},"unaccounted":{}}}"#,
+                json_string(&function.to_string()),
+                json_string_array(discharged),
+                json_string_array(propagated),
+                json_string_array(unaccounted)
+            ),
+            Record::HarvestedAssert {
+                function,
+                precondition,
+            } => format!(
+                r#"{{"kind":"harvested_assert","function":{},"precondition":{}}}"#,
+                json_string(&function.to_string()),
+                json_string(precondition)
+            ),
+        }
+    }
+}
+
+/// Renders the string as a JSON string literal.
+fn json_string(string: &str) -> String {
+    let mut result = String::with_capacity(string.len() + 2);
+
+    result.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(result, "\\u{:04x}", c as u32).expect("string writes don't fail")
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+
+    result
+}
+
+/// Renders the string as a JSON string literal or `null`, if there is none.
+fn json_optional_string(string: Option<&str>) -> String {
+    string.map_or_else(|| "null".into(), json_string)
+}
+
+/// Renders the strings as a JSON array of string literals.
+fn json_string_array(strings: &[String]) -> String {
+    let strings = strings
+        .iter()
+        .map(|string| json_string(string))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("[{}]", strings)
+}
+
+/// Computes the contract ID of a set of preconditions.
+///
+/// The contract ID is a 64-bit FNV-1a hash of the sorted and normalized preconditions, rendered as
+/// 16 hexadecimal digits. The normalization uses `canonical_tokens`, since the spacing of rendered
+/// tokens differs between compiler versions. This way the ID does not depend on the order of the
+/// preconditions or on how they are rendered, so it stays the same as long as the preconditions of
+/// a function don't change.
+pub(crate) fn contract_id<S: AsRef<str>>(preconditions: &[S]) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut preconditions = preconditions
+        .iter()
+        .map(|precondition| normalize_precondition(precondition.as_ref()))
+        .collect::<Vec<_>>();
+    preconditions.sort_unstable();
+    preconditions.dedup();
+
+    let mut hash = OFFSET_BASIS;
+    for precondition in preconditions {
+        // The separator makes sure that different splits of the same text have different IDs.
+        for byte in precondition.bytes().chain(Some(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// Renders a precondition independently of the whitespace chosen by the compiler.
+///
+/// Rendered preconditions are valid tokens, so they are lexed again and rendered canonically.
+fn normalize_precondition(precondition: &str) -> String {
+    match precondition.parse::<TokenStream>() {
+        Ok(tokens) => canonical_tokens(tokens),
+        Err(_) => precondition.to_owned(),
+    }
+}
+
+/// Returns the name of the crate that is currently compiled.
+fn crate_name() -> String {
+    env::var("CARGO_CRATE_NAME")
+        .or_else(|_| env::var("CARGO_PKG_NAME").map(|name| name.replace('-', "_")))
+        .unwrap_or_else(|_| "unknown".into())
+}
+
+/// Returns the path of the export file, if exporting is enabled.
+pub(crate) fn export_path() -> Option<PathBuf> {
+    let dir = env::var_os(METADATA_DIR_VAR)?;
+
+    Some(PathBuf::from(dir).join(format!("{}.jsonl", crate_name())))
+}
+
+/// Returns the path of the contracts manual, if writing it is enabled.
+pub(crate) fn manual_path() -> Option<PathBuf> {
+    let dir = env::var_os(MANUAL_DIR_VAR)?;
+
+    Some(
+        PathBuf::from(dir)
+            .join(crate_name())
+            .join("PRECONDITIONS.md"),
+    )
+}
+
+/// Renders the section of the contracts manual for a function.
+///
+/// The headings of the documentation are nested below the heading of the function.
+fn manual_section(function: &Ident, docs: &str) -> String {
+    let mut section = format!("## `{}`\n\n", function);
+    let mut in_code_block = false;
+
+    for line in docs.trim_end().lines() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && line.starts_with('#') {
+            section.push_str("##");
+        }
+
+        section.push_str(line);
+        section.push('\n');
+    }
+    section.push('\n');
+
+    section
+}
+
+/// Adds the documentation of the preconditions of the function to the contracts manual, if writing
+/// it is enabled.
+///
+/// The sections are written in the order the macros are invoked in, which usually is the order of
+/// the functions in the source code.
+pub(crate) fn add_to_manual(function: &Ident, docs: &str) {
+    let path = match manual_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let first_section = !MANUAL_STARTED.swap(true, Ordering::SeqCst);
+
+    // Like the export, writing the manual should not fail the compilation.
+    let _ = (|| -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!first_section)
+            .truncate(first_section)
+            .open(&path)?;
+
+        if first_section {
+            write!(file, "# Preconditions of `{}`\n\n", crate_name())?;
+        }
+
+        write!(file, "{}", manual_section(function, docs))
+    })();
+}
+
+/// Exports the record, if exporting is enabled.
+pub(crate) fn export(record: Record) {
+    let path = match export_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let first_export = !EXPORT_STARTED.swap(true, Ordering::SeqCst);
+
+    // The export is best-effort, failing to write it should not fail the compilation.
+    let _ = (|| -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!first_export)
+            .truncate(first_export)
+            .open(&path)?;
+
+        writeln!(file, "{}", record.to_json())
+    })();
+}
+
+/// Registers a function with preconditions that was defined in the current crate.
+///
+/// If `keeps_signature` is set, the function keeps its signature, for example because it is
+/// exported to foreign code.
+/// `pure` is set, if the boolean preconditions are promised to have no side effects.
+/// `docs` is the documentation generated for the preconditions, if any was generated.
+/// `module` contains the modules around the function that are known, see `Definition::module`.
+pub(crate) fn register_definition(
+    signature: &Signature,
+    module: &[Ident],
+    preconditions: &[CfgPrecondition],
+    keeps_signature: bool,
+    precondition_arg_first: bool,
+    pure: bool,
+    docs: Option<&str>,
+) {
+    let assures = preconditions
+        .iter()
+        .map(assure_attribute)
+        .collect::<Option<Vec<_>>>();
+    let skeleton = preconditions.iter().map(assure_skeleton).collect();
+    let preconditions = preconditions
+        .iter()
+        .map(|precondition| precondition.precondition().to_string())
+        .collect::<Vec<_>>();
+    let contract_id = contract_id(&preconditions);
+
+    export(Record::Definition {
+        function: &signature.ident,
+        preconditions: &preconditions,
+        contract_id: &contract_id,
+        pure,
+        docs,
+    });
+
+    // Methods are not registered, because calls to them cannot be reliably recognized.
+    if signature.receiver().is_some() {
+        return;
+    }
+
+    // Calls to functions that keep their signature don't assure the preconditions.
+    if keeps_signature {
+        return;
+    }
+
+    let definition = Definition {
+        arity: signature.inputs.len(),
+        parameters: signature
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Receiver(_) => "self".into(),
+                FnArg::Typed(PatType { pat, .. }) => match &**pat {
+                    Pat::Ident(pat) => pat.ident.to_string(),
+                    _ => "_".into(),
+                },
+            })
+            .collect(),
+        parameter_types: signature
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Receiver(receiver) => quote! { #receiver }.to_string(),
+                FnArg::Typed(PatType { ty, .. }) => quote! { #ty }.to_string(),
+            })
+            .collect(),
+        type_parameters: signature
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Type(param) => Some(param.ident.to_string()),
+                GenericParam::Const(param) => Some(param.ident.to_string()),
+                GenericParam::Lifetime(_) => None,
+            })
+            .collect(),
+        preconditions,
+        contract_id,
+        assures,
+        skeleton,
+        precondition_arg_first,
+        is_unsafe: signature.unsafety.is_some(),
+        module: module.iter().map(Ident::to_string).collect(),
+    };
+
+    let mut definitions = DEFINITIONS
+        .lock()
+        .expect("no panics while holding the lock");
+    let same_name = definitions.entry(signature.ident.to_string()).or_default();
+
+    // The same function may be expanded more than once, for example by an IDE.
+    if !same_name.contains(&definition) {
+        same_name.push(definition);
+    }
+}
+
+/// Renders the `assure` attribute that assures the precondition in generated code.
+///
+/// Returns `None` if the precondition cannot be assured without further information.
+fn assure_attribute(precondition: &CfgPrecondition) -> Option<String> {
+    let assured = match precondition.precondition() {
+        Precondition::Token { .. } => return None,
+        Precondition::Exported { ident, .. } => format!("use {}", ident),
+        precondition => precondition.to_string(),
+    };
+
+    Some(render_assure_attribute(
+        precondition,
+        format!("{}, reason = {:?}", assured, TRUSTED_REASON),
+    ))
+}
+
+/// Renders the `assure` attribute suggested for calls that don't assure the precondition.
+///
+/// The reason is a placeholder that the user has to replace.
+fn assure_skeleton(precondition: &CfgPrecondition) -> String {
+    let content = match precondition.precondition() {
+        Precondition::Token { ty, .. } => format!("token({} = /* token value */)", quote! { #ty }),
+        Precondition::Exported { ident, .. } => {
+            format!("use {}, reason = {:?}", ident, hint_reason())
+        }
+        precondition => format!("{}, reason = {:?}", precondition, hint_reason()),
+    };
+
+    render_assure_attribute(precondition, content)
+}
+
+/// Renders an `assure` attribute with the given content, behind the `cfg` of the precondition.
+fn render_assure_attribute(precondition: &CfgPrecondition, content: String) -> String {
+    match &precondition.cfg {
+        Some(cfg) => format!("#[cfg_attr({}, assure({}))]", cfg, content),
+        None => format!("#[assure({})]", content),
+    }
+}
+
+/// Returns the definition of the function with the given name, if it is known.
+///
+/// If multiple functions with this name are known, it is unknown which one is meant, so `None` is
+/// returned.
+pub(crate) fn definition(name: &str) -> Option<Definition> {
+    match DEFINITIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .get(name)
+        .map(Vec::as_slice)
+    {
+        Some([definition]) => Some(definition.clone()),
+        _ => None,
+    }
+}
+
+/// Looks up the function that the path refers to among the known functions.
+///
+/// A function matches the path if it has the same name and its known modules agree with the
+/// modules named in the path.
+pub(crate) fn resolve(path: &Path) -> Resolution {
+    let (name, modules) = match split_path(path) {
+        Some(split) => split,
+        None => return Resolution::Unknown,
+    };
+
+    let definitions = DEFINITIONS
+        .lock()
+        .expect("no panics while holding the lock");
+    let mut matching = definitions
+        .get(&name)
+        .into_iter()
+        .flatten()
+        .filter(|definition| modules_agree(&definition.module, &modules));
+
+    match (matching.next(), matching.next()) {
+        (None, _) => Resolution::Unknown,
+        (Some(definition), None) => Resolution::Resolved(Box::new(definition.clone())),
+        (Some(_), Some(_)) => Resolution::Ambiguous,
+    }
+}
+
+/// Sets the modules containing the code that is expanded next.
+///
+/// This is relative to the outermost item with a `pre` attribute, like `Definition::module`.
+pub(crate) fn set_caller_module(module: &[Ident]) {
+    *CALLER_MODULE
+        .lock()
+        .expect("no panics while holding the lock") = module.iter().map(Ident::to_string).collect();
+}
+
+/// Returns the definition of the function called through the path, if it is known to be the
+/// callee.
+///
+/// This is only the case if exactly one known function matches the path and the path either
+/// consists of just the name of a function in the module of the call or names a module that is
+/// known to contain the function. Otherwise the path may refer to a different function with the
+/// same name, such as one brought into scope by a `use` item or one in another crate. Functions
+/// with the `pre` attribute themselves are in an unknown module, which is assumed to be the same
+/// as the one of calls in such functions.
+pub(crate) fn callee_definition(path: &Path) -> Option<Definition> {
+    if path.leading_colon.is_none() && path.segments.len() == 1 {
+        let name = path.segments[0].ident.to_string();
+        let caller_module = CALLER_MODULE
+            .lock()
+            .expect("no panics while holding the lock")
+            .clone();
+        let definitions = DEFINITIONS
+            .lock()
+            .expect("no panics while holding the lock");
+        let mut matching = definitions
+            .get(&name)
+            .into_iter()
+            .flatten()
+            .filter(|definition| definition.module == caller_module);
+
+        return match (matching.next(), matching.next()) {
+            (Some(definition), None) => Some(definition.clone()),
+            _ => None,
+        };
+    }
+
+    match resolve(path) {
+        Resolution::Resolved(definition) if !definition.module.is_empty() => Some(*definition),
+        _ => None,
+    }
+}
+
+/// Splits the path into the name of the function and the modules named before it.
+///
+/// `crate`, `self` and `super` segments are skipped, since they only say where the modules are
+/// relative to. Returns `None` for paths into other crates.
+fn split_path(path: &Path) -> Option<(String, Vec<String>)> {
+    if path.leading_colon.is_some() {
+        return None;
+    }
+
+    let mut segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .filter(|segment| !matches!(segment.as_str(), "crate" | "self" | "super"))
+        .collect::<Vec<_>>();
+    let name = segments.pop()?;
+
+    Some((name, segments))
+}
+
+/// Checks whether the known modules of a function agree with the modules named in a path.
+///
+/// Both only contain the innermost modules, so they agree if their common end is the same.
+fn modules_agree(known: &[String], named: &[String]) -> bool {
+    known
+        .iter()
+        .rev()
+        .zip(named.iter().rev())
+        .all(|(known, named)| known == named)
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::{Span, TokenStream};
+
+    use super::*;
+
+    #[test]
+    fn manual_sections() {
+        let function = Ident::new("read", Span::call_site());
+
+        assert_eq!(
+            manual_section(
+                &function,
+                "# Preconditions\n\n- `x > 0`\n\n```rust,ignore\n#[assure(x > 0)]\n```\n"
+            ),
+            "## `read`\n\n### Preconditions\n\n- `x > 0`\n\n```rust,ignore\n#[assure(x > 0)]\n```\n\n"
+        );
+    }
+
+    #[test]
+    fn json_escaping() {
+        assert_eq!(json_string("foo"), r#""foo""#);
+        assert_eq!(json_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(json_string("a\nb\u{1}"), r#""a\nb\u0001""#);
+    }
+
+    #[test]
+    fn record_json() {
+        let function = Ident::new("foo", Span::call_site());
+        let caller = Ident::new("main", Span::call_site());
+
+        assert_eq!(
+            Record::Definition {
+                function: &function,
+                preconditions: &["x > 0".into(), r#""is \"valid\"""#.into()],
+                contract_id: "0123456789abcdef",
+                pure: false,
+                docs: None,
+            }
+            .to_json(),
+            r#"{"kind":"definition","function":"foo","preconditions":["x > 0","\"is \\\"valid\\\"\""],"contract_id":"0123456789abcdef","pure":false,"docs":null}"#
+        );
+        assert_eq!(
+            Record::Definition {
+                function: &function,
+                preconditions: &["x > 0".into()],
+                contract_id: "0123456789abcdef",
+                pure: true,
+                docs: Some("# This function has preconditions\n"),
+            }
+            .to_json(),
+            r##"{"kind":"definition","function":"foo","preconditions":["x > 0"],"contract_id":"0123456789abcdef","pure":true,"docs":"# This function has preconditions\n"}"##
+        );
+        assert_eq!(
+            Record::Assure {
+                function: Some("foo"),
+                call: "foo (42)",
+                precondition: "x > 0",
+                reason: "42 > 0",
+                audit: Some("JIRA-123"),
+                audited_by: Some("alice"),
+                audited_date: Some("2024-06-01"),
+                contract_id: Some("0123456789abcdef"),
+            }
+            .to_json(),
+            r#"{"kind":"assure","function":"foo","call":"foo (42)","precondition":"x > 0","reason":"42 > 0","audit":"JIRA-123","audited_by":"alice","audited_date":"2024-06-01","contract_id":"0123456789abcdef"}"#
+        );
+        assert_eq!(
+            Record::Assure {
+                function: None,
+                call: "(get_fn ()) (42)",
+                precondition: "x > 0",
+                reason: "42 > 0",
+                audit: None,
+                audited_by: None,
+                audited_date: None,
+                contract_id: None,
+            }
+            .to_json(),
+            r#"{"kind":"assure","function":null,"call":"(get_fn ()) (42)","precondition":"x > 0","reason":"42 > 0","audit":null,"audited_by":null,"audited_date":null,"contract_id":null}"#
+        );
+        assert_eq!(
+            Record::MissingAssure {
+                function: "foo",
+                caller: &caller,
+                call: "foo (42)",
+                generated: false,
+            }
+            .to_json(),
+            r#"{"kind":"missing_assure","function":"foo","caller":"main","call":"foo (42)","generated":false}"#
+        );
+        assert_eq!(
+            Record::MissingAssure {
+                function: "foo",
+                caller: &caller,
+                call: "foo (42)",
+                generated: true,
+            }
+            .to_json(),
+            r#"{"kind":"missing_assure","function":"foo","caller":"main","call":"foo (42)","generated":true}"#
+        );
+        assert_eq!(
+            Record::UnsafeCalls {
+                function: &function,
+                module: "ffi::raw",
+                covered: 3,
+                uncovered: 1,
+                safety_docs: None,
+            }
+            .to_json(),
+            r#"{"kind":"unsafe_calls","function":"foo","module":"ffi::raw","covered":3,"uncovered":1,"safety_docs":null}"#
+        );
+        assert_eq!(
+            Record::UnsafeCalls {
+                function: &function,
+                module: "",
+                covered: 1,
+                uncovered: 0,
+                safety_docs: Some("`p` must be valid"),
+            }
+            .to_json(),
+            r#"{"kind":"unsafe_calls","function":"foo","module":"","covered":1,"uncovered":0,"safety_docs":"`p` must be valid"}"#
+        );
+        assert_eq!(
+            Record::UnsafeCall {
+                caller: &caller,
+                function: "strlen",
+                call: "libc :: strlen (s)",
+                assured: &["valid_cstr(s)".into()],
+            }
+            .to_json(),
+            r#"{"kind":"unsafe_call","caller":"main","function":"strlen","call":"libc :: strlen (s)","assured":["valid_cstr(s)"]}"#
+        );
+        assert_eq!(
+            Record::Outline {
+                function: &function,
+                path: "libc::foo",
+                preconditions: &["x > 0".into(), r#""is valid""#.into()],
+            }
+            .to_json(),
+            r#"{"kind":"outline","function":"foo","path":"libc::foo","preconditions":["x > 0","\"is valid\""]}"#
+        );
+        assert_eq!(
+            Record::ContractFlow {
+                function: &function,
+                discharged: &["valid_ptr(p, r)".into()],
+                propagated: &[],
+                unaccounted: &["x > 0".into()],
+            }
+            .to_json(),
+            r#"{"kind":"contract_flow","function":"foo","discharged":["valid_ptr(p, r)"],"propagated":[],"unaccounted":["x > 0"]}"#
+        );
+        assert_eq!(
+            Record::HarvestedAssert {
+                function: &function,
+                precondition: "x > 0",
+            }
+            .to_json(),
+            r#"{"kind":"harvested_assert","function":"foo","precondition":"x > 0"}"#
+        );
+    }
+
+    #[test]
+    fn stable_contract_ids() {
+        let id = contract_id(&["x > 0", "valid_ptr(p, r)"]);
+
+        assert_eq!(id.len(), 16);
+        assert_eq!(id, contract_id(&["valid_ptr(p, r)", "x > 0", "x > 0"]));
+        assert_eq!(contract_id::<&str>(&[]), "cbf29ce484222325");
+        assert_ne!(id, contract_id(&["x > 1", "valid_ptr(p, r)"]));
+        assert_ne!(contract_id(&["ab", "c"]), contract_id(&["a", "bc"]));
+        assert_eq!(id, contract_id(&["x>0", "valid_ptr (p , r)"]));
+        assert_ne!(contract_id(&[r#""is a b""#]), contract_id(&[r#""is ab""#]));
+        assert_ne!(contract_id(&["x as u8 > 0"]), contract_id(&["xasu8 > 0"]));
+        assert_ne!(contract_id(&["a & & b"]), contract_id(&["a && b"]));
+        assert_eq!(
+            normalize_precondition(r#"x . len () > "\" a" . len ()"#),
+            r#"x.len()>"\" a".len()"#
+        );
+    }
+
+    #[test]
+    fn assure_skeletons() {
+        let precondition = |precondition: Precondition, cfg: Option<TokenStream>| CfgPrecondition {
+            precondition,
+            cfg,
+            span: Span::call_site(),
+        };
+
+        assert_eq!(
+            assure_skeleton(&precondition(syn::parse_quote!(x > 0), None)),
+            format!("#[assure(x > 0, reason = {:?})]", hint_reason())
+        );
+        assert_eq!(
+            assure_skeleton(&precondition(
+                syn::parse_quote!("is foo"),
+                Some(quote! { unix })
+            )),
+            format!(
+                "#[cfg_attr(unix, assure(\"is foo\", reason = {:?}))]",
+                hint_reason()
+            )
+        );
+        assert_eq!(
+            assure_skeleton(&precondition(syn::parse_quote!(token(Token)), None)),
+            "#[assure(token(Token = /* token value */))]"
+        );
+    }
+
+    #[test]
+    fn path_resolution() {
+        let register = |module: &[&str], function: Signature| {
+            let module = module
+                .iter()
+                .map(|module| Ident::new(module, Span::call_site()))
+                .collect::<Vec<_>>();
+            let precondition = CfgPrecondition {
+                precondition: syn::parse_quote!(x > 0),
+                cfg: None,
+                span: Span::call_site(),
+            };
+
+            register_definition(
+                &function,
+                &module,
+                &[precondition],
+                false,
+                false,
+                false,
+                None,
+            );
+        };
+        let resolve = |path: Path| match resolve(&path) {
+            Resolution::Unknown => "unknown".to_string(),
+            Resolution::Ambiguous => "ambiguous".to_string(),
+            Resolution::Resolved(definition) => definition.module.join("::"),
+        };
+        let callee = |path: Path| callee_definition(&path).map(|definition| definition.module);
+
+        register(&[], syn::parse_quote!(fn resolved_unknown_module(x: u8)));
+        assert_eq!(resolve(syn::parse_quote!(resolved_unknown_module)), "");
+        assert_eq!(
+            resolve(syn::parse_quote!(other::resolved_unknown_module)),
+            ""
+        );
+        assert!(callee(syn::parse_quote!(resolved_unknown_module)).is_some());
+        assert!(callee(syn::parse_quote!(other::resolved_unknown_module)).is_none());
+        assert!(callee(syn::parse_quote!(::other::resolved_unknown_module)).is_none());
+
+        register(
+            &["outer", "old_api"],
+            syn::parse_quote!(fn resolved_foo(x: u8)),
+        );
+        register(
+            &["new_api"],
+            syn::parse_quote!(fn resolved_foo(x: u8, y: u8)),
+        );
+        assert_eq!(resolve(syn::parse_quote!(resolved_foo)), "ambiguous");
+        assert_eq!(
+            resolve(syn::parse_quote!(super::old_api::resolved_foo)),
+            "outer::old_api"
+        );
+        assert_eq!(
+            resolve(syn::parse_quote!(crate::new_api::resolved_foo)),
+            "new_api"
+        );
+        assert_eq!(resolve(syn::parse_quote!(other::resolved_foo)), "unknown");
+        assert!(callee(syn::parse_quote!(old_api::resolved_foo)).is_some());
+        assert!(callee(syn::parse_quote!(resolved_foo)).is_none());
+        assert!(definition("resolved_foo").is_none());
+
+        set_caller_module(&[Ident::new("new_api", Span::call_site())]);
+        assert_eq!(
+            callee(syn::parse_quote!(resolved_foo)),
+            Some(vec!["new_api".to_string()])
+        );
+        assert!(callee(syn::parse_quote!(resolved_unknown_module)).is_none());
+        set_caller_module(&[]);
+    }
+}
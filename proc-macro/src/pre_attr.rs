@@ -1,32 +1,86 @@
 //! Defines the `pre` attribute and how it is handled.
 
 use proc_macro2::{Span, TokenStream};
-use proc_macro_error::{emit_error, emit_warning};
+use proc_macro_error::emit_error;
 use quote::{quote, quote_spanned};
+use std::mem;
 use syn::{
     parse::{Parse, ParseStream},
-    parse2,
+    parse2, parse_quote_spanned,
     spanned::Spanned,
     visit_mut::{
-        visit_expr_mut, visit_file_mut, visit_item_fn_mut, visit_item_mut, visit_local_mut,
-        VisitMut,
+        visit_expr_mut, visit_file_mut, visit_impl_item_method_mut, visit_item_fn_mut,
+        visit_item_mut, visit_local_mut, visit_trait_item_method_mut, VisitMut,
     },
-    Expr, File, Item, ItemFn, Local,
+    Expr, File, Ident, ImplItemMethod, Item, ItemFn, ItemMod, LitStr, Local, Macro,
+    TraitItemMethod,
 };
 
-use self::expr_handling::render_expr;
+use self::{
+    assure_each::expand_assure_each,
+    call_budget::{check_call_budget, CallBudget},
+    cfg_activation::{check_activation, Activation},
+    cfg_parameters::gate_cfg_parameters,
+    constant_assures::check_constant_assures,
+    consumed::check_uses_after_consumed,
+    contract_flow::export_contract_flow,
+    expr_handling::{hoist_operand_attributes, render_expr},
+    generic_checks::GenericCheck,
+    harvest_asserts::harvest_asserts,
+    layout_check::render_layout_check,
+    macro_handling::{scan_macro_tokens, visit_recognized_macro},
+    missing_assure::check_missing_assures,
+    pure::check_pure_conditions,
+    same_as::expand_same_as,
+    setup_marker::take_setup_marker,
+    temporary_storage::check_temporary_storage,
+    trusted::assure_trusted_calls,
+    unsafe_calls::{export_unsafe_call_records, outermost_call, safety_docs},
+    unsafe_context::check_calls_outside_unsafe,
+    unsafe_scope::check_unsafe_block,
+};
 use crate::{
-    call_handling::remove_call_attributes,
-    documentation::generate_docs,
+    adapter::render_adapter,
+    call_handling::{check_foreign_call_attributes, remove_call_attributes},
+    config,
+    documentation::{doc_attribute, generate_docs_text, KeptSignature},
+    extern_crate::{peek_when, unsupported_when_error},
     helpers::{
-        attributes_of_expression, flatten_cfgs, visit_matching_attrs_parsed_mut, Attr,
-        AttributeAction,
+        attributes_of_expression, flatten_cfgs, has_codegen_attr, visit_matching_attrs_parsed_mut,
+        Attr, AttributeAction, CRATE_NAME,
     },
+    lints::{Lint, LintAttr, LintLevels},
+    metadata::{self, contract_id, register_definition, Record},
     precondition::{CfgPrecondition, Precondition},
     render_pre,
+    violation_tests::{render_violation_tests, ViolationTests},
 };
 
+mod assure_each;
+mod call_budget;
+mod cfg_activation;
+mod cfg_parameters;
+mod constant_assures;
+mod consumed;
+mod contract_flow;
 mod expr_handling;
+mod generic_checks;
+mod harvest_asserts;
+mod layout_check;
+mod macro_handling;
+mod missing_assure;
+mod pure;
+mod same_as;
+mod setup_marker;
+mod temporary_storage;
+mod trusted;
+mod unsafe_calls;
+mod unsafe_context;
+mod unsafe_scope;
+mod wrapping_attrs;
+
+pub(crate) use trusted::TRUSTED_REASON;
+pub(crate) use wrapping_attrs::{defer_behind_wrapping_attrs, invoked_attr};
 
 /// The custom keywords used for `pre` attributes.
 mod custom_keywords {
@@ -34,6 +88,17 @@ mod custom_keywords {
 
     custom_keyword!(no_doc);
     custom_keyword!(no_debug_assert);
+    custom_keyword!(doc);
+    custom_keyword!(debug_assert);
+    custom_keyword!(strict_unsafe);
+    custom_keyword!(scan_macros);
+    custom_keyword!(adapter);
+    custom_keyword!(must_use);
+    custom_keyword!(trusted);
+    custom_keyword!(precondition_arg_first);
+    custom_keyword!(pure);
+    custom_keyword!(harvest_asserts);
+    custom_keyword!(assert_after_setup);
 }
 
 /// A `pre` attribute.
@@ -42,8 +107,36 @@ pub(crate) enum PreAttr {
     Empty,
     /// A request not to generate `pre`-related documentation for the contained item.
     NoDoc(custom_keywords::no_doc),
-    /// A request not to generate `debug_assert` statements for boolean expressions.
+    /// A request not to generate `debug_assert` statements for preconditions checkable at runtime.
     NoDebugAssert(custom_keywords::no_debug_assert),
+    /// A request to generate `pre`-related documentation, even if disabled by default.
+    Doc(custom_keywords::doc),
+    /// A request to generate `debug_assert` statements, even if disabled by default.
+    DebugAssert(custom_keywords::debug_assert),
+    /// A request to warn about operations sharing an `unsafe` block with an assured call.
+    StrictUnsafe(custom_keywords::strict_unsafe),
+    /// A request to scan the contents of all macro invocations for call attributes.
+    ScanMacros(custom_keywords::scan_macros),
+    /// A request to generate an adapter for using the function in higher-order contexts.
+    Adapter(custom_keywords::adapter),
+    /// A request to generate tests calling the function with arguments violating its
+    /// preconditions.
+    GenViolationTests(ViolationTests),
+    /// A request to warn about ignored results of the function.
+    MustUse(custom_keywords::must_use),
+    /// A request to assure the preconditions of calls in generated code automatically.
+    Trusted(custom_keywords::trusted),
+    /// A request to make the precondition argument the first argument of the function.
+    PreconditionArgFirst(custom_keywords::precondition_arg_first),
+    /// A request to report assertions at the start of functions that could be preconditions.
+    HarvestAsserts(custom_keywords::harvest_asserts),
+    /// A request to limit the number of unchecked calls in the function.
+    MaxUncheckedCalls(CallBudget),
+    /// A promise that the boolean preconditions have no side effects and can be evaluated more
+    /// than once.
+    Pure(custom_keywords::pure),
+    /// A request to change the level of some `pre` lints.
+    Lint(LintAttr),
     /// A precondition that needs to hold for the contained item.
     Precondition(Precondition),
 }
@@ -56,6 +149,34 @@ impl Parse for PreAttr {
             Ok(PreAttr::NoDoc(input.parse()?))
         } else if input.peek(custom_keywords::no_debug_assert) {
             Ok(PreAttr::NoDebugAssert(input.parse()?))
+        } else if input.peek(custom_keywords::doc) {
+            Ok(PreAttr::Doc(input.parse()?))
+        } else if input.peek(custom_keywords::debug_assert) {
+            Ok(PreAttr::DebugAssert(input.parse()?))
+        } else if input.peek(custom_keywords::strict_unsafe) {
+            Ok(PreAttr::StrictUnsafe(input.parse()?))
+        } else if input.peek(custom_keywords::scan_macros) {
+            Ok(PreAttr::ScanMacros(input.parse()?))
+        } else if input.peek(custom_keywords::adapter) {
+            Ok(PreAttr::Adapter(input.parse()?))
+        } else if ViolationTests::peek(input) {
+            Ok(PreAttr::GenViolationTests(input.parse()?))
+        } else if input.peek(custom_keywords::must_use) {
+            Ok(PreAttr::MustUse(input.parse()?))
+        } else if input.peek(custom_keywords::trusted) {
+            Ok(PreAttr::Trusted(input.parse()?))
+        } else if input.peek(custom_keywords::precondition_arg_first) {
+            Ok(PreAttr::PreconditionArgFirst(input.parse()?))
+        } else if input.peek(custom_keywords::harvest_asserts) {
+            Ok(PreAttr::HarvestAsserts(input.parse()?))
+        } else if input.peek(custom_keywords::pure) {
+            Ok(PreAttr::Pure(input.parse()?))
+        } else if CallBudget::peek(input) {
+            Ok(PreAttr::MaxUncheckedCalls(input.parse()?))
+        } else if LintAttr::peek(input) {
+            Ok(PreAttr::Lint(input.parse()?))
+        } else if peek_when(input) {
+            Err(unsupported_when_error(input))
         } else {
             Ok(PreAttr::Precondition(input.parse()?))
         }
@@ -68,6 +189,19 @@ impl Spanned for PreAttr {
             PreAttr::Empty => Span::call_site(),
             PreAttr::NoDoc(no_doc) => no_doc.span,
             PreAttr::NoDebugAssert(no_debug_assert) => no_debug_assert.span,
+            PreAttr::Doc(doc) => doc.span,
+            PreAttr::DebugAssert(debug_assert) => debug_assert.span,
+            PreAttr::StrictUnsafe(strict_unsafe) => strict_unsafe.span,
+            PreAttr::ScanMacros(scan_macros) => scan_macros.span,
+            PreAttr::Adapter(adapter) => adapter.span,
+            PreAttr::GenViolationTests(tests) => tests.span(),
+            PreAttr::MustUse(must_use) => must_use.span,
+            PreAttr::Trusted(trusted) => trusted.span,
+            PreAttr::PreconditionArgFirst(arg_first) => arg_first.span,
+            PreAttr::HarvestAsserts(harvest_asserts) => harvest_asserts.span,
+            PreAttr::MaxUncheckedCalls(budget) => budget.span(),
+            PreAttr::Pure(pure) => pure.span,
+            PreAttr::Lint(lint) => lint.span(),
             PreAttr::Precondition(precondition) => precondition.span(),
         }
     }
@@ -77,11 +211,42 @@ impl Spanned for PreAttr {
 pub(crate) struct PreAttrVisitor {
     /// The original attribute that started the visitor.
     original_attr: Option<PreAttr>,
+    /// How the original attribute activates `pre`.
+    activation: Activation,
+    /// Whether operations sharing an `unsafe` block with an assured call should be warned about.
+    strict_unsafe: bool,
+    /// Whether assertions that could be preconditions should be reported.
+    harvest_asserts: bool,
+    /// Whether the contents of all macro invocations should be scanned for call attributes.
+    scan_macros: bool,
+    /// Whether the preconditions of calls in the current scope are assured automatically.
+    trusted: bool,
+    /// The levels of the `pre` lints in the current scope.
+    lints: LintLevels,
+    /// The modules containing the current scope, relative to the annotated item.
+    module_path: Vec<Ident>,
+    /// Whether the current scope is an `unsafe` context.
+    in_unsafe: bool,
+    /// The calls in `unsafe` contexts of the current function.
+    unsafe_calls: UnsafeCallCounts,
+}
+
+/// The number of calls in `unsafe` contexts of a function.
+///
+/// Only calls with `assure` attributes and calls to functions that are known to have
+/// preconditions are counted.
+#[derive(Debug, Default, Clone, Copy)]
+struct UnsafeCallCounts {
+    /// The number of counted calls.
+    calls: usize,
+    /// The number of calls with `assure` attributes.
+    assured: usize,
 }
 
 impl PreAttrVisitor {
     /// Creates a new visitor for the syntax tree that `original_attr` was attached to.
     pub(crate) fn new(original_attr: TokenStream) -> PreAttrVisitor {
+        let activation = Activation::of_invocation(&original_attr);
         let original_attr = if !original_attr.is_empty() {
             let span = original_attr.span();
 
@@ -101,22 +266,157 @@ impl PreAttrVisitor {
             None
         };
 
-        PreAttrVisitor { original_attr }
+        metadata::set_caller_module(&[]);
+
+        PreAttrVisitor {
+            original_attr,
+            activation,
+            strict_unsafe: false,
+            harvest_asserts: false,
+            scan_macros: false,
+            trusted: false,
+            lints: LintLevels::default(),
+            module_path: Vec::new(),
+            in_unsafe: false,
+            unsafe_calls: UnsafeCallCounts::default(),
+        }
+    }
+
+    /// Visits the given function and renders it with its `pre` attributes applied.
+    fn visit_function(
+        &mut self,
+        function: &mut ItemFn,
+        first_attr: Option<PreAttr>,
+    ) -> TokenStream {
+        let attrs = parse_function_attrs(function, first_attr);
+        let stmts_after_setup = take_setup_marker(function);
+
+        let outer_strict_unsafe = self.strict_unsafe;
+        let outer_harvest_asserts = self.harvest_asserts;
+        let outer_scan_macros = self.scan_macros;
+        let outer_trusted = self.trusted;
+        let outer_lints = self.lints.clone();
+        let outer_in_unsafe = self.in_unsafe;
+        let outer_unsafe_calls = self.unsafe_calls;
+        self.in_unsafe = function.sig.unsafety.is_some();
+        self.unsafe_calls = UnsafeCallCounts::default();
+        self.strict_unsafe |= attrs.strict_unsafe;
+        self.harvest_asserts |= attrs.harvest_asserts;
+        self.scan_macros |= attrs.scan_macros;
+        self.trusted |= attrs.trusted;
+        for lint_attr in &attrs.lints {
+            self.lints.apply(lint_attr);
+        }
+
+        if self.harvest_asserts {
+            harvest_asserts(function, &attrs.preconditions);
+        }
+        expand_assure_each(&mut function.block);
+        expand_same_as(function);
+        assure_trusted_calls(function, self.trusted, &self.lints);
+        check_uses_after_consumed(function, &self.lints);
+        check_temporary_storage(function, &self.lints);
+        check_calls_outside_unsafe(function, &self.lints);
+        export_unsafe_call_records(function);
+        check_constant_assures(function);
+        export_contract_flow(function, &attrs.preconditions);
+        if let Some(budget) = &attrs.call_budget {
+            check_call_budget(function, budget);
+        }
+        if attrs.pure {
+            check_pure_conditions(&attrs.preconditions, &self.lints);
+        }
+        visit_item_fn_mut(self, function);
+        check_missing_assures(function, &self.lints);
+        self.export_unsafe_calls(function);
+
+        self.strict_unsafe = outer_strict_unsafe;
+        self.harvest_asserts = outer_harvest_asserts;
+        self.scan_macros = outer_scan_macros;
+        self.trusted = outer_trusted;
+        let lints = mem::replace(&mut self.lints, outer_lints);
+        self.in_unsafe = outer_in_unsafe;
+        self.unsafe_calls = outer_unsafe_calls;
+
+        render_function(
+            function,
+            attrs,
+            stmts_after_setup,
+            &self.module_path,
+            &lints,
+        )
+    }
+
+    /// Exports the number of calls in `unsafe` contexts of the function, if there are any.
+    fn export_unsafe_calls(&self, function: &ItemFn) {
+        let UnsafeCallCounts { calls, assured } = self.unsafe_calls;
+
+        if calls == 0 {
+            return;
+        }
+
+        let module = self
+            .module_path
+            .iter()
+            .map(Ident::to_string)
+            .collect::<Vec<_>>()
+            .join("::");
+
+        metadata::export(Record::UnsafeCalls {
+            function: &function.sig.ident,
+            module: &module,
+            covered: assured,
+            uncovered: calls.saturating_sub(assured),
+            safety_docs: safety_docs(&function.attrs).as_deref(),
+        });
+    }
+
+    /// Removes the lint and `trusted` attributes of the given module and applies them to the
+    /// current scope.
+    fn apply_module_attrs(&mut self, module: &mut ItemMod) {
+        flatten_cfgs(&mut module.attrs);
+
+        let lints = &mut self.lints;
+        let trusted = &mut self.trusted;
+        visit_matching_attrs_parsed_mut(&mut module.attrs, "pre", |attr: Attr<PreAttr>| {
+            match attr.content() {
+                PreAttr::Lint(lint_attr) => {
+                    lints.apply(lint_attr);
+
+                    AttributeAction::Remove
+                }
+                PreAttr::Trusted(_) => {
+                    *trusted = true;
+
+                    AttributeAction::Remove
+                }
+                // Other attributes are handled when the attribute on the module is expanded.
+                _ => AttributeAction::Keep,
+            }
+        });
     }
 }
 
 impl VisitMut for PreAttrVisitor {
     fn visit_file_mut(&mut self, file: &mut File) {
         let original_attr = self.original_attr.take();
+        check_activation(file, self.activation);
 
         if let [Item::Fn(function)] = &mut file.items[..] {
-            // Use `visit_item_fn_mut ` here, so that the function remains an `ItemFn` that can be
-            // passed to `render_function`. Using `visit_item_mut` here would result in an
-            // `Item::Verbatim` instead.
-            visit_item_fn_mut(self, function);
-
-            file.items[0] = Item::Verbatim(render_function(function, original_attr));
+            // Use `visit_item_fn_mut` inside of `visit_function` here, so that the function
+            // remains an `ItemFn` that can be passed to `render_function`. Using `visit_item_mut`
+            // here would result in an `Item::Verbatim` instead.
+            file.items[0] = Item::Verbatim(self.visit_function(function, original_attr));
         } else {
+            match &original_attr {
+                Some(PreAttr::StrictUnsafe(_)) => self.strict_unsafe = true,
+                Some(PreAttr::HarvestAsserts(_)) => self.harvest_asserts = true,
+                Some(PreAttr::ScanMacros(_)) => self.scan_macros = true,
+                Some(PreAttr::Trusted(_)) => self.trusted = true,
+                Some(PreAttr::Lint(lint_attr)) => self.lints.apply(lint_attr),
+                _ => (),
+            }
+
             visit_file_mut(self, file);
 
             if let Some(original_attr) = original_attr {
@@ -124,82 +424,296 @@ impl VisitMut for PreAttrVisitor {
                     PreAttr::Empty => None,
                     PreAttr::NoDoc(no_doc) => Some(no_doc.span()),
                     PreAttr::NoDebugAssert(no_debug_assert) => Some(no_debug_assert.span()),
+                    PreAttr::Doc(doc) => Some(doc.span()),
+                    PreAttr::DebugAssert(debug_assert) => Some(debug_assert.span()),
+                    PreAttr::StrictUnsafe(_) => None,
+                    PreAttr::ScanMacros(_) => None,
+                    PreAttr::Adapter(adapter) => Some(adapter.span()),
+                    PreAttr::GenViolationTests(tests) => Some(tests.span()),
+                    PreAttr::MustUse(must_use) => Some(must_use.span()),
+                    PreAttr::Trusted(_) => None,
+                    PreAttr::PreconditionArgFirst(arg_first) => Some(arg_first.span()),
+                    PreAttr::HarvestAsserts(_) => None,
+                    PreAttr::MaxUncheckedCalls(budget) => Some(budget.span()),
+                    PreAttr::Pure(pure) => Some(pure.span()),
+                    PreAttr::Lint(_) => None,
                     PreAttr::Precondition(precondition) => Some(precondition.span()),
                 } {
-                    emit_warning!(span, "this is ignored in this context")
+                    if let Some(diagnostic) = self.lints.diagnostic(
+                        Lint::IgnoredAttribute,
+                        span,
+                        "this is ignored in this context".into(),
+                    ) {
+                        diagnostic.emit();
+                    }
                 }
             }
         }
     }
 
     fn visit_item_mut(&mut self, item: &mut Item) {
-        visit_item_mut(self, item);
-
         if let Item::Fn(function) = item {
-            let rendered_function = render_function(function, None);
+            let rendered_function = self.visit_function(function, None);
             *item = Item::Verbatim(rendered_function);
+        } else if let Item::Mod(module) = item {
+            let outer_trusted = self.trusted;
+            let outer_lints = self.lints.clone();
+            self.apply_module_attrs(module);
+            self.module_path.push(module.ident.clone());
+            metadata::set_caller_module(&self.module_path);
+
+            visit_item_mut(self, item);
+
+            self.module_path.pop();
+            metadata::set_caller_module(&self.module_path);
+            self.trusted = outer_trusted;
+            self.lints = outer_lints;
+        } else {
+            visit_item_mut(self, item);
         }
     }
 
+    fn visit_impl_item_method_mut(&mut self, method: &mut ImplItemMethod) {
+        expand_assure_each(&mut method.block);
+
+        visit_impl_item_method_mut(self, method);
+    }
+
+    fn visit_trait_item_method_mut(&mut self, method: &mut TraitItemMethod) {
+        if let Some(body) = &mut method.default {
+            expand_assure_each(body);
+        }
+
+        visit_trait_item_method_mut(self, method);
+    }
+
     fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        hoist_operand_attributes(expr);
+
+        if self.strict_unsafe {
+            if let Expr::Unsafe(unsafe_block) = expr {
+                // This needs to happen before visiting the block, so that the call attributes are
+                // still present.
+                check_unsafe_block(unsafe_block);
+            }
+        }
+
+        let outer_in_unsafe = self.in_unsafe;
+        self.in_unsafe |= matches!(expr, Expr::Unsafe(_));
+        // Calls without `assure` attributes are only counted if they are known to need them,
+        // since most calls in `unsafe` contexts are safe.
+        let is_known_call = self.in_unsafe && is_known_precondition_call(expr);
+        if is_known_call {
+            self.unsafe_calls.calls += 1;
+        }
+
         visit_expr_mut(self, expr);
 
+        let in_unsafe = self.in_unsafe;
+        self.in_unsafe = outer_in_unsafe;
+
         if let Some(attrs) = attributes_of_expression(expr) {
+            check_foreign_call_attributes(attrs, &self.lints);
+
             if let Some(call_attrs) = remove_call_attributes(attrs) {
-                render_expr(expr, call_attrs);
+                if in_unsafe && !call_attrs.assure_attributes.is_empty() {
+                    self.unsafe_calls.assured += 1;
+
+                    if !is_known_call {
+                        self.unsafe_calls.calls += 1;
+                    }
+                }
+
+                render_expr(expr, call_attrs, &self.lints);
             }
         }
     }
 
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        if visit_recognized_macro(mac, |expr| self.visit_expr_mut(expr)) {
+            return;
+        }
+
+        // The tokens of macro definitions are not expressions, so they are never scanned.
+        if self.scan_macros && !mac.path.is_ident("macro_rules") {
+            let tokens = mem::take(&mut mac.tokens);
+            mac.tokens = scan_macro_tokens(tokens, &mut |expr| self.visit_expr_mut(expr));
+        }
+    }
+
     fn visit_local_mut(&mut self, local: &mut Local) {
         visit_local_mut(self, local);
 
         if let Some((_, expr)) = &mut local.init {
+            check_foreign_call_attributes(&local.attrs, &self.lints);
+
             if let Some(call_attrs) = remove_call_attributes(&mut local.attrs) {
-                render_expr(expr, call_attrs);
+                let in_unsafe = self.in_unsafe || matches!(**expr, Expr::Unsafe(_));
+                if in_unsafe && !call_attrs.assure_attributes.is_empty() {
+                    self.unsafe_calls.assured += 1;
+
+                    // Known calls were already counted when visiting the expression.
+                    if !outermost_call(expr).is_some_and(is_known_precondition_call) {
+                        self.unsafe_calls.calls += 1;
+                    }
+                }
+
+                render_expr(expr, call_attrs, &self.lints);
             }
         }
     }
 }
 
-/// Renders the given function and applies all `pre` attributes to it.
-fn render_function(function: &mut ItemFn, first_attr: Option<PreAttr>) -> TokenStream {
+/// The `pre` attributes found on a function.
+struct FunctionAttrs {
+    /// The preconditions of the function.
+    preconditions: Vec<CfgPrecondition>,
+    /// Whether documentation should be generated for the preconditions.
+    render_docs: bool,
+    /// Whether `debug_assert` statements should be generated for preconditions checkable at runtime.
+    debug_assert: bool,
+    /// Whether operations sharing an `unsafe` block with an assured call should be warned about.
+    strict_unsafe: bool,
+    /// Whether assertions that could be preconditions should be reported.
+    harvest_asserts: bool,
+    /// Whether the contents of all macro invocations should be scanned for call attributes.
+    scan_macros: bool,
+    /// Whether the preconditions of calls in the function are assured automatically.
+    trusted: bool,
+    /// Whether an adapter should be generated for the function.
+    adapter: bool,
+    /// The violation tests to generate for the function.
+    violation_tests: Option<ViolationTests>,
+    /// Whether the function should be marked as `#[must_use]`.
+    must_use: bool,
+    /// Whether the precondition argument should be the first argument of the function.
+    precondition_arg_first: bool,
+    /// The maximum number of unchecked calls in the function.
+    call_budget: Option<CallBudget>,
+    /// Whether the boolean preconditions are promised to have no side effects.
+    pure: bool,
+    /// The lint levels set for the function.
+    lints: Vec<LintAttr>,
+    /// The span best representing all the attributes.
+    span: Span,
+}
+
+/// Checks whether the expression calls a function in the current crate that has preconditions.
+///
+/// Methods are never recognized, since calls to them cannot be reliably resolved.
+fn is_known_precondition_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(path) => metadata::callee_definition(&path.path).is_some(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parses and removes all `pre` attributes of the given function.
+fn parse_function_attrs(function: &mut ItemFn, first_attr: Option<PreAttr>) -> FunctionAttrs {
     flatten_cfgs(&mut function.attrs);
 
     let first_attr_span = first_attr.as_ref().and_then(|attr| match attr {
         PreAttr::Empty => None,
         PreAttr::NoDoc(no_doc) => Some(no_doc.span()),
         PreAttr::NoDebugAssert(no_debug_assert) => Some(no_debug_assert.span()),
+        PreAttr::Doc(doc) => Some(doc.span()),
+        PreAttr::DebugAssert(debug_assert) => Some(debug_assert.span()),
+        PreAttr::StrictUnsafe(strict_unsafe) => Some(strict_unsafe.span()),
+        PreAttr::ScanMacros(scan_macros) => Some(scan_macros.span()),
+        PreAttr::Adapter(adapter) => Some(adapter.span()),
+        PreAttr::GenViolationTests(tests) => Some(tests.span()),
+        PreAttr::MustUse(must_use) => Some(must_use.span()),
+        PreAttr::Trusted(trusted) => Some(trusted.span()),
+        PreAttr::PreconditionArgFirst(arg_first) => Some(arg_first.span()),
+        PreAttr::HarvestAsserts(harvest_asserts) => Some(harvest_asserts.span()),
+        PreAttr::MaxUncheckedCalls(budget) => Some(budget.span()),
+        PreAttr::Pure(pure) => Some(pure.span()),
+        PreAttr::Lint(lint) => Some(lint.span()),
         PreAttr::Precondition(precondition) => Some(precondition.span()),
     });
 
     let mut preconditions: Vec<CfgPrecondition> = Vec::new();
 
-    let mut render_docs = true;
-    let mut debug_assert = true;
+    let mut render_docs = config::render_docs_by_default();
+    let mut debug_assert = config::debug_assert_by_default();
+    let mut strict_unsafe = false;
+    let mut harvest_asserts = false;
+    let mut scan_macros = false;
+    let mut trusted = false;
+    let mut adapter = false;
+    let mut violation_tests = None;
+    let mut must_use = false;
+    let mut precondition_arg_first = false;
+    let mut call_budget = None;
+    let mut pure = false;
+    let mut lints = Vec::new();
 
     let mut handle_attr = |attr: Attr<PreAttr>| match attr.into_content() {
         (PreAttr::Empty, _, _) => (),
         (PreAttr::NoDoc(_), _, _) => render_docs = false,
         (PreAttr::NoDebugAssert(_), _, _) => debug_assert = false,
+        (PreAttr::Doc(_), _, _) => render_docs = true,
+        (PreAttr::DebugAssert(_), _, _) => debug_assert = true,
+        (PreAttr::StrictUnsafe(_), _, _) => strict_unsafe = true,
+        (PreAttr::HarvestAsserts(_), _, _) => harvest_asserts = true,
+        (PreAttr::ScanMacros(_), _, _) => scan_macros = true,
+        (PreAttr::Trusted(_), _, _) => trusted = true,
+        (PreAttr::Adapter(_), _, _) => adapter = true,
+        (PreAttr::GenViolationTests(tests), _, _) => violation_tests = Some(tests),
+        (PreAttr::MustUse(_), _, _) => must_use = true,
+        (PreAttr::PreconditionArgFirst(_), _, _) => precondition_arg_first = true,
+        (PreAttr::MaxUncheckedCalls(budget), _, _) => call_budget = Some(budget),
+        (PreAttr::Pure(_), _, _) => pure = true,
+        (PreAttr::Lint(lint), _, _) => lints.push(lint),
         (PreAttr::Precondition(precondition), cfg, span) => {
+            if let Precondition::Token {
+                value: Some((eq, value)),
+                ..
+            } = &precondition
+            {
+                emit_error!(
+                    eq.span().join(value.span()).unwrap_or_else(|| eq.span()),
+                    "a token value can only be specified when assuring the precondition";
+                    help = "try removing the value"
+                );
+            }
             if let Precondition::Boolean(boolean_expr) = &precondition {
                 if let Expr::Path(p) = &**boolean_expr {
                     if let (None, Some(ident)) = (&p.qself, p.path.get_ident()) {
-                        emit_error!(
-                            ident.span(),
-                            "keyword `{}` is not recognized by pre", ident;
-                            help = "if you wanted to use a boolean expression, try `{} == true`",
-                            ident
-                        );
+                        if ident == "interrupts_disabled" {
+                            emit_error!(
+                                ident.span(),
+                                "keyword `{}` is not recognized by pre", ident;
+                                help = "to require that interrupts are disabled, try `interrupts_disabled()`"
+                            );
+                        } else if ident == "not_reentrant" {
+                            emit_error!(
+                                ident.span(),
+                                "keyword `{}` is not recognized by pre", ident;
+                                help = "to require that the function is not called again before it returns, try `not_reentrant()`"
+                            );
+                        } else {
+                            emit_error!(
+                                ident.span(),
+                                "keyword `{}` is not recognized by pre", ident;
+                                help = "if you wanted to use a boolean expression, try `{} == true`",
+                                ident
+                            );
+                        }
                     }
                 }
             }
-            preconditions.push(CfgPrecondition {
-                precondition,
-                cfg,
-                span,
-            })
+            preconditions.extend(
+                CfgPrecondition {
+                    precondition,
+                    cfg,
+                    span,
+                }
+                .expand(),
+            )
         }
     };
 
@@ -222,33 +736,783 @@ fn render_function(function: &mut ItemFn, first_attr: Option<PreAttr>) -> TokenS
         (None, None) => Span::call_site(), // Should never be the case for non-empty preconditions
     };
 
+    gate_cfg_parameters(&function.sig, &mut preconditions);
+
+    FunctionAttrs {
+        preconditions,
+        render_docs,
+        debug_assert,
+        strict_unsafe,
+        harvest_asserts,
+        scan_macros,
+        trusted,
+        adapter,
+        violation_tests,
+        must_use,
+        precondition_arg_first,
+        call_budget,
+        pure,
+        lints,
+        span,
+    }
+}
+
+/// Renders the condition checking a `nul_terminated` or `valid_cstr` precondition at runtime.
+///
+/// Returns the condition and the description of the kind of precondition. The condition only
+/// checks that the pointer is not null. Searching for the nul terminator cannot prove a violation,
+/// because the size of the allocated object is unknown, so a search could only read out of bounds
+/// or give up on long strings that are valid.
+///
+/// These preconditions are only checked with the `cstr-checks` feature.
+fn render_cstr_check(precondition: &Precondition) -> Option<(TokenStream, &'static str)> {
+    let (ident, kind) = match precondition {
+        Precondition::NulTerminated { ident, .. } => (ident, "nul-terminated"),
+        Precondition::ValidCStr { ident, .. } => (ident, "C string"),
+        _ => return None,
+    };
+
+    if !cfg!(feature = "cstr-checks") {
+        return None;
+    }
+
+    let check = quote_spanned! { precondition.span()=>
+        !#ident.is_null()
+    };
+
+    Some((check, kind))
+}
+
+/// Renders the condition checking a `valid_utf8` precondition at runtime.
+///
+/// The value may be anything that can be referenced as a byte slice, such as `&[u8]` or
+/// `Vec<u8>`. Validating the bytes takes time linear in their length, so these preconditions are
+/// only checked with the `utf8-checks` feature.
+fn render_utf8_check(ident: &Ident) -> TokenStream {
+    quote_spanned! { ident.span()=>
+        ::core::str::from_utf8(::core::convert::AsRef::<[u8]>::as_ref(&#ident)).is_ok()
+    }
+}
+
+/// Renders a statement detecting reentrant calls of the function at runtime.
+///
+/// The statement creates a guard that marks the function as entered on the current thread until
+/// the function returns. It is only rendered for `not_reentrant` preconditions with the
+/// `reentrancy-checks` feature and not for `const fn`s or `async fn`s, because they cannot use
+/// thread locals or may be resumed on a different thread.
+fn render_reentrancy_guard(
+    precondition: &CfgPrecondition,
+    function: &ItemFn,
+    crate_name: &Ident,
+) -> Option<TokenStream> {
+    if !cfg!(feature = "reentrancy-checks")
+        || !matches!(
+            precondition.precondition(),
+            Precondition::NotReentrant { .. }
+        )
+        || function.sig.constness.is_some()
+        || function.sig.asyncness.is_some()
+    {
+        return None;
+    }
+
+    let function_name = function.sig.ident.to_string();
+    let cfg = match &precondition.cfg {
+        Some(cfg) => quote_spanned! { precondition.span()=> all(debug_assertions, #cfg) },
+        None => quote_spanned! { precondition.span()=> debug_assertions },
+    };
+
+    Some(quote_spanned! { precondition.span()=>
+        #[cfg(#cfg)]
+        let __pre_reentrancy_guard = {
+            ::#crate_name::__thread_local! {
+                static ENTERED: ::core::cell::Cell<bool> = const { ::core::cell::Cell::new(false) };
+            }
+
+            ::#crate_name::__ReentrancyGuard::enter(&ENTERED, #function_name)
+        };
+    })
+}
+
+/// Renders a `debug_assert` statement checking the precondition, if it can be checked at runtime.
+///
+/// In `const fn`s, only checks that can be evaluated at compile time are rendered. Their messages
+/// cannot contain formatted values there.
+///
+/// With the `violation-handler`, `log-violations` or `defmt-violations` features, the checks
+/// outside of `const fn`s are rendered by `render_violation_check` instead.
+fn render_debug_assert(
+    precondition: &Precondition,
+    is_const: bool,
+    function: &Ident,
+    contract_id: &str,
+) -> Option<TokenStream> {
+    let logging = cfg!(feature = "log-violations") || cfg!(feature = "defmt-violations");
+
+    if (cfg!(feature = "violation-handler") || logging) && !is_const {
+        let crate_name = Ident::new(&CRATE_NAME, Span::call_site());
+
+        return render_violation_check(precondition, function, contract_id, &crate_name);
+    }
+
+    match precondition {
+        Precondition::Boolean(expr) if is_const => Some(quote_spanned! { expr.span()=>
+            ::core::debug_assert!(#expr, "boolean precondition was wrongly assured");
+        }),
+        Precondition::Boolean(expr) => Some(quote_spanned! { expr.span()=>
+            ::core::debug_assert!(
+                #expr,
+                "boolean precondition was wrongly assured: `{}`",
+                ::core::stringify!(#expr)
+            );
+        }),
+        // `is_null`, `contains`, `as_ref` and casts of pointers to integers cannot be used in
+        // `const fn`s.
+        _ if is_const => None,
+        Precondition::NonNull { ident, .. } => Some(quote_spanned! { precondition.span()=>
+            ::core::debug_assert!(
+                !#ident.is_null(),
+                "non-null precondition was wrongly assured: `{}` is null",
+                ::core::stringify!(#ident)
+            );
+        }),
+        Precondition::ProperAlignVal { ident, align, .. }
+        | Precondition::AlignedTo { ident, align, .. } => {
+            Some(quote_spanned! { precondition.span()=>
+                ::core::debug_assert!(
+                    (#ident as *const () as usize) & ((#align) - 1) == 0,
+                    "alignment precondition was wrongly assured: `{}` is not aligned to `{}` bytes",
+                    ::core::stringify!(#ident),
+                    ::core::stringify!(#align)
+                );
+            })
+        }
+        Precondition::InRange { ident, range, .. } => Some(quote_spanned! { precondition.span()=>
+            ::core::debug_assert!(
+                (#range).contains(&#ident),
+                "range precondition was wrongly assured: `{}` is not within `{}`",
+                ::core::stringify!(#ident),
+                ::core::stringify!(#range)
+            );
+        }),
+        Precondition::ValidUtf8 { ident, .. } if cfg!(feature = "utf8-checks") => {
+            let check = render_utf8_check(ident);
+
+            Some(quote_spanned! { precondition.span()=>
+                ::core::debug_assert!(
+                    #check,
+                    "UTF-8 precondition was wrongly assured: `{}` is not valid UTF-8",
+                    ::core::stringify!(#ident)
+                );
+            })
+        }
+        Precondition::NulTerminated { ident, .. } | Precondition::ValidCStr { ident, .. } => {
+            let (check, kind) = render_cstr_check(precondition)?;
+
+            Some(quote_spanned! { precondition.span()=>
+                ::core::debug_assert!(
+                    #check,
+                    "{} precondition was wrongly assured: `{}` is null",
+                    #kind,
+                    ::core::stringify!(#ident)
+                );
+            })
+        }
+        Precondition::Configured { .. } => {
+            let check = precondition.configured_check()?;
+            let message = configured_check_message(precondition);
+
+            Some(quote_spanned! { precondition.span()=>
+                ::core::debug_assert!(#check, "{}", #message);
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the message of a failed check of a configured precondition.
+fn configured_check_message(precondition: &Precondition) -> LitStr {
+    let keyword = match precondition {
+        Precondition::Configured { keyword, .. } => keyword,
+        _ => unreachable!("only configured preconditions have configured checks"),
+    };
+
+    LitStr::new(
+        &format!(
+            "{} precondition was wrongly assured: `{}`",
+            keyword, precondition
+        ),
+        precondition.span(),
+    )
+}
+
+/// Renders a check of the precondition that logs violations and reports them to the violation
+/// handler.
+///
+/// Violations are logged with the enabled logging backends first. Afterwards they are reported to
+/// the violation handler with the `violation-handler` feature. Otherwise they panic, unless
+/// panicking after logging is disabled in the configuration.
+///
+/// Like a `debug_assert`, the check is only performed if debug assertions are enabled.
+fn render_violation_check(
+    precondition: &Precondition,
+    function: &Ident,
+    contract_id: &str,
+    crate_name: &Ident,
+) -> Option<TokenStream> {
+    let (holds, message) = match precondition {
+        Precondition::Boolean(expr) => (
+            quote! { #expr },
+            quote! {
+                ::core::concat!(
+                    "boolean precondition was wrongly assured: `",
+                    ::core::stringify!(#expr),
+                    "`"
+                )
+            },
+        ),
+        Precondition::NonNull { ident, .. } => (
+            quote! { !#ident.is_null() },
+            quote! {
+                ::core::concat!(
+                    "non-null precondition was wrongly assured: `",
+                    ::core::stringify!(#ident),
+                    "` is null"
+                )
+            },
+        ),
+        Precondition::ProperAlignVal { ident, align, .. }
+        | Precondition::AlignedTo { ident, align, .. } => (
+            quote! { (#ident as *const () as usize) & ((#align) - 1) == 0 },
+            quote! {
+                ::core::concat!(
+                    "alignment precondition was wrongly assured: `",
+                    ::core::stringify!(#ident),
+                    "` is not aligned to `",
+                    ::core::stringify!(#align),
+                    "` bytes"
+                )
+            },
+        ),
+        Precondition::InRange { ident, range, .. } => (
+            quote! { (#range).contains(&#ident) },
+            quote! {
+                ::core::concat!(
+                    "range precondition was wrongly assured: `",
+                    ::core::stringify!(#ident),
+                    "` is not within `",
+                    ::core::stringify!(#range),
+                    "`"
+                )
+            },
+        ),
+        Precondition::ValidUtf8 { ident, .. } if cfg!(feature = "utf8-checks") => (
+            render_utf8_check(ident),
+            quote! {
+                ::core::concat!(
+                    "UTF-8 precondition was wrongly assured: `",
+                    ::core::stringify!(#ident),
+                    "` is not valid UTF-8"
+                )
+            },
+        ),
+        Precondition::NulTerminated { ident, .. } | Precondition::ValidCStr { ident, .. } => {
+            let (check, kind) = render_cstr_check(precondition)?;
+
+            (
+                check,
+                quote! {
+                    ::core::concat!(
+                        #kind,
+                        " precondition was wrongly assured: `",
+                        ::core::stringify!(#ident),
+                        "` is null"
+                    )
+                },
+            )
+        }
+        Precondition::Configured { .. } => {
+            let check = precondition.configured_check()?;
+            let message = configured_check_message(precondition);
+
+            (quote! { #check }, quote! { #message })
+        }
+        _ => return None,
+    };
+
+    let function = LitStr::new(&function.to_string(), function.span());
+    let condition = LitStr::new(&precondition.to_string(), precondition.span());
+    let contract_id = LitStr::new(contract_id, precondition.span());
+
+    let mut log = TokenStream::new();
+    if cfg!(feature = "log-violations") {
+        log.extend(quote_spanned! { precondition.span()=>
+            ::#crate_name::__log::error!(
+                "{} (precondition of `{}` with contract ID `{}`)",
+                #message,
+                #function,
+                #contract_id
+            );
+        });
+    }
+    if cfg!(feature = "defmt-violations") {
+        // `defmt` needs to be a direct dependency of the crate, so it cannot be re-exported.
+        log.extend(quote_spanned! { precondition.span()=>
+            ::defmt::error!(
+                "{=str} (precondition of `{=str}` with contract ID `{=str}`)",
+                #message,
+                #function,
+                #contract_id
+            );
+        });
+    }
+
+    let report = if cfg!(feature = "violation-handler") {
+        quote_spanned! { precondition.span()=>
+            ::#crate_name::report_violation(&::#crate_name::ContractViolation {
+                function: #function,
+                condition: #condition,
+                message: #message,
+                file: ::core::file!(),
+                line: ::core::line!(),
+            });
+        }
+    } else if config::panic_after_logging() {
+        quote_spanned! { precondition.span()=>
+            ::core::panic!("{}", #message);
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    Some(quote_spanned! { precondition.span()=>
+        #[allow(clippy::nonminimal_bool)]
+        if ::core::cfg!(debug_assertions) && !(#holds) {
+            #log
+            #report
+        }
+    })
+}
+
+/// Renders the constants and marker types for the conditions exported by the function.
+///
+/// Checks that the conditions imported by the function exist and that `layout_compatible`
+/// preconditions between concrete types hold as well. These checks are placed in the function
+/// body, unless `outside_body` is set.
+fn render_condition_defs(
+    function: &mut ItemFn,
+    preconditions: &[CfgPrecondition],
+    outside_body: bool,
+) -> TokenStream {
+    let crate_name = Ident::new(&CRATE_NAME, Span::call_site());
+    let vis = &function.vis;
+    let mut tokens = TokenStream::new();
+
+    for precondition in preconditions {
+        let cfg = precondition
+            .cfg
+            .as_ref()
+            .map(|cfg| quote_spanned! { precondition.span()=> #[cfg(#cfg)] });
+
+        match precondition.precondition() {
+            Precondition::Exported { ident, text, .. } => {
+                let name = LitStr::new(&ident.to_string(), ident.span());
+
+                // The marker type shares the name of the constant, so that paths to the constant
+                // also name the marker type, which identifies the condition in the encoding.
+                tokens.extend(quote_spanned! { precondition.span()=>
+                    #cfg
+                    #[doc = #text]
+                    #vis const #ident: ::#crate_name::ConditionDef = ::#crate_name::ConditionDef {
+                        name: #name,
+                        text: #text,
+                    };
+
+                    #cfg
+                    #[doc(hidden)]
+                    #[allow(non_camel_case_types)]
+                    #vis struct #ident {}
+                });
+            }
+            Precondition::Imported { path, .. } => {
+                let check = quote_spanned! { precondition.span()=>
+                    #cfg
+                    const _: ::#crate_name::ConditionDef = #path;
+                };
+
+                if outside_body {
+                    tokens.extend(check);
+                } else {
+                    function
+                        .block
+                        .stmts
+                        .insert(0, parse2(check).expect("valid statement"));
+                }
+            }
+            Precondition::LayoutCompatible { .. } => {
+                let check = match render_layout_check(precondition.precondition(), function) {
+                    Some(check) => quote! {
+                        #cfg
+                        #check
+                    },
+                    None => continue,
+                };
+
+                if outside_body {
+                    tokens.extend(check);
+                } else {
+                    function
+                        .block
+                        .stmts
+                        .insert(0, parse2(check).expect("valid statement"));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    tokens
+}
+
+/// Checks whether the function is exported to foreign code.
+///
+/// This is the case for functions with an explicit ABI other than `extern "Rust"`, such as
+/// `extern "C"`, and functions with a `#[no_mangle]` or `#[export_name]` attribute.
+fn is_foreign_export(function: &ItemFn) -> bool {
+    let foreign_abi = match &function.sig.abi {
+        Some(abi) => abi.name.as_ref().is_none_or(|name| name.value() != "Rust"),
+        None => false,
+    };
+
+    foreign_abi
+        || has_codegen_attr(&function.attrs, "no_mangle")
+        || has_codegen_attr(&function.attrs, "export_name")
+}
+
+/// Renders the given function and applies all `pre` attributes to it.
+///
+/// If `stmts_after_setup` is given, the `debug_assert` statements are placed before that many
+/// statements at the end of the function body instead of at its start. `module_path` contains the
+/// modules around the function inside of the item that the `pre` attribute was applied to.
+fn render_function(
+    function: &mut ItemFn,
+    FunctionAttrs {
+        preconditions,
+        render_docs,
+        mut debug_assert,
+        adapter,
+        violation_tests,
+        must_use,
+        mut precondition_arg_first,
+        pure,
+        span,
+        ..
+    }: FunctionAttrs,
+    stmts_after_setup: Option<usize>,
+    module_path: &[Ident],
+    lints: &LintLevels,
+) -> TokenStream {
+    if must_use
+        && !function
+            .attrs
+            .iter()
+            .any(|attr| attr.path.is_ident("must_use"))
+    {
+        function
+            .attrs
+            .push(parse_quote_spanned! { span=> #[must_use] });
+    }
+
+    if precondition_arg_first {
+        if let Some(receiver) = function.sig.receiver() {
+            emit_error!(
+                receiver.span(),
+                "the precondition argument cannot be placed first for methods";
+                help = "calls to methods cannot be recognized reliably, try removing `precondition_arg_first`"
+            );
+
+            precondition_arg_first = false;
+        }
+    }
+
+    // The signature of functions exported to foreign code cannot change, so their preconditions
+    // are only documented, exported and checked by debug assertions.
+    //
+    // Naked functions additionally cannot contain anything but inline assembly, so their
+    // preconditions are only documented and exported.
+    let kept_signature = if has_codegen_attr(&function.attrs, "naked") {
+        debug_assert = false;
+
+        if !preconditions.is_empty() {
+            if let Some(diagnostic) = lints.diagnostic(
+                Lint::DocumentationOnly,
+                function.sig.ident.span(),
+                format!(
+                    "the preconditions of `{}` are only documented",
+                    function.sig.ident
+                ),
+            ) {
+                diagnostic
+                    .note("naked functions can neither contain `debug_assert`s nor have their signature changed".into())
+                    .emit();
+            }
+        }
+
+        Some(KeptSignature::Naked)
+    } else if is_foreign_export(function) {
+        Some(KeptSignature::ForeignExport)
+    } else {
+        None
+    };
+
+    let condition_defs = render_condition_defs(
+        function,
+        &preconditions,
+        kept_signature == Some(KeptSignature::Naked),
+    );
+
+    let adapter = if adapter && kept_signature.is_some() {
+        emit_error!(
+            span,
+            match kept_signature {
+                Some(KeptSignature::Naked) => "adapters cannot be generated for naked functions",
+                _ => "adapters cannot be generated for functions exported to foreign code",
+            };
+            help = "these functions can be used in higher-order contexts without an adapter"
+        );
+
+        None
+    } else if adapter {
+        render_adapter(
+            function,
+            module_path,
+            &preconditions,
+            span,
+            precondition_arg_first,
+        )
+    } else {
+        None
+    };
+
+    let contract_id = contract_id(
+        &preconditions
+            .iter()
+            .map(|precondition| precondition.precondition().to_string())
+            .collect::<Vec<_>>(),
+    );
+
+    let violation_tests = violation_tests.and_then(|tests| {
+        let is_const = function.sig.constness.is_some();
+        let checked = debug_assert
+            && preconditions.iter().any(|condition| {
+                let generic_check =
+                    GenericCheck::of(condition.precondition(), &function.sig.generics);
+
+                !matches!(generic_check, GenericCheck::Unsized(_))
+                    && render_debug_assert(
+                        condition.precondition(),
+                        is_const,
+                        &function.sig.ident,
+                        &contract_id,
+                    )
+                    .is_some()
+            });
+
+        render_violation_tests(
+            function,
+            &preconditions,
+            &tests,
+            checked,
+            kept_signature.is_none(),
+            precondition_arg_first,
+        )
+    });
+
     if !preconditions.is_empty() {
-        if render_docs {
+        let docs_text = generate_docs_text(&function.sig, &preconditions, None, kept_signature);
+        metadata::add_to_manual(&function.sig.ident, &docs_text);
+
+        let docs = if render_docs { Some(docs_text) } else { None };
+
+        register_definition(
+            &function.sig,
+            module_path,
+            &preconditions,
+            kept_signature.is_some(),
+            precondition_arg_first,
+            pure,
+            docs.as_deref(),
+        );
+
+        if let Some(docs) = docs {
             function
                 .attrs
-                .push(generate_docs(&function.sig, &preconditions, None));
+                .push(doc_attribute(&docs, function.sig.span()));
         }
 
         if debug_assert {
+            let is_const = function.sig.constness.is_some();
+            let position = stmts_after_setup
+                .map(|count| function.block.stmts.len() - count)
+                .unwrap_or(0);
+
             for condition in preconditions.iter() {
-                if let Precondition::Boolean(expr) = condition.precondition() {
-                    function.block.stmts.insert(
-                        0,
-                        parse2(quote_spanned! { expr.span()=>
-                            ::core::debug_assert!(
-                                #expr,
-                                "boolean precondition was wrongly assured: `{}`",
-                                ::core::stringify!(#expr)
-                            );
-                        })
-                        .expect("valid statement"),
-                    );
+                let generic_check =
+                    GenericCheck::of(condition.precondition(), &function.sig.generics);
+
+                if let GenericCheck::Unsized(param) = &generic_check {
+                    if let Some(diagnostic) = lints.diagnostic(
+                        Lint::UncheckedCondition,
+                        condition.span(),
+                        format!(
+                            "no debug assertion is generated for the precondition `{}`",
+                            condition.precondition()
+                        ),
+                    ) {
+                        diagnostic
+                            .note(format!(
+                                "`{}` is declared as `?Sized`, but `size_of` and `align_of` require a sized type",
+                                param
+                            ))
+                            .emit();
+                    }
+
+                    continue;
+                }
+
+                if let Some(assertion) = render_debug_assert(
+                    condition.precondition(),
+                    is_const,
+                    &function.sig.ident,
+                    &contract_id,
+                ) {
+                    let assertion = generic_check.guard(assertion);
+                    let assertion = match &condition.cfg {
+                        Some(cfg) => quote_spanned! { condition.span()=>
+                            #[cfg(#cfg)]
+                            {
+                                #assertion
+                            }
+                        },
+                        None => assertion,
+                    };
+
+                    function
+                        .block
+                        .stmts
+                        .insert(position, parse2(assertion).expect("valid statement"));
+                }
+            }
+
+            let crate_name = Ident::new(&CRATE_NAME, Span::call_site());
+            for condition in preconditions.iter() {
+                if let Some(guard) = render_reentrancy_guard(condition, function, &crate_name) {
+                    function
+                        .block
+                        .stmts
+                        .insert(0, parse2(guard).expect("valid statement"));
                 }
             }
         }
 
-        render_pre(preconditions, function, span)
+        if kept_signature.is_some() {
+            return quote! {
+                #condition_defs
+                #function
+                #violation_tests
+            };
+        }
+
+        let function = render_pre(preconditions, function, span, precondition_arg_first);
+
+        quote! {
+            #condition_defs
+            #function
+            #adapter
+            #violation_tests
+        }
     } else {
-        quote! { #function }
+        quote! {
+            #function
+            #adapter
+            #violation_tests
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn violation_checks() {
+        let function = Ident::new("foo", Span::call_site());
+        let crate_name = Ident::new("pre", Span::call_site());
+        let render = |precondition| {
+            let precondition: Precondition = parse2(precondition).expect("valid precondition");
+
+            render_violation_check(&precondition, &function, "0123456789abcdef", &crate_name)
+                .map(|check| check.to_string())
+        };
+
+        let check = render(quote! { x > 0 }).expect("boolean preconditions are checked");
+        assert!(check.contains(":: core :: cfg ! (debug_assertions) && ! (x > 0)"));
+        if cfg!(feature = "violation-handler") {
+            assert!(check.contains(":: pre :: report_violation"));
+            assert!(check.contains("function : \"foo\""));
+            assert!(check.contains("condition : \"x > 0\""));
+        } else {
+            assert!(check.contains(":: core :: panic !"));
+        }
+        if cfg!(feature = "log-violations") {
+            assert!(check.contains(":: pre :: __log :: error !"));
+            assert!(check.contains("\"0123456789abcdef\""));
+        }
+
+        let check = render(quote! { in_range(x, 0..10) }).expect("ranges are checked");
+        assert!(check.contains("! ((0 .. 10) . contains (& x))"));
+
+        assert!(render(quote! { non_null(ptr) }).is_some());
+        assert!(render(quote! { proper_align_val(ptr, 32) }).is_some());
+        assert!(render(quote! { aligned_to(ptr, 32) }).is_some());
+        assert_eq!(
+            render(quote! { valid_cstr(ptr) }).is_some(),
+            cfg!(feature = "cstr-checks")
+        );
+        assert_eq!(
+            render(quote! { valid_utf8(bytes) }).is_some(),
+            cfg!(feature = "utf8-checks")
+        );
+        assert!(render(quote! { valid_ptr(ptr, r) }).is_none());
+        assert!(render(quote! { "is valid" }).is_none());
+    }
+
+    #[test]
+    fn reentrancy_guards() {
+        let crate_name = Ident::new("pre", Span::call_site());
+        let render = |precondition, function| {
+            let precondition = CfgPrecondition {
+                precondition: parse2(precondition).expect("valid precondition"),
+                cfg: None,
+                span: Span::call_site(),
+            };
+            let function: ItemFn = parse2(function).expect("valid function");
+
+            render_reentrancy_guard(&precondition, &function, &crate_name)
+                .map(|guard| guard.to_string())
+        };
+
+        let guard = render(quote! { not_reentrant() }, quote! { fn foo() {} });
+        assert_eq!(guard.is_some(), cfg!(feature = "reentrancy-checks"));
+        if let Some(guard) = guard {
+            assert!(guard.contains("# [cfg (debug_assertions)]"));
+            assert!(guard.contains(":: pre :: __ReentrancyGuard :: enter (& ENTERED , \"foo\")"));
+        }
+
+        assert!(render(quote! { not_reentrant() }, quote! { const fn foo() {} }).is_none());
+        assert!(render(quote! { not_reentrant() }, quote! { async fn foo() {} }).is_none());
+        assert!(render(quote! { x > 0 }, quote! { fn foo(x: i32) {} }).is_none());
     }
 }
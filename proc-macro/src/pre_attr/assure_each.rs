@@ -0,0 +1,352 @@
+//! Handles `assure_each` attributes on `unsafe` blocks.
+//!
+//! An `assure_each` attribute is a shorthand for adding the same `assure` attribute to every call
+//! to a function in an `unsafe` block:
+//!
+//! ```rust,ignore
+//! #[assure_each(foo, valid_ptr(ptr, r), reason = "`ptr` comes from a reference", count = 2)]
+//! unsafe {
+//!     foo(ptr);
+//!     foo(ptr.add(1));
+//! }
+//! ```
+//!
+//! turns into
+//!
+//! ```rust,ignore
+//! unsafe {
+//!     #[assure(valid_ptr(ptr, r), reason = "`ptr` comes from a reference")]
+//!     foo(ptr);
+//!     #[assure(valid_ptr(ptr, r), reason = "`ptr` comes from a reference")]
+//!     foo(ptr.add(1));
+//! }
+//! ```
+//!
+//! which is then handled like any other `assure` attribute.
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use proc_macro_error::emit_error;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream, Parser},
+    parse2,
+    spanned::Spanned,
+    visit_mut::{visit_expr_call_mut, visit_expr_mut, visit_local_mut, VisitMut},
+    Attribute, Block, Expr, ExprAsync, ExprCall, ExprClosure, ExprUnsafe, Item, LitInt, Local,
+    Macro, Path, Token,
+};
+
+use super::macro_handling::visit_recognized_macro;
+use crate::{
+    call_handling::AssureAttr,
+    helpers::{attributes_of_expression, visit_matching_attrs_parsed_mut, Attr, AttributeAction},
+};
+
+/// The custom keywords used in the `assure_each` attribute.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(count);
+}
+
+/// An `assure_each` attribute, such as `#[assure_each(foo, x > 0, reason = "...", count = 3)]`.
+struct AssureEachAttr {
+    /// The function whose calls are assured.
+    function: Path,
+    /// The comma separating the function from the `assure` attribute.
+    _comma: Token![,],
+    /// The content of the `assure` attribute added to each call.
+    assure: TokenStream,
+    /// The number of calls that are expected.
+    count: Option<CallCount>,
+}
+
+impl Spanned for AssureEachAttr {
+    fn span(&self) -> Span {
+        self.function.span()
+    }
+}
+
+impl Parse for AssureEachAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let function = input.call(Path::parse_mod_style)?;
+        let comma = input.parse()?;
+
+        let mut assure = TokenStream::new();
+        while !(input.is_empty() || (input.peek(Token![,]) && input.peek2(custom_keywords::count)))
+        {
+            assure.extend(Some(input.parse::<TokenTree>()?));
+        }
+
+        // Validate the `assure` attribute once here, instead of once for every call.
+        parse2::<AssureAttr>(assure.clone())?;
+
+        let count = if input.is_empty() {
+            None
+        } else {
+            Some(input.parse()?)
+        };
+
+        Ok(AssureEachAttr {
+            function,
+            _comma: comma,
+            assure,
+            count,
+        })
+    }
+}
+
+/// The expected number of calls, such as `count = 3`.
+struct CallCount {
+    /// The comma separating the count from the `assure` attribute.
+    _comma: Token![,],
+    /// The `count` keyword.
+    _count_keyword: custom_keywords::count,
+    /// The `=` separating the `count` keyword and the number.
+    _eq: Token![=],
+    /// The number of calls.
+    count: LitInt,
+}
+
+impl Parse for CallCount {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(CallCount {
+            _comma: input.parse()?,
+            _count_keyword: input.parse()?,
+            _eq: input.parse()?,
+            count: input.parse()?,
+        })
+    }
+}
+
+/// Adds the `assure` attributes requested by `assure_each` attributes in the body of a function.
+pub(crate) fn expand_assure_each(body: &mut Block) {
+    AssureEachVisitor.visit_block_mut(body);
+}
+
+/// Searches for `assure_each` attributes and applies them.
+struct AssureEachVisitor;
+
+impl VisitMut for AssureEachVisitor {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(attrs) = attributes_of_expression(expr) {
+            let assure_each_attrs = remove_assure_each_attrs(attrs);
+
+            if !assure_each_attrs.is_empty() {
+                match expr {
+                    Expr::Unsafe(unsafe_block) => apply(assure_each_attrs, unsafe_block),
+                    _ => report_misplaced(&assure_each_attrs),
+                }
+            }
+        }
+
+        visit_expr_mut(self, expr);
+    }
+
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        let assure_each_attrs = remove_assure_each_attrs(&mut local.attrs);
+
+        if !assure_each_attrs.is_empty() {
+            match local.init.as_mut().map(|(_, init)| &mut **init) {
+                Some(Expr::Unsafe(unsafe_block)) => apply(assure_each_attrs, unsafe_block),
+                _ => report_misplaced(&assure_each_attrs),
+            }
+        }
+
+        visit_local_mut(self, local);
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {
+        // Nested items are handled on their own.
+    }
+}
+
+/// Removes and returns all `assure_each` attributes from the given attribute list.
+fn remove_assure_each_attrs(attrs: &mut Vec<Attribute>) -> Vec<Attr<AssureEachAttr>> {
+    let mut assure_each_attrs = Vec::new();
+
+    visit_matching_attrs_parsed_mut(attrs, "assure_each", |attr| {
+        assure_each_attrs.push(attr);
+
+        AttributeAction::Remove
+    });
+
+    assure_each_attrs
+}
+
+/// Reports `assure_each` attributes that are not attached to an `unsafe` block.
+fn report_misplaced(attrs: &[Attr<AssureEachAttr>]) {
+    for attr in attrs {
+        emit_error!(
+            attr.span(),
+            "`assure_each` can only be used on `unsafe` blocks";
+            help = "use `assure` to assure the preconditions of a single call"
+        );
+    }
+}
+
+/// Adds an `assure` attribute for each of the `assure_each` attributes to the matching calls.
+fn apply(attrs: Vec<Attr<AssureEachAttr>>, unsafe_block: &mut ExprUnsafe) {
+    for attr in attrs {
+        let span = attr.span();
+        let (
+            AssureEachAttr {
+                function,
+                assure,
+                count,
+                ..
+            },
+            cfg,
+            _,
+        ) = attr.into_content();
+
+        let assure_attr = match cfg {
+            Some(cfg) => quote_spanned! { span=> #[cfg_attr(#cfg, assure(#assure))] },
+            None => quote_spanned! { span=> #[assure(#assure)] },
+        };
+        let assure_attrs = Attribute::parse_outer
+            .parse2(assure_attr)
+            .expect("assure attributes are valid");
+
+        let mut visitor = CallVisitor {
+            function: &function,
+            assure_attrs: &assure_attrs,
+            calls: 0,
+        };
+        for stmt in &mut unsafe_block.block.stmts {
+            visitor.visit_stmt_mut(stmt);
+        }
+
+        let function_str = quote! { #function }.to_string().replace(' ', "");
+        match count {
+            Some(CallCount { count, .. }) => match count.base10_parse::<usize>() {
+                Ok(expected) if expected != visitor.calls => emit_error!(
+                    count,
+                    "expected {} calls to `{}` in this block, but found {}",
+                    expected,
+                    function_str,
+                    visitor.calls;
+                    help = "review the preconditions of the changed calls and update the count"
+                ),
+                Ok(_) => (),
+                Err(err) => emit_error!(err),
+            },
+            None if visitor.calls == 0 => emit_error!(
+                function,
+                "no calls to `{}` in this block", function_str;
+                help = "only direct calls outside of closures and nested items are assured"
+            ),
+            None => (),
+        }
+    }
+}
+
+/// Adds an `assure` attribute to every call to a function.
+struct CallVisitor<'a> {
+    /// The function whose calls are assured.
+    function: &'a Path,
+    /// The attributes to add to the calls.
+    assure_attrs: &'a [Attribute],
+    /// The number of calls found so far.
+    calls: usize,
+}
+
+impl VisitMut for CallVisitor<'_> {
+    fn visit_expr_call_mut(&mut self, call: &mut ExprCall) {
+        visit_expr_call_mut(self, call);
+
+        if let Expr::Path(path) = &*call.func {
+            if path.qself.is_none() && same_function(&path.path, self.function) {
+                call.attrs.extend(self.assure_attrs.iter().cloned());
+                self.calls += 1;
+            }
+        }
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        visit_recognized_macro(mac, |expr| self.visit_expr_mut(expr));
+    }
+
+    fn visit_expr_closure_mut(&mut self, _: &mut ExprClosure) {
+        // Closures are not necessarily called in the block.
+    }
+
+    fn visit_expr_async_mut(&mut self, _: &mut ExprAsync) {
+        // Async blocks are not necessarily executed in the block.
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {
+        // Nested items are handled on their own.
+    }
+}
+
+/// Checks whether the paths refer to the same function, ignoring generic arguments.
+fn same_function(call: &Path, function: &Path) -> bool {
+    call.segments.len() == function.segments.len()
+        && call
+            .segments
+            .iter()
+            .zip(function.segments.iter())
+            .all(|(call, function)| call.ident == function.ident)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_assure_each() {
+        let attr: AssureEachAttr =
+            parse2(quote! { foo::bar, valid_ptr(p, r), reason = "valid", count = 3 })
+                .expect("valid attribute");
+        let assure = &attr.assure;
+        assert_eq!(
+            quote! { #assure }.to_string(),
+            quote! { valid_ptr(p, r), reason = "valid" }.to_string()
+        );
+        assert_eq!(
+            attr.count.map(|count| count.count.to_string()).as_deref(),
+            Some("3")
+        );
+
+        let attr: AssureEachAttr =
+            parse2(quote! { foo, x < count, reason = "valid", audit = "SEC-1" })
+                .expect("valid attribute");
+        assert!(attr.count.is_none());
+
+        assert!(parse2::<AssureEachAttr>(quote! { foo }).is_err());
+    }
+
+    #[test]
+    fn assures_direct_calls() {
+        let function: Path = parse2(quote! { ptr::read }).expect("valid path");
+        let assure_attrs = Attribute::parse_outer
+            .parse2(quote! { #[assure(x > 0, reason = "valid")] })
+            .expect("valid attribute");
+        let mut block: ExprUnsafe = parse2(quote! {
+            unsafe {
+                ptr::read(a);
+                let b = ptr::read::<u8>(ptr::read(c));
+                assert_eq!(ptr::read(d), 0);
+                read(e);
+                other::ptr::read(f);
+                let g = || ptr::read(g);
+                fn h() {
+                    ptr::read(h);
+                }
+            }
+        })
+        .expect("valid unsafe block");
+
+        let mut visitor = CallVisitor {
+            function: &function,
+            assure_attrs: &assure_attrs,
+            calls: 0,
+        };
+        for stmt in &mut block.block.stmts {
+            visitor.visit_stmt_mut(stmt);
+        }
+
+        assert_eq!(visitor.calls, 4);
+    }
+}
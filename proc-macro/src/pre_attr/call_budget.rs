@@ -0,0 +1,349 @@
+//! Enforces a maximum number of unchecked calls in a function.
+//!
+//! This is only done when requested using `#[pre(max_unchecked_calls = <n>)]`.
+//!
+//! A call is unchecked, if it either calls a function with preconditions without `assure`
+//! attributes or if it calls the original of a function in an `extern_crate` module in an `unsafe`
+//! context without a `forward` attribute, bypassing the preconditions of the `extern_crate` module.
+//!
+//! Like the other checks, this is purely syntactical. Only functions whose definition or
+//! `extern_crate` module was expanded before the checked function are known and the contents of
+//! macro invocations are not checked.
+
+use proc_macro2::Span;
+use proc_macro_error::{Diagnostic, Level};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Block, Expr, ExprCall, Item, ItemFn, LitInt, Local, Path, Token,
+};
+
+use crate::{
+    extern_crate::bypasses_outline,
+    helpers::{attributes_of_expression_ref, is_matching_attr},
+    metadata::{self, Definition},
+};
+
+/// The custom keywords used in `max_unchecked_calls` attributes.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(max_unchecked_calls);
+}
+
+/// A request to limit the number of unchecked calls in a function.
+pub(crate) struct CallBudget {
+    /// The `max_unchecked_calls` keyword.
+    keyword: custom_keywords::max_unchecked_calls,
+    /// The `=` between the keyword and the limit.
+    _eq: Token![=],
+    /// The maximum number of unchecked calls.
+    limit: usize,
+}
+
+impl CallBudget {
+    /// Checks whether the input starts with a `max_unchecked_calls` attribute.
+    pub(crate) fn peek(input: ParseStream) -> bool {
+        input.peek(custom_keywords::max_unchecked_calls)
+    }
+
+    /// The span of the `max_unchecked_calls` keyword.
+    pub(crate) fn span(&self) -> Span {
+        self.keyword.span
+    }
+}
+
+impl Parse for CallBudget {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword = input.parse()?;
+        let eq = input.parse()?;
+        let limit: LitInt = input.parse()?;
+
+        Ok(CallBudget {
+            keyword,
+            _eq: eq,
+            limit: limit.base10_parse()?,
+        })
+    }
+}
+
+/// A call that is not checked by `pre`.
+struct UncheckedCall {
+    /// The span of the call.
+    span: Span,
+    /// The path of the called function.
+    function: String,
+    /// Why the call is unchecked.
+    reason: &'static str,
+}
+
+/// The kinds of call attributes that apply to an expression.
+#[derive(Debug, Default, Clone, Copy)]
+struct CallAttrKinds {
+    /// Whether an `assure` attribute applies.
+    assure: bool,
+    /// Whether a `forward` attribute applies.
+    forward: bool,
+}
+
+impl CallAttrKinds {
+    /// Determines which kinds of call attributes are among the given attributes.
+    fn of(attrs: &[Attribute]) -> CallAttrKinds {
+        CallAttrKinds {
+            assure: attrs.iter().any(|attr| is_matching_attr("assure", attr)),
+            forward: attrs.iter().any(|attr| is_matching_attr("forward", attr)),
+        }
+    }
+
+    /// Combines the call attributes of both kinds.
+    fn or(self, other: CallAttrKinds) -> CallAttrKinds {
+        CallAttrKinds {
+            assure: self.assure || other.assure,
+            forward: self.forward || other.forward,
+        }
+    }
+}
+
+/// Finds the unchecked calls in the block.
+///
+/// Call attributes apply to the first call found in the expression they are attached to, which
+/// matches how they are handled when rendering the calls.
+fn find_unchecked_calls(
+    block: &Block,
+    in_unsafe: bool,
+    definition: impl Fn(&str) -> Option<Definition>,
+    bypasses_outline: impl Fn(&Path) -> bool,
+) -> Vec<UncheckedCall> {
+    /// Searches for unchecked calls.
+    struct CallVisitor<D, B> {
+        /// Looks up the definition of a function by its name.
+        definition: D,
+        /// Checks whether a path refers to the original of a function in an `extern_crate` module.
+        bypasses_outline: B,
+        /// Whether the currently visited expression is in an `unsafe` context.
+        in_unsafe: bool,
+        /// The call attributes that apply to the next call found.
+        pending: CallAttrKinds,
+        /// The calls found so far.
+        calls: Vec<UncheckedCall>,
+    }
+
+    impl<'ast, D, B> Visit<'ast> for CallVisitor<D, B>
+    where
+        D: Fn(&str) -> Option<Definition>,
+        B: Fn(&Path) -> bool,
+    {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            let own_attrs = attributes_of_expression_ref(expr)
+                .map(CallAttrKinds::of)
+                .unwrap_or_default();
+            let attrs = std::mem::take(&mut self.pending).or(own_attrs);
+
+            let outer_in_unsafe = self.in_unsafe;
+            self.in_unsafe |= matches!(expr, Expr::Unsafe(_));
+
+            match expr {
+                Expr::Call(call) => self.check_call(call, attrs),
+                // The attributes apply to the method call itself, but method calls are not checked.
+                Expr::MethodCall(_) => (),
+                _ => self.pending = attrs,
+            }
+
+            visit_expr(self, expr);
+
+            self.pending = CallAttrKinds::default();
+            self.in_unsafe = outer_in_unsafe;
+        }
+
+        fn visit_local(&mut self, local: &'ast Local) {
+            self.pending = CallAttrKinds::of(&local.attrs);
+
+            visit_local(self, local);
+
+            self.pending = CallAttrKinds::default();
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    impl<D, B> CallVisitor<D, B>
+    where
+        D: Fn(&str) -> Option<Definition>,
+        B: Fn(&Path) -> bool,
+    {
+        /// Records the call, if it is unchecked.
+        fn check_call(&mut self, call: &ExprCall, attrs: CallAttrKinds) {
+            let path = match &*call.func {
+                Expr::Path(path) => &path.path,
+                _ => return,
+            };
+
+            let definition = path
+                .segments
+                .last()
+                .and_then(|segment| (self.definition)(&segment.ident.to_string()));
+            let missing_assure = !attrs.assure
+                && matches!(definition, Some(definition) if definition.arity == call.args.len());
+
+            let reason = if missing_assure {
+                "is missing `assure` attributes"
+            } else if self.in_unsafe && !attrs.forward && (self.bypasses_outline)(path) {
+                "bypasses the preconditions of an `extern_crate` module"
+            } else {
+                return;
+            };
+
+            let function = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+
+            self.calls.push(UncheckedCall {
+                span: call.span(),
+                function,
+                reason,
+            });
+        }
+    }
+
+    let mut visitor = CallVisitor {
+        definition,
+        bypasses_outline,
+        in_unsafe,
+        pending: CallAttrKinds::default(),
+        calls: Vec::new(),
+    };
+    visitor.visit_block(block);
+
+    visitor.calls
+}
+
+/// Emits an error, if the function contains more unchecked calls than allowed by the budget.
+///
+/// This needs to happen before the call attributes of the function are rendered.
+pub(crate) fn check_call_budget(function: &ItemFn, budget: &CallBudget) {
+    let calls = find_unchecked_calls(
+        &function.block,
+        function.sig.unsafety.is_some(),
+        metadata::definition,
+        bypasses_outline,
+    );
+
+    if calls.len() <= budget.limit {
+        return;
+    }
+
+    let mut diagnostic = Diagnostic::spanned(
+        budget.span(),
+        Level::Error,
+        format!(
+            "`{}` contains {} unchecked call{}, but at most {} {} allowed",
+            function.sig.ident,
+            calls.len(),
+            if calls.len() == 1 { "" } else { "s" },
+            budget.limit,
+            if budget.limit == 1 { "is" } else { "are" },
+        ),
+    );
+
+    for call in calls {
+        diagnostic = diagnostic.span_note(
+            call.span,
+            format!("this call to `{}` {}", call.function, call.reason),
+        );
+    }
+
+    diagnostic.emit();
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_unchecked_calls() {
+        let block: Block = parse2(quote! {
+            {
+                foo(1);
+                #[assure("is foo", reason = "it is")]
+                foo(2);
+                #[assure("is foo", reason = "it is")]
+                let _ = unsafe { foo(3) };
+                bar(foo(4));
+                std::ptr::read(p);
+                unsafe { std::ptr::read(p) };
+                unsafe {
+                    #[forward(std -> pre_std)]
+                    #[assure(valid_ptr(src, r), reason = "it is")]
+                    std::ptr::read(p)
+                };
+                unsafe { read(p) };
+
+                fn nested() {
+                    foo(5);
+                }
+            }
+        })
+        .expect("valid block");
+
+        let calls = find_unchecked_calls(
+            &block,
+            false,
+            |name| match name {
+                "foo" => Some(Definition {
+                    arity: 1,
+                    parameters: vec!["x".into()],
+                    parameter_types: Vec::new(),
+                    type_parameters: Vec::new(),
+                    preconditions: vec!["\"is foo\"".into()],
+                    contract_id: "0123456789abcdef".into(),
+                    assures: None,
+                    skeleton: Vec::new(),
+                    precondition_arg_first: false,
+                    is_unsafe: false,
+                    module: Vec::new(),
+                }),
+                _ => None,
+            },
+            |path| {
+                path.segments
+                    .iter()
+                    .map(|segment| segment.ident.to_string())
+                    .eq(["std", "ptr", "read"].iter().copied())
+            },
+        );
+
+        let calls = calls
+            .iter()
+            .map(|call| (&*call.function, call.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            [
+                ("foo", "is missing `assure` attributes"),
+                ("foo", "is missing `assure` attributes"),
+                (
+                    "std::ptr::read",
+                    "bypasses the preconditions of an `extern_crate` module"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_call_budget() {
+        let budget: CallBudget = parse2(quote! { max_unchecked_calls = 3 }).expect("valid budget");
+        assert_eq!(budget.limit, 3);
+
+        assert!(parse2::<CallBudget>(quote! { max_unchecked_calls = -1 }).is_err());
+        assert!(parse2::<CallBudget>(quote! { max_unchecked_calls }).is_err());
+    }
+}
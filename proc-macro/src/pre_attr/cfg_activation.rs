@@ -0,0 +1,106 @@
+//! Checks that activating `pre` behind a `cfg_attr` consistently gates the item.
+//!
+//! A bare `#[cfg_attr(<predicate>, pre)]` activates `pre` for an item only in the configurations
+//! where the predicate holds. In all other configurations, `pre` attributes outside of a
+//! `cfg_attr` would either activate `pre` on their own or not be handled at all, so they are
+//! reported.
+
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::emit_error;
+use syn::{visit::Visit, Attribute, File, Item};
+
+use crate::helpers::{
+    is_in_cfg_attr, is_invoked_from_cfg_attr, is_matching_attr, is_unconditional_attr,
+};
+
+/// The attributes that are handled by `pre`.
+const PRE_ATTRS: [&str; 4] = ["pre", "assure", "forward", "assure_each"];
+
+/// How `pre` is activated for the annotated item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Activation {
+    /// By an attribute that applies in all configurations.
+    Unconditional,
+    /// By a bare `pre` inside of a `cfg_attr`, which gates all processing of the item.
+    Gated,
+    /// By a `pre` attribute with arguments inside of a `cfg_attr`.
+    Conditional,
+}
+
+impl Activation {
+    /// Determines how the currently expanded `pre` attribute with the given arguments activates
+    /// `pre`.
+    pub(crate) fn of_invocation(args: &TokenStream) -> Activation {
+        match (is_invoked_from_cfg_attr(), args.is_empty()) {
+            (false, _) => Activation::Unconditional,
+            (true, true) => Activation::Gated,
+            (true, false) => Activation::Conditional,
+        }
+    }
+}
+
+/// Checks that the attributes of the annotated item are consistent with how `pre` is activated.
+pub(crate) fn check_activation(file: &File, activation: Activation) {
+    match activation {
+        Activation::Gated => UngatedAttrVisitor.visit_file(file),
+        Activation::Unconditional => {
+            for attr in file.items.iter().flat_map(item_attrs) {
+                if is_matching_attr("pre", attr) && attr.tokens.is_empty() && is_in_cfg_attr(attr) {
+                    emit_error!(
+                        attr_span(attr),
+                        "this `cfg_attr` does not limit `pre` to some configurations";
+                        note = "`pre` is already activated in all configurations by another `pre` attribute of the item";
+                        help = "put all `pre` attributes of the item behind a `cfg_attr` with the same predicate"
+                    );
+                }
+            }
+        }
+        Activation::Conditional => (),
+    }
+}
+
+/// Reports all attributes handled by `pre` that are not behind a `cfg_attr`.
+struct UngatedAttrVisitor;
+
+impl<'ast> Visit<'ast> for UngatedAttrVisitor {
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        let name = match PRE_ATTRS
+            .iter()
+            .find(|name| is_unconditional_attr(name, attr))
+        {
+            Some(name) => name,
+            None => return,
+        };
+
+        let note = if *name == "pre" {
+            "in configurations where the `cfg_attr` does not apply, this attribute activates `pre` on its own"
+        } else {
+            "in configurations where the `cfg_attr` does not apply, this attribute is not handled by `pre`"
+        };
+
+        emit_error!(
+            attr_span(attr),
+            "`pre` is activated by a `cfg_attr` for this item, but this `{}` attribute is not behind a `cfg_attr`", name;
+            note = note;
+            help = "put it behind a `cfg_attr` with the same predicate as the one activating `pre`"
+        );
+    }
+}
+
+/// Returns the span best representing the whole attribute.
+fn attr_span(attr: &Attribute) -> Span {
+    attr.pound_token
+        .span
+        .join(attr.bracket_token.span)
+        .unwrap_or(attr.bracket_token.span)
+}
+
+/// Returns the outer attributes of the item, if it can have any that are relevant to `pre`.
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Fn(function) => &function.attrs,
+        Item::Mod(module) => &module.attrs,
+        Item::Impl(impl_block) => &impl_block.attrs,
+        _ => &[],
+    }
+}
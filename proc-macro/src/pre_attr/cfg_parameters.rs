@@ -0,0 +1,225 @@
+//! Gates the preconditions that refer to parameters removed by a `cfg`.
+//!
+//! A parameter with a `#[cfg(<predicate>)]` attribute only exists in the configurations where the
+//! predicate holds. Preconditions referring to such a parameter are only required in these
+//! configurations, so they get the same predicate, combined with any `cfg` they already have.
+//!
+//! All preconditions of a function must have syntactically equal `cfg` predicates. If gating the
+//! preconditions would violate this, an error is emitted instead.
+//!
+//! References to parameters are found syntactically, so a field or method of the same name as a
+//! parameter is not considered a reference.
+
+use proc_macro2::{TokenStream, TokenTree};
+use proc_macro_error::emit_error;
+use quote::quote;
+use syn::{spanned::Spanned, FnArg, Ident, Pat, PatIdent, PatType, Signature};
+
+use crate::precondition::{CfgPrecondition, Precondition};
+
+/// A parameter that is removed by a `cfg` in some configurations.
+struct CfgParameter {
+    /// The name of the parameter.
+    ident: Ident,
+    /// The predicate under which the parameter exists.
+    cfg: TokenStream,
+}
+
+/// Returns the parameters of the signature that have a `cfg` attribute.
+fn cfg_parameters(sig: &Signature) -> Vec<CfgParameter> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(PatType { attrs, pat, .. }) => match &**pat {
+                Pat::Ident(PatIdent { ident, .. }) => {
+                    let cfgs = attrs
+                        .iter()
+                        .filter(|attr| attr.path.is_ident("cfg"))
+                        .filter_map(|attr| attr.parse_args::<TokenStream>().ok())
+                        .collect::<Vec<_>>();
+
+                    combine_predicates(cfgs).map(|cfg| CfgParameter {
+                        ident: ident.clone(),
+                        cfg,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Combines the predicates into a single predicate that holds if all of them hold.
+fn combine_predicates(predicates: Vec<TokenStream>) -> Option<TokenStream> {
+    let mut unique: Vec<TokenStream> = Vec::new();
+
+    for predicate in predicates {
+        if !unique
+            .iter()
+            .any(|other| other.to_string() == predicate.to_string())
+        {
+            unique.push(predicate);
+        }
+    }
+
+    match &unique[..] {
+        [] => None,
+        [predicate] => Some(predicate.clone()),
+        predicates => Some(quote! { all(#(#predicates),*) }),
+    }
+}
+
+/// Checks whether the precondition refers to the given identifier.
+fn refers_to(precondition: &Precondition, ident: &Ident) -> bool {
+    /// Searches the tokens for the identifier, skipping field and method names.
+    fn contains(tokens: TokenStream, ident: &Ident) -> bool {
+        let mut after_dot = false;
+
+        for token in tokens {
+            let found = match &token {
+                TokenTree::Ident(other) => !after_dot && other == ident,
+                TokenTree::Group(group) => contains(group.stream(), ident),
+                _ => false,
+            };
+
+            if found {
+                return true;
+            }
+
+            after_dot = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '.');
+        }
+
+        false
+    }
+
+    match precondition.to_string().parse() {
+        Ok(tokens) => contains(tokens, ident),
+        Err(_) => false,
+    }
+}
+
+/// Gates the preconditions referring to parameters that are removed by a `cfg`.
+pub(crate) fn gate_cfg_parameters(sig: &Signature, preconditions: &mut [CfgPrecondition]) {
+    let parameters = cfg_parameters(sig);
+    if parameters.is_empty() {
+        return;
+    }
+
+    let gated = preconditions
+        .iter()
+        .map(|precondition| {
+            let referenced = parameters
+                .iter()
+                .filter(|parameter| refers_to(precondition.precondition(), &parameter.ident))
+                .collect::<Vec<_>>();
+
+            let cfg = combine_predicates(
+                precondition
+                    .cfg
+                    .iter()
+                    .cloned()
+                    .chain(referenced.iter().map(|parameter| parameter.cfg.clone()))
+                    .collect(),
+            );
+
+            (referenced, cfg)
+        })
+        .collect::<Vec<_>>();
+
+    let render_cfg = |cfg: &Option<TokenStream>| cfg.as_ref().map(|cfg| cfg.to_string());
+    let first_cfg = gated.first().map(|(_, cfg)| render_cfg(cfg));
+
+    if gated
+        .iter()
+        .all(|(_, cfg)| Some(render_cfg(cfg)) == first_cfg)
+    {
+        for (precondition, (_, cfg)) in preconditions.iter_mut().zip(gated) {
+            precondition.cfg = cfg;
+        }
+
+        return;
+    }
+
+    for (precondition, (referenced, _)) in preconditions.iter().zip(&gated) {
+        if let Some(parameter) = referenced.first() {
+            emit_error!(
+                precondition.span(),
+                "the precondition `{}` refers to the parameter `{}`, which only exists with `cfg({})`",
+                precondition.precondition(),
+                parameter.ident,
+                parameter.cfg;
+                note = "all preconditions of a function must have syntactically equal `cfg` predicates";
+                help = "try giving the other preconditions of `{}` the same predicate with `#[cfg_attr({}, pre(...))]`",
+                sig.ident,
+                parameter.cfg
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{parse2, ItemFn};
+
+    use super::*;
+
+    fn gate(function: TokenStream) -> Vec<Option<String>> {
+        let function: ItemFn = parse2(function).expect("valid function");
+        let mut preconditions = function
+            .attrs
+            .iter()
+            .map(|attr| CfgPrecondition {
+                precondition: attr.parse_args().expect("valid precondition"),
+                cfg: None,
+                span: proc_macro2::Span::call_site(),
+            })
+            .collect::<Vec<_>>();
+
+        gate_cfg_parameters(&function.sig, &mut preconditions);
+
+        preconditions
+            .iter()
+            .map(|precondition| precondition.cfg.as_ref().map(|cfg| cfg.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn gates_preconditions() {
+        assert_eq!(
+            gate(quote! {
+                #[pre(valid_ptr(ptr, r, len))]
+                #[pre(len > 0)]
+                fn foo(ptr: *const u8, #[cfg(unix)] #[cfg(test)] len: usize) {}
+            }),
+            [
+                Some("all (unix , test)".into()),
+                Some("all (unix , test)".into())
+            ]
+        );
+        assert_eq!(
+            gate(quote! {
+                #[pre(self.len > 0)]
+                #[pre(flag.len() > 0)]
+                fn foo(flag: &str, #[cfg(unix)] len: usize) {}
+            }),
+            [None, None]
+        );
+    }
+
+    #[test]
+    fn finds_references() {
+        let ident: Ident = parse2(quote! { len }).expect("valid ident");
+        let refers = |precondition: TokenStream| {
+            refers_to(&parse2(precondition).expect("valid precondition"), &ident)
+        };
+
+        assert!(refers(quote! { len > 0 }));
+        assert!(refers(quote! { (a + len) * 2 < 10 }));
+        assert!(refers(quote! { in_range(len, 0..10) }));
+        assert!(!refers(quote! { self.len > 0 }));
+        assert!(!refers(quote! { "len is large" }));
+        assert!(!refers(quote! { length > 0 }));
+    }
+}
@@ -0,0 +1,379 @@
+//! Evaluates assured boolean preconditions with constant arguments at compile time.
+//!
+//! If all parameters that a boolean precondition refers to are passed constant arguments, such as
+//! `4` for `align` in `#[pre(align <= 16)]`, whether the precondition holds is known at compile
+//! time. For such calls a constant is generated that binds the parameters to the arguments and
+//! asserts the precondition, so that a wrong `assure` fails to compile instead of failing a
+//! `debug_assert` at runtime.
+//!
+//! The constant cannot refer to anything but the bound parameters, because the precondition was
+//! written in the context of the called function. Only preconditions consisting of literals,
+//! operators, casts and a few `const` methods of primitive types are therefore evaluated.
+//!
+//! The parameters are taken from the definition of the called function, so the check is only
+//! generated if the path of the call cannot refer to another function with the same name.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse2,
+    spanned::Spanned,
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Expr, ExprBinary, ExprCall, ExprCast, ExprGroup, ExprLit, ExprMethodCall, ExprParen,
+    ExprPath, ExprUnary, Ident, Item, ItemFn, LitStr, Local, Path, Type, TypePath,
+};
+
+use super::{unsafe_calls::outermost_call, TRUSTED_REASON};
+use crate::{
+    call_handling::AssureAttr,
+    helpers::{attributes_of_expression_ref, visit_matching_attrs_parsed, Attr},
+    metadata::{self, Definition},
+    precondition::Precondition,
+};
+
+/// The primitive types that parameters bound in the generated constant can have.
+const PRIMITIVE_TYPES: [&str; 16] = [
+    "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128",
+    "isize", "f32", "f64",
+];
+
+/// The `const` methods of primitive types that can be used in evaluated preconditions.
+const CONST_METHODS: [&str; 12] = [
+    "is_power_of_two",
+    "count_ones",
+    "count_zeros",
+    "leading_zeros",
+    "trailing_zeros",
+    "pow",
+    "abs",
+    "signum",
+    "is_positive",
+    "is_negative",
+    "wrapping_add",
+    "wrapping_sub",
+];
+
+/// Checks if the path refers to a primitive type.
+fn is_primitive(path: &Path) -> bool {
+    matches!(path.get_ident(), Some(ident) if PRIMITIVE_TYPES.iter().any(|ty| ident == ty))
+}
+
+/// Checks if the path refers to an associated constant of a primitive type, such as `u32::MAX`.
+fn is_primitive_const(path: &Path) -> bool {
+    let segments = path.segments.iter().collect::<Vec<_>>();
+
+    match &segments[..] {
+        [ty, constant] => {
+            PRIMITIVE_TYPES
+                .iter()
+                .any(|primitive| ty.ident == primitive)
+                && constant
+                    .ident
+                    .to_string()
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Checks if the type is a primitive type.
+fn is_primitive_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => is_primitive(path),
+        Type::Paren(ty) => is_primitive_type(&ty.elem),
+        Type::Group(ty) => is_primitive_type(&ty.elem),
+        _ => false,
+    }
+}
+
+/// Checks if the expression can be evaluated in a constant where only the given names are bound.
+///
+/// The names that the expression uses are added to `used`.
+fn is_evaluable<'a>(expr: &'a Expr, bound: &[String], used: &mut Vec<&'a Ident>) -> bool {
+    match expr {
+        Expr::Lit(ExprLit { attrs, .. }) => attrs.is_empty(),
+        Expr::Path(ExprPath {
+            attrs,
+            qself: None,
+            path,
+        }) if attrs.is_empty() => {
+            if is_primitive_const(path) {
+                return true;
+            }
+
+            match path.get_ident() {
+                Some(ident) if bound.iter().any(|name| ident == name) => {
+                    used.push(ident);
+                    true
+                }
+                _ => false,
+            }
+        }
+        Expr::Binary(ExprBinary {
+            attrs, left, right, ..
+        }) => {
+            attrs.is_empty() && is_evaluable(left, bound, used) && is_evaluable(right, bound, used)
+        }
+        Expr::Unary(ExprUnary { attrs, expr, .. })
+        | Expr::Paren(ExprParen { attrs, expr, .. })
+        | Expr::Group(ExprGroup { attrs, expr, .. }) => {
+            attrs.is_empty() && is_evaluable(expr, bound, used)
+        }
+        Expr::Cast(ExprCast {
+            attrs, expr, ty, ..
+        }) => attrs.is_empty() && is_primitive_type(ty) && is_evaluable(expr, bound, used),
+        Expr::MethodCall(ExprMethodCall {
+            attrs,
+            receiver,
+            method,
+            turbofish: None,
+            args,
+            ..
+        }) => {
+            attrs.is_empty()
+                && CONST_METHODS.iter().any(|name| method == name)
+                && is_evaluable(receiver, bound, used)
+                && args.iter().all(|arg| is_evaluable(arg, bound, used))
+        }
+        _ => false,
+    }
+}
+
+/// Checks if the argument is a constant expression that can be evaluated anywhere.
+fn is_constant(arg: &Expr) -> bool {
+    let mut used = Vec::new();
+
+    is_evaluable(arg, &[], &mut used)
+}
+
+/// Renders a constant asserting the boolean precondition for the arguments of the call, if they
+/// are known at compile time.
+fn render_constant_check(
+    precondition: &Expr,
+    call: &ExprCall,
+    definition: &Definition,
+) -> Option<TokenStream> {
+    if definition.arity != call.args.len() {
+        return None;
+    }
+
+    // Preconditions that don't depend on the arguments are the same for every call.
+    let mut used = Vec::new();
+    if !is_evaluable(precondition, &definition.parameters, &mut used) || used.is_empty() {
+        return None;
+    }
+
+    let mut bindings = TokenStream::new();
+    let mut bound = Vec::new();
+    for ident in used {
+        if bound.contains(&ident) {
+            continue;
+        }
+        bound.push(ident);
+
+        let index = definition
+            .parameters
+            .iter()
+            .position(|parameter| ident == parameter)?;
+        let ty: Type = parse2(definition.parameter_types.get(index)?.parse().ok()?).ok()?;
+        let arg = &call.args[index];
+
+        if !is_primitive_type(&ty) || !is_constant(arg) {
+            return None;
+        }
+
+        bindings.extend(quote_spanned! { arg.span()=>
+            let #ident: #ty = #arg;
+        });
+    }
+
+    let message = LitStr::new(
+        &format!(
+            "boolean precondition was wrongly assured: `{}`",
+            quote! { #precondition }
+        ),
+        precondition.span(),
+    );
+
+    Some(quote_spanned! { precondition.span()=>
+        #[allow(clippy::nonminimal_bool)]
+        const _: () = {
+            #bindings
+            ::core::assert!(#precondition, #message);
+        };
+    })
+}
+
+/// Finds the assured boolean preconditions with constant arguments in the function and renders
+/// the constants checking them.
+fn find_constant_assures(
+    function: &ItemFn,
+    definition: impl Fn(&Path) -> Option<Definition>,
+) -> Vec<TokenStream> {
+    /// Searches for `assure` attributes with boolean preconditions.
+    struct AttrVisitor<F> {
+        /// Looks up the definition of the function called through a path.
+        definition: F,
+        /// The constants checking the preconditions found so far.
+        checks: Vec<TokenStream>,
+    }
+
+    impl<F: Fn(&Path) -> Option<Definition>> AttrVisitor<F> {
+        /// Checks the boolean preconditions assured by the attributes for the call in the
+        /// expression.
+        fn check_attrs(&mut self, attrs: &[Attribute], expr: &Expr) {
+            let mut preconditions = Vec::new();
+
+            visit_matching_attrs_parsed(attrs, "assure", |attr: Attr<AssureAttr>| {
+                if let AssureAttr::WithReason {
+                    precondition: Precondition::Boolean(expr),
+                    reason,
+                    ..
+                } = attr.content()
+                {
+                    if reason.text() != TRUSTED_REASON {
+                        preconditions.push((**expr).clone());
+                    }
+                }
+            });
+
+            if preconditions.is_empty() {
+                return;
+            }
+
+            let call = match outermost_call(expr) {
+                Some(Expr::Call(call)) => call,
+                _ => return,
+            };
+            let definition = match &*call.func {
+                Expr::Path(path) => (self.definition)(&path.path),
+                _ => None,
+            };
+
+            if let Some(definition) = definition {
+                for precondition in preconditions {
+                    if let Some(check) = render_constant_check(&precondition, call, &definition) {
+                        self.checks.push(check);
+                    }
+                }
+            }
+        }
+    }
+
+    impl<'ast, F: Fn(&Path) -> Option<Definition>> Visit<'ast> for AttrVisitor<F> {
+        fn visit_local(&mut self, local: &'ast Local) {
+            if let Some((_, init)) = &local.init {
+                self.check_attrs(&local.attrs, init);
+            }
+
+            visit_local(self, local);
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Some(attrs) = attributes_of_expression_ref(expr) {
+                self.check_attrs(attrs, expr);
+            }
+
+            visit_expr(self, expr);
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = AttrVisitor {
+        definition,
+        checks: Vec::new(),
+    };
+    visitor.visit_block(&function.block);
+
+    visitor.checks
+}
+
+/// Adds constants to the function that check the assured boolean preconditions with constant
+/// arguments at compile time.
+pub(crate) fn check_constant_assures(function: &mut ItemFn) {
+    for check in find_constant_assures(function, metadata::callee_definition) {
+        function
+            .block
+            .stmts
+            .insert(0, parse2(check).expect("valid statement"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_constant_assures() {
+        let function: ItemFn = parse2(quote! {
+            fn f(n: usize) {
+                #[assure(align <= 16, reason = "4 is at most 16")]
+                foo(4, 1);
+
+                #[assure(align <= 16, reason = "`n` is small")]
+                foo(n, 1);
+
+                #[assure(align.is_power_of_two() && count < u8::MAX as usize, reason = "is valid")]
+                let a = foo(1 << 3, 0);
+
+                #[assure(27 + 25 > 0, reason = "does not depend on the arguments")]
+                foo(4, 1);
+
+                #[assure(align <= MAX_ALIGN, reason = "unknown constant")]
+                foo(4, 1);
+
+                #[assure(flag, reason = "not a primitive type")]
+                bar(Flag::On);
+
+                #[assure(align <= 16, reason = "unknown function")]
+                baz(4);
+
+                #[assure(align <= 16, reason = "the call is in a `#[pre(trusted)]` scope")]
+                foo(32, 1);
+            }
+        })
+        .expect("valid function");
+
+        let checks = find_constant_assures(&function, |path| {
+            let name = path.segments.last()?.ident.to_string();
+            let (parameters, parameter_types) = match &*name {
+                "foo" => (
+                    vec!["align".into(), "count".into()],
+                    vec!["usize".into(), "usize".into()],
+                ),
+                "bar" => (vec!["flag".into()], vec!["Flag".into()]),
+                _ => return None,
+            };
+
+            Some(Definition {
+                arity: parameters.len(),
+                parameters,
+                parameter_types,
+                type_parameters: Vec::new(),
+                preconditions: Vec::new(),
+                contract_id: "0123456789abcdef".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            })
+        })
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+        assert_eq!(checks.len(), 2);
+        assert!(checks[0].contains("let align : usize = 4 ;"));
+        assert!(checks[0].contains(":: core :: assert ! (align <= 16"));
+        assert!(checks[1].contains("let align : usize = 1 << 3 ;"));
+        assert!(checks[1].contains("let count : usize = 0 ;"));
+    }
+}
@@ -0,0 +1,468 @@
+//! Warns about uses of variables after they were passed to a function with a `consumed`
+//! precondition.
+//!
+//! This check is best-effort and purely syntactical: only uses of the same variable in the
+//! statements following the statement with the assured call are found, first in the same block and
+//! then in the enclosing blocks. Shadowing the variable ends the search in the current block,
+//! reassigning it ends the search completely. Uses inside of macro invocations are not recognized.
+
+use proc_macro2::Span;
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, visit_expr_assign, visit_local, visit_stmt, Visit},
+    Block, Expr, ExprAssign, ExprCall, ExprMethodCall, ExprPath, Item, ItemFn, Local, Pat,
+    PatIdent, Stmt,
+};
+
+use crate::{
+    call_handling::AssureAttr,
+    helpers::{attributes_of_expression_ref, visit_matching_attrs_parsed, Attr},
+    lints::{Lint, LintLevels},
+    metadata::{self, Definition},
+    precondition::Precondition,
+};
+
+/// A use of a variable after it was consumed by a call.
+struct UseAfterConsumed {
+    /// The name of the variable.
+    variable: String,
+    /// The span of the call consuming the variable.
+    call_span: Span,
+    /// The span of the later use of the variable.
+    use_span: Span,
+}
+
+/// A variable that is consumed by a call.
+struct ConsumedVariable {
+    /// The name of the variable.
+    variable: String,
+    /// The span of the call consuming the variable.
+    call_span: Span,
+}
+
+/// Finds the uses of variables after they were consumed in the block.
+fn find_uses_after_consumed(
+    block: &Block,
+    definition: impl Fn(&str) -> Option<Definition>,
+) -> Vec<UseAfterConsumed> {
+    /// Searches the blocks for statements consuming variables.
+    struct BlockVisitor<'ast, F> {
+        /// Looks up the definition of a function by its name.
+        definition: F,
+        /// The statements following the currently visited statement in each enclosing block.
+        continuations: Vec<&'ast [Stmt]>,
+        /// The uses found so far.
+        uses: Vec<UseAfterConsumed>,
+    }
+
+    impl<'ast, F: Fn(&str) -> Option<Definition>> Visit<'ast> for BlockVisitor<'ast, F> {
+        fn visit_block(&mut self, block: &'ast Block) {
+            for (i, stmt) in block.stmts.iter().enumerate() {
+                self.continuations.push(&block.stmts[i + 1..]);
+
+                for consumed in consumed_variables(stmt, &self.definition) {
+                    let continuations = self.continuations.iter().rev().copied();
+
+                    if let Some(use_span) = find_use(continuations, &consumed.variable) {
+                        self.uses.push(UseAfterConsumed {
+                            variable: consumed.variable,
+                            call_span: consumed.call_span,
+                            use_span,
+                        });
+                    }
+                }
+
+                visit_stmt(self, stmt);
+                self.continuations.pop();
+            }
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = BlockVisitor {
+        definition,
+        continuations: Vec::new(),
+        uses: Vec::new(),
+    };
+    visitor.visit_block(block);
+
+    visitor.uses
+}
+
+/// Returns the variables consumed by the calls assured in the statement.
+///
+/// Only the `assure` attributes belonging to this statement are considered, not the ones in
+/// nested blocks.
+fn consumed_variables(
+    stmt: &Stmt,
+    definition: &impl Fn(&str) -> Option<Definition>,
+) -> Vec<ConsumedVariable> {
+    /// Searches for `assure` attributes with `consumed` preconditions.
+    struct AttrVisitor<'a, F> {
+        /// Looks up the definition of a function by its name.
+        definition: &'a F,
+        /// The variables found so far.
+        variables: Vec<ConsumedVariable>,
+    }
+
+    impl<F: Fn(&str) -> Option<Definition>> AttrVisitor<'_, F> {
+        /// Records the variables consumed in the expression according to the attributes.
+        fn check_attrs(&mut self, attrs: &[syn::Attribute], expr: &Expr) {
+            visit_matching_attrs_parsed(attrs, "assure", |attr: Attr<AssureAttr>| {
+                let (precondition, _, _) = attr.into_content();
+
+                if let Precondition::Consumed { ident, .. } = precondition.into() {
+                    if let Some(variable) =
+                        consumed_variable(expr, &ident.to_string(), self.definition)
+                    {
+                        self.variables.push(variable);
+                    }
+                }
+            });
+        }
+    }
+
+    impl<'ast, F: Fn(&str) -> Option<Definition>> Visit<'ast> for AttrVisitor<'_, F> {
+        fn visit_local(&mut self, local: &'ast Local) {
+            if let Some((_, init)) = &local.init {
+                self.check_attrs(&local.attrs, init);
+            }
+
+            visit_local(self, local);
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Some(attrs) = attributes_of_expression_ref(expr) {
+                self.check_attrs(attrs, expr);
+            }
+
+            visit_expr(self, expr);
+        }
+
+        fn visit_block(&mut self, _: &'ast Block) {
+            // Nested blocks are checked on their own.
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = AttrVisitor {
+        definition,
+        variables: Vec::new(),
+    };
+    visitor.visit_stmt(stmt);
+
+    visitor.variables
+}
+
+/// Returns the variable passed for the consumed parameter of the call in the expression.
+fn consumed_variable(
+    expr: &Expr,
+    parameter: &str,
+    definition: &impl Fn(&str) -> Option<Definition>,
+) -> Option<ConsumedVariable> {
+    /// Collects all calls in an expression.
+    #[derive(Default)]
+    struct CallVisitor<'ast> {
+        /// The calls found so far, outer calls first.
+        calls: Vec<&'ast Expr>,
+    }
+
+    impl<'ast> Visit<'ast> for CallVisitor<'ast> {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Expr::Call(_) | Expr::MethodCall(_) = expr {
+                self.calls.push(expr);
+            }
+
+            visit_expr(self, expr);
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = CallVisitor::default();
+    visitor.visit_expr(expr);
+
+    for call in &visitor.calls {
+        let argument = match call {
+            Expr::Call(ExprCall { func, args, .. }) => {
+                let parameters = match &**func {
+                    Expr::Path(path) => path
+                        .path
+                        .segments
+                        .last()
+                        .and_then(|segment| definition(&segment.ident.to_string()))
+                        .filter(|definition| definition.arity == args.len())
+                        .map(|definition| definition.parameters),
+                    _ => None,
+                };
+
+                match parameters {
+                    // Use the argument at the position of the parameter, if the definition is known.
+                    Some(parameters) => parameters
+                        .iter()
+                        .position(|name| name == parameter)
+                        .and_then(|i| variable_name(&args[i])),
+                    // Otherwise the argument is likely named like the parameter.
+                    None => args
+                        .iter()
+                        .filter_map(variable_name)
+                        .find(|name| name == parameter),
+                }
+            }
+            Expr::MethodCall(ExprMethodCall { receiver, .. }) if parameter == "self" => {
+                variable_name(receiver)
+            }
+            Expr::MethodCall(ExprMethodCall { args, .. }) => args
+                .iter()
+                .filter_map(variable_name)
+                .find(|name| name == parameter),
+            _ => None,
+        };
+
+        // A single call with a single variable argument must consume it.
+        let argument = argument.or_else(|| match (call, &visitor.calls[..]) {
+            (Expr::Call(ExprCall { args, .. }), [_]) if args.len() == 1 => variable_name(&args[0]),
+            _ => None,
+        });
+
+        if let Some(variable) = argument {
+            return Some(ConsumedVariable {
+                variable,
+                call_span: call.span(),
+            });
+        }
+    }
+
+    None
+}
+
+/// Returns the name of the variable, if the expression is a single variable.
+fn variable_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => path.get_ident().map(|ident| ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Finds the first use of the variable in the statements of the blocks, innermost block first.
+///
+/// Shadowing the variable ends the search in the current block, reassigning it ends the search.
+fn find_use<'ast>(blocks: impl Iterator<Item = &'ast [Stmt]>, variable: &str) -> Option<Span> {
+    /// Searches for uses of a variable.
+    struct UseVisitor<'a> {
+        /// The name of the variable.
+        variable: &'a str,
+        /// The span of the first use found.
+        use_span: Option<Span>,
+        /// Whether the variable was shadowed.
+        shadowed: bool,
+        /// Whether the variable was reassigned.
+        reassigned: bool,
+    }
+
+    impl<'ast> Visit<'ast> for UseVisitor<'_> {
+        fn visit_expr_path(&mut self, path: &'ast ExprPath) {
+            if self.use_span.is_none() && path.qself.is_none() && path.path.is_ident(self.variable)
+            {
+                self.use_span = Some(path.span());
+            }
+        }
+
+        fn visit_expr_assign(&mut self, assign: &'ast ExprAssign) {
+            if variable_name(&assign.left).as_deref() == Some(self.variable) {
+                self.visit_expr(&assign.right);
+                self.reassigned = true;
+            } else {
+                visit_expr_assign(self, assign);
+            }
+        }
+
+        fn visit_local(&mut self, local: &'ast Local) {
+            visit_local(self, local);
+
+            if binds_variable(&local.pat, self.variable) {
+                self.shadowed = true;
+            }
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items have their own variables.
+        }
+    }
+
+    let mut visitor = UseVisitor {
+        variable,
+        use_span: None,
+        shadowed: false,
+        reassigned: false,
+    };
+
+    for stmts in blocks {
+        for stmt in stmts {
+            visitor.visit_stmt(stmt);
+
+            if visitor.use_span.is_some() || visitor.reassigned {
+                return visitor.use_span;
+            } else if visitor.shadowed {
+                visitor.shadowed = false;
+                break;
+            }
+        }
+    }
+
+    None
+}
+
+/// Checks whether the pattern binds a variable of the given name.
+fn binds_variable(pat: &Pat, variable: &str) -> bool {
+    /// Searches for bindings of a variable.
+    struct BindingVisitor<'a> {
+        /// The name of the variable.
+        variable: &'a str,
+        /// Whether a binding was found.
+        found: bool,
+    }
+
+    impl<'ast> Visit<'ast> for BindingVisitor<'_> {
+        fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+            self.found |= pat.ident == self.variable;
+        }
+    }
+
+    let mut visitor = BindingVisitor {
+        variable,
+        found: false,
+    };
+    visitor.visit_pat(pat);
+
+    visitor.found
+}
+
+/// Emits a warning for each use of a variable after it was consumed in the function.
+pub(crate) fn check_uses_after_consumed(function: &ItemFn, lints: &LintLevels) {
+    for found in find_uses_after_consumed(&function.block, metadata::definition) {
+        if let Some(diagnostic) = lints.diagnostic(
+            Lint::UseAfterConsumed,
+            found.use_span,
+            format!("`{}` is used after it was consumed", found.variable),
+        ) {
+            diagnostic
+                .span_note(
+                    found.call_span,
+                    format!(
+                        "`{}` was consumed here, as assured by a `consumed` precondition",
+                        found.variable
+                    ),
+                )
+                .emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_uses_after_consumed() {
+        let block: Block = parse2(quote! {
+            {
+                #[assure(consumed(ptr), reason = "not used again")]
+                let a = unsafe { take(ptr) };
+                let b = *ptr;
+
+                #[assure(consumed(raw), reason = "not used again")]
+                let c = unsafe { Box::from_raw(boxed) };
+                drop(boxed);
+
+                #[assure(consumed(ptr), reason = "not used again")]
+                unsafe { take_second(1, other) };
+                other = new_ptr();
+                let d = other;
+
+                #[assure(consumed(ptr), reason = "not used again")]
+                unsafe { take(unrelated) };
+                let unrelated = 5;
+                let e = unrelated;
+
+                {
+                    #[assure(consumed(ptr), reason = "not used again")]
+                    unsafe { take(inner) };
+                    let f = inner;
+                }
+                let g = inner;
+
+                let h = unsafe {
+                    #[assure(consumed(ptr), reason = "not used again")]
+                    take(nested)
+                };
+                let i = nested;
+
+                {
+                    #[assure(consumed(ptr), reason = "not used again")]
+                    unsafe { take(shadowed) };
+                    let shadowed = 1;
+                    let j = shadowed;
+                }
+                let k = shadowed;
+
+                #[assure(consumed(ptr), reason = "not used again")]
+                unsafe { take(late) };
+                let late_fn = || late;
+            }
+        })
+        .expect("valid block");
+
+        let uses = find_uses_after_consumed(&block, |name| match name {
+            "take" => Some(Definition {
+                arity: 1,
+                parameters: vec!["ptr".into()],
+                parameter_types: Vec::new(),
+                type_parameters: Vec::new(),
+                preconditions: vec!["consumed(ptr)".into()],
+                contract_id: "0123456789abcdef".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            }),
+            "take_second" => Some(Definition {
+                arity: 2,
+                parameters: vec!["x".into(), "ptr".into()],
+                parameter_types: Vec::new(),
+                type_parameters: Vec::new(),
+                preconditions: vec!["consumed(ptr)".into()],
+                contract_id: "0123456789abcdef".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            }),
+            _ => None,
+        });
+
+        let uses = uses
+            .iter()
+            .map(|found| &*found.variable)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uses,
+            ["ptr", "boxed", "inner", "nested", "shadowed", "late"]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Exports how the preconditions of a function flow into the calls it makes.
+//!
+//! Each precondition of a function is classified as
+//!
+//! - discharged, if it is assured with a reason for a call in the function,
+//! - propagated, if it is a precondition of a function called by the function without being
+//!   assured with a reason, for example because the call is in a `#[pre(trusted)]` scope, or
+//! - unaccounted, if no call in the function relates to it.
+//!
+//! This gives reviewers an overview of how contracts pass through layers of wrapper functions.
+//! Preconditions are compared by their text, so only calls to functions defined before the wrapper
+//! in the same crate can propagate a precondition.
+
+use std::collections::BTreeSet;
+use syn::{
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Expr, ExprCall, Item, ItemFn, Local,
+};
+
+use super::TRUSTED_REASON;
+use crate::{
+    call_handling::AssureAttr,
+    helpers::{attributes_of_expression_ref, visit_matching_attrs_parsed, Attr},
+    metadata::{self, Definition, Record},
+    precondition::CfgPrecondition,
+};
+
+/// The preconditions of a function, classified by how they flow into its calls.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ContractFlow {
+    /// The preconditions assured with a reason for a call in the function.
+    discharged: Vec<String>,
+    /// The preconditions of called functions that are not assured with a reason.
+    propagated: Vec<String>,
+    /// The preconditions that no call in the function relates to.
+    unaccounted: Vec<String>,
+}
+
+/// Classifies the preconditions of the function by how they flow into its calls.
+fn find_contract_flow(
+    function: &ItemFn,
+    preconditions: &[String],
+    definition: impl Fn(&str) -> Option<Definition>,
+) -> ContractFlow {
+    /// Collects the preconditions assured for and required by the calls in a function.
+    struct CallVisitor<F> {
+        /// Looks up the definition of a function by its name.
+        definition: F,
+        /// The preconditions assured with a reason.
+        assured: BTreeSet<String>,
+        /// The preconditions of the called functions.
+        required: BTreeSet<String>,
+    }
+
+    impl<F> CallVisitor<F> {
+        /// Remembers the preconditions assured with a reason by the attributes.
+        fn collect_assures(&mut self, attrs: &[Attribute]) {
+            let assured = &mut self.assured;
+
+            visit_matching_attrs_parsed(attrs, "assure", |attr: Attr<AssureAttr>| {
+                if let AssureAttr::WithReason {
+                    precondition,
+                    reason,
+                    ..
+                } = attr.content()
+                {
+                    if reason.text() != TRUSTED_REASON {
+                        assured.insert(precondition.to_string());
+                    }
+                }
+            });
+        }
+    }
+
+    impl<'ast, F: Fn(&str) -> Option<Definition>> Visit<'ast> for CallVisitor<F> {
+        fn visit_local(&mut self, local: &'ast Local) {
+            self.collect_assures(&local.attrs);
+
+            visit_local(self, local);
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Some(attrs) = attributes_of_expression_ref(expr) {
+                self.collect_assures(attrs);
+            }
+
+            if let Expr::Call(ExprCall { func, .. }) = expr {
+                if let Expr::Path(path) = &**func {
+                    if let Some(segment) = path.path.segments.last() {
+                        if let Some(definition) = (self.definition)(&segment.ident.to_string()) {
+                            self.required.extend(definition.preconditions);
+                        }
+                    }
+                }
+            }
+
+            visit_expr(self, expr);
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = CallVisitor {
+        definition,
+        assured: BTreeSet::new(),
+        required: BTreeSet::new(),
+    };
+    visitor.visit_block(&function.block);
+
+    let mut flow = ContractFlow::default();
+    for precondition in preconditions {
+        let kind = if visitor.assured.contains(precondition) {
+            &mut flow.discharged
+        } else if visitor.required.contains(precondition) {
+            &mut flow.propagated
+        } else {
+            &mut flow.unaccounted
+        };
+
+        if !kind.contains(precondition) {
+            kind.push(precondition.clone());
+        }
+    }
+
+    flow
+}
+
+/// Exports how the preconditions of the function flow into the calls it makes.
+pub(crate) fn export_contract_flow(function: &ItemFn, preconditions: &[CfgPrecondition]) {
+    if preconditions.is_empty() || metadata::export_path().is_none() {
+        return;
+    }
+
+    let preconditions = preconditions
+        .iter()
+        .map(|precondition| precondition.precondition().to_string())
+        .collect::<Vec<_>>();
+    let flow = find_contract_flow(function, &preconditions, metadata::definition);
+
+    metadata::export(Record::ContractFlow {
+        function: &function.sig.ident,
+        discharged: &flow.discharged,
+        propagated: &flow.propagated,
+        unaccounted: &flow.unaccounted,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn classifies_preconditions() {
+        let function: ItemFn = parse2(quote! {
+            fn wrapper<T>(ptr: *const T, len: usize) {
+                #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+                #[assure(len > 0, reason = "the call is in a `#[pre(trusted)]` scope")]
+                let first = read(ptr, len);
+
+                fn nested(len: usize) {
+                    #[assure(len < 100, reason = "is small")]
+                    check(len);
+                }
+            }
+        })
+        .expect("valid function");
+        let preconditions = [
+            "valid_ptr(ptr, r)".to_string(),
+            "len > 0".to_string(),
+            "len < 100".to_string(),
+            "\"is initialized\"".to_string(),
+        ];
+
+        let flow = find_contract_flow(&function, &preconditions, |name| {
+            if name != "read" {
+                return None;
+            }
+
+            Some(Definition {
+                arity: 2,
+                parameters: vec!["ptr".into(), "len".into()],
+                parameter_types: vec!["* const T".into(), "usize".into()],
+                type_parameters: vec!["T".into()],
+                preconditions: vec!["valid_ptr(ptr, r)".into(), "len > 0".into()],
+                contract_id: "0123456789abcdef".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            })
+        });
+
+        assert_eq!(
+            flow,
+            ContractFlow {
+                discharged: vec!["valid_ptr(ptr, r)".into()],
+                propagated: vec!["len > 0".into()],
+                unaccounted: vec!["len < 100".into(), "\"is initialized\"".into()],
+            }
+        );
+    }
+}
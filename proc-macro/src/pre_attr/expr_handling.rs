@@ -2,30 +2,115 @@
 
 use proc_macro2::Span;
 use proc_macro_error::emit_error;
-use std::convert::TryInto;
-use syn::{spanned::Spanned, Block, Expr, Local, Stmt};
+use quote::ToTokens;
+use std::{convert::TryInto, iter, mem};
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, Visit},
+    Block, Expr, ExprClosure, ExprMacro, ExprRange, Local, Stmt,
+};
 
-use crate::call_handling::{render_call, CallAttributes};
+use super::macro_handling::recognized_macro_args;
+use crate::{
+    call_handling::{render_call, CallAttributes},
+    helpers::{attributes_of_expression, is_matching_attr},
+    lints::LintLevels,
+};
+
+/// Moves the call attributes of the leftmost operand of a binary expression to the expression.
+///
+/// The attributes of a statement such as `#[assure(...)] x = foo();` are parsed as belonging to
+/// the leftmost operand `x` instead of the whole expression. If that operand contains no call,
+/// the attributes are meant for the whole expression.
+pub(crate) fn hoist_operand_attributes(expr: &mut Expr) {
+    /// Returns the left operand of the expression, if it is a binary expression.
+    fn left_operand(expr: &mut Expr) -> Option<&mut Expr> {
+        match expr {
+            Expr::Assign(expr) => Some(&mut expr.left),
+            Expr::AssignOp(expr) => Some(&mut expr.left),
+            Expr::Binary(expr) => Some(&mut expr.left),
+            Expr::Cast(expr) => Some(&mut expr.expr),
+            Expr::Range(ExprRange {
+                from: Some(from), ..
+            }) => Some(from),
+            _ => None,
+        }
+    }
+
+    let mut operand = match left_operand(expr) {
+        Some(operand) => operand,
+        None => return,
+    };
+    while left_operand(operand).is_some() {
+        operand = left_operand(operand).expect("the operand is a binary expression");
+    }
+
+    if extract_call_expr(operand).is_some() {
+        return;
+    }
+
+    let call_attrs = match attributes_of_expression(operand) {
+        Some(attrs) => {
+            let (call_attrs, other_attrs) = mem::take(attrs).into_iter().partition(|attr| {
+                is_matching_attr("assure", attr) || is_matching_attr("forward", attr)
+            });
+            *attrs = other_attrs;
+
+            call_attrs
+        }
+        None => return,
+    };
+
+    if let Some(attrs) = attributes_of_expression(expr) {
+        attrs.extend(call_attrs);
+    }
+}
 
 /// Renders the contained call in the given expression.
 ///
 /// This only works, if the call can be unambiguosly determined.
 /// Otherwise errors are printed.
-pub(crate) fn render_expr(expr: &mut Expr, attrs: CallAttributes) {
+pub(crate) fn render_expr(expr: &mut Expr, attrs: CallAttributes, lints: &LintLevels) {
+    if let Expr::Macro(ExprMacro { mac, .. }) = expr {
+        if let Some(mut args) = recognized_macro_args(mac) {
+            // The call needs to be unambiguos among all arguments of the macro.
+            let mut calls = args.iter_mut().filter_map(extract_call_expr);
+
+            if let (Some(expr), None) = (calls.next(), calls.next()) {
+                let call = expr
+                    .clone()
+                    .try_into()
+                    .expect("`extract_call_expr` should only return call expressions");
+
+                *expr = render_call(attrs, call, lints);
+                mac.tokens = args.into_token_stream();
+
+                return;
+            }
+        }
+    }
+
     if let Some(expr) = extract_call_expr(expr) {
         let call = expr
             .clone()
             .try_into()
             .expect("`extract_call_expr` should only return call expressions");
 
-        *expr = render_call(attrs, call);
+        *expr = render_call(attrs, call, lints);
     } else {
-        let emit_err = |span: Span| {
-            emit_error!(
+        let ambiguous_field = ambiguous_struct_field(expr);
+        let emit_err = |span: Span| match ambiguous_field {
+            Some(field_span) => emit_error!(
+                span,
+                "could not decide between the call in the functional update base and the calls in the fields";
+                note = field_span => "this field contains a call";
+                help = "try moving the calls in the fields into `let` statements before the struct expression"
+            ),
+            None => emit_error!(
                 span,
                 "could not find an unambiguos call to apply this to";
                 help = "try moving it closer to the call it should apply to"
-            )
+            ),
         };
 
         if let Some(forward) = attrs.forward {
@@ -38,6 +123,28 @@ pub(crate) fn render_expr(expr: &mut Expr, attrs: CallAttributes) {
     }
 }
 
+/// Returns the span of the first field containing a call, if the expression is a struct expression
+/// with a call in its functional update base.
+///
+/// In this case it is likely that the call in the base is meant, but it cannot be distinguished
+/// from the calls in the fields.
+fn ambiguous_struct_field(expr: &mut Expr) -> Option<Span> {
+    let expr = match expr {
+        Expr::Struct(expr) => expr,
+        _ => return None,
+    };
+
+    expr.rest
+        .as_mut()
+        .and_then(|rest| extract_call_expr(rest))?;
+
+    expr.fields.iter_mut().find_map(|field| {
+        let span = field.span();
+
+        extract_call_expr(&mut field.expr).map(|_| span)
+    })
+}
+
 /// Extracts an expression that is a valid call from the given expression.
 ///
 /// This may descend into nested expressions, if it would be obvious which nested expression is
@@ -60,6 +167,18 @@ fn extract_call_expr(expr: &mut Expr) -> Option<&mut Expr> {
         }
     }
 
+    /// Returns the only call among the candidates, if there is exactly one.
+    fn unique_call<'a>(
+        candidates: impl IntoIterator<Item = Option<&'a mut Expr>>,
+    ) -> Option<&'a mut Expr> {
+        let mut calls = candidates.into_iter().flatten();
+
+        match (calls.next(), calls.next()) {
+            (Some(call), None) => Some(call),
+            _ => None,
+        }
+    }
+
     macro_rules! find_subexpr {
         ($expr:expr;
          direct_return:
@@ -118,10 +237,8 @@ fn extract_call_expr(expr: &mut Expr) -> Option<&mut Expr> {
 
     find_subexpr! { expr;
         direct_return:
-            Call,
-            MethodCall;
+            Call;
         subexpressions:
-            Await.base,
             Box.expr,
             Cast.expr,
             Closure.body,
@@ -130,14 +247,13 @@ fn extract_call_expr(expr: &mut Expr) -> Option<&mut Expr> {
             Let.expr,
             Paren.expr,
             Reference.expr,
-            Try.expr,
+            Repeat.expr,
             Type.expr,
             Unary.expr;
         binary_subexpressions:
             Assign: left ^ right,
             AssignOp: left ^ right,
-            Binary: left ^ right,
-            Index: expr ^ index;
+            Binary: left ^ right;
         optional_subexpressions:
             Break.ExprBreak ? expr,
             Return.ExprReturn ? expr,
@@ -149,6 +265,173 @@ fn extract_call_expr(expr: &mut Expr) -> Option<&mut Expr> {
             TryBlock.block,
             Unsafe.block;
         manual:
-            Expr::Tuple(expr) if expr.elems.len() == 1 => extract_call_expr(&mut expr.elems[0]);
+            Expr::Await(_) | Expr::Try(_) | Expr::MethodCall(_)
+                if closure_argument(expr).is_some_and(|closure| contains_call(&closure.body)) =>
+                closure_argument_mut(expr).and_then(|closure| extract_call_expr(&mut closure.body)),
+            Expr::Await(_) | Expr::Try(_) | Expr::Index(_) if is_adapted_call(expr) => Some(expr),
+            Expr::MethodCall(call) if !chain_contains_adapted_call(&call.receiver) => Some(expr),
+            Expr::MethodCall(call) => extract_call_expr(&mut call.receiver),
+            Expr::Await(expr) => extract_call_expr(&mut expr.base),
+            Expr::Try(expr) => extract_call_expr(&mut expr.expr),
+            Expr::Index(expr) =>
+                extract_call_expr(&mut expr.expr).xor(extract_call_expr(&mut expr.index)),
+            Expr::Array(expr) => unique_call(expr.elems.iter_mut().map(extract_call_expr)),
+            Expr::Tuple(expr) => unique_call(expr.elems.iter_mut().map(extract_call_expr)),
+            Expr::Struct(expr) => unique_call(
+                expr.fields
+                    .iter_mut()
+                    .map(|field| extract_call_expr(&mut field.expr))
+                    .chain(expr.rest.as_mut().map(|rest| extract_call_expr(rest))),
+            ),
+            Expr::Range(expr) => unique_call(vec![
+                expr.from.as_mut().and_then(|from| extract_call_expr(from)),
+                expr.to.as_mut().and_then(|to| extract_call_expr(to)),
+            ]),
+            Expr::If(expr) => unique_call(vec![
+                extract_call_expr(&mut expr.cond),
+                extract_from_block(&mut expr.then_branch),
+                expr.else_branch
+                    .as_mut()
+                    .and_then(|(_, else_branch)| extract_call_expr(else_branch)),
+            ]),
+            Expr::Match(expr) => unique_call(
+                iter::once(extract_call_expr(&mut expr.expr)).chain(
+                    expr.arms.iter_mut().flat_map(|arm| {
+                        vec![
+                            arm.guard
+                                .as_mut()
+                                .and_then(|(_, guard)| extract_call_expr(guard)),
+                            extract_call_expr(&mut arm.body),
+                        ]
+                    }),
+                ),
+            ),
+            Expr::ForLoop(expr) => unique_call(vec![
+                extract_call_expr(&mut expr.expr),
+                extract_from_block(&mut expr.body),
+            ]),
+            Expr::While(expr) => unique_call(vec![
+                extract_call_expr(&mut expr.cond),
+                extract_from_block(&mut expr.body),
+            ]);
+    }
+}
+
+/// Returns the only closure argument of the method called in the expression, if there is one.
+///
+/// Methods taking a closure, such as `map_err` or `unwrap_or_else`, usually call it to produce
+/// their result, so attributes applying to such a call are meant for the call in the closure
+/// instead, such as `foo` in `r.map_err(|e| foo(e))?`. Only closures written directly as an
+/// argument count, so passing a variable containing the closure applies the attributes to the
+/// method.
+///
+/// Adapters applied to the result of the method, such as `?`, are skipped. If the receiver of the
+/// method contains an adapted call, such as `foo()?.map(...)`, the attributes apply to that call
+/// instead, like in other method chains.
+fn closure_argument(expr: &Expr) -> Option<&ExprClosure> {
+    match expr {
+        Expr::Await(expr) => closure_argument(&expr.base),
+        Expr::Try(expr) => closure_argument(&expr.expr),
+        Expr::MethodCall(call) if !chain_contains_adapted_call(&call.receiver) => {
+            let mut closures = call.args.iter().filter_map(|arg| match arg {
+                Expr::Closure(closure) => Some(closure),
+                _ => None,
+            });
+
+            match (closures.next(), closures.next()) {
+                (Some(closure), None) => Some(closure),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the only closure argument of the method called in the expression mutably.
+///
+/// See `closure_argument` for which closure is returned.
+fn closure_argument_mut(expr: &mut Expr) -> Option<&mut ExprClosure> {
+    match expr {
+        Expr::Await(expr) => closure_argument_mut(&mut expr.base),
+        Expr::Try(expr) => closure_argument_mut(&mut expr.expr),
+        Expr::MethodCall(call) if !chain_contains_adapted_call(&call.receiver) => {
+            let mut closures = call.args.iter_mut().filter_map(|arg| match arg {
+                Expr::Closure(closure) => Some(closure),
+                _ => None,
+            });
+
+            match (closures.next(), closures.next()) {
+                (Some(closure), None) => Some(closure),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Checks if the expression contains a function or method call.
+fn contains_call(expr: &Expr) -> bool {
+    /// Searches for calls.
+    struct CallFinder {
+        /// Whether a call was found.
+        found: bool,
+    }
+
+    impl<'ast> Visit<'ast> for CallFinder {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            match expr {
+                Expr::Call(_) | Expr::MethodCall(_) => self.found = true,
+                _ => visit_expr(self, expr),
+            }
+        }
+    }
+
+    let mut finder = CallFinder { found: false };
+    finder.visit_expr(expr);
+
+    finder.found
+}
+
+/// Checks if the expression is a call with adapters applied to its result, such as `foo(x)?`.
+///
+/// In a method chain containing such a call, such as `foo(x)?.bar().baz()`, attributes apply to
+/// the first call with an adapted result, so the method calls after it are not treated as
+/// adapted calls here.
+fn is_adapted_call(expr: &Expr) -> bool {
+    let mut base = expr;
+    let mut adapted = false;
+
+    loop {
+        base = match base {
+            Expr::Await(expr) => &expr.base,
+            Expr::Try(expr) => &expr.expr,
+            Expr::Index(expr) if extract_call_expr(&mut expr.index.clone()).is_none() => &expr.expr,
+            _ => break,
+        };
+        adapted = true;
+    }
+
+    adapted
+        && match base {
+            Expr::Call(_) => true,
+            Expr::MethodCall(call) => !chain_contains_adapted_call(&call.receiver),
+            _ => false,
+        }
+}
+
+/// Checks if the method chain ending in the expression contains an adapted call.
+fn chain_contains_adapted_call(mut expr: &Expr) -> bool {
+    loop {
+        if is_adapted_call(expr) {
+            return true;
+        }
+
+        expr = match expr {
+            Expr::MethodCall(call) => &call.receiver,
+            Expr::Await(expr) => &expr.base,
+            Expr::Try(expr) => &expr.expr,
+            Expr::Index(expr) => &expr.expr,
+            _ => return false,
+        };
     }
 }
@@ -0,0 +1,272 @@
+//! Adapts the runtime checks of boolean preconditions to the generics of the function.
+//!
+//! Some boolean preconditions only make sense for some instantiations of a generic function:
+//!
+//! - `size_of::<T>()` and `align_of::<T>()` require `T: Sized`, so a check mentioning them does
+//!   not compile if `T` is declared as `?Sized`. No check is generated for such preconditions.
+//! - Dividing by `size_of::<T>()` panics for zero-sized types, although preconditions such as
+//!   `len <= isize::MAX as usize / size_of::<T>()` are commonly written for all types. Such checks
+//!   are only performed if the size is not zero.
+//!
+//! Only the generics declared on the function itself are known, so type parameters of an
+//! enclosing `impl` block are not taken into account.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, Visit},
+    BinOp, Expr, ExprBinary, ExprCall, ExprParen, GenericArgument, GenericParam, Generics, Ident,
+    PathArguments, Type, TypeParamBound, TypePath, WherePredicate,
+};
+
+use crate::precondition::Precondition;
+
+/// The functions in `core::mem` that require a sized type argument.
+const SIZED_FUNCTIONS: [&str; 2] = ["size_of", "align_of"];
+
+/// How the check of a precondition is adapted to the generics of the function.
+#[derive(Debug, PartialEq)]
+pub(crate) enum GenericCheck {
+    /// The check can be performed for all instantiations of the function.
+    Unchanged,
+    /// The check is only performed if the sizes of the given types are not zero.
+    NonZeroSize(Vec<Type>),
+    /// The check cannot be compiled, because it requires the given type parameter to be `Sized`.
+    Unsized(Ident),
+}
+
+impl GenericCheck {
+    /// Determines how the check of the precondition is adapted to the generics.
+    pub(crate) fn of(precondition: &Precondition, generics: &Generics) -> GenericCheck {
+        let expr = match precondition {
+            Precondition::Boolean(expr) => expr,
+            _ => return GenericCheck::Unchanged,
+        };
+
+        let mut visitor = SizeVisitor {
+            unsized_params: unsized_params(generics),
+            unsized_use: None,
+            divisors: Vec::new(),
+        };
+        visitor.visit_expr(expr);
+
+        if let Some(param) = visitor.unsized_use {
+            GenericCheck::Unsized(param)
+        } else if visitor.divisors.is_empty() {
+            GenericCheck::Unchanged
+        } else {
+            GenericCheck::NonZeroSize(visitor.divisors)
+        }
+    }
+
+    /// Wraps the rendered check in the guard required for the generics.
+    pub(crate) fn guard(&self, check: TokenStream) -> TokenStream {
+        match self {
+            GenericCheck::NonZeroSize(types) => quote_spanned! { check.span()=>
+                if #(::core::mem::size_of::<#types>() != 0)&&* {
+                    #check
+                }
+            },
+            _ => check,
+        }
+    }
+}
+
+/// Finds the uses of `size_of` and `align_of` in an expression.
+struct SizeVisitor {
+    /// The type parameters declared as `?Sized`.
+    unsized_params: Vec<Ident>,
+    /// The first type parameter declared as `?Sized` that a sized function is used with.
+    unsized_use: Option<Ident>,
+    /// The types whose sizes the expression divides by.
+    divisors: Vec<Type>,
+}
+
+impl<'ast> Visit<'ast> for SizeVisitor {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let Some((_, Type::Path(TypePath { qself: None, path }))) = sized_function_call(expr) {
+            if let Some(param) = path.get_ident() {
+                if self.unsized_use.is_none() && self.unsized_params.contains(param) {
+                    self.unsized_use = Some(param.clone());
+                }
+            }
+        }
+
+        if let Expr::Binary(ExprBinary { op, right, .. }) = expr {
+            let divides = matches!(op, BinOp::Div(_) | BinOp::Rem(_));
+
+            if let (true, Some(("size_of", ty))) = (divides, sized_function_call(unparen(right))) {
+                let ty_str = quote! { #ty }.to_string();
+
+                if !self
+                    .divisors
+                    .iter()
+                    .any(|divisor| quote! { #divisor }.to_string() == ty_str)
+                {
+                    self.divisors.push(ty.clone());
+                }
+            }
+        }
+
+        visit_expr(self, expr);
+    }
+}
+
+/// Removes any parentheses surrounding the expression.
+fn unparen(mut expr: &Expr) -> &Expr {
+    while let Expr::Paren(ExprParen { expr: inner, .. }) = expr {
+        expr = inner;
+    }
+
+    expr
+}
+
+/// Returns the name of the function and the type argument, if the expression is a call to
+/// `size_of::<T>()` or `align_of::<T>()`.
+fn sized_function_call(expr: &Expr) -> Option<(&'static str, &Type)> {
+    let (func, args) = match expr {
+        Expr::Call(ExprCall { func, args, .. }) => (func, args),
+        _ => return None,
+    };
+    let segment = match &**func {
+        Expr::Path(path) if args.is_empty() => path.path.segments.last()?,
+        _ => return None,
+    };
+    let function = SIZED_FUNCTIONS
+        .iter()
+        .find(|function| segment.ident == function)?;
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match &arguments.args[0] {
+                GenericArgument::Type(ty) => Some((function, ty)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the type parameters that are declared as `?Sized`.
+fn unsized_params(generics: &Generics) -> Vec<Ident> {
+    let is_maybe_sized = |bound: &TypeParamBound| match bound {
+        TypeParamBound::Trait(bound) => {
+            matches!(bound.modifier, syn::TraitBoundModifier::Maybe(_))
+                && bound.path.is_ident("Sized")
+        }
+        _ => false,
+    };
+
+    let mut params = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) if param.bounds.iter().any(is_maybe_sized) => {
+                Some(param.ident.clone())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(where_clause) = &generics.where_clause {
+        for predicate in &where_clause.predicates {
+            if let WherePredicate::Type(predicate) = predicate {
+                if let Type::Path(TypePath { qself: None, path }) = &predicate.bounded_ty {
+                    if let Some(ident) = path.get_ident() {
+                        if predicate.bounds.iter().any(is_maybe_sized) {
+                            params.push(ident.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse2, parse_quote, ItemFn};
+
+    use super::*;
+
+    fn check(function: TokenStream) -> Vec<GenericCheck> {
+        let function: ItemFn = parse2(function).expect("valid function");
+
+        function
+            .attrs
+            .iter()
+            .map(|attr| {
+                let precondition: Precondition = attr.parse_args().expect("valid precondition");
+
+                GenericCheck::of(&precondition, &function.sig.generics)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_unsized_uses() {
+        let checks = check(quote! {
+            #[pre(len * ::core::mem::size_of::<T>() <= isize::MAX as usize)]
+            #[pre(len * mem::size_of::<U>() <= isize::MAX as usize)]
+            #[pre(mem::align_of::<U>() <= 8)]
+            #[pre(len > 0)]
+            #[pre(valid_ptr(ptr, r))]
+            fn foo<T: ?Sized, U>(ptr: *const T, len: usize) where U: ?Sized + Copy {}
+        });
+
+        assert_eq!(
+            checks,
+            [
+                GenericCheck::Unsized(parse_quote!(T)),
+                GenericCheck::Unsized(parse_quote!(U)),
+                GenericCheck::Unsized(parse_quote!(U)),
+                GenericCheck::Unchanged,
+                GenericCheck::Unchanged,
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_size_divisors() {
+        let checks = check(quote! {
+            #[pre(len <= isize::MAX as usize / mem::size_of::<T>())]
+            #[pre(len % (size_of::<T>()) == 0 && len / size_of::<[T; 2]>() > size_of::<T>())]
+            #[pre(len * size_of::<T>() <= isize::MAX as usize)]
+            fn foo<T>(len: usize) {}
+        });
+
+        assert_eq!(
+            checks,
+            [
+                GenericCheck::NonZeroSize(vec![parse_quote!(T)]),
+                GenericCheck::NonZeroSize(vec![parse_quote!(T), parse_quote!([T; 2])]),
+                GenericCheck::Unchanged,
+            ]
+        );
+    }
+
+    #[test]
+    fn guards_checks() {
+        let guarded = GenericCheck::NonZeroSize(vec![parse_quote!(T), parse_quote!(U)])
+            .guard(quote! { check(); });
+
+        assert_eq!(
+            guarded.to_string(),
+            quote! {
+                if ::core::mem::size_of::<T>() != 0 && ::core::mem::size_of::<U>() != 0 {
+                    check();
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            GenericCheck::Unchanged
+                .guard(quote! { check(); })
+                .to_string(),
+            "check () ;"
+        );
+    }
+}
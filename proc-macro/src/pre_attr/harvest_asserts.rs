@@ -0,0 +1,286 @@
+//! Reports the preconditions equivalent to the assertions at the start of a function.
+//!
+//! This is only done when requested using `#[pre(harvest_asserts)]`. It is meant to ease
+//! converting code that checks its requirements using assertions to `pre`.
+
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use proc_macro_error::emit_warning;
+use quote::quote;
+use syn::{parse_quote, spanned::Spanned, BinOp, Expr, ExprBinary, Item, ItemFn, Macro, Stmt};
+
+use super::macro_handling::recognized_macro_args;
+use crate::{
+    metadata::{self, Record},
+    precondition::{CfgPrecondition, Precondition},
+    stable_warnings,
+};
+
+/// The names of the macros that are recognized as assertions.
+const ASSERTION_MACROS: [&str; 6] = [
+    "assert",
+    "debug_assert",
+    "assert_eq",
+    "debug_assert_eq",
+    "assert_ne",
+    "debug_assert_ne",
+];
+
+/// Reports the assertions at the start of the function that could be preconditions instead.
+///
+/// Assertions that are already present as a boolean precondition are skipped, as well as
+/// assertions whose condition cannot be determined.
+pub(crate) fn harvest_asserts(function: &ItemFn, preconditions: &[CfgPrecondition]) {
+    let existing = preconditions
+        .iter()
+        .filter_map(|precondition| match precondition.precondition() {
+            Precondition::Boolean(expr) => Some(quote! { #expr }.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for stmt in &function.block.stmts {
+        let mac = match stmt {
+            Stmt::Item(Item::Macro(item_macro)) => &item_macro.mac,
+            Stmt::Semi(Expr::Macro(expr_macro), _) | Stmt::Expr(Expr::Macro(expr_macro)) => {
+                &expr_macro.mac
+            }
+            _ => break,
+        };
+        if !is_assertion(mac) {
+            break;
+        }
+        let condition = match assertion_condition(mac) {
+            Some(condition) => condition,
+            None => continue,
+        };
+
+        let condition_str = quote! { #condition }.to_string();
+        if existing.contains(&condition_str) {
+            continue;
+        }
+
+        metadata::export(Record::HarvestedAssert {
+            function: &function.sig.ident,
+            precondition: &condition_str,
+        });
+
+        stable_warnings::record(
+            "harvest_asserts",
+            mac.span(),
+            "this assertion can be written as a precondition",
+        );
+        emit_warning!(
+            mac.span(),
+            "this assertion can be written as a precondition";
+            help = "add `#[pre({})]` to `{}`", render_tokens(quote! { #condition }), function.sig.ident;
+            note = "this warning is enabled by `#[pre(harvest_asserts)]`"
+        );
+    }
+}
+
+/// Checks if the macro is an assertion.
+fn is_assertion(mac: &Macro) -> bool {
+    match mac.path.segments.last() {
+        Some(segment) => ASSERTION_MACROS
+            .iter()
+            .any(|assertion| segment.ident == assertion),
+        None => false,
+    }
+}
+
+/// Returns the condition checked by the macro, if it is an assertion.
+fn assertion_condition(mac: &Macro) -> Option<Expr> {
+    let name = mac.path.segments.last()?.ident.to_string();
+    let mut args = recognized_macro_args(mac)?.into_iter();
+
+    match &*name {
+        "assert" | "debug_assert" => args.next(),
+        "assert_eq" | "debug_assert_eq" => Some(comparison(
+            args.next()?,
+            BinOp::Eq(Default::default()),
+            args.next()?,
+        )),
+        "assert_ne" | "debug_assert_ne" => Some(comparison(
+            args.next()?,
+            BinOp::Ne(Default::default()),
+            args.next()?,
+        )),
+        _ => None,
+    }
+}
+
+/// Compares the operands of an `assert_eq` or `assert_ne` assertion.
+///
+/// Operands that bind less tightly than a comparison, such as `a | b` or `a < b`, are put in
+/// parentheses, so that the condition compares the same values as the assertion.
+fn comparison(left: Expr, op: BinOp, right: Expr) -> Expr {
+    let operand = |expr: Expr| match expr {
+        Expr::Binary(_)
+        | Expr::Range(_)
+        | Expr::Assign(_)
+        | Expr::AssignOp(_)
+        | Expr::Closure(_)
+        | Expr::Let(_)
+        | Expr::Return(_)
+        | Expr::Break(_)
+        | Expr::Yield(_) => parse_quote! { (#expr) },
+        expr => expr,
+    };
+
+    Expr::Binary(ExprBinary {
+        attrs: Vec::new(),
+        left: Box::new(operand(left)),
+        op,
+        right: Box::new(operand(right)),
+    })
+}
+
+/// Renders the tokens the way they are usually written.
+///
+/// `quote` separates all tokens by spaces, such as in `! p . is_null ()`, which is hard to read
+/// in a suggestion. This only keeps the spaces around binary operators, after commas and inside
+/// of braces, which covers the code that is usually found in assertions.
+fn render_tokens(tokens: TokenStream) -> String {
+    let mut rendered = String::new();
+    let mut prev: Option<TokenTree> = None;
+    // Whether the previous token may be followed by a space.
+    let mut space_after_prev = false;
+    // The number of generic argument lists opened by `::<` that are not closed yet.
+    let mut open_generics = 0usize;
+    // Whether the previous token closed a generic argument list, such as in `size_of::<T>()`.
+    let mut closed_generics = false;
+
+    for token in tokens {
+        let follows_operand = matches!(
+            prev,
+            Some(TokenTree::Ident(_)) | Some(TokenTree::Group(_)) | Some(TokenTree::Literal(_))
+        );
+        let follows_alone_punct =
+            matches!(&prev, Some(TokenTree::Punct(punct)) if punct.spacing() == Spacing::Alone);
+        let follows_path_sep = matches!(&prev, Some(TokenTree::Punct(punct)) if punct.as_char() == ':')
+            && !space_after_prev;
+
+        let closes_generics = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '>')
+            && open_generics > 0;
+        let (space_before, space_after) = match &token {
+            TokenTree::Punct(punct) => match punct.as_char() {
+                '.' | ',' | ';' | '?' => (false, matches!(punct.as_char(), ',' | ';')),
+                ':' if follows_operand => (false, punct.spacing() == Spacing::Alone),
+                // The second colon of a path separator.
+                ':' if !space_after_prev => (false, false),
+                // The `!` of a macro invocation.
+                '!' if matches!(prev, Some(TokenTree::Ident(_)))
+                    && punct.spacing() == Spacing::Alone =>
+                {
+                    (false, false)
+                }
+                '<' if follows_path_sep => {
+                    open_generics += 1;
+                    (false, false)
+                }
+                '>' if closes_generics => {
+                    open_generics -= 1;
+                    (false, true)
+                }
+                // Prefix operators such as `!`, `-`, `&` and `*`.
+                '!' | '-' | '&' | '*' if prev.is_none() || follows_alone_punct => (true, false),
+                _ => (true, punct.spacing() == Spacing::Alone),
+            },
+            TokenTree::Group(group) => {
+                let is_call_or_index = group.delimiter() != Delimiter::Brace
+                    && (follows_operand
+                        || closed_generics
+                        || matches!(&prev, Some(TokenTree::Punct(punct)) if punct.as_char() == '!'));
+                (!is_call_or_index, true)
+            }
+            _ => (true, true),
+        };
+
+        if space_before && space_after_prev {
+            rendered.push(' ');
+        }
+        match &token {
+            TokenTree::Group(group) => {
+                let inner = render_tokens(group.stream());
+                match group.delimiter() {
+                    Delimiter::Parenthesis => rendered.push_str(&format!("({})", inner)),
+                    Delimiter::Bracket => rendered.push_str(&format!("[{}]", inner)),
+                    Delimiter::Brace if inner.is_empty() => rendered.push_str("{}"),
+                    Delimiter::Brace => rendered.push_str(&format!("{{ {} }}", inner)),
+                    Delimiter::None => rendered.push_str(&inner),
+                }
+            }
+            token => rendered.push_str(&token.to_string()),
+        }
+
+        space_after_prev = space_after;
+        closed_generics = closes_generics;
+        prev = Some(token);
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    /// Renders the condition of the given macro invocation.
+    fn condition(mac: proc_macro2::TokenStream) -> Option<String> {
+        let mac: Macro = parse2(mac).expect("valid macro");
+
+        assertion_condition(&mac).map(|condition| render_tokens(quote! { #condition }))
+    }
+
+    #[test]
+    fn assertion_conditions() {
+        assert_eq!(
+            condition(quote! { assert!(x > 0) }).as_deref(),
+            Some("x > 0")
+        );
+        assert_eq!(
+            condition(quote! { debug_assert!(!p.is_null(), "null: {:?}", p) }).as_deref(),
+            Some("!p.is_null()")
+        );
+        assert_eq!(
+            condition(quote! { std::assert_eq!(a.len(), b.len()) }).as_deref(),
+            Some("a.len() == b.len()")
+        );
+        assert_eq!(
+            condition(quote! { assert_eq!(a | b, c) }).as_deref(),
+            Some("(a | b) == c")
+        );
+        assert_eq!(
+            condition(quote! { assert_ne!(a < b, c < d) }).as_deref(),
+            Some("(a < b) != (c < d)")
+        );
+        assert_eq!(
+            condition(quote! { debug_assert_ne!(x, 0, "zero") }).as_deref(),
+            Some("x != 0")
+        );
+        assert_eq!(condition(quote! { assert_eq!(x) }), None);
+        assert_eq!(condition(quote! { println!("{}", x) }), None);
+    }
+
+    #[test]
+    fn rendered_tokens() {
+        let render = render_tokens;
+
+        assert_eq!(render(quote! { x > 0 && y <= -1 }), "x > 0 && y <= -1");
+        assert_eq!(
+            render(quote! { !matches!(x, Some(_)) }),
+            "!matches!(x, Some(_))"
+        );
+        assert_eq!(render(quote! { *p == v[0] }), "*p == v[0]");
+        assert_eq!(
+            render(quote! { len <= ::core::mem::size_of::<T>() * 2 }),
+            "len <= ::core::mem::size_of::<T>() * 2"
+        );
+        assert_eq!(
+            render(quote! { p.offset_from(q) == Foo { a: 1 }.a }),
+            "p.offset_from(q) == Foo { a: 1 }.a"
+        );
+    }
+}
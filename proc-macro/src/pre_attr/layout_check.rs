@@ -0,0 +1,152 @@
+//! Checks `layout_compatible` preconditions between concrete types at compile time.
+//!
+//! The check is a constant item in the function body, so it cannot refer to the generic
+//! parameters or named lifetimes of the function, nor to the parameters of an enclosing `impl`
+//! block. It is therefore only generated for types that mention none of these, as far as that can
+//! be told from the function alone.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    spanned::Spanned,
+    visit::{visit_path, visit_type, Visit},
+    GenericParam, Ident, ItemFn, Lifetime, Path, Type, TypeImplTrait, TypeInfer,
+};
+
+use crate::precondition::Precondition;
+
+/// Checks if the type can be used in an item nested in the function.
+fn is_concrete(ty: &Type, function: &ItemFn) -> bool {
+    /// Searches for parts of a type that are only known in the context of the function.
+    struct GenericVisitor<'a> {
+        /// The names of the generic type and const parameters of the function.
+        params: Vec<&'a Ident>,
+        /// Whether the type refers to something only known in the context of the function.
+        found: bool,
+    }
+
+    impl<'ast> Visit<'ast> for GenericVisitor<'_> {
+        fn visit_path(&mut self, path: &'ast Path) {
+            if let Some(first) = path.segments.first() {
+                if first.ident == "Self" || self.params.iter().any(|param| first.ident == **param) {
+                    self.found = true;
+                }
+            }
+
+            visit_path(self, path);
+        }
+
+        fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+            if lifetime.ident != "static" {
+                self.found = true;
+            }
+        }
+
+        fn visit_type_impl_trait(&mut self, _: &'ast TypeImplTrait) {
+            self.found = true;
+        }
+
+        fn visit_type_infer(&mut self, _: &'ast TypeInfer) {
+            self.found = true;
+        }
+    }
+
+    let mut visitor = GenericVisitor {
+        params: function
+            .sig
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Type(param) => Some(&param.ident),
+                GenericParam::Const(param) => Some(&param.ident),
+                GenericParam::Lifetime(_) => None,
+            })
+            .collect(),
+        found: false,
+    };
+    visit_type(&mut visitor, ty);
+
+    !visitor.found
+}
+
+/// Renders a constant that fails to compile if the `layout_compatible` precondition does not hold.
+///
+/// Returns `None` for other preconditions and for types that cannot be checked in the function.
+/// Methods are not checked, because the types may refer to the parameters of their `impl` block.
+pub(crate) fn render_layout_check(
+    precondition: &Precondition,
+    function: &ItemFn,
+) -> Option<TokenStream> {
+    let (from, to) = match precondition {
+        Precondition::LayoutCompatible { from, to, .. } => (from, to),
+        _ => return None,
+    };
+
+    if function.sig.receiver().is_some()
+        || !is_concrete(from, function)
+        || !is_concrete(to, function)
+    {
+        return None;
+    }
+
+    let message = format!(
+        "layout_compatible precondition does not hold: `{}` is larger or more strictly aligned than `{}`",
+        quote! { #to },
+        quote! { #from }
+    );
+
+    Some(quote_spanned! { precondition.span()=>
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<#to>() <= ::core::mem::size_of::<#from>()
+                && ::core::mem::align_of::<#to>() <= ::core::mem::align_of::<#from>(),
+            #message
+        );
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn checks_only_concrete_types() {
+        let function: ItemFn = parse2(quote! {
+            fn foo<'a, T, const N: usize>(x: &'a T) {}
+        })
+        .expect("valid function");
+        let check = |tokens| {
+            let precondition = parse2::<Precondition>(tokens).expect("valid precondition");
+
+            render_layout_check(&precondition, &function).is_some()
+        };
+
+        assert!(check(quote! { layout_compatible(u32, [u8; 4]) }));
+        assert!(check(
+            quote! { layout_compatible(&'static str, core::num::NonZeroU64) }
+        ));
+        assert!(check(quote! { layout_compatible(Header, *const u8) }));
+        assert!(!check(quote! { layout_compatible(T, u8) }));
+        assert!(!check(quote! { layout_compatible(u8, Option<T>) }));
+        assert!(!check(quote! { layout_compatible([u8; N], u8) }));
+        assert!(!check(quote! { layout_compatible(&'a u8, u8) }));
+        assert!(!check(quote! { layout_compatible(Self, u8) }));
+        assert!(!check(quote! { layout_compatible(T::Output, u8) }));
+        assert!(!check(quote! { valid_value(x, u8) }));
+    }
+
+    #[test]
+    fn does_not_check_methods() {
+        let function: ItemFn = parse2(quote! {
+            fn foo(&self) {}
+        })
+        .expect("valid function");
+        let precondition = parse2::<Precondition>(quote! { layout_compatible(u32, u8) })
+            .expect("valid precondition");
+
+        assert!(render_layout_check(&precondition, &function).is_none());
+    }
+}
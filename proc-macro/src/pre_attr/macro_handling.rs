@@ -0,0 +1,223 @@
+//! Handles call attributes inside of macro invocations.
+//!
+//! The arguments of a set of recognized std macros are always parsed as expressions, so that
+//! calls in them can be handled like any other call. The contents of other macros are only
+//! scanned for call attributes when requested using `#[pre(scan_macros)]`.
+
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    parse::{ParseStream, Parser},
+    punctuated::Punctuated,
+    Expr, Macro, Token,
+};
+
+/// The std macros whose arguments are treated as comma separated expressions.
+const RECOGNIZED_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "dbg",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "eprint",
+    "eprintln",
+    "format",
+    "format_args",
+    "panic",
+    "print",
+    "println",
+    "vec",
+    "write",
+    "writeln",
+];
+
+/// The names of the attributes that mark a call.
+const CALL_ATTRIBUTES: &[&str] = &["assure", "forward"];
+
+/// Parses the arguments of the macro, if it is a recognized macro.
+pub(crate) fn recognized_macro_args(mac: &Macro) -> Option<Punctuated<Expr, Token![,]>> {
+    let name = mac.path.segments.last()?.ident.to_string();
+
+    if !RECOGNIZED_MACROS.contains(&&*name) {
+        return None;
+    }
+
+    // Invocations that don't consist of expressions, such as `vec![0; 4]`, are left alone.
+    mac.parse_body_with(Punctuated::parse_terminated).ok()
+}
+
+/// Visits the arguments of the macro, if it is a recognized macro.
+///
+/// Returns whether the macro was recognized.
+pub(crate) fn visit_recognized_macro(mac: &mut Macro, mut visit: impl FnMut(&mut Expr)) -> bool {
+    match recognized_macro_args(mac) {
+        Some(mut args) => {
+            for arg in args.iter_mut() {
+                visit(arg);
+            }
+
+            mac.tokens = args.into_token_stream();
+
+            true
+        }
+        None => false,
+    }
+}
+
+/// Checks whether the token is the name of a call attribute.
+fn is_call_attribute_name(token: &TokenTree) -> bool {
+    match token {
+        TokenTree::Ident(ident) => CALL_ATTRIBUTES.iter().any(|name| ident == name),
+        _ => false,
+    }
+}
+
+/// Checks whether the tokens start with a call attribute.
+fn starts_with_call_attribute(tokens: &[TokenTree]) -> bool {
+    match tokens {
+        [TokenTree::Punct(pound), TokenTree::Group(group), ..]
+            if pound.as_char() == '#' && group.delimiter() == Delimiter::Bracket =>
+        {
+            let mut attr_tokens = group.stream().into_iter();
+
+            match attr_tokens.next() {
+                Some(TokenTree::Ident(ident)) if ident == "cfg_attr" => {
+                    attr_tokens.any(|token| match token {
+                        TokenTree::Group(group) => group
+                            .stream()
+                            .into_iter()
+                            .any(|token| is_call_attribute_name(&token)),
+                        _ => false,
+                    })
+                }
+                Some(token) => is_call_attribute_name(&token),
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether the tokens contain a call attribute anywhere.
+pub(crate) fn contains_call_attributes(tokens: TokenStream) -> bool {
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+
+    (0..tokens.len()).any(|i| {
+        starts_with_call_attribute(&tokens[i..])
+            || match &tokens[i] {
+                TokenTree::Group(group) => contains_call_attributes(group.stream()),
+                _ => false,
+            }
+    })
+}
+
+/// Scans the tokens for expressions with call attributes and visits them.
+///
+/// Tokens following a call attribute that cannot be parsed as an expression are left unchanged.
+pub(crate) fn scan_macro_tokens(
+    tokens: TokenStream,
+    visit: &mut impl FnMut(&mut Expr),
+) -> TokenStream {
+    let mut tokens = tokens.into_iter().collect::<Vec<_>>();
+    let mut output = TokenStream::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if starts_with_call_attribute(&tokens[i..]) {
+            let rest = tokens[i..].iter().cloned().collect::<TokenStream>();
+            let parser = |input: ParseStream| {
+                let expr: Expr = input.parse()?;
+                let rest: TokenStream = input.parse()?;
+
+                Ok((expr, rest))
+            };
+
+            if let Ok((mut expr, rest)) = parser.parse2(rest) {
+                visit(&mut expr);
+                expr.to_tokens(&mut output);
+
+                tokens = rest.into_iter().collect();
+                i = 0;
+
+                continue;
+            }
+        }
+
+        match &tokens[i] {
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), scan_macro_tokens(group.stream(), visit));
+                new_group.set_span(group.span());
+
+                output.extend(Some(TokenTree::Group(new_group)));
+            }
+            token => output.extend(Some(token.clone())),
+        }
+
+        i += 1;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    /// Removes the attributes of the visited call.
+    fn remove_attrs(expr: &mut Expr) {
+        if let Expr::Call(call) = expr {
+            call.attrs.clear();
+        }
+    }
+
+    #[test]
+    fn recognizes_std_macros() {
+        let mac: Macro = parse2(quote! { dbg!(foo(x), bar) }).expect("valid macro");
+        assert_eq!(recognized_macro_args(&mac).map(|args| args.len()), Some(2));
+
+        let mac: Macro = parse2(quote! { std::println!("{}", x) }).expect("valid macro");
+        assert_eq!(recognized_macro_args(&mac).map(|args| args.len()), Some(2));
+
+        let mac: Macro = parse2(quote! { vec![0; 4] }).expect("valid macro");
+        assert!(recognized_macro_args(&mac).is_none());
+
+        let mac: Macro = parse2(quote! { my_macro!(foo(x)) }).expect("valid macro");
+        assert!(recognized_macro_args(&mac).is_none());
+    }
+
+    #[test]
+    fn finds_call_attributes() {
+        assert!(contains_call_attributes(
+            quote! { a, { #[assure(x > 0, reason = "")] foo(x) } }
+        ));
+        assert!(contains_call_attributes(
+            quote! { #[cfg_attr(test, forward(bar))] foo(x) }
+        ));
+        assert!(!contains_call_attributes(
+            quote! { #[allow(unused)] foo(x) }
+        ));
+        assert!(!contains_call_attributes(quote! { assure(x) }));
+    }
+
+    #[test]
+    fn scans_macro_tokens() {
+        let tokens = scan_macro_tokens(
+            quote! { x => [#[assure(x > 0, reason = "")] foo(x), y] },
+            &mut remove_attrs,
+        );
+
+        assert_eq!(tokens.to_string(), quote! { x => [foo(x), y] }.to_string());
+
+        let unchanged = quote! { #[assure(x > 0, reason = "")] => foo(x) };
+        assert_eq!(
+            scan_macro_tokens(unchanged.clone(), &mut remove_attrs).to_string(),
+            unchanged.to_string()
+        );
+    }
+}
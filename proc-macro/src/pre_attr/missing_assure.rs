@@ -0,0 +1,341 @@
+//! Groups the call sites of functions with preconditions that are missing `assure` attributes.
+//!
+//! The compiler reports an error for each of these call sites on its own, which can be a lot of
+//! errors after adding preconditions to a function. The warning emitted here summarizes them per
+//! called function. The errors are already reported on stable, so the warning is only emitted on
+//! nightly.
+//!
+//! Only calls whose path cannot refer to another function with the same name are reported, so
+//! that calls to functions in other modules or crates are not mistaken for calls to a function
+//! with preconditions. The warning also contains the `assure` attributes to add to the calls, so
+//! that they can be copied into the code. For calls specifying
+//! the generic arguments using a turbofish, the preconditions with the type parameters replaced by
+//! the concrete arguments are shown as well.
+
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr_call, Visit},
+    Block, Expr, ExprCall, GenericArgument, Item, ItemFn, Path, PathArguments,
+};
+
+use crate::{
+    lints::{Lint, LintLevels},
+    metadata::{self, Definition, Record, METADATA_DIR_VAR},
+};
+
+/// The maximum number of call sites listed in a single warning.
+const MAX_LISTED_CALL_SITES: usize = 5;
+
+/// A call to a function with preconditions without a precondition argument.
+struct UnassuredCall {
+    /// The name of the called function.
+    function: String,
+    /// The span of the call.
+    span: Span,
+    /// The call expression.
+    call: String,
+    /// The generic arguments of the call specified using a turbofish, excluding lifetimes.
+    generic_args: Vec<String>,
+    /// The definition of the called function.
+    definition: Definition,
+}
+
+/// Finds the calls in the block that are missing the precondition argument.
+fn find_unassured_calls(
+    block: &Block,
+    definition: impl Fn(&Path) -> Option<Definition>,
+) -> Vec<UnassuredCall> {
+    /// Searches for calls with a matching definition, but no precondition argument.
+    struct CallVisitor<F> {
+        /// Looks up the definition of the function called through a path.
+        definition: F,
+        /// The calls found so far.
+        calls: Vec<UnassuredCall>,
+    }
+
+    impl<'ast, F: Fn(&Path) -> Option<Definition>> Visit<'ast> for CallVisitor<F> {
+        fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+            if let Expr::Path(path) = &*call.func {
+                if let Some(segment) = path.path.segments.last() {
+                    let function = segment.ident.to_string();
+
+                    // Calls with assured preconditions have one additional argument.
+                    if let Some(definition) = (self.definition)(&path.path) {
+                        if definition.arity == call.args.len() {
+                            let generic_args = match &segment.arguments {
+                                PathArguments::AngleBracketed(args) => args
+                                    .args
+                                    .iter()
+                                    .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_)))
+                                    .map(|arg| quote! { #arg }.to_string())
+                                    .collect(),
+                                _ => Vec::new(),
+                            };
+
+                            self.calls.push(UnassuredCall {
+                                function,
+                                span: call.span(),
+                                call: quote! { #call }.to_string(),
+                                generic_args,
+                                definition,
+                            });
+                        }
+                    }
+                }
+            }
+
+            visit_expr_call(self, call);
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = CallVisitor {
+        definition,
+        calls: Vec::new(),
+    };
+    visitor.visit_block(block);
+
+    visitor.calls
+}
+
+/// Emits a summarizing warning for each function that is called without assuring its preconditions.
+pub(crate) fn check_missing_assures(function: &ItemFn, lints: &LintLevels) {
+    let calls = find_unassured_calls(&function.block, metadata::callee_definition);
+
+    let mut grouped: Vec<(String, Vec<UnassuredCall>)> = Vec::new();
+    for call in calls {
+        metadata::export(Record::MissingAssure {
+            function: &call.function,
+            caller: &function.sig.ident,
+            call: &call.call,
+            generated: false,
+        });
+
+        match grouped.iter_mut().find(|(name, _)| *name == call.function) {
+            Some((_, calls)) => calls.push(call),
+            None => grouped.push((call.function.clone(), vec![call])),
+        }
+    }
+
+    for (name, calls) in grouped {
+        let message = if calls.len() == 1 {
+            format!("1 call site to `{}` needs `assure` attributes", name)
+        } else {
+            format!(
+                "{} call sites to `{}` need `assure` attributes",
+                calls.len(),
+                name
+            )
+        };
+
+        let mut diagnostic =
+            match lints.diagnostic(Lint::MissingAssureSummary, calls[0].span, message) {
+                Some(diagnostic) => diagnostic,
+                None => continue,
+            };
+
+        let definition = &calls[0].definition;
+        diagnostic = diagnostic.note(format!(
+            "`{}` has the following preconditions: {}",
+            name,
+            definition.preconditions.join(", ")
+        ));
+
+        if cfg!(nightly) && !definition.skeleton.is_empty() {
+            diagnostic = diagnostic.note(format!(
+                "add the following attributes to the calls and replace the reasons:\n{}",
+                definition.skeleton.join("\n")
+            ));
+        }
+
+        for call in calls.iter().skip(1).take(MAX_LISTED_CALL_SITES - 1) {
+            diagnostic = diagnostic.span_note(call.span, format!("`{}` is also called here", name));
+        }
+
+        for call in calls.iter().take(MAX_LISTED_CALL_SITES) {
+            if let Some(note) = concrete_preconditions_note(&name, definition, call) {
+                diagnostic = diagnostic.span_note(call.span, note);
+            }
+        }
+
+        if calls.len() > MAX_LISTED_CALL_SITES {
+            let unlisted = calls.len() - MAX_LISTED_CALL_SITES;
+
+            diagnostic = diagnostic.note(match metadata::export_path() {
+                Some(path) => format!(
+                    "{} more call sites are not listed here, the complete list is exported to `{}`",
+                    unlisted,
+                    path.display()
+                ),
+                None => format!(
+                    "{} more call sites are not listed here, set `{}` to export the complete list",
+                    unlisted, METADATA_DIR_VAR
+                ),
+            });
+        }
+
+        diagnostic.emit();
+    }
+}
+
+/// Describes the preconditions with the type parameters replaced by the generic arguments of the
+/// call.
+///
+/// Returns `None`, if the call does not specify generic arguments or if the preconditions don't
+/// refer to them.
+fn concrete_preconditions_note(
+    name: &str,
+    definition: &Definition,
+    call: &UnassuredCall,
+) -> Option<String> {
+    let substitutions = definition
+        .type_parameters
+        .iter()
+        .zip(&call.generic_args)
+        .collect::<Vec<_>>();
+
+    let preconditions = definition
+        .preconditions
+        .iter()
+        .map(|precondition| substitute_type_parameters(precondition, &substitutions))
+        .collect::<Vec<_>>();
+
+    if preconditions == definition.preconditions {
+        return None;
+    }
+
+    let substitutions = substitutions
+        .iter()
+        .map(|(parameter, arg)| format!("`{} = {}`", parameter, arg))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Some(format!(
+        "with {} here, `{}` has the following preconditions: {}",
+        substitutions,
+        name,
+        preconditions.join(", ")
+    ))
+}
+
+/// Replaces the type parameters in the text by the given arguments.
+///
+/// Only whole identifiers are replaced, so a type parameter `T` does not affect `To` or `t`.
+fn substitute_type_parameters(text: &str, substitutions: &[(&String, &String)]) -> String {
+    let mut substituted = String::new();
+    let mut identifier = String::new();
+
+    let flush = |identifier: &mut String, substituted: &mut String| {
+        match substitutions
+            .iter()
+            .find(|(parameter, _)| **parameter == *identifier)
+        {
+            Some((_, arg)) => substituted.push_str(arg),
+            None => substituted.push_str(identifier),
+        }
+        identifier.clear();
+    };
+
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            identifier.push(c);
+        } else {
+            flush(&mut identifier, &mut substituted);
+            substituted.push(c);
+        }
+    }
+    flush(&mut identifier, &mut substituted);
+
+    substituted
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_unassured_calls() {
+        let block: Block = parse2(quote! {
+            {
+                foo(1);
+                foo(1, (::pre::CustomCondition::<"is foo">,));
+                bar(foo(2));
+                path::to::foo(3);
+                baz();
+
+                fn nested() {
+                    foo(4);
+                }
+            }
+        })
+        .expect("valid block");
+
+        let calls = find_unassured_calls(&block, |path| match path.get_ident() {
+            Some(name) if name == "foo" => Some(Definition {
+                arity: 1,
+                parameters: vec!["x".into()],
+                parameter_types: Vec::new(),
+                type_parameters: Vec::new(),
+                preconditions: vec!["\"is foo\"".into()],
+                contract_id: "0123456789abcdef".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            }),
+            _ => None,
+        });
+
+        let calls = calls.iter().map(|call| &*call.call).collect::<Vec<_>>();
+        assert_eq!(calls, ["foo (1)", "foo (2)"]);
+    }
+
+    #[test]
+    fn substitutes_generic_args() {
+        let definition = Definition {
+            arity: 0,
+            parameters: Vec::new(),
+            parameter_types: Vec::new(),
+            type_parameters: vec!["T".into(), "N".into()],
+            preconditions: vec![
+                "\"an all-zero byte-pattern is a valid value of `T`\"".into(),
+                "N > 0".into(),
+                "\"To be or not to be\"".into(),
+            ],
+            contract_id: "0123456789abcdef".into(),
+            assures: None,
+            skeleton: Vec::new(),
+            precondition_arg_first: false,
+            is_unsafe: false,
+            module: Vec::new(),
+        };
+        let call = |generic_args: &[&str]| UnassuredCall {
+            function: "zeroed".into(),
+            span: Span::call_site(),
+            call: String::new(),
+            generic_args: generic_args.iter().map(|arg| arg.to_string()).collect(),
+            definition: definition.clone(),
+        };
+
+        assert_eq!(
+            concrete_preconditions_note("zeroed", &definition, &call(&["u32", "4"])).as_deref(),
+            Some(
+                "with `T = u32`, `N = 4` here, `zeroed` has the following preconditions: \
+                 \"an all-zero byte-pattern is a valid value of `u32`\", 4 > 0, \
+                 \"To be or not to be\""
+            )
+        );
+        assert_eq!(
+            concrete_preconditions_note("zeroed", &definition, &call(&[])),
+            None
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Warns about boolean preconditions of `#[pre(pure)]` functions that may have side effects.
+//!
+//! `#[pre(pure)]` promises that the boolean preconditions of a function can be evaluated any
+//! number of times, which is required for checking them more than once. This is a heuristic
+//! check of that promise: it only finds method calls whose names suggest that they mutate their
+//! receiver, assignments and mutable borrows. Calls to other methods or functions are assumed to
+//! be free of side effects.
+
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, Visit},
+    Expr, ExprMethodCall, ExprReference, Ident,
+};
+
+use crate::{
+    lints::{Lint, LintLevels},
+    precondition::{CfgPrecondition, Precondition},
+};
+
+/// The names of methods that usually mutate their receiver.
+const MUTATING_METHODS: &[&str] = &[
+    "append",
+    "borrow_mut",
+    "clear",
+    "drain",
+    "extend",
+    "fill",
+    "flush",
+    "get_mut",
+    "insert",
+    "lock",
+    "next",
+    "next_back",
+    "pop",
+    "push",
+    "recv",
+    "remove",
+    "replace",
+    "reserve",
+    "resize",
+    "retain",
+    "reverse",
+    "send",
+    "sort",
+    "sort_unstable",
+    "store",
+    "swap",
+    "swap_remove",
+    "take",
+    "truncate",
+    "try_lock",
+    "write",
+];
+
+/// The prefixes of the names of methods that usually mutate their receiver.
+const MUTATING_PREFIXES: &[&str] = &[
+    "compare_exchange",
+    "fetch_",
+    "pop_",
+    "push_",
+    "set_",
+    "write_",
+];
+
+/// Warns about the boolean preconditions that may have side effects.
+pub(crate) fn check_pure_conditions(preconditions: &[CfgPrecondition], lints: &LintLevels) {
+    for precondition in preconditions {
+        let expr = match precondition.precondition() {
+            Precondition::Boolean(expr) => expr,
+            _ => continue,
+        };
+
+        for (span, note) in side_effects(expr) {
+            if let Some(diagnostic) = lints.diagnostic(
+                Lint::ImpureCondition,
+                span,
+                format!(
+                    "the boolean precondition `{}` may have side effects",
+                    quote! { #expr }
+                ),
+            ) {
+                diagnostic
+                    .note(note)
+                    .help("`#[pre(pure)]` promises that the boolean preconditions can be evaluated more than once".into())
+                    .emit();
+            }
+        }
+    }
+}
+
+/// Finds the parts of the expression that may have side effects.
+///
+/// Returns the span of each part together with a note explaining why it may have side effects.
+fn side_effects(expr: &Expr) -> Vec<(Span, String)> {
+    /// Searches the expression for parts that may have side effects.
+    #[derive(Default)]
+    struct SideEffectVisitor {
+        /// The parts found so far.
+        side_effects: Vec<(Span, String)>,
+    }
+
+    impl<'ast> Visit<'ast> for SideEffectVisitor {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            match expr {
+                Expr::MethodCall(ExprMethodCall { method, .. }) if is_mutating(method) => {
+                    self.side_effects.push((
+                        method.span(),
+                        format!("`{}` usually mutates the value it is called on", method),
+                    ))
+                }
+                Expr::Assign(_) | Expr::AssignOp(_) => self
+                    .side_effects
+                    .push((expr.span(), "assignments change the assigned value".into())),
+                Expr::Reference(ExprReference {
+                    mutability: Some(_),
+                    ..
+                }) => self.side_effects.push((
+                    expr.span(),
+                    "mutable borrows allow changing the borrowed value".into(),
+                )),
+                _ => (),
+            }
+
+            visit_expr(self, expr);
+        }
+    }
+
+    let mut visitor = SideEffectVisitor::default();
+    visitor.visit_expr(expr);
+
+    visitor.side_effects
+}
+
+/// Checks whether the name of the method suggests that it mutates its receiver.
+fn is_mutating(method: &Ident) -> bool {
+    let method = method.to_string();
+
+    MUTATING_METHODS.contains(&&*method)
+        || MUTATING_PREFIXES
+            .iter()
+            .any(|prefix| method.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    /// Returns the notes of the side effects found in the expression.
+    fn notes(expr: Expr) -> Vec<String> {
+        side_effects(&expr)
+            .into_iter()
+            .map(|(_, note)| note)
+            .collect()
+    }
+
+    #[test]
+    fn finds_side_effects() {
+        assert!(notes(parse_quote! { x.len() > 0 && !p.is_null() }).is_empty());
+        assert!(notes(parse_quote! { v.iter().all(|x| x.is_positive()) }).is_empty());
+        assert!(notes(parse_quote! { c.get() == 1 && a.load(Ordering::SeqCst) == 0 }).is_empty());
+
+        assert_eq!(
+            notes(parse_quote! { v.pop().is_some() }),
+            vec!["`pop` usually mutates the value it is called on"]
+        );
+        assert_eq!(
+            notes(parse_quote! { a.fetch_add(1, Ordering::SeqCst) == 0 }),
+            vec!["`fetch_add` usually mutates the value it is called on"]
+        );
+        assert_eq!(
+            notes(parse_quote! { { x += 1; x } > 0 }),
+            vec!["assignments change the assigned value"]
+        );
+        assert_eq!(
+            notes(parse_quote! { check(&mut state) }),
+            vec!["mutable borrows allow changing the borrowed value"]
+        );
+        assert_eq!(notes(parse_quote! { iter.next().is_none() }).len(), 1);
+    }
+}
@@ -0,0 +1,354 @@
+//! Handles `same_as` in `assure` attributes.
+//!
+//! A `same_as` precondition assures all preconditions of another function at once, which is useful
+//! for thin wrappers whose contract is the same as the contract of the function they wrap:
+//!
+//! ```rust,ignore
+//! #[assure(same_as(old_api::foo), reason = "the contract did not change")]
+//! new_api::foo(ptr);
+//! ```
+//!
+//! turns into
+//!
+//! ```rust,ignore
+//! #[assure(valid_ptr(ptr, r), reason = "the contract did not change")]
+//! #[assure(ptr.is_aligned(), reason = "the contract did not change")]
+//! new_api::foo(ptr);
+//! ```
+//!
+//! if `old_api::foo` has these two preconditions, which is then handled like any other `assure`
+//! attribute. The preconditions are looked up when the attribute is expanded, so only functions
+//! defined earlier in the same crate can be named. If multiple such functions match the path, an
+//! error is reported instead of picking one of them. Exported and imported conditions are assured by
+//! importing them relative to the module of the named function. Token preconditions are not
+//! copied, because their value is unknown.
+
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use proc_macro_error::emit_error;
+use quote::{quote, quote_spanned};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream, Parser},
+    spanned::Spanned,
+    token::Paren,
+    visit_mut::{visit_expr_mut, visit_local_mut, VisitMut},
+    Attribute, Expr, Ident, Item, ItemFn, Local, Macro, Path, Token,
+};
+
+use super::macro_handling::visit_recognized_macro;
+use crate::{
+    helpers::{attributes_of_expression, visit_matching_attrs_parsed_mut, Attr, AttributeAction},
+    metadata::{self, Resolution},
+    precondition::Precondition,
+};
+
+/// The custom keywords used in `same_as` preconditions.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(same_as);
+}
+
+/// The content of an `assure` attribute, distinguishing `same_as` preconditions from all others.
+enum AssureContent {
+    /// The preconditions of another function are assured, such as in
+    /// `#[assure(same_as(old_api::foo), reason = "...")]`.
+    SameAs {
+        /// The `same_as` keyword.
+        same_as_keyword: custom_keywords::same_as,
+        /// The parentheses following the `same_as` keyword.
+        parentheses: Paren,
+        /// The function whose preconditions are assured.
+        function: Path,
+        /// The rest of the attribute, such as the reason.
+        rest: TokenStream,
+    },
+    /// Any other precondition is assured.
+    Other(TokenStream),
+}
+
+impl Parse for AssureContent {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Boolean preconditions calling a function named `same_as` are left alone.
+        if input.peek(custom_keywords::same_as) && input.peek2(Paren) {
+            if let Ok(same_as) = parse_same_as(&input.fork()) {
+                input.parse::<TokenStream>()?;
+
+                return Ok(same_as);
+            }
+        }
+
+        Ok(AssureContent::Other(input.parse()?))
+    }
+}
+
+/// Parses a `same_as` precondition followed by the rest of the attribute.
+fn parse_same_as(input: ParseStream) -> syn::Result<AssureContent> {
+    let same_as_keyword = input.parse()?;
+    let content;
+    let parentheses = parenthesized!(content in input);
+    let function = content.call(Path::parse_mod_style)?;
+
+    if !content.is_empty() {
+        return Err(content.error("unexpected token"));
+    }
+    if !(input.is_empty() || input.peek(Token![,])) {
+        return Err(input.error("expected `,`"));
+    }
+
+    Ok(AssureContent::SameAs {
+        same_as_keyword,
+        parentheses,
+        function,
+        rest: input.parse()?,
+    })
+}
+
+impl Spanned for AssureContent {
+    fn span(&self) -> Span {
+        match self {
+            AssureContent::SameAs {
+                same_as_keyword,
+                parentheses,
+                ..
+            } => same_as_keyword
+                .span
+                .join(parentheses.span)
+                .unwrap_or(same_as_keyword.span),
+            AssureContent::Other(tokens) => tokens.span(),
+        }
+    }
+}
+
+/// Replaces the `same_as` preconditions in the `assure` attributes of the function.
+pub(crate) fn expand_same_as(function: &mut ItemFn) {
+    SameAsVisitor.visit_block_mut(&mut function.block);
+}
+
+/// Searches for `assure` attributes with `same_as` preconditions and expands them.
+struct SameAsVisitor;
+
+impl VisitMut for SameAsVisitor {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(attrs) = attributes_of_expression(expr) {
+            expand_attrs(attrs);
+        }
+
+        visit_expr_mut(self, expr);
+    }
+
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        expand_attrs(&mut local.attrs);
+
+        visit_local_mut(self, local);
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        visit_recognized_macro(mac, |expr| self.visit_expr_mut(expr));
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {
+        // Nested items are handled on their own.
+    }
+}
+
+/// Replaces each `assure` attribute with a `same_as` precondition by one `assure` attribute for
+/// every precondition of the named function.
+fn expand_attrs(attrs: &mut Vec<Attribute>) {
+    let mut expanded = Vec::new();
+
+    visit_matching_attrs_parsed_mut(attrs, "assure", |attr: Attr<AssureContent>| {
+        let span = attr.span();
+        let (content, cfg, _) = attr.into_content();
+
+        let (function, rest) = match content {
+            AssureContent::SameAs { function, rest, .. } => (function, rest),
+            AssureContent::Other(_) => return AttributeAction::Keep,
+        };
+
+        let function_str = quote! { #function }.to_string().replace(' ', "");
+        let definition = match metadata::resolve(&function) {
+            Resolution::Resolved(definition) => definition,
+            Resolution::Unknown => {
+                emit_error!(
+                    function,
+                    "the preconditions of `{}` are unknown", function_str;
+                    help = "`same_as` can only name functions with preconditions that are defined earlier in the same crate"
+                );
+
+                return AttributeAction::Remove;
+            }
+            Resolution::Ambiguous => {
+                emit_error!(
+                    function,
+                    "`{}` may refer to multiple functions with preconditions", function_str;
+                    help = "apply `#[pre]` to the modules containing the functions and name the module in the path, so that the function can be told apart from other functions with the same name"
+                );
+
+                return AttributeAction::Remove;
+            }
+        };
+
+        for precondition in &definition.preconditions {
+            let precondition = match copied_precondition(precondition, &function) {
+                Some(precondition) => respan(precondition, span),
+                None => continue,
+            };
+
+            let assure_attr = match &cfg {
+                Some(cfg) => {
+                    quote_spanned! { span=> #[cfg_attr(#cfg, assure(#precondition #rest))] }
+                }
+                None => quote_spanned! { span=> #[assure(#precondition #rest)] },
+            };
+            expanded.extend(
+                Attribute::parse_outer
+                    .parse2(assure_attr)
+                    .expect("assure attributes are valid"),
+            );
+        }
+
+        AttributeAction::Remove
+    });
+
+    attrs.extend(expanded);
+}
+
+/// Returns the tokens assuring the precondition of the given function at the call site.
+///
+/// Returns `None` for token preconditions, because their value is unknown.
+fn copied_precondition(precondition: &str, function: &Path) -> Option<TokenStream> {
+    let module = function
+        .segments
+        .iter()
+        .take(function.segments.len().saturating_sub(1))
+        .map(|segment| segment.ident.clone())
+        .collect::<Vec<_>>();
+
+    match syn::parse_str::<Precondition>(precondition).ok()? {
+        Precondition::Token { .. } => None,
+        Precondition::Exported { ident, .. } => Some(quote! { use #(#module::)* #ident }),
+        Precondition::Imported { path, .. } => {
+            let path = resolve_path(&module, &path);
+
+            Some(quote! { use #path })
+        }
+        _ => precondition.parse().ok(),
+    }
+}
+
+/// Resolves a path that is relative to the given module, so that it can be used where the module
+/// is reachable under that name.
+///
+/// Absolute paths and paths that leave the known part of the module path are returned unchanged.
+fn resolve_path(module: &[Ident], path: &Path) -> TokenStream {
+    let mut module = module.to_vec();
+    let mut segments = path.segments.iter().peekable();
+
+    if path.leading_colon.is_some() {
+        return quote! { #path };
+    }
+
+    while let Some(segment) = segments.peek() {
+        if segment.ident == "self" || (segment.ident == "super" && module.pop().is_some()) {
+            segments.next();
+        } else if segment.ident == "super" || segment.ident == "crate" {
+            return quote! { #path };
+        } else {
+            break;
+        }
+    }
+
+    quote! { #(#module::)* #(#segments)::* }
+}
+
+/// Sets the span of all tokens to the given span.
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut tree| {
+            if let TokenTree::Group(group) = &tree {
+                let mut new_group = Group::new(group.delimiter(), respan(group.stream(), span));
+                new_group.set_span(span);
+
+                tree = TokenTree::Group(new_group);
+            } else {
+                tree.set_span(span);
+            }
+
+            tree
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn parse_same_as() {
+        let content: AssureContent =
+            parse2(quote! { same_as(old_api::foo), reason = "same contract" })
+                .expect("valid attribute");
+        match content {
+            AssureContent::SameAs { function, rest, .. } => {
+                assert_eq!(quote! { #function }.to_string(), "old_api :: foo");
+                assert_eq!(
+                    rest.to_string(),
+                    quote! { , reason = "same contract" }.to_string()
+                );
+            }
+            AssureContent::Other(_) => panic!("expected `same_as`"),
+        }
+
+        assert!(matches!(
+            parse2(quote! { same_as, reason = "valid" }),
+            Ok(AssureContent::Other(_))
+        ));
+        assert!(matches!(
+            parse2(quote! { same_as(a) && b, reason = "valid" }),
+            Ok(AssureContent::Other(_))
+        ));
+        assert!(matches!(
+            parse2(quote! { same_as(a, b), reason = "valid" }),
+            Ok(AssureContent::Other(_))
+        ));
+    }
+
+    #[test]
+    fn copies_preconditions() {
+        let function: Path = parse2(quote! { old_api::v1::foo }).expect("valid path");
+        let copy = |precondition| {
+            copied_precondition(precondition, &function).map(|tokens| tokens.to_string())
+        };
+
+        assert_eq!(
+            copy("valid_ptr(ptr, r)"),
+            Some(quote! { valid_ptr(ptr, r) }.to_string())
+        );
+        assert_eq!(copy("\"is valid\""), Some("\"is valid\"".into()));
+        assert_eq!(
+            copy("export = COND_INIT, \"is initialized\""),
+            Some(quote! { use old_api::v1::COND_INIT }.to_string())
+        );
+        assert_eq!(
+            copy("use super::COND_INIT"),
+            Some(quote! { use old_api::COND_INIT }.to_string())
+        );
+        assert_eq!(
+            copy("use self::conditions::COND_INIT"),
+            Some(quote! { use old_api::v1::conditions::COND_INIT }.to_string())
+        );
+        assert_eq!(
+            copy("use crate::COND_INIT"),
+            Some(quote! { use crate::COND_INIT }.to_string())
+        );
+        assert_eq!(
+            copy("use super::super::super::COND_INIT"),
+            Some(quote! { use super::super::super::COND_INIT }.to_string())
+        );
+        assert_eq!(copy("token(Init)"), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! Handles the `#[pre(assert_after_setup)]` marker inside of function bodies.
+//!
+//! By default the checks of the preconditions are placed at the very start of the function. The
+//! marker moves them after the statement it is attached to, so that they can refer to bindings
+//! created by the setup code before it.
+
+use proc_macro2::Span;
+use proc_macro_error::emit_error;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse2,
+    visit_mut::{visit_expr_mut, visit_local_mut, VisitMut},
+    Attribute, Expr, Item, ItemFn, Local, Stmt,
+};
+
+use super::custom_keywords;
+use crate::helpers::{
+    attributes_of_expression, is_matching_attr, visit_matching_attrs_parsed_mut, AttributeAction,
+};
+
+/// Removes the setup marker from the body of the function.
+///
+/// Returns the number of statements following the marked statement, if there is a marker.
+pub(crate) fn take_setup_marker(function: &mut ItemFn) -> Option<usize> {
+    let stmt_count = function.block.stmts.len();
+    let mut marked: Option<(usize, Span)> = None;
+
+    for (i, stmt) in function.block.stmts.iter_mut().enumerate() {
+        let attrs = match stmt {
+            Stmt::Local(local) => &mut local.attrs,
+            Stmt::Expr(expr) | Stmt::Semi(expr, _) => match attributes_of_expression(expr) {
+                Some(attrs) => attrs,
+                None => continue,
+            },
+            // `pre` attributes on items are handled when visiting the item.
+            Stmt::Item(_) => continue,
+        };
+
+        if !attrs.iter().any(|attr| setup_marker(attr).is_some()) {
+            continue;
+        }
+
+        visit_matching_attrs_parsed_mut(attrs, "pre", |attr| {
+            let keyword: &custom_keywords::assert_after_setup = attr.content();
+
+            if let Some((_, first_span)) = marked {
+                emit_error!(
+                    keyword.span,
+                    "duplicate `assert_after_setup` marker";
+                    help = first_span => "the checks can only be placed at one location, try removing one of the markers"
+                );
+            } else {
+                marked = Some((i, keyword.span));
+            }
+
+            AttributeAction::Remove
+        });
+    }
+
+    for stmt in &mut function.block.stmts {
+        match stmt {
+            Stmt::Local(local) => visit_local_mut(&mut NestedMarkerVisitor, local),
+            Stmt::Expr(expr) | Stmt::Semi(expr, _) => {
+                visit_expr_mut(&mut NestedMarkerVisitor, expr)
+            }
+            Stmt::Item(_) => (),
+        }
+    }
+
+    marked.map(|(i, _)| stmt_count - i - 1)
+}
+
+/// Returns the keyword of the attribute, if it is a `#[pre(assert_after_setup)]` marker.
+fn setup_marker(attr: &Attribute) -> Option<custom_keywords::assert_after_setup> {
+    if !is_matching_attr("pre", attr) {
+        return None;
+    }
+
+    parse2::<MarkerTokens>(attr.tokens.clone())
+        .ok()
+        .map(|tokens| tokens.keyword)
+}
+
+/// The tokens of a setup marker attribute after its path: `(assert_after_setup)`.
+struct MarkerTokens {
+    /// The `assert_after_setup` keyword.
+    keyword: custom_keywords::assert_after_setup,
+}
+
+impl Parse for MarkerTokens {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+
+        Ok(MarkerTokens {
+            keyword: content.parse()?,
+        })
+    }
+}
+
+/// Reports and removes setup markers that are not directly in the function body.
+struct NestedMarkerVisitor;
+
+impl NestedMarkerVisitor {
+    /// Reports and removes the setup markers in the given attributes.
+    fn remove_markers(&self, attrs: &mut Vec<Attribute>) {
+        attrs.retain(|attr| {
+            let keyword = match setup_marker(attr) {
+                Some(keyword) => keyword,
+                None => return true,
+            };
+
+            emit_error!(
+                keyword.span,
+                "`assert_after_setup` can only mark statements directly in the function body";
+                help = "try moving the marker to the statement containing this one"
+            );
+
+            false
+        });
+    }
+}
+
+impl VisitMut for NestedMarkerVisitor {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(attrs) = attributes_of_expression(expr) {
+            self.remove_markers(attrs);
+        }
+
+        visit_expr_mut(self, expr);
+    }
+
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        self.remove_markers(&mut local.attrs);
+
+        visit_local_mut(self, local);
+    }
+
+    // Markers in nested functions belong to those functions.
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn statements_after_marker() {
+        let mut function: ItemFn = parse2(quote! {
+            fn foo(x: u32) -> u32 {
+                let y = x + 1;
+                #[pre(assert_after_setup)]
+                let z = y * 2;
+                #[pre::pre(some_precondition)]
+                fn nested() {}
+                z
+            }
+        })
+        .expect("valid function");
+
+        assert_eq!(take_setup_marker(&mut function), Some(2));
+        assert!(matches!(&function.block.stmts[1], Stmt::Local(local) if local.attrs.is_empty()));
+        assert!(matches!(&function.block.stmts[2], Stmt::Item(Item::Fn(f)) if f.attrs.len() == 1));
+
+        let mut function: ItemFn = parse2(quote! {
+            fn foo(x: u32) -> u32 {
+                #[pre(x > 0)]
+                let y = x;
+                y
+            }
+        })
+        .expect("valid function");
+        assert_eq!(take_setup_marker(&mut function), None);
+    }
+}
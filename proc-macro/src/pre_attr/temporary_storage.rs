@@ -0,0 +1,382 @@
+//! Warns about arguments for `lives_until_end_of_scope` and pointer validity preconditions that are
+//! obtained from temporary values.
+//!
+//! A temporary value is dropped at the end of the statement that created it, so a pointer into it,
+//! such as in `CStr::from_ptr(CString::new("x").unwrap().as_ptr())`, dangles afterwards. This check
+//! is best-effort and purely syntactical: only arguments that are method calls on a temporary
+//! value, such as the result of another call, are recognized. For pointer validity preconditions,
+//! only `as_ptr` and `as_mut_ptr` calls are recognized, because other methods may return pointers
+//! that do not point into the temporary value.
+
+use proc_macro2::Span;
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Expr, ExprCall, ExprCast, ExprMethodCall, ExprParen, ExprPath, ExprReference, Ident,
+    Item, ItemFn, Local,
+};
+
+use crate::{
+    call_handling::AssureAttr,
+    helpers::{attributes_of_expression_ref, visit_matching_attrs_parsed, Attr},
+    lints::{Lint, LintLevels},
+    metadata::{self, Definition},
+    precondition::Precondition,
+};
+
+/// The methods returning a pointer into their receiver.
+const POINTER_METHODS: [&str; 2] = ["as_ptr", "as_mut_ptr"];
+
+/// The methods returning a reference into their receiver.
+///
+/// The result of these methods does not own its value, so the storage of a pointer obtained from
+/// it lives in their receiver.
+const BORROWING_METHODS: [&str; 10] = [
+    "as_bytes",
+    "as_bytes_with_nul",
+    "as_c_str",
+    "as_mut",
+    "as_mut_slice",
+    "as_ref",
+    "as_slice",
+    "as_str",
+    "borrow",
+    "borrow_mut",
+];
+
+/// An argument for a precondition that is obtained from a temporary.
+struct TemporaryStorage {
+    /// The name of the parameter.
+    parameter: String,
+    /// The span of the argument.
+    argument_span: Span,
+    /// The span of the temporary value.
+    temporary_span: Span,
+    /// The method returning a pointer into the temporary value, if the argument is such a pointer.
+    pointer_method: Option<String>,
+    /// The name of the type of the temporary value, if it is known.
+    type_name: Option<String>,
+}
+
+/// Finds the arguments for `lives_until_end_of_scope` and pointer validity preconditions obtained
+/// from temporaries in the function.
+fn find_temporary_storage(
+    function: &ItemFn,
+    definition: impl Fn(&str) -> Option<Definition>,
+) -> Vec<TemporaryStorage> {
+    /// Searches for `assure` attributes with preconditions that the argument must outlive the
+    /// statement for.
+    struct AttrVisitor<F> {
+        /// Looks up the definition of a function by its name.
+        definition: F,
+        /// The arguments found so far.
+        found: Vec<TemporaryStorage>,
+    }
+
+    impl<F: Fn(&str) -> Option<Definition>> AttrVisitor<F> {
+        /// Checks the arguments of the call in the expression according to the attributes.
+        fn check_attrs(&mut self, attrs: &[Attribute], expr: &Expr) {
+            // The parameters and whether they must live until the end of the scope.
+            let mut parameters: Vec<(String, bool)> = Vec::new();
+
+            visit_matching_attrs_parsed(attrs, "assure", |attr: Attr<AssureAttr>| {
+                let (precondition, _, _) = attr.into_content();
+                let (ident, until_end_of_scope) = match precondition.into() {
+                    Precondition::LivesUntilEndOfScope { ident, .. } => (ident, true),
+                    Precondition::ValidPtr { ident, .. }
+                    | Precondition::NulTerminated { ident, .. }
+                    | Precondition::ValidCStr { ident, .. }
+                    | Precondition::Initialized { ident, .. } => (ident, false),
+                    _ => return,
+                };
+                let parameter = ident.to_string();
+
+                match parameters.iter_mut().find(|(name, _)| *name == parameter) {
+                    Some((_, scope)) => *scope |= until_end_of_scope,
+                    None => parameters.push((parameter, until_end_of_scope)),
+                }
+            });
+
+            for (parameter, until_end_of_scope) in parameters {
+                let argument = match argument(expr, &parameter, &self.definition) {
+                    Some(argument) => argument,
+                    None => continue,
+                };
+                let (method, temporary) = match temporary_receiver(argument) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                let pointer_method = Some(method.to_string())
+                    .filter(|method| POINTER_METHODS.contains(&method.as_str()));
+
+                if pointer_method.is_some() || until_end_of_scope {
+                    self.found.push(TemporaryStorage {
+                        parameter,
+                        argument_span: argument.span(),
+                        temporary_span: temporary.span(),
+                        pointer_method,
+                        type_name: constructed_type(temporary),
+                    });
+                }
+            }
+        }
+    }
+
+    impl<'ast, F: Fn(&str) -> Option<Definition>> Visit<'ast> for AttrVisitor<F> {
+        fn visit_local(&mut self, local: &'ast Local) {
+            if let Some((_, init)) = &local.init {
+                self.check_attrs(&local.attrs, init);
+            }
+
+            visit_local(self, local);
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Some(attrs) = attributes_of_expression_ref(expr) {
+                self.check_attrs(attrs, expr);
+            }
+
+            visit_expr(self, expr);
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = AttrVisitor {
+        definition,
+        found: Vec::new(),
+    };
+    visitor.visit_block(&function.block);
+
+    visitor.found
+}
+
+/// Returns the argument passed for the parameter to the outermost call in the expression.
+fn argument<'a>(
+    expr: &'a Expr,
+    parameter: &str,
+    definition: &impl Fn(&str) -> Option<Definition>,
+) -> Option<&'a Expr> {
+    /// Finds the outermost call in an expression.
+    #[derive(Default)]
+    struct CallVisitor<'ast> {
+        /// The outermost call found.
+        call: Option<&'ast Expr>,
+    }
+
+    impl<'ast> Visit<'ast> for CallVisitor<'ast> {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if self.call.is_some() {
+                return;
+            }
+
+            if let Expr::Call(_) | Expr::MethodCall(_) = expr {
+                self.call = Some(expr);
+            } else {
+                visit_expr(self, expr);
+            }
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = CallVisitor::default();
+    visitor.visit_expr(expr);
+
+    let (name, args) = match visitor.call? {
+        Expr::Call(ExprCall { func, args, .. }) => match &**func {
+            Expr::Path(path) => (path.path.segments.last()?.ident.to_string(), args),
+            _ => return None,
+        },
+        Expr::MethodCall(ExprMethodCall { method, args, .. }) => (method.to_string(), args),
+        _ => return None,
+    };
+
+    match definition(&name).filter(|definition| definition.arity == args.len()) {
+        // Use the argument at the position of the parameter, if the definition is known.
+        Some(definition) => definition
+            .parameters
+            .iter()
+            .position(|name| name == parameter)
+            .map(|i| &args[i]),
+        // Otherwise only a single argument must be the one for the parameter.
+        None if args.len() == 1 => Some(&args[0]),
+        None => None,
+    }
+}
+
+/// Returns the method called on a temporary value and the temporary value that the argument is
+/// obtained from, if any.
+///
+/// This is the case for method calls, such as `as_ptr`, on the result of a call or a macro.
+fn temporary_receiver(argument: &Expr) -> Option<(&Ident, &Expr)> {
+    match argument {
+        Expr::Paren(ExprParen { expr, .. })
+        | Expr::Cast(ExprCast { expr, .. })
+        | Expr::Reference(ExprReference { expr, .. }) => temporary_receiver(expr),
+        Expr::MethodCall(ExprMethodCall {
+            receiver, method, ..
+        }) => owning_temporary(receiver).map(|temporary| (method, temporary)),
+        _ => None,
+    }
+}
+
+/// Returns the temporary value that owns the storage of the expression, if there is one.
+///
+/// Borrowing methods such as `as_slice` are skipped, so that only the results of calls, macros and
+/// struct expressions that own their value are considered temporary.
+fn owning_temporary(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Paren(ExprParen { expr, .. }) => owning_temporary(expr),
+        Expr::MethodCall(ExprMethodCall {
+            receiver, method, ..
+        }) if BORROWING_METHODS.iter().any(|name| method == name) => owning_temporary(receiver),
+        Expr::Call(_) | Expr::MethodCall(_) | Expr::Macro(_) | Expr::Struct(_) => Some(expr),
+        _ => None,
+    }
+}
+
+/// Returns the name of the type of the temporary value, if it is created by a constructor such as
+/// `CString::new(..)`, possibly followed by `unwrap` or `expect`.
+fn constructed_type(temporary: &Expr) -> Option<String> {
+    match temporary {
+        Expr::MethodCall(ExprMethodCall {
+            receiver, method, ..
+        }) if method == "unwrap" || method == "expect" => constructed_type(receiver),
+        Expr::Call(ExprCall { func, .. }) => match &**func {
+            Expr::Path(ExprPath {
+                qself: None, path, ..
+            }) if path.segments.len() >= 2 => {
+                let segment = path.segments.iter().rev().nth(1)?;
+
+                Some(segment.ident.to_string())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Emits a warning for each argument for a `lives_until_end_of_scope` or pointer validity
+/// precondition that is obtained from a temporary in the function.
+pub(crate) fn check_temporary_storage(function: &ItemFn, lints: &LintLevels) {
+    for found in find_temporary_storage(function, metadata::definition) {
+        let temporary = match &found.type_name {
+            Some(type_name) => format!("temporary `{}`", type_name),
+            None => "temporary value".to_string(),
+        };
+        let message = match &found.pointer_method {
+            Some(method) => format!(
+                "`{}` dangles, because `{}` returns a pointer into a {}",
+                found.parameter, method, temporary
+            ),
+            None => format!(
+                "`{}` must live until the end of the scope, but refers to a {}",
+                found.parameter, temporary
+            ),
+        };
+
+        if let Some(diagnostic) =
+            lints.diagnostic(Lint::TemporaryStorage, found.argument_span, message)
+        {
+            diagnostic
+                .span_note(
+                    found.temporary_span,
+                    format!("this {} is dropped at the end of the statement", temporary),
+                )
+                .help("bind the temporary value to a variable before the call".into())
+                .emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_temporary_storage() {
+        let function: ItemFn = parse2(quote! {
+            fn f() {
+                #[assure(lives_until_end_of_scope(ptr), reason = "lives long enough")]
+                let a = unsafe { from_ptr(CString::new("a").unwrap().as_ptr()) };
+
+                let owned = CString::new("b").unwrap();
+                #[assure(lives_until_end_of_scope(ptr), reason = "lives long enough")]
+                let b = unsafe { from_ptr(owned.as_ptr()) };
+
+                #[assure(lives_until_end_of_scope(ptr), reason = "lives long enough")]
+                let c = unsafe { from_ptr_len(5, (format!("c").as_ptr()) as *const i8) };
+
+                #[assure(lives_until_end_of_scope(ptr), reason = "lives long enough")]
+                let d = unsafe { unknown(vec![1].as_ptr(), 1) };
+
+                #[assure(lives_until_end_of_scope(ptr), reason = "lives long enough")]
+                let e = unsafe { unknown(make().as_ptr()) };
+
+                #[assure(valid_cstr(ptr), reason = "is a C string")]
+                let f = unsafe { from_ptr(CString::new("f").expect("no nul").as_ptr()) };
+
+                #[assure(valid_ptr(ptr, r), reason = "is valid")]
+                let g = unsafe { from_ptr(make().into_raw()) };
+
+                #[assure(valid_ptr(ptr, r), reason = "is valid")]
+                #[assure(lives_until_end_of_scope(ptr), reason = "lives long enough")]
+                let h = unsafe { from_ptr(make().as_mut_ptr()) };
+
+                let v = vec![1];
+                #[assure(valid_ptr(ptr, r), reason = "is valid")]
+                let i = unsafe { from_ptr(v.as_slice().as_ptr()) };
+
+                #[assure(valid_ptr(ptr, r), reason = "is valid")]
+                let j = unsafe { from_ptr(CString::new("j").unwrap().as_bytes().as_ptr()) };
+            }
+        })
+        .expect("valid function");
+
+        let found = find_temporary_storage(&function, |name| {
+            let parameters = match name {
+                "from_ptr" => vec!["ptr".into()],
+                "from_ptr_len" => vec!["len".into(), "ptr".into()],
+                _ => return None,
+            };
+
+            Some(Definition {
+                arity: parameters.len(),
+                parameters,
+                parameter_types: Vec::new(),
+                type_parameters: Vec::new(),
+                preconditions: vec!["lives_until_end_of_scope(ptr)".into()],
+                contract_id: "0123456789abcdef".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            })
+        });
+
+        let found = found
+            .iter()
+            .map(|found| (found.pointer_method.as_deref(), found.type_name.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                (Some("as_ptr"), Some("CString")),
+                (Some("as_ptr"), None),
+                (Some("as_ptr"), None),
+                (Some("as_ptr"), Some("CString")),
+                (Some("as_mut_ptr"), None),
+                (Some("as_ptr"), Some("CString")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Handles scopes marked with `#[pre(trusted)]`.
+//!
+//! Code generated by other tools, such as bindgen or derive macros, cannot contain `assure`
+//! attributes. Calls to functions with preconditions in such a scope are assured automatically
+//! and a warning is emitted for each of them instead of the compiler reporting an error.
+//!
+//! Only calls to functions defined in the current crate, whose definitions were already expanded,
+//! can be recognized. Calls to functions with token preconditions are never assured
+//! automatically, because the token value is unknown.
+
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse::Parser,
+    spanned::Spanned,
+    visit_mut::{visit_expr_call_mut, visit_expr_mut, visit_local_mut, VisitMut},
+    Attribute, Block, Expr, ExprCall, Item, ItemFn, Local,
+};
+
+use super::PreAttr;
+use crate::{
+    helpers::{
+        attributes_of_expression, is_matching_attr, visit_matching_attrs_parsed_mut, Attr,
+        AttributeAction,
+    },
+    lints::{Lint, LintLevels},
+    metadata::{self, Definition, Record},
+};
+
+/// The reason used for automatically assured preconditions.
+pub(crate) const TRUSTED_REASON: &str = "the call is in a `#[pre(trusted)]` scope";
+
+/// A call that was assured automatically, because it is in a trusted scope.
+struct TrustedCall {
+    /// The name of the called function.
+    function: String,
+    /// The span of the call.
+    span: Span,
+    /// The call expression.
+    call: String,
+    /// The preconditions that were assured.
+    preconditions: Vec<String>,
+}
+
+/// Adds `assure` attributes to the calls in trusted scopes of the block that are missing them.
+fn assure_trusted_calls_in(
+    block: &mut Block,
+    trusted: bool,
+    definition: impl Fn(&str) -> Option<Definition>,
+) -> Vec<TrustedCall> {
+    /// Searches for calls with a matching definition, but no precondition argument.
+    struct TrustedVisitor<F> {
+        /// Whether the currently visited code is in a trusted scope.
+        trusted: bool,
+        /// Looks up the definition of a function by its name.
+        definition: F,
+        /// The calls assured so far.
+        calls: Vec<TrustedCall>,
+    }
+
+    impl<F: Fn(&str) -> Option<Definition>> TrustedVisitor<F> {
+        /// Visits code, treating it as trusted if it is marked as such.
+        fn visit_trusted(&mut self, trusted: bool, visit: impl FnOnce(&mut Self)) {
+            let outer_trusted = self.trusted;
+            self.trusted |= trusted;
+
+            visit(self);
+
+            self.trusted = outer_trusted;
+        }
+    }
+
+    impl<F: Fn(&str) -> Option<Definition>> VisitMut for TrustedVisitor<F> {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            let trusted = match attributes_of_expression(expr) {
+                Some(attrs) => remove_trusted_attr(attrs),
+                None => false,
+            };
+
+            self.visit_trusted(trusted, |this| visit_expr_mut(this, expr));
+        }
+
+        fn visit_local_mut(&mut self, local: &mut Local) {
+            let trusted = remove_trusted_attr(&mut local.attrs);
+
+            self.visit_trusted(trusted, |this| visit_local_mut(this, local));
+        }
+
+        fn visit_expr_call_mut(&mut self, call: &mut ExprCall) {
+            visit_expr_call_mut(self, call);
+
+            if !self.trusted
+                || call.attrs.iter().any(|attr| {
+                    is_matching_attr("assure", attr) || is_matching_attr("forward", attr)
+                })
+            {
+                return;
+            }
+
+            if let Expr::Path(path) = &*call.func {
+                if let Some(segment) = path.path.segments.last() {
+                    let function = segment.ident.to_string();
+
+                    // Calls with assured preconditions have one additional argument.
+                    if let Some(definition) = (self.definition)(&function) {
+                        if let (true, Some(assures)) =
+                            (definition.arity == call.args.len(), &definition.assures)
+                        {
+                            let span = call.span();
+                            let call_string = quote! { #call }.to_string();
+
+                            for assure in assures {
+                                call.attrs.extend(
+                                    Attribute::parse_outer
+                                        .parse_str(assure)
+                                        .expect("assure attributes are valid"),
+                                );
+                            }
+
+                            self.calls.push(TrustedCall {
+                                function,
+                                span,
+                                call: call_string,
+                                preconditions: definition.preconditions,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        fn visit_item_mut(&mut self, _: &mut Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = TrustedVisitor {
+        trusted,
+        definition,
+        calls: Vec::new(),
+    };
+    visitor.visit_block_mut(block);
+
+    visitor.calls
+}
+
+/// Removes all `#[pre(trusted)]` attributes and returns whether there were any.
+fn remove_trusted_attr(attrs: &mut Vec<Attribute>) -> bool {
+    let mut trusted = false;
+
+    visit_matching_attrs_parsed_mut(attrs, "pre", |attr: Attr<PreAttr>| match attr.content() {
+        PreAttr::Trusted(_) => {
+            trusted = true;
+
+            AttributeAction::Remove
+        }
+        _ => AttributeAction::Keep,
+    });
+
+    trusted
+}
+
+/// Assures the preconditions of calls in trusted scopes of the function automatically.
+///
+/// A warning is emitted for each of these calls and they are exported as generated.
+pub(crate) fn assure_trusted_calls(function: &mut ItemFn, trusted: bool, lints: &LintLevels) {
+    let calls = assure_trusted_calls_in(&mut function.block, trusted, metadata::definition);
+
+    for call in calls {
+        metadata::export(Record::MissingAssure {
+            function: &call.function,
+            caller: &function.sig.ident,
+            call: &call.call,
+            generated: true,
+        });
+
+        if let Some(diagnostic) = lints.diagnostic(
+            Lint::TrustedAssure,
+            call.span,
+            format!(
+                "the preconditions of `{}` are assured automatically",
+                call.function
+            ),
+        ) {
+            diagnostic
+                .note(format!(
+                    "`{}` has the following preconditions: {}",
+                    call.function,
+                    call.preconditions.join(", ")
+                ))
+                .note("the call is in a `#[pre(trusted)]` scope".into())
+                .emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn assures_trusted_calls() {
+        let mut block: Block = parse2(quote! {
+            {
+                foo(1);
+                #[pre(trusted)]
+                {
+                    foo(2);
+                    bar(3);
+                    #[assure(x > 0, reason = "4 > 0")]
+                    foo(4);
+                }
+                #[pre(trusted)]
+                let x = path::to::foo(5);
+                #[pre(trusted)]
+                foo(6);
+
+                #[pre(trusted)]
+                fn nested() {
+                    foo(7);
+                }
+            }
+        })
+        .expect("valid block");
+
+        let calls = assure_trusted_calls_in(&mut block, false, |name| match name {
+            "foo" => Some(Definition {
+                arity: 1,
+                parameters: vec!["x".into()],
+                parameter_types: Vec::new(),
+                type_parameters: Vec::new(),
+                preconditions: vec!["x > 0".into()],
+                contract_id: "0123456789abcdef".into(),
+                assures: Some(vec![format!(
+                    "#[assure(x > 0, reason = {:?})]",
+                    TRUSTED_REASON
+                )]),
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            }),
+            "bar" => Some(Definition {
+                arity: 1,
+                parameters: vec!["x".into()],
+                parameter_types: Vec::new(),
+                type_parameters: Vec::new(),
+                preconditions: vec!["token(Token)".into()],
+                contract_id: "fedcba9876543210".into(),
+                assures: None,
+                skeleton: Vec::new(),
+                precondition_arg_first: false,
+                is_unsafe: false,
+                module: Vec::new(),
+            }),
+            _ => None,
+        });
+
+        let calls = calls.iter().map(|call| &*call.call).collect::<Vec<_>>();
+        assert_eq!(calls, ["foo (2)", "path :: to :: foo (5)", "foo (6)"]);
+
+        // Only the attribute of the nested function remains, it is handled with the function.
+        let block = quote! { #block }.to_string();
+        assert_eq!(block.matches("reason = \"the call is in").count(), 3);
+        assert_eq!(block.matches("pre (trusted)").count(), 1);
+    }
+}
@@ -0,0 +1,292 @@
+//! Exports the calls in `unsafe` contexts of a function together with the preconditions assured
+//! for them.
+//!
+//! Together with the functions of `extern_crate` modules, this allows finding wrapper functions
+//! that call a foreign function without discharging or forwarding all of its preconditions, even
+//! if the wrapper and the `extern_crate` module are in different crates.
+
+use quote::quote;
+use std::collections::HashMap;
+use syn::{
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Expr, ExprCall, ExprMethodCall, Item, ItemFn, Lit, Local, Meta,
+};
+
+use super::TRUSTED_REASON;
+use crate::{
+    call_handling::AssureAttr,
+    helpers::{attributes_of_expression_ref, visit_matching_attrs_parsed, Attr},
+    metadata::{self, Record},
+};
+
+/// A call in an `unsafe` context.
+struct UnsafeCall {
+    /// The name of the called function or method.
+    function: String,
+    /// The call expression.
+    call: String,
+    /// The preconditions assured for the call with a reason.
+    assured: Vec<String>,
+}
+
+/// Finds the calls in `unsafe` contexts of the function.
+fn find_unsafe_calls(function: &ItemFn) -> Vec<UnsafeCall> {
+    /// Searches for calls in `unsafe` contexts and the `assure` attributes applying to them.
+    struct CallVisitor {
+        /// Whether the visitor is currently in an `unsafe` context.
+        in_unsafe: bool,
+        /// The preconditions assured for the calls with `assure` attributes, by their address.
+        assured: HashMap<*const Expr, Vec<String>>,
+        /// The calls found so far.
+        calls: Vec<UnsafeCall>,
+    }
+
+    impl CallVisitor {
+        /// Remembers the preconditions assured by the attributes for the call in the expression.
+        fn collect_assures(&mut self, attrs: &[Attribute], expr: &Expr) {
+            let mut assured = Vec::new();
+
+            visit_matching_attrs_parsed(attrs, "assure", |attr: Attr<AssureAttr>| {
+                if let AssureAttr::WithReason {
+                    precondition,
+                    reason,
+                    ..
+                } = attr.content()
+                {
+                    if reason.text() != TRUSTED_REASON {
+                        assured.push(precondition.to_string());
+                    }
+                }
+            });
+
+            if !assured.is_empty() {
+                if let Some(call) = outermost_call(expr) {
+                    self.assured
+                        .entry(call as *const Expr)
+                        .or_default()
+                        .extend(assured);
+                }
+            }
+        }
+    }
+
+    impl<'ast> Visit<'ast> for CallVisitor {
+        fn visit_local(&mut self, local: &'ast Local) {
+            if let Some((_, init)) = &local.init {
+                self.collect_assures(&local.attrs, init);
+            }
+
+            visit_local(self, local);
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Some(attrs) = attributes_of_expression_ref(expr) {
+                self.collect_assures(attrs, expr);
+            }
+
+            let function = match expr {
+                Expr::Call(ExprCall { func, .. }) => match &**func {
+                    Expr::Path(path) => path
+                        .path
+                        .segments
+                        .last()
+                        .map(|segment| segment.ident.to_string()),
+                    _ => None,
+                },
+                Expr::MethodCall(ExprMethodCall { method, .. }) => Some(method.to_string()),
+                _ => None,
+            };
+
+            if let (true, Some(function)) = (self.in_unsafe, function) {
+                self.calls.push(UnsafeCall {
+                    function,
+                    call: quote! { #expr }.to_string(),
+                    assured: self
+                        .assured
+                        .remove(&(expr as *const Expr))
+                        .unwrap_or_default(),
+                });
+            }
+
+            let outer_in_unsafe = self.in_unsafe;
+            self.in_unsafe |= matches!(expr, Expr::Unsafe(_));
+            visit_expr(self, expr);
+            self.in_unsafe = outer_in_unsafe;
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = CallVisitor {
+        in_unsafe: function.sig.unsafety.is_some(),
+        assured: HashMap::new(),
+        calls: Vec::new(),
+    };
+    visitor.visit_block(&function.block);
+
+    visitor.calls
+}
+
+/// Returns the outermost call in the expression, which is the one `assure` attributes apply to.
+pub(super) fn outermost_call(expr: &Expr) -> Option<&Expr> {
+    /// Finds the outermost call in an expression.
+    #[derive(Default)]
+    struct OutermostCallVisitor<'ast> {
+        /// The outermost call found.
+        call: Option<&'ast Expr>,
+    }
+
+    impl<'ast> Visit<'ast> for OutermostCallVisitor<'ast> {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if self.call.is_some() {
+                return;
+            }
+
+            if let Expr::Call(_) | Expr::MethodCall(_) = expr {
+                self.call = Some(expr);
+            } else {
+                visit_expr(self, expr);
+            }
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = OutermostCallVisitor::default();
+    visitor.visit_expr(expr);
+
+    visitor.call
+}
+
+/// Returns the `# Safety` section of the documentation in the attributes, if there is one.
+pub(crate) fn safety_docs(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(meta)) => match meta.lit {
+                Lit::Str(doc) => Some(doc.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let start = lines
+        .iter()
+        .position(|line| line.trim().eq_ignore_ascii_case("# safety"))?
+        + 1;
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.starts_with('#'))
+        .map_or(lines.len(), |len| start + len);
+
+    let section = lines[start..end].join("\n");
+    let section = section.trim();
+
+    if section.is_empty() {
+        None
+    } else {
+        Some(section.to_string())
+    }
+}
+
+/// Exports a record for each call in an `unsafe` context of the function.
+pub(crate) fn export_unsafe_call_records(function: &ItemFn) {
+    if metadata::export_path().is_none() {
+        return;
+    }
+
+    for call in find_unsafe_calls(function) {
+        metadata::export(Record::UnsafeCall {
+            caller: &function.sig.ident,
+            function: &call.function,
+            call: &call.call,
+            assured: &call.assured,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_unsafe_calls() {
+        let function: ItemFn = parse2(quote! {
+            fn wrapper(s: *const c_char, buf: &mut [u8]) -> usize {
+                let len = safe_len(buf);
+
+                #[assure(valid_cstr(s), reason = "checked by the caller")]
+                let n = unsafe { sys::strlen(s) };
+
+                unsafe {
+                    #[assure(valid_ptr(dst, w), reason = "`buf` is a mutable reference")]
+                    sys::memcpy(buf.as_mut_ptr(), s, n);
+
+                    #[assure(x > 0, reason = "the call is in a `#[pre(trusted)]` scope")]
+                    generated(n);
+                }
+
+                n + len
+            }
+        })
+        .expect("valid function");
+
+        let calls = find_unsafe_calls(&function)
+            .into_iter()
+            .map(|call| (call.function, call.assured))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            calls,
+            [
+                ("strlen".to_string(), vec!["valid_cstr(s)".to_string()]),
+                ("memcpy".to_string(), vec!["valid_ptr(dst, w)".to_string()]),
+                ("as_mut_ptr".to_string(), vec![]),
+                ("generated".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_safety_docs() {
+        let function: ItemFn = parse2(quote! {
+            /// Reads from `p`.
+            ///
+            /// # Safety
+            ///
+            /// `p` must be valid for reads.
+            /// It must be aligned.
+            ///
+            /// # Examples
+            fn foo(p: *const u8) {}
+        })
+        .expect("valid function");
+
+        assert_eq!(
+            safety_docs(&function.attrs).as_deref(),
+            Some("`p` must be valid for reads.\nIt must be aligned.")
+        );
+
+        let function: ItemFn = parse2(quote! {
+            /// Does nothing.
+            fn foo() {}
+        })
+        .expect("valid function");
+
+        assert!(safety_docs(&function.attrs).is_none());
+    }
+}
@@ -0,0 +1,227 @@
+//! Warns about assured calls to `unsafe` functions that are not within an `unsafe` block.
+//!
+//! The body of an `unsafe fn` is an `unsafe` context, so calls to `unsafe` functions compile there
+//! without an `unsafe` block. Such calls are easy to miss when reviewing where the `assure`d
+//! preconditions are relied on, so they are reported as well.
+//!
+//! This check is purely syntactical: only `unsafe` blocks written in the source of the function
+//! are recognized. The unsafety of the called function is only known for functions defined
+//! earlier in the same crate and for functions in `extern_crate` modules that were already
+//! expanded.
+
+use proc_macro2::Span;
+use std::collections::HashSet;
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Expr, ExprCall, ExprPath, Item, ItemFn, Local, Path,
+};
+
+use super::{unsafe_calls::outermost_call, TRUSTED_REASON};
+use crate::{
+    call_handling::AssureAttr,
+    extern_crate::outline_is_unsafe,
+    helpers::{attributes_of_expression_ref, visit_matching_attrs_parsed, Attr},
+    lints::{Lint, LintLevels},
+    metadata,
+};
+
+/// An assured call to an `unsafe` function outside of an `unsafe` block.
+struct UnsafeCallOutsideBlock {
+    /// The name of the called function.
+    function: String,
+    /// The span of the call.
+    call_span: Span,
+}
+
+/// Finds the assured calls to `unsafe` functions that are not within an `unsafe` block.
+fn find_calls_outside_unsafe(
+    function: &ItemFn,
+    is_unsafe: impl Fn(&Path) -> Option<bool>,
+) -> Vec<UnsafeCallOutsideBlock> {
+    /// Searches for assured calls and the `unsafe` blocks surrounding them.
+    struct CallVisitor<F> {
+        /// Determines whether the function at the path is an `unsafe fn`, if it is known.
+        is_unsafe: F,
+        /// Whether the visitor is currently within an `unsafe` block.
+        in_unsafe_block: bool,
+        /// The addresses of the calls with `assure` attributes.
+        assured: HashSet<*const Expr>,
+        /// The calls found so far.
+        found: Vec<UnsafeCallOutsideBlock>,
+    }
+
+    impl<F: Fn(&Path) -> Option<bool>> CallVisitor<F> {
+        /// Remembers the call in the expression, if the attributes assure its preconditions.
+        fn collect_assures(&mut self, attrs: &[Attribute], expr: &Expr) {
+            let mut assured = false;
+
+            visit_matching_attrs_parsed(attrs, "assure", |attr: Attr<AssureAttr>| {
+                if let AssureAttr::WithReason { reason, .. } = attr.content() {
+                    assured |= reason.text() != TRUSTED_REASON;
+                }
+            });
+
+            if assured {
+                if let Some(call) = outermost_call(expr) {
+                    self.assured.insert(call as *const Expr);
+                }
+            }
+        }
+    }
+
+    impl<'ast, F: Fn(&Path) -> Option<bool>> Visit<'ast> for CallVisitor<F> {
+        fn visit_local(&mut self, local: &'ast Local) {
+            if let Some((_, init)) = &local.init {
+                self.collect_assures(&local.attrs, init);
+            }
+
+            visit_local(self, local);
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let Some(attrs) = attributes_of_expression_ref(expr) {
+                self.collect_assures(attrs, expr);
+            }
+
+            if let Expr::Call(ExprCall { func, .. }) = expr {
+                if let Expr::Path(ExprPath { path, .. }) = &**func {
+                    if self.assured.remove(&(expr as *const Expr))
+                        && !self.in_unsafe_block
+                        && (self.is_unsafe)(path) == Some(true)
+                    {
+                        if let Some(segment) = path.segments.last() {
+                            self.found.push(UnsafeCallOutsideBlock {
+                                function: segment.ident.to_string(),
+                                call_span: expr.span(),
+                            });
+                        }
+                    }
+                }
+            }
+
+            let outer_in_unsafe_block = self.in_unsafe_block;
+            self.in_unsafe_block |= matches!(expr, Expr::Unsafe(_));
+            visit_expr(self, expr);
+            self.in_unsafe_block = outer_in_unsafe_block;
+        }
+
+        fn visit_item(&mut self, _: &'ast Item) {
+            // Nested items are checked on their own.
+        }
+    }
+
+    let mut visitor = CallVisitor {
+        is_unsafe,
+        in_unsafe_block: false,
+        assured: HashSet::new(),
+        found: Vec::new(),
+    };
+    visitor.visit_block(&function.block);
+
+    visitor.found
+}
+
+/// Returns whether the function at the path is an `unsafe fn`, if it is known.
+fn callee_is_unsafe(path: &Path) -> Option<bool> {
+    let outline = if path.segments.len() >= 2 {
+        outline_is_unsafe(path)
+    } else {
+        None
+    };
+
+    outline.or_else(|| {
+        let name = path.segments.last()?.ident.to_string();
+
+        metadata::definition(&name).map(|definition| definition.is_unsafe)
+    })
+}
+
+/// Emits a warning for each assured call to an `unsafe` function in the function that is not
+/// within an `unsafe` block.
+pub(crate) fn check_calls_outside_unsafe(function: &ItemFn, lints: &LintLevels) {
+    for found in find_calls_outside_unsafe(function, callee_is_unsafe) {
+        if let Some(diagnostic) = lints.diagnostic(
+            Lint::AssureOutsideUnsafe,
+            found.call_span,
+            format!(
+                "the assured call to the `unsafe` function `{}` is not within an `unsafe` block",
+                found.function
+            ),
+        ) {
+            let diagnostic = if function.sig.unsafety.is_some() {
+                diagnostic.note(format!(
+                    "the call compiles, because the body of the `unsafe fn` `{}` is an `unsafe` context",
+                    function.sig.ident
+                ))
+            } else {
+                diagnostic
+            };
+
+            diagnostic
+                .help("wrap the call in an `unsafe` block to mark where the assured preconditions are relied on".into())
+                .emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn finds_calls_outside_unsafe() {
+        let function: ItemFn = parse2(quote! {
+            unsafe fn wrapper(ptr: *const u8) -> u8 {
+                #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+                let first = read(ptr);
+
+                let second = unsafe {
+                    #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+                    read(ptr)
+                };
+
+                #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+                let third = unsafe { read(ptr) };
+
+                #[assure(x > 0, reason = "1 > 0")]
+                check(1);
+
+                #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+                raw::read(ptr);
+
+                #[assure(valid_ptr(ptr, r), reason = "the call is in a `#[pre(trusted)]` scope")]
+                read(ptr);
+
+                fn nested(ptr: *const u8) {
+                    #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+                    read(ptr);
+                }
+
+                first + second + third
+            }
+        })
+        .expect("valid function");
+
+        let found = find_calls_outside_unsafe(&function, |path| {
+            let path = quote! { #path }.to_string();
+
+            match &*path {
+                "read" | "raw :: read" => Some(true),
+                "check" => Some(false),
+                _ => None,
+            }
+        });
+
+        assert_eq!(
+            found
+                .iter()
+                .map(|found| found.function.as_str())
+                .collect::<Vec<_>>(),
+            ["read", "read"]
+        );
+    }
+}
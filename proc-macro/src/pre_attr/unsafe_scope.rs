@@ -0,0 +1,176 @@
+//! Checks that `unsafe` blocks containing assured calls are kept minimal.
+//!
+//! This is only done when requested using `#[pre(strict_unsafe)]`.
+
+use proc_macro2::Span;
+use proc_macro_error::emit_warning;
+use syn::{
+    spanned::Spanned,
+    visit::{visit_expr, visit_local, Visit},
+    Attribute, Expr, ExprUnsafe, Item, Local, UnOp,
+};
+
+use super::macro_handling::contains_call_attributes;
+use crate::{helpers::is_matching_attr, metadata, stable_warnings};
+
+/// Emits warnings for operations in the `unsafe` block that are unrelated to its assured calls.
+pub(crate) fn check_unsafe_block(unsafe_block: &ExprUnsafe) {
+    if has_call_attributes(&unsafe_block.attrs) {
+        // The whole block is the assured call, so there can be no unrelated operations.
+        return;
+    }
+
+    let mut visitor = OperationVisitor {
+        contains_assured_call: false,
+        unrelated_operations: Vec::new(),
+    };
+
+    for stmt in &unsafe_block.block.stmts {
+        visitor.visit_stmt(stmt);
+    }
+
+    if !visitor.contains_assured_call {
+        return;
+    }
+
+    let unsafe_span = unsafe_block.unsafe_token.span;
+
+    for span in visitor.unrelated_operations {
+        stable_warnings::record(
+            "strict_unsafe",
+            span,
+            "operation shares an `unsafe` block with an assured call",
+        );
+        emit_warning!(
+            span,
+            "operation shares an `unsafe` block with an assured call";
+            help = unsafe_span => "consider moving it out of the `unsafe` block to keep the scope of `unsafe` minimal";
+            note = "this warning is enabled by `#[pre(strict_unsafe)]`"
+        );
+    }
+}
+
+/// Checks whether the given attributes contain `assure` or `forward` attributes.
+fn has_call_attributes(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| is_matching_attr("assure", attr) || is_matching_attr("forward", attr))
+}
+
+/// Collects the operations in an `unsafe` block that could require the `unsafe` block.
+///
+/// These are dereferences and calls to functions that are known to have preconditions. Other
+/// calls and macro invocations are not collected, since they are usually safe.
+struct OperationVisitor {
+    /// Whether an assured call was found in the block.
+    contains_assured_call: bool,
+    /// The spans of operations that are not part of an assured call.
+    unrelated_operations: Vec<Span>,
+}
+
+impl<'ast> Visit<'ast> for OperationVisitor {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        let attrs = match expr {
+            Expr::Call(call) => &call.attrs,
+            Expr::MethodCall(call) => &call.attrs,
+            Expr::Unsafe(unsafe_block) => &unsafe_block.attrs,
+            Expr::Block(block) => &block.attrs,
+            Expr::Paren(paren) => &paren.attrs,
+            Expr::Macro(mac) => &mac.attrs,
+            _ => return self.visit_operation(expr),
+        };
+
+        if has_call_attributes(attrs) {
+            // Everything inside of an assured call, including its arguments, is related to it.
+            self.contains_assured_call = true;
+        } else {
+            self.visit_operation(expr);
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast Local) {
+        if has_call_attributes(&local.attrs) {
+            self.contains_assured_call = true;
+        } else {
+            visit_local(self, local);
+        }
+    }
+
+    fn visit_item(&mut self, item: &'ast Item) {
+        // Macros in statement position are parsed as items.
+        //
+        // Other nested items are not affected by the surrounding `unsafe` block.
+        if let Item::Macro(mac) = item {
+            if contains_call_attributes(mac.mac.tokens.clone()) {
+                self.contains_assured_call = true;
+            }
+        }
+    }
+}
+
+impl OperationVisitor {
+    /// Visits an expression without call attributes.
+    fn visit_operation(&mut self, expr: &Expr) {
+        match expr {
+            // Closures are skipped, since they are usually not called where they are defined.
+            //
+            // Nested `unsafe` blocks are checked on their own.
+            Expr::Closure(_) | Expr::Unsafe(_) => return,
+            Expr::Call(call) => {
+                // Calls without call attributes are only reported if they are known to need an
+                // `assure` attribute.
+                if let Expr::Path(path) = &*call.func {
+                    if metadata::callee_definition(&path.path).is_some() {
+                        self.unrelated_operations.push(expr.span())
+                    }
+                }
+            }
+            // Macros containing assured calls are treated like assured calls.
+            Expr::Macro(mac) if contains_call_attributes(mac.mac.tokens.clone()) => {
+                self.contains_assured_call = true;
+                return;
+            }
+            Expr::Unary(unary) => {
+                if let UnOp::Deref(_) = unary.op {
+                    self.unrelated_operations.push(expr.span())
+                }
+            }
+            _ => (),
+        }
+
+        visit_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn reports_only_unsafe_operations() {
+        let unsafe_block: ExprUnsafe = parse2(quote! {
+            unsafe {
+                #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+                let a = read(ptr);
+                let b = a.abs();
+                println!("{}", compute(b));
+                *ptr + b
+            }
+        })
+        .expect("parses as an `unsafe` block");
+
+        let mut visitor = OperationVisitor {
+            contains_assured_call: false,
+            unrelated_operations: Vec::new(),
+        };
+        for stmt in &unsafe_block.block.stmts {
+            visitor.visit_stmt(stmt);
+        }
+
+        assert!(visitor.contains_assured_call);
+        assert_eq!(visitor.unrelated_operations.len(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+//! Defers the expansion of `pre` on a function behind attributes that rewrite its body.
+//!
+//! Attribute macros are expanded from the outermost to the innermost attribute. If `pre` is
+//! expanded before an attribute such as `#[tracing::instrument]`, that attribute sees the
+//! precondition parameter and the inserted `debug_assert` statements as part of the function. It
+//! may then record the parameter, move the statements somewhere else or drop them entirely.
+//!
+//! For the known attributes of this kind, `pre` therefore moves its own attributes below the last
+//! of them and leaves the function unchanged otherwise. The compiler then expands the other
+//! attributes first and `pre` afterwards, so the signature and body of the function are changed
+//! exactly once, no matter in which order the attributes were written.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_quote, Attribute, File, Ident, Item, Path};
+
+use super::cfg_activation::Activation;
+use crate::helpers::{is_matching_attr, CRATE_NAME};
+
+/// The names of the known attributes that rewrite the body of a function.
+///
+/// These are `instrument` of `tracing` and `logfn` and `logfn_inputs` of `log-derive`.
+const WRAPPING_ATTRS: [&str; 3] = ["instrument", "logfn", "logfn_inputs"];
+
+/// Checks if the path refers to a known attribute that rewrites the body of a function.
+fn is_wrapping_attr(path: &Path) -> bool {
+    match path.segments.last() {
+        Some(segment) => WRAPPING_ATTRS
+            .iter()
+            .any(|wrapping_attr| segment.ident == wrapping_attr),
+        None => false,
+    }
+}
+
+/// Returns the `pre` attribute currently being expanded, given its content.
+///
+/// If the attribute was written inside of a `cfg_attr`, it stays inside of one, so that it
+/// activates `pre` the same way when it is expanded again. The predicate of the original
+/// `cfg_attr` is not passed to the macro, but it holds in every configuration where the macro is
+/// expanded, so `all()` is used instead.
+pub(crate) fn invoked_attr(attr: &TokenStream) -> Attribute {
+    let crate_name = Ident::new(&CRATE_NAME, Span::call_site());
+    let pre_attr = if attr.is_empty() {
+        quote! { #crate_name::pre }
+    } else {
+        quote! { #crate_name::pre(#attr) }
+    };
+
+    match Activation::of_invocation(attr) {
+        Activation::Unconditional => parse_quote! { #[#pre_attr] },
+        Activation::Gated | Activation::Conditional => {
+            parse_quote! { #[cfg_attr(all(), #pre_attr)] }
+        }
+    }
+}
+
+/// Moves the `pre` attributes of the function in the file below the last known attribute that
+/// rewrites its body.
+///
+/// `invoked_attr` returns the `pre` attribute currently being expanded, which is not part of the
+/// attributes of the function anymore. Returns whether there was such an attribute below the
+/// `pre` attributes.
+pub(crate) fn defer_behind_wrapping_attrs(
+    file: &mut File,
+    invoked_attr: impl FnOnce() -> Attribute,
+) -> bool {
+    let function = match &mut file.items[..] {
+        [Item::Fn(function)] => function,
+        _ => return false,
+    };
+
+    let last_wrapping_attr = match function
+        .attrs
+        .iter()
+        .rposition(|attribute| is_wrapping_attr(&attribute.path))
+    {
+        Some(index) => index,
+        None => return false,
+    };
+
+    let mut attrs = Vec::with_capacity(function.attrs.len() + 1);
+    let mut moved_attrs = vec![invoked_attr()];
+    for (i, attribute) in function.attrs.drain(..).enumerate() {
+        if i < last_wrapping_attr && is_matching_attr("pre", &attribute) {
+            moved_attrs.push(attribute);
+        } else {
+            attrs.push(attribute);
+        }
+
+        if i == last_wrapping_attr {
+            attrs.append(&mut moved_attrs);
+        }
+    }
+    function.attrs = attrs;
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn moves_pre_attrs_below_wrapping_attrs() {
+        let mut file: File = parse2(quote! {
+            #[doc = "foo"]
+            #[pre(y > 0)]
+            #[tracing::instrument(skip(x))]
+            #[inline]
+            #[pre(z > 0)]
+            fn foo(x: i32, y: i32, z: i32) {}
+        })
+        .expect("valid file");
+
+        assert!(defer_behind_wrapping_attrs(
+            &mut file,
+            || parse_quote! { #[pre::pre(x > 0)] }
+        ));
+        assert_eq!(
+            quote! { #file }.to_string(),
+            quote! {
+                #[doc = "foo"]
+                #[tracing::instrument(skip(x))]
+                #[pre::pre(x > 0)]
+                #[pre(y > 0)]
+                #[inline]
+                #[pre(z > 0)]
+                fn foo(x: i32, y: i32, z: i32) {}
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn keeps_functions_without_wrapping_attrs() {
+        let mut file: File = parse2(quote! {
+            #[inline]
+            #[pre(y > 0)]
+            fn foo(x: i32, y: i32) {}
+        })
+        .expect("valid file");
+
+        assert!(!defer_behind_wrapping_attrs(
+            &mut file,
+            || parse_quote! { #[pre::pre(x > 0)] }
+        ));
+
+        let mut file: File = parse2(quote! {
+            #[instrument]
+            mod foo {}
+        })
+        .expect("valid file");
+
+        assert!(!defer_behind_wrapping_attrs(
+            &mut file,
+            || parse_quote! { #[pre::pre] }
+        ));
+    }
+}
@@ -7,17 +7,70 @@ use syn::{
     ext::IdentExt,
     parenthesized,
     parse::{Parse, ParseStream},
+    parse_quote_spanned,
+    punctuated::Punctuated,
     spanned::Spanned,
     token::Paren,
-    Error, Expr, Ident, LitStr, Token,
+    Error, Expr, ExprLit, ExprRange, Ident, Lit, LitStr, Path, Token, Type,
 };
 
+use crate::{
+    config::{self, ConditionKind},
+    helpers::CRATE_NAME,
+};
+
+/// The names of the kinds of preconditions that are built into pre.
+///
+/// These names cannot be used for kinds of preconditions configured in `pre.toml`.
+pub(crate) const BUILTIN_KINDS: [&str; 21] = [
+    "valid_ptr",
+    "proper_align",
+    "proper_align_val",
+    "aligned_to",
+    "non_null",
+    "nul_terminated",
+    "valid_cstr",
+    "initialized",
+    "non_overlapping",
+    "valid_slice",
+    "interrupts_disabled",
+    "not_reentrant",
+    "dma_quiescent",
+    "lives_until_end_of_scope",
+    "in_range",
+    "valid_value",
+    "layout_compatible",
+    "consumed",
+    "valid_utf8",
+    "token",
+    "export",
+];
+
 /// The custom keywords used by the precondition kinds.
 mod custom_keywords {
     use syn::custom_keyword;
 
     custom_keyword!(valid_ptr);
     custom_keyword!(proper_align);
+    custom_keyword!(proper_align_val);
+    custom_keyword!(aligned_to);
+    custom_keyword!(non_null);
+    custom_keyword!(nul_terminated);
+    custom_keyword!(valid_cstr);
+    custom_keyword!(initialized);
+    custom_keyword!(non_overlapping);
+    custom_keyword!(valid_slice);
+    custom_keyword!(interrupts_disabled);
+    custom_keyword!(not_reentrant);
+    custom_keyword!(dma_quiescent);
+    custom_keyword!(lives_until_end_of_scope);
+    custom_keyword!(in_range);
+    custom_keyword!(valid_value);
+    custom_keyword!(layout_compatible);
+    custom_keyword!(consumed);
+    custom_keyword!(valid_utf8);
+    custom_keyword!(token);
+    custom_keyword!(export);
     custom_keyword!(r);
     custom_keyword!(w);
 }
@@ -37,6 +90,8 @@ pub(crate) enum Precondition {
         _comma: Token![,],
         /// Information on what accesses of the pointer must be valid.
         read_write: ReadWrite,
+        /// The comma and the number of elements that the pointer must be valid for, if given.
+        count: Option<(Token![,], Box<Expr>)>,
     },
     ProperAlign {
         /// The `proper_align` keyword.
@@ -46,6 +101,250 @@ pub(crate) enum Precondition {
         /// The identifier of the pointer.
         ident: Ident,
     },
+    /// Requires that the given pointer is aligned to an explicitly given alignment.
+    ProperAlignVal {
+        /// The `proper_align_val` keyword.
+        proper_align_val_keyword: custom_keywords::proper_align_val,
+        /// The parentheses following the `proper_align_val` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer.
+        ident: Ident,
+        /// The comma between the identifier and the alignment.
+        _comma: Token![,],
+        /// The alignment in bytes that the pointer must have.
+        align: Box<Expr>,
+    },
+    /// Requires that the given pointer is aligned to an explicitly given alignment.
+    ///
+    /// This is the same condition as `ProperAlignVal`, but it is encoded separately.
+    AlignedTo {
+        /// The `aligned_to` keyword.
+        aligned_to_keyword: custom_keywords::aligned_to,
+        /// The parentheses following the `aligned_to` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer.
+        ident: Ident,
+        /// The comma between the identifier and the alignment.
+        _comma: Token![,],
+        /// The alignment in bytes that the pointer must have.
+        align: Box<Expr>,
+    },
+    /// Requires that the given pointer is not null.
+    NonNull {
+        /// The `non_null` keyword.
+        non_null_keyword: custom_keywords::non_null,
+        /// The parentheses following the `non_null` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer.
+        ident: Ident,
+    },
+    /// Requires that the given pointer points to a nul-terminated string.
+    NulTerminated {
+        /// The `nul_terminated` keyword.
+        nul_terminated_keyword: custom_keywords::nul_terminated,
+        /// The parentheses following the `nul_terminated` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer.
+        ident: Ident,
+    },
+    /// Requires that the given pointer points to a valid C string.
+    ValidCStr {
+        /// The `valid_cstr` keyword.
+        valid_cstr_keyword: custom_keywords::valid_cstr,
+        /// The parentheses following the `valid_cstr` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer.
+        ident: Ident,
+    },
+    /// Requires that the memory behind the given pointer contains initialized values.
+    Initialized {
+        /// The `initialized` keyword.
+        initialized_keyword: custom_keywords::initialized,
+        /// The parentheses following the `initialized` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer.
+        ident: Ident,
+        /// The comma and the number of initialized elements, if given.
+        count: Option<(Token![,], Box<Expr>)>,
+    },
+    /// Requires that the memory regions behind the given pointers do not overlap.
+    NonOverlapping {
+        /// The `non_overlapping` keyword.
+        non_overlapping_keyword: custom_keywords::non_overlapping,
+        /// The parentheses following the `non_overlapping` keyword.
+        parentheses: Paren,
+        /// The identifier of the first pointer.
+        first: Ident,
+        /// The comma between the two pointers.
+        _first_comma: Token![,],
+        /// The identifier of the second pointer.
+        second: Ident,
+        /// The comma between the second pointer and the element count.
+        _second_comma: Token![,],
+        /// The number of elements in each memory region.
+        count: Box<Expr>,
+    },
+    /// Requires that the given pointer and length can be used to create a slice.
+    ///
+    /// This is a composite precondition, which is expanded into its parts when it is parsed in an
+    /// attribute, see `Precondition::expand`.
+    ValidSlice {
+        /// The `valid_slice` keyword.
+        valid_slice_keyword: custom_keywords::valid_slice,
+        /// The parentheses following the `valid_slice` keyword.
+        parentheses: Paren,
+        /// The identifier of the pointer to the first element.
+        ident: Ident,
+        /// The comma between the identifier and the length.
+        _first_comma: Token![,],
+        /// The number of elements in the slice.
+        len: Box<Expr>,
+        /// The comma between the length and the read/write information.
+        _second_comma: Token![,],
+        /// Information on what accesses of the slice must be valid.
+        read_write: ReadWrite,
+    },
+    /// Requires that interrupts are disabled for the duration of the call.
+    InterruptsDisabled {
+        /// The `interrupts_disabled` keyword.
+        interrupts_disabled_keyword: custom_keywords::interrupts_disabled,
+        /// The empty parentheses following the `interrupts_disabled` keyword.
+        parentheses: Paren,
+    },
+    /// Requires that the function is not called again before the call returns.
+    NotReentrant {
+        /// The `not_reentrant` keyword.
+        not_reentrant_keyword: custom_keywords::not_reentrant,
+        /// The empty parentheses following the `not_reentrant` keyword.
+        parentheses: Paren,
+    },
+    /// Requires that no DMA engine accesses the given buffer for the duration of the call.
+    DmaQuiescent {
+        /// The `dma_quiescent` keyword.
+        dma_quiescent_keyword: custom_keywords::dma_quiescent,
+        /// The parentheses following the `dma_quiescent` keyword.
+        parentheses: Paren,
+        /// The identifier of the buffer.
+        ident: Ident,
+    },
+    /// Requires that the storage referenced by the given argument lives until the end of the scope
+    /// that the result of the call is used in.
+    LivesUntilEndOfScope {
+        /// The `lives_until_end_of_scope` keyword.
+        lives_until_end_of_scope_keyword: custom_keywords::lives_until_end_of_scope,
+        /// The parentheses following the `lives_until_end_of_scope` keyword.
+        parentheses: Paren,
+        /// The identifier of the argument referencing the storage.
+        ident: Ident,
+    },
+    /// Requires that the given value is within a range.
+    InRange {
+        /// The `in_range` keyword.
+        in_range_keyword: custom_keywords::in_range,
+        /// The parentheses following the `in_range` keyword.
+        parentheses: Paren,
+        /// The identifier of the value.
+        ident: Ident,
+        /// The comma between the identifier and the range.
+        _comma: Token![,],
+        /// The range that the value must be within.
+        range: Box<ExprRange>,
+    },
+    /// Requires that the bits of the given value form a valid value of a type.
+    ValidValue {
+        /// The `valid_value` keyword.
+        valid_value_keyword: custom_keywords::valid_value,
+        /// The parentheses following the `valid_value` keyword.
+        parentheses: Paren,
+        /// The identifier of the value.
+        ident: Ident,
+        /// The comma between the identifier and the type.
+        _comma: Token![,],
+        /// The type that the value must be valid for.
+        ty: Box<Type>,
+    },
+    /// Requires that a value of the second type can be read from the memory of a value of the
+    /// first type.
+    LayoutCompatible {
+        /// The `layout_compatible` keyword.
+        layout_compatible_keyword: custom_keywords::layout_compatible,
+        /// The parentheses following the `layout_compatible` keyword.
+        parentheses: Paren,
+        /// The type whose memory is read.
+        from: Box<Type>,
+        /// The comma between the two types.
+        _comma: Token![,],
+        /// The type that is read.
+        to: Box<Type>,
+    },
+    /// Requires that the ownership of the given value is transferred to the function.
+    Consumed {
+        /// The `consumed` keyword.
+        consumed_keyword: custom_keywords::consumed,
+        /// The parentheses following the `consumed` keyword.
+        parentheses: Paren,
+        /// The identifier of the value.
+        ident: Ident,
+    },
+    /// Requires that the bytes of the given value are valid UTF-8.
+    ValidUtf8 {
+        /// The `valid_utf8` keyword.
+        valid_utf8_keyword: custom_keywords::valid_utf8,
+        /// The parentheses following the `valid_utf8` keyword.
+        parentheses: Paren,
+        /// The identifier of the value.
+        ident: Ident,
+    },
+    /// Requires that a token value of the given type is passed.
+    Token {
+        /// The `token` keyword.
+        token_keyword: custom_keywords::token,
+        /// The parentheses following the `token` keyword.
+        parentheses: Paren,
+        /// The type of the token.
+        ty: Box<Type>,
+        /// The `=` and the value of the token.
+        ///
+        /// This is only present when assuring the precondition.
+        value: Option<(Token![=], Box<Expr>)>,
+    },
+    /// A custom precondition that is exported as a constant under the given name.
+    Exported {
+        /// The `export` keyword.
+        export_keyword: custom_keywords::export,
+        /// The `=` between the `export` keyword and the name.
+        _eq: Token![=],
+        /// The name of the exported condition.
+        ident: Ident,
+        /// The comma between the name and the text of the condition.
+        _comma: Token![,],
+        /// The text describing the condition.
+        text: LitStr,
+    },
+    /// A reference to a condition that was exported elsewhere.
+    ///
+    /// Exported and imported conditions are identified by the marker type generated next to the
+    /// constant, which is checked by the compiler. Here they are only known by the name of the
+    /// constant, which is used to order them.
+    Imported {
+        /// The `use` keyword.
+        use_token: Token![use],
+        /// The path to the exported constant.
+        path: Path,
+    },
+    /// A precondition of a kind that is configured in `pre.toml`.
+    ///
+    /// It is encoded like a custom precondition with its description.
+    Configured {
+        /// The name of the kind of the precondition.
+        keyword: Ident,
+        /// The parentheses following the name.
+        parentheses: Paren,
+        /// The arguments of the precondition.
+        args: Punctuated<Ident, Token![,]>,
+        /// The configuration of the kind of the precondition.
+        kind: &'static ConditionKind,
+    },
     /// An expression that should evaluate to a boolean value.
     Boolean(Box<Expr>),
     /// A custom precondition that is spelled out in a string.
@@ -55,12 +354,99 @@ pub(crate) enum Precondition {
 impl fmt::Display for Precondition {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            Precondition::ValidPtr {
+                ident,
+                read_write,
+                count: Some((_, count)),
+                ..
+            } => write!(
+                f,
+                "valid_ptr({}, {}, {})",
+                ident,
+                read_write,
+                quote! { #count }
+            ),
             Precondition::ValidPtr {
                 ident, read_write, ..
             } => write!(f, "valid_ptr({}, {})", ident.to_string(), read_write),
             Precondition::ProperAlign { ident, .. } => {
                 write!(f, "proper_align({})", ident.to_string())
             }
+            Precondition::ProperAlignVal { ident, align, .. } => {
+                write!(f, "proper_align_val({}, {})", ident, quote! { #align })
+            }
+            Precondition::AlignedTo { ident, align, .. } => {
+                write!(f, "aligned_to({}, {})", ident, quote! { #align })
+            }
+            Precondition::NonNull { ident, .. } => write!(f, "non_null({})", ident),
+            Precondition::NulTerminated { ident, .. } => write!(f, "nul_terminated({})", ident),
+            Precondition::ValidCStr { ident, .. } => write!(f, "valid_cstr({})", ident),
+            Precondition::Initialized {
+                ident,
+                count: Some((_, count)),
+                ..
+            } => write!(f, "initialized({}, {})", ident, quote! { #count }),
+            Precondition::Initialized { ident, .. } => write!(f, "initialized({})", ident),
+            Precondition::NonOverlapping {
+                first,
+                second,
+                count,
+                ..
+            } => write!(
+                f,
+                "non_overlapping({}, {}, {})",
+                first,
+                second,
+                quote! { #count }
+            ),
+            Precondition::ValidSlice {
+                ident,
+                len,
+                read_write,
+                ..
+            } => write!(
+                f,
+                "valid_slice({}, {}, {})",
+                ident,
+                quote! { #len },
+                read_write
+            ),
+            Precondition::InterruptsDisabled { .. } => write!(f, "interrupts_disabled()"),
+            Precondition::NotReentrant { .. } => write!(f, "not_reentrant()"),
+            Precondition::DmaQuiescent { ident, .. } => write!(f, "dma_quiescent({})", ident),
+            Precondition::LivesUntilEndOfScope { ident, .. } => {
+                write!(f, "lives_until_end_of_scope({})", ident)
+            }
+            Precondition::InRange { ident, range, .. } => {
+                write!(f, "in_range({}, {})", ident, quote! { #range })
+            }
+            Precondition::ValidValue { ident, ty, .. } => {
+                write!(f, "valid_value({}, {})", ident, quote! { #ty })
+            }
+            Precondition::LayoutCompatible { from, to, .. } => {
+                write!(
+                    f,
+                    "layout_compatible({}, {})",
+                    quote! { #from },
+                    quote! { #to }
+                )
+            }
+            Precondition::Consumed { ident, .. } => write!(f, "consumed({})", ident),
+            Precondition::ValidUtf8 { ident, .. } => write!(f, "valid_utf8({})", ident),
+            Precondition::Token { ty, .. } => write!(f, "token({})", quote! { #ty }),
+            Precondition::Exported { ident, text, .. } => {
+                write!(f, "export = {}, {:?}", ident, text.value())
+            }
+            Precondition::Imported { path, .. } => write!(f, "use {}", quote! { #path }),
+            Precondition::Configured { keyword, args, .. } => write!(
+                f,
+                "{}({})",
+                keyword,
+                args.iter()
+                    .map(Ident::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Precondition::Boolean(expr) => write!(f, "{}", quote! { #expr }),
             Precondition::Custom(lit) => write!(f, "{:?}", lit.value()),
         }
@@ -91,6 +477,11 @@ impl Parse for Precondition {
             let ident = parse_precondition_ident(&content)?;
             let comma = content.parse()?;
             let read_write = content.parse()?;
+            let count = if content.peek(Token![,]) {
+                Some((content.parse()?, content.parse()?))
+            } else {
+                None
+            };
 
             if content.is_empty() {
                 Ok(Precondition::ValidPtr {
@@ -99,6 +490,7 @@ impl Parse for Precondition {
                     ident,
                     _comma: comma,
                     read_write,
+                    count,
                 })
             } else {
                 Err(content.error("unexpected token"))
@@ -118,107 +510,1097 @@ impl Parse for Precondition {
             } else {
                 Err(content.error("unexpected token"))
             }
-        } else if input.peek(LitStr) {
-            Ok(Precondition::Custom(input.parse()?))
-        } else {
-            let expr = input.parse();
+        } else if input.peek(custom_keywords::proper_align_val) {
+            let proper_align_val_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+            let comma = content.parse()?;
+            let align = parse_alignment(&content)?;
 
-            match expr {
-                Ok(expr) => Ok(Precondition::Boolean(Box::new(expr))),
-                Err(mut err) => {
-                    err.combine(Error::new(
-                        start_span,
-                        "expected `valid_ptr`, `proper_align`, a string literal or a boolean expression",
-                    ));
+            if content.is_empty() {
+                Ok(Precondition::ProperAlignVal {
+                    proper_align_val_keyword,
+                    parentheses,
+                    ident,
+                    _comma: comma,
+                    align,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::aligned_to) {
+            let aligned_to_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+            let comma = content.parse()?;
+            let align = parse_alignment(&content)?;
 
-                    Err(err)
-                }
+            if content.is_empty() {
+                Ok(Precondition::AlignedTo {
+                    aligned_to_keyword,
+                    parentheses,
+                    ident,
+                    _comma: comma,
+                    align,
+                })
+            } else {
+                Err(content.error("unexpected token"))
             }
-        }
-    }
-}
+        } else if input.peek(custom_keywords::non_null) {
+            let non_null_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
 
-impl Spanned for Precondition {
-    fn span(&self) -> Span {
-        match self {
-            Precondition::ValidPtr {
-                valid_ptr_keyword,
-                parentheses,
-                ..
-            } => valid_ptr_keyword
-                .span()
-                .join(parentheses.span)
-                .unwrap_or_else(|| valid_ptr_keyword.span()),
-            Precondition::ProperAlign {
-                proper_align_keyword,
-                parentheses,
-                ..
-            } => proper_align_keyword
-                .span()
-                .join(parentheses.span)
-                .unwrap_or_else(|| proper_align_keyword.span()),
-            Precondition::Boolean(expr) => expr.span(),
-            Precondition::Custom(lit) => lit.span(),
-        }
-    }
-}
+            if content.is_empty() {
+                Ok(Precondition::NonNull {
+                    non_null_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::nul_terminated) {
+            let nul_terminated_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
 
-impl Precondition {
-    /// Returns a unique id for each descriminant.
-    fn descriminant_id(&self) -> usize {
-        match self {
-            Precondition::ValidPtr { .. } => 0,
-            Precondition::ProperAlign { .. } => 1,
-            Precondition::Boolean(_) => 2,
-            Precondition::Custom(_) => 3,
-        }
-    }
-}
+            if content.is_empty() {
+                Ok(Precondition::NulTerminated {
+                    nul_terminated_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::valid_cstr) {
+            let valid_cstr_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
 
-// Define an order for the preconditions here.
-//
-// The exact ordering does not really matter, as long as it is deterministic.
-impl Ord for Precondition {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (
-                Precondition::ValidPtr {
-                    ident: ident_self, ..
-                },
-                Precondition::ValidPtr {
-                    ident: ident_other, ..
-                },
-            ) => ident_self.cmp(ident_other),
-            (
-                Precondition::ProperAlign {
-                    ident: ident_self, ..
-                },
-                Precondition::ProperAlign {
-                    ident: ident_other, ..
-                },
-            ) => ident_self.cmp(ident_other),
-            (Precondition::Boolean(expr_self), Precondition::Boolean(expr_other)) => {
-                quote!(#expr_self)
-                    .to_string()
-                    .cmp(&quote!(#expr_other).to_string())
+            if content.is_empty() {
+                Ok(Precondition::ValidCStr {
+                    valid_cstr_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
             }
-            (Precondition::Custom(lit_self), Precondition::Custom(lit_other)) => {
-                lit_self.value().cmp(&lit_other.value())
+        } else if input.peek(custom_keywords::initialized) {
+            let initialized_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+            let count = if content.peek(Token![,]) {
+                Some((content.parse()?, content.parse()?))
+            } else {
+                None
+            };
+
+            if content.is_empty() {
+                Ok(Precondition::Initialized {
+                    initialized_keyword,
+                    parentheses,
+                    ident,
+                    count,
+                })
+            } else {
+                Err(content.error("unexpected token"))
             }
-            _ => {
-                debug_assert_ne!(self.descriminant_id(), other.descriminant_id());
+        } else if input.peek(custom_keywords::non_overlapping) {
+            let non_overlapping_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let first = parse_precondition_ident(&content)?;
+            let first_comma = content.parse()?;
+            let second = parse_precondition_ident(&content)?;
+            let second_comma = content.parse()?;
+            let count = content.parse()?;
 
-                self.descriminant_id().cmp(&other.descriminant_id())
+            if content.is_empty() {
+                Ok(Precondition::NonOverlapping {
+                    non_overlapping_keyword,
+                    parentheses,
+                    first,
+                    _first_comma: first_comma,
+                    second,
+                    _second_comma: second_comma,
+                    count,
+                })
+            } else {
+                Err(content.error("unexpected token"))
             }
-        }
-    }
-}
+        } else if input.peek(custom_keywords::valid_slice) {
+            let valid_slice_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+            let first_comma = content.parse()?;
+            let len = content.parse()?;
+            let second_comma = content.parse()?;
+            let read_write = content.parse()?;
 
-impl PartialOrd for Precondition {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+            if content.is_empty() {
+                Ok(Precondition::ValidSlice {
+                    valid_slice_keyword,
+                    parentheses,
+                    ident,
+                    _first_comma: first_comma,
+                    len,
+                    _second_comma: second_comma,
+                    read_write,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::interrupts_disabled) && input.peek2(Paren) {
+            let interrupts_disabled_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+
+            if content.is_empty() {
+                Ok(Precondition::InterruptsDisabled {
+                    interrupts_disabled_keyword,
+                    parentheses,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::not_reentrant) && input.peek2(Paren) {
+            let not_reentrant_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+
+            if content.is_empty() {
+                Ok(Precondition::NotReentrant {
+                    not_reentrant_keyword,
+                    parentheses,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::dma_quiescent) {
+            let dma_quiescent_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+
+            if content.is_empty() {
+                Ok(Precondition::DmaQuiescent {
+                    dma_quiescent_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::lives_until_end_of_scope) {
+            let lives_until_end_of_scope_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+
+            if content.is_empty() {
+                Ok(Precondition::LivesUntilEndOfScope {
+                    lives_until_end_of_scope_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::in_range) {
+            let in_range_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+            let comma = content.parse()?;
+            let range_span = content.span();
+            let range = match content.parse()? {
+                Expr::Range(range) => range,
+                _ => return Err(Error::new(range_span, "expected a range expression")),
+            };
+
+            if range.from.is_none() && range.to.is_none() {
+                Err(Error::new(
+                    range_span,
+                    "the range must have at least one bound",
+                ))
+            } else if content.is_empty() {
+                Ok(Precondition::InRange {
+                    in_range_keyword,
+                    parentheses,
+                    ident,
+                    _comma: comma,
+                    range: Box::new(range),
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::valid_value) {
+            let valid_value_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+            let comma = content.parse()?;
+            let ty = content.parse()?;
+
+            if content.is_empty() {
+                Ok(Precondition::ValidValue {
+                    valid_value_keyword,
+                    parentheses,
+                    ident,
+                    _comma: comma,
+                    ty,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::layout_compatible) {
+            let layout_compatible_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let from = content.parse()?;
+            let comma = content.parse()?;
+            let to = content.parse()?;
+
+            if content.is_empty() {
+                Ok(Precondition::LayoutCompatible {
+                    layout_compatible_keyword,
+                    parentheses,
+                    from,
+                    _comma: comma,
+                    to,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::consumed) {
+            let consumed_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+
+            if content.is_empty() {
+                Ok(Precondition::Consumed {
+                    consumed_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::valid_utf8) {
+            let valid_utf8_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ident = parse_precondition_ident(&content)?;
+
+            if content.is_empty() {
+                Ok(Precondition::ValidUtf8 {
+                    valid_utf8_keyword,
+                    parentheses,
+                    ident,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::token) {
+            let token_keyword = input.parse()?;
+            let content;
+            let parentheses = parenthesized!(content in input);
+            let ty = content.parse()?;
+            let value = if content.peek(Token![=]) {
+                Some((content.parse()?, content.parse()?))
+            } else {
+                None
+            };
+
+            if content.is_empty() {
+                Ok(Precondition::Token {
+                    token_keyword,
+                    parentheses,
+                    ty,
+                    value,
+                })
+            } else {
+                Err(content.error("unexpected token"))
+            }
+        } else if input.peek(custom_keywords::export)
+            && input.peek2(Token![=])
+            && !input.peek2(Token![==])
+        {
+            let export_keyword = input.parse()?;
+            let eq = input.parse()?;
+            let ident = input.parse()?;
+            let comma = input.parse()?;
+            let text = input.parse()?;
+
+            Ok(Precondition::Exported {
+                export_keyword,
+                _eq: eq,
+                ident,
+                _comma: comma,
+                text,
+            })
+        } else if input.peek(Token![use]) {
+            let use_token = input.parse()?;
+            let path = input.call(Path::parse_mod_style)?;
+
+            Ok(Precondition::Imported { use_token, path })
+        } else if input.peek(LitStr) {
+            Ok(Precondition::Custom(input.parse()?))
+        } else if let Some(kind) = configured_kind(input) {
+            parse_configured(input, kind)
+        } else {
+            let expr = input.parse();
+
+            match expr {
+                Ok(expr) => Ok(Precondition::Boolean(Box::new(expr))),
+                Err(mut err) => {
+                    err.combine(Error::new(
+                        start_span,
+                        "expected `valid_ptr`, `proper_align`, `proper_align_val`, `aligned_to`, `non_null`, `nul_terminated`, `valid_cstr`, `initialized`, `non_overlapping`, `valid_slice`, `interrupts_disabled`, `not_reentrant`, `dma_quiescent`, `lives_until_end_of_scope`, `in_range`, `valid_value`, `layout_compatible`, `consumed`, `valid_utf8`, `token`, `export`, `use`, a string literal or a boolean expression",
+                    ));
+
+                    Err(err)
+                }
+            }
+        }
+    }
+}
+
+/// Parses the alignment of an alignment precondition.
+///
+/// Integer literals are checked to be a power of two.
+fn parse_alignment(input: ParseStream) -> syn::Result<Box<Expr>> {
+    let align: Box<Expr> = input.parse()?;
+
+    if let Expr::Lit(ExprLit {
+        lit: Lit::Int(lit), ..
+    }) = &*align
+    {
+        if !lit.base10_parse::<u128>()?.is_power_of_two() {
+            return Err(Error::new(
+                lit.span(),
+                "the alignment must be a power of two",
+            ));
+        }
+    }
+
+    Ok(align)
+}
+
+/// Returns the configured kind of the precondition starting at the input, if there is one.
+fn configured_kind(input: ParseStream) -> Option<&'static ConditionKind> {
+    if !input.peek2(Paren) {
+        return None;
+    }
+
+    let ident = input.fork().parse::<Ident>().ok()?;
+
+    config::condition_kind(&ident.to_string())
+}
+
+/// Parses a precondition of the given configured kind.
+fn parse_configured(input: ParseStream, kind: &'static ConditionKind) -> syn::Result<Precondition> {
+    let keyword: Ident = input.parse()?;
+    let content;
+    let parentheses = parenthesized!(content in input);
+    let args = content.parse_terminated(parse_precondition_ident)?;
+
+    if args.len() != kind.args.len() {
+        return Err(Error::new(
+            parentheses.span,
+            format!(
+                "`{}` expects {} argument{}: `{}({})`",
+                keyword,
+                kind.args.len(),
+                if kind.args.len() == 1 { "" } else { "s" },
+                keyword,
+                kind.args.join(", ")
+            ),
+        ));
+    }
+
+    Ok(Precondition::Configured {
+        keyword,
+        parentheses,
+        args,
+        kind,
+    })
+}
+
+impl Spanned for Precondition {
+    fn span(&self) -> Span {
+        match self {
+            Precondition::ValidPtr {
+                valid_ptr_keyword,
+                parentheses,
+                ..
+            } => valid_ptr_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| valid_ptr_keyword.span()),
+            Precondition::ProperAlign {
+                proper_align_keyword,
+                parentheses,
+                ..
+            } => proper_align_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| proper_align_keyword.span()),
+            Precondition::ProperAlignVal {
+                proper_align_val_keyword,
+                parentheses,
+                ..
+            } => proper_align_val_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| proper_align_val_keyword.span()),
+            Precondition::AlignedTo {
+                aligned_to_keyword,
+                parentheses,
+                ..
+            } => aligned_to_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| aligned_to_keyword.span()),
+            Precondition::NonNull {
+                non_null_keyword,
+                parentheses,
+                ..
+            } => non_null_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| non_null_keyword.span()),
+            Precondition::NulTerminated {
+                nul_terminated_keyword,
+                parentheses,
+                ..
+            } => nul_terminated_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| nul_terminated_keyword.span()),
+            Precondition::ValidCStr {
+                valid_cstr_keyword,
+                parentheses,
+                ..
+            } => valid_cstr_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| valid_cstr_keyword.span()),
+            Precondition::Initialized {
+                initialized_keyword,
+                parentheses,
+                ..
+            } => initialized_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| initialized_keyword.span()),
+            Precondition::NonOverlapping {
+                non_overlapping_keyword,
+                parentheses,
+                ..
+            } => non_overlapping_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| non_overlapping_keyword.span()),
+            Precondition::ValidSlice {
+                valid_slice_keyword,
+                parentheses,
+                ..
+            } => valid_slice_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| valid_slice_keyword.span()),
+            Precondition::InterruptsDisabled {
+                interrupts_disabled_keyword,
+                parentheses,
+            } => interrupts_disabled_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| interrupts_disabled_keyword.span()),
+            Precondition::NotReentrant {
+                not_reentrant_keyword,
+                parentheses,
+            } => not_reentrant_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| not_reentrant_keyword.span()),
+            Precondition::DmaQuiescent {
+                dma_quiescent_keyword,
+                parentheses,
+                ..
+            } => dma_quiescent_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| dma_quiescent_keyword.span()),
+            Precondition::LivesUntilEndOfScope {
+                lives_until_end_of_scope_keyword,
+                parentheses,
+                ..
+            } => lives_until_end_of_scope_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| lives_until_end_of_scope_keyword.span()),
+            Precondition::InRange {
+                in_range_keyword,
+                parentheses,
+                ..
+            } => in_range_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| in_range_keyword.span()),
+            Precondition::ValidValue {
+                valid_value_keyword,
+                parentheses,
+                ..
+            } => valid_value_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| valid_value_keyword.span()),
+            Precondition::LayoutCompatible {
+                layout_compatible_keyword,
+                parentheses,
+                ..
+            } => layout_compatible_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| layout_compatible_keyword.span()),
+            Precondition::Consumed {
+                consumed_keyword,
+                parentheses,
+                ..
+            } => consumed_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| consumed_keyword.span()),
+            Precondition::ValidUtf8 {
+                valid_utf8_keyword,
+                parentheses,
+                ..
+            } => valid_utf8_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| valid_utf8_keyword.span()),
+            Precondition::Token {
+                token_keyword,
+                parentheses,
+                ..
+            } => token_keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| token_keyword.span()),
+            Precondition::Exported {
+                export_keyword,
+                text,
+                ..
+            } => export_keyword
+                .span()
+                .join(text.span())
+                .unwrap_or_else(|| export_keyword.span()),
+            Precondition::Imported { use_token, path } => use_token
+                .span()
+                .join(path.span())
+                .unwrap_or_else(|| use_token.span()),
+            Precondition::Configured {
+                keyword,
+                parentheses,
+                ..
+            } => keyword
+                .span()
+                .join(parentheses.span)
+                .unwrap_or_else(|| keyword.span()),
+            Precondition::Boolean(expr) => expr.span(),
+            Precondition::Custom(lit) => lit.span(),
+        }
+    }
+}
+
+impl Precondition {
+    /// Expands a composite precondition into the preconditions it consists of.
+    ///
+    /// `valid_slice(data, len, r)` expands to
+    ///
+    /// - `valid_ptr(data, r, len)`,
+    /// - `proper_align(data)`,
+    /// - `initialized(data, len)` and
+    /// - `::pre::slice_len_fits(data, len)`, which checks that the slice is at most `isize::MAX`
+    ///   bytes large.
+    ///
+    /// All other preconditions are returned unchanged.
+    pub(crate) fn expand(self) -> Vec<Precondition> {
+        match self {
+            Precondition::ValidSlice {
+                valid_slice_keyword,
+                parentheses,
+                ident,
+                len,
+                read_write,
+                ..
+            } => {
+                let span = valid_slice_keyword.span;
+                let crate_name = Ident::new(&CRATE_NAME, span);
+
+                vec![
+                    Precondition::ValidPtr {
+                        valid_ptr_keyword: custom_keywords::valid_ptr(span),
+                        parentheses,
+                        ident: ident.clone(),
+                        _comma: Token![,](span),
+                        read_write,
+                        count: Some((Token![,](span), len.clone())),
+                    },
+                    Precondition::ProperAlign {
+                        proper_align_keyword: custom_keywords::proper_align(span),
+                        parentheses,
+                        ident: ident.clone(),
+                    },
+                    Precondition::Initialized {
+                        initialized_keyword: custom_keywords::initialized(span),
+                        parentheses,
+                        ident: ident.clone(),
+                        count: Some((Token![,](span), len.clone())),
+                    },
+                    Precondition::Boolean(parse_quote_spanned! { span=>
+                        ::#crate_name::slice_len_fits(#ident, #len)
+                    }),
+                ]
+            }
+            precondition => vec![precondition],
+        }
+    }
+
+    /// Returns a unique id for each descriminant.
+    ///
+    /// The ids determine the order of the different kinds of preconditions in the encoding.
+    fn descriminant_id(&self) -> usize {
+        match self {
+            Precondition::ValidPtr { .. } => 0,
+            Precondition::ProperAlign { .. } => 1,
+            Precondition::Boolean(_) => 2,
+            // Configured conditions are encoded like custom conditions with their description.
+            Precondition::Custom(_) | Precondition::Configured { .. } => 3,
+            Precondition::Token { .. } => 4,
+            Precondition::NonNull { .. } => 5,
+            Precondition::InRange { .. } => 6,
+            // Exported and imported conditions need to compare equal to each other.
+            Precondition::Exported { .. } | Precondition::Imported { .. } => 7,
+            Precondition::ValidValue { .. } => 8,
+            Precondition::Consumed { .. } => 9,
+            Precondition::ProperAlignVal { .. } => 10,
+            Precondition::NulTerminated { .. } => 11,
+            Precondition::ValidCStr { .. } => 12,
+            Precondition::InterruptsDisabled { .. } => 13,
+            Precondition::DmaQuiescent { .. } => 14,
+            Precondition::LivesUntilEndOfScope { .. } => 15,
+            Precondition::LayoutCompatible { .. } => 16,
+            Precondition::Initialized { .. } => 17,
+            Precondition::NonOverlapping { .. } => 18,
+            Precondition::ValidSlice { .. } => 19,
+            Precondition::NotReentrant { .. } => 20,
+            Precondition::ValidUtf8 { .. } => 21,
+            Precondition::AlignedTo { .. } => 22,
+        }
+    }
+
+    /// Returns the key determining the position of the precondition in the generated encoding.
+    ///
+    /// The key consists of the rank of the kind of the precondition and up to two strings, which
+    /// are the arguments of the condition type used on the nightly compiler. Sorting by it orders
+    /// preconditions like the `Ord` implementation does, but additionally orders `valid_ptr`
+    /// preconditions for the same pointer by their access type.
+    ///
+    /// This order is part of the public interface and must agree with
+    /// `pre::encoding::ConditionKey`.
+    pub(crate) fn encoding_key(&self) -> (usize, String, String) {
+        let (first, second) = match self {
+            Precondition::ValidPtr {
+                ident,
+                read_write,
+                count,
+                ..
+            } => (ident.to_string(), read_write.encoding(count.as_ref())),
+            Precondition::ProperAlign { ident, .. }
+            | Precondition::NonNull { ident, .. }
+            | Precondition::NulTerminated { ident, .. }
+            | Precondition::ValidCStr { ident, .. }
+            | Precondition::DmaQuiescent { ident, .. }
+            | Precondition::LivesUntilEndOfScope { ident, .. }
+            | Precondition::Consumed { ident, .. }
+            | Precondition::ValidUtf8 { ident, .. } => (ident.to_string(), String::new()),
+            Precondition::InterruptsDisabled { .. } | Precondition::NotReentrant { .. } => {
+                (String::new(), String::new())
+            }
+            Precondition::Initialized { ident, count, .. } => (
+                ident.to_string(),
+                count
+                    .as_ref()
+                    .map(|(_, count)| quote!(#count).to_string())
+                    .unwrap_or_default(),
+            ),
+            Precondition::NonOverlapping {
+                first,
+                second,
+                count,
+                ..
+            } => (first.to_string(), format!("{}, {}", second, quote!(#count))),
+            Precondition::ValidSlice {
+                ident,
+                len,
+                read_write,
+                ..
+            } => (
+                ident.to_string(),
+                format!("{}, {}", quote!(#len), read_write),
+            ),
+            Precondition::InRange { ident, range, .. } => {
+                (ident.to_string(), quote!(#range).to_string())
+            }
+            Precondition::ProperAlignVal { ident, align, .. }
+            | Precondition::AlignedTo { ident, align, .. } => {
+                (ident.to_string(), quote!(#align).to_string())
+            }
+            Precondition::ValidValue { ident, ty, .. } => {
+                (ident.to_string(), quote!(#ty).to_string())
+            }
+            Precondition::LayoutCompatible { from, to, .. } => {
+                (quote!(#from).to_string(), quote!(#to).to_string())
+            }
+            Precondition::Token { ty, .. } => (quote!(#ty).to_string(), String::new()),
+            Precondition::Boolean(expr) => (quote!(#expr).to_string(), String::new()),
+            Precondition::Custom(_) | Precondition::Configured { .. } => {
+                (self.custom_text().unwrap_or_default(), String::new())
+            }
+            Precondition::Exported { .. } | Precondition::Imported { .. } => (
+                self.condition_name()
+                    .map(Ident::to_string)
+                    .unwrap_or_default(),
+                String::new(),
+            ),
+        };
+
+        (self.descriminant_id(), first, second)
+    }
+
+    /// Returns the text that the precondition is encoded with, if it is a custom or configured
+    /// precondition.
+    pub(crate) fn custom_text(&self) -> Option<String> {
+        match self {
+            Precondition::Custom(lit) => Some(lit.value()),
+            Precondition::Configured { args, kind, .. } => {
+                Some(kind.description(&args.iter().map(Ident::to_string).collect::<Vec<_>>()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a boolean expression checking the precondition, if it is a configured precondition
+    /// with a configured check.
+    pub(crate) fn configured_check(&self) -> Option<Expr> {
+        match self {
+            Precondition::Configured { args, kind, .. } => {
+                kind.check(&args.iter().map(Ident::to_string).collect::<Vec<_>>())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the condition, if it is exported or imported.
+    pub(crate) fn condition_name(&self) -> Option<&Ident> {
+        match self {
+            Precondition::Exported { ident, .. } => Some(ident),
+            Precondition::Imported { path, .. } => path.segments.last().map(|seg| &seg.ident),
+            _ => None,
+        }
+    }
+}
+
+// Define an order for the preconditions here.
+//
+// The preconditions are encoded in this order on the nightly compiler, so it is part of the public
+// interface: changing it breaks calls between crates using different versions of pre. See
+// `Precondition::encoding_key` for the order used when generating code.
+impl Ord for Precondition {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (
+                Precondition::ValidPtr {
+                    ident: ident_self,
+                    count: count_self,
+                    ..
+                },
+                Precondition::ValidPtr {
+                    ident: ident_other,
+                    count: count_other,
+                    ..
+                },
+            ) => ident_self.cmp(ident_other).then_with(|| {
+                let count_self = count_self
+                    .as_ref()
+                    .map(|(_, count)| quote!(#count).to_string());
+                let count_other = count_other
+                    .as_ref()
+                    .map(|(_, count)| quote!(#count).to_string());
+
+                count_self.cmp(&count_other)
+            }),
+            (
+                Precondition::ProperAlign {
+                    ident: ident_self, ..
+                },
+                Precondition::ProperAlign {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::NonNull {
+                    ident: ident_self, ..
+                },
+                Precondition::NonNull {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::NulTerminated {
+                    ident: ident_self, ..
+                },
+                Precondition::NulTerminated {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::ValidCStr {
+                    ident: ident_self, ..
+                },
+                Precondition::ValidCStr {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::Initialized {
+                    ident: ident_self,
+                    count: count_self,
+                    ..
+                },
+                Precondition::Initialized {
+                    ident: ident_other,
+                    count: count_other,
+                    ..
+                },
+            ) => ident_self.cmp(ident_other).then_with(|| {
+                let count_self = count_self
+                    .as_ref()
+                    .map(|(_, count)| quote!(#count).to_string());
+                let count_other = count_other
+                    .as_ref()
+                    .map(|(_, count)| quote!(#count).to_string());
+
+                count_self.cmp(&count_other)
+            }),
+            (
+                Precondition::NonOverlapping {
+                    first: first_self,
+                    second: second_self,
+                    count: count_self,
+                    ..
+                },
+                Precondition::NonOverlapping {
+                    first: first_other,
+                    second: second_other,
+                    count: count_other,
+                    ..
+                },
+            ) => first_self
+                .cmp(first_other)
+                .then_with(|| second_self.cmp(second_other))
+                .then_with(|| {
+                    quote!(#count_self)
+                        .to_string()
+                        .cmp(&quote!(#count_other).to_string())
+                }),
+            (
+                Precondition::ValidSlice {
+                    ident: ident_self,
+                    len: len_self,
+                    read_write: read_write_self,
+                    ..
+                },
+                Precondition::ValidSlice {
+                    ident: ident_other,
+                    len: len_other,
+                    read_write: read_write_other,
+                    ..
+                },
+            ) => ident_self
+                .cmp(ident_other)
+                .then_with(|| {
+                    quote!(#len_self)
+                        .to_string()
+                        .cmp(&quote!(#len_other).to_string())
+                })
+                .then_with(|| {
+                    read_write_self
+                        .to_string()
+                        .cmp(&read_write_other.to_string())
+                }),
+            (Precondition::InterruptsDisabled { .. }, Precondition::InterruptsDisabled { .. }) => {
+                Ordering::Equal
+            }
+            (Precondition::NotReentrant { .. }, Precondition::NotReentrant { .. }) => {
+                Ordering::Equal
+            }
+            (
+                Precondition::DmaQuiescent {
+                    ident: ident_self, ..
+                },
+                Precondition::DmaQuiescent {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::LivesUntilEndOfScope {
+                    ident: ident_self, ..
+                },
+                Precondition::LivesUntilEndOfScope {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::InRange {
+                    ident: ident_self,
+                    range: range_self,
+                    ..
+                },
+                Precondition::InRange {
+                    ident: ident_other,
+                    range: range_other,
+                    ..
+                },
+            ) => ident_self.cmp(ident_other).then_with(|| {
+                quote!(#range_self)
+                    .to_string()
+                    .cmp(&quote!(#range_other).to_string())
+            }),
+            (
+                Precondition::ProperAlignVal {
+                    ident: ident_self,
+                    align: align_self,
+                    ..
+                },
+                Precondition::ProperAlignVal {
+                    ident: ident_other,
+                    align: align_other,
+                    ..
+                },
+            )
+            | (
+                Precondition::AlignedTo {
+                    ident: ident_self,
+                    align: align_self,
+                    ..
+                },
+                Precondition::AlignedTo {
+                    ident: ident_other,
+                    align: align_other,
+                    ..
+                },
+            ) => ident_self.cmp(ident_other).then_with(|| {
+                quote!(#align_self)
+                    .to_string()
+                    .cmp(&quote!(#align_other).to_string())
+            }),
+            (
+                Precondition::Consumed {
+                    ident: ident_self, ..
+                },
+                Precondition::Consumed {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::ValidUtf8 {
+                    ident: ident_self, ..
+                },
+                Precondition::ValidUtf8 {
+                    ident: ident_other, ..
+                },
+            ) => ident_self.cmp(ident_other),
+            (
+                Precondition::ValidValue {
+                    ident: ident_self,
+                    ty: ty_self,
+                    ..
+                },
+                Precondition::ValidValue {
+                    ident: ident_other,
+                    ty: ty_other,
+                    ..
+                },
+            ) => ident_self.cmp(ident_other).then_with(|| {
+                quote!(#ty_self)
+                    .to_string()
+                    .cmp(&quote!(#ty_other).to_string())
+            }),
+            (
+                Precondition::LayoutCompatible {
+                    from: from_self,
+                    to: to_self,
+                    ..
+                },
+                Precondition::LayoutCompatible {
+                    from: from_other,
+                    to: to_other,
+                    ..
+                },
+            ) => quote!(#from_self)
+                .to_string()
+                .cmp(&quote!(#from_other).to_string())
+                .then_with(|| {
+                    quote!(#to_self)
+                        .to_string()
+                        .cmp(&quote!(#to_other).to_string())
+                }),
+            (Precondition::Token { ty: ty_self, .. }, Precondition::Token { ty: ty_other, .. }) => {
+                quote!(#ty_self)
+                    .to_string()
+                    .cmp(&quote!(#ty_other).to_string())
+            }
+            (Precondition::Boolean(expr_self), Precondition::Boolean(expr_other)) => {
+                quote!(#expr_self)
+                    .to_string()
+                    .cmp(&quote!(#expr_other).to_string())
+            }
+            (
+                Precondition::Custom(_) | Precondition::Configured { .. },
+                Precondition::Custom(_) | Precondition::Configured { .. },
+            ) => self.custom_text().cmp(&other.custom_text()),
+            (
+                Precondition::Exported { .. } | Precondition::Imported { .. },
+                Precondition::Exported { .. } | Precondition::Imported { .. },
+            ) => self.condition_name().cmp(&other.condition_name()),
+            _ => {
+                debug_assert_ne!(self.descriminant_id(), other.descriminant_id());
+
+                self.descriminant_id().cmp(&other.descriminant_id())
+            }
+        }
+    }
+}
+
+impl PartialOrd for Precondition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
 impl PartialEq for Precondition {
     fn eq(&self, other: &Self) -> bool {
@@ -264,144 +1646,505 @@ impl ReadWrite {
             ReadWrite::Both { .. } => "reads and writes",
         }
     }
-}
 
-impl fmt::Display for ReadWrite {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            ReadWrite::Read { .. } => write!(f, "r"),
-            ReadWrite::Write { .. } => write!(f, "w"),
-            ReadWrite::Both { .. } => write!(f, "r+w"),
-        }
+    /// The access type argument of the condition type encoding a `valid_ptr` precondition.
+    ///
+    /// The element count follows the access type, separated by a comma, if it is given.
+    pub(crate) fn encoding(&self, count: Option<&(Token![,], Box<Expr>)>) -> String {
+        match count {
+            Some((_, count)) => format!("{}, {}", self, quote! { #count }),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ReadWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadWrite::Read { .. } => write!(f, "r"),
+            ReadWrite::Write { .. } => write!(f, "w"),
+            ReadWrite::Both { .. } => write!(f, "r+w"),
+        }
+    }
+}
+
+impl Parse for ReadWrite {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(custom_keywords::w) {
+            Ok(ReadWrite::Write {
+                w_keyword: input.parse()?,
+            })
+        } else if lookahead.peek(custom_keywords::r) {
+            let r_keyword = input.parse()?;
+
+            if input.peek(Token![+]) {
+                let plus = input.parse()?;
+                let w_keyword = input.parse()?;
+
+                Ok(ReadWrite::Both {
+                    r_keyword,
+                    _plus: plus,
+                    w_keyword,
+                })
+            } else {
+                Ok(ReadWrite::Read { r_keyword })
+            }
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl Spanned for ReadWrite {
+    fn span(&self) -> Span {
+        match self {
+            ReadWrite::Read { r_keyword } => r_keyword.span,
+            ReadWrite::Write { w_keyword } => w_keyword.span,
+            ReadWrite::Both {
+                r_keyword,
+                w_keyword,
+                ..
+            } => r_keyword
+                .span
+                .join(w_keyword.span)
+                .unwrap_or(r_keyword.span),
+        }
+    }
+}
+
+/// A precondition with an optional `cfg` applying to it.
+#[derive(Clone)]
+pub(crate) struct CfgPrecondition {
+    /// The precondition with additional data.
+    pub(crate) precondition: Precondition,
+    /// The `cfg` applying to the precondition.
+    #[allow(dead_code)]
+    pub(crate) cfg: Option<TokenStream>,
+    /// The span best representing the precondition.
+    pub(crate) span: Span,
+}
+
+impl CfgPrecondition {
+    /// The raw precondition.
+    pub(crate) fn precondition(&self) -> &Precondition {
+        &self.precondition
+    }
+
+    /// Expands a composite precondition, keeping the `cfg` and the span of the original.
+    pub(crate) fn expand(self) -> Vec<CfgPrecondition> {
+        let CfgPrecondition {
+            precondition,
+            cfg,
+            span,
+        } = self;
+
+        precondition
+            .expand()
+            .into_iter()
+            .map(|precondition| CfgPrecondition {
+                precondition,
+                cfg: cfg.clone(),
+                span,
+            })
+            .collect()
+    }
+}
+
+impl Spanned for CfgPrecondition {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Sorts the preconditions in the order they are encoded in.
+///
+/// The order does not depend on the order the preconditions were written in, so that all crates
+/// generate the same encoding for the same preconditions.
+pub(crate) fn sort_for_encoding(preconditions: &mut [CfgPrecondition]) {
+    preconditions.sort_by_cached_key(|precondition| precondition.precondition().encoding_key());
+}
+
+impl PartialEq for CfgPrecondition {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.cmp(other), Ordering::Equal)
+    }
+}
+
+impl Eq for CfgPrecondition {}
+
+impl PartialOrd for CfgPrecondition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CfgPrecondition {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.precondition.cmp(&other.precondition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{parse::Parser, parse2};
+
+    use super::*;
+
+    #[test]
+    fn parse_correct_custom() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            "foo"
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn parse_correct_valid_ptr() {
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_ptr(foo, r)
+            });
+            assert!(result.is_ok());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_ptr(foo, r+w)
+            });
+            assert!(result.is_ok());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_ptr(foo, w)
+            });
+            assert!(result.is_ok());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_ptr(foo, r, count * 2)
+            });
+            assert_eq!(
+                result.expect("valid precondition").to_string(),
+                "valid_ptr(foo, r, count * 2)"
+            );
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_ptr(foo, r, count, 2)
+            });
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn valid_ptr_counts_are_compared() {
+        let parse = |tokens| parse2::<Precondition>(tokens).expect("valid precondition");
+
+        assert!(parse(quote! { valid_ptr(p, r, len) }) == parse(quote! { valid_ptr(p, r, len) }));
+        assert!(parse(quote! { valid_ptr(p, r, len) }) != parse(quote! { valid_ptr(p, r, n) }));
+        assert!(parse(quote! { valid_ptr(p, r, len) }) != parse(quote! { valid_ptr(p, r) }));
+    }
+
+    #[test]
+    fn parse_correct_non_null() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            non_null(foo)
+        });
+        assert!(matches!(result, Ok(Precondition::NonNull { .. })));
+    }
+
+    #[test]
+    fn parse_correct_c_strings() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            nul_terminated(foo)
+        });
+        assert!(matches!(result, Ok(Precondition::NulTerminated { .. })));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            valid_cstr(foo)
+        });
+        assert!(matches!(result, Ok(Precondition::ValidCStr { .. })));
+    }
+
+    #[test]
+    fn parse_correct_initialized() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            initialized(foo)
+        });
+        assert!(matches!(
+            result,
+            Ok(Precondition::Initialized { count: None, .. })
+        ));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            initialized(foo, len * 2)
+        });
+        assert!(matches!(
+            result,
+            Ok(Precondition::Initialized { count: Some(_), .. })
+        ));
+    }
+
+    #[test]
+    fn parse_correct_non_overlapping() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            non_overlapping(src, dst, count * 2)
+        });
+        assert!(matches!(result, Ok(Precondition::NonOverlapping { .. })));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            non_overlapping(self, dest, count)
+        });
+        assert!(matches!(result, Ok(Precondition::NonOverlapping { .. })));
+    }
+
+    #[test]
+    fn parse_correct_valid_slice() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            valid_slice(data, len, r)
+        });
+        assert!(matches!(result, Ok(Precondition::ValidSlice { .. })));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            valid_slice(data, len * 2, r+w)
+        });
+        assert!(matches!(result, Ok(Precondition::ValidSlice { .. })));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            valid_slice(data, r)
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_correct_embedded() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            interrupts_disabled()
+        });
+        assert!(matches!(
+            result,
+            Ok(Precondition::InterruptsDisabled { .. })
+        ));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            dma_quiescent(buf)
+        });
+        assert!(matches!(result, Ok(Precondition::DmaQuiescent { .. })));
+
+        // Without parentheses, this is a boolean precondition, which is reported when used.
+        let result: Result<Precondition, _> = parse2(quote! {
+            interrupts_disabled
+        });
+        assert!(matches!(result, Ok(Precondition::Boolean(_))));
+
+        assert!(parse2::<Precondition>(quote! { interrupts_disabled(cpu) }).is_err());
+        assert!(parse2::<Precondition>(quote! { dma_quiescent() }).is_err());
     }
-}
 
-impl Parse for ReadWrite {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let lookahead = input.lookahead1();
+    #[test]
+    fn parse_correct_not_reentrant() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            not_reentrant()
+        });
+        assert!(matches!(result, Ok(Precondition::NotReentrant { .. })));
 
-        if lookahead.peek(custom_keywords::w) {
-            Ok(ReadWrite::Write {
-                w_keyword: input.parse()?,
-            })
-        } else if lookahead.peek(custom_keywords::r) {
-            let r_keyword = input.parse()?;
+        assert!(parse2::<Precondition>(quote! { not_reentrant(callback) }).is_err());
+    }
 
-            if input.peek(Token![+]) {
-                let plus = input.parse()?;
-                let w_keyword = input.parse()?;
+    #[test]
+    fn parse_correct_lives_until_end_of_scope() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            lives_until_end_of_scope(ptr)
+        });
+        assert!(matches!(
+            result,
+            Ok(Precondition::LivesUntilEndOfScope { .. })
+        ));
 
-                Ok(ReadWrite::Both {
-                    r_keyword,
-                    _plus: plus,
-                    w_keyword,
-                })
-            } else {
-                Ok(ReadWrite::Read { r_keyword })
-            }
-        } else {
-            Err(lookahead.error())
-        }
+        assert!(parse2::<Precondition>(quote! { lives_until_end_of_scope() }).is_err());
+        assert!(parse2::<Precondition>(quote! { lives_until_end_of_scope(a, b) }).is_err());
     }
-}
 
-impl Spanned for ReadWrite {
-    fn span(&self) -> Span {
-        match self {
-            ReadWrite::Read { r_keyword } => r_keyword.span,
-            ReadWrite::Write { w_keyword } => w_keyword.span,
-            ReadWrite::Both {
-                r_keyword,
-                w_keyword,
-                ..
-            } => r_keyword
-                .span
-                .join(w_keyword.span)
-                .unwrap_or(r_keyword.span),
-        }
+    #[test]
+    fn parse_correct_proper_align_val() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            proper_align_val(foo, 32)
+        });
+        assert!(matches!(result, Ok(Precondition::ProperAlignVal { .. })));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            proper_align_val(foo, ALIGN)
+        });
+        assert!(matches!(result, Ok(Precondition::ProperAlignVal { .. })));
     }
-}
 
-/// A precondition with an optional `cfg` applying to it.
-pub(crate) struct CfgPrecondition {
-    /// The precondition with additional data.
-    pub(crate) precondition: Precondition,
-    /// The `cfg` applying to the precondition.
-    #[allow(dead_code)]
-    pub(crate) cfg: Option<TokenStream>,
-    /// The span best representing the precondition.
-    pub(crate) span: Span,
-}
+    #[test]
+    fn parse_correct_aligned_to() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            aligned_to(foo, 4096)
+        });
+        assert!(matches!(result, Ok(Precondition::AlignedTo { .. })));
 
-impl CfgPrecondition {
-    /// The raw precondition.
-    pub(crate) fn precondition(&self) -> &Precondition {
-        &self.precondition
+        let result: Result<Precondition, _> = parse2(quote! {
+            aligned_to(foo, layout.align())
+        });
+        assert!(matches!(result, Ok(Precondition::AlignedTo { .. })));
     }
-}
 
-impl Spanned for CfgPrecondition {
-    fn span(&self) -> Span {
-        self.span
+    #[test]
+    fn parse_correct_in_range() {
+        for range in [quote! { 0..=isize::MAX }, quote! { ..len }, quote! { 1.. }].iter() {
+            let result: Result<Precondition, _> = parse2(quote! {
+                in_range(foo, #range)
+            });
+            assert!(matches!(result, Ok(Precondition::InRange { .. })));
+        }
     }
-}
 
-impl PartialEq for CfgPrecondition {
-    fn eq(&self, other: &Self) -> bool {
-        matches!(self.cmp(other), Ordering::Equal)
+    #[test]
+    fn parse_wrong_in_range() {
+        for range in [quote! { .. }, quote! { 17 }, quote! { 0..5, bar }].iter() {
+            let result: Result<Precondition, _> = parse2(quote! {
+                in_range(foo, #range)
+            });
+            assert!(result.is_err());
+        }
     }
-}
 
-impl Eq for CfgPrecondition {}
+    #[test]
+    fn in_range_order() {
+        let parse = |tokens| parse2::<Precondition>(tokens).expect("valid precondition");
 
-impl PartialOrd for CfgPrecondition {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+        let a = parse(quote! { in_range(a, 5..10) });
+        let b = parse(quote! { in_range(a, 0..=isize::MAX) });
+        let c = parse(quote! { in_range(b, 0..1) });
+
+        assert!(b < a);
+        assert!(a < c);
+        assert!(a == parse(quote! { in_range(a, 5..10) }));
     }
-}
 
-impl Ord for CfgPrecondition {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.precondition.cmp(&other.precondition)
+    #[test]
+    fn parse_correct_valid_value() {
+        for ty in [quote! { char }, quote! { U }, quote! { Option<&'a T> }].iter() {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_value(foo, #ty)
+            });
+            assert!(matches!(result, Ok(Precondition::ValidValue { .. })));
+        }
+
+        for args in [quote! { foo }, quote! { foo, 17 }, quote! { foo, u8, u16 }].iter() {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_value(#args)
+            });
+            assert!(result.is_err());
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use quote::quote;
-    use syn::parse2;
+    #[test]
+    fn parse_correct_consumed() {
+        let result: Result<Precondition, _> = parse2(quote! {
+            consumed(foo)
+        });
+        assert!(matches!(result, Ok(Precondition::Consumed { .. })));
 
-    use super::*;
+        let result: Result<Precondition, _> = parse2(quote! {
+            consumed(foo, bar)
+        });
+        assert!(result.is_err());
+    }
 
     #[test]
-    fn parse_correct_custom() {
+    fn parse_correct_valid_utf8() {
         let result: Result<Precondition, _> = parse2(quote! {
-            "foo"
+            valid_utf8(bytes)
         });
-        assert!(result.is_ok());
+        assert!(matches!(result, Ok(Precondition::ValidUtf8 { .. })));
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            valid_utf8(bytes, len)
+        });
+        assert!(result.is_err());
     }
 
     #[test]
-    fn parse_correct_valid_ptr() {
+    fn valid_value_order() {
+        let parse = |tokens| parse2::<Precondition>(tokens).expect("valid precondition");
+
+        let a = parse(quote! { valid_value(a, char) });
+        let b = parse(quote! { valid_value(a, bool) });
+        let c = parse(quote! { valid_value(b, bool) });
+
+        assert!(b < a);
+        assert!(a < c);
+        assert!(a == parse(quote! { valid_value(a, char) }));
+        assert!(a != parse(quote! { in_range(a, 0..1) }));
+    }
+
+    #[test]
+    fn parse_correct_layout_compatible() {
+        for args in [
+            quote! { u32, [u8; 4] },
+            quote! { T, U },
+            quote! { &'a [u8], *const Header<T> },
+        ]
+        .iter()
         {
             let result: Result<Precondition, _> = parse2(quote! {
-                valid_ptr(foo, r)
+                layout_compatible(#args)
             });
-            assert!(result.is_ok());
+            assert!(matches!(result, Ok(Precondition::LayoutCompatible { .. })));
+        }
+
+        for args in [quote! { T }, quote! { T, 17 }, quote! { T, U, V }].iter() {
+            let result: Result<Precondition, _> = parse2(quote! {
+                layout_compatible(#args)
+            });
+            assert!(result.is_err());
         }
+    }
+
+    #[test]
+    fn layout_compatible_order() {
+        let parse = |tokens| parse2::<Precondition>(tokens).expect("valid precondition");
 
+        let a = parse(quote! { layout_compatible(T, u8) });
+        let b = parse(quote! { layout_compatible(T, U) });
+        let c = parse(quote! { layout_compatible(U, u8) });
+
+        assert!(b < a);
+        assert!(a < c);
+        assert!(a == parse(quote! { layout_compatible(T, u8) }));
+        assert!(a != parse(quote! { layout_compatible(u8, T) }));
+        assert!(a != parse(quote! { valid_value(T, u8) }));
+    }
+
+    #[test]
+    fn parse_correct_token() {
         {
             let result: Result<Precondition, _> = parse2(quote! {
-                valid_ptr(foo, r+w)
+                token(InitToken)
             });
-            assert!(result.is_ok());
+            assert!(matches!(
+                result,
+                Ok(Precondition::Token { value: None, .. })
+            ));
         }
 
         {
             let result: Result<Precondition, _> = parse2(quote! {
-                valid_ptr(foo, w)
+                token(&InitToken = &init_token)
             });
-            assert!(result.is_ok());
+            assert!(matches!(
+                result,
+                Ok(Precondition::Token { value: Some(_), .. })
+            ));
         }
     }
 
@@ -437,5 +2180,253 @@ mod tests {
             });
             assert!(result.is_err());
         }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                non_null(foo, bar)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                valid_cstr(foo, 16)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                initialized(foo, 16, 32)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                non_overlapping(foo, bar)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                non_overlapping(foo, bar, 16, 32)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                proper_align_val(foo)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                proper_align_val(foo, 24)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                aligned_to(foo, 24)
+            });
+            assert!(result.is_err());
+        }
+
+        {
+            let result: Result<Precondition, _> = parse2(quote! {
+                token(InitToken = init_token, foo)
+            });
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn parse_correct_named() {
+        let exported = parse2::<Precondition>(quote! {
+            export = COND_INIT, "the library is initialized"
+        })
+        .expect("valid precondition");
+        let imported = parse2::<Precondition>(quote! {
+            use other_crate::conditions::COND_INIT
+        })
+        .expect("valid precondition");
+
+        assert!(matches!(exported, Precondition::Exported { .. }));
+        assert!(matches!(imported, Precondition::Imported { .. }));
+        assert!(exported == imported);
+        assert!(exported != parse2(quote! { use COND_OTHER }).unwrap());
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            export == true
+        });
+        assert!(matches!(result, Ok(Precondition::Boolean(_))));
+    }
+
+    #[test]
+    fn parse_correct_self_boolean() {
+        let precondition = parse2::<Precondition>(quote! {
+            self.len < self.cap
+        })
+        .expect("valid precondition");
+        assert!(matches!(precondition, Precondition::Boolean(_)));
+
+        let respaced = parse2::<Precondition>("self . len<self .cap".parse().unwrap())
+            .expect("valid precondition");
+        assert!(precondition == respaced);
+        assert_eq!(precondition.encoding_key(), respaced.encoding_key());
+
+        let result: Result<Precondition, _> = parse2(quote! {
+            in_range(self, 0..10)
+        });
+        assert!(matches!(result, Ok(Precondition::InRange { .. })));
+    }
+
+    #[test]
+    fn parse_configured() {
+        let kind: &'static ConditionKind = Box::leak(Box::new(ConditionKind {
+            args: vec!["ptr".into(), "len".into()],
+            doc: "`{ptr}` must point to `{len}` bytes of DMA-capable memory".into(),
+            check: Some("dma::is_dma_capable({ptr}, {len})".into()),
+        }));
+        let parse =
+            |tokens| (|input: ParseStream| super::parse_configured(input, kind)).parse2(tokens);
+
+        let precondition = parse(quote! { dma_capable(buf, n) }).expect("valid precondition");
+        assert_eq!(precondition.to_string(), "dma_capable(buf, n)");
+        assert_eq!(
+            precondition.custom_text().as_deref(),
+            Some("`buf` must point to `n` bytes of DMA-capable memory")
+        );
+        assert_eq!(
+            precondition.encoding_key(),
+            (
+                3,
+                "`buf` must point to `n` bytes of DMA-capable memory".to_string(),
+                String::new()
+            )
+        );
+        let check = precondition
+            .configured_check()
+            .expect("check is configured");
+        assert_eq!(
+            quote! { #check }.to_string(),
+            quote! { dma::is_dma_capable(buf, n) }.to_string()
+        );
+
+        // Configured preconditions are equal to custom preconditions with the same description.
+        let custom: Precondition = parse2(quote! {
+            "`buf` must point to `n` bytes of DMA-capable memory"
+        })
+        .expect("valid precondition");
+        assert!(precondition == custom);
+
+        assert!(parse(quote! { dma_capable(buf) }).is_err());
+        assert!(parse(quote! { dma_capable(buf, n, m) }).is_err());
+        assert!(parse(quote! { dma_capable(buf, n + 1) }).is_err());
+    }
+
+    // The encoding order is part of the public interface and must agree with
+    // `pre::encoding::ConditionKind`, so changing it is a breaking change.
+    #[test]
+    fn encoding_order() {
+        let expected = [
+            quote! { valid_ptr(p, r) },
+            quote! { valid_ptr(p, r+w) },
+            quote! { valid_ptr(p, r, len) },
+            quote! { valid_ptr(p, w) },
+            quote! { valid_ptr(q, r) },
+            quote! { proper_align(p) },
+            quote! { idx < len },
+            quote! { len > 0 },
+            quote! { "a custom condition" },
+            quote! { token(&Lock) },
+            quote! { token(Init) },
+            quote! { non_null(p) },
+            quote! { in_range(x, 0..10) },
+            quote! { use COND_A },
+            quote! { export = COND_B, "b" },
+            quote! { valid_value(x, u8) },
+            quote! { consumed(x) },
+            quote! { proper_align_val(p, 16) },
+            quote! { proper_align_val(p, 32) },
+            quote! { nul_terminated(s) },
+            quote! { valid_cstr(s) },
+            quote! { interrupts_disabled() },
+            quote! { dma_quiescent(a) },
+            quote! { dma_quiescent(b) },
+            quote! { lives_until_end_of_scope(a) },
+            quote! { lives_until_end_of_scope(b) },
+            quote! { initialized(p) },
+            quote! { initialized(p, 1) },
+            quote! { initialized(p, n) },
+            quote! { non_overlapping(a, b, 1) },
+            quote! { non_overlapping(a, b, n) },
+            quote! { non_overlapping(a, c, 1) },
+            quote! { non_overlapping(b, a, 1) },
+            quote! { not_reentrant() },
+            quote! { valid_utf8(a) },
+            quote! { valid_utf8(b) },
+            quote! { aligned_to(p, 16) },
+            quote! { aligned_to(p, 32) },
+        ]
+        .iter()
+        .map(|tokens| tokens.to_string())
+        .collect::<Vec<_>>();
+
+        let parse = |tokens: &String| CfgPrecondition {
+            precondition: parse2(tokens.parse().expect("valid tokens"))
+                .expect("valid precondition"),
+            cfg: None,
+            span: Span::call_site(),
+        };
+
+        let expected_keys = expected
+            .iter()
+            .map(|tokens| parse(tokens).precondition().encoding_key())
+            .collect::<Vec<_>>();
+        let orders = vec![
+            expected.iter().collect::<Vec<_>>(),
+            expected.iter().rev().collect(),
+            expected
+                .iter()
+                .step_by(2)
+                .chain(expected.iter().skip(1).step_by(2))
+                .collect(),
+        ];
+
+        for order in orders {
+            let mut preconditions = order.into_iter().map(parse).collect::<Vec<_>>();
+            sort_for_encoding(&mut preconditions);
+
+            let keys = preconditions
+                .iter()
+                .map(|precondition| precondition.precondition().encoding_key())
+                .collect::<Vec<_>>();
+            assert_eq!(keys, expected_keys);
+        }
+
+        assert_eq!(
+            parse(&quote! { valid_ptr(p, r+w) }.to_string())
+                .precondition()
+                .encoding_key(),
+            (0, "p".to_string(), "r+w".to_string())
+        );
+        assert_eq!(
+            parse(&quote! { valid_ptr(p, w, n * 2) }.to_string())
+                .precondition()
+                .encoding_key(),
+            (0, "p".to_string(), "w, n * 2".to_string())
+        );
+        assert_eq!(
+            parse(&quote! { token(&Lock) }.to_string())
+                .precondition()
+                .encoding_key(),
+            (4, "& Lock".to_string(), String::new())
+        );
     }
 }
@@ -0,0 +1,158 @@
+//! Provides the `rename_condition!` macro that helps with rewording custom preconditions.
+//!
+//! All procedural macro invocations for a crate usually happen in the same compiler process, but
+//! their order is not guaranteed. Because of this, both sides are checked:
+//! - `rename_condition!` reports the call sites that were expanded before it and still assure the
+//!   old condition.
+//! - Call sites that are expanded after it and still assure the old condition are reported at
+//!   the call site itself.
+
+use lazy_static::lazy_static;
+use proc_macro2::TokenStream;
+use proc_macro_error::emit_error;
+use std::{collections::HashMap, sync::Mutex};
+use syn::{
+    parse::{Parse, ParseStream},
+    LitStr, Token,
+};
+
+lazy_static! {
+    /// The custom conditions assured so far, together with the call assuring them.
+    static ref ASSURED_CONDITIONS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+    /// The custom conditions that were renamed, mapped to their new text.
+    static ref RENAMED_CONDITIONS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+/// The parsed input of the `rename_condition!` macro.
+pub(crate) struct RenameCondition {
+    /// The old text of the condition.
+    old: LitStr,
+    /// The `=>` separating the old and the new text.
+    _arrow: Token![=>],
+    /// The new text of the condition.
+    new: LitStr,
+}
+
+impl Parse for RenameCondition {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let old = input.parse()?;
+        let arrow = input.parse()?;
+        let new = input.parse()?;
+
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(RenameCondition {
+            old,
+            _arrow: arrow,
+            new,
+        })
+    }
+}
+
+impl RenameCondition {
+    /// Registers the rename and reports the call sites that still assure the old condition.
+    pub(crate) fn render(self) -> TokenStream {
+        let old = self.old.value();
+        let new = self.new.value();
+
+        if old == new {
+            emit_error!(
+                self.new,
+                "the new condition is the same as the old one";
+                help = "specify the new text of the condition here"
+            );
+
+            return TokenStream::new();
+        }
+
+        RENAMED_CONDITIONS
+            .lock()
+            .expect("no panics while holding the lock")
+            .insert(old.clone(), new.clone());
+
+        let stale_calls = stale_calls(
+            &ASSURED_CONDITIONS
+                .lock()
+                .expect("no panics while holding the lock"),
+            &old,
+        );
+
+        if !stale_calls.is_empty() {
+            let message = if stale_calls.len() == 1 {
+                format!("1 call site still assures {:?}", old)
+            } else {
+                format!("{} call sites still assure {:?}", stale_calls.len(), old)
+            };
+
+            emit_error!(
+                self.old,
+                message;
+                note = "the stale call sites are: {}", stale_calls.join(", ");
+                help = "replace the condition with {:?} at these call sites", new
+            );
+        }
+
+        TokenStream::new()
+    }
+}
+
+/// Returns the calls assuring the given condition.
+fn stale_calls(assured_conditions: &[(String, String)], condition: &str) -> Vec<String> {
+    assured_conditions
+        .iter()
+        .filter(|(assured, _)| assured == condition)
+        .map(|(_, call)| format!("`{}`", call))
+        .collect()
+}
+
+/// Registers that the call assures the condition.
+///
+/// Returns the new text of the condition, if it was renamed.
+pub(crate) fn register_assured_condition(condition: &str, call: String) -> Option<String> {
+    let renamed = RENAMED_CONDITIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .get(condition)
+        .cloned();
+
+    ASSURED_CONDITIONS
+        .lock()
+        .expect("no panics while holding the lock")
+        .push((condition.to_string(), call));
+
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn parse_rename() {
+        let rename: RenameCondition =
+            parse2(quote! { "old text" => "new text" }).expect("valid rename");
+        assert_eq!(rename.old.value(), "old text");
+        assert_eq!(rename.new.value(), "new text");
+
+        assert!(parse2::<RenameCondition>(quote! { "old text" => "new text", }).is_ok());
+        assert!(parse2::<RenameCondition>(quote! { "old text", "new text" }).is_err());
+        assert!(parse2::<RenameCondition>(quote! { old => new }).is_err());
+    }
+
+    #[test]
+    fn finds_stale_calls() {
+        let assured = [
+            ("old".to_string(), "foo (1)".to_string()),
+            ("new".to_string(), "foo (2)".to_string()),
+            ("old".to_string(), "bar (3)".to_string()),
+        ];
+
+        assert_eq!(stale_calls(&assured, "old"), ["`foo (1)`", "`bar (3)`"]);
+        assert!(stale_calls(&assured, "other").is_empty());
+    }
+}
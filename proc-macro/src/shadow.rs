@@ -0,0 +1,348 @@
+//! Provides the `shadow!` macro that creates a preconditioned twin of a local module.
+//!
+//! # What the generated code looks like
+//!
+//! ```rust,ignore
+//! pre::shadow! {
+//!     pub(crate) mod buffer {
+//!         #[pre(valid_ptr(ptr, r))]
+//!         pub(crate) unsafe fn read(ptr: *const u8) -> u8 {
+//!             *ptr
+//!         }
+//!
+//!         impl Buffer {
+//!             #[pre(index < self.len())]
+//!             pub(crate) unsafe fn get_unchecked(&self, index: usize) -> u8 {
+//!                 /* ... */
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! turns into
+//!
+//! ```rust,ignore
+//! pub(crate) mod buffer {
+//!     pub(crate) unsafe fn read(ptr: *const u8) -> u8 {
+//!         *ptr
+//!     }
+//!
+//!     impl Buffer {
+//!         pub(crate) unsafe fn get_unchecked(&self, index: usize) -> u8 {
+//!             /* ... */
+//!         }
+//!     }
+//! }
+//!
+//! #[pre::extern_crate(super::buffer)]
+//! pub(crate) mod pre_buffer {
+//!     #[pre(valid_ptr(ptr, r))]
+//!     pub(crate) unsafe fn read(ptr: *const u8) -> u8;
+//!
+//!     impl Buffer {
+//!         #[pre(index < self.len())]
+//!         pub(crate) unsafe fn get_unchecked(&self, index: usize) -> u8;
+//!     }
+//! }
+//! ```
+
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::emit_error;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Attribute, Ident, ImplItem, Item, ItemImpl, ItemMod, Signature, Type, Visibility,
+};
+
+use crate::{
+    helpers::{Attr, CRATE_NAME},
+    pre_attr::PreAttr,
+};
+
+/// The parsed input of the `shadow!` macro.
+pub(crate) struct Shadow {
+    /// The module to create a preconditioned twin of.
+    module: ItemMod,
+}
+
+impl Parse for Shadow {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let module: ItemMod = input.parse()?;
+
+        if module.content.is_none() {
+            return Err(syn::Error::new(
+                module.span(),
+                "`shadow!` requires a module with its content given inline",
+            ));
+        }
+
+        Ok(Shadow { module })
+    }
+}
+
+impl Shadow {
+    /// Returns the original module unchanged, for use if errors occur.
+    pub(crate) fn render_dummy(&self) -> TokenStream {
+        self.module.to_token_stream()
+    }
+
+    /// Renders the module without preconditions and its preconditioned twin.
+    pub(crate) fn render(self) -> TokenStream {
+        let (original, twin) = split_module(self.module);
+
+        let crate_name = Ident::new(&CRATE_NAME, Span::call_site());
+        let original_name = &original.ident;
+        let twin_attrs = &twin.attrs;
+        let twin_vis = &twin.vis;
+        let twin_name = &twin.ident;
+        let twin_items = &twin.items;
+
+        quote! {
+            #original
+
+            #(#twin_attrs)*
+            #[#crate_name::extern_crate(super::#original_name)]
+            #twin_vis mod #twin_name {
+                #(#twin_items)*
+            }
+        }
+    }
+}
+
+/// The outline of the preconditioned twin of a module.
+struct Twin {
+    /// The `cfg` attributes of the original module.
+    attrs: Vec<Attribute>,
+    /// The visibility of the twin.
+    vis: Visibility,
+    /// The name of the twin.
+    ident: Ident,
+    /// The signatures of the functions and impl blocks in the twin.
+    items: Vec<TokenStream>,
+}
+
+/// Splits the module into the module without preconditions and the outline of its twin.
+fn split_module(mut module: ItemMod) -> (ItemMod, Twin) {
+    let mut twin = Twin {
+        attrs: cfg_attrs(&module.attrs),
+        vis: module.vis.clone(),
+        ident: format_ident!("pre_{}", module.ident, span = module.ident.span()),
+        items: Vec::new(),
+    };
+
+    let items = match &mut module.content {
+        Some((_, items)) => items,
+        None => return (module, twin),
+    };
+
+    for item in items {
+        match item {
+            Item::Fn(function) => {
+                if let Some(signature) = shadow_signature(
+                    &mut function.attrs,
+                    &function.vis,
+                    &function.sig,
+                    "function",
+                ) {
+                    twin.items.push(signature);
+                }
+            }
+            Item::Impl(impl_block) => {
+                if let Some(impl_block) = shadow_impl_block(impl_block) {
+                    twin.items.push(impl_block);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    (module, twin)
+}
+
+/// Creates the outline of an inherent impl block with the methods that have preconditions.
+fn shadow_impl_block(impl_block: &mut ItemImpl) -> Option<TokenStream> {
+    // Preconditions of trait methods are part of the trait, not the impl block.
+    if impl_block.trait_.is_some() {
+        return None;
+    }
+
+    if !matches!(&*impl_block.self_ty, Type::Path(_)) {
+        return None;
+    }
+
+    let methods = impl_block
+        .items
+        .iter_mut()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => {
+                shadow_signature(&mut method.attrs, &method.vis, &method.sig, "method")
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if methods.is_empty() {
+        return None;
+    }
+
+    let attrs = cfg_attrs(&impl_block.attrs);
+    let impl_token = &impl_block.impl_token;
+    let generics = &impl_block.generics;
+    let where_clause = &generics.where_clause;
+    let self_ty = &impl_block.self_ty;
+
+    Some(quote! {
+        #(#attrs)*
+        #impl_token #generics #self_ty #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+/// Removes the preconditions from a function and returns its signature with the preconditions.
+fn shadow_signature(
+    attrs: &mut Vec<Attribute>,
+    vis: &Visibility,
+    sig: &Signature,
+    kind: &str,
+) -> Option<TokenStream> {
+    let mut preconditions = Vec::new();
+    attrs.retain(|attr| match Attr::<PreAttr>::from_inner("pre", attr) {
+        Some(parsed) if matches!(parsed.content(), PreAttr::Precondition(_)) => {
+            preconditions.push(attr.clone());
+            false
+        }
+        _ => true,
+    });
+
+    if preconditions.is_empty() {
+        return None;
+    }
+
+    if let Visibility::Inherited = vis {
+        emit_error!(
+            sig.ident,
+            "{} `{}` is not visible to the preconditioned twin module", kind, sig.ident;
+            help = "make it visible in the parent module, for example with `pub(crate)`"
+        );
+    }
+
+    let cfgs = cfg_attrs(attrs);
+
+    Some(quote! {
+        #(#cfgs)*
+        #(#preconditions)*
+        #vis #sig;
+    })
+}
+
+/// Returns the `cfg` attributes among the given attributes.
+fn cfg_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    #[test]
+    fn splits_preconditions() {
+        let module: ItemMod = parse2(quote! {
+            pub(crate) mod buffer {
+                #[pre(valid_ptr(ptr, r))]
+                #[pre(no_doc)]
+                #[cfg(unix)]
+                pub(crate) unsafe fn read(ptr: *const u8) -> u8 {
+                    *ptr
+                }
+
+                pub(crate) fn len() -> usize {
+                    0
+                }
+
+                impl<T: Copy> Buffer<T> {
+                    #[pre("`index` is in bounds")]
+                    pub(crate) unsafe fn get_unchecked(&self, index: usize) -> T {
+                        self.get(index)
+                    }
+
+                    pub(crate) fn get(&self, index: usize) -> T {
+                        self.0[index]
+                    }
+                }
+
+                impl Clone for Buffer<u8> {
+                    #[pre("is never checked")]
+                    fn clone(&self) -> Self {
+                        Buffer(self.0.clone())
+                    }
+                }
+            }
+        })
+        .expect("valid module");
+
+        let (original, twin) = split_module(module);
+
+        let expected_original: ItemMod = parse2(quote! {
+            pub(crate) mod buffer {
+                #[pre(no_doc)]
+                #[cfg(unix)]
+                pub(crate) unsafe fn read(ptr: *const u8) -> u8 {
+                    *ptr
+                }
+
+                pub(crate) fn len() -> usize {
+                    0
+                }
+
+                impl<T: Copy> Buffer<T> {
+                    pub(crate) unsafe fn get_unchecked(&self, index: usize) -> T {
+                        self.get(index)
+                    }
+
+                    pub(crate) fn get(&self, index: usize) -> T {
+                        self.0[index]
+                    }
+                }
+
+                impl Clone for Buffer<u8> {
+                    #[pre("is never checked")]
+                    fn clone(&self) -> Self {
+                        Buffer(self.0.clone())
+                    }
+                }
+            }
+        })
+        .expect("valid module");
+        assert_eq!(
+            original.to_token_stream().to_string(),
+            expected_original.to_token_stream().to_string()
+        );
+
+        let twin_items = &twin.items;
+        assert_eq!(twin.ident.to_string(), "pre_buffer");
+        assert_eq!(
+            quote! { #(#twin_items)* }.to_string(),
+            quote! {
+                #[cfg(unix)]
+                #[pre(valid_ptr(ptr, r))]
+                pub(crate) unsafe fn read(ptr: *const u8) -> u8;
+
+                impl<T: Copy> Buffer<T> {
+                    #[pre("`index` is in bounds")]
+                    pub(crate) unsafe fn get_unchecked(&self, index: usize) -> T;
+                }
+            }
+            .to_string()
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Makes the warnings of pre visible on the stable compiler.
+//!
+//! Warnings emitted by procedural macros are only shown on the nightly compiler. On the stable
+//! compiler, each warning is additionally recorded here and rendered as the use of a deprecated
+//! item, whose note contains the message of the warning:
+//!
+//! ```rust,ignore
+//! {
+//!     #[deprecated(note = "the reason is a `TODO`")]
+//!     #[allow(non_camel_case_types)]
+//!     struct todo_reason;
+//!     let _ = todo_reason;
+//! }
+//! ```
+//!
+//! The use of the item has the span of the original warning, so the compiler reports a
+//! `deprecated` warning with the message at the right location. The item is named after the lint
+//! or the option that enabled the warning. Notes and help messages of the original warning are not
+//! included.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote_spanned;
+use std::{cell::RefCell, mem};
+use syn::{parse2, parse_quote, File, ImplItem, Item, LitStr, Stmt, TraitItem};
+
+thread_local! {
+    /// The warnings recorded during the current macro invocation.
+    static WARNINGS: RefCell<Vec<Warning>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A warning that is rendered as a use of a deprecated item.
+struct Warning {
+    /// The name of the deprecated item.
+    name: &'static str,
+    /// The span of the warning.
+    span: Span,
+    /// The message of the warning.
+    message: String,
+}
+
+impl Warning {
+    /// Renders the block defining and using the deprecated item.
+    fn render(&self) -> TokenStream {
+        let name = syn::Ident::new(self.name, self.span);
+        let message = LitStr::new(&self.message, Span::call_site());
+
+        quote_spanned! { Span::call_site()=>
+            {
+                #[deprecated(note = #message)]
+                #[allow(non_camel_case_types)]
+                struct #name;
+                let _ = #name;
+            }
+        }
+    }
+}
+
+/// Records a warning to be made visible on the stable compiler.
+///
+/// On the nightly compiler, warnings are shown directly, so nothing is recorded.
+pub(crate) fn record(name: &'static str, span: Span, message: &str) {
+    if cfg!(nightly) {
+        return;
+    }
+
+    WARNINGS.with(|warnings| {
+        warnings.borrow_mut().push(Warning {
+            name,
+            span,
+            message: message.to_string(),
+        })
+    });
+}
+
+/// Forgets the warnings of a previous macro invocation that did not finish.
+pub(crate) fn clear() {
+    WARNINGS.with(|warnings| warnings.borrow_mut().clear());
+}
+
+/// Takes the rendered warnings recorded so far.
+fn take() -> Vec<TokenStream> {
+    WARNINGS.with(|warnings| {
+        warnings
+            .borrow_mut()
+            .drain(..)
+            .map(|warning| warning.render())
+            .collect()
+    })
+}
+
+/// Adds the recorded warnings to the body of the first function in the file.
+///
+/// Functions are often already rendered into verbatim tokens, so these are parsed again to find
+/// their body. If the file contains no function body, the warnings are added as an unnamed
+/// constant instead.
+pub(crate) fn add_to_file(file: &mut File) {
+    let warnings = take();
+    if warnings.is_empty() {
+        return;
+    }
+
+    file.items = mem::take(&mut file.items)
+        .into_iter()
+        .flat_map(|item| match item {
+            Item::Verbatim(tokens) => match parse2::<File>(tokens.clone()) {
+                Ok(parsed) => parsed.items,
+                Err(_) => vec![Item::Verbatim(tokens)],
+            },
+            item => vec![item],
+        })
+        .collect();
+
+    let stmts = warnings
+        .iter()
+        .map(|warning| parse_quote!(#warning))
+        .collect::<Vec<Stmt>>();
+
+    if let Some(body) = file.items.iter_mut().find_map(first_body) {
+        body.splice(0..0, stmts);
+    } else {
+        file.items.push(parse_quote! {
+            const _: () = {
+                #(#warnings)*
+            };
+        });
+    }
+}
+
+/// Appends the recorded warnings as an unnamed constant to the items.
+pub(crate) fn add_to_items(items: TokenStream) -> TokenStream {
+    let warnings = take();
+    if warnings.is_empty() {
+        return items;
+    }
+
+    quote_spanned! { Span::call_site()=>
+        #items
+
+        const _: () = {
+            #(#warnings)*
+        };
+    }
+}
+
+/// Prepends the recorded warnings to the expression.
+pub(crate) fn add_to_expr(expr: TokenStream) -> TokenStream {
+    let warnings = take();
+    if warnings.is_empty() {
+        return expr;
+    }
+
+    quote_spanned! { Span::call_site()=>
+        {
+            #(#warnings)*
+            #expr
+        }
+    }
+}
+
+/// Returns the statements of the first function body in the item.
+fn first_body(item: &mut Item) -> Option<&mut Vec<Stmt>> {
+    match item {
+        Item::Fn(function) => Some(&mut function.block.stmts),
+        Item::Impl(item_impl) => item_impl.items.iter_mut().find_map(|item| match item {
+            ImplItem::Method(method) => Some(&mut method.block.stmts),
+            _ => None,
+        }),
+        Item::Trait(item_trait) => item_trait.items.iter_mut().find_map(|item| match item {
+            TraitItem::Method(method) => method.default.as_mut().map(|block| &mut block.stmts),
+            _ => None,
+        }),
+        Item::Mod(item_mod) => item_mod
+            .content
+            .as_mut()
+            .and_then(|(_, items)| items.iter_mut().find_map(first_body)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn warnings_are_added() {
+        clear();
+        record("todo_reason", Span::call_site(), "the reason is a `TODO`");
+
+        let mut file: File = parse2(quote! {
+            struct Foo;
+        })
+        .expect("valid file");
+        file.items.push(Item::Verbatim(quote! {
+            fn foo() {
+                bar();
+            }
+        }));
+        add_to_file(&mut file);
+        let file = quote! { #file }.to_string();
+
+        if cfg!(nightly) {
+            assert!(!file.contains("deprecated"));
+        } else {
+            assert!(file.contains(
+                "fn foo () { { # [deprecated (note = \"the reason is a `TODO`\")] # [allow (non_camel_case_types)] struct todo_reason ; let _ = todo_reason ; } bar () ; }"
+            ));
+        }
+
+        assert_eq!(
+            add_to_expr(quote! { foo() }).to_string(),
+            quote! { foo() }.to_string()
+        );
+    }
+}
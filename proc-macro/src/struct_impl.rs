@@ -39,7 +39,8 @@
 //! #[allow(non_snake_case)]
 //! #[cfg(not(doc))]
 //! struct has_preconditions {
-//!     _boolean_some__val_20_3e_2042_2e0: (),
+//!     _pre_marker_abi_1: (),
+//!     _boolean_some__val_3e42_2e0: (),
 //! }
 //!
 //! #[doc = "..."]
@@ -58,23 +59,54 @@
 //!     has_preconditions(
 //!         43.0,
 //!         has_preconditions {
-//!             _boolean_some__val_20_3e_2042_2e0: (),
+//!             _pre_marker_abi_1: (),
+//!             _boolean_some__val_3e42_2e0: (),
 //!         },
 //!     );
 //! }
 //! ```
+//!
+//! # Stability of the encoding
+//!
+//! The names of the struct and its fields are part of the public interface of `pre`, because
+//! crates depending on different versions of `pre` must agree on them. They must only be changed
+//! together with [`MARKER_ABI_VERSION`], which is encoded as an additional field in every struct.
+//! This way a mismatch results in an error naming the expected version.
+//!
+//! Expressions and types in the names are rendered using `canonical_tokens`, because the
+//! `Display` output of tokens differs between compiler versions.
 
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error::emit_error;
 use quote::{format_ident, quote, quote_spanned, TokenStreamExt};
-use syn::{parse2, spanned::Spanned, Ident, ItemFn, PathArguments};
+use syn::{
+    parse2,
+    spanned::Spanned,
+    visit_mut::{visit_type_reference_mut, VisitMut},
+    Expr, Ident, ItemFn, Lifetime, ParenthesizedGenericArguments, PathArguments, Type, TypeBareFn,
+    TypeReference,
+};
 
 use crate::{
     call::Call,
-    helpers::{add_span_to_signature, combine_cfg},
-    precondition::{CfgPrecondition, Precondition, ReadWrite},
+    helpers::{
+        add_precondition_param, add_span_to_signature, canonical_tokens, combine_call_site_cfg,
+        combine_cfg, CRATE_NAME,
+    },
+    precondition::{sort_for_encoding, CfgPrecondition, Precondition, ReadWrite},
 };
 
+/// The version of the naming scheme used for the generated structs and their fields.
+///
+/// This must be incremented whenever the output of `render_as_ident` or the types of the fields
+/// change.
+pub(crate) const MARKER_ABI_VERSION: u32 = 1;
+
+/// Renders the name of the field marking the version of the naming scheme.
+fn render_abi_field(span: Span) -> Ident {
+    format_ident!("_pre_marker_abi_{}", MARKER_ABI_VERSION, span = span)
+}
+
 /// Renders a precondition as a `String` representing an identifier.
 pub(crate) fn render_as_ident(precondition: &CfgPrecondition) -> Ident {
     /// Escapes characters that are not valid in identifiers.
@@ -91,24 +123,126 @@ pub(crate) fn render_as_ident(precondition: &CfgPrecondition) -> Ident {
 
     let mut ident = match precondition.precondition() {
         Precondition::ValidPtr {
-            ident, read_write, ..
-        } => format_ident!(
-            "_valid_ptr_{}_{}",
             ident,
-            match read_write {
+            read_write,
+            count,
+            ..
+        } => {
+            let read_write = match read_write {
                 ReadWrite::Read { .. } => "r",
                 ReadWrite::Write { .. } => "w",
                 ReadWrite::Both { .. } => "rw",
+            };
+
+            match count {
+                Some((_, count)) => format_ident!(
+                    "_valid_ptr_{}_{}_{}",
+                    ident,
+                    read_write,
+                    escape_non_ident_chars(canonical_tokens(quote! { #count }))
+                ),
+                None => format_ident!("_valid_ptr_{}_{}", ident, read_write),
             }
-        ),
+        }
         Precondition::ProperAlign { ident, .. } => format_ident!("_proper_align_{}", ident),
+        Precondition::ProperAlignVal { ident, align, .. } => format_ident!(
+            "_proper_align_val_{}_{}",
+            ident,
+            escape_non_ident_chars(canonical_tokens(quote! { #align }))
+        ),
+        Precondition::AlignedTo { ident, align, .. } => format_ident!(
+            "_aligned_to_{}_{}",
+            ident,
+            escape_non_ident_chars(canonical_tokens(quote! { #align }))
+        ),
+        Precondition::NonNull { ident, .. } => format_ident!("_non_null_{}", ident),
+        Precondition::NulTerminated { ident, .. } => format_ident!("_nul_terminated_{}", ident),
+        Precondition::ValidCStr { ident, .. } => format_ident!("_valid_cstr_{}", ident),
+        Precondition::Initialized { ident, count, .. } => match count {
+            Some((_, count)) => format_ident!(
+                "_initialized_{}_{}",
+                ident,
+                escape_non_ident_chars(canonical_tokens(quote! { #count }))
+            ),
+            None => format_ident!("_initialized_{}", ident),
+        },
+        Precondition::NonOverlapping {
+            first,
+            second,
+            count,
+            ..
+        } => format_ident!(
+            "_non_overlapping_{}_{}_{}",
+            first,
+            second,
+            escape_non_ident_chars(canonical_tokens(quote! { #count }))
+        ),
+        Precondition::ValidSlice {
+            ident,
+            len,
+            read_write,
+            ..
+        } => {
+            let read_write = match read_write {
+                ReadWrite::Read { .. } => "r",
+                ReadWrite::Write { .. } => "w",
+                ReadWrite::Both { .. } => "rw",
+            };
+
+            format_ident!(
+                "_valid_slice_{}_{}_{}",
+                ident,
+                escape_non_ident_chars(canonical_tokens(quote! { #len })),
+                read_write
+            )
+        }
+        Precondition::InterruptsDisabled { .. } => format_ident!("_interrupts_disabled"),
+        Precondition::NotReentrant { .. } => format_ident!("_not_reentrant"),
+        Precondition::DmaQuiescent { ident, .. } => format_ident!("_dma_quiescent_{}", ident),
+        Precondition::LivesUntilEndOfScope { ident, .. } => {
+            format_ident!("_lives_until_end_of_scope_{}", ident)
+        }
+        Precondition::InRange { ident, range, .. } => format_ident!(
+            "_in_range_{}_{}",
+            ident,
+            escape_non_ident_chars(canonical_tokens(quote! { #range }))
+        ),
+        Precondition::Consumed { ident, .. } => format_ident!("_consumed_{}", ident),
+        Precondition::ValidUtf8 { ident, .. } => format_ident!("_valid_utf8_{}", ident),
+        Precondition::ValidValue { ident, ty, .. } => format_ident!(
+            "_valid_value_{}_{}",
+            ident,
+            escape_non_ident_chars(canonical_tokens(quote! { #ty }))
+        ),
+        Precondition::LayoutCompatible { from, to, .. } => format_ident!(
+            "_layout_compatible_{}_{}",
+            escape_non_ident_chars(canonical_tokens(quote! { #from })),
+            escape_non_ident_chars(canonical_tokens(quote! { #to }))
+        ),
+        Precondition::Token { ty, .. } => format_ident!(
+            "_token_{}",
+            escape_non_ident_chars(canonical_tokens(quote! { #ty }))
+        ),
+        Precondition::Exported { .. } | Precondition::Imported { .. } => format_ident!(
+            "_named_{}",
+            precondition
+                .precondition()
+                .condition_name()
+                .expect("exported and imported conditions have a name")
+        ),
         Precondition::Boolean(expr) => format_ident!(
             "_boolean_{}",
-            escape_non_ident_chars(quote! { #expr }.to_string())
+            escape_non_ident_chars(canonical_tokens(quote! { #expr }))
+        ),
+        Precondition::Custom(_) | Precondition::Configured { .. } => format_ident!(
+            "_custom_{}",
+            escape_non_ident_chars(
+                precondition
+                    .precondition()
+                    .custom_text()
+                    .expect("custom and configured preconditions have a text")
+            )
         ),
-        Precondition::Custom(string) => {
-            format_ident!("_custom_{}", escape_non_ident_chars(string.value()))
-        }
     };
 
     ident.set_span(precondition.span());
@@ -116,11 +250,147 @@ pub(crate) fn render_as_ident(precondition: &CfgPrecondition) -> Ident {
     ident
 }
 
+/// The name of the lifetime used for borrowed tokens in the generated struct.
+const TOKEN_LIFETIME: &str = "'__pre_token";
+
+/// Replaces all elided lifetimes in a type with the token lifetime.
+struct NameElidedLifetimes {
+    /// Whether any lifetime was replaced.
+    replaced_any: bool,
+}
+
+impl NameElidedLifetimes {
+    /// Returns the token lifetime.
+    fn lifetime(&mut self, span: Span) -> Lifetime {
+        self.replaced_any = true;
+
+        Lifetime::new(TOKEN_LIFETIME, span)
+    }
+}
+
+impl VisitMut for NameElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.lifetime(reference.and_token.span));
+        }
+
+        visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.lifetime(lifetime.span());
+        }
+    }
+
+    // Elided lifetimes in function pointers and `Fn` traits don't refer to the struct lifetime.
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {
+    }
+}
+
+/// Renders the type of the field for the given precondition in the generated struct.
+///
+/// Returns `true` as the second value, if the type uses the token lifetime.
+fn render_field_type(precondition: &CfgPrecondition, span: Span) -> (TokenStream, bool) {
+    match precondition.precondition() {
+        Precondition::Token { ty, .. } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+            let mut ty: Type = (**ty).clone();
+            let mut visitor = NameElidedLifetimes {
+                replaced_any: false,
+            };
+            visitor.visit_type_mut(&mut ty);
+
+            (
+                quote_spanned! { precondition.span()=>
+                    ::#crate_name::TokenCondition<#ty>
+                },
+                visitor.replaced_any,
+            )
+        }
+        Precondition::Exported { ident, .. } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+
+            (
+                quote_spanned! { precondition.span()=>
+                    ::#crate_name::NamedCondition<#ident>
+                },
+                false,
+            )
+        }
+        Precondition::Imported { path, .. } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+
+            (
+                quote_spanned! { precondition.span()=>
+                    ::#crate_name::NamedCondition<#path>
+                },
+                false,
+            )
+        }
+        _ => (quote_spanned! { span=> () }, false),
+    }
+}
+
+/// Renders the value of the field for the given precondition in the generated struct.
+fn render_field_value(precondition: &CfgPrecondition, span: Span) -> TokenStream {
+    match precondition.precondition() {
+        Precondition::Token {
+            ty,
+            value: Some((_, value)),
+            ..
+        } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+            let value: &Expr = value;
+
+            quote_spanned! { precondition.span()=>
+                ::#crate_name::TokenCondition::<#ty>(#value)
+            }
+        }
+        // Make sure that the imported condition actually exists.
+        Precondition::Imported { path, .. } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+
+            quote_spanned! { precondition.span()=>
+                {
+                    const _: ::#crate_name::ConditionDef = #path;
+                    ::#crate_name::NamedCondition::<#path>(#path {})
+                }
+            }
+        }
+        // An error was already emitted for the missing value.
+        // The placeholder does not diverge, to avoid an additional warning.
+        Precondition::Token {
+            ty, value: None, ..
+        } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+
+            quote_spanned! { precondition.span()=>
+                ::#crate_name::TokenCondition::<#ty>(::core::option::Option::None.unwrap())
+            }
+        }
+        // An error was already emitted for exporting the condition at the call.
+        Precondition::Exported { .. } => {
+            let crate_name = Ident::new(&CRATE_NAME, span);
+
+            quote_spanned! { precondition.span()=>
+                ::#crate_name::NamedCondition(::core::option::Option::None.unwrap())
+            }
+        }
+        _ => quote_spanned! { span=> () },
+    }
+}
+
 /// Generates the code for the function with the precondition handling added.
+///
+/// If `arg_first` is set, the precondition argument is the first argument of the function.
 pub(crate) fn render_pre(
-    preconditions: Vec<CfgPrecondition>,
+    mut preconditions: Vec<CfgPrecondition>,
     function: &mut ItemFn,
     span: Span,
+    arg_first: bool,
 ) -> TokenStream {
     let combined_cfg = combine_cfg(&preconditions, span);
     if function.sig.receiver().is_some() {
@@ -131,21 +401,36 @@ pub(crate) fn render_pre(
         return quote! { #function };
     }
 
+    sort_for_encoding(&mut preconditions);
+
     let vis = &function.vis;
-    let mut preconditions_rendered = TokenStream::new();
-    preconditions_rendered.append_all(
-        preconditions
-            .iter()
-            .map(render_as_ident)
-            .map(|ident| quote_spanned! { span=> #vis #ident: (), }),
-    );
+    let abi_field = render_abi_field(span);
+    let mut preconditions_rendered = quote_spanned! { span=> #vis #abi_field: (), };
+    let mut uses_token_lifetime = false;
+    for precondition in &preconditions {
+        let ident = render_as_ident(precondition);
+        let (ty, uses_lifetime) = render_field_type(precondition, span);
+        uses_token_lifetime |= uses_lifetime;
+
+        preconditions_rendered.append_all(quote_spanned! { span=> #vis #ident: #ty, });
+    }
 
     let function_name = function.sig.ident.clone();
+    let (struct_generics, arg_generics) = if uses_token_lifetime {
+        let lifetime = Lifetime::new(TOKEN_LIFETIME, span);
+
+        (
+            Some(quote_spanned! { span=> <#lifetime> }),
+            Some(quote_spanned! { span=> <'_> }),
+        )
+    } else {
+        (None, None)
+    };
     let struct_def = quote_spanned! { span=>
         #[allow(non_camel_case_types)]
         #[allow(non_snake_case)]
         #[cfg(all(not(doc), #combined_cfg))]
-        #vis struct #function_name {
+        #vis struct #function_name #struct_generics {
             #preconditions_rendered
         }
     };
@@ -154,12 +439,12 @@ pub(crate) fn render_pre(
     // This improves the error messages for the case where no preconditions are specified.
     add_span_to_signature(span, &mut function.sig);
 
-    function.sig.inputs.push(
-        parse2(quote_spanned! { span=>
-            #[cfg(all(not(doc), #combined_cfg))]
-            _: #function_name
-        })
-        .expect("parses as valid function argument"),
+    add_precondition_param(
+        &mut function.sig,
+        combined_cfg,
+        quote_spanned! { span=> #function_name #arg_generics },
+        arg_first,
+        span,
     );
 
     quote! {
@@ -169,15 +454,18 @@ pub(crate) fn render_pre(
 }
 
 /// Generates the code for the call with the precondition handling added.
+///
+/// If `arg_first` is set, the precondition argument is the first argument of the call.
 pub(crate) fn render_assure(
-    preconditions: Vec<CfgPrecondition>,
+    mut preconditions: Vec<CfgPrecondition>,
     mut call: Call,
     span: Span,
+    arg_first: bool,
 ) -> Call {
-    let combined_cfg = combine_cfg(&preconditions, span);
+    let combined_cfg = combine_call_site_cfg(&preconditions);
     if !call.is_function() {
         emit_error!(
-            call,
+            call.base(),
             "method calls are not supported by `pre` on the stable compiler"
         );
 
@@ -189,7 +477,7 @@ pub(crate) fn render_assure(
     if let Some(p) = call.path() {
         path = p;
     } else {
-        match &call {
+        match call.base() {
             Call::Function(call) => emit_error!(
                 call.func,
                 "unable to determine at compile time which function is being called";
@@ -211,15 +499,18 @@ pub(crate) fn render_assure(
         last_path_segment.ident.set_span(span);
     }
 
-    let mut preconditions_rendered = TokenStream::new();
-    preconditions_rendered.append_all(
-        preconditions
-            .iter()
-            .map(render_as_ident)
-            .map(|ident| quote_spanned! { span=> #ident: (), }),
-    );
+    sort_for_encoding(&mut preconditions);
 
-    call.args_mut().push(
+    let abi_field = render_abi_field(span);
+    let mut preconditions_rendered = quote_spanned! { span=> #abi_field: (), };
+    preconditions_rendered.append_all(preconditions.iter().map(|precondition| {
+        let ident = render_as_ident(precondition);
+        let value = render_field_value(precondition, span);
+
+        quote_spanned! { span=> #ident: #value, }
+    }));
+
+    call.add_precondition_arg(
         parse2(quote_spanned! { span=>
             #[cfg(all(not(doc), #combined_cfg))]
             #path {
@@ -227,7 +518,69 @@ pub(crate) fn render_assure(
             }
         })
         .expect("parses as an expression"),
+        arg_first,
     );
 
     call
 }
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+    use quote::quote;
+    use syn::parse2;
+
+    use super::*;
+
+    /// Renders the identifier for the given precondition.
+    fn ident(tokens: TokenStream) -> String {
+        let precondition = CfgPrecondition {
+            precondition: parse2(tokens).expect("valid precondition"),
+            cfg: None,
+            span: Span::call_site(),
+        };
+
+        render_as_ident(&precondition).to_string()
+    }
+
+    // Changing any of these requires incrementing `MARKER_ABI_VERSION`.
+    #[test]
+    fn stable_marker_idents() {
+        assert_eq!(render_abi_field(Span::call_site()), "_pre_marker_abi_1");
+
+        assert_eq!(ident(quote! { valid_ptr(p, r) }), "_valid_ptr_p_r");
+        assert_eq!(ident(quote! { valid_ptr(p, w) }), "_valid_ptr_p_w");
+        assert_eq!(ident(quote! { valid_ptr(p, r+w) }), "_valid_ptr_p_rw");
+        assert_eq!(ident(quote! { valid_ptr(p, r, len) }), "_valid_ptr_p_r_len");
+        assert_eq!(
+            ident(quote! { valid_ptr(p, w, n * 2) }),
+            "_valid_ptr_p_w_n_2a2"
+        );
+        assert_eq!(ident(quote! { proper_align(p) }), "_proper_align_p");
+        assert_eq!(ident(quote! { non_null(p) }), "_non_null_p");
+        assert_eq!(ident(quote! { nul_terminated(s) }), "_nul_terminated_s");
+        assert_eq!(ident(quote! { valid_cstr(s) }), "_valid_cstr_s");
+        assert_eq!(ident(quote! { initialized(p) }), "_initialized_p");
+        assert_eq!(ident(quote! { initialized(p, len) }), "_initialized_p_len");
+        assert_eq!(
+            ident(quote! { non_overlapping(src, dst, len) }),
+            "_non_overlapping_src_dst_len"
+        );
+        assert_eq!(
+            ident(quote! { proper_align_val(p, 32) }),
+            "_proper_align_val_p_32"
+        );
+        assert_eq!(ident(quote! { aligned_to(p, 32) }), "_aligned_to_p_32");
+        assert_eq!(
+            ident(quote! { in_range(x, 0..10) }),
+            "_in_range_x_0_2e_2e10"
+        );
+        assert_eq!(ident(quote! { consumed(x) }), "_consumed_x");
+        assert_eq!(ident(quote! { valid_utf8(x) }), "_valid_utf8_x");
+        assert_eq!(ident(quote! { valid_value(x, u8) }), "_valid_value_x_u8");
+        assert_eq!(ident(quote! { token(Lock) }), "_token_Lock");
+        assert_eq!(ident(quote! { x > 0 }), "_boolean_x_3e0");
+        assert_eq!(ident(quote! { x as u8 > 0 }), "_boolean_x_20as_20u8_3e0");
+        assert_eq!(ident(quote! { "foo_bar" }), "_custom_foo__bar");
+    }
+}
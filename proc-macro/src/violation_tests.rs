@@ -0,0 +1,341 @@
+//! Generates tests verifying that the preconditions of a function are checked at runtime.
+//!
+//! # What the generated code looks like
+//!
+//! ```rust,ignore
+//! #[pre::pre(x > 0)]
+//! #[pre::pre(gen_violation_tests(case(x = 0), case(x = -1)))]
+//! fn double(x: i32) -> i32 {
+//!     x * 2
+//! }
+//! ```
+//!
+//! turns into (using the `const_generics_impl` backend for brevity)
+//!
+//! ```rust,ignore
+//! fn double(x: i32, _: (::pre::BooleanCondition<"x > 0">,)) -> i32 {
+//!     ::core::debug_assert!(x > 0, "boolean precondition was wrongly assured: `{}`", "x > 0");
+//!     x * 2
+//! }
+//!
+//! #[cfg(all(test, debug_assertions))]
+//! #[allow(non_snake_case)]
+//! mod double__violation_tests__ {
+//!     use super::*;
+//!
+//!     #[test]
+//!     #[should_panic]
+//!     fn case_0() {
+//!         let _ = double(0, (::pre::BooleanCondition<"x > 0">,));
+//!     }
+//!
+//!     #[test]
+//!     #[should_panic]
+//!     fn case_1() {
+//!         let _ = double(-1, (::pre::BooleanCondition<"x > 0">,));
+//!     }
+//! }
+//! ```
+
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::emit_error;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse2,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token::Paren,
+    Expr, ExprCall, FnArg, Ident, ItemFn, Pat, Token,
+};
+
+use crate::{precondition::CfgPrecondition, render_assure};
+
+/// The custom keywords used in `gen_violation_tests` attributes.
+mod custom_keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(gen_violation_tests);
+    custom_keyword!(case);
+}
+
+/// A request to generate tests calling the function with arguments violating its preconditions.
+pub(crate) struct ViolationTests {
+    /// The `gen_violation_tests` keyword.
+    keyword: custom_keywords::gen_violation_tests,
+    /// The parentheses around the cases.
+    _parentheses: Paren,
+    /// The cases to generate a test for.
+    cases: Punctuated<ViolationCase, Token![,]>,
+}
+
+impl ViolationTests {
+    /// Checks whether the input starts with a `gen_violation_tests` attribute.
+    pub(crate) fn peek(input: ParseStream) -> bool {
+        input.peek(custom_keywords::gen_violation_tests)
+    }
+
+    /// The span of the `gen_violation_tests` keyword.
+    pub(crate) fn span(&self) -> Span {
+        self.keyword.span
+    }
+}
+
+impl Parse for ViolationTests {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword = input.parse()?;
+        let content;
+        let parentheses = parenthesized!(content in input);
+
+        Ok(ViolationTests {
+            keyword,
+            _parentheses: parentheses,
+            cases: Punctuated::parse_terminated(&content)?,
+        })
+    }
+}
+
+/// A single call with arguments violating a precondition, such as `case(x = 0, y = 1)`.
+struct ViolationCase {
+    /// The `case` keyword.
+    keyword: custom_keywords::case,
+    /// The parentheses around the arguments.
+    _parentheses: Paren,
+    /// The arguments of the call.
+    arguments: Punctuated<CaseArgument, Token![,]>,
+}
+
+impl Parse for ViolationCase {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword = input.parse()?;
+        let content;
+        let parentheses = parenthesized!(content in input);
+
+        Ok(ViolationCase {
+            keyword,
+            _parentheses: parentheses,
+            arguments: Punctuated::parse_terminated(&content)?,
+        })
+    }
+}
+
+/// The value of a single argument in a case, such as `x = 0`.
+struct CaseArgument {
+    /// The name of the parameter.
+    ident: Ident,
+    /// The `=` between the name and the value.
+    _eq: Token![=],
+    /// The value passed for the parameter.
+    value: Expr,
+}
+
+impl Parse for CaseArgument {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(CaseArgument {
+            ident: input.parse()?,
+            _eq: input.parse()?,
+            value: input.parse()?,
+        })
+    }
+}
+
+/// Returns the name of the module containing the violation tests for the given function.
+fn module_name(fn_name: &Ident) -> Ident {
+    format_ident!("{}__violation_tests__", fn_name, span = fn_name.span())
+}
+
+/// Generates the violation tests for the given function.
+///
+/// The function must not yet have the precondition argument added. `checked` specifies whether
+/// any of the preconditions is checked at runtime, `assured` whether calls need to assure the
+/// preconditions and `arg_first` whether the precondition argument is the first argument.
+pub(crate) fn render_violation_tests(
+    function: &ItemFn,
+    preconditions: &[CfgPrecondition],
+    tests: &ViolationTests,
+    checked: bool,
+    assured: bool,
+    arg_first: bool,
+) -> Option<TokenStream> {
+    let sig = &function.sig;
+    let span = tests.span();
+    let mut supported = true;
+
+    if let Some(receiver) = sig.receiver() {
+        emit_error!(
+            receiver.span(),
+            "violation tests are not supported for methods"
+        );
+        supported = false;
+    }
+    if let Some(asyncness) = &sig.asyncness {
+        emit_error!(
+            asyncness.span,
+            "violation tests are not supported for `async` functions"
+        );
+        supported = false;
+    }
+    if let Some(abi) = &sig.abi {
+        let name = abi.name.as_ref().map(|name| name.value());
+
+        if !matches!(name.as_deref(), Some(name) if name == "Rust" || name.ends_with("-unwind")) {
+            emit_error!(
+                abi.span(),
+                "violation tests are not supported for functions that cannot unwind";
+                note = "panics in these functions abort the test process"
+            );
+            supported = false;
+        }
+    }
+    if !checked {
+        emit_error!(
+            span,
+            "none of the preconditions of `{}` are checked at runtime", sig.ident;
+            help = "only boolean, `proper_align_val`, `aligned_to`, `non_null` and `in_range` preconditions are checked by `debug_assert`s, as well as `nul_terminated` and `valid_cstr` preconditions with the `cstr-checks` feature and `valid_utf8` preconditions with the `utf8-checks` feature"
+        );
+        supported = false;
+    }
+    if tests.cases.is_empty() {
+        emit_error!(
+            span,
+            "no cases for the violation tests specified";
+            help = "add the arguments of a call violating the preconditions, such as `case(x = 0)`"
+        );
+        supported = false;
+    }
+
+    let parameters = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => Some(&*pat_type.pat),
+            FnArg::Receiver(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut test_fns = Vec::new();
+    for (i, case) in tests.cases.iter().enumerate() {
+        let mut values = Vec::with_capacity(parameters.len());
+
+        for parameter in &parameters {
+            let name = match parameter {
+                Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => {
+                    emit_error!(
+                        parameter.span(),
+                        "violation tests are only supported for parameters bound to a name"
+                    );
+                    return None;
+                }
+            };
+
+            let mut matching = case.arguments.iter().filter(|arg| arg.ident == *name);
+            match (matching.next(), matching.next()) {
+                (Some(argument), None) => values.push(&argument.value),
+                (Some(_), Some(duplicate)) => {
+                    emit_error!(duplicate.ident, "duplicate value for `{}`", name);
+                    supported = false;
+                }
+                (None, _) => {
+                    emit_error!(
+                        case.keyword.span,
+                        "missing a value for `{}`", name;
+                        help = "add `{} = <value>` to the case", name
+                    );
+                    supported = false;
+                }
+            }
+        }
+
+        for argument in &case.arguments {
+            let is_parameter = parameters
+                .iter()
+                .any(|parameter| matches!(parameter, Pat::Ident(p) if p.ident == argument.ident));
+
+            if !is_parameter {
+                emit_error!(
+                    argument.ident,
+                    "`{}` has no parameter named `{}`",
+                    sig.ident,
+                    argument.ident
+                );
+                supported = false;
+            }
+        }
+
+        let fn_name = &sig.ident;
+        let call: ExprCall = parse2(quote_spanned! { span=>
+            #fn_name(#(#values),*)
+        })
+        .expect("parses as a call expression");
+        let call = if assured {
+            let call = render_assure(preconditions.to_vec(), call.into(), span, arg_first);
+            quote! { #call }
+        } else {
+            quote! { #call }
+        };
+
+        let body = if sig.unsafety.is_some() {
+            quote_spanned! { span=> let _ = unsafe { #call }; }
+        } else {
+            quote_spanned! { span=> let _ = #call; }
+        };
+        let test_name = format_ident!("case_{}", i, span = case.keyword.span);
+
+        test_fns.push(quote_spanned! { span=>
+            #[test]
+            #[should_panic]
+            fn #test_name() {
+                #body
+            }
+        });
+    }
+
+    if !supported {
+        return None;
+    }
+
+    let name = module_name(&sig.ident);
+
+    Some(quote_spanned! { span=>
+        #[cfg(all(test, debug_assertions))]
+        #[allow(non_snake_case)]
+        mod #name {
+            use super::*;
+
+            #(#test_fns)*
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_violation_tests() {
+        let tests: ViolationTests =
+            parse2(quote! { gen_violation_tests(case(x = 0), case(x = -1, y = "foo"),) })
+                .expect("valid violation tests");
+
+        let cases = tests
+            .cases
+            .iter()
+            .map(|case| {
+                case.arguments
+                    .iter()
+                    .map(|arg| {
+                        let value = &arg.value;
+                        format!("{} = {}", arg.ident, quote! { #value })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(cases, [vec!["x = 0"], vec!["x = - 1", "y = \"foo\""]]);
+
+        assert!(parse2::<ViolationTests>(quote! { gen_violation_tests() }).is_ok());
+        assert!(parse2::<ViolationTests>(quote! { gen_violation_tests(x = 0) }).is_err());
+        assert!(parse2::<ViolationTests>(quote! { gen_violation_tests(case(0)) }).is_err());
+    }
+}
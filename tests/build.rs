@@ -1,12 +1,19 @@
-use rustc_version::{version_meta, Channel};
+use rustc_version::version_meta;
+
+// The detection of the nightly version is shared with the `pre` crate, so that both agree on which
+// version of pre is used.
+include!("../main/build/nightly.rs");
 
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../main/build/nightly.rs");
 
-    match version_meta() {
-        Ok(version) if version.channel == Channel::Nightly => {
-            println!("cargo:rustc-cfg=nightly");
-        }
-        _ => (),
+    // The `nightly` cfg selects the expected output of the tests, which depends on the encoding
+    // of the preconditions. The `backend-struct` feature uses the stable encoding on any compiler.
+    if env::var_os("CARGO_FEATURE_BACKEND_STRUCT").is_some() {
+        println!("cargo:rustc-check-cfg=cfg(nightly)");
+        return;
     }
+
+    detect_nightly(version_meta().ok().as_ref());
 }
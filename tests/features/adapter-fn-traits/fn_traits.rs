@@ -0,0 +1,38 @@
+#![feature(unboxed_closures, fn_traits)]
+
+use pre::{assure_for_all_calls, pre};
+
+#[pre(adapter)]
+#[pre(x > 0)]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pre(adapter)]
+#[pre(valid_ptr(p, r))]
+unsafe fn first<'a, T: Copy>(p: *const T, _marker: &'a ()) -> T {
+    *p
+}
+
+fn apply_all(f: impl Fn(i32) -> i32, values: &[i32]) -> Vec<i32> {
+    values.iter().map(|&v| f(v)).collect()
+}
+
+fn main() {
+    let doubler = assure_for_all_calls!(
+        #[assure(x > 0, reason = "all values are positive")]
+        double as DoubleChecked
+    );
+
+    assert_eq!(doubler(2), 4);
+    assert_eq!(apply_all(doubler, &[1, 2]), vec![2, 4]);
+
+    let value = 3u8;
+    let reader = unsafe {
+        assure_for_all_calls!(
+            #[assure(valid_ptr(p, r), reason = "`p` points to `value`")]
+            first as FirstChecked
+        )
+    };
+    assert_eq!(reader(&value as *const u8, &()), 3);
+}
@@ -0,0 +1,39 @@
+use pre::pre;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic,
+    ptr,
+};
+
+#[pre(valid_cstr(string))]
+fn length(string: *const c_char) -> usize {
+    unsafe { CStr::from_ptr(string) }.to_bytes().len()
+}
+
+#[pre(nul_terminated(string))]
+fn is_empty(string: *const c_char) -> bool {
+    unsafe { *string == 0 }
+}
+
+#[pre]
+fn main() {
+    // Valid strings are never reported, no matter how long they are.
+    let long = CString::new(vec![b'a'; 4097]).unwrap();
+
+    #[assure(valid_cstr(string), reason = "`long` is a `CString` that outlives the call")]
+    let len = length(long.as_ptr());
+    assert_eq!(len, 4097);
+
+    #[assure(nul_terminated(string), reason = "`long` is a `CString` that outlives the call")]
+    let empty = is_empty(long.as_ptr());
+    assert!(!empty);
+
+    // Null pointers are still reported.
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(|| {
+        #[assure(valid_cstr(string), reason = "wrongly assured to trigger the null check")]
+        length(ptr::null())
+    });
+    assert!(result.is_err());
+}
@@ -0,0 +1,31 @@
+use pre::pre;
+
+defmt::timestamp!("{=u32}", 0);
+
+#[defmt::global_logger]
+struct Logger;
+
+unsafe impl defmt::Logger for Logger {
+    fn acquire() {}
+
+    unsafe fn flush() {}
+
+    unsafe fn release() {}
+
+    unsafe fn write(_bytes: &[u8]) {}
+}
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    let x = std::hint::black_box(1);
+
+    #[assure(x > 0, reason = "`x` is 1")]
+    let y = positive(x);
+
+    assert_eq!(y, 1);
+}
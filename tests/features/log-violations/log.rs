@@ -0,0 +1,43 @@
+use log::{Log, Metadata, Record};
+use pre::pre;
+use std::{panic, sync::Mutex};
+
+static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        MESSAGES.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    log::set_logger(&Logger).unwrap();
+    log::set_max_level(log::LevelFilter::Error);
+
+    let x = std::hint::black_box(0);
+
+    let result = panic::catch_unwind(|| {
+        #[assure(x > 0, reason = "wrongly assured to log the violation")]
+        positive(x)
+    });
+
+    assert!(result.is_err());
+
+    let messages = MESSAGES.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("(precondition of `positive` with contract ID `"));
+}
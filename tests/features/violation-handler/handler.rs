@@ -0,0 +1,31 @@
+use pre::{pre, ContractViolation};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static VIOLATIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_violation(violation: &ContractViolation) {
+    assert_eq!(violation.function, "checked_div");
+    assert_eq!(violation.condition, "divisor != 0");
+    assert!(violation.file.ends_with("handler.rs"));
+
+    VIOLATIONS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[pre(divisor != 0)]
+fn checked_div(dividend: u32, divisor: u32) -> Option<u32> {
+    dividend.checked_div(divisor)
+}
+
+#[pre]
+fn main() {
+    pre::set_violation_handler(count_violation);
+
+    let divisor = std::hint::black_box(0);
+
+    #[assure(divisor != 0, reason = "wrongly assured to call the handler")]
+    let result = checked_div(4, divisor);
+
+    assert_eq!(result, None);
+    assert_eq!(VIOLATIONS.load(Ordering::SeqCst), 1);
+    assert!(pre::take_violation_handler().is_some());
+}
@@ -0,0 +1,13 @@
+use pre::extern_crate;
+
+#[extern_crate(core)]
+mod pre_core {
+    mod mem {
+        const SIZE: usize = 4;
+    }
+}
+
+#[allow(unused_imports)]
+use pre_core::mem as _;
+
+fn main() {}
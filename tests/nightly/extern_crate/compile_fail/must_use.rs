@@ -0,0 +1,29 @@
+#![deny(unused_must_use)]
+
+use pre::pre;
+
+#[pre::extern_crate(core)]
+#[pre(must_use)]
+mod pre_core {
+    mod mem {
+        #[pre("`dest` is valid")]
+        fn replace<T>(dest: &mut T, src: T) -> T;
+    }
+}
+
+#[pre(must_use)]
+#[pre(x > 0)]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pre]
+fn main() {
+    let mut value = 1;
+
+    #[assure("`dest` is valid", reason = "`value` is a local variable")]
+    pre_core::mem::replace(&mut value, 2);
+
+    #[assure(x > 0, reason = "2 > 0")]
+    double(2);
+}
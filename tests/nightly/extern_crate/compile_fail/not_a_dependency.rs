@@ -0,0 +1,6 @@
+#[pre::extern_crate(libc)]
+mod pre_libc {
+    unsafe fn strlen(cs: *const i8) -> usize;
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+use pre::pre;
+
+mod a {
+    pub(crate) mod io {
+        pub(crate) trait Read {
+            unsafe fn read_unchecked(&self, index: usize) -> u8;
+        }
+    }
+
+    pub(crate) struct File(pub(crate) [u8; 4]);
+
+    impl io::Read for File {
+        unsafe fn read_unchecked(&self, index: usize) -> u8 {
+            *self.0.get_unchecked(index)
+        }
+    }
+}
+
+#[pre::extern_crate(crate::a)]
+mod b {
+    impl io::Read for File {
+        #[pre("`index` is less than 4")]
+        unsafe fn read_unchecked(&self, index: usize) -> u8;
+    }
+}
+
+#[pre]
+fn main() {
+    use a::io::Read;
+
+    let file = a::File([1, 2, 3, 4]);
+
+    #[forward(impl b::io::Read for File)]
+    let _ = unsafe { file.read_unchecked(2) };
+
+    #[forward(impl b::File)]
+    #[assure("`index` is less than 4", reason = "2 is less than 4")]
+    let _ = unsafe { file.read_unchecked(2) };
+}
@@ -0,0 +1,21 @@
+use pre::pre;
+
+#[pre::extern_crate(core)]
+mod pre_core {
+    type Ptr<T> = ptr::NonNull<T>;
+
+    mod ptr {
+        impl<T> NonNull<T> {
+            #[pre(!ptr.is_null())]
+            const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T>;
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    let mut val = 42;
+
+    #[forward(impl pre_core::Ptr)]
+    let _ptr = unsafe { core::ptr::NonNull::new_unchecked(&mut val) };
+}
@@ -0,0 +1,46 @@
+use pre::pre;
+
+#[pre]
+fn main() {
+    use pre::alloc::alloc::{alloc, dealloc, realloc, Layout};
+
+    #[forward(impl pre::alloc::alloc::Layout)]
+    #[assure(align.is_power_of_two(), reason = "4 is a power of two")]
+    #[assure(
+        "`size`, when rounded up to the nearest multiple of `align`, does not overflow an `isize`",
+        reason = "16 is a multiple of 4"
+    )]
+    let layout = unsafe { Layout::from_size_align_unchecked(16, 4) };
+
+    #[assure(layout.size() != 0, reason = "the size of the layout is 16")]
+    let ptr = unsafe { alloc(layout) };
+    assert!(!ptr.is_null());
+
+    #[assure(
+        "`ptr` is currently allocated via the global allocator",
+        reason = "`ptr` was just allocated"
+    )]
+    #[assure(
+        "`layout` is the same layout that was used to allocate `ptr`",
+        reason = "`ptr` was allocated with `layout`"
+    )]
+    #[assure(new_size != 0, reason = "32 is not 0")]
+    #[assure(
+        "`new_size`, when rounded up to the nearest multiple of `layout.align()`, does not overflow an `isize`",
+        reason = "32 is a multiple of 4"
+    )]
+    let ptr = unsafe { realloc(ptr, layout, 32) };
+    assert!(!ptr.is_null());
+
+    let layout = Layout::from_size_align(32, 4).unwrap();
+
+    #[assure(
+        "`ptr` is currently allocated via the global allocator",
+        reason = "`ptr` was just reallocated"
+    )]
+    #[assure(
+        "`layout` is the same layout that was used to allocate `ptr`",
+        reason = "`ptr` was reallocated to the size of `layout`"
+    )]
+    unsafe { dealloc(ptr, layout) };
+}
@@ -0,0 +1,59 @@
+use pre::pre;
+
+mod a {
+    pub(crate) struct X<T>(T);
+
+    impl X<bool> {
+        pub(crate) fn foo() {}
+    }
+
+    impl X<()> {
+        pub(crate) fn foo() {}
+    }
+
+    impl<T> X<T> {
+        pub(crate) fn bar() {}
+    }
+}
+
+#[pre::extern_crate(crate::a)]
+mod b {
+    impl X<bool> {
+        #[pre("foo is called for `bool`")]
+        fn foo();
+    }
+
+    impl X<()> {
+        #[pre("foo is called for `()`")]
+        fn foo();
+    }
+
+    impl<T> X<T> {
+        #[pre("bar is called")]
+        fn bar();
+    }
+}
+
+#[allow(non_snake_case)]
+fn X__impl__foo__() {}
+
+#[pre]
+fn main() {
+    X__impl__foo__();
+
+    #[forward(impl b::X<bool>)]
+    #[assure("foo is called for `bool`", reason = "it is")]
+    a::X::<bool>::foo();
+
+    #[forward(impl b::X<()>)]
+    #[assure("foo is called for `()`", reason = "it is")]
+    a::X::<()>::foo();
+
+    #[forward(impl b::X)]
+    #[assure("bar is called", reason = "it is")]
+    a::X::<u8>::bar();
+
+    #[forward(impl b::X<_>)]
+    #[assure("bar is called", reason = "it is")]
+    a::X::<u8>::bar();
+}
@@ -0,0 +1,61 @@
+use pre::pre;
+
+#[pre::extern_crate(std)]
+mod pre_std {
+    mod ptr {
+        #[pre(valid_ptr(src, r))]
+        #[pre(proper_align(src))]
+        #[pre("`src` points to a properly initialized value of type `T`")]
+        #[pre("`T` is `Copy` or the value at `*src` isn't used after this call")]
+        unsafe fn read<T>(src: *const T) -> T;
+    }
+
+    impl<T> ptr::NonNull<T> {
+        #[pre(!ptr.is_null())]
+        const unsafe fn new_unchecked(ptr: *mut T) -> Self;
+    }
+
+    impl sync::atomic::AtomicUsize {
+        #[pre(valid_ptr(ptr, r+w))]
+        #[pre("`ptr` is aligned to `align_of::<AtomicUsize>()`")]
+        #[pre("the memory at `ptr` is only accessed atomically during the lifetime `'a`")]
+        unsafe fn from_ptr<'a>(ptr: *mut usize) -> &'a AtomicUsize;
+    }
+}
+
+#[pre]
+fn main() {
+    let mut val = 0;
+
+    #[forward(impl pre_std::ptr::NonNull)]
+    #[assure(!ptr.is_null(), reason = "`ptr` is a reference")]
+    let non_null = unsafe { std::ptr::NonNull::new_unchecked(&mut val) };
+    assert_eq!(non_null, std::ptr::NonNull::from(&mut val));
+
+    #[assure(valid_ptr(src, r), reason = "`src` is a reference")]
+    #[assure(proper_align(src), reason = "`src` is a reference")]
+    #[assure(
+        "`src` points to a properly initialized value of type `T`",
+        reason = "`src` is a reference"
+    )]
+    #[assure(
+        "`T` is `Copy` or the value at `*src` isn't used after this call",
+        reason = "`usize` is `Copy`"
+    )]
+    let copy = unsafe { pre_std::ptr::read(&val) };
+    assert_eq!(copy, 0);
+
+    #[forward(impl pre_std::sync::atomic::AtomicUsize)]
+    #[assure(valid_ptr(ptr, r+w), reason = "`ptr` is a mutable reference")]
+    #[assure(
+        "`ptr` is aligned to `align_of::<AtomicUsize>()`",
+        reason = "`usize` and `AtomicUsize` have the same alignment on this platform"
+    )]
+    #[assure(
+        "the memory at `ptr` is only accessed atomically during the lifetime `'a`",
+        reason = "`val` is not used while `atomic` exists"
+    )]
+    let atomic = unsafe { std::sync::atomic::AtomicUsize::from_ptr(&mut val) };
+    atomic.store(42, std::sync::atomic::Ordering::Relaxed);
+    assert_eq!(val, 42);
+}
@@ -0,0 +1,46 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[pre::extern_crate(core)]
+mod pre_core {
+    type Ptr<T> = ptr::NonNull<T>;
+
+    mod ptr {
+        type NonNullPtr<T> = NonNull<T>;
+
+        impl<T> NonNull<T> {
+            #[pre(!ptr.is_null())]
+            const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T>;
+        }
+    }
+
+    mod num {
+        type NonZero = NonZeroUsize;
+
+        impl NonZeroUsize {
+            #[pre(n != 0)]
+            const unsafe fn new_unchecked(n: usize) -> NonZeroUsize;
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    let mut val = 42;
+
+    #[forward(impl pre_core::ptr::NonNullPtr)]
+    #[assure(!ptr.is_null(), reason = "`ptr` is a reference")]
+    let ptr: pre_core::ptr::NonNullPtr<i32> = unsafe { core::ptr::NonNull::new_unchecked(&mut val) };
+    assert_eq!(unsafe { *ptr.as_ptr() }, 42);
+
+    #[forward(impl pre_core::Ptr)]
+    #[assure(!ptr.is_null(), reason = "`ptr` is a reference")]
+    let ptr: pre_core::Ptr<i32> = unsafe { core::ptr::NonNull::new_unchecked(&mut val) };
+    assert_eq!(unsafe { *ptr.as_ptr() }, 42);
+
+    #[forward(impl pre_core::num::NonZero)]
+    #[assure(n != 0, reason = "42 is not 0")]
+    let n = unsafe { core::num::NonZeroUsize::new_unchecked(42) };
+    assert_eq!(n.get(), 42);
+}
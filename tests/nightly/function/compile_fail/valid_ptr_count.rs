@@ -0,0 +1,16 @@
+use pre::pre;
+
+#[pre(valid_ptr(src, r, count))]
+unsafe fn read_all(src: *const u8, count: usize) -> u8 {
+    *src.add(count - 1)
+}
+
+#[pre]
+fn main() {
+    let src = [1, 2, 3, 4];
+
+    let _ = unsafe {
+        #[assure(valid_ptr(src, r), reason = "`src` is valid for reads")]
+        read_all(src.as_ptr(), 4)
+    };
+}
@@ -0,0 +1,72 @@
+use pre::pre;
+
+mod checked {
+    use pre::pre;
+
+    #[pre(x > 0)]
+    pub fn digits(x: i32) -> Result<Vec<i32>, ()> {
+        Ok(vec![x])
+    }
+}
+
+fn digits(x: i32) -> Result<Vec<i32>, ()> {
+    Ok(vec![x])
+}
+
+#[pre(x > 0)]
+fn positive(x: i32) -> Result<Vec<i32>, ()> {
+    Ok(vec![x])
+}
+
+#[pre(x > 0)]
+async fn positive_async(x: i32) -> Vec<i32> {
+    vec![x]
+}
+
+#[pre]
+fn results() -> Result<(), ()> {
+    #[assure(x > 0, reason = "1 > 0")]
+    positive(1)?;
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let first = positive(1)?[0];
+    assert_eq!(first, 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let len = positive(1)?.len();
+    assert_eq!(len, 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let last = positive(1)?.iter().last().copied();
+    assert_eq!(last, Some(1));
+
+    #[forward(checked)]
+    #[assure(x > 0, reason = "1 > 0")]
+    digits(1)?;
+
+    #[forward(checked)]
+    #[assure(x > 0, reason = "1 > 0")]
+    let len = digits(1)?.len();
+    assert_eq!(len, 1);
+
+    Ok(())
+}
+
+#[pre]
+async fn awaited() {
+    #[assure(x > 0, reason = "1 > 0")]
+    positive_async(1).await;
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let first = positive_async(1).await[0];
+    assert_eq!(first, 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let len = positive_async(1).await.len();
+    assert_eq!(len, 1);
+}
+
+fn main() {
+    results().unwrap();
+    let _ = awaited();
+}
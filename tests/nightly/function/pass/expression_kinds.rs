@@ -0,0 +1,216 @@
+#![allow(unused_assignments, unused_must_use, unused_parens, unused_unsafe)]
+
+use pre::pre;
+
+#[pre(x > 0)]
+fn f(x: i32) -> i32 {
+    x
+}
+
+#[pre(x > 0)]
+fn s(x: i32) -> S {
+    S { a: x }
+}
+
+#[pre(x > 0)]
+fn opt(x: i32) -> Option<i32> {
+    Some(x)
+}
+
+#[pre(x > 0)]
+async fn af(x: i32) -> i32 {
+    x
+}
+
+struct S {
+    a: i32,
+}
+
+macro_rules! assure_positive {
+    ($e:expr) => {
+        #[pre]
+        fn group() -> i32 {
+            #[assure(x > 0, reason = "1 > 0")]
+            $e
+        }
+    };
+}
+
+assure_positive!(f(1));
+
+#[pre]
+fn try_position() -> Option<i32> {
+    let val = #[assure(x > 0, reason = "1 > 0")]
+    opt(1)?;
+
+    Some(val)
+}
+
+#[pre]
+fn main() {
+    let mut v = 0;
+
+    // Array
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    [0, f(1)];
+
+    // Assign
+    #[assure(x > 0, reason = "1 > 0")]
+    v = f(1);
+
+    // AssignOp
+    #[assure(x > 0, reason = "1 > 0")]
+    v += f(1);
+
+    // Async
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    async {
+        f(1)
+    };
+
+    // Await
+    let _ = async {
+        #[assure(x > 0, reason = "1 > 0")]
+        af(1).await
+    };
+
+    // Binary
+    #[assure(x > 0, reason = "1 > 0")]
+    v + f(1);
+
+    // Block
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    {
+        f(1)
+    };
+
+    // Break
+    let _ = loop {
+        #[assure(x > 0, reason = "1 > 0")]
+        break f(1);
+    };
+
+    // Call
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    f(1);
+
+    // Cast
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    f(1) as i64;
+
+    // Closure
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    |y: i32| f(1) + y;
+
+    // Field
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    s(1).a;
+
+    // ForLoop
+    #[assure(x > 0, reason = "1 > 0")]
+    for _ in 0..f(1) {}
+
+    // Group
+    assert_eq!(group(), 1);
+
+    // If
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    if f(1) > 0 {
+        1
+    } else {
+        0
+    };
+
+    // Index
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    [1, 2][f(1) as usize];
+
+    // Let
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    if let 1 = f(1) {
+        1
+    } else {
+        0
+    };
+
+    // Loop
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    loop {
+        break f(1);
+    };
+
+    // Macro
+    #[assure(x > 0, reason = "1 > 0")]
+    assert_eq!(f(1), 1);
+
+    // Match
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    match f(1) {
+        _ => 0,
+    };
+
+    // Paren
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    (f(1));
+
+    // Range
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    (0..f(1));
+    #[assure(x > 0, reason = "1 > 0")]
+    0..f(1);
+
+    // Reference
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    &f(1);
+
+    // Repeat
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    [f(1); 2];
+
+    // Return
+    let _ = || {
+        #[assure(x > 0, reason = "1 > 0")]
+        return f(1);
+    };
+    let _ = || {
+        #[assure(x > 0, reason = "1 > 0")]
+        return unsafe { f(1) };
+    };
+
+    // Struct
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    S { a: f(1) };
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    S { ..s(1) };
+    let a = 2;
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    S { a, ..s(1) };
+    #[assure(x > 0, reason = "1 > 0")]
+    let _ = S {
+        a,
+        ..unsafe { s(1) }
+    };
+
+    // Try
+    assert_eq!(try_position(), Some(1));
+
+    // Tuple
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    (f(1), 2);
+
+    // Unary
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    -f(1);
+
+    // Unsafe
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    unsafe {
+        f(1)
+    };
+
+    // While
+    #[assure(x > 0, reason = "1 > 0")]
+    while f(1) < 0 {}
+
+    assert_eq!(v, 2);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+
+#[pre(x > 0)]
+#[pre("`x` is a valid index")]
+extern "C" fn c_abi(x: u32) -> u32 {
+    x
+}
+
+#[pre(x > 0)]
+extern "Rust" fn rust_abi(x: u32) -> u32 {
+    x
+}
+
+#[pre(valid_ptr(ptr, r))]
+#[no_mangle]
+unsafe fn __pre_test_read(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+#[pre]
+fn main() {
+    assert_eq!(c_abi(1), 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let result = rust_abi(1);
+    assert_eq!(result, 1);
+
+    let val = 42;
+    assert_eq!(unsafe { __pre_test_read(&val) }, 42);
+
+    let c_fn: extern "C" fn(u32) -> u32 = c_abi;
+    assert_eq!(c_fn(2), 2);
+}
@@ -0,0 +1,37 @@
+use pre::pre;
+use std::mem::size_of;
+
+#[pre(len <= isize::MAX as usize / size_of::<T>())]
+fn fits<T>(len: usize) -> usize {
+    len
+}
+
+#[pre(allow(unchecked_condition))]
+#[pre(len * size_of::<T>() <= isize::MAX as usize)]
+fn describe<T: ?Sized>(_value: &T, len: usize) -> usize {
+    len
+}
+
+#[pre]
+fn main() {
+    #[assure(
+        len <= isize::MAX as usize / size_of::<T>(),
+        reason = "zero-sized types fit any number of times"
+    )]
+    let len = fits::<()>(usize::MAX);
+    assert_eq!(len, usize::MAX);
+
+    #[assure(
+        len <= isize::MAX as usize / size_of::<T>(),
+        reason = "4 `u32`s fit into `isize::MAX` bytes"
+    )]
+    let len = fits::<u32>(4);
+    assert_eq!(len, 4);
+
+    #[assure(
+        len * size_of::<T>() <= isize::MAX as usize,
+        reason = "`str` is unsized, the condition is only documented"
+    )]
+    let len = describe("pre", 3);
+    assert_eq!(len, 3);
+}
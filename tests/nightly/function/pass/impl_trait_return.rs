@@ -0,0 +1,42 @@
+use pre::pre;
+use std::future::Future;
+
+#[pre(x > 0)]
+fn count_down(x: u32) -> impl Iterator<Item = u32> {
+    (0..x).rev()
+}
+
+#[pre(valid_ptr(ptr, r))]
+fn read_later<'a>(ptr: *const u32) -> impl Future<Output = u32> + 'a {
+    async move { unsafe { *ptr } }
+}
+
+#[pre(x > 0)]
+async fn halve(x: u32) -> u32 {
+    x / 2
+}
+
+#[pre(x > 0)]
+fn halve_later(x: u32) -> impl Future<Output = u32> {
+    async move {
+        #[assure(x > 0, reason = "`x > 0` is a precondition of `halve_later`")]
+        halve(x).await
+    }
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "3 > 0")]
+    let values = count_down(3);
+    assert_eq!(values.collect::<Vec<_>>(), [2, 1, 0]);
+
+    let value = 42;
+
+    #[assure(valid_ptr(ptr, r), reason = "`value` lives until the future is dropped")]
+    let future = read_later(&value);
+    drop(future);
+
+    #[assure(x > 0, reason = "4 > 0")]
+    let future = halve_later(4);
+    drop(future);
+}
@@ -0,0 +1,40 @@
+use pre::{assure_for_all_calls, pre};
+
+#[pre(precondition_arg_first)]
+#[pre(x > 0)]
+#[pre(adapter)]
+fn div(x: i32, y: i32) -> i32 {
+    y / x
+}
+
+mod checked {
+    use pre::pre;
+
+    #[pre(precondition_arg_first)]
+    #[pre(x > 0)]
+    pub fn half(x: i32) -> i32 {
+        x / 2
+    }
+}
+
+fn half(x: i32) -> i32 {
+    x / 2
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "2 > 0")]
+    let quotient = div(2, 4);
+    assert_eq!(quotient, 2);
+
+    #[forward(checked)]
+    #[assure(x > 0, reason = "2 > 0")]
+    let halved = half(2);
+    assert_eq!(halved, 1);
+
+    let div_positive = assure_for_all_calls!(
+        #[assure(x > 0, reason = "only called with positive values")]
+        div
+    );
+    assert_eq!(div_positive(2, 4), 2);
+}
@@ -0,0 +1,25 @@
+use pre::pre;
+
+#[pre]
+mod local {
+    #[pre("the file is open")]
+    pub fn close() {}
+}
+
+use other::close;
+
+#[pre]
+fn main() {
+    #[assure("the file is open", reason = "the file was never closed")]
+    local::close();
+
+    #[assure("the file is opened", reason = "the file was never closed")]
+    close();
+}
+
+mod other {
+    use pre::pre;
+
+    #[pre("the file is opened")]
+    pub fn close() {}
+}
@@ -0,0 +1,10 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+#[pre(len > 0)]
+unsafe fn read(ptr: *const u8, #[cfg(all())] len: usize) -> u8 {
+    let _ = len;
+    *ptr
+}
+
+fn main() {}
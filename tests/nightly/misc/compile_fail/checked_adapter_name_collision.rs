@@ -0,0 +1,15 @@
+use pre::pre;
+
+#[pre(adapter)]
+#[pre(x > 0)]
+fn read_value(x: i32) -> i32 {
+    x
+}
+
+#[pre(adapter)]
+#[pre(x > 0)]
+fn read__value(x: i32) -> i32 {
+    x
+}
+
+fn main() {}
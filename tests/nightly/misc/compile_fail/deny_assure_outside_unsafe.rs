@@ -0,0 +1,30 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read_byte(ptr: *const u8) -> u8 {
+    *ptr
+}
+
+#[pre(valid_ptr(ptr, r))]
+#[pre(deny(assure_outside_unsafe))]
+unsafe fn read_twice(ptr: *const u8) -> (u8, u8) {
+    #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+    let first = read_byte(ptr);
+
+    let second = unsafe {
+        #[assure(valid_ptr(ptr, r), reason = "guaranteed by the caller")]
+        read_byte(ptr)
+    };
+
+    (first, second)
+}
+
+#[pre]
+fn main() {
+    let byte = 42;
+
+    #[assure(valid_ptr(ptr, r), reason = "`byte` is a local variable")]
+    let bytes = unsafe { read_twice(&byte) };
+
+    assert_eq!(bytes, (42, 42));
+}
@@ -0,0 +1,31 @@
+use pre::pre;
+
+#[pre::extern_crate(std)]
+mod pre_std {
+    mod ptr {
+        #[pre(valid_ptr(src, r))]
+        unsafe fn read<T>(src: *const T) -> T;
+
+        fn null<T>() -> *const T;
+
+        impl<T> NonNull<T> {
+            fn dangling() -> NonNull<T>;
+        }
+    }
+}
+
+#[pre(deny(forward_without_preconditions))]
+fn main() {
+    #[forward(std -> pre_std)]
+    let ptr = std::ptr::null::<i32>();
+
+    #[forward(impl pre_std::ptr::NonNull)]
+    let _dangling = std::ptr::NonNull::<i32>::dangling();
+
+    let value = 42;
+    #[forward(std -> pre_std)]
+    #[assure(valid_ptr(src, r), reason = "a reference is a valid pointer")]
+    let _value = unsafe { std::ptr::read(&value) };
+
+    let _ = ptr;
+}
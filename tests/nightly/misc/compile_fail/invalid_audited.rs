@@ -0,0 +1,15 @@
+use pre::pre;
+
+#[pre(x > 0)]
+fn foo(x: u32) -> u32 {
+    x
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "1 > 0", audited(by = "", date = "2024-06-01"))]
+    foo(1);
+
+    #[assure(x > 0, reason = "2 > 0", audited(by = "alice", date = "June 1st"))]
+    foo(2);
+}
@@ -0,0 +1,30 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+#[pre]
+fn main() {
+    let values = [1, 2, 3];
+    let ptr = values.as_ptr();
+
+    #[assure_each(read, valid_ptr(ptr, r), reason = "`ptr` points into `values`", count = 2)]
+    unsafe {
+        read(ptr);
+        read(ptr);
+        read(ptr);
+    }
+
+    #[assure_each(read, valid_ptr(ptr, r), reason = "`ptr` points into `values`")]
+    unsafe {
+        let _read_later = || {
+            #[assure(valid_ptr(ptr, r), reason = "`ptr` points into `values`")]
+            read(ptr)
+        };
+    }
+
+    #[assure_each(read, valid_ptr(ptr, r), reason = "`ptr` points into `values`")]
+    let _ = values.len();
+}
@@ -0,0 +1,24 @@
+use pre::pre;
+
+#[pre("`x` is divisible by two")]
+fn half(x: u32) -> u32 {
+    x / 2
+}
+
+#[pre]
+fn before() {
+    #[assure("`x` is even", reason = "42 is even")]
+    half(42);
+}
+
+pre::rename_condition!("`x` is even" => "`x` is divisible by two");
+
+#[pre]
+fn after() {
+    #[assure("`x` is even", reason = "42 is even")]
+    half(42);
+}
+
+pre::rename_condition!("`x` is odd" => "`x` is odd");
+
+fn main() {}
@@ -0,0 +1,25 @@
+use pre::pre;
+
+mod old_api {
+    use pre::pre;
+
+    #[pre(x > 0)]
+    pub fn foo(x: i32) {
+        let _ = x;
+    }
+}
+
+mod new_api {
+    use pre::pre;
+
+    #[pre(x > 1)]
+    pub fn foo(x: i32) {
+        let _ = x;
+    }
+}
+
+#[pre]
+fn main() {
+    #[assure(same_as(old_api::foo), reason = "the same contract")]
+    old_api::foo(2);
+}
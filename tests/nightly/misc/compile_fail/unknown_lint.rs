@@ -0,0 +1,4 @@
+use pre::pre;
+
+#[pre(allow(todo_reasons))]
+fn main() {}
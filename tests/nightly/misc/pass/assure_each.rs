@@ -0,0 +1,52 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+mod inner {
+    use pre::pre;
+
+    #[pre(x > 0)]
+    #[pre(x < 100)]
+    pub(crate) unsafe fn checked(x: u32) -> u32 {
+        x
+    }
+}
+
+struct Reader(*const u32);
+
+#[pre]
+impl Reader {
+    fn sum(&self) -> u32 {
+        #[assure_each(read, valid_ptr(ptr, r), reason = "`self.0` points to a value", count = 2)]
+        unsafe {
+            read(self.0) + read(self.0)
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    let values = [1, 2, 3];
+    let ptr = values.as_ptr();
+
+    #[assure_each(read, valid_ptr(ptr, r), reason = "`ptr` points into `values`", count = 3)]
+    unsafe {
+        let sum = read(ptr) + read(ptr);
+        assert_eq!(sum, read(ptr) * 2);
+    }
+
+    #[assure_each(inner::checked, x > 0, reason = "all values are positive")]
+    #[assure_each(
+        inner::checked,
+        x < 100,
+        reason = "all values are small",
+        audit = "REVIEW-1"
+    )]
+    let total = unsafe { inner::checked(values[0]) + inner::checked(values[2]) };
+    assert_eq!(total, 4);
+
+    assert_eq!(Reader(ptr).sum(), 2);
+}
@@ -0,0 +1,28 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r, len))]
+#[pre(len > 0)]
+unsafe fn first_removed(ptr: *const u8, #[cfg(any())] len: usize) -> u8 {
+    *ptr
+}
+
+#[pre(valid_ptr(ptr, r, len))]
+#[pre(len > 0)]
+unsafe fn first_kept(ptr: *const u8, #[cfg(all())] len: usize) -> u8 {
+    *ptr
+}
+
+#[pre]
+fn main() {
+    let bytes = [42, 43];
+
+    unsafe {
+        first_removed(bytes.as_ptr());
+    }
+
+    unsafe {
+        #[assure(valid_ptr(ptr, r, len), reason = "`bytes` contains two elements")]
+        #[assure(len > 0, reason = "2 > 0")]
+        first_kept(bytes.as_ptr(), 2);
+    }
+}
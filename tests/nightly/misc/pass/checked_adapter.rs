@@ -0,0 +1,101 @@
+use pre::{assure_for_all_calls, pre};
+
+#[pre(adapter)]
+#[pre(x > 0)]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pre(adapter)]
+#[pre(x > 0)]
+fn triple(x: i32) -> i32 {
+    x * 3
+}
+
+#[pre(adapter)]
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read_value<T: Copy>(ptr: *const T) -> T {
+    *ptr
+}
+
+#[pre(adapter)]
+#[pre("`s` is not empty")]
+fn first(s: &str) -> &str {
+    &s[..1]
+}
+
+mod inner {
+    use pre::pre;
+
+    #[pre(adapter)]
+    #[pre("is called from a test")]
+    pub fn nothing() {}
+}
+
+enum Operation {
+    Double(DoubleChecked),
+    Triple(TripleChecked),
+}
+
+impl Operation {
+    fn apply(&self, x: i32) -> i32 {
+        match self {
+            Operation::Double(double) => double.call(x),
+            Operation::Triple(triple) => triple.call(x),
+        }
+    }
+}
+
+fn main() {
+    let table = [
+        (
+            "double",
+            Operation::Double(assure_for_all_calls!(
+                #[assure(x > 0, reason = "the table is only used with positive values")]
+                double as DoubleChecked
+            )),
+        ),
+        (
+            "triple",
+            Operation::Triple(assure_for_all_calls!(
+                #[assure(x > 0, reason = "the table is only used with positive values")]
+                triple as TripleChecked
+            )),
+        ),
+    ];
+    let results: Vec<_> = table
+        .iter()
+        .map(|(name, operation)| (*name, operation.apply(2)))
+        .collect();
+    assert_eq!(results, vec![("double", 4), ("triple", 6)]);
+
+    let values = [1u8, 2, 3];
+    let read: ReadValueChecked<u8> = unsafe {
+        assure_for_all_calls!(
+            #[assure(valid_ptr(ptr, r), reason = "all pointers are created from references")]
+            read_value::<u8> as ReadValueChecked
+        )
+    };
+    let read_values: Vec<_> = values
+        .iter()
+        .map(|v| v as *const u8)
+        .map(read.as_fn())
+        .collect();
+    assert_eq!(read_values, values);
+
+    let first: FirstChecked = assure_for_all_calls!(
+        #[assure("`s` is not empty", reason = "no string is empty")]
+        first as FirstChecked
+    );
+    let s = String::from("abc");
+    assert_eq!(first.call(&s), "a");
+
+    let nothing: Box<dyn Fn()> = Box::new(
+        assure_for_all_calls!(
+            #[assure("is called from a test", reason = "it is")]
+            inner::nothing as inner::NothingChecked
+        )
+        .as_fn(),
+    );
+    nothing();
+}
@@ -0,0 +1,44 @@
+use pre::pre;
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre(allow(todo_reason, unfinished_reason))]
+fn main() {
+    #[assure(x > 0, reason = "TODO")]
+    positive(1);
+
+    #[assure(x > 0, reason = "?")]
+    positive(2);
+
+    inner::call();
+}
+
+#[pre(deny(todo_reason))]
+mod inner {
+    #[pre(x > 0)]
+    fn positive(x: i32) -> i32 {
+        x
+    }
+
+    #[pre(allow(todo_reason))]
+    pub(super) fn call() {
+        #[assure(x > 0, reason = "TODO")]
+        positive(3);
+    }
+
+    #[pre(allow(unfinished_reason))]
+    mod nested {
+        #[pre(x > 0)]
+        fn positive(x: i32) -> i32 {
+            x
+        }
+
+        fn call() {
+            #[assure(x > 0, reason = "")]
+            positive(4);
+        }
+    }
+}
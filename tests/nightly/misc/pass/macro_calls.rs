@@ -0,0 +1,63 @@
+use pre::pre;
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read(ptr: *const i32) -> i32 {
+    *ptr
+}
+
+macro_rules! identity {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+#[pre]
+fn main() {
+    let val = 42;
+    let ptr = &val as *const i32;
+
+    dbg!(unsafe {
+        #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+        read(ptr)
+    });
+
+    assert_eq!(
+        #[assure(x > 0, reason = "1 is positive")]
+        positive(1),
+        1
+    );
+
+    println!(
+        "{}",
+        #[assure(x > 0, reason = "2 is positive")]
+        positive(2)
+    );
+
+    #[assure(x > 0, reason = "3 is positive")]
+    let three = dbg!(positive(3));
+
+    let numbers = vec![
+        #[assure(x > 0, reason = "4 is positive")]
+        positive(4),
+        three,
+    ];
+
+    assert_eq!(numbers, [4, 3]);
+
+    scanned();
+}
+
+#[pre(scan_macros)]
+fn scanned() {
+    let five = identity! {
+        #[assure(x > 0, reason = "5 is positive")]
+        positive(5)
+    };
+
+    assert_eq!(five, 5);
+}
@@ -0,0 +1,36 @@
+use pre::pre;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[pre(pure)]
+#[pre(!values.is_empty())]
+#[pre(values.iter().all(|value| *value > 0))]
+fn first(values: &[i32]) -> i32 {
+    values[0]
+}
+
+#[pre(pure)]
+#[pre(allow(impure_condition))]
+#[pre(counter.fetch_add(1, Ordering::SeqCst) < 10)]
+fn count(counter: &AtomicUsize) -> usize {
+    counter.load(Ordering::SeqCst)
+}
+
+#[pre]
+fn main() {
+    #[assure(!values.is_empty(), reason = "the slice has one element")]
+    #[assure(
+        values.iter().all(|value| *value > 0),
+        reason = "the only element is positive"
+    )]
+    let value = first(&[1]);
+    assert_eq!(value, 1);
+
+    let counter = AtomicUsize::new(0);
+
+    #[assure(
+        counter.fetch_add(1, Ordering::SeqCst) < 10,
+        reason = "the counter starts at zero"
+    )]
+    let count = count(&counter);
+    assert_eq!(count, 1);
+}
@@ -0,0 +1,31 @@
+use pre::pre;
+
+#[pre(x > 0)]
+#[pre(gen_violation_tests(case(x = 0), case(x = -1)))]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pre(non_null(ptr))]
+#[pre(in_range(len, 1..=8))]
+#[pre(gen_violation_tests(case(ptr = std::ptr::null(), len = 1), case(ptr = &0, len = 9)))]
+unsafe fn read(ptr: *const u8, len: usize) -> u8 {
+    *ptr.add(len - 1)
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "1 > 0")]
+    let doubled = double(1);
+    assert_eq!(doubled, 2);
+
+    let val = 42;
+    assert_eq!(
+        unsafe {
+            #[assure(non_null(ptr), reason = "a reference is never null")]
+            #[assure(in_range(len, 1..=8), reason = "1 is in range")]
+            read(&val, 1)
+        },
+        42
+    );
+}
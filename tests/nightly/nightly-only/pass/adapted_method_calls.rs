@@ -0,0 +1,31 @@
+use pre::pre;
+
+struct Client;
+
+impl Client {
+    #[pre(x > 0)]
+    async fn fetch(&self, x: i32) -> Result<Vec<i32>, ()> {
+        Ok(vec![x])
+    }
+
+    #[pre(x > 0)]
+    fn get(&self, x: i32) -> Result<Vec<i32>, ()> {
+        Ok(vec![x])
+    }
+}
+
+#[pre]
+async fn fetch_all(client: &Client) -> Result<usize, ()> {
+    #[assure(x > 0, reason = "1 > 0")]
+    let first = client.fetch(1).await?[0];
+    assert_eq!(first, 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let len = client.get(1)?.len();
+
+    Ok(len)
+}
+
+fn main() {
+    let _ = fetch_all(&Client);
+}
@@ -0,0 +1,53 @@
+use pre::pre;
+use std::panic;
+
+#[derive(Clone, Copy)]
+struct Buf {
+    len: usize,
+    cap: usize,
+}
+
+impl Buf {
+    #[pre(self.len < self.cap)]
+    fn push(&mut self) {
+        self.len += 1;
+    }
+
+    #[pre(self.len <= self.cap)]
+    #[pre(n > 0)]
+    fn into_len(self, n: usize) -> usize {
+        self.len * n
+    }
+
+    #[pre]
+    fn fill(&mut self) {
+        while self.len < self.cap {
+            #[assure(self.len < self.cap, reason = "checked by the loop condition")]
+            self.push();
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    let mut buf = Buf { len: 0, cap: 4 };
+
+    // The spacing of the expression does not matter.
+    #[assure(self . len<self . cap, reason = "the buffer is empty")]
+    buf.push();
+
+    buf.fill();
+
+    #[assure(self.len <= self.cap, reason = "`fill` does not overfill the buffer")]
+    #[assure(n > 0, reason = "1 > 0")]
+    let len = buf.into_len(1);
+    assert_eq!(len, 4);
+
+    let result = panic::catch_unwind(move || {
+        let mut buf = buf;
+
+        #[assure(self.len < self.cap, reason = "this is wrong on purpose")]
+        buf.push();
+    });
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
@@ -4,7 +4,9 @@ use pre::pre;
 #[pre(valid_ptr(some_ptr, r))]
 #[pre(proper_align(some_ptr))]
 #[pre(!some_ptr.is_null())]
-fn foo<T>(some_ptr: *const T) {}
+#[pre(non_null(some_ptr))]
+#[pre(in_range(len, 0..=isize::MAX as usize))]
+fn foo<T>(some_ptr: *const T, len: usize) {}
 
 #[pre]
 fn main() {
@@ -12,5 +14,7 @@ fn main() {
     #[assure(!some_ptr.is_null(), reason = "it is from a reference")]
     #[assure("`some_ptr` is from a reference", reason = "it is")]
     #[assure(proper_align(some_ptr), reason = "it is from a reference")]
-    foo(&42)
+    #[assure(non_null(some_ptr), reason = "it is from a reference")]
+    #[assure(in_range(len, 0..=isize::MAX as usize), reason = "1 is in the range")]
+    foo(&42, 1)
 }
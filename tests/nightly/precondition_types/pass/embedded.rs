@@ -0,0 +1,34 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[pre(interrupts_disabled())]
+#[pre(dma_quiescent(buf))]
+fn refill(buf: &mut [u8], value: u8) {
+    for byte in buf.iter_mut() {
+        *byte = value;
+    }
+}
+
+#[pre(interrupts_disabled())]
+fn critical_section() -> u32 {
+    #[assure(interrupts_disabled(), reason = "the caller disabled interrupts")]
+    #[assure(dma_quiescent(buf), reason = "the buffer is not shared with a DMA engine")]
+    refill(&mut [0; 4], 1);
+
+    42
+}
+
+#[pre]
+fn main() {
+    let mut buf = [0; 16];
+
+    #[assure(interrupts_disabled(), reason = "this is a single-threaded test without interrupts")]
+    #[assure(dma_quiescent(buf), reason = "`buf` is on the stack and no DMA engine is set up")]
+    refill(&mut buf, 7);
+    assert!(buf.iter().all(|&byte| byte == 7));
+
+    #[assure(interrupts_disabled(), reason = "this is a single-threaded test without interrupts")]
+    let value = critical_section();
+    assert_eq!(value, 42);
+}
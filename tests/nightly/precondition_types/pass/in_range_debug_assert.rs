@@ -0,0 +1,21 @@
+use pre::pre;
+use std::panic;
+
+#[pre(in_range(index, 1..=10))]
+#[pre(in_range(offset, ..0))]
+fn foo(index: u8, offset: i32) {}
+
+#[pre]
+fn main() {
+    #[assure(in_range(index, 1..=10), reason = "10 is in the range")]
+    #[assure(in_range(offset, ..0), reason = "-1 is negative")]
+    foo(10, -1);
+
+    let result = panic::catch_unwind(|| {
+        #[assure(in_range(index, 1..=10), reason = "this is wrong on purpose")]
+        #[assure(in_range(offset, ..0), reason = "-1 is negative")]
+        foo(0, -1);
+    });
+
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
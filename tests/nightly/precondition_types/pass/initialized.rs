@@ -0,0 +1,39 @@
+#![deny(warnings)]
+
+use pre::pre;
+use std::mem::MaybeUninit;
+
+#[pre(valid_ptr(src, r))]
+#[pre(proper_align(src))]
+#[pre(initialized(src))]
+unsafe fn read(src: *const u32) -> u32 {
+    src.read()
+}
+
+#[pre(valid_ptr(src, r, count))]
+#[pre(initialized(src, count))]
+unsafe fn sum(src: *const u32, count: usize) -> u32 {
+    std::slice::from_raw_parts(src, count).iter().sum()
+}
+
+#[pre]
+fn main() {
+    let value = MaybeUninit::new(42);
+
+    let result = unsafe {
+        #[assure(valid_ptr(src, r), reason = "`value` is a local variable")]
+        #[assure(proper_align(src), reason = "`value` is a local variable")]
+        #[assure(initialized(src), reason = "`value` was created with `MaybeUninit::new`")]
+        read(value.as_ptr())
+    };
+    assert_eq!(result, 42);
+
+    let values = [1, 2, 3, 4];
+
+    let result = unsafe {
+        #[assure(valid_ptr(src, r, count), reason = "`values` has four elements")]
+        #[assure(initialized(src, count), reason = "arrays are always initialized")]
+        sum(values.as_ptr(), 3)
+    };
+    assert_eq!(result, 6);
+}
@@ -0,0 +1,53 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[repr(C)]
+struct Header {
+    len: u32,
+    flags: u32,
+}
+
+#[pre(valid_ptr(ptr, r))]
+#[pre(layout_compatible([u32; 2], Header))]
+unsafe fn read_header(ptr: *const [u32; 2]) -> Header {
+    (ptr as *const Header).read()
+}
+
+#[pre(layout_compatible(T, U))]
+unsafe fn reinterpret<T, U>(value: &T) -> U {
+    (value as *const T as *const U).read()
+}
+
+#[pre(layout_compatible(u64, [u8; 8]))]
+const fn to_bytes(value: u64) -> [u8; 8] {
+    value.to_ne_bytes()
+}
+
+#[pre]
+fn main() {
+    let raw = [3u32, 0];
+
+    #[assure(valid_ptr(ptr, r), reason = "`raw` is a local array")]
+    #[assure(
+        layout_compatible([u32; 2], Header),
+        reason = "`Header` consists of two `u32` fields"
+    )]
+    let header = unsafe { read_header(&raw) };
+    assert_eq!(header.len, 3);
+    assert_eq!(header.flags, 0);
+
+    #[assure(
+        layout_compatible(T, U),
+        reason = "`[u16; 2]` is as large as and more strictly aligned than `[u8; 4]`"
+    )]
+    let bytes: [u8; 4] = unsafe { reinterpret(&[0u16; 2]) };
+    assert_eq!(bytes, [0; 4]);
+
+    #[assure(
+        layout_compatible(u64, [u8; 8]),
+        reason = "`u64` and `[u8; 8]` have the same size"
+    )]
+    let bytes = to_bytes(0);
+    assert_eq!(bytes, [0; 8]);
+}
@@ -0,0 +1,27 @@
+#![deny(warnings)]
+
+use pre::pre;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+};
+
+#[pre(valid_cstr(ptr))]
+#[pre(lives_until_end_of_scope(ptr))]
+unsafe fn name_of<'a>(ptr: *const c_char) -> &'a CStr {
+    CStr::from_ptr(ptr)
+}
+
+#[pre]
+fn main() {
+    let owned = CString::new("pre").expect("no interior nul byte");
+
+    #[assure(valid_cstr(ptr), reason = "`owned` is a `CString`")]
+    #[assure(
+        lives_until_end_of_scope(ptr),
+        reason = "`owned` is dropped after `name` at the end of `main`"
+    )]
+    let name = unsafe { name_of(owned.as_ptr()) };
+
+    assert_eq!(name.to_str(), Ok("pre"));
+}
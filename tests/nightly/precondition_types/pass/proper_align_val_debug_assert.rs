@@ -0,0 +1,28 @@
+use pre::pre;
+use std::panic;
+
+const ALIGN: usize = 16;
+
+#[repr(align(32))]
+struct Aligned([u8; 64]);
+
+#[pre(proper_align_val(ptr, 32))]
+#[pre(proper_align_val(ptr, ALIGN))]
+fn foo(ptr: *const u8) {}
+
+#[pre]
+fn main() {
+    let data = Aligned([0; 64]);
+
+    #[assure(proper_align_val(ptr, 32), reason = "`Aligned` has an alignment of 32")]
+    #[assure(proper_align_val(ptr, ALIGN), reason = "`Aligned` has an alignment of 32")]
+    foo(data.0.as_ptr());
+
+    let result = panic::catch_unwind(|| {
+        #[assure(proper_align_val(ptr, 32), reason = "this is wrong on purpose")]
+        #[assure(proper_align_val(ptr, ALIGN), reason = "this is wrong on purpose")]
+        foo(data.0[1..].as_ptr());
+    });
+
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
@@ -0,0 +1,36 @@
+use pre::pre;
+
+#[pre(valid_utf8(bytes))]
+fn first_char(bytes: &[u8]) -> Option<char> {
+    unsafe { core::str::from_utf8_unchecked(bytes) }.chars().next()
+}
+
+#[pre(valid_utf8(buf))]
+#[pre(buf.len() >= 2)]
+fn first_two(buf: Vec<u8>) -> String {
+    unsafe { String::from_utf8_unchecked(buf) }.chars().take(2).collect()
+}
+
+#[pre]
+fn main() {
+    #[assure(valid_utf8(bytes), reason = "the bytes are taken from a string literal")]
+    let c = first_char("äbc".as_bytes());
+    assert_eq!(c, Some('ä'));
+
+    #[assure(valid_utf8(buf), reason = "the bytes are taken from a string")]
+    #[assure(buf.len() >= 2, reason = "the string has three bytes")]
+    let s = first_two(String::from("xyz").into_bytes());
+    assert_eq!(s, "xy");
+
+    let bytes = b"hello";
+
+    #[forward(pre::std)]
+    #[assure(valid_utf8(v), reason = "`bytes` is an ASCII byte string literal")]
+    let s = unsafe { str::from_utf8_unchecked(bytes) };
+    assert_eq!(s, "hello");
+
+    #[forward(impl pre::std::string::String)]
+    #[assure(valid_utf8(bytes), reason = "the bytes are taken from a string")]
+    let s = unsafe { String::from_utf8_unchecked(String::from("world").into_bytes()) };
+    assert_eq!(s, "world");
+}
@@ -0,0 +1,49 @@
+use pre::pre;
+
+#[derive(Debug, PartialEq)]
+#[repr(u8)]
+enum Color {
+    Red,
+    Green,
+}
+
+#[pre(valid_value(discriminant, Color))]
+unsafe fn color_from_u8(discriminant: u8) -> Color {
+    std::mem::transmute(discriminant)
+}
+
+#[pre]
+fn main() {
+    use pre::std::{char, mem};
+
+    #[assure(valid_value(discriminant, Color), reason = "1 is the discriminant of `Green`")]
+    let color = unsafe { color_from_u8(1) };
+    assert_eq!(color, Color::Green);
+
+    #[assure(valid_value(i, char), reason = "97 is the code point of `a`")]
+    let c = unsafe { char::from_u32_unchecked(97) };
+    assert_eq!(c, 'a');
+
+    #[assure(valid_value(src, U), reason = "every bit pattern is a valid `i32`")]
+    #[assure(
+        "`src` is valid for reads of `size_of::<U>()` bytes",
+        reason = "`u32` and `i32` have the same size"
+    )]
+    let value: i32 = unsafe { mem::transmute_copy(&7u32) };
+    assert_eq!(value, 7);
+
+    #[assure(valid_value(src, U), reason = "every bit pattern is a valid `u32`")]
+    #[assure(
+        "`src` is valid for reads of `size_of::<U>()` bytes",
+        reason = "`[u8; 4]` and `u32` have the same size, the stricter alignment does not matter"
+    )]
+    let value: u32 = unsafe { mem::transmute_copy(&7u32.to_ne_bytes()) };
+    assert_eq!(value, 7);
+
+    let uninit = mem::MaybeUninit::new(42);
+
+    #[forward(impl pre::std::mem::MaybeUninit)]
+    #[assure(valid_value(self, T), reason = "it was created using `MaybeUninit::new`")]
+    let value = unsafe { uninit.assume_init() };
+    assert_eq!(value, 42);
+}
@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod violation_tests;
+
 #[cfg(test)]
 mod tests {
     use trybuild::TestCases;
@@ -37,4 +40,37 @@ mod tests {
 
         add_category!(test_cases, "nightly", "nightly-only");
     }
+
+    // The tests for features of pre only run if the feature is enabled, for example using
+    // `cargo test --features adapter-fn-traits feature_tests`.
+
+    #[cfg(all(nightly, feature = "adapter-fn-traits"))]
+    #[test]
+    fn adapter_fn_traits_feature_tests() {
+        TestCases::new().pass("features/adapter-fn-traits/*.rs");
+    }
+
+    #[cfg(feature = "violation-handler")]
+    #[test]
+    fn violation_handler_feature_tests() {
+        TestCases::new().pass("features/violation-handler/*.rs");
+    }
+
+    #[cfg(feature = "log-violations")]
+    #[test]
+    fn log_violations_feature_tests() {
+        TestCases::new().pass("features/log-violations/*.rs");
+    }
+
+    #[cfg(feature = "defmt-violations")]
+    #[test]
+    fn defmt_violations_feature_tests() {
+        TestCases::new().pass("features/defmt-violations/*.rs");
+    }
+
+    #[cfg(feature = "cstr-checks")]
+    #[test]
+    fn cstr_checks_feature_tests() {
+        TestCases::new().pass("features/cstr-checks/*.rs");
+    }
 }
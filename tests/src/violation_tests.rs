@@ -0,0 +1,17 @@
+//! The tests generated by `gen_violation_tests` are only compiled with `cfg(test)`, so they are
+//! compiled and run as part of the tests of this crate.
+
+use pre::pre;
+
+#[pre(x > 0)]
+#[pre(gen_violation_tests(case(x = 0), case(x = -1)))]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pre(non_null(ptr))]
+#[pre(in_range(len, 1..=8))]
+#[pre(gen_violation_tests(case(ptr = std::ptr::null(), len = 1), case(ptr = &0, len = 9)))]
+unsafe fn read(ptr: *const u8, len: usize) -> u8 {
+    *ptr.add(len - 1)
+}
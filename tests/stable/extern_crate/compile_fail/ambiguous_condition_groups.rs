@@ -0,0 +1,17 @@
+use pre::pre;
+
+mod lib {
+    pub unsafe fn read(ptr: *const u8) -> u8 {
+        *ptr
+    }
+}
+
+#[pre::extern_crate(crate::lib)]
+mod pre_lib {
+    #[pre(valid_ptr(ptr, r))]
+    #[pre(when(feature = "strict"), "`ptr` is aligned to 8 bytes")]
+    #[pre(when(not(feature = "zeroed")), "`ptr` points to an initialized byte")]
+    unsafe fn read(ptr: *const u8) -> u8;
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use pre::pre;
+
+#[pre::extern_crate(core, test)]
+mod new_core {
+    mod mem {
+        #[pre("an all-zero byte-pattern is valid for `T`")]
+        unsafe fn zeroed<T>() -> T;
+    }
+}
+
+fn main() {}
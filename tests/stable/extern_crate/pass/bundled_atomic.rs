@@ -0,0 +1,69 @@
+use pre::pre;
+
+use pre::std::{
+    cell::RefCell,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+#[pre]
+fn main() {
+    let mut counter = 0usize;
+    let counter_ptr = &mut counter as *mut usize;
+
+    #[forward(impl pre::std::sync::atomic::AtomicUsize)]
+    #[assure(valid_ptr(ptr, r+w), reason = "the pointer was created from a reference")]
+    #[assure(
+        proper_align_val(ptr, ::core::mem::align_of::<AtomicUsize>()),
+        reason = "`AtomicUsize` has the same alignment as `usize`"
+    )]
+    #[assure(
+        "`ptr` is valid for reads and writes for the whole lifetime `'a`",
+        reason = "`counter` outlives `atomic`"
+    )]
+    #[assure(
+        "the memory at `ptr` is only accessed atomically during the lifetime `'a`",
+        reason = "`counter` is only accessed through `atomic` until `atomic` is dropped"
+    )]
+    #[assure(
+        "the memory at `ptr` is not accessed by atomic operations of a different size during the lifetime `'a`",
+        reason = "`counter` is only accessed through `atomic` until `atomic` is dropped"
+    )]
+    let atomic = unsafe { AtomicUsize::from_ptr(counter_ptr) };
+    atomic.fetch_add(2, Ordering::Relaxed);
+    assert_eq!(counter, 2);
+
+    let mut value = 42;
+    let mut slot = &mut value as *mut i32;
+    let slot_ptr = &mut slot as *mut *mut i32;
+
+    #[forward(impl pre::std::sync::atomic::AtomicPtr)]
+    #[assure(valid_ptr(ptr, r+w), reason = "the pointer was created from a reference")]
+    #[assure(
+        proper_align_val(ptr, ::core::mem::align_of::<AtomicPtr<T>>()),
+        reason = "`AtomicPtr<T>` has the same alignment as `*mut T`"
+    )]
+    #[assure(
+        "`ptr` is valid for reads and writes for the whole lifetime `'a`",
+        reason = "`slot` outlives `atomic`"
+    )]
+    #[assure(
+        "the memory at `ptr` is only accessed atomically during the lifetime `'a`",
+        reason = "`slot` is only accessed through `atomic` until `atomic` is dropped"
+    )]
+    #[assure(
+        "the memory at `ptr` is not accessed by atomic operations of a different size during the lifetime `'a`",
+        reason = "`slot` is only accessed through `atomic` until `atomic` is dropped"
+    )]
+    let atomic = unsafe { AtomicPtr::from_ptr(slot_ptr) };
+    assert_eq!(unsafe { *atomic.load(Ordering::Acquire) }, 42);
+
+    let cell = RefCell::new(1);
+
+    #[forward(impl pre::std::cell::RefCell)]
+    #[assure(
+        "the `RefCell` is not mutably borrowed while the returned reference is alive",
+        reason = "`cell` is not borrowed mutably in this function"
+    )]
+    let borrowed = unsafe { cell.try_borrow_unguarded() };
+    assert_eq!(borrowed.ok(), Some(&1));
+}
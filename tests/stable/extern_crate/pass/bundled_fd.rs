@@ -0,0 +1,51 @@
+use pre::pre;
+
+#[cfg(unix)]
+#[pre]
+fn reopen() {
+    use std::{
+        fs::File,
+        io::Write,
+        os::unix::io::{AsRawFd, BorrowedFd, FromRawFd, IntoRawFd},
+    };
+
+    let path = std::env::temp_dir().join("pre_bundled_fd_test");
+    let mut file = File::create(&path).expect("the temporary directory is writable");
+    file.write_all(b"pre").expect("the file is writable");
+
+    let raw = file.as_raw_fd();
+    #[forward(impl pre::std::os::fd::BorrowedFd)]
+    #[assure("`fd` is an open file descriptor", reason = "`raw` belongs to `file`")]
+    #[assure(
+        "`fd` remains open for the duration of `'fd`",
+        reason = "`file` is not closed before `borrowed` is dropped"
+    )]
+    let borrowed = unsafe { BorrowedFd::borrow_raw(raw) };
+    assert_eq!(borrowed.as_raw_fd(), raw);
+
+    let fd = file.into_raw_fd();
+    #[forward(impl pre::std::os::fd::FromRawFd for File)]
+    #[assure(
+        "`fd` is an open file descriptor",
+        reason = "`fd` was just returned by `into_raw_fd`"
+    )]
+    #[assure(
+        "`fd` is owned by the caller and is not used or closed anywhere else afterwards",
+        reason = "`into_raw_fd` transferred the ownership of `fd`"
+    )]
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(b"-rs").expect("the file is still writable");
+
+    let content = std::fs::read_to_string(&path).expect("the file is readable");
+    assert_eq!(content, "pre-rs");
+
+    drop(file);
+    std::fs::remove_file(&path).expect("the file can be removed");
+}
+
+#[cfg(not(unix))]
+fn reopen() {}
+
+fn main() {
+    reopen();
+}
@@ -0,0 +1,28 @@
+use pre::pre;
+
+// Only one of the two modules is included, so their names don't clash.
+#[pre::extern_crate(core, cfg(all()))]
+mod new_core {
+    mod mem {
+        #[pre("an all-zero byte-pattern is valid for `T`")]
+        unsafe fn zeroed<T>() -> T;
+    }
+}
+
+#[pre::extern_crate(core, cfg(any()))]
+mod new_core {
+    mod mem {
+        #[pre("this outline is never used")]
+        unsafe fn zeroed<T>() -> T;
+    }
+}
+
+#[pre]
+fn main() {
+    #[assure(
+        "an all-zero byte-pattern is valid for `T`",
+        reason = "`usize` supports an all-zero byte-pattern"
+    )]
+    let x: usize = unsafe { new_core::mem::zeroed() };
+    assert_eq!(x, 0);
+}
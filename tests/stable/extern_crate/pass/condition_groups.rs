@@ -0,0 +1,29 @@
+use pre::pre;
+
+mod lib {
+    pub unsafe fn read(ptr: *const u8) -> u8 {
+        *ptr
+    }
+}
+
+#[pre::extern_crate(crate::lib)]
+mod pre_lib {
+    #[pre(valid_ptr(ptr, r))]
+    #[pre(when(feature = "strict"), "`ptr` is aligned to 8 bytes")]
+    #[pre(when(not(feature = "strict")), "`ptr` points to an initialized byte")]
+    unsafe fn read(ptr: *const u8) -> u8;
+}
+
+#[pre]
+fn main() {
+    let byte = 42;
+
+    #[assure(valid_ptr(ptr, r), reason = "`byte` is a local variable")]
+    #[assure(
+        "`ptr` points to an initialized byte",
+        reason = "`byte` is initialized"
+    )]
+    let value = unsafe { pre_lib::read(&byte) };
+
+    assert_eq!(value, 42);
+}
@@ -0,0 +1,67 @@
+use pre::pre;
+
+mod library {
+    use std::fmt::Display;
+
+    pub fn sort_by_key<T, K: Ord>(slice: &mut [T], f: impl FnMut(&T) -> K) {
+        slice.sort_by_key(f)
+    }
+
+    pub fn describe(value: impl Display, suffix: &str) -> impl Display {
+        format!("{}{}", value, suffix)
+    }
+
+    pub struct Wrapper<T>(pub Vec<T>);
+
+    impl<T> Wrapper<T> {
+        pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+            self.0.retain(f)
+        }
+
+        pub fn first_where<P: FnMut(&&T) -> bool>(&self, predicate: P) -> impl Iterator<Item = &T> {
+            self.0.iter().filter(predicate).take(1)
+        }
+    }
+}
+
+#[pre::extern_crate(crate::library)]
+mod pre_library {
+    use std::fmt::Display;
+
+    #[pre(!slice.is_empty())]
+    fn sort_by_key<T, K: Ord>(slice: &mut [T], f: impl FnMut(&T) -> K);
+
+    #[pre(!suffix.is_empty())]
+    fn describe(value: impl Display, suffix: &str) -> impl Display;
+
+    impl<T> Wrapper<T> {
+        #[pre("the closure does not panic")]
+        fn retain(&mut self, f: impl FnMut(&T) -> bool);
+
+        #[pre("the predicate does not panic")]
+        fn first_where<P: FnMut(&&T) -> bool>(&self, predicate: P) -> impl Iterator<Item = &T>;
+    }
+}
+
+#[pre]
+fn main() {
+    let mut values = [3, 1, 2];
+    #[assure(!slice.is_empty(), reason = "`values` has three elements")]
+    pre_library::sort_by_key(&mut values, |&x| x);
+    assert_eq!(values, [1, 2, 3]);
+
+    #[assure(!suffix.is_empty(), reason = "`\"!\"` is not empty")]
+    let description = pre_library::describe(42, "!");
+    assert_eq!(description.to_string(), "42!");
+
+    let mut wrapper = library::Wrapper(vec![1, 2, 3, 4]);
+    #[forward(impl pre_library::Wrapper)]
+    #[assure("the closure does not panic", reason = "the closure only compares numbers")]
+    wrapper.retain(|&x| x % 2 == 0);
+    assert_eq!(wrapper.0, [2, 4]);
+
+    #[forward(impl pre_library::Wrapper)]
+    #[assure("the predicate does not panic", reason = "the predicate only compares numbers")]
+    let first = wrapper.first_where(|&&x| x > 2);
+    assert_eq!(first.collect::<Vec<_>>(), [&4]);
+}
@@ -0,0 +1,52 @@
+use pre::pre;
+
+mod library {
+    use std::future::Future;
+
+    pub fn count_down(x: u32) -> impl Iterator<Item = u32> {
+        (0..x).rev()
+    }
+
+    pub async fn halve(x: u32) -> u32 {
+        x / 2
+    }
+
+    pub fn later(x: u32) -> impl Future<Output = u32> {
+        async move { x }
+    }
+}
+
+#[pre::extern_crate(crate::library)]
+mod pre_library {
+    use std::future::Future;
+
+    #[pre(x > 0)]
+    fn count_down(x: u32) -> impl Iterator<Item = u32>;
+
+    #[pre(x > 0)]
+    async fn halve(x: u32) -> u32;
+
+    #[pre(x > 0)]
+    fn later(x: u32) -> impl Future<Output = u32>;
+}
+
+#[pre]
+async fn quarter(x: u32) -> u32 {
+    #[assure(x > 0, reason = "`x` is positive in this test")]
+    let half = pre_library::halve(x).await;
+
+    half / 2
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "3 > 0")]
+    let values = pre_library::count_down(3);
+    assert_eq!(values.collect::<Vec<_>>(), [2, 1, 0]);
+
+    drop(quarter(8));
+
+    #[assure(x > 0, reason = "3 > 0")]
+    let future = pre_library::later(3);
+    drop(future);
+}
@@ -0,0 +1,62 @@
+use pre::pre;
+
+#[pre::extern_crate(std, inline = "never")]
+mod never_inlined_std {
+    mod ptr {
+        #[pre(valid_ptr(src, r))]
+        unsafe fn read_unaligned<T>(src: *const T) -> T;
+
+        #[pre(valid_ptr(dst, w))]
+        #[inline(always)]
+        unsafe fn write_unaligned<T>(dst: *mut T, src: T);
+
+        impl<T> NonNull<T> {
+            #[pre(!ptr.is_null())]
+            #[inline]
+            const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T>;
+        }
+    }
+}
+
+#[pre::extern_crate(core, cfg(not(test)), inline = "hint")]
+mod hinted_core {
+    mod mem {
+        #[pre("`T` can be forgotten")]
+        const fn forget<T>(t: T);
+    }
+}
+
+#[pre::extern_crate(core, inline = "none")]
+mod plain_core {
+    mod mem {
+        #[pre("`dest` is a valid reference")]
+        fn replace<T>(dest: &mut T, src: T) -> T;
+    }
+}
+
+#[pre]
+fn main() {
+    let mut val = 0;
+
+    #[assure(valid_ptr(dst, w), reason = "`dst` is a reference")]
+    unsafe {
+        never_inlined_std::ptr::write_unaligned(&mut val, 42)
+    };
+
+    #[assure(valid_ptr(src, r), reason = "`src` is a reference")]
+    let result = unsafe { never_inlined_std::ptr::read_unaligned(&val) };
+    assert_eq!(result, 42);
+
+    #[forward(impl never_inlined_std::ptr::NonNull)]
+    #[assure(!ptr.is_null(), reason = "`ptr` is a reference")]
+    let non_null = unsafe { std::ptr::NonNull::new_unchecked(&mut val) };
+    assert_eq!(unsafe { *non_null.as_ptr() }, 42);
+
+    #[assure("`T` can be forgotten", reason = "`i32` has no destructor")]
+    hinted_core::mem::forget(val);
+
+    #[assure("`dest` is a valid reference", reason = "`dest` is a reference")]
+    let old = plain_core::mem::replace(&mut val, 1);
+    assert_eq!(old, 42);
+    assert_eq!(val, 1);
+}
@@ -0,0 +1,22 @@
+use pre::pre;
+
+#[pre::extern_crate(std)]
+mod pre_std {
+    mod ptr {
+        impl<T> NonNull<T> {
+            #[pre(!ptr.is_null())]
+            const unsafe fn new_unchecked(ptr: *mut T) -> Self;
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    let mut val = 0;
+
+    #[forward(impl pre_std::ptr::NonNull)]
+    #[assure(!ptr.is_null(), reason = "`ptr` is a reference")]
+    let non_null = unsafe { std::ptr::NonNull::new_unchecked(&mut val) };
+
+    assert_eq!(non_null, std::ptr::NonNull::from(&mut val));
+}
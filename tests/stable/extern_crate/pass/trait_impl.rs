@@ -0,0 +1,94 @@
+use pre::pre;
+
+mod a {
+    pub(crate) mod io {
+        pub(crate) unsafe trait Read {
+            unsafe fn read_unchecked(&self, index: usize) -> u8;
+        }
+
+        pub(crate) trait Convert<T> {
+            unsafe fn convert(value: T) -> Self;
+        }
+    }
+
+    pub(crate) struct File(pub(crate) [u8; 4]);
+
+    unsafe impl io::Read for File {
+        unsafe fn read_unchecked(&self, index: usize) -> u8 {
+            *self.0.get_unchecked(index)
+        }
+    }
+
+    impl File {
+        pub(crate) unsafe fn read_unchecked(&self, _index: usize) -> u8 {
+            0
+        }
+    }
+
+    impl io::Convert<u8> for File {
+        unsafe fn convert(value: u8) -> Self {
+            File([value; 4])
+        }
+    }
+
+    impl io::Convert<u16> for File {
+        unsafe fn convert(value: u16) -> Self {
+            File([value as u8; 4])
+        }
+    }
+}
+
+#[pre::extern_crate(crate::a)]
+mod b {
+    impl io::Read for File {
+        #[pre("`index` is less than 4")]
+        unsafe fn read_unchecked(&self, index: usize) -> u8;
+    }
+
+    impl File {
+        #[pre("the inherent method is called")]
+        unsafe fn read_unchecked(&self, index: usize) -> u8;
+    }
+
+    impl io::Convert<u8> for File {
+        #[pre(value < 16)]
+        unsafe fn convert(value: u8) -> Self;
+    }
+
+    impl io::Convert<u16> for File {
+        #[pre("`value` fits into a `u8`")]
+        unsafe fn convert(value: u16) -> Self;
+    }
+}
+
+#[pre]
+fn main() {
+    use a::io::{Convert, Read};
+
+    let file = a::File([1, 2, 3, 4]);
+
+    #[forward(impl b::io::Read for File)]
+    #[assure("`index` is less than 4", reason = "2 is less than 4")]
+    let value = unsafe { Read::read_unchecked(&file, 2) };
+    assert_eq!(value, 3);
+
+    #[forward(impl b::io::Read for File)]
+    #[assure("`index` is less than 4", reason = "3 is less than 4")]
+    let value = unsafe { <a::File as Read>::read_unchecked(&file, 3) };
+    assert_eq!(value, 4);
+
+    #[forward(impl b::File)]
+    #[assure("the inherent method is called", reason = "it is")]
+    let value = unsafe { file.read_unchecked(0) };
+    assert_eq!(value, 0);
+
+    #[forward(impl b::io::Convert<u8> for File)]
+    #[assure(value < 16, reason = "7 is less than 16")]
+    let file: a::File = unsafe { Convert::convert(7u8) };
+    assert_eq!(file.0, [7; 4]);
+
+    #[forward(impl b::io::Convert<u16> for File)]
+    #[assure("`value` fits into a `u8`", reason = "200 fits into a `u8`")]
+    let file: a::File = unsafe { Convert::convert(200u16) };
+    assert_eq!(file.0, [200; 4]);
+}
@@ -0,0 +1,18 @@
+use pre::pre;
+
+#[pre(align <= 16)]
+#[pre(align.is_power_of_two())]
+fn aligned(align: usize, len: usize) -> usize {
+    (len + align - 1) / align * align
+}
+
+#[pre]
+fn main() {
+    #[assure(align <= 16, reason = "this is wrong")]
+    #[assure(align.is_power_of_two(), reason = "32 is a power of two")]
+    aligned(32, 5);
+
+    #[assure(align <= 16, reason = "12 is at most 16")]
+    #[assure(align.is_power_of_two(), reason = "this is wrong")]
+    aligned(1 + 11, 5);
+}
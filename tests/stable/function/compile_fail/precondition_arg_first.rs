@@ -0,0 +1,14 @@
+use pre::pre;
+
+#[pre(precondition_arg_first)]
+#[pre(x > 0)]
+fn div(x: i32, y: i32) -> i32 {
+    y / x
+}
+
+#[pre]
+fn main() {
+    let _ = div(2, 4);
+    // rustc's help suggests `div(/* … */, 4, 2)` here, swapping the arguments. This comes from
+    // rustc's argument matching, not from pre, and is documented for `precondition_arg_first`.
+}
@@ -0,0 +1,29 @@
+use pre::pre;
+use std::alloc::Layout;
+
+#[pre(aligned_to(ptr, layout.align()))]
+#[pre(layout.size() <= 64)]
+unsafe fn fill(ptr: *mut u8, layout: Layout) {
+    ptr.write_bytes(0xff, layout.size());
+}
+
+#[repr(align(16))]
+struct Aligned([u8; 64]);
+
+#[pre]
+fn main() {
+    let mut data = Aligned([0; 64]);
+    let layout = Layout::from_size_align(32, 16).unwrap();
+
+    unsafe {
+        #[assure(
+            aligned_to(ptr, layout.align()),
+            reason = "`Aligned` has an alignment of 16"
+        )]
+        #[assure(layout.size() <= 64, reason = "32 <= 64")]
+        fill(data.0.as_mut_ptr(), layout);
+    }
+
+    assert_eq!(data.0[31], 0xff);
+    assert_eq!(data.0[32], 0);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+
+mod other {
+    use pre::pre;
+
+    #[pre("the file is open")]
+    pub fn close() {}
+
+    #[pre(len > 0)]
+    pub fn fill(len: usize) {
+        let _ = len;
+    }
+}
+
+#[pre("the file is opened")]
+fn close() {}
+
+#[pre(len > 0)]
+fn fill(len: u8) {
+    let _ = len;
+}
+
+#[pre]
+fn main() {
+    #[assure("the file is open", reason = "the file was never closed")]
+    other::close();
+
+    #[assure("the file is opened", reason = "the file was never closed")]
+    close();
+
+    #[assure(len > 0, reason = "300 > 0")]
+    other::fill(300);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+use tracing::instrument;
+
+#[cfg_attr(all(), pre)]
+#[tracing::instrument]
+#[cfg_attr(all(), pre(x > 0))]
+fn gated(x: i32) -> i32 {
+    x
+}
+
+#[cfg_attr(all(), pre(x > 0))]
+#[cfg_attr(all(), pre)]
+#[instrument]
+fn conditional(x: i32) -> i32 {
+    x
+}
+
+#[cfg_attr(any(), pre(x > 0))]
+#[instrument]
+fn inactive(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    #[cfg_attr(all(), assure(x > 0, reason = "1 > 0"))]
+    assert_eq!(gated(1), 1);
+
+    #[assure(x > 0, reason = "2 > 0")]
+    assert_eq!(conditional(2), 2);
+
+    assert_eq!(inactive(0), 0);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+
+#[pre(valid_ptr(src, r, count))]
+#[pre(valid_ptr(dst, w, count))]
+unsafe fn copy(src: *const u8, dst: *mut u8, count: usize) {
+    std::ptr::copy_nonoverlapping(src, dst, count)
+}
+
+#[pre(valid_ptr(ptr, r, len * 2))]
+#[pre(valid_ptr(ptr, r))]
+unsafe fn last_of_pairs(ptr: *const u8, len: usize) -> u8 {
+    *ptr.add(len * 2 - 1)
+}
+
+#[pre]
+fn main() {
+    let src = [1, 2, 3, 4];
+    let mut dst = [0; 4];
+
+    unsafe {
+        #[assure(valid_ptr(src, r, count), reason = "`src` has 4 elements")]
+        #[assure(valid_ptr(dst, w, count), reason = "`dst` has 4 elements")]
+        copy(src.as_ptr(), dst.as_mut_ptr(), 4);
+    }
+    assert_eq!(dst, src);
+
+    let last = unsafe {
+        #[assure(valid_ptr(ptr, r), reason = "`src` is not empty")]
+        #[assure(valid_ptr(ptr, r, len * 2), reason = "`src` has 2 pairs")]
+        last_of_pairs(src.as_ptr(), 2)
+    };
+    assert_eq!(last, 4);
+}
@@ -0,0 +1,17 @@
+use pre::pre;
+
+#[cfg_attr(any(target_endian = "little", target_endian = "big"), pre("foo"))]
+fn foo() {}
+
+#[cfg_attr(any(target_endian = "little", target_endian = "big"), pre)]
+#[pre("bar")]
+fn bar() {
+    #[assure("foo", reason = "is foo")]
+    foo();
+}
+
+#[pre("baz")]
+#[cfg_attr(any(target_endian = "little", target_endian = "big"), pre)]
+fn baz() {}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use pre::{assure_for_all_calls, pre};
+
+#[pre(adapter)]
+#[pre(x > 0)]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn main() {
+    let _ = assure_for_all_calls!(
+        #[assure(x > 0, reason = "all values are positive")]
+        double as DoubledChecked
+    );
+}
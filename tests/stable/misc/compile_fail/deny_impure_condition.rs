@@ -0,0 +1,10 @@
+use pre::pre;
+
+#[pre(deny(impure_condition))]
+#[pre(pure)]
+#[pre(stack.pop().is_some())]
+fn pop_checked(stack: &mut Vec<i32>) -> Option<i32> {
+    stack.pop()
+}
+
+fn main() {}
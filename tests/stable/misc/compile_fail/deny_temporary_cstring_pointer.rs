@@ -0,0 +1,19 @@
+use pre::pre;
+use std::{ffi::CString, os::raw::c_char};
+
+#[pre(valid_cstr(ptr))]
+unsafe fn c_strlen(ptr: *const c_char) -> usize {
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    len
+}
+
+#[pre(deny(temporary_storage))]
+fn main() {
+    #[assure(valid_cstr(ptr), reason = "`CString`s are valid C strings")]
+    let len = unsafe { c_strlen(CString::new("pre").unwrap().as_ptr()) };
+
+    assert_eq!(len, 3);
+}
@@ -0,0 +1,17 @@
+use pre::pre;
+
+#[pre(consumed(ptr))]
+unsafe fn take(ptr: *mut i32) -> i32 {
+    *Box::from_raw(ptr)
+}
+
+#[pre(deny(use_after_consumed))]
+fn main() {
+    let ptr = Box::into_raw(Box::new(42));
+
+    #[assure(consumed(ptr), reason = "`ptr` is not used after this call")]
+    let value = unsafe { take(ptr) };
+
+    assert_eq!(value, 42);
+    let _copy = ptr;
+}
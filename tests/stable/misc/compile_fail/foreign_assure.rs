@@ -0,0 +1,10 @@
+use pre::pre;
+
+#[pre("is foo")]
+fn foo() {}
+
+#[pre(deny(foreign_attribute))]
+fn main() {
+    #[other::assure("is foo", reason = "is foo")]
+    foo();
+}
@@ -0,0 +1,21 @@
+use pre::pre;
+
+#[pre::extern_crate(std)]
+mod pre_std {
+    mod ptr {
+        #[pre(valid_ptr(src, r))]
+        unsafe fn read<T>(src: *const T) -> T;
+    }
+}
+
+#[pre(max_unchecked_calls = 1)]
+fn bypassed(a: &i32, b: &i32) -> i32 {
+    let a = unsafe { std::ptr::read(a) };
+    let b = unsafe { std::ptr::read(b) };
+
+    a + b
+}
+
+fn main() {
+    bypassed(&1, &2);
+}
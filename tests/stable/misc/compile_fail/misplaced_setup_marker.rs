@@ -0,0 +1,25 @@
+use pre::pre;
+
+#[pre(x > 0)]
+fn duplicate(x: i32) -> i32 {
+    #[pre(assert_after_setup)]
+    let y = x + 1;
+    #[pre(assert_after_setup)]
+    let z = y + 1;
+
+    z
+}
+
+#[pre(x > 0)]
+fn nested(x: i32) -> i32 {
+    let y = {
+        #[pre(assert_after_setup)]
+        let z = x + 1;
+
+        z
+    };
+
+    y
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+#[pre("`ptr` points to an initialized `u32`")]
+unsafe fn read(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+mod inner {
+    use pre::pre;
+
+    #[pre(x > 0)]
+    pub fn positive(x: i32) -> i32 {
+        x
+    }
+}
+
+pre::assert_requires!(read, ["`ptr` points to an initialized `u32`", valid_ptr(ptr, r)]);
+
+fn main() {
+    pre::assert_requires!(inner::positive, [x > 0]);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+#[pre(x > 0)]
+unsafe fn read_plus(ptr: *const u32, x: u32) -> u32 {
+    *ptr + x
+}
+
+#[pre]
+fn main() {
+    let val = 41;
+
+    #[assure(
+        valid_ptr(ptr, r),
+        reason = "`ptr` is created from a reference",
+        audit = "SEC-123"
+    )]
+    #[assure(x > 0, reason = "1 > 0")]
+    let sum = unsafe { read_plus(&val, 1) };
+
+    assert_eq!(sum, 42);
+
+    #[assure(
+        valid_ptr(ptr, r),
+        reason = "`ptr` is created from a reference",
+        audit = "SEC-124",
+        audited(by = "alice", date = "2024-06-01")
+    )]
+    #[assure(x > 0, reason = "2 > 0", audited(date = "2024-06-02", by = "bob"))]
+    let sum = unsafe { read_plus(&val, 2) };
+
+    assert_eq!(sum, 43);
+}
@@ -0,0 +1,61 @@
+use pre::pre;
+
+#[pre(x > 0)]
+unsafe fn raw_from(x: i32) -> u32 {
+    x as u32
+}
+
+#[pre(x > 0)]
+fn checked_from(x: i32) -> Result<u32, u32> {
+    Ok(x as u32)
+}
+
+#[pre]
+fn convert(r: Result<u32, i32>) -> Result<u32, u32> {
+    #[assure(x > 0, reason = "errors are positive")]
+    let a = r.map_err(|e| unsafe { raw_from(e) })?;
+
+    let b = r.map_err(|e| {
+        #[assure(x > 0, reason = "errors are positive")]
+        unsafe {
+            raw_from(e)
+        }
+    })?;
+
+    #[assure(x > 0, reason = "errors are positive")]
+    let c = r.or_else(|e| checked_from(e))?;
+
+    let d = r
+        .as_ref()
+        .map_err(
+            #[assure(x > 0, reason = "errors are positive")]
+            |e| unsafe { raw_from(*e) },
+        )
+        .map(|v| *v)?;
+
+    Ok(a + b + c + d)
+}
+
+struct Wrapper(i32);
+
+impl Wrapper {
+    fn with<T>(&self, f: impl FnOnce(i32) -> T) -> T {
+        f(self.0)
+    }
+}
+
+#[pre]
+fn main() {
+    assert_eq!(convert(Ok(1)), Ok(4));
+    assert_eq!(convert(Err(2)), Err(2));
+
+    let value = Some(3);
+    #[assure(x > 0, reason = "3 > 0")]
+    let converted = value.map(|x| unsafe { raw_from(x) });
+    assert_eq!(converted, Some(3));
+
+    let wrapper = Wrapper(4);
+    #[assure(x > 0, reason = "4 > 0")]
+    let converted = wrapper.with(|x| unsafe { raw_from(x) });
+    assert_eq!(converted, 4);
+}
@@ -0,0 +1,31 @@
+use pre::pre;
+
+#[pre(harvest_asserts)]
+#[pre(divisor != 0)]
+fn divide(dividend: u32, divisor: u32) -> u32 {
+    assert!(divisor != 0);
+    debug_assert!(dividend >= divisor, "the result would be zero");
+
+    dividend / divisor
+}
+
+#[pre(harvest_asserts)]
+mod inner {
+    pub(crate) fn zip_len(a: &[u8], b: &[u8]) -> usize {
+        assert_eq!(a.len(), b.len(), "the slices must have the same length");
+        let len = a.len();
+        // Assertions after the first statement are not reported.
+        debug_assert_ne!(len, 0);
+
+        len
+    }
+}
+
+#[pre]
+fn main() {
+    #[assure(divisor != 0, reason = "2 != 0")]
+    let quotient = divide(4, 2);
+    assert_eq!(quotient, 2);
+
+    assert_eq!(inner::zip_len(&[1, 2], &[3, 4]), 2);
+}
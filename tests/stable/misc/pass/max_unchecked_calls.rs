@@ -0,0 +1,34 @@
+use pre::pre;
+
+#[pre::extern_crate(std)]
+mod pre_std {
+    mod ptr {
+        #[pre(valid_ptr(src, r))]
+        unsafe fn read<T>(src: *const T) -> T;
+    }
+}
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre(max_unchecked_calls = 0)]
+fn checked(value: &i32) -> i32 {
+    #[forward(std -> pre_std)]
+    #[assure(valid_ptr(src, r), reason = "a reference is a valid pointer")]
+    let value = unsafe { std::ptr::read(value) };
+
+    #[assure(x > 0, reason = "`abs` of a value greater than `i32::MIN` is non-negative")]
+    positive(value.abs() + 1)
+}
+
+#[pre(max_unchecked_calls = 1)]
+fn one_unchecked(value: &i32) -> i32 {
+    unsafe { std::ptr::read(value) }
+}
+
+fn main() {
+    assert_eq!(checked(&41), 42);
+    assert_eq!(one_unchecked(&42), 42);
+}
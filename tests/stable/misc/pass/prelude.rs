@@ -0,0 +1,44 @@
+use pre::prelude::*;
+
+#[pre(y != 0)]
+fn divide(x: u32, y: u32) -> u32 {
+    x / y
+}
+
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read(ptr: *const u8) -> u8 {
+    *ptr
+}
+
+#[pre(export = NOT_EMPTY, "`v` is not empty")]
+fn first(v: &[u8]) -> u8 {
+    v[0]
+}
+
+struct Initialized;
+
+impl Token for Initialized {}
+
+#[pre(token(Initialized))]
+fn use_initialized() {}
+
+#[pre]
+fn main() {
+    #[assure(y != 0, reason = "2 != 0")]
+    let quotient = divide(8, 2);
+    assert_eq!(quotient, 4);
+
+    let value = 7;
+    #[assure_each(read, valid_ptr(ptr, r), reason = "`ptr` comes from a reference", count = 2)]
+    let sum = unsafe { read(&value) + read(&value) };
+    assert_eq!(sum, 14);
+
+    let condition: ConditionDef = NOT_EMPTY;
+    assert_eq!(condition.text, "`v` is not empty");
+    #[assure(use NOT_EMPTY, reason = "the slice has one element")]
+    let head = first(&[1]);
+    assert_eq!(head, 1);
+
+    #[assure(token(Initialized = Initialized))]
+    use_initialized();
+}
@@ -0,0 +1,16 @@
+use pre::pre;
+
+pre::rename_condition!("`x` is even" => "`x` is divisible by two");
+
+#[pre("`x` is divisible by two")]
+fn half(x: u32) -> u32 {
+    x / 2
+}
+
+#[pre]
+fn main() {
+    #[assure("`x` is divisible by two", reason = "42 is even")]
+    let val = half(42);
+
+    assert_eq!(val, 21);
+}
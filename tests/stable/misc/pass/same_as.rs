@@ -0,0 +1,51 @@
+use pre::pre;
+
+mod old_api {
+    use pre::pre;
+
+    #[pre(export = COND_INIT, "the library is initialized")]
+    #[pre(valid_ptr(ptr, r))]
+    #[pre(len > 0)]
+    pub unsafe fn read_byte(ptr: *const u8, len: usize) -> u8 {
+        let _ = len;
+        *ptr
+    }
+}
+
+mod new_api {
+    use pre::pre;
+
+    #[pre(use super::old_api::COND_INIT)]
+    #[pre(valid_ptr(ptr, r))]
+    #[pre(len > 0)]
+    pub unsafe fn read(ptr: *const u8, len: usize) -> u8 {
+        #[assure(
+            same_as(super::old_api::read_byte),
+            reason = "`read` has the same contract as `read_byte`"
+        )]
+        super::old_api::read_byte(ptr, len)
+    }
+}
+
+#[pre]
+fn main() {
+    let value = 42u8;
+    let ptr = &value as *const u8;
+    let len = 1;
+
+    #[assure(
+        use old_api::COND_INIT,
+        reason = "the library is always initialized in this test"
+    )]
+    #[assure(valid_ptr(ptr, r), reason = "`ptr` comes from a reference")]
+    #[assure(len > 0, reason = "`len` is one")]
+    let first = unsafe { new_api::read(ptr, len) };
+
+    #[cfg_attr(
+        any(target_endian = "little", target_endian = "big"),
+        assure(same_as(new_api::read), reason = "the same as for the first read")
+    )]
+    let second = unsafe { old_api::read_byte(ptr, len) };
+
+    assert_eq!(first, second);
+}
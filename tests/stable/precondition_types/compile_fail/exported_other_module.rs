@@ -0,0 +1,21 @@
+use pre::pre;
+
+mod library {
+    use pre::pre;
+
+    #[pre(export = COND_INIT, "the library is initialized")]
+    pub fn read() {}
+}
+
+mod other {
+    use pre::pre;
+
+    #[pre(export = COND_INIT, "the other library is initialized")]
+    pub fn init() {}
+}
+
+#[pre]
+fn main() {
+    #[assure(use other::COND_INIT, reason = "the other library is initialized")]
+    library::read();
+}
@@ -0,0 +1,22 @@
+use pre::pre;
+
+mod library {
+    use pre::pre;
+
+    #[pre(export = COND_INIT, "the library is initialized")]
+    pub fn read() {}
+
+    pub const COND_OTHER: u32 = 0;
+}
+
+#[pre]
+fn main() {
+    #[assure(use library::COND_MISSING, reason = "the library is initialized")]
+    library::read();
+
+    #[assure(use library::COND_OTHER, reason = "the library is initialized")]
+    library::read();
+
+    #[assure(export = COND_INIT, "the library is initialized", reason = "it is")]
+    library::read();
+}
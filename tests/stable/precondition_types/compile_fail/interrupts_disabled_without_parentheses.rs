@@ -0,0 +1,6 @@
+use pre::pre;
+
+#[pre(interrupts_disabled)]
+fn foo() {}
+
+fn main() {}
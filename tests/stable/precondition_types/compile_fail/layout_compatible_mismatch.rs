@@ -0,0 +1,9 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+#[pre(layout_compatible(u8, u32))]
+unsafe fn read_u32(ptr: *const u8) -> u32 {
+    (ptr as *const u32).read()
+}
+
+fn main() {}
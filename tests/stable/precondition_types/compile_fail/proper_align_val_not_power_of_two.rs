@@ -0,0 +1,6 @@
+use pre::pre;
+
+#[pre(proper_align_val(ptr, 24))]
+fn foo(ptr: *const u8) {}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use pre::pre;
+
+struct InitToken;
+
+#[pre(token(InitToken))]
+fn use_after_init() {}
+
+#[pre]
+fn main() {
+    #[assure(token(InitToken = InitToken))]
+    use_after_init();
+}
@@ -0,0 +1,57 @@
+use pre::pre;
+use std::os::raw::c_char;
+
+#[pre(valid_cstr(s))]
+unsafe fn c_strlen(s: *const c_char) -> usize {
+    let mut len = 0;
+    while *s.add(len) != 0 {
+        len += 1;
+    }
+    len
+}
+
+#[pre(nul_terminated(s))]
+#[pre(valid_ptr(s, w))]
+unsafe fn clear(s: *mut c_char) {
+    *s = 0;
+}
+
+#[pre]
+fn main() {
+    use pre::std::ffi::{CStr, CString};
+
+    let bytes = b"hello\0";
+
+    #[assure(valid_cstr(s), reason = "`bytes` is a byte string literal ending with a nul byte")]
+    let len = unsafe { c_strlen(bytes.as_ptr() as *const c_char) };
+    assert_eq!(len, 5);
+
+    #[forward(impl pre::std::ffi::CStr)]
+    #[assure(valid_cstr(ptr), reason = "`bytes` is a byte string literal ending with a nul byte")]
+    #[assure(
+        "the memory referenced by the returned `CStr` is not mutated for the duration of `'a`",
+        reason = "`bytes` is immutable"
+    )]
+    let c_str = unsafe { CStr::from_ptr(bytes.as_ptr() as *const c_char) };
+    assert_eq!(c_str.to_bytes(), b"hello");
+
+    let mut buffer = *b"abc\0";
+
+    #[assure(nul_terminated(s), reason = "`buffer` ends with a nul byte")]
+    #[assure(valid_ptr(s, w), reason = "the pointer was created from a mutable reference")]
+    unsafe { clear(buffer.as_mut_ptr() as *mut c_char) };
+    assert_eq!(buffer, *b"\0bc\0");
+
+    let raw = CString::new("world").expect("no nul bytes").into_raw();
+
+    #[forward(impl pre::std::ffi::CString)]
+    #[assure(nul_terminated(ptr), reason = "`raw` was returned by `CString::into_raw`")]
+    #[assure(consumed(ptr), reason = "`raw` is not used after this call")]
+    #[assure("`ptr` was returned by `CString::into_raw`", reason = "it was")]
+    #[assure(
+        "the length of the string at `ptr` was not changed since it was returned by `CString::into_raw`",
+        reason = "the string at `raw` was not modified"
+    )]
+    let c_string = unsafe { CString::from_raw(raw) };
+    drop(c_string);
+}
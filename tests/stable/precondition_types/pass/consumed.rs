@@ -0,0 +1,44 @@
+use pre::pre;
+
+#[pre(consumed(ptr))]
+unsafe fn take(ptr: *mut i32) -> i32 {
+    *Box::from_raw(ptr)
+}
+
+#[pre]
+fn main() {
+    let ptr = Box::into_raw(Box::new(42));
+
+    #[assure(consumed(ptr), reason = "`ptr` is not used after this call")]
+    let value = unsafe { take(ptr) };
+    assert_eq!(value, 42);
+
+    let ptr = Box::into_raw(Box::new(7));
+
+    #[forward(impl pre::std::boxed::Box)]
+    #[assure(consumed(raw), reason = "`ptr` is not used after this call")]
+    #[assure("`raw` was returned by `Box::into_raw`", reason = "it was")]
+    let boxed = unsafe { Box::from_raw(ptr) };
+    assert_eq!(*boxed, 7);
+
+    let mut vec = std::mem::ManuallyDrop::new(vec![1, 2, 3]);
+    let (ptr, len, capacity) = (vec.as_mut_ptr(), vec.len(), vec.capacity());
+
+    #[forward(impl pre::std::vec::Vec)]
+    #[assure(
+        "`ptr` has been previously allocated via `String` or `Vec<T>`",
+        reason = "it was allocated by `vec!`"
+    )]
+    #[assure(
+        "`T` has the same size and alignment as what `ptr` was allocated with",
+        reason = "the element type did not change"
+    )]
+    #[assure(length <= capacity, reason = "the values are from the same `Vec`")]
+    #[assure(
+        "`capacity` is the capacity that `ptr` was allocated with",
+        reason = "the values are from the same `Vec`"
+    )]
+    #[assure(consumed(ptr), reason = "the original `Vec` is never dropped")]
+    let vec = unsafe { Vec::from_raw_parts(ptr, len, capacity) };
+    assert_eq!(vec, [1, 2, 3]);
+}
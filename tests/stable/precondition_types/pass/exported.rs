@@ -0,0 +1,44 @@
+use pre::pre;
+
+mod library {
+    use pre::pre;
+
+    #[pre(export = COND_INIT, "the library is initialized")]
+    pub fn read() -> u32 {
+        42
+    }
+
+    #[pre(use COND_INIT)]
+    #[pre(x > 0)]
+    pub fn write(x: u32) {
+        let _ = x;
+    }
+}
+
+mod conditions {
+    pub use super::library::COND_INIT;
+}
+
+#[pre(use library::COND_INIT)]
+fn read_twice() -> u32 {
+    #[assure(use library::COND_INIT, reason = "this is required by `read_twice`")]
+    let value = library::read();
+
+    value * 2
+}
+
+#[pre]
+fn main() {
+    assert_eq!(library::COND_INIT.name, "COND_INIT");
+    assert_eq!(library::COND_INIT.text, "the library is initialized");
+
+    #[assure(use conditions::COND_INIT, reason = "the library is always initialized in this test")]
+    library::read();
+
+    #[assure(use library::COND_INIT, reason = "the library is always initialized in this test")]
+    #[assure(x > 0, reason = "1 > 0")]
+    library::write(1);
+
+    #[assure(use library::COND_INIT, reason = "the library is always initialized in this test")]
+    read_twice();
+}
@@ -0,0 +1,9 @@
+pre::assert_requires!(
+    pre::core::ptr::read,
+    [
+        valid_ptr(src, r),
+        "`src` points to a properly initialized value of type `T`",
+    ]
+);
+
+fn main() {}
@@ -0,0 +1,17 @@
+#![deny(deprecated)]
+
+use pre::pre;
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "TODO")]
+    positive(1);
+
+    #[assure(x > 0, reason = "?")]
+    positive(2);
+}
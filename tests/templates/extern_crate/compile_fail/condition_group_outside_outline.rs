@@ -0,0 +1,8 @@
+use pre::pre;
+
+#[pre(when(feature = "strict"), x > 0)]
+fn foo(x: i32) -> i32 {
+    x
+}
+
+fn main() {}
@@ -1,11 +1,8 @@
 #[pre::extern_crate(std)]
 mod pre_std {
-    impl<T> ptr::NonNull<T> {
-        #[pre(!ptr.is_null())]
-        const unsafe fn new_unchecked(ptr: *mut T) -> NonNull<T>;
-    }
+    impl super::Foo {}
 
-    impl foo::bar::Baz {}
+    impl<T> vec::Vec<T>::Bar {}
 }
 
 fn main() {}
@@ -0,0 +1,42 @@
+#![deny(warnings)]
+
+macro_rules! generate {
+    () => {
+        mod generated {
+            use pre::pre;
+
+            #[pre::extern_crate(::std)]
+            pub(crate) mod pre_std {
+                mod ptr {
+                    #[pre(valid_ptr(src, r))]
+                    unsafe fn read_unaligned<T>(src: *const T) -> T;
+                }
+            }
+
+            use ::std::ptr::read_unaligned;
+
+            #[pre]
+            pub(crate) fn read(val: &u32) -> u32 {
+                #[forward(::std -> crate::generated::pre_std)]
+                #[assure(valid_ptr(src, r), reason = "`src` is a reference")]
+                let first = unsafe { ::std::ptr::read_unaligned(val) };
+
+                #[forward(crate::generated::pre_std::ptr)]
+                #[assure(valid_ptr(src, r), reason = "`src` is a reference")]
+                let second = unsafe { read_unaligned(val) };
+
+                #[forward(::std::ptr -> self::pre_std::ptr)]
+                #[assure(valid_ptr(src, r), reason = "`src` is a reference")]
+                let third = unsafe { ::std::ptr::read_unaligned(val) };
+
+                first + second + third
+            }
+        }
+    };
+}
+
+generate!();
+
+fn main() {
+    assert_eq!(generated::read(&14), 42);
+}
@@ -0,0 +1,35 @@
+use pre::pre;
+
+#[cfg(target_arch = "x86_64")]
+#[repr(align(16))]
+struct Aligned([f32; 4]);
+
+#[cfg(target_arch = "x86_64")]
+#[pre]
+fn main() {
+    use pre::core::arch::x86_64::{_mm_load_ps, _mm_store_ps};
+
+    let src = Aligned([1.0, 2.0, 3.0, 4.0]);
+    let mut dst = Aligned([0.0; 4]);
+
+    #[assure(valid_ptr(mem_addr, r, 4), reason = "`src.0` has four elements")]
+    #[assure(
+        proper_align_val(mem_addr, 16),
+        reason = "`Aligned` has an alignment of 16"
+    )]
+    let value = unsafe { _mm_load_ps(src.0.as_ptr()) };
+
+    #[assure(valid_ptr(mem_addr, w, 4), reason = "`dst.0` has four elements")]
+    #[assure(
+        proper_align_val(mem_addr, 16),
+        reason = "`Aligned` has an alignment of 16"
+    )]
+    unsafe {
+        _mm_store_ps(dst.0.as_mut_ptr(), value)
+    };
+
+    assert_eq!(dst.0, src.0);
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn main() {}
@@ -0,0 +1,105 @@
+use pre::pre;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use pre::std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+static WAKE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[pre]
+unsafe fn clone(data: *const ()) -> RawWaker {
+    #[forward(impl pre::std::task::RawWaker)]
+    #[assure(
+        "the functions in `vtable` uphold the `RawWaker` contract when called with `data`",
+        reason = "the functions of `VTABLE` ignore the data pointer"
+    )]
+    #[assure(
+        "the functions in `vtable` are safe to call with `data` from any thread",
+        reason = "the functions of `VTABLE` only use an atomic counter"
+    )]
+    let waker = RawWaker::new(data, &VTABLE);
+    waker
+}
+
+unsafe fn wake(_: *const ()) {
+    WAKE_COUNT.fetch_add(1, Ordering::SeqCst);
+}
+
+unsafe fn drop(_: *const ()) {}
+
+#[pre]
+const fn vtable() -> RawWakerVTable {
+    #[forward(impl pre::std::task::RawWakerVTable)]
+    #[assure(
+        "`clone` returns a `RawWaker` that wakes up the same task as the given one and retains all resources required for that",
+        reason = "all wakers share the same global counter and hold no resources"
+    )]
+    #[assure(
+        "`wake` and `wake_by_ref` wake up the task associated with the given data pointer",
+        reason = "the counter is the only task"
+    )]
+    #[assure(
+        "`wake` and `drop` release all resources associated with the given data pointer",
+        reason = "there are no resources associated with the data pointer"
+    )]
+    #[assure(
+        "`clone`, `wake`, `wake_by_ref` and `drop` are safe to call from any thread",
+        reason = "they only use an atomic counter"
+    )]
+    let vtable = RawWakerVTable::new(clone, wake, wake, drop);
+    vtable
+}
+
+static VTABLE: RawWakerVTable = vtable();
+
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    #[assure(
+        "the functions in `vtable` uphold the `RawWaker` contract when called with `data`",
+        reason = "the functions of `VTABLE` ignore the data pointer"
+    )]
+    #[assure(
+        "the functions in `vtable` are safe to call with `data` from any thread",
+        reason = "the functions of `VTABLE` only use an atomic counter"
+    )]
+    #[forward(impl pre::std::task::RawWaker)]
+    let raw_waker = RawWaker::new(std::ptr::null(), &VTABLE);
+
+    #[forward(impl pre::std::task::Waker)]
+    #[assure(
+        "the functions in the vtable of `waker` uphold the `RawWaker` contract when called with its data pointer",
+        reason = "`raw_waker` was created with `VTABLE`"
+    )]
+    #[assure(
+        "the functions in the vtable of `waker` are safe to call with its data pointer from any thread",
+        reason = "`raw_waker` was created with `VTABLE`"
+    )]
+    let waker = unsafe { Waker::from_raw(raw_waker) };
+    let mut cx = Context::from_waker(&waker);
+
+    let mut future = YieldOnce(false);
+    let mut future = Pin::new(&mut future);
+    while future.as_mut().poll(&mut cx).is_pending() {}
+
+    waker.clone().wake();
+    assert_eq!(WAKE_COUNT.load(Ordering::SeqCst), 2);
+}
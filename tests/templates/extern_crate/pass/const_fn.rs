@@ -0,0 +1,34 @@
+use pre::pre;
+
+#[pre::extern_crate(core)]
+mod pre_core {
+    mod mem {
+        #[pre(true)]
+        const fn forget<T>(t: T);
+    }
+}
+
+#[pre(x != 0)]
+#[pre(in_range(x, 1..10))]
+#[pre(non_null(ptr))]
+const fn checked(x: u8, ptr: *const u8) -> u8 {
+    let _ = ptr;
+    x
+}
+
+#[pre]
+const fn compute() -> u8 {
+    #[assure(true, reason = "this is always true")]
+    pre_core::mem::forget(1u8);
+
+    #[assure(x != 0, reason = "1 is not 0")]
+    #[assure(in_range(x, 1..10), reason = "1 is within 1..10")]
+    #[assure(non_null(ptr), reason = "the pointer is created from a reference")]
+    checked(1, &0)
+}
+
+const VALUE: u8 = compute();
+
+fn main() {
+    assert_eq!(VALUE, 1);
+}
@@ -0,0 +1,49 @@
+use pre::pre;
+
+mod lib {
+    pub(crate) mod a {
+        pub(crate) struct X;
+
+        impl X {
+            pub(crate) fn foo() {}
+        }
+    }
+
+    pub(crate) mod b {
+        pub(crate) struct X;
+
+        impl X {
+            pub(crate) fn foo() {}
+        }
+    }
+}
+
+#[pre::extern_crate(crate::lib)]
+mod pre_lib {
+    impl a::X {
+        #[pre("foo is called for `a::X`")]
+        fn foo();
+    }
+
+    impl b::X {
+        #[pre("foo is called for `b::X`")]
+        fn foo();
+    }
+}
+
+use pre_lib::b;
+
+#[pre]
+fn main() {
+    #[forward(impl pre_lib::a::X)]
+    #[assure("foo is called for `a::X`", reason = "it is")]
+    lib::a::X::foo();
+
+    #[forward(impl pre_lib::b::X)]
+    #[assure("foo is called for `b::X`", reason = "it is")]
+    lib::b::X::foo();
+
+    #[forward(impl b::X)]
+    #[assure("foo is called for `b::X`", reason = "it is")]
+    lib::b::X::foo();
+}
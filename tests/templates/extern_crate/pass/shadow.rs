@@ -0,0 +1,45 @@
+use pre::pre;
+
+pre::shadow! {
+    mod buffer {
+        pub(crate) struct Buffer<T>(pub(crate) [T; 4]);
+
+        #[pre(valid_ptr(ptr, r))]
+        pub(crate) unsafe fn read(ptr: *const u8) -> u8 {
+            *ptr
+        }
+
+        pub(crate) fn len() -> usize {
+            4
+        }
+
+        impl<T: Copy> Buffer<T> {
+            #[pre("`index` is less than 4")]
+            pub(crate) unsafe fn get_unchecked(&self, index: usize) -> T {
+                *self.0.get_unchecked(index)
+            }
+        }
+    }
+}
+
+#[pre]
+fn main() {
+    let value = 42;
+
+    // Call sites without `forward` work as before.
+    assert_eq!(unsafe { buffer::read(&value) }, 42);
+
+    #[forward(buffer -> pre_buffer)]
+    #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+    let read_value = unsafe { buffer::read(&value) };
+    assert_eq!(read_value, 42);
+
+    let buffer = buffer::Buffer([1, 2, 3, 4]);
+
+    #[forward(impl pre_buffer::Buffer)]
+    #[assure("`index` is less than 4", reason = "2 is less than 4")]
+    let element = unsafe { buffer.get_unchecked(2) };
+    assert_eq!(element, 3);
+
+    assert_eq!(pre_buffer::len(), 4);
+}
@@ -0,0 +1,21 @@
+use pre::pre;
+
+struct S {
+    a: i32,
+    b: i32,
+}
+
+#[pre(x > 0)]
+fn s(x: i32) -> S {
+    S { a: x, b: x }
+}
+
+fn f(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    let _ = #[assure(x > 0, reason = "1 > 0")]
+    S { a: f(2), ..s(1) };
+}
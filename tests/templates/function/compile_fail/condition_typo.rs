@@ -0,0 +1,20 @@
+use pre::pre;
+
+#[pre("`ptr` is valid")]
+#[pre("the memory at `ptr` is initialized")]
+fn foo(ptr: *const u8) {
+    let _ = ptr;
+}
+
+#[pre("is fine")]
+fn bar() {}
+
+#[pre]
+fn main() {
+    #[assure("`ptr` is vaild", reason = "it is")]
+    #[assure("the memory at `ptr` is initialised", reason = "it is")]
+    foo(std::ptr::null());
+
+    #[assure("is totally unrelated", reason = "it is")]
+    bar();
+}
@@ -0,0 +1,42 @@
+use pre::pre;
+
+#[cfg(target_arch = "x86_64")]
+#[pre(allow(documentation_only))]
+#[pre("returns the answer")]
+#[pre(x > 0)]
+#[unsafe(naked)]
+extern "C" fn answer(x: u32) -> u32 {
+    core::arch::naked_asm!("mov eax, 42", "ret")
+}
+
+#[pre(x > 0)]
+#[inline(never)]
+#[cold]
+#[track_caller]
+fn never_inlined(x: u32) -> u32 {
+    x
+}
+
+#[pre(valid_ptr(ptr, r))]
+#[unsafe(no_mangle)]
+unsafe fn __pre_test_codegen_read(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+#[pre]
+fn main() {
+    #[cfg(target_arch = "x86_64")]
+    {
+        assert_eq!(answer(0), 42);
+
+        let naked_fn: extern "C" fn(u32) -> u32 = answer;
+        assert_eq!(naked_fn(1), 42);
+    }
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let one = never_inlined(1);
+    assert_eq!(one, 1);
+
+    let val = 42;
+    assert_eq!(unsafe { __pre_test_codegen_read(&val) }, 42);
+}
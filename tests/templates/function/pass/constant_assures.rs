@@ -0,0 +1,33 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[pre(align <= 16)]
+#[pre(align.is_power_of_two())]
+fn aligned(align: usize, len: usize) -> usize {
+    (len + align - 1) / align * align
+}
+
+#[pre(count as u32 <= u8::MAX as u32 / 2)]
+fn halve(count: u8) -> u8 {
+    count / 2
+}
+
+#[pre]
+fn main() {
+    let len = 5;
+
+    #[assure(align <= 16, reason = "4 is at most 16")]
+    #[assure(align.is_power_of_two(), reason = "4 is a power of two")]
+    let rounded = aligned(4, len);
+    assert_eq!(rounded, 8);
+
+    #[assure(count as u32 <= u8::MAX as u32 / 2, reason = "100 is less than 127")]
+    assert_eq!(halve(100), 50);
+
+    let count = 127;
+
+    #[assure(count as u32 <= u8::MAX as u32 / 2, reason = "`count` is 127")]
+    let half = halve(count);
+    assert_eq!(half, 63);
+}
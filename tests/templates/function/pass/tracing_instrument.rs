@@ -0,0 +1,60 @@
+use pre::pre;
+use std::panic;
+use tracing::instrument;
+
+#[pre(x > 0)]
+#[instrument]
+fn pre_first(x: i32) -> i32 {
+    x
+}
+
+#[instrument]
+#[pre(x > 0)]
+fn instrument_first(x: i32) -> i32 {
+    x
+}
+
+#[pre(precondition_arg_first)]
+#[pre(x > 0)]
+#[instrument(level = "debug")]
+#[pre("is called from a test")]
+fn arg_first(x: i32) -> i32 {
+    x
+}
+
+#[pre(x > 0)]
+#[tracing::instrument]
+async fn asynchronous(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "1 > 0")]
+    assert_eq!(pre_first(1), 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    assert_eq!(instrument_first(1), 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    #[assure("is called from a test", reason = "this is a test")]
+    assert_eq!(arg_first(1), 1);
+
+    #[assure(x > 0, reason = "1 > 0")]
+    let _future = asynchronous(1);
+
+    // A constant argument would be rejected at compile time.
+    let zero = 0;
+
+    let result = panic::catch_unwind(|| {
+        #[assure(x > 0, reason = "this is wrong on purpose")]
+        pre_first(zero)
+    });
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+
+    let result = panic::catch_unwind(|| {
+        #[assure(x > 0, reason = "this is wrong on purpose")]
+        instrument_first(zero)
+    });
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
@@ -0,0 +1,18 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+#[pre("`ptr` points to an initialized `u32`")]
+unsafe fn read(ptr: *const u32) -> u32 {
+    *ptr
+}
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+pre::assert_requires!(read, [valid_ptr(ptr, r), "`ptr` is aligned"]);
+
+fn main() {
+    pre::assert_requires!(positive, [x > 0, x < 10]);
+}
@@ -0,0 +1,21 @@
+use pre::pre;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+};
+
+#[pre(lives_until_end_of_scope(ptr))]
+unsafe fn name_of<'a>(ptr: *const c_char) -> &'a CStr {
+    CStr::from_ptr(ptr)
+}
+
+#[pre(deny(temporary_storage))]
+fn main() {
+    #[assure(
+        lives_until_end_of_scope(ptr),
+        reason = "the `CString` lives until the end of `main`"
+    )]
+    let name = unsafe { name_of(CString::new("pre").unwrap().as_ptr()) };
+
+    assert_eq!(name.to_str(), Ok("pre"));
+}
@@ -0,0 +1,12 @@
+use pre::pre;
+
+#[pre(x > 0)]
+fn positive(x: i32) -> i32 {
+    x
+}
+
+#[pre(deny(todo_reason))]
+fn main() {
+    #[assure(x > 0, reason = "TODO")]
+    positive(1);
+}
@@ -0,0 +1,17 @@
+use pre::pre;
+
+#[pre]
+mod generated {
+    #[pre(x > 0)]
+    fn checked(x: usize) -> usize {
+        x
+    }
+
+    #[pre(deny(trusted_assure))]
+    #[pre(trusted)]
+    fn bindings() -> usize {
+        checked(1)
+    }
+}
+
+fn main() {}
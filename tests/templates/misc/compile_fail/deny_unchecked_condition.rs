@@ -0,0 +1,7 @@
+use pre::pre;
+
+#[pre(deny(unchecked_condition))]
+#[pre(core::mem::align_of::<T>() <= 8)]
+fn aligned<T: ?Sized>(_value: &T) {}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use pre::{assure, pre};
+
+#[pre("is valid")]
+fn foo() -> i32 {
+    let x = ;
+    x
+}
+
+#[assure("is valid", reason = "is valid")]
+fn bar() {}
+
+fn main() {
+    foo();
+    bar();
+}
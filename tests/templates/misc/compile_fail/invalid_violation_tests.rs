@@ -0,0 +1,24 @@
+use pre::pre;
+
+#[pre(x > 0)]
+#[pre(gen_violation_tests(case(x = 0, y = 1)))]
+fn unknown_parameter(x: i32) {}
+
+#[pre(x > 0)]
+#[pre(y > 0)]
+#[pre(gen_violation_tests(case(x = 0), case(x = 1, x = 2, y = 0)))]
+fn missing_and_duplicate(x: i32, y: i32) {}
+
+#[pre("`x` is valid")]
+#[pre(gen_violation_tests(case(x = 0)))]
+fn unchecked(x: i32) {}
+
+#[pre(x > 0)]
+#[pre(gen_violation_tests())]
+fn no_cases(x: i32) {}
+
+#[pre(x > 0)]
+#[pre(gen_violation_tests(case(x = 0)))]
+extern "C" fn c_abi(x: i32) {}
+
+fn main() {}
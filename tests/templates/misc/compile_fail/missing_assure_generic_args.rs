@@ -0,0 +1,11 @@
+use pre::pre;
+
+#[pre("an all-zero byte-pattern is a valid value of `T`")]
+unsafe fn zeroed<T>() -> T {
+    std::mem::zeroed()
+}
+
+#[pre(deny(missing_assure_summary))]
+fn main() {
+    let _: u32 = unsafe { zeroed::<u32>() };
+}
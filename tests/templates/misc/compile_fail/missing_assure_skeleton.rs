@@ -0,0 +1,12 @@
+use pre::pre;
+
+#[pre(x > 0)]
+#[pre("`x` is a valid index")]
+fn checked(x: usize) -> usize {
+    x
+}
+
+#[pre(deny(missing_assure_summary))]
+fn main() {
+    checked(1);
+}
@@ -0,0 +1,20 @@
+use pre::pre;
+
+#[pre("is valid")]
+#[pre(x > 0)]
+fn foo(x: i32) {}
+
+#[pre("is valid")]
+fn bar() {}
+
+#[pre]
+fn main() {
+    #[assure(same_as(baz), reason = "baz has the same contract")]
+    foo(1);
+
+    #[assure(same_as(foo))]
+    foo(1);
+
+    #[assure(same_as(foo), reason = "foo has the same contract")]
+    bar();
+}
@@ -0,0 +1,66 @@
+use pre::{assure_for_all_calls, pre};
+
+#[pre(adapter)]
+#[pre(x > 0)]
+#[pre("`x` is small enough not to overflow")]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pre(adapter)]
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read<T: Copy>(ptr: *const T) -> T {
+    *ptr
+}
+
+#[pre(adapter)]
+#[pre("`s` is not empty")]
+fn first(s: &str) -> &str {
+    &s[..1]
+}
+
+mod inner {
+    use pre::pre;
+
+    #[pre(adapter)]
+    #[pre("is called from a test")]
+    pub fn nothing() {}
+}
+
+#[pre]
+fn main() {
+    let doubled: Vec<_> = vec![1, 2, 3]
+        .into_iter()
+        .map(assure_for_all_calls!(
+            #[assure(x > 0, reason = "all values are positive")]
+            #[assure("`x` is small enough not to overflow", reason = "all values are small")]
+            double
+        ))
+        .collect();
+    assert_eq!(doubled, vec![2, 4, 6]);
+
+    let values = [1u8, 2, 3];
+    let read_adapter = unsafe {
+        assure_for_all_calls!(
+            #[assure(valid_ptr(ptr, r), reason = "all pointers are created from references")]
+            read::<u8>
+        )
+    };
+    let read_values: Vec<_> = values.iter().map(|v| v as *const u8).map(read_adapter).collect();
+    assert_eq!(read_values, values);
+
+    let firsts: Vec<_> = vec!["abc", "de"]
+        .into_iter()
+        .map(assure_for_all_calls!(
+            #[assure("`s` is not empty", reason = "no string is empty")]
+            first
+        ))
+        .collect();
+    assert_eq!(firsts, vec!["a", "d"]);
+
+    let nothing = assure_for_all_calls!(
+        #[assure("is called from a test", reason = "it is")]
+        inner::nothing
+    );
+    nothing();
+}
@@ -0,0 +1,30 @@
+use pre::pre;
+
+struct Wrapper(u32);
+
+#[pre(low < high)]
+#[pre(step > 0)]
+fn pattern_arguments((low, high): (u32, u32), Wrapper(step): Wrapper, mut count: usize) -> usize {
+    count += ((high - low) / step) as usize;
+    count
+}
+
+#[pre(total <= limit)]
+fn after_setup(parts: &[u32], limit: u32) -> u32 {
+    #[pre(assert_after_setup)]
+    let total: u32 = parts.iter().sum();
+
+    limit - total
+}
+
+#[pre]
+fn main() {
+    #[assure(low < high, reason = "1 < 7")]
+    #[assure(step > 0, reason = "3 > 0")]
+    let count = pattern_arguments((1, 7), Wrapper(3), 1);
+    assert_eq!(count, 3);
+
+    #[assure(total <= limit, reason = "6 <= 10")]
+    let remaining = after_setup(&[1, 2, 3], 10);
+    assert_eq!(remaining, 4);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+
+#[cfg_attr(any(target_endian = "little", target_endian = "big"), pre("foo"))]
+fn foo() {}
+
+#[cfg_attr(all(target_endian = "little", target_endian = "big"), pre("never"))]
+fn never() {}
+
+#[cfg_attr(any(target_endian = "little", target_endian = "big"), pre)]
+#[cfg_attr(any(target_endian = "little", target_endian = "big"), pre("bar"))]
+fn bar() {
+    #[cfg_attr(
+        any(target_endian = "little", target_endian = "big"),
+        assure("foo", reason = "is foo")
+    )]
+    foo();
+}
+
+#[cfg_attr(all(target_endian = "little", target_endian = "big"), pre)]
+fn baz() {
+    #[cfg_attr(
+        all(target_endian = "little", target_endian = "big"),
+        assure("never", reason = "is never compiled")
+    )]
+    never();
+}
+
+#[pre]
+fn main() {
+    #[assure("bar", reason = "is bar")]
+    bar();
+    baz();
+}
@@ -0,0 +1,32 @@
+use pre::pre;
+
+#[pre("foo")]
+#[pre("bar")]
+fn foo() {}
+
+#[pre("baz")]
+#[cfg_attr(any(target_endian = "big", target_endian = "little"), pre("qux"))]
+fn bar() {}
+
+#[cfg(any(target_endian = "big", target_endian = "little"))]
+#[pre]
+fn call_foo() {
+    #[assure("foo", reason = "is foo")]
+    #[cfg_attr(
+        any(target_endian = "big", target_endian = "little"),
+        assure("bar", reason = "is bar")
+    )]
+    foo();
+}
+
+#[pre]
+fn main() {
+    call_foo();
+
+    #[assure("baz", reason = "is baz")]
+    #[cfg_attr(
+        any(target_endian = "big", target_endian = "little"),
+        assure("qux", reason = "is qux")
+    )]
+    bar();
+}
@@ -0,0 +1,26 @@
+use pre::pre;
+
+#[pre(len > 0)]
+#[pre(valid_ptr(ptr, w))]
+#[pre(non_null(ptr))]
+#[pre(valid_ptr(ptr, r))]
+#[pre("`ptr` is not aliased")]
+unsafe fn increment(ptr: *mut u32, len: usize) {
+    *ptr += len as u32;
+}
+
+#[pre]
+fn main() {
+    let mut val = 41;
+
+    #[assure("`ptr` is not aliased", reason = "`val` is not borrowed")]
+    #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+    #[assure(non_null(ptr), reason = "`ptr` is created from a reference")]
+    #[assure(len > 0, reason = "1 > 0")]
+    #[assure(valid_ptr(ptr, w), reason = "`ptr` is created from a mutable reference")]
+    unsafe {
+        increment(&mut val, 1)
+    };
+
+    assert_eq!(val, 42);
+}
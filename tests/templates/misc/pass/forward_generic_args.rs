@@ -0,0 +1,69 @@
+use pre::pre;
+
+mod plain {
+    pub(crate) fn size_of_val<T: Default>(x: T) -> usize {
+        let _ = T::default();
+        let _ = x;
+        0
+    }
+
+    pub(crate) fn identity<T>(x: T) -> T {
+        x
+    }
+}
+
+mod checked {
+    use pre::pre;
+
+    #[pre("`x` is fine")]
+    pub(crate) fn size_of_val<T: Default>(x: T) -> usize {
+        let _ = x;
+        ::core::mem::size_of::<T>()
+    }
+
+    #[pre("`x` is fine")]
+    pub(crate) fn identity<T>(x: T) -> T {
+        x
+    }
+}
+
+#[pre]
+fn main() {
+    #[forward(plain -> checked)]
+    #[assure("`x` is fine", reason = "it is")]
+    let a = plain::size_of_val::<u64>(Default::default());
+    assert_eq!(a, 8);
+
+    #[forward(plain::identity -> checked::identity)]
+    #[assure("`x` is fine", reason = "it is")]
+    let b = plain::identity::<u16>(1u8.into());
+    assert_eq!(b, 1);
+
+    use plain::identity;
+    #[forward(checked)]
+    #[assure("`x` is fine", reason = "it is")]
+    let c = identity::<u8>(2.into());
+    assert_eq!(c, 2);
+
+    #[forward(identity -> checked::identity)]
+    #[assure("`x` is fine", reason = "it is")]
+    let d = identity::<u32>(3u8.into());
+    assert_eq!(d, 3);
+
+    let val = 42u32;
+    let e = unsafe {
+        #[forward(std -> pre::std)]
+        #[assure(valid_ptr(src, r), reason = "`src` is created from a reference")]
+        #[assure(proper_align(src), reason = "`src` is created from a reference")]
+        #[assure(
+            "`src` points to a properly initialized value of type `T`",
+            reason = "`src` is created from a reference"
+        )]
+        #[assure(
+            "`T` is `Copy` or the value at `*src` isn't used after this call",
+            reason = "`u32` is `Copy`"
+        )]
+        std::ptr::read::<u32>(&val)
+    };
+    assert_eq!(e, 42);
+}
@@ -0,0 +1,33 @@
+use pre::pre;
+
+#[pre(doc)]
+#[pre(debug_assert)]
+#[pre(x > 0)]
+fn foo(x: i32) -> i32 {
+    x
+}
+
+#[pre(no_debug_assert)]
+#[pre(debug_assert)]
+#[pre(x > 0)]
+fn bar(x: i32) -> i32 {
+    x
+}
+
+#[pre]
+fn main() {
+    #[assure(x > 0, reason = "1 > 0")]
+    foo(1);
+
+    // A constant argument would be rejected at compile time.
+    let zero = 0;
+
+    let result = std::panic::catch_unwind(|| {
+        #[assure(x > 0, reason = "this is wrong on purpose")]
+        bar(zero)
+    });
+
+    if cfg!(debug_assertions) {
+        assert!(result.is_err());
+    }
+}
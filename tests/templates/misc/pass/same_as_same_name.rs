@@ -0,0 +1,37 @@
+use pre::pre;
+
+#[pre]
+mod old_api {
+    #[pre(valid_ptr(ptr, r))]
+    #[pre(len > 0)]
+    pub unsafe fn read(ptr: *const u8, len: usize) -> u8 {
+        let _ = len;
+        *ptr
+    }
+}
+
+#[pre]
+mod new_api {
+    #[pre(valid_ptr(ptr, r))]
+    #[pre(len > 0)]
+    pub unsafe fn read(ptr: *const u8, len: usize) -> u8 {
+        #[assure(
+            same_as(super::old_api::read),
+            reason = "`new_api::read` has the same contract as `old_api::read`"
+        )]
+        unsafe { super::old_api::read(ptr, len) }
+    }
+}
+
+#[pre]
+fn main() {
+    let value = 42u8;
+
+    #[assure(same_as(new_api::read), reason = "`ptr` comes from a reference and `len` is one")]
+    let first = unsafe { new_api::read(&value, 1) };
+
+    #[assure(same_as(old_api::read), reason = "`ptr` comes from a reference and `len` is one")]
+    let second = unsafe { old_api::read(&value, 1) };
+
+    assert_eq!(first, second);
+}
@@ -0,0 +1,44 @@
+use pre::pre;
+
+#[pre(valid_ptr(ptr, r))]
+unsafe fn read(ptr: *const i32) -> i32 {
+    *ptr
+}
+
+#[pre(strict_unsafe)]
+fn main() {
+    let val = 42;
+    let ptr = &val as *const i32;
+
+    let a = unsafe {
+        #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+        read(ptr)
+    };
+
+    #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+    let b = unsafe { read(ptr) };
+
+    unsafe {
+        #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+        read(ptr);
+    }
+
+    assert_eq!(a, b);
+}
+
+#[pre(strict_unsafe)]
+mod inner {
+    use pre::pre;
+
+    #[pre(valid_ptr(ptr, r))]
+    unsafe fn read(ptr: *const i32) -> i32 {
+        *ptr
+    }
+
+    fn _use_read(val: &i32) -> i32 {
+        unsafe {
+            #[assure(valid_ptr(ptr, r), reason = "`ptr` is created from a reference")]
+            read(val as *const i32)
+        }
+    }
+}
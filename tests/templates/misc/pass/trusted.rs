@@ -0,0 +1,46 @@
+use pre::pre;
+
+#[pre]
+mod generated {
+    #[pre(x > 0)]
+    #[pre("`x` is a valid index")]
+    pub(super) fn checked(x: usize) -> usize {
+        x
+    }
+
+    #[pre(valid_ptr(ptr, r))]
+    pub(super) unsafe fn read(ptr: *const i32) -> i32 {
+        *ptr
+    }
+
+    #[pre(trusted)]
+    pub(super) fn bindings() -> usize {
+        let val = 42;
+
+        checked(1) + unsafe { read(&val) } as usize
+    }
+
+    pub(super) fn partially_trusted() -> usize {
+        #[assure(x > 0, reason = "2 > 0")]
+        #[assure("`x` is a valid index", reason = "the index is in bounds")]
+        let a = checked(2);
+
+        #[pre(trusted)]
+        let b = checked(3);
+
+        a + b
+    }
+
+    #[pre(trusted)]
+    pub(super) mod derived {
+        pub(crate) fn call() -> usize {
+            super::checked(4)
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(generated::bindings(), 43);
+    assert_eq!(generated::partially_trusted(), 5);
+    assert_eq!(generated::derived::call(), 4);
+}
@@ -0,0 +1,81 @@
+use pre::pre;
+use std::mem::MaybeUninit;
+
+mod library {
+    pub struct Counter(pub u32);
+
+    impl Counter {
+        pub fn decrement(&mut self) {
+            self.0 -= 1;
+        }
+
+        pub fn get<T: From<u32>>(&self) -> T {
+            T::from(self.0)
+        }
+
+        pub fn decrement_if(&mut self, condition: impl FnOnce(u32) -> bool) {
+            if condition(self.0) {
+                self.0 -= 1;
+            }
+        }
+    }
+}
+
+#[pre::extern_crate(crate::library)]
+mod pre_library {
+    impl Counter {
+        #[pre(self.0 > 0)]
+        fn decrement(&mut self);
+
+        #[pre("the counter was decremented")]
+        fn get<T: From<u32>>(&self) -> T;
+
+        #[pre(self.0 > 0)]
+        fn decrement_if(&mut self, condition: impl FnOnce(u32) -> bool);
+    }
+}
+
+use pre::std::pre_ext::*;
+use pre_library::pre_ext::*;
+
+#[pre]
+fn main() {
+    let value = MaybeUninit::new(42);
+
+    #[assure(
+        valid_value(self, T),
+        reason = "it was created using `MaybeUninit::new`"
+    )]
+    let value = unsafe { value.assume_init_pre() };
+    assert_eq!(value, 42);
+
+    let mut counter = library::Counter(2);
+
+    #[assure(self.0 > 0, reason = "the counter starts at 2")]
+    counter.decrement_if_pre(|count| count == 2);
+
+    #[assure(self.0 > 0, reason = "the counter was decremented once")]
+    counter.decrement_pre();
+
+    #[assure("the counter was decremented", reason = "it was decremented above")]
+    let count: u64 = counter.get_pre();
+    assert_eq!(count, 0);
+
+    let ptr = &value as *const i32;
+
+    #[assure(proper_align(self), reason = "the pointer was created from a reference")]
+    #[assure(
+        "`self` is null or `self` is a valid for reads",
+        reason = "the pointer was created from a reference"
+    )]
+    #[assure(
+        "`self` is null or `self` points to an initialized value of type `T`",
+        reason = "the pointer was created from a reference"
+    )]
+    #[assure(
+        "the memory referenced by the returned reference is not mutated by any pointer for the duration of `'a`, except inside a contained `UnsafeCell`",
+        reason = "`value` is not mutable"
+    )]
+    let reference = unsafe { ptr.as_ref_pre() };
+    assert_eq!(reference, Some(&42));
+}
@@ -0,0 +1,18 @@
+#![feature(try_blocks)]
+
+use pre::pre;
+
+#[pre(x > 0)]
+fn checked(x: i32) -> Option<i32> {
+    Some(x)
+}
+
+#[pre]
+fn main() {
+    let val: Option<i32> = #[assure(x > 0, reason = "1 > 0")]
+    try {
+        checked(1)?
+    };
+
+    assert_eq!(val, Some(1));
+}
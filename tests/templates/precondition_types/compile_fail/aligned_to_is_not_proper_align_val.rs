@@ -0,0 +1,15 @@
+use pre::pre;
+
+#[repr(align(32))]
+struct Aligned([u8; 64]);
+
+#[pre(aligned_to(ptr, 32))]
+fn foo(ptr: *const u8) {}
+
+#[pre]
+fn main() {
+    let data = Aligned([0; 64]);
+
+    #[assure(proper_align_val(ptr, 32), reason = "`Aligned` has an alignment of 32")]
+    foo(data.0.as_ptr());
+}
@@ -0,0 +1,14 @@
+use pre::pre;
+
+struct InitToken;
+
+impl pre::Token for InitToken {}
+
+#[pre(token(InitToken))]
+fn foo() {}
+
+#[pre]
+fn main() {
+    #[assure(token(InitToken))]
+    foo()
+}
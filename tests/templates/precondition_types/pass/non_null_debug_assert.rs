@@ -0,0 +1,18 @@
+use pre::pre;
+use std::{panic, ptr};
+
+#[pre(non_null(ptr))]
+fn foo(ptr: *const i32) {}
+
+#[pre]
+fn main() {
+    #[assure(non_null(ptr), reason = "it is from a reference")]
+    foo(&42);
+
+    let result = panic::catch_unwind(|| {
+        #[assure(non_null(ptr), reason = "this is wrong on purpose")]
+        foo(ptr::null());
+    });
+
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
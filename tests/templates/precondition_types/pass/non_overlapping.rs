@@ -0,0 +1,46 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[pre(valid_ptr(src, r, count))]
+#[pre(valid_ptr(dst, w, count))]
+#[pre(non_overlapping(src, dst, count))]
+unsafe fn copy(src: *const u32, dst: *mut u32, count: usize) {
+    for i in 0..count {
+        *dst.add(i) = *src.add(i);
+    }
+}
+
+#[pre]
+fn main() {
+    let src = [1, 2, 3, 4];
+    let mut dst = [0; 4];
+
+    #[assure(valid_ptr(src, r, count), reason = "`src` has four elements")]
+    #[assure(valid_ptr(dst, w, count), reason = "`dst` has four elements")]
+    #[assure(
+        non_overlapping(src, dst, count),
+        reason = "`src` and `dst` are different local variables"
+    )]
+    unsafe {
+        copy(src.as_ptr(), dst.as_mut_ptr(), 2)
+    };
+    assert_eq!(dst, [1, 2, 0, 0]);
+
+    #[assure(valid_ptr(src, r, count), reason = "`src` has four elements")]
+    #[assure(valid_ptr(dst, w, count), reason = "`dst` has four elements")]
+    #[assure(proper_align(src), reason = "`src` is a local variable")]
+    #[assure(proper_align(dst), reason = "`dst` is a local variable")]
+    #[assure(
+        non_overlapping(src, dst, count),
+        reason = "`src` and `dst` are different local variables"
+    )]
+    #[assure(
+        "`T` is `Copy` or only the values in one of the regions are used after this call",
+        reason = "`u32` is `Copy`"
+    )]
+    unsafe {
+        pre::core::ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr(), 4)
+    };
+    assert_eq!(dst, src);
+}
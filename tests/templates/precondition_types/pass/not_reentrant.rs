@@ -0,0 +1,32 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[pre(not_reentrant())]
+fn for_each_entry(entries: &[u32], callback: &mut dyn FnMut(u32)) {
+    for &entry in entries {
+        callback(entry);
+    }
+}
+
+#[pre(not_reentrant())]
+#[pre(factor > 0)]
+fn notify(entries: &[u32], factor: u32, callback: &mut dyn FnMut(u32)) {
+    for &entry in entries {
+        callback(entry * factor);
+    }
+}
+
+#[pre]
+fn main() {
+    let mut sum = 0;
+
+    #[assure(not_reentrant(), reason = "the callback does not call `for_each_entry`")]
+    for_each_entry(&[1, 2, 3], &mut |entry| sum += entry);
+    assert_eq!(sum, 6);
+
+    #[assure(not_reentrant(), reason = "the callback does not call `notify`")]
+    #[assure(factor > 0, reason = "2 > 0")]
+    notify(&[1, 2], 2, &mut |entry| sum += entry);
+    assert_eq!(sum, 12);
+}
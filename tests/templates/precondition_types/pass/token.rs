@@ -0,0 +1,40 @@
+use pre::pre;
+
+struct InitToken(());
+
+impl pre::Token for InitToken {}
+
+struct Guard;
+
+impl pre::Token for Guard {}
+
+fn init() -> InitToken {
+    InitToken(())
+}
+
+#[pre(token(&InitToken))]
+fn borrows_token() {}
+
+#[pre(token(InitToken))]
+#[pre(token(&mut Guard))]
+#[pre("some other precondition")]
+fn consumes_token(a: i32) -> i32 {
+    a
+}
+
+#[pre]
+fn main() {
+    let init_token = init();
+    let mut guard = Guard;
+
+    #[assure(token(&InitToken = &init_token))]
+    borrows_token();
+
+    #[assure(token(&InitToken = &init_token))]
+    borrows_token();
+
+    #[assure(token(&mut Guard = &mut guard))]
+    #[assure(token(InitToken = init_token), reason = "a reason is allowed, but not required")]
+    #[assure("some other precondition", reason = "this is just a test")]
+    consumes_token(42);
+}
@@ -0,0 +1,50 @@
+#![deny(warnings)]
+
+use pre::pre;
+
+#[pre(valid_slice(data, len, r))]
+unsafe fn sum(data: *const u32, len: usize) -> u32 {
+    std::slice::from_raw_parts(data, len).iter().sum()
+}
+
+#[pre(valid_slice(data, len, r+w))]
+unsafe fn double(data: *mut u32, len: usize) {
+    for value in std::slice::from_raw_parts_mut(data, len) {
+        *value *= 2;
+    }
+}
+
+#[pre]
+fn main() {
+    let mut values = [1, 2, 3, 4];
+
+    #[assure(
+        valid_slice(data, len, r),
+        reason = "`values` is an initialized array of four elements"
+    )]
+    let result = unsafe { sum(values.as_ptr(), 2) };
+    assert_eq!(result, 3);
+
+    #[assure(valid_ptr(data, r+w, len), reason = "`values` has four elements")]
+    #[assure(proper_align(data), reason = "`values` is a local variable")]
+    #[assure(initialized(data, len), reason = "`values` is initialized")]
+    #[assure(
+        ::pre::slice_len_fits(data, len),
+        reason = "`values` is a local variable"
+    )]
+    unsafe {
+        double(values.as_mut_ptr(), 4)
+    };
+    assert_eq!(values, [2, 4, 6, 8]);
+
+    #[assure(
+        valid_slice(data, len, r),
+        reason = "`values` is an initialized array of four elements"
+    )]
+    #[assure(
+        "the memory referenced by the returned slice is not mutated by any pointer for the duration of `'a`, except inside a contained `UnsafeCell`",
+        reason = "`values` is not mutated while the slice is used"
+    )]
+    let slice = unsafe { pre::core::slice::from_raw_parts(values.as_ptr(), values.len()) };
+    assert_eq!(slice, [2, 4, 6, 8]);
+}
@@ -0,0 +1,18 @@
+pre::assert_requires!(
+    pre::core::ptr::read,
+    [
+        valid_ptr(src, r),
+        proper_align(src),
+        "`src` points to a properly initialized value of type `T`",
+        "`T` is `Copy` or the value at `*src` isn't used after this call",
+    ]
+);
+
+fn main() {
+    pre::assert_requires!(pre::core::ptr::read::<u8>, [
+        "`T` is `Copy` or the value at `*src` isn't used after this call",
+        "`src` points to a properly initialized value of type `T`",
+        proper_align(src),
+        valid_ptr(src, r),
+    ]);
+}